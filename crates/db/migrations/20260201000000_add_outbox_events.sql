@@ -0,0 +1,15 @@
+-- Transactional outbox: side-effect intents written alongside the state change
+-- that caused them, then delivered at-least-once by a background worker
+CREATE TABLE outbox_events (
+    id            BLOB PRIMARY KEY,
+    kind          TEXT NOT NULL,
+    payload       TEXT NOT NULL,
+    attempts      INTEGER NOT NULL DEFAULT 0,
+    last_error    TEXT,
+    available_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    processed_at  TEXT,
+    created_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE INDEX idx_outbox_events_pending ON outbox_events(available_at)
+WHERE processed_at IS NULL;
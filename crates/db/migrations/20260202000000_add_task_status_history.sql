@@ -0,0 +1,35 @@
+-- Record every task status change so past board states can be reconstructed
+CREATE TABLE task_status_history (
+    id          BLOB PRIMARY KEY,
+    task_id     BLOB NOT NULL,
+    status      TEXT NOT NULL
+                   CHECK (status IN ('todo','inprogress','done','cancelled','inreview')),
+    changed_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_task_status_history_task_changed
+ON task_status_history (task_id, changed_at);
+
+CREATE TRIGGER trg_task_status_history_insert
+AFTER INSERT ON tasks
+BEGIN
+    INSERT INTO task_status_history (id, task_id, status)
+    VALUES (randomblob(16), NEW.id, NEW.status);
+END;
+
+CREATE TRIGGER trg_task_status_history_update
+AFTER UPDATE OF status ON tasks
+WHEN OLD.status IS NOT NEW.status
+BEGIN
+    INSERT INTO task_status_history (id, task_id, status)
+    VALUES (randomblob(16), NEW.id, NEW.status);
+END;
+
+-- Backfill: existing tasks are assumed to start in todo and reach their
+-- current status at their last update
+INSERT INTO task_status_history (id, task_id, status, changed_at)
+SELECT randomblob(16), id, 'todo', created_at FROM tasks;
+
+INSERT INTO task_status_history (id, task_id, status, changed_at)
+SELECT randomblob(16), id, status, updated_at FROM tasks WHERE status != 'todo';
@@ -0,0 +1,9 @@
+-- Map bot messages back to the task they were about, so replies can be routed
+CREATE TABLE telegram_message_tasks (
+    chat_id     INTEGER NOT NULL,
+    message_id  INTEGER NOT NULL,
+    task_id     BLOB NOT NULL,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (chat_id, message_id),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
@@ -0,0 +1,12 @@
+-- Per-project limits on how long a task may stay in a status
+CREATE TABLE sla_policies (
+    id          BLOB PRIMARY KEY,
+    project_id  BLOB NOT NULL,
+    status      TEXT NOT NULL
+                   CHECK (status IN ('todo','inprogress','inreview')),
+    max_hours   INTEGER NOT NULL CHECK (max_hours > 0),
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+    UNIQUE (project_id, status)
+);
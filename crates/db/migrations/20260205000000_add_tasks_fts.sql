@@ -0,0 +1,30 @@
+-- Full-text index over task titles and descriptions, kept in sync by triggers
+CREATE VIRTUAL TABLE tasks_fts USING fts5(
+    task_id UNINDEXED,
+    title,
+    description
+);
+
+INSERT INTO tasks_fts (task_id, title, description)
+SELECT id, title, COALESCE(description, '') FROM tasks;
+
+CREATE TRIGGER trg_tasks_fts_insert
+AFTER INSERT ON tasks
+BEGIN
+    INSERT INTO tasks_fts (task_id, title, description)
+    VALUES (NEW.id, NEW.title, COALESCE(NEW.description, ''));
+END;
+
+CREATE TRIGGER trg_tasks_fts_update
+AFTER UPDATE OF title, description ON tasks
+BEGIN
+    DELETE FROM tasks_fts WHERE task_id = OLD.id;
+    INSERT INTO tasks_fts (task_id, title, description)
+    VALUES (NEW.id, NEW.title, COALESCE(NEW.description, ''));
+END;
+
+CREATE TRIGGER trg_tasks_fts_delete
+AFTER DELETE ON tasks
+BEGIN
+    DELETE FROM tasks_fts WHERE task_id = OLD.id;
+END;
@@ -0,0 +1,14 @@
+-- Output files registered by attempts (screenshots, coverage reports, binaries)
+CREATE TABLE workspace_artifacts (
+    id             BLOB PRIMARY KEY,
+    workspace_id   BLOB NOT NULL,
+    file_path      TEXT NOT NULL,  -- relative path within cache/artifacts/
+    original_name  TEXT NOT NULL,
+    mime_type      TEXT,
+    size_bytes     INTEGER NOT NULL,
+    hash           TEXT NOT NULL,  -- SHA256 of the file contents
+    created_at     TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_workspace_artifacts_workspace_id ON workspace_artifacts(workspace_id);
@@ -0,0 +1,11 @@
+-- Projects with a row here are web apps: after a successful attempt the dev
+-- server is booted and `preview_url` is screenshotted
+CREATE TABLE project_preview_settings (
+    project_id            BLOB PRIMARY KEY,
+    preview_url           TEXT NOT NULL,
+    startup_timeout_secs  INTEGER NOT NULL DEFAULT 60
+                             CHECK (startup_timeout_secs > 0),
+    created_at            TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at            TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
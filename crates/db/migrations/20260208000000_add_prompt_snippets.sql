@@ -0,0 +1,16 @@
+-- Reusable prompt snippets with {{variable}} placeholders. Rows without a
+-- project_id are global; a project snippet shadows a global one of the same name
+CREATE TABLE prompt_snippets (
+    id           BLOB PRIMARY KEY,
+    project_id   BLOB,
+    name         TEXT NOT NULL,
+    description  TEXT,
+    content      TEXT NOT NULL,
+    created_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+-- NULL project_ids never collide in a plain UNIQUE constraint
+CREATE UNIQUE INDEX idx_prompt_snippets_scope_name
+    ON prompt_snippets (IFNULL(project_id, x''), name);
@@ -0,0 +1,32 @@
+-- A/B evaluations: each benchmark task of a run gets one attempt per profile
+CREATE TABLE eval_runs (
+    id            BLOB PRIMARY KEY,
+    project_id    BLOB NOT NULL,
+    name          TEXT NOT NULL,
+    profile_a     TEXT NOT NULL,
+    profile_b     TEXT NOT NULL,
+    test_command  TEXT,
+    created_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE TABLE eval_attempts (
+    id               BLOB PRIMARY KEY,
+    eval_run_id      BLOB NOT NULL,
+    benchmark_label  TEXT NOT NULL,
+    variant          TEXT NOT NULL CHECK (variant IN ('a', 'b')),
+    task_id          BLOB NOT NULL,
+    workspace_id     BLOB,
+    outcome          TEXT NOT NULL DEFAULT 'pending'
+                       CHECK (outcome IN ('pending', 'passed', 'failed')),
+    duration_secs    INTEGER,
+    total_tokens     INTEGER,
+    created_at       TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    completed_at     TEXT,
+    FOREIGN KEY (eval_run_id) REFERENCES eval_runs(id) ON DELETE CASCADE,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_eval_attempts_run ON eval_attempts (eval_run_id);
+CREATE INDEX idx_eval_attempts_pending ON eval_attempts (outcome) WHERE outcome = 'pending';
@@ -0,0 +1,25 @@
+-- Projects with a row here import `- [ ] item` lines from a markdown file in
+-- one of their repos as tasks
+CREATE TABLE project_todo_sync_settings (
+    project_id      BLOB PRIMARY KEY,
+    repo_id         BLOB NOT NULL,
+    file_path       TEXT NOT NULL DEFAULT 'TODO.md',
+    -- Tick the checkbox in the file when the imported task is done
+    write_back      INTEGER NOT NULL DEFAULT 0,
+    last_synced_at  TEXT,
+    created_at      TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at      TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
+
+-- Which file item each imported task came from, keyed by normalized title
+CREATE TABLE todo_sync_items (
+    project_id  BLOB NOT NULL,
+    item_key    TEXT NOT NULL,
+    task_id     BLOB NOT NULL,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (project_id, item_key),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
@@ -0,0 +1,8 @@
+-- Per-chat bot state that should survive restarts
+CREATE TABLE telegram_chat_settings (
+    chat_id            INTEGER PRIMARY KEY,
+    -- Project used by /tasks, /newtask etc. when no project id is given
+    active_project_id  BLOB,
+    updated_at         TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (active_project_id) REFERENCES projects(id) ON DELETE SET NULL
+);
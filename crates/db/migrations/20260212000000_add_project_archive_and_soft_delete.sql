@@ -0,0 +1,4 @@
+-- Archived projects stay listed but are marked as such; soft-deleted
+-- projects are hidden everywhere and keep their tasks and history
+ALTER TABLE projects ADD COLUMN archived_at TEXT;
+ALTER TABLE projects ADD COLUMN deleted_at TEXT;
@@ -0,0 +1,24 @@
+-- Every Telegram account that linked itself to the bot with /start <token>.
+-- The first one stays the primary account in the config file.
+CREATE TABLE telegram_accounts (
+    user_id     INTEGER PRIMARY KEY,
+    chat_id     INTEGER NOT NULL,
+    username    TEXT,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+-- Linked accounts that are assigned to or watching a task; notifications for
+-- the task only go to these accounts
+CREATE TABLE task_assignees (
+    task_id           BLOB NOT NULL,
+    telegram_user_id  INTEGER NOT NULL,
+    role              TEXT NOT NULL DEFAULT 'assignee'
+                         CHECK (role IN ('assignee','watcher')),
+    created_at        TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (task_id, telegram_user_id),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (telegram_user_id) REFERENCES telegram_accounts(user_id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_task_assignees_telegram_user_id ON task_assignees(telegram_user_id);
@@ -0,0 +1,14 @@
+-- Replicates a project's tasks with the same project on another vibe-kanban
+-- instance. Both instances keep a row pointing at each other, with the same
+-- shared token.
+CREATE TABLE project_peer_sync_settings (
+    project_id         BLOB PRIMARY KEY,
+    -- Base URL of the other instance, e.g. http://homeserver:3000
+    peer_url           TEXT NOT NULL,
+    remote_project_id  BLOB NOT NULL,
+    token              TEXT NOT NULL,
+    last_synced_at     TEXT,
+    created_at         TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at         TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
@@ -0,0 +1,11 @@
+-- The bot message that is edited on each status change of a task, per chat,
+-- when notifications are set to edit in place
+CREATE TABLE telegram_progress_messages (
+    task_id     BLOB NOT NULL,
+    chat_id     INTEGER NOT NULL,
+    message_id  INTEGER NOT NULL,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (task_id, chat_id),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
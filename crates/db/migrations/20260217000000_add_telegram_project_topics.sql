@@ -0,0 +1,14 @@
+-- Forum topics of Telegram groups, each dedicated to one project. The
+-- project's notifications and the replies to commands sent in the topic
+-- land in it.
+CREATE TABLE telegram_project_topics (
+    chat_id            INTEGER NOT NULL,
+    message_thread_id  INTEGER NOT NULL,
+    project_id         BLOB NOT NULL,
+    created_at         TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (chat_id, message_thread_id),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_telegram_project_topics_project
+ON telegram_project_topics (chat_id, project_id);
@@ -0,0 +1,10 @@
+-- Per-chat project shortcuts: sending /<name> in the chat switches to the
+-- project, like /project <id>.
+CREATE TABLE telegram_project_shortcuts (
+    chat_id     INTEGER NOT NULL,
+    name        TEXT NOT NULL,
+    project_id  BLOB NOT NULL,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (chat_id, name),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
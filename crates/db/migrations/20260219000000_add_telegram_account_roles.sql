@@ -0,0 +1,4 @@
+-- Read-only accounts can look at projects and tasks but not change them.
+-- Existing accounts keep full access.
+ALTER TABLE telegram_accounts
+ADD COLUMN role TEXT NOT NULL DEFAULT 'admin' CHECK (role IN ('admin', 'read_only'));
@@ -0,0 +1,12 @@
+-- Undo/redo journal of task edits, kept per client session
+CREATE TABLE task_mutations (
+    id              BLOB PRIMARY KEY,
+    client_session  TEXT NOT NULL,
+    task_id         BLOB NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    before_state    TEXT NOT NULL,
+    after_state     TEXT NOT NULL,
+    undone_at       TEXT,
+    created_at      TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE INDEX idx_task_mutations_session ON task_mutations(client_session, created_at);
@@ -0,0 +1,10 @@
+-- Webhook updates already handled, per bot, so updates Telegram redelivers
+-- after a slow response are not processed twice
+CREATE TABLE telegram_seen_updates (
+    bot_id     TEXT NOT NULL,
+    update_id  INTEGER NOT NULL,
+    seen_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (bot_id, update_id)
+);
+
+CREATE INDEX idx_telegram_seen_updates_seen_at ON telegram_seen_updates(seen_at);
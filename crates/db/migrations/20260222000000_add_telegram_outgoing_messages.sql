@@ -0,0 +1,23 @@
+-- Telegram notifications waiting to be sent, so they survive restarts and
+-- Telegram outages. Each chat gets its messages in the order they were queued.
+CREATE TABLE telegram_outgoing_messages (
+    id                 BLOB PRIMARY KEY,
+    -- Project whose own bot sends the message, NULL for the default bot
+    bot_project_id     BLOB,
+    chat_id            INTEGER NOT NULL,
+    message_thread_id  INTEGER,
+    task_id            BLOB,
+    text               TEXT NOT NULL,
+    reply_markup       TEXT,
+    silent             INTEGER NOT NULL DEFAULT 0,
+    attempts           INTEGER NOT NULL DEFAULT 0,
+    last_error         TEXT,
+    available_at       TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    processed_at       TEXT,
+    created_at         TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_telegram_outgoing_messages_pending
+ON telegram_outgoing_messages(chat_id, created_at)
+WHERE processed_at IS NULL;
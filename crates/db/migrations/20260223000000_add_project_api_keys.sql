@@ -0,0 +1,17 @@
+-- Keys that give automation (e.g. a repo's CI) access to a single project.
+-- Only a hash of each key is stored; the key itself is shown once, when it is
+-- created or rotated.
+CREATE TABLE project_api_keys (
+    id            BLOB PRIMARY KEY,
+    project_id    BLOB NOT NULL,
+    name          TEXT NOT NULL,
+    -- Start of the key, so keys can be told apart in the UI
+    key_prefix    TEXT NOT NULL,
+    key_hash      TEXT NOT NULL UNIQUE,
+    last_used_at  TEXT,
+    rotated_at    TEXT,
+    created_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_project_api_keys_project_id ON project_api_keys(project_id);
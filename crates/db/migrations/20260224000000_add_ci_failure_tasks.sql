@@ -0,0 +1,16 @@
+-- The task tracking failures of one CI workflow on one branch, so repeated
+-- failures update that task instead of filing a new one each run.
+CREATE TABLE ci_failure_tasks (
+    project_id     BLOB NOT NULL,
+    workflow       TEXT NOT NULL,
+    branch         TEXT NOT NULL,
+    task_id        BLOB NOT NULL,
+    -- Failures reported since the task was filed
+    failure_count  INTEGER NOT NULL DEFAULT 1,
+    last_run_url   TEXT,
+    created_at     TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at     TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (project_id, workflow, branch),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
@@ -0,0 +1,18 @@
+-- Tests reported failing by a project's CI. Repeat failures of a test are
+-- counted on its row and grouped under one task.
+CREATE TABLE ci_test_failures (
+    project_id     BLOB NOT NULL,
+    test_name      TEXT NOT NULL,
+    occurrences    INTEGER NOT NULL DEFAULT 1,
+    -- Task filed once the test failed repeatedly
+    task_id        BLOB,
+    last_run_url   TEXT,
+    first_seen_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    last_seen_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (project_id, test_name),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_ci_test_failures_occurrences
+    ON ci_test_failures(project_id, occurrences DESC, last_seen_at DESC);
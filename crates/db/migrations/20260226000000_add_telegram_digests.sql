@@ -0,0 +1,17 @@
+-- Accounts can have their notifications collected into an hourly or daily
+-- digest instead of getting each one as it happens.
+ALTER TABLE telegram_accounts
+ADD COLUMN digest_mode TEXT NOT NULL DEFAULT 'immediate' CHECK (digest_mode IN ('immediate', 'hourly', 'daily'));
+
+-- Notifications held back for a chat's next digest
+CREATE TABLE telegram_digest_items (
+    id          BLOB PRIMARY KEY,
+    chat_id     INTEGER NOT NULL,
+    task_id     BLOB,
+    text        TEXT NOT NULL,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_telegram_digest_items_chat_id
+ON telegram_digest_items(chat_id, created_at);
@@ -0,0 +1,14 @@
+-- Explicit position of a task on its project's board, lowest first. Existing
+-- tasks keep the newest-first order they were listed in.
+ALTER TABLE tasks ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+
+UPDATE tasks
+SET position = (
+    SELECT COUNT(*)
+    FROM tasks t
+    WHERE t.project_id = tasks.project_id
+      AND (t.created_at > tasks.created_at
+           OR (t.created_at = tasks.created_at AND t.id < tasks.id))
+);
+
+CREATE INDEX idx_tasks_project_id_position ON tasks(project_id, position);
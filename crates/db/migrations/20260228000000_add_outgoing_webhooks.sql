@@ -0,0 +1,84 @@
+-- User-configured endpoints that receive task and attempt events
+CREATE TABLE webhook_endpoints (
+    id          BLOB PRIMARY KEY,
+    url         TEXT NOT NULL,
+    -- Key the request signature is computed with; shown once, on creation
+    secret      TEXT NOT NULL,
+    -- JSON array of event types to deliver; empty for all of them
+    events      TEXT NOT NULL DEFAULT '[]',
+    format      TEXT NOT NULL DEFAULT 'native'
+                   CHECK (format IN ('native','slack','discord','cloud_events')),
+    -- JSON array of dotted paths of the event data to include; empty for all
+    fields      TEXT NOT NULL DEFAULT '[]',
+    enabled     INTEGER NOT NULL DEFAULT 1,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+-- One row per event and endpoint. Rows are queued by the triggers below, in
+-- the same transaction as the change, and double as the delivery log.
+CREATE TABLE webhook_deliveries (
+    id                    BLOB PRIMARY KEY,
+    endpoint_id           BLOB NOT NULL,
+    event_type            TEXT NOT NULL
+                             CHECK (event_type IN ('task.created','task.status_changed','attempt.finished')),
+    -- Not a foreign key, so the log outlives deleted tasks
+    task_id               BLOB NOT NULL,
+    execution_process_id  BLOB,
+    from_status           TEXT,
+    to_status             TEXT,
+    -- Body as first sent, so retries post the same payload
+    payload               TEXT,
+    status                TEXT NOT NULL DEFAULT 'pending'
+                             CHECK (status IN ('pending','delivered','failed')),
+    attempts              INTEGER NOT NULL DEFAULT 0,
+    response_status       INTEGER,
+    last_error            TEXT,
+    next_attempt_at       TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    delivered_at          TEXT,
+    created_at            TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (endpoint_id) REFERENCES webhook_endpoints(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_webhook_deliveries_due ON webhook_deliveries (status, next_attempt_at);
+CREATE INDEX idx_webhook_deliveries_endpoint ON webhook_deliveries (endpoint_id, created_at);
+
+CREATE TRIGGER trg_webhook_task_created
+AFTER INSERT ON tasks
+BEGIN
+    INSERT INTO webhook_deliveries (id, endpoint_id, event_type, task_id, to_status)
+    SELECT randomblob(16), e.id, 'task.created', NEW.id, NEW.status
+    FROM webhook_endpoints e
+    WHERE e.enabled = 1
+      AND (json_array_length(e.events) = 0
+           OR EXISTS (SELECT 1 FROM json_each(e.events) WHERE value = 'task.created'));
+END;
+
+CREATE TRIGGER trg_webhook_task_status_changed
+AFTER UPDATE OF status ON tasks
+WHEN OLD.status IS NOT NEW.status
+BEGIN
+    INSERT INTO webhook_deliveries (id, endpoint_id, event_type, task_id, from_status, to_status)
+    SELECT randomblob(16), e.id, 'task.status_changed', NEW.id, OLD.status, NEW.status
+    FROM webhook_endpoints e
+    WHERE e.enabled = 1
+      AND (json_array_length(e.events) = 0
+           OR EXISTS (SELECT 1 FROM json_each(e.events) WHERE value = 'task.status_changed'));
+END;
+
+-- Coding agent runs that leave the running (or stalled) state
+CREATE TRIGGER trg_webhook_attempt_finished
+AFTER UPDATE OF status ON execution_processes
+WHEN NEW.run_reason = 'codingagent'
+ AND OLD.status IN ('running','stalled')
+ AND NEW.status IN ('completed','failed','killed')
+BEGIN
+    INSERT INTO webhook_deliveries (id, endpoint_id, event_type, task_id, execution_process_id, from_status, to_status)
+    SELECT randomblob(16), e.id, 'attempt.finished', w.task_id, NEW.id, OLD.status, NEW.status
+    FROM webhook_endpoints e
+    JOIN sessions s ON s.id = NEW.session_id
+    JOIN workspaces w ON w.id = s.workspace_id
+    WHERE e.enabled = 1
+      AND (json_array_length(e.events) = 0
+           OR EXISTS (SELECT 1 FROM json_each(e.events) WHERE value = 'attempt.finished'));
+END;
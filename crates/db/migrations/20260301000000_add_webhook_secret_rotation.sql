@@ -0,0 +1,4 @@
+-- Secret replaced by the last rotation. Requests are signed with it as well
+-- until it expires, so receivers can switch over without rejecting any.
+ALTER TABLE webhook_endpoints ADD COLUMN previous_secret TEXT;
+ALTER TABLE webhook_endpoints ADD COLUMN previous_secret_expires_at TEXT;
@@ -0,0 +1,12 @@
+-- Browser push subscriptions the web UI registered for task notifications
+CREATE TABLE web_push_subscriptions (
+    id          BLOB PRIMARY KEY,
+    -- Push service URL the encrypted messages are posted to
+    endpoint    TEXT NOT NULL UNIQUE,
+    -- Browser's P-256 public key and auth secret, base64url, for encryption
+    p256dh      TEXT NOT NULL,
+    auth        TEXT NOT NULL,
+    user_agent  TEXT,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
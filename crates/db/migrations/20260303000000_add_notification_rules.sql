@@ -0,0 +1,17 @@
+-- Rules mapping task events to the channels they are delivered through. While
+-- any rule is enabled, an event only goes to the channels of the rules it
+-- matches.
+CREATE TABLE notification_rules (
+    id          BLOB PRIMARY KEY,
+    name        TEXT NOT NULL,
+    -- Only events of this project match; NULL for any project
+    project_id  BLOB,
+    -- JSON array of task notification kinds that match; empty for all of them
+    events      TEXT NOT NULL DEFAULT '[]',
+    -- JSON array of channels matching events are delivered through
+    channels    TEXT NOT NULL DEFAULT '[]',
+    enabled     INTEGER NOT NULL DEFAULT 1,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
@@ -0,0 +1,21 @@
+-- Log of task notifications sent through each channel, so what went out can
+-- be audited and failed deliveries sent again
+CREATE TABLE notifications (
+    id          BLOB PRIMARY KEY,
+    -- Not foreign keys, so the log outlives deleted tasks
+    task_id     BLOB NOT NULL,
+    project_id  BLOB NOT NULL,
+    channel     TEXT NOT NULL,
+    event_type  TEXT NOT NULL,
+    -- JSON of what the notification said about the task
+    payload     TEXT NOT NULL,
+    status      TEXT NOT NULL
+                   CHECK (status IN ('sent','failed')),
+    -- Why the last delivery failed
+    error       TEXT,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE INDEX idx_notifications_created ON notifications (created_at);
+CREATE INDEX idx_notifications_task ON notifications (task_id, created_at);
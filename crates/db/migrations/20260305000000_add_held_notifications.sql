@@ -0,0 +1,14 @@
+-- Task notifications held back by quiet hours. Once a channel's quiet hours
+-- end, its held notifications are sent as one catch-up message.
+CREATE TABLE held_notifications (
+    id          BLOB PRIMARY KEY,
+    task_id     BLOB NOT NULL,
+    project_id  BLOB NOT NULL,
+    channel     TEXT NOT NULL,
+    event_type  TEXT NOT NULL,
+    -- JSON of what the notification would have said about the task
+    payload     TEXT NOT NULL,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE INDEX idx_held_notifications_channel ON held_notifications (channel, created_at);
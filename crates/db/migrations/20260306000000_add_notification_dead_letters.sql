@@ -0,0 +1,14 @@
+-- Notifications whose delivery failed even after retrying, kept until they
+-- are delivered or discarded so they can be inspected and sent again
+CREATE TABLE notification_dead_letters (
+    id               BLOB PRIMARY KEY,
+    notification_id  BLOB NOT NULL UNIQUE
+                        REFERENCES notifications(id) ON DELETE CASCADE,
+    -- Deliveries tried, redeliveries included
+    attempts         INTEGER NOT NULL DEFAULT 0,
+    last_error       TEXT NOT NULL,
+    created_at       TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at       TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE INDEX idx_notification_dead_letters_created ON notification_dead_letters (created_at);
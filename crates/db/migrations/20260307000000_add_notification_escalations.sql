@@ -0,0 +1,17 @@
+-- When someone saw to a notification; acknowledging stops its escalation
+ALTER TABLE notifications ADD COLUMN acknowledged_at TEXT;
+
+-- Task failures being sent through more channels until one of their
+-- notifications is acknowledged. One per task; a failure while the task's
+-- escalation is under way joins it.
+CREATE TABLE notification_escalations (
+    id           BLOB PRIMARY KEY,
+    task_id      BLOB NOT NULL UNIQUE,
+    project_id   BLOB NOT NULL,
+    event_type   TEXT NOT NULL,
+    -- JSON of what the notification said about the task
+    payload      TEXT NOT NULL,
+    -- Steps of the escalation policy taken so far
+    steps_taken  INTEGER NOT NULL DEFAULT 0,
+    created_at   TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
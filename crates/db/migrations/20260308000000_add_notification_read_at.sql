@@ -0,0 +1,3 @@
+-- When the notification was first seen in the web UI. Acknowledging it
+-- also marks it read.
+ALTER TABLE notifications ADD COLUMN read_at TEXT;
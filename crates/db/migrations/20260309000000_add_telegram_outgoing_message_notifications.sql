@@ -0,0 +1,10 @@
+-- History entries of the notifications a queued Telegram message delivers,
+-- so a message given up on marks them failed and puts them in the dead
+-- letters. A combined batch message delivers several.
+CREATE TABLE telegram_outgoing_message_notifications (
+    message_id       BLOB NOT NULL
+                        REFERENCES telegram_outgoing_messages(id) ON DELETE CASCADE,
+    notification_id  BLOB NOT NULL
+                        REFERENCES notifications(id) ON DELETE CASCADE,
+    PRIMARY KEY (message_id, notification_id)
+);
@@ -0,0 +1,146 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Links a CI workflow failing on a branch to the task filed for it
+#[derive(Debug, Clone, FromRow)]
+pub struct CiFailureTask {
+    pub project_id: Uuid,
+    pub workflow: String,
+    pub branch: String,
+    pub task_id: Uuid,
+    /// Failures reported since the task was filed
+    pub failure_count: i64,
+    pub last_run_url: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl CiFailureTask {
+    pub async fn find(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        workflow: &str,
+        branch: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            CiFailureTask,
+            r#"SELECT project_id as "project_id!: Uuid", workflow, branch, task_id as "task_id!: Uuid", failure_count, last_run_url, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM ci_failure_tasks
+               WHERE project_id = $1 AND workflow = $2 AND branch = $3"#,
+            project_id,
+            workflow,
+            branch
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Record a failure against `task_id`. The failure count restarts when the
+    /// workflow moves on to a new task.
+    pub async fn record(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        workflow: &str,
+        branch: &str,
+        task_id: Uuid,
+        run_url: Option<&str>,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            CiFailureTask,
+            r#"INSERT INTO ci_failure_tasks (project_id, workflow, branch, task_id, last_run_url)
+               VALUES ($1, $2, $3, $4, $5)
+               ON CONFLICT (project_id, workflow, branch) DO UPDATE
+               SET failure_count = CASE WHEN task_id = excluded.task_id THEN failure_count + 1 ELSE 1 END,
+                   task_id = excluded.task_id,
+                   last_run_url = excluded.last_run_url,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING project_id as "project_id!: Uuid", workflow, branch, task_id as "task_id!: Uuid", failure_count, last_run_url, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            project_id,
+            workflow,
+            branch,
+            task_id,
+            run_url
+        )
+        .fetch_one(pool)
+        .await
+    }
+}
+
+/// A test the project's CI reported failing, with how often it did
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct CiTestFailure {
+    pub project_id: Uuid,
+    pub test_name: String,
+    pub occurrences: i64,
+    /// Task filed once the test failed repeatedly
+    pub task_id: Option<Uuid>,
+    pub last_run_url: Option<String>,
+    pub first_seen_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+}
+
+impl CiTestFailure {
+    /// Count another failure of `test_name`
+    pub async fn record(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        test_name: &str,
+        run_url: Option<&str>,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            CiTestFailure,
+            r#"INSERT INTO ci_test_failures (project_id, test_name, last_run_url)
+               VALUES ($1, $2, $3)
+               ON CONFLICT (project_id, test_name) DO UPDATE
+               SET occurrences = occurrences + 1,
+                   last_run_url = excluded.last_run_url,
+                   last_seen_at = datetime('now', 'subsec')
+               RETURNING project_id as "project_id!: Uuid", test_name, occurrences, task_id as "task_id: Uuid", last_run_url, first_seen_at as "first_seen_at!: DateTime<Utc>", last_seen_at as "last_seen_at!: DateTime<Utc>""#,
+            project_id,
+            test_name,
+            run_url
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn set_task(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        test_name: &str,
+        task_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE ci_test_failures SET task_id = $3 WHERE project_id = $1 AND test_name = $2",
+            project_id,
+            test_name,
+            task_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// The project's most often failing tests, most recent first among equals
+    pub async fn find_flakiest(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            CiTestFailure,
+            r#"SELECT project_id as "project_id!: Uuid", test_name, occurrences, task_id as "task_id: Uuid", last_run_url, first_seen_at as "first_seen_at!: DateTime<Utc>", last_seen_at as "last_seen_at!: DateTime<Utc>"
+               FROM ci_test_failures
+               WHERE project_id = $1
+               ORDER BY occurrences DESC, last_seen_at DESC
+               LIMIT $2"#,
+            project_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -81,6 +81,35 @@ impl CodingAgentTurn {
         .await
     }
 
+    /// Find all coding agent turns across a task's attempts, oldest first
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            CodingAgentTurn,
+            r#"SELECT
+                cat.id as "id!: Uuid",
+                cat.execution_process_id as "execution_process_id!: Uuid",
+                cat.agent_session_id,
+                cat.agent_message_id,
+                cat.prompt,
+                cat.summary,
+                cat.seen as "seen!: bool",
+                cat.created_at as "created_at!: DateTime<Utc>",
+                cat.updated_at as "updated_at!: DateTime<Utc>"
+               FROM coding_agent_turns cat
+               JOIN execution_processes ep ON cat.execution_process_id = ep.id
+               JOIN sessions s ON ep.session_id = s.id
+               JOIN workspaces w ON s.workspace_id = w.id
+               WHERE w.task_id = $1
+               ORDER BY cat.created_at ASC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     pub async fn find_by_agent_session_id(
         pool: &SqlitePool,
         agent_session_id: &str,
@@ -0,0 +1,199 @@
+use chrono::{DateTime, Utc};
+use executors::profile::ExecutorProfileId;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A labeled set of benchmark tasks run once against each of two executor
+/// profiles
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct EvalRun {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub name: String,
+    #[ts(type = "ExecutorProfileId")]
+    pub profile_a: sqlx::types::Json<ExecutorProfileId>,
+    #[ts(type = "ExecutorProfileId")]
+    pub profile_b: sqlx::types::Json<ExecutorProfileId>,
+    /// Run in the attempt's worktree once the agent finishes; exit code 0
+    /// counts as a pass. Without one, a clean agent exit is a pass.
+    pub test_command: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "eval_variant", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum EvalVariant {
+    A,
+    B,
+}
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "eval_outcome", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum EvalOutcome {
+    Pending,
+    Passed,
+    Failed,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct EvalAttempt {
+    pub id: Uuid,
+    pub eval_run_id: Uuid,
+    pub benchmark_label: String,
+    pub variant: EvalVariant,
+    pub task_id: Uuid,
+    pub workspace_id: Option<Uuid>,
+    pub outcome: EvalOutcome,
+    /// Wall-clock time of the coding agent run
+    pub duration_secs: Option<i64>,
+    /// Peak context usage reported by the agent, used as a cost proxy
+    pub total_tokens: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateEvalRun {
+    pub project_id: Uuid,
+    pub name: String,
+    pub profile_a: ExecutorProfileId,
+    pub profile_b: ExecutorProfileId,
+    pub test_command: Option<String>,
+}
+
+impl EvalRun {
+    pub async fn find_by_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            EvalRun,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", name, profile_a as "profile_a!: sqlx::types::Json<ExecutorProfileId>", profile_b as "profile_b!: sqlx::types::Json<ExecutorProfileId>", test_command, created_at as "created_at!: DateTime<Utc>"
+               FROM eval_runs
+               WHERE project_id = $1
+               ORDER BY created_at DESC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            EvalRun,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", name, profile_a as "profile_a!: sqlx::types::Json<ExecutorProfileId>", profile_b as "profile_b!: sqlx::types::Json<ExecutorProfileId>", test_command, created_at as "created_at!: DateTime<Utc>"
+               FROM eval_runs
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn create(pool: &SqlitePool, data: &CreateEvalRun) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let profile_a = sqlx::types::Json(&data.profile_a);
+        let profile_b = sqlx::types::Json(&data.profile_b);
+        sqlx::query_as!(
+            EvalRun,
+            r#"INSERT INTO eval_runs (id, project_id, name, profile_a, profile_b, test_command)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", name, profile_a as "profile_a!: sqlx::types::Json<ExecutorProfileId>", profile_b as "profile_b!: sqlx::types::Json<ExecutorProfileId>", test_command, created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            data.project_id,
+            data.name,
+            profile_a,
+            profile_b,
+            data.test_command
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub fn profile(&self, variant: EvalVariant) -> &ExecutorProfileId {
+        match variant {
+            EvalVariant::A => &self.profile_a,
+            EvalVariant::B => &self.profile_b,
+        }
+    }
+}
+
+impl EvalAttempt {
+    pub async fn find_by_run(
+        pool: &SqlitePool,
+        eval_run_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            EvalAttempt,
+            r#"SELECT id as "id!: Uuid", eval_run_id as "eval_run_id!: Uuid", benchmark_label, variant as "variant!: EvalVariant", task_id as "task_id!: Uuid", workspace_id as "workspace_id: Uuid", outcome as "outcome!: EvalOutcome", duration_secs, total_tokens, created_at as "created_at!: DateTime<Utc>", completed_at as "completed_at: DateTime<Utc>"
+               FROM eval_attempts
+               WHERE eval_run_id = $1
+               ORDER BY created_at ASC, variant ASC"#,
+            eval_run_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_pending(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            EvalAttempt,
+            r#"SELECT id as "id!: Uuid", eval_run_id as "eval_run_id!: Uuid", benchmark_label, variant as "variant!: EvalVariant", task_id as "task_id!: Uuid", workspace_id as "workspace_id: Uuid", outcome as "outcome!: EvalOutcome", duration_secs, total_tokens, created_at as "created_at!: DateTime<Utc>", completed_at as "completed_at: DateTime<Utc>"
+               FROM eval_attempts
+               WHERE outcome = 'pending'
+               ORDER BY created_at ASC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        eval_run_id: Uuid,
+        benchmark_label: &str,
+        variant: EvalVariant,
+        task_id: Uuid,
+        workspace_id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            EvalAttempt,
+            r#"INSERT INTO eval_attempts (id, eval_run_id, benchmark_label, variant, task_id, workspace_id)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               RETURNING id as "id!: Uuid", eval_run_id as "eval_run_id!: Uuid", benchmark_label, variant as "variant!: EvalVariant", task_id as "task_id!: Uuid", workspace_id as "workspace_id: Uuid", outcome as "outcome!: EvalOutcome", duration_secs, total_tokens, created_at as "created_at!: DateTime<Utc>", completed_at as "completed_at: DateTime<Utc>""#,
+            id,
+            eval_run_id,
+            benchmark_label,
+            variant,
+            task_id,
+            workspace_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn complete(
+        pool: &SqlitePool,
+        id: Uuid,
+        outcome: EvalOutcome,
+        duration_secs: Option<i64>,
+        total_tokens: Option<i64>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE eval_attempts
+               SET outcome = $2, duration_secs = $3, total_tokens = $4, completed_at = datetime('now', 'subsec')
+               WHERE id = $1"#,
+            id,
+            outcome,
+            duration_secs,
+            total_tokens
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
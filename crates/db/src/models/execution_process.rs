@@ -47,6 +47,8 @@ pub enum ExecutionProcessStatus {
     Completed,
     Failed,
     Killed,
+    /// Was running, but its runner stopped sending heartbeats
+    Stalled,
 }
 
 #[derive(Debug, Clone, Type, Serialize, Deserialize, PartialEq, TS)]
@@ -113,6 +115,16 @@ pub struct LatestProcessInfo {
     pub completed_at: Option<DateTime<Utc>>,
 }
 
+/// A coding agent run that failed, with its task
+#[derive(Debug, Clone, FromRow)]
+pub struct FailedAgentRun {
+    pub execution_process_id: Uuid,
+    pub project_id: Uuid,
+    pub task_id: Uuid,
+    pub task_title: String,
+    pub completed_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum ExecutorActionField {
@@ -132,6 +144,29 @@ pub struct MissingBeforeContext {
 }
 
 impl ExecutionProcess {
+    /// Coding agent runs that failed at or after `since`, most recent first
+    pub async fn find_failed_agent_runs_since(
+        pool: &SqlitePool,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<FailedAgentRun>, sqlx::Error> {
+        sqlx::query_as!(
+            FailedAgentRun,
+            r#"SELECT ep.id as "execution_process_id!: Uuid", t.project_id as "project_id!: Uuid", t.id as "task_id!: Uuid", t.title as "task_title!", ep.completed_at as "completed_at!: DateTime<Utc>"
+               FROM execution_processes ep
+               JOIN sessions s ON s.id = ep.session_id
+               JOIN workspaces w ON w.id = s.workspace_id
+               JOIN tasks t ON t.id = w.task_id
+               WHERE ep.run_reason = 'codingagent'
+                 AND ep.status = 'failed'
+                 AND ep.completed_at IS NOT NULL
+                 AND julianday(ep.completed_at) >= julianday($1)
+               ORDER BY ep.completed_at DESC"#,
+            since
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     /// Find execution process by ID
     pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
@@ -282,6 +317,17 @@ impl ExecutionProcess {
         .await
     }
 
+    /// Count coding agent attempts that are currently running
+    pub async fn count_running_coding_agents(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!: i64"
+               FROM execution_processes
+               WHERE status = 'running' AND run_reason = 'codingagent'"#
+        )
+        .fetch_one(pool)
+        .await
+    }
+
     /// Find running dev servers for a specific project
     pub async fn find_running_dev_servers_by_project(
         pool: &SqlitePool,
@@ -499,6 +545,67 @@ impl ExecutionProcess {
         Ok(())
     }
 
+    /// Record that the runner of a running process is still alive
+    pub async fn record_heartbeat(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+        sqlx::query!(
+            r#"UPDATE execution_processes
+               SET last_heartbeat_at = $1
+               WHERE id = $2 AND status = 'running'"#,
+            now,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Running processes without a heartbeat since `cutoff`. Processes that never
+    /// sent one are judged by their start time.
+    pub async fn find_stalled(
+        pool: &SqlitePool,
+        cutoff: DateTime<Utc>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcess,
+            r#"SELECT
+                    ep.id as "id!: Uuid",
+                    ep.session_id as "session_id!: Uuid",
+                    ep.run_reason as "run_reason!: ExecutionProcessRunReason",
+                    ep.executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>",
+                    ep.status as "status!: ExecutionProcessStatus",
+                    ep.exit_code,
+                    ep.dropped as "dropped!: bool",
+                    ep.started_at as "started_at!: DateTime<Utc>",
+                    ep.completed_at as "completed_at?: DateTime<Utc>",
+                    ep.created_at as "created_at!: DateTime<Utc>",
+                    ep.updated_at as "updated_at!: DateTime<Utc>"
+               FROM execution_processes ep
+               WHERE ep.status = 'running'
+                 AND datetime(COALESCE(ep.last_heartbeat_at, ep.started_at)) < datetime($1)
+               ORDER BY ep.created_at ASC"#,
+            cutoff
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Mark a process as stalled unless it finished in the meantime. Returns
+    /// whether the status changed.
+    pub async fn mark_stalled(pool: &SqlitePool, id: Uuid) -> Result<bool, sqlx::Error> {
+        let now = Utc::now();
+        let result = sqlx::query!(
+            r#"UPDATE execution_processes
+               SET status = 'stalled', completed_at = $1
+               WHERE id = $2 AND status = 'running'"#,
+            now,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
     pub fn executor_action(&self) -> Result<&ExecutorAction, anyhow::Error> {
         match &self.executor_action.0 {
             ExecutorActionField::ExecutorAction(action) => Ok(action),
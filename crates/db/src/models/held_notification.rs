@@ -0,0 +1,83 @@
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqlitePool, types::Json};
+use uuid::Uuid;
+
+use super::{notification::NotificationPayload, notification_rule::NotificationChannel};
+
+/// A task notification waiting for its channel's quiet hours to end
+#[derive(Debug, Clone, FromRow)]
+pub struct HeldNotification {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub project_id: Uuid,
+    pub channel: NotificationChannel,
+    pub event_type: String,
+    pub payload: Json<NotificationPayload>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateHeldNotification {
+    pub task_id: Uuid,
+    pub project_id: Uuid,
+    pub channel: NotificationChannel,
+    pub event_type: String,
+    pub payload: NotificationPayload,
+}
+
+impl HeldNotification {
+    pub async fn create(
+        pool: &SqlitePool,
+        data: &CreateHeldNotification,
+    ) -> Result<Uuid, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let payload = Json(&data.payload);
+        sqlx::query!(
+            r#"INSERT INTO held_notifications (id, task_id, project_id, channel, event_type, payload)
+               VALUES ($1, $2, $3, $4, $5, $6)"#,
+            id,
+            data.task_id,
+            data.project_id,
+            data.channel,
+            data.event_type,
+            payload
+        )
+        .execute(pool)
+        .await?;
+        Ok(id)
+    }
+
+    /// Channels with notifications waiting
+    pub async fn find_channels(pool: &SqlitePool) -> Result<Vec<NotificationChannel>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT DISTINCT channel as "channel!: NotificationChannel"
+               FROM held_notifications"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Notifications waiting for `channel`, oldest first
+    pub async fn find_by_channel(
+        pool: &SqlitePool,
+        channel: NotificationChannel,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            HeldNotification,
+            r#"SELECT id as "id!: Uuid", task_id as "task_id!: Uuid", project_id as "project_id!: Uuid", channel as "channel!: NotificationChannel", event_type, payload as "payload!: Json<NotificationPayload>", created_at as "created_at!: DateTime<Utc>"
+               FROM held_notifications
+               WHERE channel = $1
+               ORDER BY created_at ASC, rowid ASC"#,
+            channel
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!("DELETE FROM held_notifications WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
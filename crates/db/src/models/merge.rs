@@ -60,6 +60,17 @@ pub enum MergeType {
     Pr,
 }
 
+/// An open PR and the task it was opened for
+#[derive(Debug, Clone, FromRow)]
+pub struct OpenPr {
+    pub project_id: Uuid,
+    pub task_id: Uuid,
+    pub task_title: String,
+    pub pr_number: i64,
+    pub pr_url: String,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(FromRow)]
 struct MergeRow {
     id: Uuid,
@@ -196,6 +207,21 @@ impl Merge {
         Ok(rows.into_iter().map(Into::into).collect())
     }
 
+    /// Open PRs with the task and project they belong to
+    pub async fn find_open_prs_with_task(pool: &SqlitePool) -> Result<Vec<OpenPr>, sqlx::Error> {
+        sqlx::query_as!(
+            OpenPr,
+            r#"SELECT t.project_id as "project_id!: Uuid", t.id as "task_id!: Uuid", t.title as "task_title!", m.pr_number as "pr_number!: i64", m.pr_url as "pr_url!", m.created_at as "created_at!: DateTime<Utc>"
+               FROM merges m
+               JOIN workspaces w ON w.id = m.workspace_id
+               JOIN tasks t ON t.id = w.task_id
+               WHERE m.merge_type = 'pr' AND m.pr_status = 'open'
+               ORDER BY m.created_at ASC"#,
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     /// Update PR status for a workspace
     pub async fn update_status(
         pool: &SqlitePool,
@@ -1,15 +1,41 @@
+pub mod ci_failure;
 pub mod coding_agent_turn;
+pub mod eval_run;
 pub mod execution_process;
 pub mod execution_process_logs;
 pub mod execution_process_repo_state;
+pub mod held_notification;
 pub mod image;
 pub mod merge;
+pub mod notification;
+pub mod notification_dead_letter;
+pub mod notification_escalation;
+pub mod notification_rule;
+pub mod outbox_event;
+pub mod peer_sync;
 pub mod project;
+pub mod project_api_key;
+pub mod project_preview;
 pub mod project_repo;
+pub mod prompt_snippet;
 pub mod repo;
 pub mod scratch;
 pub mod session;
+pub mod sla_policy;
 pub mod tag;
 pub mod task;
+pub mod task_assignee;
+pub mod task_mutation;
+pub mod task_status_history;
+pub mod telegram_account;
+pub mod telegram_chat;
+pub mod telegram_digest_item;
+pub mod telegram_message;
+pub mod telegram_outgoing_message;
+pub mod todo_sync;
+pub mod web_push_subscription;
+pub mod webhook_delivery;
+pub mod webhook_endpoint;
 pub mod workspace;
+pub mod workspace_artifact;
 pub mod workspace_repo;
@@ -0,0 +1,260 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::notification_rule::NotificationChannel;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Type, Serialize, Deserialize, TS)]
+#[sqlx(type_name = "notification_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationStatus {
+    Sent,
+    /// The channel could not deliver it, even after retrying
+    Failed,
+}
+
+/// What a notification said about its task
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct NotificationPayload {
+    pub task_title: String,
+    /// The agent's summary of its work
+    pub summary: Option<String>,
+}
+
+/// A task notification sent, or attempted, through one channel
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct Notification {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub project_id: Uuid,
+    pub channel: NotificationChannel,
+    /// Row of the notification preferences the event belonged to
+    #[ts(type = "NotificationEventType")]
+    pub event_type: String,
+    #[ts(type = "NotificationPayload")]
+    pub payload: Json<NotificationPayload>,
+    pub status: NotificationStatus,
+    /// Why the last delivery failed
+    pub error: Option<String>,
+    /// When it was first seen
+    pub read_at: Option<DateTime<Utc>>,
+    /// When someone saw to it; set, it stops the failure's escalation
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateNotification {
+    pub task_id: Uuid,
+    pub project_id: Uuid,
+    pub channel: NotificationChannel,
+    pub event_type: String,
+    pub payload: NotificationPayload,
+    /// `None` when the notification was delivered
+    pub error: Option<String>,
+}
+
+/// Which notifications to list; unset fields match any value
+#[derive(Debug, Clone, Default, Deserialize, TS)]
+pub struct NotificationFilter {
+    pub project_id: Option<Uuid>,
+    pub task_id: Option<Uuid>,
+    pub channel: Option<NotificationChannel>,
+    #[ts(type = "NotificationEventType | null")]
+    pub event_type: Option<String>,
+    pub status: Option<NotificationStatus>,
+    pub read: Option<bool>,
+    pub acknowledged: Option<bool>,
+}
+
+impl Notification {
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Notification,
+            r#"SELECT id as "id!: Uuid", task_id as "task_id!: Uuid", project_id as "project_id!: Uuid", channel as "channel!: NotificationChannel", event_type, payload as "payload!: Json<NotificationPayload>", status as "status!: NotificationStatus", error, read_at as "read_at: DateTime<Utc>", acknowledged_at as "acknowledged_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM notifications
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// The most recent notifications matching `filter`, newest first
+    pub async fn find_filtered(
+        pool: &SqlitePool,
+        filter: &NotificationFilter,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Notification,
+            r#"SELECT id as "id!: Uuid", task_id as "task_id!: Uuid", project_id as "project_id!: Uuid", channel as "channel!: NotificationChannel", event_type, payload as "payload!: Json<NotificationPayload>", status as "status!: NotificationStatus", error, read_at as "read_at: DateTime<Utc>", acknowledged_at as "acknowledged_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM notifications
+               WHERE ($1 IS NULL OR project_id = $1)
+                 AND ($2 IS NULL OR task_id = $2)
+                 AND ($3 IS NULL OR channel = $3)
+                 AND ($4 IS NULL OR event_type = $4)
+                 AND ($5 IS NULL OR status = $5)
+                 AND ($6 IS NULL OR (read_at IS NOT NULL) = $6)
+                 AND ($7 IS NULL OR (acknowledged_at IS NOT NULL) = $7)
+               ORDER BY created_at DESC, rowid DESC
+               LIMIT $8"#,
+            filter.project_id,
+            filter.task_id,
+            filter.channel,
+            filter.event_type,
+            filter.status,
+            filter.read,
+            filter.acknowledged,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn create(pool: &SqlitePool, data: &CreateNotification) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let payload = Json(&data.payload);
+        let status = match data.error {
+            Some(_) => NotificationStatus::Failed,
+            None => NotificationStatus::Sent,
+        };
+        sqlx::query_as!(
+            Notification,
+            r#"INSERT INTO notifications (id, task_id, project_id, channel, event_type, payload, status, error)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+               RETURNING id as "id!: Uuid", task_id as "task_id!: Uuid", project_id as "project_id!: Uuid", channel as "channel!: NotificationChannel", event_type, payload as "payload!: Json<NotificationPayload>", status as "status!: NotificationStatus", error, read_at as "read_at: DateTime<Utc>", acknowledged_at as "acknowledged_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            data.task_id,
+            data.project_id,
+            data.channel,
+            data.event_type,
+            payload,
+            status,
+            data.error
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Record the outcome of sending the notification again; `error` is
+    /// `None` when it was delivered
+    pub async fn record_resend(
+        pool: &SqlitePool,
+        id: Uuid,
+        error: Option<&str>,
+    ) -> Result<Self, sqlx::Error> {
+        let status = match error {
+            Some(_) => NotificationStatus::Failed,
+            None => NotificationStatus::Sent,
+        };
+        sqlx::query_as!(
+            Notification,
+            r#"UPDATE notifications
+               SET status = $2, error = $3, updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid", task_id as "task_id!: Uuid", project_id as "project_id!: Uuid", channel as "channel!: NotificationChannel", event_type, payload as "payload!: Json<NotificationPayload>", status as "status!: NotificationStatus", error, read_at as "read_at: DateTime<Utc>", acknowledged_at as "acknowledged_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            status,
+            error
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Mark a notification the channel accepted as failed after all, e.g. a
+    /// queued Telegram message that could not be sent
+    pub async fn mark_failed(pool: &SqlitePool, id: Uuid, error: &str) -> Result<(), sqlx::Error> {
+        let status = NotificationStatus::Failed;
+        sqlx::query!(
+            r#"UPDATE notifications
+               SET status = $2, error = $3, updated_at = datetime('now', 'subsec')
+               WHERE id = $1"#,
+            id,
+            status,
+            error
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Mark the notification as seen. Marking it again keeps the first time.
+    pub async fn mark_read(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Notification,
+            r#"UPDATE notifications
+               SET read_at = COALESCE(read_at, datetime('now', 'subsec'))
+               WHERE id = $1
+               RETURNING id as "id!: Uuid", task_id as "task_id!: Uuid", project_id as "project_id!: Uuid", channel as "channel!: NotificationChannel", event_type, payload as "payload!: Json<NotificationPayload>", status as "status!: NotificationStatus", error, read_at as "read_at: DateTime<Utc>", acknowledged_at as "acknowledged_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Mark the notification as seen to, and as read. Acknowledging it again
+    /// keeps the first time.
+    pub async fn acknowledge(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Notification,
+            r#"UPDATE notifications
+               SET acknowledged_at = COALESCE(acknowledged_at, datetime('now', 'subsec')),
+                   read_at = COALESCE(read_at, datetime('now', 'subsec'))
+               WHERE id = $1
+               RETURNING id as "id!: Uuid", task_id as "task_id!: Uuid", project_id as "project_id!: Uuid", channel as "channel!: NotificationChannel", event_type, payload as "payload!: Json<NotificationPayload>", status as "status!: NotificationStatus", error, read_at as "read_at: DateTime<Utc>", acknowledged_at as "acknowledged_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Acknowledge every notification of `event_type` about the task that
+    /// was not yet, e.g. from a button on one of them. Returns how many were.
+    pub async fn acknowledge_for_task(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        event_type: &str,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE notifications
+               SET acknowledged_at = datetime('now', 'subsec'),
+                   read_at = COALESCE(read_at, datetime('now', 'subsec'))
+               WHERE task_id = $1
+                 AND event_type = $2
+                 AND acknowledged_at IS NULL"#,
+            task_id,
+            event_type
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Whether any notification of `event_type` about the task sent since
+    /// `since` has been acknowledged
+    pub async fn is_acknowledged_since(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        event_type: &str,
+        since: DateTime<Utc>,
+    ) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                   SELECT 1 FROM notifications
+                   WHERE task_id = $1
+                     AND event_type = $2
+                     AND julianday(created_at) >= julianday($3)
+                     AND acknowledged_at IS NOT NULL
+               ) as "acknowledged!: bool""#,
+            task_id,
+            event_type,
+            since
+        )
+        .fetch_one(pool)
+        .await
+    }
+}
@@ -0,0 +1,132 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::{notification::NotificationPayload, notification_rule::NotificationChannel};
+
+/// A notification that could not be delivered even after retrying, with
+/// what the history knows about it
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct NotificationDeadLetter {
+    pub id: Uuid,
+    pub notification_id: Uuid,
+    pub task_id: Uuid,
+    pub project_id: Uuid,
+    pub channel: NotificationChannel,
+    #[ts(type = "NotificationEventType")]
+    pub event_type: String,
+    #[ts(type = "NotificationPayload")]
+    pub payload: Json<NotificationPayload>,
+    /// Deliveries tried, redeliveries included
+    pub attempts: i64,
+    pub last_error: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Which dead letters to list; unset fields match any value
+#[derive(Debug, Clone, Default, Deserialize, TS)]
+pub struct DeadLetterFilter {
+    pub project_id: Option<Uuid>,
+    pub channel: Option<NotificationChannel>,
+}
+
+impl NotificationDeadLetter {
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            NotificationDeadLetter,
+            r#"SELECT d.id as "id!: Uuid", d.notification_id as "notification_id!: Uuid", n.task_id as "task_id!: Uuid", n.project_id as "project_id!: Uuid", n.channel as "channel!: NotificationChannel", n.event_type, n.payload as "payload!: Json<NotificationPayload>", d.attempts, d.last_error, d.created_at as "created_at!: DateTime<Utc>", d.updated_at as "updated_at!: DateTime<Utc>"
+               FROM notification_dead_letters d
+               JOIN notifications n ON n.id = d.notification_id
+               WHERE d.id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// The most recent dead letters matching `filter`, newest first
+    pub async fn find_filtered(
+        pool: &SqlitePool,
+        filter: &DeadLetterFilter,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            NotificationDeadLetter,
+            r#"SELECT d.id as "id!: Uuid", d.notification_id as "notification_id!: Uuid", n.task_id as "task_id!: Uuid", n.project_id as "project_id!: Uuid", n.channel as "channel!: NotificationChannel", n.event_type, n.payload as "payload!: Json<NotificationPayload>", d.attempts, d.last_error, d.created_at as "created_at!: DateTime<Utc>", d.updated_at as "updated_at!: DateTime<Utc>"
+               FROM notification_dead_letters d
+               JOIN notifications n ON n.id = d.notification_id
+               WHERE ($1 IS NULL OR n.project_id = $1)
+                 AND ($2 IS NULL OR n.channel = $2)
+               ORDER BY d.created_at DESC, d.rowid DESC
+               LIMIT $3"#,
+            filter.project_id,
+            filter.channel,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Every dead letter, oldest first
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            NotificationDeadLetter,
+            r#"SELECT d.id as "id!: Uuid", d.notification_id as "notification_id!: Uuid", n.task_id as "task_id!: Uuid", n.project_id as "project_id!: Uuid", n.channel as "channel!: NotificationChannel", n.event_type, n.payload as "payload!: Json<NotificationPayload>", d.attempts, d.last_error, d.created_at as "created_at!: DateTime<Utc>", d.updated_at as "updated_at!: DateTime<Utc>"
+               FROM notification_dead_letters d
+               JOIN notifications n ON n.id = d.notification_id
+               ORDER BY d.created_at ASC, d.rowid ASC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Add a failed delivery of the notification, taking `attempts` tries,
+    /// to its dead letter, creating it if there is none yet
+    pub async fn record_failure(
+        pool: &SqlitePool,
+        notification_id: Uuid,
+        attempts: i64,
+        error: &str,
+    ) -> Result<(), sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query!(
+            r#"INSERT INTO notification_dead_letters (id, notification_id, attempts, last_error)
+               VALUES ($1, $2, $3, $4)
+               ON CONFLICT(notification_id) DO UPDATE SET
+                   attempts = attempts + excluded.attempts,
+                   last_error = excluded.last_error,
+                   updated_at = datetime('now', 'subsec')"#,
+            id,
+            notification_id,
+            attempts,
+            error
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM notification_dead_letters WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Drop the notification's dead letter, once it has been delivered
+    pub async fn delete_by_notification_id(
+        pool: &SqlitePool,
+        notification_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM notification_dead_letters WHERE notification_id = $1",
+            notification_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqlitePool, types::Json};
+use uuid::Uuid;
+
+use super::notification::NotificationPayload;
+
+/// A task failure sent through more channels until someone acknowledges it
+#[derive(Debug, Clone, FromRow)]
+pub struct NotificationEscalation {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub project_id: Uuid,
+    pub event_type: String,
+    pub payload: Json<NotificationPayload>,
+    /// Steps of the escalation policy taken so far
+    pub steps_taken: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateNotificationEscalation {
+    pub task_id: Uuid,
+    pub project_id: Uuid,
+    pub event_type: String,
+    pub payload: NotificationPayload,
+}
+
+impl NotificationEscalation {
+    /// Start escalating a failure, unless the task's escalation is already
+    /// under way
+    pub async fn create(
+        pool: &SqlitePool,
+        data: &CreateNotificationEscalation,
+    ) -> Result<(), sqlx::Error> {
+        let id = Uuid::new_v4();
+        let payload = Json(&data.payload);
+        sqlx::query!(
+            r#"INSERT INTO notification_escalations (id, task_id, project_id, event_type, payload)
+               VALUES ($1, $2, $3, $4, $5)
+               ON CONFLICT(task_id) DO NOTHING"#,
+            id,
+            data.task_id,
+            data.project_id,
+            data.event_type,
+            payload
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Every escalation under way, oldest first
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            NotificationEscalation,
+            r#"SELECT id as "id!: Uuid", task_id as "task_id!: Uuid", project_id as "project_id!: Uuid", event_type, payload as "payload!: Json<NotificationPayload>", steps_taken, created_at as "created_at!: DateTime<Utc>"
+               FROM notification_escalations
+               ORDER BY created_at ASC, rowid ASC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Count the next step of the escalation as taken
+    pub async fn record_step(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE notification_escalations SET steps_taken = steps_taken + 1 WHERE id = $1",
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!("DELETE FROM notification_escalations WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,228 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::rust::double_option;
+use sqlx::{FromRow, SqlitePool, Type, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::task::TaskStatus;
+
+/// Task events that can trigger a notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskNotificationKind {
+    /// Task moved to InProgress (an attempt started)
+    Started,
+    /// Task moved to InReview outside of a completed attempt
+    InReview,
+    /// The coding agent attempt failed
+    Failed,
+    /// The coding agent attempt completed
+    Done,
+    /// Task moved to Cancelled
+    Cancelled,
+}
+
+impl TaskNotificationKind {
+    /// Map a newly entered task status to its notification kind, if any
+    pub fn from_status(status: &TaskStatus) -> Option<Self> {
+        match status {
+            TaskStatus::InProgress => Some(Self::Started),
+            TaskStatus::InReview => Some(Self::InReview),
+            TaskStatus::Done => Some(Self::Done),
+            TaskStatus::Cancelled => Some(Self::Cancelled),
+            TaskStatus::Todo => None,
+        }
+    }
+}
+
+/// Where task notifications can be delivered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Type, Serialize, Deserialize, TS)]
+#[sqlx(type_name = "notification_channel", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationChannel {
+    /// Desktop notification and sound on the machine running the server
+    Desktop,
+    Telegram,
+    Slack,
+    Email,
+    Ntfy,
+    Pushover,
+    Gotify,
+    WebPush,
+    /// Apprise-style URLs from the config
+    Apprise,
+    /// Text messages sent through Twilio
+    Sms,
+    #[serde(rename = "whatsapp")]
+    #[sqlx(rename = "whatsapp")]
+    WhatsApp,
+}
+
+impl NotificationChannel {
+    /// Name of the channel in messages
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Desktop => "desktop",
+            Self::Telegram => "Telegram",
+            Self::Slack => "Slack",
+            Self::Email => "email",
+            Self::Ntfy => "ntfy",
+            Self::Pushover => "Pushover",
+            Self::Gotify => "Gotify",
+            Self::WebPush => "Web Push",
+            Self::Apprise => "Apprise",
+            Self::Sms => "SMS",
+            Self::WhatsApp => "WhatsApp",
+        }
+    }
+}
+
+/// Routes task events matching its conditions to a set of channels
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct NotificationRule {
+    pub id: Uuid,
+    pub name: String,
+    /// Only events of this project match; `None` for any project
+    pub project_id: Option<Uuid>,
+    /// Kinds of events that match; empty for all of them
+    #[ts(type = "Array<TaskNotificationKind>")]
+    pub events: Json<Vec<TaskNotificationKind>>,
+    /// Channels matching events are delivered through
+    #[ts(type = "Array<NotificationChannel>")]
+    pub channels: Json<Vec<NotificationChannel>>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct CreateNotificationRule {
+    pub name: String,
+    #[serde(default)]
+    pub project_id: Option<Uuid>,
+    #[serde(default)]
+    pub events: Vec<TaskNotificationKind>,
+    pub channels: Vec<NotificationChannel>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct UpdateNotificationRule {
+    pub name: Option<String>,
+    /// `null` makes the rule match any project
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "double_option"
+    )]
+    #[ts(optional, type = "string | null")]
+    pub project_id: Option<Option<Uuid>>,
+    pub events: Option<Vec<TaskNotificationKind>>,
+    pub channels: Option<Vec<NotificationChannel>>,
+    pub enabled: Option<bool>,
+}
+
+impl NotificationRule {
+    /// Whether an event of `kind` about a task of `project_id` matches
+    pub fn matches(&self, project_id: Uuid, kind: TaskNotificationKind) -> bool {
+        self.enabled
+            && self.project_id.is_none_or(|id| id == project_id)
+            && (self.events.is_empty() || self.events.contains(&kind))
+    }
+
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            NotificationRule,
+            r#"SELECT id as "id!: Uuid", name, project_id as "project_id: Uuid", events as "events!: Json<Vec<TaskNotificationKind>>", channels as "channels!: Json<Vec<NotificationChannel>>", enabled as "enabled!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM notification_rules
+               ORDER BY created_at ASC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_enabled(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            NotificationRule,
+            r#"SELECT id as "id!: Uuid", name, project_id as "project_id: Uuid", events as "events!: Json<Vec<TaskNotificationKind>>", channels as "channels!: Json<Vec<NotificationChannel>>", enabled as "enabled!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM notification_rules
+               WHERE enabled = 1
+               ORDER BY created_at ASC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            NotificationRule,
+            r#"SELECT id as "id!: Uuid", name, project_id as "project_id: Uuid", events as "events!: Json<Vec<TaskNotificationKind>>", channels as "channels!: Json<Vec<NotificationChannel>>", enabled as "enabled!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM notification_rules
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        data: &CreateNotificationRule,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let events = Json(&data.events);
+        let channels = Json(&data.channels);
+        sqlx::query_as!(
+            NotificationRule,
+            r#"INSERT INTO notification_rules (id, name, project_id, events, channels)
+               VALUES ($1, $2, $3, $4, $5)
+               RETURNING id as "id!: Uuid", name, project_id as "project_id: Uuid", events as "events!: Json<Vec<TaskNotificationKind>>", channels as "channels!: Json<Vec<NotificationChannel>>", enabled as "enabled!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            data.name,
+            data.project_id,
+            events,
+            channels
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn update(
+        pool: &SqlitePool,
+        id: Uuid,
+        data: &UpdateNotificationRule,
+    ) -> Result<Self, sqlx::Error> {
+        let existing = Self::find_by_id(pool, id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        let name = data.name.as_ref().unwrap_or(&existing.name);
+        let project_id = data.project_id.unwrap_or(existing.project_id);
+        let events = Json(data.events.as_ref().unwrap_or(&existing.events.0));
+        let channels = Json(data.channels.as_ref().unwrap_or(&existing.channels.0));
+        let enabled = data.enabled.unwrap_or(existing.enabled);
+
+        sqlx::query_as!(
+            NotificationRule,
+            r#"UPDATE notification_rules
+               SET name = $2, project_id = $3, events = $4, channels = $5, enabled = $6, updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid", name, project_id as "project_id: Uuid", events as "events!: Json<Vec<TaskNotificationKind>>", channels as "channels!: Json<Vec<NotificationChannel>>", enabled as "enabled!: bool", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            name,
+            project_id,
+            events,
+            channels,
+            enabled
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM notification_rules WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,82 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool};
+use uuid::Uuid;
+
+/// A pending side effect recorded in the same transaction as the change that caused it
+#[derive(Debug, Clone, FromRow)]
+pub struct OutboxEvent {
+    pub id: Uuid,
+    pub kind: String,
+    pub payload: String,
+    pub attempts: i64,
+    pub last_error: Option<String>,
+    pub available_at: DateTime<Utc>,
+    pub processed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl OutboxEvent {
+    pub async fn enqueue<'e, E>(executor: E, kind: &str, payload: &str) -> Result<Uuid, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let id = Uuid::new_v4();
+        sqlx::query!(
+            "INSERT INTO outbox_events (id, kind, payload) VALUES ($1, $2, $3)",
+            id,
+            kind,
+            payload
+        )
+        .execute(executor)
+        .await?;
+        Ok(id)
+    }
+
+    /// Unprocessed events whose retry time has passed, oldest first
+    pub async fn find_due(pool: &SqlitePool, limit: i64) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            OutboxEvent,
+            r#"SELECT id as "id!: Uuid", kind, payload, attempts, last_error, available_at as "available_at!: DateTime<Utc>", processed_at as "processed_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>"
+               FROM outbox_events
+               WHERE processed_at IS NULL AND julianday(available_at) <= julianday('now')
+               ORDER BY created_at ASC
+               LIMIT $1"#,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn mark_processed(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE outbox_events SET processed_at = datetime('now', 'subsec') WHERE id = $1",
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record a failed delivery and schedule the next attempt `retry_in_secs` from now
+    pub async fn mark_failed(
+        pool: &SqlitePool,
+        id: Uuid,
+        error: &str,
+        retry_in_secs: i64,
+    ) -> Result<(), sqlx::Error> {
+        let modifier = format!("+{retry_in_secs} seconds");
+        sqlx::query!(
+            r#"UPDATE outbox_events
+               SET attempts = attempts + 1,
+                   last_error = $2,
+                   available_at = datetime('now', 'subsec', $3)
+               WHERE id = $1"#,
+            id,
+            error,
+            modifier
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,100 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Replicates the project's tasks with a project on another instance
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ProjectPeerSyncSettings {
+    pub project_id: Uuid,
+    /// Base URL of the other instance, e.g. `http://homeserver:3000`
+    pub peer_url: String,
+    /// The same project's id on the other instance
+    pub remote_project_id: Uuid,
+    /// Shared secret both instances send and expect; never returned by the API
+    #[serde(skip)]
+    pub token: String,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct UpsertProjectPeerSyncSettings {
+    pub peer_url: String,
+    pub remote_project_id: Uuid,
+    pub token: String,
+}
+
+impl ProjectPeerSyncSettings {
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectPeerSyncSettings,
+            r#"SELECT project_id as "project_id!: Uuid", peer_url, remote_project_id as "remote_project_id!: Uuid", token, last_synced_at as "last_synced_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_peer_sync_settings"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectPeerSyncSettings,
+            r#"SELECT project_id as "project_id!: Uuid", peer_url, remote_project_id as "remote_project_id!: Uuid", token, last_synced_at as "last_synced_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_peer_sync_settings
+               WHERE project_id = $1"#,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn upsert(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &UpsertProjectPeerSyncSettings,
+    ) -> Result<Self, sqlx::Error> {
+        let peer_url = data.peer_url.trim_end_matches('/');
+        sqlx::query_as!(
+            ProjectPeerSyncSettings,
+            r#"INSERT INTO project_peer_sync_settings (project_id, peer_url, remote_project_id, token)
+               VALUES ($1, $2, $3, $4)
+               ON CONFLICT (project_id) DO UPDATE
+               SET peer_url = excluded.peer_url,
+                   remote_project_id = excluded.remote_project_id,
+                   token = excluded.token,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING project_id as "project_id!: Uuid", peer_url, remote_project_id as "remote_project_id!: Uuid", token, last_synced_at as "last_synced_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            project_id,
+            peer_url,
+            data.remote_project_id,
+            data.token
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn mark_synced(pool: &SqlitePool, project_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE project_peer_sync_settings SET last_synced_at = datetime('now', 'subsec') WHERE project_id = $1",
+            project_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete(pool: &SqlitePool, project_id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM project_peer_sync_settings WHERE project_id = $1",
+            project_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -23,12 +23,26 @@ pub struct Project {
     pub name: String,
     pub default_agent_working_dir: Option<String>,
     pub remote_project_id: Option<Uuid>,
+    /// Set while the project is archived
+    #[ts(type = "Date | null")]
+    pub archived_at: Option<DateTime<Utc>>,
     #[ts(type = "Date")]
     pub created_at: DateTime<Utc>,
     #[ts(type = "Date")]
     pub updated_at: DateTime<Utc>,
 }
 
+/// A project with its open-task count and when its tasks last changed
+#[derive(Debug, Clone, FromRow)]
+pub struct ProjectActivity {
+    pub id: Uuid,
+    pub name: String,
+    /// Tasks in To Do, In Progress or In Review
+    pub open_tasks: i64,
+    /// Latest task update, or the project's own update when it has no tasks
+    pub last_active_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Deserialize, TS)]
 pub struct CreateProject {
     pub name: String,
@@ -59,9 +73,11 @@ pub enum SearchMatchType {
 
 impl Project {
     pub async fn count(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
-        sqlx::query_scalar!(r#"SELECT COUNT(*) as "count!: i64" FROM projects"#)
-            .fetch_one(pool)
-            .await
+        sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!: i64" FROM projects WHERE deleted_at IS NULL"#
+        )
+        .fetch_one(pool)
+        .await
     }
 
     pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
@@ -71,15 +87,37 @@ impl Project {
                       name,
                       default_agent_working_dir,
                       remote_project_id as "remote_project_id: Uuid",
+                      archived_at as "archived_at: DateTime<Utc>",
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM projects
+               WHERE deleted_at IS NULL
                ORDER BY created_at DESC"#
         )
         .fetch_all(pool)
         .await
     }
 
+    /// Every project with its activity, most recently active first
+    pub async fn find_all_by_activity(
+        pool: &SqlitePool,
+    ) -> Result<Vec<ProjectActivity>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectActivity,
+            r#"SELECT p.id as "id!: Uuid",
+                      p.name,
+                      COALESCE(SUM(t.status IN ('todo', 'inprogress', 'inreview')), 0) as "open_tasks!: i64",
+                      COALESCE(MAX(t.updated_at), p.updated_at) as "last_active_at!: DateTime<Utc>"
+               FROM projects p
+               LEFT JOIN tasks t ON t.project_id = p.id
+               WHERE p.deleted_at IS NULL
+               GROUP BY p.id
+               ORDER BY COALESCE(MAX(t.updated_at), p.updated_at) DESC, p.name"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     /// Find the most actively used projects based on recent task activity
     pub async fn find_most_active(pool: &SqlitePool, limit: i32) -> Result<Vec<Self>, sqlx::Error> {
         sqlx::query_as!(
@@ -88,9 +126,10 @@ impl Project {
             SELECT p.id as "id!: Uuid", p.name,
                    p.default_agent_working_dir,
                    p.remote_project_id as "remote_project_id: Uuid",
+                   p.archived_at as "archived_at: DateTime<Utc>",
                    p.created_at as "created_at!: DateTime<Utc>", p.updated_at as "updated_at!: DateTime<Utc>"
             FROM projects p
-            WHERE p.id IN (
+            WHERE p.deleted_at IS NULL AND p.id IN (
                 SELECT DISTINCT t.project_id
                 FROM tasks t
                 INNER JOIN workspaces w ON w.task_id = t.id
@@ -111,10 +150,11 @@ impl Project {
                       name,
                       default_agent_working_dir,
                       remote_project_id as "remote_project_id: Uuid",
+                      archived_at as "archived_at: DateTime<Utc>",
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM projects
-               WHERE id = $1"#,
+               WHERE id = $1 AND deleted_at IS NULL"#,
             id
         )
         .fetch_optional(pool)
@@ -128,10 +168,11 @@ impl Project {
                       name,
                       default_agent_working_dir,
                       remote_project_id as "remote_project_id: Uuid",
+                      archived_at as "archived_at: DateTime<Utc>",
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM projects
-               WHERE rowid = $1"#,
+               WHERE rowid = $1 AND deleted_at IS NULL"#,
             rowid
         )
         .fetch_optional(pool)
@@ -148,10 +189,11 @@ impl Project {
                       name,
                       default_agent_working_dir,
                       remote_project_id as "remote_project_id: Uuid",
+                      archived_at as "archived_at: DateTime<Utc>",
                       created_at as "created_at!: DateTime<Utc>",
                       updated_at as "updated_at!: DateTime<Utc>"
                FROM projects
-               WHERE remote_project_id = $1
+               WHERE remote_project_id = $1 AND deleted_at IS NULL
                LIMIT 1"#,
             remote_project_id
         )
@@ -176,6 +218,7 @@ impl Project {
                           name,
                           default_agent_working_dir,
                           remote_project_id as "remote_project_id: Uuid",
+                          archived_at as "archived_at: DateTime<Utc>",
                           created_at as "created_at!: DateTime<Utc>",
                           updated_at as "updated_at!: DateTime<Utc>""#,
             project_id,
@@ -205,6 +248,7 @@ impl Project {
                          name,
                          default_agent_working_dir,
                          remote_project_id as "remote_project_id: Uuid",
+                         archived_at as "archived_at: DateTime<Utc>",
                          created_at as "created_at!: DateTime<Utc>",
                          updated_at as "updated_at!: DateTime<Utc>""#,
             id,
@@ -254,6 +298,108 @@ impl Project {
         Ok(())
     }
 
+    /// Id of a soft-deleted project, so live streams can drop it
+    pub async fn find_deleted_id_by_rowid(
+        pool: &SqlitePool,
+        rowid: i64,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT id as "id!: Uuid" FROM projects WHERE rowid = $1 AND deleted_at IS NOT NULL"#,
+            rowid
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Whether the project exists; soft-deleted projects only count with
+    /// `include_deleted`
+    pub async fn exists<'e, E>(
+        executor: E,
+        id: Uuid,
+        include_deleted: bool,
+    ) -> Result<bool, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM projects WHERE id = $1 AND ($2 OR deleted_at IS NULL)
+            ) as "exists!: bool""#,
+            id,
+            include_deleted
+        )
+        .fetch_one(executor)
+        .await
+    }
+
+    /// Archive or unarchive a project. Returns false when it already was in
+    /// that state or is soft-deleted.
+    pub async fn set_archived<'e, E>(
+        executor: E,
+        id: Uuid,
+        archived: bool,
+    ) -> Result<bool, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let result = sqlx::query!(
+            r#"UPDATE projects
+               SET archived_at = CASE WHEN $2 THEN datetime('now', 'subsec') END,
+                   updated_at = datetime('now', 'subsec')
+               WHERE id = $1 AND deleted_at IS NULL AND (archived_at IS NOT NULL) != $2"#,
+            id,
+            archived
+        )
+        .execute(executor)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Soft-delete or restore a project. Returns false when it already was in
+    /// that state.
+    pub async fn set_deleted<'e, E>(
+        executor: E,
+        id: Uuid,
+        deleted: bool,
+    ) -> Result<bool, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let result = sqlx::query!(
+            r#"UPDATE projects
+               SET deleted_at = CASE WHEN $2 THEN datetime('now', 'subsec') END,
+                   updated_at = datetime('now', 'subsec')
+               WHERE id = $1 AND (deleted_at IS NOT NULL) != $2"#,
+            id,
+            deleted
+        )
+        .execute(executor)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Returns false when the project already used `dir` or is soft-deleted
+    pub async fn set_default_agent_working_dir<'e, E>(
+        executor: E,
+        id: Uuid,
+        dir: Option<&str>,
+    ) -> Result<bool, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let result = sqlx::query!(
+            r#"UPDATE projects
+               SET default_agent_working_dir = $2,
+                   updated_at = datetime('now', 'subsec')
+               WHERE id = $1 AND deleted_at IS NULL AND default_agent_working_dir IS NOT $2"#,
+            id,
+            dir
+        )
+        .execute(executor)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
     pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
         let result = sqlx::query!("DELETE FROM projects WHERE id = $1", id)
             .execute(pool)
@@ -0,0 +1,133 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Key that lets automation read and write the tasks of a single project
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ProjectApiKey {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub name: String,
+    /// Start of the key, to tell keys apart without revealing them
+    pub key_prefix: String,
+    /// SHA-256 of the key; never returned by the API
+    #[serde(skip)]
+    pub key_hash: String,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub rotated_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct CreateProjectApiKey {
+    pub name: String,
+}
+
+impl ProjectApiKey {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectApiKey,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", name, key_prefix, key_hash, last_used_at as "last_used_at: DateTime<Utc>", rotated_at as "rotated_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>"
+               FROM project_api_keys
+               WHERE project_id = $1
+               ORDER BY created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_key_hash(
+        pool: &SqlitePool,
+        key_hash: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectApiKey,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", name, key_prefix, key_hash, last_used_at as "last_used_at: DateTime<Utc>", rotated_at as "rotated_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>"
+               FROM project_api_keys
+               WHERE key_hash = $1"#,
+            key_hash
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        name: &str,
+        key_prefix: &str,
+        key_hash: &str,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            ProjectApiKey,
+            r#"INSERT INTO project_api_keys (id, project_id, name, key_prefix, key_hash)
+               VALUES ($1, $2, $3, $4, $5)
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", name, key_prefix, key_hash, last_used_at as "last_used_at: DateTime<Utc>", rotated_at as "rotated_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            project_id,
+            name,
+            key_prefix,
+            key_hash
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Replace the key's secret; the old one stops working immediately
+    pub async fn rotate(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        id: Uuid,
+        key_prefix: &str,
+        key_hash: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectApiKey,
+            r#"UPDATE project_api_keys
+               SET key_prefix = $3,
+                   key_hash = $4,
+                   rotated_at = datetime('now', 'subsec')
+               WHERE id = $1 AND project_id = $2
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", name, key_prefix, key_hash, last_used_at as "last_used_at: DateTime<Utc>", rotated_at as "rotated_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            project_id,
+            key_prefix,
+            key_hash
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Record that the key was just used, at most once a minute
+    pub async fn touch_last_used(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE project_api_keys
+               SET last_used_at = datetime('now', 'subsec')
+               WHERE id = $1
+                 AND (last_used_at IS NULL
+                      OR datetime(last_used_at) < datetime('now', '-1 minute'))"#,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete(pool: &SqlitePool, project_id: Uuid, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM project_api_keys WHERE id = $1 AND project_id = $2",
+            id,
+            project_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
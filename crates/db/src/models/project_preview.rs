@@ -0,0 +1,73 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Marks a project as a web app whose attempts get a preview screenshot
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ProjectPreviewSettings {
+    pub project_id: Uuid,
+    /// Page to capture once the dev server is up, e.g. http://localhost:3000
+    pub preview_url: String,
+    /// How long to wait for `preview_url` to respond before giving up
+    pub startup_timeout_secs: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct UpsertProjectPreviewSettings {
+    pub preview_url: String,
+    pub startup_timeout_secs: Option<i64>,
+}
+
+impl ProjectPreviewSettings {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectPreviewSettings,
+            r#"SELECT project_id as "project_id!: Uuid", preview_url, startup_timeout_secs, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_preview_settings
+               WHERE project_id = $1"#,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn upsert(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &UpsertProjectPreviewSettings,
+    ) -> Result<Self, sqlx::Error> {
+        let startup_timeout_secs = data.startup_timeout_secs.unwrap_or(60);
+        sqlx::query_as!(
+            ProjectPreviewSettings,
+            r#"INSERT INTO project_preview_settings (project_id, preview_url, startup_timeout_secs)
+               VALUES ($1, $2, $3)
+               ON CONFLICT (project_id) DO UPDATE
+               SET preview_url = excluded.preview_url,
+                   startup_timeout_secs = excluded.startup_timeout_secs,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING project_id as "project_id!: Uuid", preview_url, startup_timeout_secs, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            project_id,
+            data.preview_url,
+            startup_timeout_secs
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, project_id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM project_preview_settings WHERE project_id = $1",
+            project_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
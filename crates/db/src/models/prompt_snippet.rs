@@ -0,0 +1,150 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Reusable prompt text with `{{variable}}` placeholders. Snippets without a
+/// project are available everywhere.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct PromptSnippet {
+    pub id: Uuid,
+    pub project_id: Option<Uuid>,
+    pub name: String,
+    pub description: Option<String>,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct CreatePromptSnippet {
+    pub project_id: Option<Uuid>,
+    pub name: String,
+    pub description: Option<String>,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct UpdatePromptSnippet {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub content: Option<String>,
+}
+
+impl PromptSnippet {
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            PromptSnippet,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id: Uuid", name, description, content, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM prompt_snippets
+               ORDER BY name ASC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Snippets usable in `project_id`: its own plus global ones it does not shadow
+    pub async fn find_for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            PromptSnippet,
+            r#"SELECT ps.id as "id!: Uuid", ps.project_id as "project_id: Uuid", ps.name, ps.description, ps.content, ps.created_at as "created_at!: DateTime<Utc>", ps.updated_at as "updated_at!: DateTime<Utc>"
+               FROM prompt_snippets ps
+               WHERE ps.project_id = $1
+                  OR (ps.project_id IS NULL AND NOT EXISTS (
+                        SELECT 1 FROM prompt_snippets own
+                        WHERE own.project_id = $1 AND own.name = ps.name))
+               ORDER BY ps.name ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            PromptSnippet,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id: Uuid", name, description, content, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM prompt_snippets
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Resolve `name` for `project_id`, preferring the project's own snippet
+    pub async fn find_by_name(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        name: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            PromptSnippet,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id: Uuid", name, description, content, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM prompt_snippets
+               WHERE name = $2 AND (project_id = $1 OR project_id IS NULL)
+               ORDER BY project_id IS NULL ASC
+               LIMIT 1"#,
+            project_id,
+            name
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn create(pool: &SqlitePool, data: &CreatePromptSnippet) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            PromptSnippet,
+            r#"INSERT INTO prompt_snippets (id, project_id, name, description, content)
+               VALUES ($1, $2, $3, $4, $5)
+               RETURNING id as "id!: Uuid", project_id as "project_id: Uuid", name, description, content, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            data.project_id,
+            data.name,
+            data.description,
+            data.content
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn update(
+        pool: &SqlitePool,
+        id: Uuid,
+        data: &UpdatePromptSnippet,
+    ) -> Result<Self, sqlx::Error> {
+        let existing = Self::find_by_id(pool, id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        let name = data.name.as_ref().unwrap_or(&existing.name);
+        let description = data.description.as_ref().or(existing.description.as_ref());
+        let content = data.content.as_ref().unwrap_or(&existing.content);
+
+        sqlx::query_as!(
+            PromptSnippet,
+            r#"UPDATE prompt_snippets
+               SET name = $2, description = $3, content = $4, updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid", project_id as "project_id: Uuid", name, description, content, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            name,
+            description,
+            content
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM prompt_snippets WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,120 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::task::TaskStatus;
+
+/// Maximum time a task in a project may stay in `status` before the SLA is breached
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct SlaPolicy {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub status: TaskStatus,
+    pub max_hours: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct UpsertSlaPolicy {
+    pub status: TaskStatus,
+    pub max_hours: i64,
+}
+
+/// A task currently in a status and when it entered it
+#[derive(Debug, Clone, FromRow)]
+pub struct TaskInStatus {
+    pub task_id: Uuid,
+    pub title: String,
+    pub entered_at: DateTime<Utc>,
+}
+
+impl SlaPolicy {
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            SlaPolicy,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", status as "status!: TaskStatus", max_hours, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM sla_policies
+               ORDER BY created_at ASC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            SlaPolicy,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", status as "status!: TaskStatus", max_hours, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM sla_policies
+               WHERE project_id = $1
+               ORDER BY created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Create the project's policy for `data.status`, or replace its limit if one exists
+    pub async fn upsert(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &UpsertSlaPolicy,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            SlaPolicy,
+            r#"INSERT INTO sla_policies (id, project_id, status, max_hours)
+               VALUES ($1, $2, $3, $4)
+               ON CONFLICT (project_id, status) DO UPDATE
+               SET max_hours = excluded.max_hours,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", status as "status!: TaskStatus", max_hours, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            project_id,
+            data.status,
+            data.max_hours
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, project_id: Uuid, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM sla_policies WHERE id = $1 AND project_id = $2",
+            id,
+            project_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Tasks this policy applies to, with the time they last entered its status
+    pub async fn find_tasks_in_status(
+        &self,
+        pool: &SqlitePool,
+    ) -> Result<Vec<TaskInStatus>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskInStatus,
+            r#"SELECT t.id as "task_id!: Uuid",
+                      t.title,
+                      COALESCE(
+                          (SELECT MAX(h.changed_at) FROM task_status_history h
+                           WHERE h.task_id = t.id AND h.status = t.status),
+                          t.updated_at
+                      ) as "entered_at!: DateTime<Utc>"
+               FROM tasks t
+               WHERE t.project_id = $1 AND t.status = $2
+               ORDER BY t.created_at ASC"#,
+            self.project_id,
+            self.status
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
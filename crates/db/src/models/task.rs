@@ -30,6 +30,8 @@ pub struct Task {
     pub description: Option<String>,
     pub status: TaskStatus,
     pub parent_workspace_id: Option<Uuid>, // Foreign key to parent Workspace
+    /// Place on the project's board, lowest first
+    pub position: i64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -57,6 +59,17 @@ impl std::ops::DerefMut for TaskWithAttemptStatus {
     }
 }
 
+/// Number of tasks in each board column of a project
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct TaskStatusCounts {
+    pub todo: usize,
+    pub in_progress: usize,
+    pub in_review: usize,
+    pub done: usize,
+    pub cancelled: usize,
+    pub total: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 pub struct TaskRelationships {
     pub parent_task: Option<Task>, // The task that owns the parent workspace
@@ -98,6 +111,10 @@ pub struct UpdateTask {
     pub status: Option<TaskStatus>,
     pub parent_workspace_id: Option<Uuid>,
     pub image_ids: Option<Vec<Uuid>>,
+    /// `updated_at` of the client's copy; the update is rejected if the task changed since
+    #[serde(default)]
+    #[ts(optional)]
+    pub expected_updated_at: Option<DateTime<Utc>>,
 }
 
 impl Task {
@@ -125,6 +142,7 @@ impl Task {
   t.description,
   t.status                        AS "status!: TaskStatus",
   t.parent_workspace_id           AS "parent_workspace_id: Uuid",
+  t.position,
   t.created_at                    AS "created_at!: DateTime<Utc>",
   t.updated_at                    AS "updated_at!: DateTime<Utc>",
 
@@ -148,7 +166,7 @@ impl Task {
      AND ep.run_reason IN ('setupscript','cleanupscript','codingagent')
      ORDER BY ep.created_at DESC
      LIMIT 1
-  ) IN ('failed','killed') THEN 1 ELSE 0 END
+  ) IN ('failed','killed','stalled') THEN 1 ELSE 0 END
                                  AS "last_attempt_failed!: i64",
 
   ( SELECT s.executor
@@ -161,7 +179,7 @@ impl Task {
 
 FROM tasks t
 WHERE t.project_id = $1
-ORDER BY t.created_at DESC"#,
+ORDER BY t.position ASC, t.created_at DESC, t.id ASC"#,
             project_id
         )
         .fetch_all(pool)
@@ -177,6 +195,7 @@ ORDER BY t.created_at DESC"#,
                     description: rec.description,
                     status: rec.status,
                     parent_workspace_id: rec.parent_workspace_id,
+                    position: rec.position,
                     created_at: rec.created_at,
                     updated_at: rec.updated_at,
                 },
@@ -189,10 +208,40 @@ ORDER BY t.created_at DESC"#,
         Ok(tasks)
     }
 
+    /// Count a project's tasks per status without loading them
+    pub async fn count_by_status(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<TaskStatusCounts, sqlx::Error> {
+        let record = sqlx::query!(
+            r#"SELECT
+                 COALESCE(SUM(status = 'todo'), 0)       AS "todo!: i64",
+                 COALESCE(SUM(status = 'inprogress'), 0) AS "in_progress!: i64",
+                 COALESCE(SUM(status = 'inreview'), 0)   AS "in_review!: i64",
+                 COALESCE(SUM(status = 'done'), 0)       AS "done!: i64",
+                 COALESCE(SUM(status = 'cancelled'), 0)  AS "cancelled!: i64",
+                 COUNT(*)                                AS "total!: i64"
+               FROM tasks
+               WHERE project_id = $1"#,
+            project_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(TaskStatusCounts {
+            todo: record.todo as usize,
+            in_progress: record.in_progress as usize,
+            in_review: record.in_review as usize,
+            done: record.done as usize,
+            cancelled: record.cancelled as usize,
+            total: record.total as usize,
+        })
+    }
+
     pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", position, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks
                WHERE id = $1"#,
             id
@@ -204,7 +253,7 @@ ORDER BY t.created_at DESC"#,
     pub async fn find_by_rowid(pool: &SqlitePool, rowid: i64) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", position, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks
                WHERE rowid = $1"#,
             rowid
@@ -213,6 +262,142 @@ ORDER BY t.created_at DESC"#,
         .await
     }
 
+    /// A project's tasks in board order. Tasks sharing a position are listed
+    /// newest first, so the order is stable.
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", position, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM tasks
+               WHERE project_id = $1
+               ORDER BY position ASC, created_at DESC, id ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// IDs and stored positions of a project's tasks, in board order
+    pub async fn find_positions<'e, E>(
+        executor: E,
+        project_id: Uuid,
+    ) -> Result<Vec<(Uuid, i64)>, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let records = sqlx::query!(
+            r#"SELECT id as "id!: Uuid", position
+               FROM tasks
+               WHERE project_id = $1
+               ORDER BY position ASC, created_at DESC, id ASC"#,
+            project_id
+        )
+        .fetch_all(executor)
+        .await?;
+        Ok(records
+            .into_iter()
+            .map(|record| (record.id, record.position))
+            .collect())
+    }
+
+    /// Move a task to `position` without touching `updated_at`, so moving a
+    /// task does not make clients' copies of it stale
+    pub async fn set_position<'e, E>(
+        executor: E,
+        id: Uuid,
+        position: i64,
+    ) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!("UPDATE tasks SET position = $2 WHERE id = $1", id, position)
+            .execute(executor)
+            .await?;
+        Ok(())
+    }
+
+    /// Tasks that are in progress or in review, plus tasks completed since `since`
+    pub async fn find_for_digest(
+        pool: &SqlitePool,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", position, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM tasks
+               WHERE status IN ('inprogress', 'inreview')
+                  OR (status = 'done' AND datetime(updated_at) >= datetime($1))
+               ORDER BY updated_at DESC"#,
+            since
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Tasks with failure notifications sent since `since` that nobody has
+    /// acknowledged, most recently updated first
+    pub async fn find_with_unacknowledged_failures(
+        pool: &SqlitePool,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", position, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM tasks
+               WHERE EXISTS (
+                   SELECT 1 FROM notifications n
+                   WHERE n.task_id = tasks.id
+                     AND n.event_type = 'task_failed'
+                     AND n.acknowledged_at IS NULL
+                     AND julianday(n.created_at) >= julianday($1)
+               )
+               ORDER BY updated_at DESC"#,
+            since
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Full-text search over titles and descriptions, best matches first.
+    ///
+    /// Each word of `query` is matched as a prefix; FTS syntax in the query is ignored.
+    pub async fn search(
+        pool: &SqlitePool,
+        query: &str,
+        project_id: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let match_expr = query
+            .split_whitespace()
+            .map(|word| word.replace('"', ""))
+            .filter(|word| !word.is_empty())
+            .map(|word| format!("\"{word}\"*"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        if match_expr.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        sqlx::query_as!(
+            Task,
+            r#"SELECT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_workspace_id as "parent_workspace_id: Uuid", t.position, t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>"
+               FROM tasks_fts
+               JOIN tasks t ON t.id = tasks_fts.task_id
+               WHERE tasks_fts MATCH $1 AND ($2 IS NULL OR t.project_id = $2)
+               ORDER BY bm25(tasks_fts)
+               LIMIT $3"#,
+            match_expr,
+            project_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Create a task at the top of its project's board
     pub async fn create(
         pool: &SqlitePool,
         data: &CreateTask,
@@ -221,9 +406,10 @@ ORDER BY t.created_at DESC"#,
         let status = data.status.clone().unwrap_or_default();
         sqlx::query_as!(
             Task,
-            r#"INSERT INTO tasks (id, project_id, title, description, status, parent_workspace_id)
-               VALUES ($1, $2, $3, $4, $5, $6)
-               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            r#"INSERT INTO tasks (id, project_id, title, description, status, parent_workspace_id, position)
+               VALUES ($1, $2, $3, $4, $5, $6,
+                       (SELECT COALESCE(MIN(position), 0) - 1 FROM tasks WHERE project_id = $2))
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", position, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             task_id,
             data.project_id,
             data.title,
@@ -235,43 +421,55 @@ ORDER BY t.created_at DESC"#,
         .await
     }
 
-    pub async fn update(
-        pool: &SqlitePool,
+    /// Update a task. When `expected_updated_at` is set, the row is only written if it still
+    /// matches, and `None` is returned when the caller's copy is stale.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn update<'e, E>(
+        executor: E,
         id: Uuid,
         project_id: Uuid,
         title: String,
         description: Option<String>,
         status: TaskStatus,
         parent_workspace_id: Option<Uuid>,
-    ) -> Result<Self, sqlx::Error> {
+        expected_updated_at: Option<DateTime<Utc>>,
+    ) -> Result<Option<Self>, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         sqlx::query_as!(
             Task,
             r#"UPDATE tasks
-               SET title = $3, description = $4, status = $5, parent_workspace_id = $6
+               SET title = $3, description = $4, status = $5, parent_workspace_id = $6, updated_at = datetime('now', 'subsec')
                WHERE id = $1 AND project_id = $2
-               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+                 AND ($7 IS NULL OR datetime(updated_at, 'subsec') = datetime($7, 'subsec'))
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", position, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             id,
             project_id,
             title,
             description,
             status,
-            parent_workspace_id
+            parent_workspace_id,
+            expected_updated_at
         )
-        .fetch_one(pool)
+        .fetch_optional(executor)
         .await
     }
 
-    pub async fn update_status(
-        pool: &SqlitePool,
+    pub async fn update_status<'e, E>(
+        executor: E,
         id: Uuid,
         status: TaskStatus,
-    ) -> Result<(), sqlx::Error> {
+    ) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         sqlx::query!(
             "UPDATE tasks SET status = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1",
             id,
             status
         )
-        .execute(pool)
+        .execute(executor)
         .await?;
         Ok(())
     }
@@ -292,6 +490,40 @@ ORDER BY t.created_at DESC"#,
         Ok(())
     }
 
+    /// Insert or update a task replicated from another instance, keeping the
+    /// peer's `updated_at`. An existing task is only overwritten when the
+    /// incoming copy is newer. Returns whether anything changed.
+    pub async fn upsert_replicated(
+        pool: &SqlitePool,
+        id: Uuid,
+        project_id: Uuid,
+        title: &str,
+        description: Option<&str>,
+        status: TaskStatus,
+        updated_at: DateTime<Utc>,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"INSERT INTO tasks (id, project_id, title, description, status, updated_at)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               ON CONFLICT (id) DO UPDATE
+               SET title = excluded.title,
+                   description = excluded.description,
+                   status = excluded.status,
+                   updated_at = excluded.updated_at
+               WHERE tasks.project_id = excluded.project_id
+                 AND datetime(excluded.updated_at, 'subsec') > datetime(tasks.updated_at, 'subsec')"#,
+            id,
+            project_id,
+            title,
+            description,
+            status,
+            updated_at
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
     /// Nullify parent_workspace_id for all tasks that reference the given workspace ID
     /// This breaks parent-child relationships before deleting a parent task
     pub async fn nullify_children_by_workspace_id<'e, E>(
@@ -327,7 +559,7 @@ ORDER BY t.created_at DESC"#,
         // Find only child tasks that have this workspace as their parent
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_workspace_id as "parent_workspace_id: Uuid", position, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM tasks
                WHERE parent_workspace_id = $1
                ORDER BY created_at DESC"#,
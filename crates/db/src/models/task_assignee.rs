@@ -0,0 +1,109 @@
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqlitePool, Type};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Type, PartialEq, Eq)]
+#[sqlx(type_name = "task_assignee_role", rename_all = "lowercase")]
+pub enum TaskAssigneeRole {
+    Assignee,
+    /// Gets the task's notifications without being responsible for it
+    Watcher,
+}
+
+/// A linked Telegram account assigned to or watching a task
+#[derive(Debug, Clone, FromRow)]
+pub struct TaskAssignee {
+    pub task_id: Uuid,
+    pub telegram_user_id: i64,
+    pub chat_id: i64,
+    pub username: Option<String>,
+    pub role: TaskAssigneeRole,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TaskAssignee {
+    /// Add `telegram_user_id` to the task. Watching a task one is already
+    /// assigned to keeps the assignment.
+    pub async fn add(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        telegram_user_id: i64,
+        role: TaskAssigneeRole,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT INTO task_assignees (task_id, telegram_user_id, role)
+               VALUES ($1, $2, $3)
+               ON CONFLICT (task_id, telegram_user_id) DO UPDATE
+               SET role = CASE WHEN task_assignees.role = 'assignee' THEN 'assignee' ELSE excluded.role END"#,
+            task_id,
+            telegram_user_id,
+            role
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Returns whether the account was on the task
+    pub async fn remove(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        telegram_user_id: i64,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM task_assignees WHERE task_id = $1 AND telegram_user_id = $2",
+            task_id,
+            telegram_user_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Assignees first, then watchers, each in the order they were added
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskAssignee,
+            r#"SELECT ta.task_id as "task_id!: Uuid",
+                      ta.telegram_user_id as "telegram_user_id!: i64",
+                      a.chat_id as "chat_id!: i64",
+                      a.username,
+                      ta.role as "role!: TaskAssigneeRole",
+                      ta.created_at as "created_at!: DateTime<Utc>"
+               FROM task_assignees ta
+               JOIN telegram_accounts a ON a.user_id = ta.telegram_user_id
+               WHERE ta.task_id = $1
+               ORDER BY ta.role = 'watcher', ta.created_at ASC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Assignees, not watchers, of every task in the project
+    pub async fn find_assignees_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskAssignee,
+            r#"SELECT ta.task_id as "task_id!: Uuid",
+                      ta.telegram_user_id as "telegram_user_id!: i64",
+                      a.chat_id as "chat_id!: i64",
+                      a.username,
+                      ta.role as "role!: TaskAssigneeRole",
+                      ta.created_at as "created_at!: DateTime<Utc>"
+               FROM task_assignees ta
+               JOIN telegram_accounts a ON a.user_id = ta.telegram_user_id
+               JOIN tasks t ON t.id = ta.task_id
+               WHERE t.project_id = $1 AND ta.role = 'assignee'
+               ORDER BY ta.created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
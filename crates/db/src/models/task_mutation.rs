@@ -0,0 +1,173 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool, types::Json};
+use uuid::Uuid;
+
+use super::task::{Task, TaskStatus};
+
+/// Mutations kept per client session; older ones can no longer be undone
+const HISTORY_LIMIT: i64 = 50;
+
+/// The fields of a task that an undo or redo restores
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskSnapshot {
+    pub title: String,
+    pub description: Option<String>,
+    pub status: TaskStatus,
+    pub parent_workspace_id: Option<Uuid>,
+}
+
+impl From<&Task> for TaskSnapshot {
+    fn from(task: &Task) -> Self {
+        Self {
+            title: task.title.clone(),
+            description: task.description.clone(),
+            status: task.status.clone(),
+            parent_workspace_id: task.parent_workspace_id,
+        }
+    }
+}
+
+/// A task edit made from a client session, with the task's state before and
+/// after it
+#[derive(Debug, Clone, FromRow)]
+pub struct TaskMutation {
+    pub id: Uuid,
+    pub client_session: String,
+    pub task_id: Uuid,
+    pub before: Json<TaskSnapshot>,
+    pub after: Json<TaskSnapshot>,
+    /// Set while the mutation is undone, so it can be redone
+    pub undone_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TaskMutation {
+    /// Record an edit. Callers clear the session's redo history first with
+    /// [`Self::delete_undone`], and prune it afterwards with [`Self::prune`].
+    pub async fn create<'e, E>(
+        executor: E,
+        client_session: &str,
+        task_id: Uuid,
+        before: &TaskSnapshot,
+        after: &TaskSnapshot,
+    ) -> Result<Uuid, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let id = Uuid::new_v4();
+        let (before, after) = (Json(before), Json(after));
+        sqlx::query!(
+            r#"INSERT INTO task_mutations (id, client_session, task_id, before_state, after_state)
+               VALUES ($1, $2, $3, $4, $5)"#,
+            id,
+            client_session,
+            task_id,
+            before,
+            after
+        )
+        .execute(executor)
+        .await?;
+        Ok(id)
+    }
+
+    /// Forget the session's undone mutations, which a new edit makes impossible to redo
+    pub async fn delete_undone<'e, E>(executor: E, client_session: &str) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            "DELETE FROM task_mutations WHERE client_session = $1 AND undone_at IS NOT NULL",
+            client_session
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    /// Drop all but the session's most recent mutations
+    pub async fn prune<'e, E>(executor: E, client_session: &str) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            r#"DELETE FROM task_mutations
+               WHERE client_session = $1
+                 AND id NOT IN (
+                     SELECT id FROM task_mutations
+                     WHERE client_session = $1
+                     ORDER BY created_at DESC
+                     LIMIT $2
+                 )"#,
+            client_session,
+            HISTORY_LIMIT
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    /// The session's most recent mutation that is not undone
+    pub async fn find_latest_undoable(
+        pool: &SqlitePool,
+        client_session: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskMutation,
+            r#"SELECT id as "id!: Uuid",
+                      client_session,
+                      task_id as "task_id!: Uuid",
+                      before_state as "before!: Json<TaskSnapshot>",
+                      after_state as "after!: Json<TaskSnapshot>",
+                      undone_at as "undone_at: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM task_mutations
+               WHERE client_session = $1 AND undone_at IS NULL
+               ORDER BY created_at DESC
+               LIMIT 1"#,
+            client_session
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// The session's most recently undone mutation
+    pub async fn find_latest_redoable(
+        pool: &SqlitePool,
+        client_session: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskMutation,
+            r#"SELECT id as "id!: Uuid",
+                      client_session,
+                      task_id as "task_id!: Uuid",
+                      before_state as "before!: Json<TaskSnapshot>",
+                      after_state as "after!: Json<TaskSnapshot>",
+                      undone_at as "undone_at: DateTime<Utc>",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM task_mutations
+               WHERE client_session = $1 AND undone_at IS NOT NULL
+               ORDER BY undone_at DESC
+               LIMIT 1"#,
+            client_session
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn set_undone<'e, E>(executor: E, id: Uuid, undone: bool) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            r#"UPDATE task_mutations
+               SET undone_at = CASE WHEN $2 THEN datetime('now', 'subsec') ELSE NULL END
+               WHERE id = $1"#,
+            id,
+            undone
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::task::TaskStatus;
+
+/// A single status transition, recorded by triggers on the tasks table
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskStatusChange {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub status: TaskStatus,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// A task and the status it had at a point in time
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskStatusAt {
+    pub task_id: Uuid,
+    pub title: String,
+    pub status: TaskStatus,
+}
+
+/// A task that moved to done, and when
+#[derive(Debug, Clone, FromRow)]
+pub struct CompletedTask {
+    pub task_id: Uuid,
+    pub project_id: Uuid,
+    pub title: String,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: DateTime<Utc>,
+}
+
+impl TaskStatusChange {
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskStatusChange,
+            r#"SELECT id as "id!: Uuid", task_id as "task_id!: Uuid", status as "status!: TaskStatus", changed_at as "changed_at!: DateTime<Utc>"
+               FROM task_status_history
+               WHERE task_id = $1
+               ORDER BY changed_at ASC, rowid ASC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// The task's most recent status change as `(previous, current)`; the
+    /// previous status is `None` for a task that has only been created
+    pub async fn find_latest_transition(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Option<(Option<TaskStatus>, TaskStatus)>, sqlx::Error> {
+        let mut latest = sqlx::query_scalar!(
+            r#"SELECT status as "status!: TaskStatus"
+               FROM task_status_history
+               WHERE task_id = $1
+               ORDER BY changed_at DESC, rowid DESC
+               LIMIT 2"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter();
+        Ok(latest.next().map(|current| (latest.next(), current)))
+    }
+
+    /// Status of every task in the project as of `at`. Tasks created after `at`
+    /// and deleted tasks are not included.
+    pub async fn find_project_state_at(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        at: DateTime<Utc>,
+    ) -> Result<Vec<TaskStatusAt>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskStatusAt,
+            r#"SELECT t.id as "task_id!: Uuid", t.title, h.status as "status!: TaskStatus"
+               FROM tasks t
+               JOIN task_status_history h ON h.rowid = (
+                   SELECT h2.rowid
+                   FROM task_status_history h2
+                   WHERE h2.task_id = t.id AND julianday(h2.changed_at) <= julianday($2)
+                   ORDER BY h2.changed_at DESC, h2.rowid DESC
+                   LIMIT 1
+               )
+               WHERE t.project_id = $1
+               ORDER BY t.created_at ASC"#,
+            project_id,
+            at
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Tasks that are done and last moved to done at or after `since`, most
+    /// recent first
+    pub async fn find_completed_since(
+        pool: &SqlitePool,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<CompletedTask>, sqlx::Error> {
+        sqlx::query_as!(
+            CompletedTask,
+            r#"SELECT t.id as "task_id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.created_at as "created_at!: DateTime<Utc>", MAX(h.changed_at) as "completed_at!: DateTime<Utc>"
+               FROM tasks t
+               JOIN task_status_history h ON h.task_id = t.id AND h.status = 'done'
+               WHERE t.status = 'done'
+               GROUP BY t.id
+               HAVING julianday(MAX(h.changed_at)) >= julianday($1)
+               ORDER BY MAX(h.changed_at) DESC"#,
+            since
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
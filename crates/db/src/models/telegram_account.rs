@@ -0,0 +1,180 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[sqlx(type_name = "telegram_account_role", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum TelegramAccountRole {
+    /// Can run every command
+    Admin,
+    /// Can look at projects and tasks, but not create, change or merge them
+    ReadOnly,
+}
+
+/// How an account gets its task notifications
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, TS, PartialEq, Eq, Default)]
+#[sqlx(type_name = "telegram_digest_mode", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum TelegramDigestMode {
+    /// Each notification as it happens
+    #[default]
+    Immediate,
+    /// Collected into one message an hour after the first one
+    Hourly,
+    /// Collected into one message a day after the first one
+    Daily,
+}
+
+/// A Telegram account that linked itself to the bot
+#[derive(Debug, Clone, FromRow, Serialize, TS)]
+pub struct TelegramAccount {
+    pub user_id: i64,
+    pub chat_id: i64,
+    pub username: Option<String>,
+    pub role: TelegramAccountRole,
+    pub digest_mode: TelegramDigestMode,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TelegramAccount {
+    pub async fn upsert(
+        pool: &SqlitePool,
+        user_id: i64,
+        chat_id: i64,
+        username: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT INTO telegram_accounts (user_id, chat_id, username)
+               VALUES ($1, $2, $3)
+               ON CONFLICT (user_id) DO UPDATE
+               SET chat_id = excluded.chat_id,
+                   username = excluded.username,
+                   updated_at = datetime('now', 'subsec')"#,
+            user_id,
+            chat_id,
+            username
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn find_by_user_id(
+        pool: &SqlitePool,
+        user_id: i64,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TelegramAccount,
+            r#"SELECT user_id as "user_id!: i64",
+                      chat_id as "chat_id!: i64",
+                      username,
+                      role as "role!: TelegramAccountRole",
+                      digest_mode as "digest_mode!: TelegramDigestMode",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM telegram_accounts
+               WHERE user_id = $1"#,
+            user_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Find an account by Telegram username, ignoring case
+    pub async fn find_by_username(
+        pool: &SqlitePool,
+        username: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TelegramAccount,
+            r#"SELECT user_id as "user_id!: i64",
+                      chat_id as "chat_id!: i64",
+                      username,
+                      role as "role!: TelegramAccountRole",
+                      digest_mode as "digest_mode!: TelegramDigestMode",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM telegram_accounts
+               WHERE username = $1 COLLATE NOCASE"#,
+            username
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TelegramAccount,
+            r#"SELECT user_id as "user_id!: i64",
+                      chat_id as "chat_id!: i64",
+                      username,
+                      role as "role!: TelegramAccountRole",
+                      digest_mode as "digest_mode!: TelegramDigestMode",
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM telegram_accounts
+               ORDER BY created_at"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Returns whether the account exists
+    pub async fn set_role(
+        pool: &SqlitePool,
+        user_id: i64,
+        role: TelegramAccountRole,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE telegram_accounts
+               SET role = $2, updated_at = datetime('now', 'subsec')
+               WHERE user_id = $1"#,
+            user_id,
+            role
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Digest mode of the account whose private chat is `chat_id`, None when
+    /// the chat is not an account's
+    pub async fn find_digest_mode_by_chat_id(
+        pool: &SqlitePool,
+        chat_id: i64,
+    ) -> Result<Option<TelegramDigestMode>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT digest_mode as "digest_mode!: TelegramDigestMode"
+               FROM telegram_accounts
+               WHERE chat_id = $1
+               LIMIT 1"#,
+            chat_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Returns whether the account exists
+    pub async fn set_digest_mode(
+        pool: &SqlitePool,
+        user_id: i64,
+        digest_mode: TelegramDigestMode,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE telegram_accounts
+               SET digest_mode = $2, updated_at = datetime('now', 'subsec')
+               WHERE user_id = $1"#,
+            user_id,
+            digest_mode
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn delete_all(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+        sqlx::query!("DELETE FROM telegram_accounts")
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,176 @@
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// Per-chat bot state, such as the project chosen with /project
+pub struct TelegramChatSettings;
+
+impl TelegramChatSettings {
+    pub async fn find_active_project_id(
+        pool: &SqlitePool,
+        chat_id: i64,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        let project_id = sqlx::query_scalar!(
+            r#"SELECT active_project_id as "active_project_id: Uuid"
+               FROM telegram_chat_settings
+               WHERE chat_id = $1"#,
+            chat_id
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(project_id.flatten())
+    }
+
+    pub async fn set_active_project_id(
+        pool: &SqlitePool,
+        chat_id: i64,
+        project_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT INTO telegram_chat_settings (chat_id, active_project_id)
+               VALUES ($1, $2)
+               ON CONFLICT (chat_id) DO UPDATE
+               SET active_project_id = excluded.active_project_id,
+                   updated_at = datetime('now', 'subsec')"#,
+            chat_id,
+            project_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+/// Forum topic of a group chat dedicated to one project
+pub struct TelegramProjectTopic;
+
+impl TelegramProjectTopic {
+    pub async fn find_project_id(
+        pool: &SqlitePool,
+        chat_id: i64,
+        message_thread_id: i32,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT project_id as "project_id!: Uuid"
+               FROM telegram_project_topics
+               WHERE chat_id = $1 AND message_thread_id = $2"#,
+            chat_id,
+            message_thread_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Topic of `chat_id` the project's notifications are sent to; the most
+    /// recently mapped one if the project has several
+    pub async fn find_thread_id(
+        pool: &SqlitePool,
+        chat_id: i64,
+        project_id: Uuid,
+    ) -> Result<Option<i32>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT message_thread_id as "message_thread_id!: i32"
+               FROM telegram_project_topics
+               WHERE chat_id = $1 AND project_id = $2
+               ORDER BY created_at DESC
+               LIMIT 1"#,
+            chat_id,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn set(
+        pool: &SqlitePool,
+        chat_id: i64,
+        message_thread_id: i32,
+        project_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT INTO telegram_project_topics (chat_id, message_thread_id, project_id)
+               VALUES ($1, $2, $3)
+               ON CONFLICT (chat_id, message_thread_id) DO UPDATE
+               SET project_id = excluded.project_id,
+                   created_at = datetime('now', 'subsec')"#,
+            chat_id,
+            message_thread_id,
+            project_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+/// Project shortcut of a chat, used as a command (`/p1`)
+#[derive(Debug, Clone)]
+pub struct TelegramProjectShortcut {
+    pub name: String,
+    pub project_id: Uuid,
+}
+
+impl TelegramProjectShortcut {
+    pub async fn find_project_id(
+        pool: &SqlitePool,
+        chat_id: i64,
+        name: &str,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT project_id as "project_id!: Uuid"
+               FROM telegram_project_shortcuts
+               WHERE chat_id = $1 AND name = $2"#,
+            chat_id,
+            name
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_by_chat_id(
+        pool: &SqlitePool,
+        chat_id: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TelegramProjectShortcut,
+            r#"SELECT name, project_id as "project_id!: Uuid"
+               FROM telegram_project_shortcuts
+               WHERE chat_id = $1
+               ORDER BY name"#,
+            chat_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn set(
+        pool: &SqlitePool,
+        chat_id: i64,
+        name: &str,
+        project_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT INTO telegram_project_shortcuts (chat_id, name, project_id)
+               VALUES ($1, $2, $3)
+               ON CONFLICT (chat_id, name) DO UPDATE
+               SET project_id = excluded.project_id"#,
+            chat_id,
+            name,
+            project_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Returns whether a shortcut was removed
+    pub async fn delete(pool: &SqlitePool, chat_id: i64, name: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM telegram_project_shortcuts WHERE chat_id = $1 AND name = $2",
+            chat_id,
+            name
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
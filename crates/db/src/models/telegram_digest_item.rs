@@ -0,0 +1,73 @@
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqlitePool};
+use uuid::Uuid;
+
+/// A notification held back for a chat's next digest
+#[derive(Debug, Clone, FromRow)]
+pub struct TelegramDigestItem {
+    pub id: Uuid,
+    pub chat_id: i64,
+    /// Task the notification is about
+    pub task_id: Option<Uuid>,
+    pub text: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TelegramDigestItem {
+    pub async fn create(
+        pool: &SqlitePool,
+        chat_id: i64,
+        task_id: Option<Uuid>,
+        text: &str,
+    ) -> Result<(), sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query!(
+            r#"INSERT INTO telegram_digest_items (id, chat_id, task_id, text)
+               VALUES ($1, $2, $3, $4)"#,
+            id,
+            chat_id,
+            task_id,
+            text
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Chats whose digest is due: their oldest held notification is an hour
+    /// or a day old, depending on the account's digest mode. Chats that are
+    /// no longer in a digest mode are due right away.
+    pub async fn find_due_chat_ids(pool: &SqlitePool) -> Result<Vec<i64>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT i.chat_id as "chat_id!: i64"
+               FROM telegram_digest_items i
+               GROUP BY i.chat_id
+               HAVING julianday(MIN(i.created_at)) <= julianday('now', CASE (
+                          SELECT a.digest_mode FROM telegram_accounts a
+                          WHERE a.chat_id = i.chat_id
+                          LIMIT 1
+                      )
+                      WHEN 'hourly' THEN '-1 hours'
+                      WHEN 'daily' THEN '-1 days'
+                      ELSE '+0 seconds'
+                  END)"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Remove and return the notifications held for `chat_id`, oldest first
+    pub async fn take_for_chat(pool: &SqlitePool, chat_id: i64) -> Result<Vec<Self>, sqlx::Error> {
+        let mut items = sqlx::query_as!(
+            TelegramDigestItem,
+            r#"DELETE FROM telegram_digest_items
+               WHERE chat_id = $1
+               RETURNING id as "id!: Uuid", chat_id, task_id as "task_id: Uuid", text, created_at as "created_at!: DateTime<Utc>""#,
+            chat_id
+        )
+        .fetch_all(pool)
+        .await?;
+        items.sort_by_key(|item| item.created_at);
+        Ok(items)
+    }
+}
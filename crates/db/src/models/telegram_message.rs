@@ -0,0 +1,121 @@
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// Mapping from a Telegram message sent by the bot to the task it refers to
+pub struct TelegramMessageTask;
+
+impl TelegramMessageTask {
+    pub async fn create(
+        pool: &SqlitePool,
+        chat_id: i64,
+        message_id: i32,
+        task_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT OR REPLACE INTO telegram_message_tasks (chat_id, message_id, task_id)
+               VALUES ($1, $2, $3)"#,
+            chat_id,
+            message_id,
+            task_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn find_task_id(
+        pool: &SqlitePool,
+        chat_id: i64,
+        message_id: i32,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT task_id as "task_id!: Uuid"
+               FROM telegram_message_tasks
+               WHERE chat_id = $1 AND message_id = $2"#,
+            chat_id,
+            message_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+}
+
+/// The message edited on each status change of a task, per chat
+pub struct TelegramProgressMessage;
+
+impl TelegramProgressMessage {
+    pub async fn find_message_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        chat_id: i64,
+    ) -> Result<Option<i32>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT message_id as "message_id!: i32"
+               FROM telegram_progress_messages
+               WHERE task_id = $1 AND chat_id = $2"#,
+            task_id,
+            chat_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn upsert(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        chat_id: i64,
+        message_id: i32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT INTO telegram_progress_messages (task_id, chat_id, message_id)
+               VALUES ($1, $2, $3)
+               ON CONFLICT (task_id, chat_id) DO UPDATE
+               SET message_id = excluded.message_id,
+                   updated_at = datetime('now', 'subsec')"#,
+            task_id,
+            chat_id,
+            message_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+/// Webhook updates already handled, per bot
+pub struct TelegramSeenUpdate;
+
+impl TelegramSeenUpdate {
+    /// Remember `update_id` as handled by `bot_id`. Returns false when it was
+    /// seen before.
+    pub async fn record(
+        pool: &SqlitePool,
+        bot_id: &str,
+        update_id: i64,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"INSERT OR IGNORE INTO telegram_seen_updates (bot_id, update_id)
+               VALUES ($1, $2)"#,
+            bot_id,
+            update_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Forget updates seen before `cutoff`
+    pub async fn delete_seen_before(
+        pool: &SqlitePool,
+        cutoff: DateTime<Utc>,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"DELETE FROM telegram_seen_updates WHERE datetime(seen_at) < datetime($1)"#,
+            cutoff
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,174 @@
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqlitePool};
+use uuid::Uuid;
+
+/// A Telegram notification waiting to be sent
+#[derive(Debug, Clone, FromRow)]
+pub struct TelegramOutgoingMessage {
+    pub id: Uuid,
+    /// Project whose own bot sends the message, None for the default bot
+    pub bot_project_id: Option<Uuid>,
+    pub chat_id: i64,
+    pub message_thread_id: Option<i32>,
+    /// Task the message is about, so replies to it can be routed there
+    pub task_id: Option<Uuid>,
+    pub text: String,
+    /// Serialized reply markup
+    pub reply_markup: Option<String>,
+    pub silent: bool,
+    pub attempts: i64,
+    pub last_error: Option<String>,
+    pub available_at: DateTime<Utc>,
+    pub processed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateTelegramOutgoingMessage {
+    pub bot_project_id: Option<Uuid>,
+    pub chat_id: i64,
+    pub message_thread_id: Option<i32>,
+    pub task_id: Option<Uuid>,
+    pub text: String,
+    pub reply_markup: Option<String>,
+    pub silent: bool,
+}
+
+impl TelegramOutgoingMessage {
+    pub async fn enqueue(
+        pool: &SqlitePool,
+        data: &CreateTelegramOutgoingMessage,
+    ) -> Result<Uuid, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query!(
+            r#"INSERT INTO telegram_outgoing_messages
+                   (id, bot_project_id, chat_id, message_thread_id, task_id, text, reply_markup, silent)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"#,
+            id,
+            data.bot_project_id,
+            data.chat_id,
+            data.message_thread_id,
+            data.task_id,
+            data.text,
+            data.reply_markup,
+            data.silent
+        )
+        .execute(pool)
+        .await?;
+        Ok(id)
+    }
+
+    /// The oldest unsent message of each chat, when its retry time has passed.
+    /// A chat waits while its oldest message backs off, so it never gets
+    /// messages out of order.
+    pub async fn find_due_heads(pool: &SqlitePool, limit: i64) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TelegramOutgoingMessage,
+            r#"SELECT m.id as "id!: Uuid", m.bot_project_id as "bot_project_id: Uuid", m.chat_id, m.message_thread_id as "message_thread_id: i32", m.task_id as "task_id: Uuid", m.text, m.reply_markup, m.silent as "silent!: bool", m.attempts, m.last_error, m.available_at as "available_at!: DateTime<Utc>", m.processed_at as "processed_at: DateTime<Utc>", m.created_at as "created_at!: DateTime<Utc>"
+               FROM telegram_outgoing_messages m
+               WHERE m.rowid = (
+                     SELECT rowid FROM telegram_outgoing_messages
+                     WHERE processed_at IS NULL AND chat_id = m.chat_id
+                     ORDER BY created_at ASC, rowid ASC
+                     LIMIT 1
+                 )
+                 AND julianday(m.available_at) <= julianday('now')
+               ORDER BY m.created_at ASC
+               LIMIT $1"#,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Tie the message to the history entry of a notification it delivers
+    pub async fn link_notification(
+        pool: &SqlitePool,
+        id: Uuid,
+        notification_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT OR IGNORE INTO telegram_outgoing_message_notifications (message_id, notification_id)
+               VALUES ($1, $2)"#,
+            id,
+            notification_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Tie the unsent messages about `task_id` that are not tied to a
+    /// notification yet to `notification_id`
+    pub async fn link_task_notification(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        notification_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"INSERT INTO telegram_outgoing_message_notifications (message_id, notification_id)
+               SELECT m.id, $2
+               FROM telegram_outgoing_messages m
+               WHERE m.task_id = $1
+                 AND m.processed_at IS NULL
+                 AND NOT EXISTS (
+                     SELECT 1 FROM telegram_outgoing_message_notifications l
+                     WHERE l.message_id = m.id
+                 )"#,
+            task_id,
+            notification_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// History entries of the notifications the message delivers
+    pub async fn find_notification_ids(
+        pool: &SqlitePool,
+        id: Uuid,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT notification_id as "notification_id!: Uuid"
+               FROM telegram_outgoing_message_notifications
+               WHERE message_id = $1"#,
+            id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Mark a message as done with, whether it was sent or given up on
+    pub async fn mark_processed(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE telegram_outgoing_messages SET processed_at = datetime('now', 'subsec') WHERE id = $1",
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record a failed send and schedule the next attempt `retry_in_secs` from now
+    pub async fn mark_failed(
+        pool: &SqlitePool,
+        id: Uuid,
+        error: &str,
+        retry_in_secs: i64,
+    ) -> Result<(), sqlx::Error> {
+        let modifier = format!("+{retry_in_secs} seconds");
+        sqlx::query!(
+            r#"UPDATE telegram_outgoing_messages
+               SET attempts = attempts + 1,
+                   last_error = $2,
+                   available_at = datetime('now', 'subsec', $3)
+               WHERE id = $1"#,
+            id,
+            error,
+            modifier
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
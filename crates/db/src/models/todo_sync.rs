@@ -0,0 +1,141 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Imports checkbox items from a markdown file in one of the project's repos
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ProjectTodoSyncSettings {
+    pub project_id: Uuid,
+    pub repo_id: Uuid,
+    /// Relative to the repo root, e.g. `TODO.md` or `docs/BACKLOG.md`
+    pub file_path: String,
+    /// Tick an item's checkbox in the file once its task is done
+    pub write_back: bool,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct UpsertProjectTodoSyncSettings {
+    pub repo_id: Uuid,
+    pub file_path: Option<String>,
+    pub write_back: Option<bool>,
+}
+
+/// Links an imported task to the file item it came from
+#[derive(Debug, Clone, FromRow)]
+pub struct TodoSyncItem {
+    pub project_id: Uuid,
+    pub item_key: String,
+    pub task_id: Uuid,
+}
+
+impl ProjectTodoSyncSettings {
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectTodoSyncSettings,
+            r#"SELECT project_id as "project_id!: Uuid", repo_id as "repo_id!: Uuid", file_path, write_back as "write_back!: bool", last_synced_at as "last_synced_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_todo_sync_settings"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectTodoSyncSettings,
+            r#"SELECT project_id as "project_id!: Uuid", repo_id as "repo_id!: Uuid", file_path, write_back as "write_back!: bool", last_synced_at as "last_synced_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_todo_sync_settings
+               WHERE project_id = $1"#,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn upsert(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &UpsertProjectTodoSyncSettings,
+    ) -> Result<Self, sqlx::Error> {
+        let file_path = data.file_path.as_deref().unwrap_or("TODO.md");
+        let write_back = data.write_back.unwrap_or(false);
+        sqlx::query_as!(
+            ProjectTodoSyncSettings,
+            r#"INSERT INTO project_todo_sync_settings (project_id, repo_id, file_path, write_back)
+               VALUES ($1, $2, $3, $4)
+               ON CONFLICT (project_id) DO UPDATE
+               SET repo_id = excluded.repo_id,
+                   file_path = excluded.file_path,
+                   write_back = excluded.write_back,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING project_id as "project_id!: Uuid", repo_id as "repo_id!: Uuid", file_path, write_back as "write_back!: bool", last_synced_at as "last_synced_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            project_id,
+            data.repo_id,
+            file_path,
+            write_back
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn mark_synced(pool: &SqlitePool, project_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE project_todo_sync_settings SET last_synced_at = datetime('now', 'subsec') WHERE project_id = $1",
+            project_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete(pool: &SqlitePool, project_id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM project_todo_sync_settings WHERE project_id = $1",
+            project_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+impl TodoSyncItem {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TodoSyncItem,
+            r#"SELECT project_id as "project_id!: Uuid", item_key, task_id as "task_id!: Uuid"
+               FROM todo_sync_items
+               WHERE project_id = $1"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        item_key: &str,
+        task_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "INSERT INTO todo_sync_items (project_id, item_key, task_id) VALUES ($1, $2, $3)",
+            project_id,
+            item_key,
+            task_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
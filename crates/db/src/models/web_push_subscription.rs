@@ -0,0 +1,97 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A browser that asked to be sent Web Push notifications
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct WebPushSubscription {
+    pub id: Uuid,
+    /// Push service URL the encrypted messages are posted to
+    pub endpoint: String,
+    /// The browser's P-256 public key, base64url
+    #[serde(skip)]
+    pub p256dh: String,
+    /// The browser's auth secret, base64url
+    #[serde(skip)]
+    pub auth: String,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Encryption keys of a push subscription, as in `PushSubscription.toJSON()`
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct WebPushKeys {
+    pub p256dh: String,
+    pub auth: String,
+}
+
+/// A browser's `PushSubscription.toJSON()`, along with its user agent
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct CreateWebPushSubscription {
+    pub endpoint: String,
+    pub keys: WebPushKeys,
+    #[serde(default)]
+    pub user_agent: Option<String>,
+}
+
+impl WebPushSubscription {
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WebPushSubscription,
+            r#"SELECT id as "id!: Uuid", endpoint, p256dh, auth, user_agent, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM web_push_subscriptions
+               ORDER BY created_at ASC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Store a subscription. A browser subscribing again with the same
+    /// endpoint replaces its keys.
+    pub async fn upsert(
+        pool: &SqlitePool,
+        data: &CreateWebPushSubscription,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            WebPushSubscription,
+            r#"INSERT INTO web_push_subscriptions (id, endpoint, p256dh, auth, user_agent)
+               VALUES ($1, $2, $3, $4, $5)
+               ON CONFLICT(endpoint) DO UPDATE SET
+                   p256dh = excluded.p256dh,
+                   auth = excluded.auth,
+                   user_agent = excluded.user_agent,
+                   updated_at = datetime('now', 'subsec')
+               RETURNING id as "id!: Uuid", endpoint, p256dh, auth, user_agent, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            data.endpoint,
+            data.keys.p256dh,
+            data.keys.auth,
+            data.user_agent
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM web_push_subscriptions WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Forget a subscription, e.g. when the browser unsubscribed or the push
+    /// service reports it gone
+    pub async fn delete_by_endpoint(pool: &SqlitePool, endpoint: &str) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM web_push_subscriptions WHERE endpoint = $1",
+            endpoint
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
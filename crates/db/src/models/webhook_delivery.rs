@@ -0,0 +1,175 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::webhook_endpoint::WebhookEventType;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Type, Serialize, Deserialize, TS)]
+#[sqlx(type_name = "webhook_delivery_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookDeliveryStatus {
+    /// Not sent yet, or waiting to be retried
+    Pending,
+    Delivered,
+    /// Gave up after too many failed attempts
+    Failed,
+}
+
+/// An event queued for, or sent to, one webhook endpoint
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub endpoint_id: Uuid,
+    pub event_type: WebhookEventType,
+    pub task_id: Uuid,
+    /// The coding agent run, for `attempt.finished`
+    pub execution_process_id: Option<Uuid>,
+    /// Task status before the change, or the run's status for `attempt.finished`
+    pub from_status: Option<String>,
+    /// Task status after the change, or the run's final status
+    pub to_status: Option<String>,
+    /// Body as first sent
+    pub payload: Option<String>,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: i64,
+    /// HTTP status of the last response, if the endpoint answered
+    pub response_status: Option<i64>,
+    pub last_error: Option<String>,
+    pub next_attempt_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl WebhookDelivery {
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WebhookDelivery,
+            r#"SELECT id as "id!: Uuid", endpoint_id as "endpoint_id!: Uuid", event_type as "event_type!: WebhookEventType", task_id as "task_id!: Uuid", execution_process_id as "execution_process_id: Uuid", from_status, to_status, payload, status as "status!: WebhookDeliveryStatus", attempts, response_status, last_error, next_attempt_at as "next_attempt_at!: DateTime<Utc>", delivered_at as "delivered_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>"
+               FROM webhook_deliveries
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// The endpoint's most recent deliveries, newest first
+    pub async fn find_by_endpoint_id(
+        pool: &SqlitePool,
+        endpoint_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WebhookDelivery,
+            r#"SELECT id as "id!: Uuid", endpoint_id as "endpoint_id!: Uuid", event_type as "event_type!: WebhookEventType", task_id as "task_id!: Uuid", execution_process_id as "execution_process_id: Uuid", from_status, to_status, payload, status as "status!: WebhookDeliveryStatus", attempts, response_status, last_error, next_attempt_at as "next_attempt_at!: DateTime<Utc>", delivered_at as "delivered_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>"
+               FROM webhook_deliveries
+               WHERE endpoint_id = $1
+               ORDER BY created_at DESC, rowid DESC
+               LIMIT $2"#,
+            endpoint_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Pending deliveries whose retry time has passed, oldest first
+    pub async fn find_due(pool: &SqlitePool, limit: i64) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WebhookDelivery,
+            r#"SELECT id as "id!: Uuid", endpoint_id as "endpoint_id!: Uuid", event_type as "event_type!: WebhookEventType", task_id as "task_id!: Uuid", execution_process_id as "execution_process_id: Uuid", from_status, to_status, payload, status as "status!: WebhookDeliveryStatus", attempts, response_status, last_error, next_attempt_at as "next_attempt_at!: DateTime<Utc>", delivered_at as "delivered_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>"
+               FROM webhook_deliveries
+               WHERE status = 'pending' AND julianday(next_attempt_at) <= julianday('now')
+               ORDER BY created_at ASC, rowid ASC
+               LIMIT $1"#,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn set_payload(
+        pool: &SqlitePool,
+        id: Uuid,
+        payload: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE webhook_deliveries SET payload = $2 WHERE id = $1",
+            id,
+            payload
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn mark_delivered(
+        pool: &SqlitePool,
+        id: Uuid,
+        response_status: i64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE webhook_deliveries
+               SET status = 'delivered',
+                   attempts = attempts + 1,
+                   response_status = $2,
+                   last_error = NULL,
+                   delivered_at = datetime('now', 'subsec')
+               WHERE id = $1"#,
+            id,
+            response_status
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record a failed attempt and retry `retry_in_secs` from now, or give up
+    /// when it is `None`
+    pub async fn mark_failed(
+        pool: &SqlitePool,
+        id: Uuid,
+        response_status: Option<i64>,
+        error: &str,
+        retry_in_secs: Option<i64>,
+    ) -> Result<(), sqlx::Error> {
+        let status = match retry_in_secs {
+            Some(_) => WebhookDeliveryStatus::Pending,
+            None => WebhookDeliveryStatus::Failed,
+        };
+        let modifier = format!("+{} seconds", retry_in_secs.unwrap_or(0));
+        sqlx::query!(
+            r#"UPDATE webhook_deliveries
+               SET status = $2,
+                   attempts = attempts + 1,
+                   response_status = $3,
+                   last_error = $4,
+                   next_attempt_at = datetime('now', 'subsec', $5)
+               WHERE id = $1"#,
+            id,
+            status,
+            response_status,
+            error,
+            modifier
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Queue the delivery to be sent again right away, with the same payload
+    pub async fn redeliver(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE webhook_deliveries
+               SET status = 'pending',
+                   next_attempt_at = datetime('now', 'subsec')
+               WHERE id = $1"#,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
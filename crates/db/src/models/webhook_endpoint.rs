@@ -0,0 +1,222 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Type, Serialize, Deserialize, TS)]
+#[sqlx(type_name = "webhook_payload_format", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookPayloadFormat {
+    /// `{id, type, occurred_at, data}`
+    #[default]
+    Native,
+    /// A Slack incoming-webhook message
+    Slack,
+    /// A Discord webhook message with one embed
+    Discord,
+    /// A CloudEvents 1.0 event in structured JSON mode
+    CloudEvents,
+}
+
+impl WebhookPayloadFormat {
+    /// Content-Type header for payloads of this format
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::CloudEvents => "application/cloudevents+json",
+            _ => "application/json",
+        }
+    }
+}
+
+/// Events an endpoint can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Type, Serialize, Deserialize, TS)]
+#[sqlx(type_name = "webhook_event_type")]
+pub enum WebhookEventType {
+    #[sqlx(rename = "task.created")]
+    #[serde(rename = "task.created")]
+    TaskCreated,
+    #[sqlx(rename = "task.status_changed")]
+    #[serde(rename = "task.status_changed")]
+    TaskStatusChanged,
+    /// A coding agent run finished, failed or was stopped
+    #[sqlx(rename = "attempt.finished")]
+    #[serde(rename = "attempt.finished")]
+    AttemptFinished,
+}
+
+impl WebhookEventType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::TaskCreated => "task.created",
+            Self::TaskStatusChanged => "task.status_changed",
+            Self::AttemptFinished => "attempt.finished",
+        }
+    }
+}
+
+/// An outgoing webhook. Deliveries are queued by database triggers, so every
+/// change is covered no matter which API, bot or background job made it.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct WebhookEndpoint {
+    pub id: Uuid,
+    pub url: String,
+    /// Key requests are signed with; only returned when the endpoint is created
+    #[serde(skip)]
+    pub secret: String,
+    /// Events delivered to this endpoint; empty for all of them
+    #[ts(type = "Array<WebhookEventType>")]
+    pub events: Json<Vec<WebhookEventType>>,
+    pub format: WebhookPayloadFormat,
+    /// Dotted paths of the event data to include; empty for all of it
+    #[ts(type = "Array<string>")]
+    pub fields: Json<Vec<String>>,
+    pub enabled: bool,
+    /// Secret replaced by the last rotation, still signed with until it expires
+    #[serde(skip)]
+    pub previous_secret: Option<String>,
+    /// When requests stop carrying a signature under the previous secret
+    pub previous_secret_expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct CreateWebhookEndpoint {
+    pub url: String,
+    #[serde(default)]
+    pub events: Vec<WebhookEventType>,
+    #[serde(default)]
+    pub format: WebhookPayloadFormat,
+    #[serde(default)]
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct UpdateWebhookEndpoint {
+    pub url: Option<String>,
+    pub events: Option<Vec<WebhookEventType>>,
+    pub format: Option<WebhookPayloadFormat>,
+    pub fields: Option<Vec<String>>,
+    pub enabled: Option<bool>,
+}
+
+impl WebhookEndpoint {
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WebhookEndpoint,
+            r#"SELECT id as "id!: Uuid", url, secret, events as "events!: Json<Vec<WebhookEventType>>", format as "format!: WebhookPayloadFormat", fields as "fields!: Json<Vec<String>>", enabled as "enabled!: bool", previous_secret, previous_secret_expires_at as "previous_secret_expires_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM webhook_endpoints
+               ORDER BY created_at ASC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WebhookEndpoint,
+            r#"SELECT id as "id!: Uuid", url, secret, events as "events!: Json<Vec<WebhookEventType>>", format as "format!: WebhookPayloadFormat", fields as "fields!: Json<Vec<String>>", enabled as "enabled!: bool", previous_secret, previous_secret_expires_at as "previous_secret_expires_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM webhook_endpoints
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        data: &CreateWebhookEndpoint,
+        secret: &str,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let events = Json(&data.events);
+        let fields = Json(&data.fields);
+        sqlx::query_as!(
+            WebhookEndpoint,
+            r#"INSERT INTO webhook_endpoints (id, url, secret, events, format, fields)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               RETURNING id as "id!: Uuid", url, secret, events as "events!: Json<Vec<WebhookEventType>>", format as "format!: WebhookPayloadFormat", fields as "fields!: Json<Vec<String>>", enabled as "enabled!: bool", previous_secret, previous_secret_expires_at as "previous_secret_expires_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            data.url,
+            secret,
+            events,
+            data.format,
+            fields
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn update(
+        pool: &SqlitePool,
+        id: Uuid,
+        data: &UpdateWebhookEndpoint,
+    ) -> Result<Self, sqlx::Error> {
+        let existing = Self::find_by_id(pool, id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        let url = data.url.as_ref().unwrap_or(&existing.url);
+        let events = Json(data.events.as_ref().unwrap_or(&existing.events.0));
+        let format = data.format.unwrap_or(existing.format);
+        let fields = Json(data.fields.as_ref().unwrap_or(&existing.fields.0));
+        let enabled = data.enabled.unwrap_or(existing.enabled);
+
+        sqlx::query_as!(
+            WebhookEndpoint,
+            r#"UPDATE webhook_endpoints
+               SET url = $2, events = $3, format = $4, fields = $5, enabled = $6, updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid", url, secret, events as "events!: Json<Vec<WebhookEventType>>", format as "format!: WebhookPayloadFormat", fields as "fields!: Json<Vec<String>>", enabled as "enabled!: bool", previous_secret, previous_secret_expires_at as "previous_secret_expires_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            url,
+            events,
+            format,
+            fields,
+            enabled
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Replace the endpoint's secret. The old one keeps signing requests
+    /// alongside it for `grace_secs`, so receivers can switch over without
+    /// rejecting any.
+    pub async fn rotate_secret(
+        pool: &SqlitePool,
+        id: Uuid,
+        secret: &str,
+        grace_secs: i64,
+    ) -> Result<Self, sqlx::Error> {
+        let modifier = format!("+{grace_secs} seconds");
+        sqlx::query_as!(
+            WebhookEndpoint,
+            r#"UPDATE webhook_endpoints
+               SET previous_secret = secret, previous_secret_expires_at = datetime('now', 'subsec', $3), secret = $2, updated_at = datetime('now', 'subsec')
+               WHERE id = $1
+               RETURNING id as "id!: Uuid", url, secret, events as "events!: Json<Vec<WebhookEventType>>", format as "format!: WebhookPayloadFormat", fields as "fields!: Json<Vec<String>>", enabled as "enabled!: bool", previous_secret, previous_secret_expires_at as "previous_secret_expires_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            secret,
+            modifier
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// The previous secret, while it is still within its grace period
+    pub fn active_previous_secret(&self) -> Option<&str> {
+        match self.previous_secret_expires_at {
+            Some(expires_at) if expires_at > Utc::now() => self.previous_secret.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM webhook_endpoints WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
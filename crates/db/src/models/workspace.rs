@@ -574,7 +574,7 @@ impl Workspace {
                       AND ep.run_reason IN ('setupscript','cleanupscript','codingagent')
                     ORDER BY ep.created_at DESC
                     LIMIT 1
-                ) IN ('failed','killed') THEN 1 ELSE 0 END AS "is_errored!: i64"
+                ) IN ('failed','killed','stalled') THEN 1 ELSE 0 END AS "is_errored!: i64"
 
             FROM workspaces w
             ORDER BY w.updated_at DESC"#
@@ -675,7 +675,7 @@ impl Workspace {
                       AND ep.run_reason IN ('setupscript','cleanupscript','codingagent')
                     ORDER BY ep.created_at DESC
                     LIMIT 1
-                ) IN ('failed','killed') THEN 1 ELSE 0 END AS "is_errored!: i64"
+                ) IN ('failed','killed','stalled') THEN 1 ELSE 0 END AS "is_errored!: i64"
 
             FROM workspaces w
             WHERE w.id = $1"#,
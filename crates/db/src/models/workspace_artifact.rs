@@ -0,0 +1,131 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A file produced by an attempt, beyond its diff
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct WorkspaceArtifact {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub file_path: String, // relative path within cache/artifacts/
+    pub original_name: String,
+    pub mime_type: Option<String>,
+    pub size_bytes: i64,
+    pub hash: String, // SHA256 hash of the contents
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateWorkspaceArtifact {
+    pub workspace_id: Uuid,
+    pub file_path: String,
+    pub original_name: String,
+    pub mime_type: Option<String>,
+    pub size_bytes: i64,
+    pub hash: String,
+}
+
+impl WorkspaceArtifact {
+    pub async fn create<'e, E>(
+        executor: E,
+        data: &CreateWorkspaceArtifact,
+    ) -> Result<Self, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            WorkspaceArtifact,
+            r#"INSERT INTO workspace_artifacts (id, workspace_id, file_path, original_name, mime_type, size_bytes, hash)
+               VALUES ($1, $2, $3, $4, $5, $6, $7)
+               RETURNING id as "id!: Uuid",
+                         workspace_id as "workspace_id!: Uuid",
+                         file_path as "file_path!",
+                         original_name as "original_name!",
+                         mime_type,
+                         size_bytes as "size_bytes!",
+                         hash as "hash!",
+                         created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            data.workspace_id,
+            data.file_path,
+            data.original_name,
+            data.mime_type,
+            data.size_bytes,
+            data.hash,
+        )
+        .fetch_one(executor)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceArtifact,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      file_path,
+                      original_name,
+                      mime_type,
+                      size_bytes,
+                      hash,
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM workspace_artifacts
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_by_workspace_id(
+        pool: &SqlitePool,
+        workspace_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkspaceArtifact,
+            r#"SELECT id as "id!: Uuid",
+                      workspace_id as "workspace_id!: Uuid",
+                      file_path,
+                      original_name,
+                      mime_type,
+                      size_bytes,
+                      hash,
+                      created_at as "created_at!: DateTime<Utc>"
+               FROM workspace_artifacts
+               WHERE workspace_id = $1
+               ORDER BY created_at ASC"#,
+            workspace_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Total bytes stored for artifacts of all attempts in a project
+    pub async fn total_size_for_project<'e, E>(
+        executor: E,
+        project_id: Uuid,
+    ) -> Result<i64, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query_scalar!(
+            r#"SELECT COALESCE(SUM(a.size_bytes), 0) as "total!: i64"
+               FROM workspace_artifacts a
+               JOIN workspaces w ON w.id = a.workspace_id
+               JOIN tasks t ON t.id = w.task_id
+               WHERE t.project_id = $1"#,
+            project_id
+        )
+        .fetch_one(executor)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!("DELETE FROM workspace_artifacts WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
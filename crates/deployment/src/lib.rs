@@ -18,20 +18,37 @@ use git2::Error as Git2Error;
 use serde_json::Value;
 use services::services::{
     analytics::{AnalyticsContext, AnalyticsService},
+    analytics_export::AnalyticsExportService,
     approvals::Approvals,
+    artifact::{ArtifactError, ArtifactService},
     auth::AuthContext,
-    config::{Config, ConfigError},
+    config::{Config, ConfigError, ConfigMigrationReport},
     container::{ContainerError, ContainerService},
+    digest::DigestService,
+    email::EmailNotifier,
+    escalation::EscalationWorker,
     events::{EventError, EventService},
     file_search::FileSearchCache,
     filesystem::{FilesystemError, FilesystemService},
     filesystem_watcher::FilesystemWatcherError,
+    health::HealthService,
     image::{ImageError, ImageService},
+    outbox::OutboxWorker,
+    peer_sync::PeerSyncService,
     pr_monitor::PrMonitorService,
     project::ProjectService,
     queued_message::QueuedMessageService,
+    quiet_hours::QuietHoursWorker,
     repo::RepoService,
+    sla::SlaMonitorService,
+    slack::SlackService,
+    stall_monitor::StallMonitorService,
     telegram::TelegramService,
+    telegram_queue::TelegramQueueWorker,
+    todo_sync::TodoSyncService,
+    vault_export::VaultExportService,
+    webhooks::WebhookWorker,
+    weekly_digest::WeeklyDigestService,
     worktree_manager::WorktreeError,
 };
 use sqlx::Error as SqlxError;
@@ -64,6 +81,8 @@ pub enum DeploymentError {
     #[error(transparent)]
     Image(#[from] ImageError),
     #[error(transparent)]
+    Artifact(#[from] ArtifactError),
+    #[error(transparent)]
     Filesystem(#[from] FilesystemError),
     #[error(transparent)]
     Worktree(#[from] WorktreeError),
@@ -85,6 +104,9 @@ pub trait Deployment: Clone + Send + Sync + 'static {
 
     fn config(&self) -> &Arc<RwLock<Config>>;
 
+    /// Report of fields touched while loading the config file at startup
+    fn config_migration_report(&self) -> Option<&ConfigMigrationReport>;
+
     fn db(&self) -> &DBService;
 
     fn analytics(&self) -> &Option<AnalyticsService>;
@@ -99,6 +121,8 @@ pub trait Deployment: Clone + Send + Sync + 'static {
 
     fn image(&self) -> &ImageService;
 
+    fn artifacts(&self) -> &ArtifactService;
+
     fn filesystem(&self) -> &FilesystemService;
 
     fn events(&self) -> &EventService;
@@ -111,6 +135,10 @@ pub trait Deployment: Clone + Send + Sync + 'static {
 
     fn telegram_service(&self) -> Option<&TelegramService>;
 
+    fn slack_service(&self) -> Option<&SlackService>;
+
+    fn health(&self) -> &HealthService;
+
     fn auth_context(&self) -> &AuthContext;
 
     async fn update_sentry_scope(&self) -> Result<(), DeploymentError> {
@@ -132,7 +160,89 @@ pub trait Deployment: Clone + Send + Sync + 'static {
                 user_id: self.user_id().to_string(),
                 analytics_service: analytics_service.clone(),
             });
-        PrMonitorService::spawn(db, analytics).await
+        PrMonitorService::spawn(
+            db,
+            analytics,
+            self.telegram_service().cloned(),
+            self.config().clone(),
+        )
+        .await
+    }
+
+    /// Spawn the scheduled Telegram digest job (no-op without a configured bot)
+    async fn spawn_digest_service(&self) -> Option<tokio::task::JoinHandle<()>> {
+        let telegram = self.telegram_service()?.clone();
+        Some(DigestService::spawn(self.db().clone(), telegram).await)
+    }
+
+    /// Spawn the weekly project report sent by email
+    async fn spawn_weekly_digest_service(&self) -> tokio::task::JoinHandle<()> {
+        let email = EmailNotifier::new(
+            self.config().clone(),
+            self.db().pool.clone(),
+            self.git().clone(),
+        );
+        WeeklyDigestService::spawn(self.db().clone(), self.config().clone(), email).await
+    }
+
+    /// Spawn the SLA breach monitor (no-op without a configured bot)
+    async fn spawn_sla_monitor_service(&self) -> Option<tokio::task::JoinHandle<()>> {
+        let telegram = self.telegram_service()?.clone();
+        Some(SlaMonitorService::spawn(self.db().clone(), telegram).await)
+    }
+
+    /// Spawn the sender of queued Telegram messages (no-op without a configured bot)
+    async fn spawn_telegram_queue_worker(&self) -> Option<tokio::task::JoinHandle<()>> {
+        let telegram = self.telegram_service()?.clone();
+        Some(TelegramQueueWorker::spawn(self.db().clone(), telegram).await)
+    }
+
+    /// Spawn the scheduled task exchange with paired instances
+    async fn spawn_peer_sync_service(&self) -> tokio::task::JoinHandle<()> {
+        PeerSyncService::spawn(self.db().clone(), self.config().clone()).await
+    }
+
+    /// Spawn the scheduled import of project TODO files
+    async fn spawn_todo_sync_service(&self) -> tokio::task::JoinHandle<()> {
+        TodoSyncService::spawn(self.db().clone(), self.config().clone()).await
+    }
+
+    /// Spawn the scheduled markdown vault export
+    async fn spawn_vault_export_service(&self) -> tokio::task::JoinHandle<()> {
+        VaultExportService::spawn(self.db().clone(), self.config().clone()).await
+    }
+
+    /// Spawn the scheduled analytics export
+    async fn spawn_analytics_export_service(&self) -> tokio::task::JoinHandle<()> {
+        AnalyticsExportService::spawn(self.db().clone(), self.config().clone()).await
+    }
+
+    /// Spawn the monitor that marks processes without a heartbeat as stalled
+    async fn spawn_stall_monitor_service(&self) -> tokio::task::JoinHandle<()> {
+        StallMonitorService::spawn(self.db().clone(), self.telegram_service().cloned()).await
+    }
+
+    async fn spawn_outbox_worker(&self) -> tokio::task::JoinHandle<()> {
+        OutboxWorker::spawn(
+            self.db().clone(),
+            self.container().notification_dispatcher().clone(),
+        )
+        .await
+    }
+
+    /// Spawn the worker that sends what quiet hours held back once they end
+    async fn spawn_quiet_hours_worker(&self) -> tokio::task::JoinHandle<()> {
+        QuietHoursWorker::spawn(self.container().notification_dispatcher().clone()).await
+    }
+
+    /// Spawn the worker that escalates failures nobody acknowledges
+    async fn spawn_escalation_worker(&self) -> tokio::task::JoinHandle<()> {
+        EscalationWorker::spawn(self.container().notification_dispatcher().clone()).await
+    }
+
+    /// Spawn the worker that sends outgoing webhooks
+    async fn spawn_webhook_worker(&self) -> tokio::task::JoinHandle<()> {
+        WebhookWorker::spawn(self.db().clone()).await
     }
 
     async fn track_if_analytics_allowed(&self, event_name: &str, properties: Value) {
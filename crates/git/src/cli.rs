@@ -107,6 +107,25 @@ impl GitCli {
         Ok(())
     }
 
+    /// Run `git -C <repo> worktree add --detach <path> <rev>`
+    pub fn worktree_add_detached(
+        &self,
+        repo_path: &Path,
+        worktree_path: &Path,
+        rev: &str,
+    ) -> Result<(), GitCliError> {
+        self.ensure_available()?;
+        let args: Vec<OsString> = vec![
+            "worktree".into(),
+            "add".into(),
+            "--detach".into(),
+            worktree_path.as_os_str().into(),
+            OsString::from(rev),
+        ];
+        self.git(repo_path, args)?;
+        Ok(())
+    }
+
     /// Run `git -C <repo> worktree remove <path>`
     pub fn worktree_remove(
         &self,
@@ -1225,6 +1225,19 @@ impl GitService {
         Ok(())
     }
 
+    /// Add a worktree with a detached HEAD at `rev`, leaving no branch behind
+    pub fn add_detached_worktree(
+        &self,
+        repo_path: &Path,
+        worktree_path: &Path,
+        rev: &str,
+    ) -> Result<(), GitServiceError> {
+        let git = GitCli::new();
+        git.worktree_add_detached(repo_path, worktree_path, rev)
+            .map_err(|e| GitServiceError::InvalidRepository(e.to_string()))?;
+        Ok(())
+    }
+
     /// Remove a worktree
     pub fn remove_worktree(
         &self,
@@ -1694,6 +1707,28 @@ impl GitService {
             .map_err(GitServiceError::from)
     }
 
+    /// Update the remote-tracking ref of `branch_name` without touching the
+    /// local branch, returning its name, e.g. `origin/main`
+    pub fn fetch_remote_tracking_branch(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+    ) -> Result<String, GitServiceError> {
+        let remote = self.resolve_remote_for_branch(repo_path, branch_name)?;
+        let branch_name = branch_name
+            .strip_prefix(&format!("{}/", remote.name))
+            .unwrap_or(branch_name);
+        let refspec = format!(
+            "+refs/heads/{branch_name}:refs/remotes/{}/{branch_name}",
+            remote.name
+        );
+        let git_cli = GitCli::new();
+        git_cli
+            .fetch_with_refspec(repo_path, &remote.url, &refspec)
+            .map_err(GitServiceError::from)?;
+        Ok(format!("{}/{branch_name}", remote.name))
+    }
+
     pub fn resolve_remote_for_branch(
         &self,
         repo_path: &Path,
@@ -3,7 +3,7 @@ use std::{
     io,
     path::{Path, PathBuf},
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use anyhow::anyhow;
@@ -42,14 +42,27 @@ use git::GitService;
 use serde_json::json;
 use services::services::{
     analytics::AnalyticsContext,
+    apprise::AppriseNotifier,
     approvals::{Approvals, executor_approvals::ExecutorApprovalBridge},
     config::Config,
     container::{ContainerError, ContainerRef, ContainerService},
     diff_stream::{self, DiffStreamHandle},
+    email::EmailNotifier,
+    gotify::GotifyNotifier,
     image::ImageService,
     notification::NotificationService,
+    notification_dispatcher::NotificationDispatcher,
+    ntfy::NtfyNotifier,
+    preview_capture::PreviewCaptureService,
+    pushover::PushoverNotifier,
     queued_message::QueuedMessageService,
+    rate_limit::ProviderCooldowns,
+    slack::SlackService,
+    stall_monitor::HEARTBEAT_INTERVAL,
     telegram::TelegramService,
+    twilio::TwilioNotifier,
+    web_push::WebPushNotifier,
+    whatsapp::WhatsAppService,
     workspace_manager::{RepoWorkspaceInput, WorkspaceManager},
 };
 use tokio::{sync::RwLock, task::JoinHandle};
@@ -80,7 +93,13 @@ pub struct LocalContainerService {
     approvals: Approvals,
     queued_message_service: QueuedMessageService,
     notification_service: NotificationService,
+    notification_dispatcher: NotificationDispatcher,
+    web_push_notifier: WebPushNotifier,
+    whatsapp_service: WhatsAppService,
     telegram_service: Option<TelegramService>,
+    slack_service: Option<SlackService>,
+    preview_capture: PreviewCaptureService,
+    provider_cooldowns: ProviderCooldowns,
 }
 
 impl LocalContainerService {
@@ -95,12 +114,38 @@ impl LocalContainerService {
         approvals: Approvals,
         queued_message_service: QueuedMessageService,
         telegram_service: Option<TelegramService>,
+        slack_service: Option<SlackService>,
+        preview_capture: PreviewCaptureService,
+        provider_cooldowns: ProviderCooldowns,
     ) -> Self {
         let child_store = Arc::new(RwLock::new(HashMap::new()));
         let cancellation_tokens = Arc::new(RwLock::new(HashMap::new()));
         let db_stream_handles = Arc::new(RwLock::new(HashMap::new()));
         let exit_monitor_handles = Arc::new(RwLock::new(HashMap::new()));
         let notification_service = NotificationService::new(config.clone());
+        let web_push_notifier = WebPushNotifier::new(config.clone(), db.pool.clone());
+        let whatsapp_service = WhatsAppService::new(config.clone(), db.pool.clone());
+        let mut notification_dispatcher =
+            NotificationDispatcher::new(db.pool.clone(), config.clone())
+                .with(notification_service.clone())
+                .with(EmailNotifier::new(
+                    config.clone(),
+                    db.pool.clone(),
+                    git.clone(),
+                ))
+                .with(NtfyNotifier::new(config.clone()))
+                .with(PushoverNotifier::new(config.clone()))
+                .with(GotifyNotifier::new(config.clone()))
+                .with(web_push_notifier.clone())
+                .with(AppriseNotifier::new(config.clone()))
+                .with(TwilioNotifier::new(config.clone()))
+                .with(whatsapp_service.clone());
+        if let Some(telegram) = &telegram_service {
+            notification_dispatcher = notification_dispatcher.with(telegram.clone());
+        }
+        if let Some(slack) = &slack_service {
+            notification_dispatcher = notification_dispatcher.with(slack.clone());
+        }
 
         let container = LocalContainerService {
             db,
@@ -116,7 +161,13 @@ impl LocalContainerService {
             approvals,
             queued_message_service,
             notification_service,
+            notification_dispatcher,
+            web_push_notifier,
+            whatsapp_service,
             telegram_service,
+            slack_service,
+            preview_capture,
+            provider_cooldowns,
         };
 
         container.spawn_workspace_cleanup();
@@ -634,7 +685,9 @@ impl LocalContainerService {
     ) -> tokio::sync::oneshot::Receiver<std::io::Result<std::process::ExitStatus>> {
         let (tx, rx) = tokio::sync::oneshot::channel::<std::io::Result<std::process::ExitStatus>>();
         let child_store = self.child_store.clone();
+        let db = self.db.clone();
         tokio::spawn(async move {
+            let mut last_heartbeat = Instant::now();
             loop {
                 let child_lock = {
                     let map = child_store.read().await;
@@ -653,6 +706,16 @@ impl LocalContainerService {
                             break;
                         }
                     }
+                    drop(child_handler);
+
+                    // The child is still alive, so tell the stall monitor we are too
+                    if last_heartbeat.elapsed() >= HEARTBEAT_INTERVAL {
+                        last_heartbeat = Instant::now();
+                        if let Err(e) = ExecutionProcess::record_heartbeat(&db.pool, exec_id).await
+                        {
+                            tracing::warn!("Failed to record heartbeat for {}: {}", exec_id, e);
+                        }
+                    }
                 } else {
                     let _ = tx.send(Err(io::Error::other(format!(
                         "Child handle missing for {exec_id}"
@@ -924,6 +987,37 @@ impl LocalContainerService {
         )
         .await
     }
+
+    /// Start `follow_up` on the session now when nothing is running in its
+    /// workspace; otherwise queue it after the session's queued message, to be
+    /// sent when the current run finishes. Returns whether it was started.
+    pub async fn start_follow_up_or_queue(
+        &self,
+        session_id: Uuid,
+        follow_up: DraftFollowUpData,
+    ) -> Result<bool, ContainerError> {
+        let latest = ExecutionProcess::find_latest_by_session_and_run_reason(
+            &self.db.pool,
+            session_id,
+            &ExecutionProcessRunReason::CodingAgent,
+        )
+        .await?;
+        if let Some(latest) = latest {
+            let ctx = ExecutionProcess::load_context(&self.db.pool, latest.id).await?;
+            if !ExecutionProcess::has_running_non_dev_server_processes_for_workspace(
+                &self.db.pool,
+                ctx.workspace.id,
+            )
+            .await?
+            {
+                self.start_queued_follow_up(&ctx, &follow_up).await?;
+                return Ok(true);
+            }
+        }
+        self.queued_message_service
+            .append_message(session_id, follow_up);
+        Ok(false)
+    }
 }
 
 fn failure_exit_status() -> std::process::ExitStatus {
@@ -957,10 +1051,34 @@ impl ContainerService for LocalContainerService {
         &self.notification_service
     }
 
+    fn notification_dispatcher(&self) -> &NotificationDispatcher {
+        &self.notification_dispatcher
+    }
+
+    fn web_push_notifier(&self) -> &WebPushNotifier {
+        &self.web_push_notifier
+    }
+
+    fn whatsapp_service(&self) -> &WhatsAppService {
+        &self.whatsapp_service
+    }
+
     fn telegram_service(&self) -> Option<&TelegramService> {
         self.telegram_service.as_ref()
     }
 
+    fn slack_service(&self) -> Option<&SlackService> {
+        self.slack_service.as_ref()
+    }
+
+    fn preview_capture(&self) -> &PreviewCaptureService {
+        &self.preview_capture
+    }
+
+    fn provider_cooldowns(&self) -> &ProviderCooldowns {
+        &self.provider_cooldowns
+    }
+
     async fn store_db_stream_handle(&self, id: Uuid, handle: JoinHandle<()>) {
         self.add_db_stream_handle(id, handle).await;
     }
@@ -8,18 +8,24 @@ use git::GitService;
 use services::services::{
     analytics::{AnalyticsConfig, AnalyticsContext, AnalyticsService, generate_user_id},
     approvals::Approvals,
+    artifact::ArtifactService,
     auth::AuthContext,
-    config::{Config, load_config_from_file, save_config_to_file},
+    config::{Config, ConfigMigrationReport, load_config_with_report, save_config_to_file},
     container::ContainerService,
     events::EventService,
     file_search::FileSearchCache,
     filesystem::FilesystemService,
+    git_host::github::GhCli,
+    health::HealthService,
     image::ImageService,
     oauth_credentials::OAuthCredentials,
+    preview_capture::PreviewCaptureService,
     project::ProjectService,
     queued_message::QueuedMessageService,
+    rate_limit::ProviderCooldowns,
     remote_client::{RemoteClient, RemoteClientError},
     repo::RepoService,
+    slack::{SlackAppCredentials, SlackService},
     telegram::TelegramService,
     worktree_manager::WorktreeManager,
 };
@@ -48,6 +54,7 @@ pub struct LocalDeployment {
     project: ProjectService,
     repo: RepoService,
     image: ImageService,
+    artifacts: ArtifactService,
     filesystem: FilesystemService,
     events: EventService,
     file_search_cache: Arc<FileSearchCache>,
@@ -58,6 +65,9 @@ pub struct LocalDeployment {
     oauth_handoffs: Arc<RwLock<HashMap<Uuid, PendingHandoff>>>,
     pty: PtyService,
     telegram: Option<TelegramService>,
+    slack: Option<SlackService>,
+    health: HealthService,
+    config_migration_report: Option<Arc<ConfigMigrationReport>>,
 }
 
 #[derive(Debug, Clone)]
@@ -69,7 +79,8 @@ struct PendingHandoff {
 #[async_trait]
 impl Deployment for LocalDeployment {
     async fn new() -> Result<Self, DeploymentError> {
-        let mut raw_config = load_config_from_file(&config_path()).await;
+        let (mut raw_config, config_migration_report) =
+            load_config_with_report(&config_path()).await;
 
         let profiles = ExecutorConfigs::get_cached();
         if !raw_config.onboarding_acknowledged
@@ -97,6 +108,7 @@ impl Deployment for LocalDeployment {
             let path = utils::path::expand_tilde(workspace_dir);
             WorktreeManager::set_workspace_dir_override(path);
         }
+        GhCli::set_token(raw_config.github.cli_token.clone());
 
         let config = Arc::new(RwLock::new(raw_config));
         let user_id = generate_user_id();
@@ -122,6 +134,7 @@ impl Deployment for LocalDeployment {
         };
 
         let image = ImageService::new(db.clone().pool)?;
+        let artifacts = ArtifactService::new(db.clone().pool)?;
         {
             let image_service = image.clone();
             tokio::spawn(async move {
@@ -166,12 +179,24 @@ impl Deployment for LocalDeployment {
 
         let oauth_handoffs = Arc::new(RwLock::new(HashMap::new()));
 
+        let provider_cooldowns = ProviderCooldowns::new();
+        let health = HealthService::new(
+            db.pool.clone(),
+            config.clone(),
+            queued_message_service.clone(),
+            provider_cooldowns.clone(),
+        );
+
         // Create Telegram service if bot token is configured
         let telegram = {
             let bot_token = std::env::var("TELEGRAM_BOT_TOKEN").ok();
             let bot_username = std::env::var("TELEGRAM_BOT_USERNAME").ok();
             bot_token.map(|token| {
-                let service = TelegramService::new(Some(token), config.clone(), db.pool.clone());
+                let service = TelegramService::new(Some(token), config.clone(), db.pool.clone())
+                    .with_image_service(image.clone())
+                    .with_queued_message_service(queued_message_service.clone())
+                    .with_git_service(git.clone())
+                    .with_health_service(health.clone());
                 if let Some(username) = bot_username {
                     service.with_bot_username(username)
                 } else {
@@ -180,6 +205,27 @@ impl Deployment for LocalDeployment {
             })
         };
 
+        // Create Slack service if the app's credentials are configured
+        let slack = match (
+            std::env::var("SLACK_CLIENT_ID"),
+            std::env::var("SLACK_CLIENT_SECRET"),
+            std::env::var("SLACK_SIGNING_SECRET"),
+        ) {
+            (Ok(client_id), Ok(client_secret), Ok(signing_secret)) => {
+                let credentials = SlackAppCredentials {
+                    client_id,
+                    client_secret,
+                    signing_secret,
+                };
+                let service = SlackService::new(credentials, config.clone(), db.pool.clone());
+                match std::env::var("SLACK_REDIRECT_URI") {
+                    Ok(redirect_uri) => Some(service.with_redirect_uri(redirect_uri)),
+                    Err(_) => Some(service),
+                }
+            }
+            _ => None,
+        };
+
         // We need to make analytics accessible to the ContainerService
         // TODO: Handle this more gracefully
         let analytics_ctx = analytics.as_ref().map(|s| AnalyticsContext {
@@ -196,6 +242,9 @@ impl Deployment for LocalDeployment {
             approvals.clone(),
             queued_message_service.clone(),
             telegram.clone(),
+            slack.clone(),
+            PreviewCaptureService::new(db.clone().pool, artifacts.clone(), config.clone()),
+            provider_cooldowns,
         )
         .await;
 
@@ -215,6 +264,7 @@ impl Deployment for LocalDeployment {
             project,
             repo,
             image,
+            artifacts,
             filesystem,
             events,
             file_search_cache,
@@ -225,6 +275,9 @@ impl Deployment for LocalDeployment {
             oauth_handoffs,
             pty,
             telegram,
+            slack,
+            health,
+            config_migration_report: config_migration_report.map(Arc::new),
         };
 
         Ok(deployment)
@@ -238,6 +291,10 @@ impl Deployment for LocalDeployment {
         &self.config
     }
 
+    fn config_migration_report(&self) -> Option<&ConfigMigrationReport> {
+        self.config_migration_report.as_deref()
+    }
+
     fn db(&self) -> &DBService {
         &self.db
     }
@@ -266,6 +323,10 @@ impl Deployment for LocalDeployment {
         &self.image
     }
 
+    fn artifacts(&self) -> &ArtifactService {
+        &self.artifacts
+    }
+
     fn filesystem(&self) -> &FilesystemService {
         &self.filesystem
     }
@@ -290,6 +351,14 @@ impl Deployment for LocalDeployment {
         self.telegram.as_ref()
     }
 
+    fn slack_service(&self) -> Option<&SlackService> {
+        self.slack.as_ref()
+    }
+
+    fn health(&self) -> &HealthService {
+        &self.health
+    }
+
     fn auth_context(&self) -> &AuthContext {
         &self.auth_context
     }
@@ -26,12 +26,37 @@ fn generate_types_content() -> String {
         db::models::tag::Tag::decl(),
         db::models::tag::CreateTag::decl(),
         db::models::tag::UpdateTag::decl(),
+        db::models::prompt_snippet::PromptSnippet::decl(),
+        db::models::prompt_snippet::CreatePromptSnippet::decl(),
+        db::models::prompt_snippet::UpdatePromptSnippet::decl(),
+        db::models::eval_run::EvalRun::decl(),
+        db::models::eval_run::EvalVariant::decl(),
+        db::models::eval_run::EvalOutcome::decl(),
+        db::models::eval_run::EvalAttempt::decl(),
         db::models::task::TaskStatus::decl(),
         db::models::task::Task::decl(),
         db::models::task::TaskWithAttemptStatus::decl(),
+        db::models::task::TaskStatusCounts::decl(),
         db::models::task::TaskRelationships::decl(),
         db::models::task::CreateTask::decl(),
         db::models::task::UpdateTask::decl(),
+        db::models::task_status_history::TaskStatusChange::decl(),
+        db::models::ci_failure::CiTestFailure::decl(),
+        db::models::task_status_history::TaskStatusAt::decl(),
+        db::models::sla_policy::SlaPolicy::decl(),
+        db::models::workspace_artifact::WorkspaceArtifact::decl(),
+        db::models::sla_policy::UpsertSlaPolicy::decl(),
+        db::models::project_preview::ProjectPreviewSettings::decl(),
+        db::models::project_preview::UpsertProjectPreviewSettings::decl(),
+        db::models::todo_sync::ProjectTodoSyncSettings::decl(),
+        db::models::todo_sync::UpsertProjectTodoSyncSettings::decl(),
+        db::models::peer_sync::ProjectPeerSyncSettings::decl(),
+        db::models::peer_sync::UpsertProjectPeerSyncSettings::decl(),
+        db::models::project_api_key::ProjectApiKey::decl(),
+        db::models::project_api_key::CreateProjectApiKey::decl(),
+        db::models::telegram_account::TelegramAccountRole::decl(),
+        db::models::telegram_account::TelegramDigestMode::decl(),
+        db::models::telegram_account::TelegramAccount::decl(),
         db::models::scratch::DraftFollowUpData::decl(),
         db::models::scratch::DraftWorkspaceData::decl(),
         db::models::scratch::DraftWorkspaceRepo::decl(),
@@ -94,6 +119,9 @@ fn generate_types_content() -> String {
         server::routes::repo::InitRepoRequest::decl(),
         server::routes::tags::TagSearchParams::decl(),
         server::routes::oauth::TokenResponse::decl(),
+        server::routes::projects::BoardColumn::decl(),
+        server::routes::projects::BoardSnapshot::decl(),
+        server::routes::projects::ProjectApiKeyWithSecret::decl(),
         server::routes::config::UserSystemInfo::decl(),
         server::routes::config::Environment::decl(),
         server::routes::config::McpServerQuery::decl(),
@@ -102,6 +130,8 @@ fn generate_types_content() -> String {
         server::routes::config::CheckEditorAvailabilityQuery::decl(),
         server::routes::config::CheckEditorAvailabilityResponse::decl(),
         server::routes::config::CheckAgentAvailabilityQuery::decl(),
+        server::routes::config::RotateGitHubTokenRequest::decl(),
+        server::routes::config::GitHubTokenRotation::decl(),
         server::routes::oauth::CurrentUserResponse::decl(),
         server::routes::sessions::CreateFollowUpAttempt::decl(),
         server::routes::task_attempts::ChangeTargetBranchRequest::decl(),
@@ -115,6 +145,8 @@ fn generate_types_content() -> String {
         server::routes::task_attempts::OpenEditorRequest::decl(),
         server::routes::task_attempts::OpenEditorResponse::decl(),
         server::routes::tasks::CreateAndStartTaskRequest::decl(),
+        server::routes::tasks::ReorderTasksRequest::decl(),
+        server::routes::maintenance::MaintenanceStatus::decl(),
         server::routes::task_attempts::pr::CreatePrApiRequest::decl(),
         server::routes::images::ImageResponse::decl(),
         server::routes::images::ImageMetadata::decl(),
@@ -153,6 +185,7 @@ fn generate_types_content() -> String {
         services::services::file_search::SearchMode::decl(),
         services::services::config::Config::decl(),
         services::services::config::NotificationConfig::decl(),
+        services::services::config::PushoverConfig::decl(),
         services::services::config::ThemeMode::decl(),
         services::services::config::EditorConfig::decl(),
         services::services::config::EditorType::decl(),
@@ -162,10 +195,113 @@ fn generate_types_content() -> String {
         services::services::config::UiLanguage::decl(),
         services::services::config::ShowcaseState::decl(),
         services::services::config::SendMessageShortcut::decl(),
+        services::services::config::NotificationPreferences::decl(),
+        services::services::config::NotificationEventType::decl(),
+        services::services::config::QuietHours::decl(),
+        services::services::config::EscalationStep::decl(),
+        services::services::config::EscalationPolicy::decl(),
         services::services::config::TelegramConfig::decl(),
+        services::services::config::TelegramProjectBot::decl(),
+        services::services::config::SlackConfig::decl(),
+        services::services::config::SmtpTls::decl(),
+        services::services::config::EmailConfig::decl(),
+        services::services::config::DigestWeekday::decl(),
+        services::services::config::WeeklyDigestConfig::decl(),
+        services::services::config::NtfyConfig::decl(),
+        services::services::config::GotifyConfig::decl(),
+        services::services::config::WebPushConfig::decl(),
+        services::services::config::AppriseConfig::decl(),
+        services::services::config::TwilioConfig::decl(),
+        services::services::config::WhatsAppConfig::decl(),
+        services::services::config::DigestSchedule::decl(),
+        services::services::config::TelegramParseMode::decl(),
+        services::services::config::TtsBackend::decl(),
+        services::services::config::TtsConfig::decl(),
+        services::services::config::TelegramRetryConfig::decl(),
+        services::services::config::StatusTransitionRule::decl(),
+        services::services::telegram::TaskNotificationKind::decl(),
+        services::services::telegram::NotificationPreview::decl(),
+        services::services::telegram::TelegramSetupStepKind::decl(),
+        services::services::telegram::TelegramSetupStepStatus::decl(),
+        services::services::telegram::TelegramUpdateMode::decl(),
+        services::services::telegram::TelegramSetupStep::decl(),
+        services::services::telegram::TelegramSetupState::decl(),
+        services::services::config::VaultExportConfig::decl(),
+        services::services::config::VaultExportSchedule::decl(),
+        services::services::config::PostMergeVerificationConfig::decl(),
+        services::services::config::AnalyticsExportConfig::decl(),
+        services::services::config::AnalyticsExportFormat::decl(),
+        services::services::config::ConfigMigrationReport::decl(),
+        services::services::sla::SlaState::decl(),
+        services::services::sla::SlaViolation::decl(),
+        services::services::todo_sync::TodoSyncReport::decl(),
+        services::services::peer_sync::PeerSyncReport::decl(),
+        services::services::ci_failure::CiFailureReport::decl(),
+        services::services::ci_failure::CiFailureOutcome::decl(),
+        services::services::vault_export::VaultExportReport::decl(),
+        services::services::analytics_export::AnalyticsExportReport::decl(),
+        db::models::webhook_endpoint::WebhookPayloadFormat::decl(),
+        db::models::webhook_endpoint::WebhookEventType::decl(),
+        db::models::webhook_endpoint::WebhookEndpoint::decl(),
+        db::models::webhook_endpoint::CreateWebhookEndpoint::decl(),
+        db::models::webhook_endpoint::UpdateWebhookEndpoint::decl(),
+        db::models::webhook_delivery::WebhookDeliveryStatus::decl(),
+        db::models::webhook_delivery::WebhookDelivery::decl(),
+        server::routes::webhooks::WebhookEndpointWithSecret::decl(),
+        server::routes::webhooks::WebhookDeliveryQuery::decl(),
+        server::routes::webhooks::RotateWebhookSecretRequest::decl(),
+        db::models::web_push_subscription::WebPushSubscription::decl(),
+        db::models::web_push_subscription::WebPushKeys::decl(),
+        db::models::web_push_subscription::CreateWebPushSubscription::decl(),
+        server::routes::web_push::WebPushPublicKey::decl(),
+        server::routes::web_push::DeleteWebPushSubscriptionRequest::decl(),
+        db::models::notification_rule::NotificationChannel::decl(),
+        db::models::notification_rule::NotificationRule::decl(),
+        db::models::notification_rule::CreateNotificationRule::decl(),
+        db::models::notification_rule::UpdateNotificationRule::decl(),
+        db::models::notification::NotificationStatus::decl(),
+        db::models::notification::NotificationPayload::decl(),
+        db::models::notification::Notification::decl(),
+        db::models::notification::NotificationFilter::decl(),
+        server::routes::notifications::NotificationHistoryQuery::decl(),
+        db::models::notification_dead_letter::NotificationDeadLetter::decl(),
+        db::models::notification_dead_letter::DeadLetterFilter::decl(),
+        services::services::notification_dispatcher::RedeliveryReport::decl(),
+        server::routes::notifications::RedeliverRequest::decl(),
+        services::services::project::BatchProjectOperation::decl(),
+        services::services::project::BatchProjectRequest::decl(),
+        services::services::project::BatchProjectResult::decl(),
+        services::services::workload::AssigneeWorkload::decl(),
+        services::services::task_order::TaskPlacement::decl(),
+        services::services::task_order::TaskMove::decl(),
+        services::services::rate_limit::ProviderCooldown::decl(),
+        services::services::health::HealthSnapshot::decl(),
+        services::services::transcript::TranscriptFormat::decl(),
+        services::services::transcript::TranscriptEntryKind::decl(),
+        services::services::transcript::TranscriptEntry::decl(),
+        services::services::transcript::TranscriptTurn::decl(),
+        services::services::transcript::Transcript::decl(),
+        services::services::prompt_library::RenderPromptRequest::decl(),
+        services::services::prompt_library::RenderedPrompt::decl(),
+        server::routes::prompts::PromptSnippetQuery::decl(),
+        services::services::eval::EvalVariantSummary::decl(),
+        services::services::eval::EvalBenchmarkRow::decl(),
+        services::services::eval::EvalReport::decl(),
+        server::routes::evals::EvalBenchmarkTask::decl(),
+        server::routes::evals::CreateEvalRunBody::decl(),
         server::routes::telegram::TelegramLinkInfo::decl(),
+        server::routes::telegram::TelegramLinkFormat::decl(),
+        server::routes::telegram::TelegramLinkQuery::decl(),
         server::routes::telegram::TelegramStatusResponse::decl(),
         server::routes::telegram::UpdateTelegramSettingsRequest::decl(),
+        server::routes::telegram::SetTelegramAccountRoleRequest::decl(),
+        server::routes::telegram::SetTelegramDigestModeRequest::decl(),
+        server::routes::telegram::RotateTelegramTokenRequest::decl(),
+        server::routes::telegram::TelegramTokenRotation::decl(),
+        server::routes::telegram::TelegramPreviewRequest::decl(),
+        server::routes::slack::SlackInstallInfo::decl(),
+        server::routes::slack::SlackStatusResponse::decl(),
+        server::routes::slack::UpdateSlackSettingsRequest::decl(),
         git::GitBranch::decl(),
         services::services::queued_message::QueuedMessage::decl(),
         services::services::queued_message::QueueStatus::decl(),
@@ -0,0 +1,131 @@
+//! Fill a database with a large synthetic dataset for soak testing.
+//!
+//! This writes straight to the database, so it is only available to whoever
+//! runs the instance. Stop the server first, or point `--database` at a copy.
+//!
+//! ```sh
+//! cargo run --bin soak_data -- --yes --projects 100 --tasks 5000 --log-lines 50000
+//! cargo run --bin soak_data -- --yes --remove
+//! ```
+
+use std::{env, path::PathBuf, process, str::FromStr, time::Instant};
+
+use db::DBService;
+use services::services::soak_data::{self, SoakDataSpec};
+
+const USAGE: &str = "Usage: soak_data [--database <path>] [--yes] [--remove]
+                 [--projects <n>] [--tasks <n>] [--attempt-every <n>] [--log-lines <n>]
+
+Generates projects named \"[soak] ...\" with their tasks, attempts and logs.
+Without --database the instance's own database is used, which needs --yes.
+--remove deletes the generated projects again.";
+
+struct Args {
+    database: Option<PathBuf>,
+    confirmed: bool,
+    remove: bool,
+    spec: SoakDataSpec,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        database: None,
+        confirmed: false,
+        remove: false,
+        spec: SoakDataSpec::default(),
+    };
+    let mut iter = env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        let mut value = || iter.next().ok_or_else(|| format!("{arg} needs a value"));
+        match arg.as_str() {
+            "--database" => args.database = Some(PathBuf::from(value()?)),
+            "--yes" => args.confirmed = true,
+            "--remove" => args.remove = true,
+            "--projects" => args.spec.projects = parse_count(&arg, &value()?)?,
+            "--tasks" => args.spec.tasks_per_project = parse_count(&arg, &value()?)?,
+            "--attempt-every" => args.spec.attempt_every = parse_count(&arg, &value()?)?,
+            "--log-lines" => args.spec.log_lines = parse_count(&arg, &value()?)?,
+            "--help" | "-h" => {
+                println!("{USAGE}");
+                process::exit(0);
+            }
+            _ => return Err(format!("Unknown argument '{arg}'")),
+        }
+    }
+    Ok(args)
+}
+
+fn parse_count(arg: &str, value: &str) -> Result<usize, String> {
+    usize::from_str(value).map_err(|_| format!("{arg} expects a number, got '{value}'"))
+}
+
+#[tokio::main]
+async fn main() {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            process::exit(2);
+        }
+    };
+    if args.database.is_none() && !args.confirmed {
+        eprintln!("Refusing to change the instance database without --yes\n\n{USAGE}");
+        process::exit(2);
+    }
+
+    let db = match &args.database {
+        Some(path) => DBService::new_at(path).await,
+        None => DBService::new().await,
+    };
+    let db = match db {
+        Ok(db) => db,
+        Err(e) => {
+            eprintln!("❌ Failed to open the database: {e}");
+            process::exit(1);
+        }
+    };
+
+    if args.remove {
+        match soak_data::remove(&db.pool).await {
+            Ok(removed) => println!("Removed {removed} generated projects"),
+            Err(e) => {
+                eprintln!("❌ Failed to remove generated projects: {e}");
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let spec = args.spec;
+    println!(
+        "Generating {} projects × {} tasks, an attempt every {} tasks, {} log lines per project…",
+        spec.projects, spec.tasks_per_project, spec.attempt_every, spec.log_lines
+    );
+    let started = Instant::now();
+    let result = soak_data::generate(&db.pool, &spec, |report| {
+        println!(
+            "  {}/{} projects, {} tasks, {} attempts, {} log lines ({:.0?})",
+            report.projects,
+            spec.projects,
+            report.tasks,
+            report.attempts,
+            report.log_lines,
+            started.elapsed()
+        );
+    })
+    .await;
+    match result {
+        Ok(report) => println!(
+            "✅ Generated {} projects, {} tasks, {} attempts and {} log lines in {:.1?}",
+            report.projects,
+            report.tasks,
+            report.attempts,
+            report.log_lines,
+            started.elapsed()
+        ),
+        Err(e) => {
+            eprintln!("❌ Failed to generate soak data: {e}");
+            process::exit(1);
+        }
+    }
+}
@@ -15,13 +15,19 @@ use git::GitServiceError;
 use git2::Error as Git2Error;
 use local_deployment::pty::PtyError;
 use services::services::{
+    artifact::ArtifactError,
     config::{ConfigError, EditorOpenError},
     container::ContainerError,
     git_host::GitHostError,
+    i18n::{Locale, ServerMessage},
     image::ImageError,
     project::ProjectServiceError,
     remote_client::RemoteClientError,
     repo::RepoError as RepoServiceError,
+    slack::SlackError,
+    task_order::TaskOrderError,
+    web_push::WebPushError,
+    whatsapp::WhatsAppError,
     worktree_manager::WorktreeError,
 };
 use thiserror::Error;
@@ -60,6 +66,8 @@ pub enum ApiError {
     Config(#[from] ConfigError),
     #[error(transparent)]
     Image(#[from] ImageError),
+    #[error(transparent)]
+    Artifact(#[from] ArtifactError),
     #[error("Multipart error: {0}")]
     Multipart(#[from] MultipartError),
     #[error("IO error: {0}")]
@@ -76,6 +84,10 @@ pub enum ApiError {
     Conflict(String),
     #[error("Forbidden: {0}")]
     Forbidden(String),
+    #[error("Not found: {0}")]
+    NotFound(String),
+    #[error("Service unavailable: {0}")]
+    ServiceUnavailable(String),
     #[error(transparent)]
     CommandBuilder(#[from] CommandBuildError),
     #[error(transparent)]
@@ -124,6 +136,9 @@ impl IntoResponse for ApiError {
             },
             ApiError::GitHost(_) => (StatusCode::INTERNAL_SERVER_ERROR, "GitHostError"),
             ApiError::Deployment(_) => (StatusCode::INTERNAL_SERVER_ERROR, "DeploymentError"),
+            ApiError::Container(ContainerError::ProviderCoolingDown(_)) => {
+                (StatusCode::TOO_MANY_REQUESTS, "ProviderRateLimited")
+            }
             ApiError::Container(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ContainerError"),
             ApiError::Executor(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ExecutorError"),
             ApiError::CommandBuilder(_) => (StatusCode::INTERNAL_SERVER_ERROR, "CommandBuildError"),
@@ -136,6 +151,13 @@ impl IntoResponse for ApiError {
                 ImageError::NotFound => (StatusCode::NOT_FOUND, "ImageNotFound"),
                 _ => (StatusCode::INTERNAL_SERVER_ERROR, "ImageError"),
             },
+            ApiError::Artifact(err) => match err {
+                ArtifactError::TooLarge(_, _) | ArtifactError::QuotaExceeded { .. } => {
+                    (StatusCode::PAYLOAD_TOO_LARGE, "ArtifactTooLarge")
+                }
+                ArtifactError::NotFound => (StatusCode::NOT_FOUND, "ArtifactNotFound"),
+                _ => (StatusCode::INTERNAL_SERVER_ERROR, "ArtifactError"),
+            },
             ApiError::Io(_) => (StatusCode::INTERNAL_SERVER_ERROR, "IoError"),
             ApiError::EditorOpen(err) => match err {
                 EditorOpenError::LaunchFailed { .. } => {
@@ -180,6 +202,10 @@ impl IntoResponse for ApiError {
             ApiError::BadRequest(_) => (StatusCode::BAD_REQUEST, "BadRequest"),
             ApiError::Conflict(_) => (StatusCode::CONFLICT, "ConflictError"),
             ApiError::Forbidden(_) => (StatusCode::FORBIDDEN, "ForbiddenError"),
+            ApiError::NotFound(_) => (StatusCode::NOT_FOUND, "NotFound"),
+            ApiError::ServiceUnavailable(_) => {
+                (StatusCode::SERVICE_UNAVAILABLE, "ServiceUnavailable")
+            }
             ApiError::Pty(err) => match err {
                 PtyError::SessionNotFound(_) => (StatusCode::NOT_FOUND, "PtyError"),
                 PtyError::SessionClosed => (StatusCode::GONE, "PtyError"),
@@ -187,82 +213,77 @@ impl IntoResponse for ApiError {
             },
         };
 
+        let locale = Locale::current();
+        let localized = |message: ServerMessage| message.render(locale);
         let error_message = match &self {
             ApiError::Image(img_err) => match img_err {
-                ImageError::InvalidFormat => "This file type is not supported. Please upload an image file (PNG, JPG, GIF, WebP, or BMP).".to_string(),
-                ImageError::TooLarge(size, max) => format!(
-                    "This image is too large ({:.1} MB). Maximum file size is {:.1} MB.",
-                    *size as f64 / 1_048_576.0,
-                    *max as f64 / 1_048_576.0
-                ),
-                ImageError::NotFound => "Image not found.".to_string(),
-                _ => {
-                    "Failed to process image. Please try again.".to_string()
-                }
+                ImageError::InvalidFormat => localized(ServerMessage::UnsupportedImageType),
+                ImageError::TooLarge(size, max) => localized(ServerMessage::ImageTooLarge {
+                    size_mb: *size as f64 / 1_048_576.0,
+                    max_mb: *max as f64 / 1_048_576.0,
+                }),
+                ImageError::NotFound => localized(ServerMessage::ImageNotFound),
+                _ => localized(ServerMessage::ImageProcessingFailed),
             },
             ApiError::GitService(git_err) => match git_err {
-                git::GitServiceError::MergeConflicts { message, .. } => {
-                    message.clone()
-                }
+                git::GitServiceError::MergeConflicts { message, .. } => message.clone(),
                 git::GitServiceError::RebaseInProgress => {
-                    "A rebase is already in progress. Resolve conflicts or abort the rebase, then retry.".to_string()
+                    localized(ServerMessage::RebaseInProgress)
                 }
                 _ => format!("{}: {}", error_type, self),
             },
-            ApiError::Multipart(_) => "Failed to upload file. Please ensure the file is valid and try again.".to_string(),
+            ApiError::Multipart(_) => localized(ServerMessage::UploadFailed),
             ApiError::RemoteClient(err) => match err {
-                RemoteClientError::Auth => "Unauthorized. Please sign in again.".to_string(),
-                RemoteClientError::Timeout => "Remote service timeout. Please try again.".to_string(),
-                RemoteClientError::Transport(_) => "Remote service unavailable. Please try again.".to_string(),
+                RemoteClientError::Auth => localized(ServerMessage::SignInAgain),
+                RemoteClientError::Timeout => localized(ServerMessage::RemoteTimeout),
+                RemoteClientError::Transport(_) => localized(ServerMessage::RemoteUnavailable),
                 RemoteClientError::Http { body, .. } => {
                     if body.is_empty() {
-                        "Remote service error. Please try again.".to_string()
+                        localized(ServerMessage::RemoteError)
                     } else {
                         body.clone()
                     }
                 }
-                RemoteClientError::Token(_) => {
-                    "Remote service returned an invalid access token. Please sign in again.".to_string()
-                }
-                RemoteClientError::Storage(_) => {
-                    "Failed to persist credentials locally. Please retry.".to_string()
-                }
+                RemoteClientError::Token(_) => localized(ServerMessage::RemoteInvalidToken),
+                RemoteClientError::Storage(_) => localized(ServerMessage::CredentialsNotSaved),
                 RemoteClientError::Api(code) => match code {
                     services::services::remote_client::HandoffErrorCode::NotFound => {
-                        "The requested resource was not found.".to_string()
+                        localized(ServerMessage::RemoteNotFound)
                     }
                     services::services::remote_client::HandoffErrorCode::Expired => {
-                        "The link or token has expired.".to_string()
+                        localized(ServerMessage::RemoteExpired)
                     }
                     services::services::remote_client::HandoffErrorCode::AccessDenied => {
-                        "Access denied.".to_string()
+                        localized(ServerMessage::AccessDenied)
                     }
                     services::services::remote_client::HandoffErrorCode::UnsupportedProvider => {
-                        "Unsupported authentication provider.".to_string()
+                        localized(ServerMessage::UnsupportedAuthProvider)
                     }
                     services::services::remote_client::HandoffErrorCode::InvalidReturnUrl => {
-                        "Invalid return URL.".to_string()
+                        localized(ServerMessage::InvalidReturnUrl)
                     }
                     services::services::remote_client::HandoffErrorCode::InvalidChallenge => {
-                        "Invalid authentication challenge.".to_string()
+                        localized(ServerMessage::InvalidAuthChallenge)
                     }
                     services::services::remote_client::HandoffErrorCode::ProviderError => {
-                        "Authentication provider error. Please try again.".to_string()
+                        localized(ServerMessage::AuthProviderError)
                     }
                     services::services::remote_client::HandoffErrorCode::InternalError => {
-                        "Internal remote service error. Please try again.".to_string()
+                        localized(ServerMessage::RemoteInternalError)
                     }
                     services::services::remote_client::HandoffErrorCode::Other(msg) => {
-                        format!("Authentication error: {}", msg)
+                        localized(ServerMessage::AuthenticationError(msg))
                     }
                 },
-                RemoteClientError::Serde(_) => "Unexpected response from remote service.".to_string(),
-                RemoteClientError::Url(_) => "Remote service URL is invalid.".to_string(),
+                RemoteClientError::Serde(_) => localized(ServerMessage::UnexpectedRemoteResponse),
+                RemoteClientError::Url(_) => localized(ServerMessage::InvalidRemoteUrl),
             },
-            ApiError::Unauthorized => "Unauthorized. Please sign in again.".to_string(),
+            ApiError::Unauthorized => localized(ServerMessage::SignInAgain),
             ApiError::BadRequest(msg) => msg.clone(),
             ApiError::Conflict(msg) => msg.clone(),
             ApiError::Forbidden(msg) => msg.clone(),
+            ApiError::NotFound(msg) => msg.clone(),
+            ApiError::ServiceUnavailable(msg) => msg.clone(),
             _ => format!("{}: {}", error_type, self),
         };
         let response = ApiResponse::<()>::error(&error_message);
@@ -300,6 +321,49 @@ impl From<ProjectServiceError> for ApiError {
             ProjectServiceError::RemoteClient(msg) => {
                 ApiError::BadRequest(format!("Remote client error: {}", msg))
             }
+            e @ ProjectServiceError::ProjectsNotFound(_) => ApiError::BadRequest(e.to_string()),
+        }
+    }
+}
+
+impl From<TaskOrderError> for ApiError {
+    fn from(err: TaskOrderError) -> Self {
+        match err {
+            TaskOrderError::Database(db_err) => ApiError::Database(db_err),
+            e @ (TaskOrderError::TaskNotInProject(_) | TaskOrderError::SelfReference(_)) => {
+                ApiError::BadRequest(e.to_string())
+            }
+        }
+    }
+}
+
+impl From<SlackError> for ApiError {
+    fn from(err: SlackError) -> Self {
+        match err {
+            SlackError::Database(db_err) => ApiError::Database(db_err),
+            SlackError::InvalidSignature => ApiError::Unauthorized,
+            e @ SlackError::Maintenance => ApiError::ServiceUnavailable(e.to_string()),
+            e => ApiError::BadRequest(e.to_string()),
+        }
+    }
+}
+
+impl From<WebPushError> for ApiError {
+    fn from(err: WebPushError) -> Self {
+        match err {
+            WebPushError::Database(db_err) => ApiError::Database(db_err),
+            e => ApiError::BadRequest(e.to_string()),
+        }
+    }
+}
+
+impl From<WhatsAppError> for ApiError {
+    fn from(err: WhatsAppError) -> Self {
+        match err {
+            WhatsAppError::Database(db_err) => ApiError::Database(db_err),
+            WhatsAppError::InvalidSignature => ApiError::Unauthorized,
+            e @ WhatsAppError::InvalidVerifyToken => ApiError::Forbidden(e.to_string()),
+            e => ApiError::BadRequest(e.to_string()),
         }
     }
 }
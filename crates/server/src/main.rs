@@ -1,13 +1,13 @@
 use anyhow::{self, Error as AnyhowError};
 use deployment::{Deployment, DeploymentError};
 use server::{DeploymentImpl, routes};
-use services::services::container::ContainerService;
+use services::services::{config::save_config_to_file, container::ContainerService};
 use sqlx::Error as SqlxError;
 use strip_ansi_escapes::strip;
 use thiserror::Error;
 use tracing_subscriber::{EnvFilter, prelude::*};
 use utils::{
-    assets::asset_dir,
+    assets::{asset_dir, config_path},
     browser::open_browser,
     port_file::write_port_file,
     sentry::{self as sentry_utils, SentrySource, sentry_layer},
@@ -71,6 +71,20 @@ async fn main() -> Result<(), VibeKanbanError> {
         .await
         .map_err(DeploymentError::from)?;
     deployment.spawn_pr_monitor_service().await;
+    deployment.spawn_digest_service().await;
+    deployment.spawn_weekly_digest_service().await;
+    deployment.spawn_sla_monitor_service().await;
+    deployment.spawn_outbox_worker().await;
+    deployment.spawn_quiet_hours_worker().await;
+    deployment.spawn_escalation_worker().await;
+    deployment.spawn_webhook_worker().await;
+    deployment.spawn_telegram_queue_worker().await;
+    deployment.spawn_todo_sync_service().await;
+    deployment.spawn_peer_sync_service().await;
+    deployment.spawn_vault_export_service().await;
+    deployment.spawn_analytics_export_service().await;
+    deployment.spawn_stall_monitor_service().await;
+    routes::evals::spawn_eval_watcher(deployment.clone());
     deployment
         .track_if_analytics_allowed("session_start", serde_json::json!({}))
         .await;
@@ -113,6 +127,25 @@ async fn main() -> Result<(), VibeKanbanError> {
 
     tracing::info!("Server running on http://{host}:{actual_port}");
 
+    // Point the Telegram bots at this server when a public URL is configured
+    if let Some(telegram) = deployment.telegram_service().cloned() {
+        let config = deployment.config().clone();
+        tokio::spawn(async move {
+            match telegram.register_webhooks().await {
+                Ok(true) => {
+                    tracing::info!("Registered Telegram webhooks");
+                    // Keep the webhook secrets generated on first registration
+                    let config = config.read().await.clone();
+                    if let Err(e) = save_config_to_file(&config, &config_path()).await {
+                        tracing::error!("Failed to save Telegram webhook secrets: {}", e);
+                    }
+                }
+                Ok(false) => {}
+                Err(e) => tracing::warn!("Failed to register Telegram webhooks: {}", e),
+            }
+        });
+    }
+
     if !cfg!(debug_assertions) {
         tracing::info!("Opening browser...");
         tokio::spawn(async move {
@@ -177,4 +210,10 @@ pub async fn perform_cleanup_actions(deployment: &DeploymentImpl) {
         .kill_all_running_processes()
         .await
         .expect("Failed to cleanly kill running execution processes");
+
+    if let Some(telegram) = deployment.telegram_service()
+        && let Err(e) = telegram.delete_webhooks().await
+    {
+        tracing::warn!("Failed to delete Telegram webhooks: {}", e);
+    }
 }
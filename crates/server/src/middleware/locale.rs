@@ -0,0 +1,31 @@
+use axum::{
+    extract::{Request, State},
+    http::header,
+    middleware::Next,
+    response::Response,
+};
+use deployment::Deployment;
+use services::services::{config::UiLanguage, i18n::Locale};
+
+use crate::DeploymentImpl;
+
+/// Handle the request with the user's [`Locale`] as [`Locale::current`], so
+/// error messages are rendered in it. The configured UI language wins; on
+/// auto-detect the request's `Accept-Language` is used.
+pub async fn locale_middleware(
+    State(deployment): State<DeploymentImpl>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let language = deployment.config().read().await.language.clone();
+    let locale = match language {
+        UiLanguage::Browser => request
+            .headers()
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(Locale::from_accept_language)
+            .unwrap_or_default(),
+        language => Locale::resolve(&language, None),
+    };
+    locale.scope(next.run(request)).await
+}
@@ -0,0 +1,45 @@
+use axum::{
+    extract::{Request, State},
+    http::Method,
+    middleware::Next,
+    response::Response,
+};
+use deployment::Deployment;
+use services::services::i18n::{Locale, ServerMessage};
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Reject requests that could change anything with 503 while the instance is in
+/// maintenance mode. Reads keep working.
+pub async fn maintenance_mode_middleware(
+    State(deployment): State<DeploymentImpl>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    if !is_read_only_method(request.method()) && deployment.config().read().await.maintenance_mode {
+        return Err(ApiError::ServiceUnavailable(
+            ServerMessage::MaintenanceMode.render(Locale::current()),
+        ));
+    }
+    Ok(next.run(request).await)
+}
+
+fn is_read_only_method(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_safe_methods_pass_in_maintenance_mode() {
+        assert!(is_read_only_method(&Method::GET));
+        assert!(is_read_only_method(&Method::HEAD));
+        assert!(is_read_only_method(&Method::OPTIONS));
+        assert!(!is_read_only_method(&Method::POST));
+        assert!(!is_read_only_method(&Method::PUT));
+        assert!(!is_read_only_method(&Method::PATCH));
+        assert!(!is_read_only_method(&Method::DELETE));
+    }
+}
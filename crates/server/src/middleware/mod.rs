@@ -1,5 +1,13 @@
+pub mod locale;
+pub mod maintenance;
 pub mod model_loaders;
 pub mod origin;
+pub mod project_api_key;
+pub mod webhook_source;
 
+pub use locale::*;
+pub use maintenance::*;
 pub use model_loaders::*;
 pub use origin::*;
+pub use project_api_key::*;
+pub use webhook_source::*;
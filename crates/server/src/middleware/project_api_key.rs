@@ -0,0 +1,94 @@
+use axum::{
+    extract::{Request, State},
+    http::header,
+    middleware::Next,
+    response::Response,
+};
+use db::models::project_api_key::ProjectApiKey;
+use deployment::Deployment;
+use rand::{Rng, distributions::Alphanumeric};
+use sha2::{Digest, Sha256};
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Every project API key starts with this, so leaked keys are easy to spot
+const API_KEY_PREFIX: &str = "vkp_";
+/// Random characters after the prefix
+const API_KEY_SECRET_LEN: usize = 40;
+/// Characters of the key kept in the clear to tell keys apart
+const API_KEY_DISPLAY_LEN: usize = 12;
+
+/// A freshly generated project API key
+pub struct GeneratedApiKey {
+    /// The key itself, shown to the user once
+    pub secret: String,
+    pub prefix: String,
+    pub hash: String,
+}
+
+pub fn generate_api_key() -> GeneratedApiKey {
+    let random: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(API_KEY_SECRET_LEN)
+        .map(char::from)
+        .collect();
+    let secret = format!("{API_KEY_PREFIX}{random}");
+    GeneratedApiKey {
+        prefix: secret[..API_KEY_DISPLAY_LEN].to_string(),
+        hash: hash_api_key(&secret),
+        secret,
+    }
+}
+
+pub fn hash_api_key(secret: &str) -> String {
+    format!("{:x}", Sha256::digest(secret.as_bytes()))
+}
+
+/// Authenticate the request with its `Authorization: Bearer <project API key>`
+/// header and insert the [`ProjectApiKey`] as an extension, for handlers to
+/// scope the request to the key's project.
+pub async fn require_project_api_key(
+    State(deployment): State<DeploymentImpl>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let secret = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .filter(|secret| secret.starts_with(API_KEY_PREFIX))
+        .ok_or(ApiError::Unauthorized)?;
+
+    let pool = &deployment.db().pool;
+    let key = ProjectApiKey::find_by_key_hash(pool, &hash_api_key(secret))
+        .await?
+        .ok_or(ApiError::Unauthorized)?;
+    if let Err(e) = ProjectApiKey::touch_last_used(pool, key.id).await {
+        tracing::warn!("Failed to record use of API key {}: {}", key.id, e);
+    }
+
+    request.extensions_mut().insert(key);
+    Ok(next.run(request).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_keys_are_unique_and_hash_consistently() {
+        let first = generate_api_key();
+        let second = generate_api_key();
+
+        assert_ne!(first.secret, second.secret);
+        assert!(first.secret.starts_with(API_KEY_PREFIX));
+        assert_eq!(
+            first.secret.len(),
+            API_KEY_PREFIX.len() + API_KEY_SECRET_LEN
+        );
+        assert!(first.secret.starts_with(&first.prefix));
+        assert_eq!(first.hash, hash_api_key(&first.secret));
+        assert_ne!(first.hash, second.hash);
+    }
+}
@@ -0,0 +1,106 @@
+use std::net::{IpAddr, SocketAddr};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use deployment::Deployment;
+use ipnetwork::IpNetwork;
+
+use crate::DeploymentImpl;
+
+/// Reject Telegram webhook requests that do not come from
+/// `TelegramConfig::webhook_allowed_cidrs`, unless
+/// `TelegramConfig::verify_webhook_source` is turned off. The webhook routes
+/// skip origin validation; this check and the webhook secret the routes
+/// compare are what keep other senders out.
+pub async fn telegram_webhook_source_middleware(
+    State(deployment): State<DeploymentImpl>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let allowed = {
+        let config = deployment.config().read().await;
+        if !config.telegram.verify_webhook_source {
+            return Ok(next.run(request).await);
+        }
+        parse_cidrs(&config.telegram.webhook_allowed_cidrs)
+    };
+
+    let source = client_ip(peer.ip(), request.headers());
+    if !allowed.iter().any(|network| network.contains(source)) {
+        tracing::warn!("Rejected Telegram webhook request from {}", source);
+        return Err(StatusCode::FORBIDDEN);
+    }
+    Ok(next.run(request).await)
+}
+
+fn parse_cidrs(cidrs: &[String]) -> Vec<IpNetwork> {
+    cidrs
+        .iter()
+        .filter_map(|cidr| match cidr.trim().parse() {
+            Ok(network) => Some(network),
+            Err(e) => {
+                tracing::warn!("Ignoring invalid webhook CIDR '{}': {}", cidr, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// The address the request came from. A reverse proxy or tunnel on this host
+/// is looked through: the address it appended to `X-Forwarded-For` is used.
+/// Forwarded-for headers from anywhere else are ignored, as the sender can set
+/// them to anything.
+fn client_ip(peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+    if !peer.is_loopback() {
+        return peer;
+    }
+    headers
+        .get("X-Forwarded-For")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.rsplit(',').next())
+        .and_then(|last| last.trim().parse().ok())
+        .unwrap_or(peer)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    fn forwarded_for(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Forwarded-For", HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn test_client_ip_trusts_forwarded_for_from_loopback_only() {
+        let telegram: IpAddr = "149.154.167.220".parse().unwrap();
+        let local: IpAddr = "127.0.0.1".parse().unwrap();
+        let remote: IpAddr = "203.0.113.7".parse().unwrap();
+        let headers = forwarded_for("10.0.0.1, 149.154.167.220");
+
+        assert_eq!(client_ip(local, &headers), telegram);
+        assert_eq!(client_ip(remote, &headers), remote);
+        assert_eq!(client_ip(local, &HeaderMap::new()), local);
+    }
+
+    #[test]
+    fn test_parse_cidrs_skips_invalid_entries() {
+        let cidrs = parse_cidrs(&[
+            "149.154.160.0/20".to_string(),
+            "not a network".to_string(),
+            "91.108.4.0/22".to_string(),
+        ]);
+
+        assert_eq!(cidrs.len(), 2);
+        assert!(cidrs[0].contains("149.154.167.220".parse().unwrap()));
+        assert!(!cidrs[1].contains("8.8.8.8".parse().unwrap()));
+    }
+}
@@ -0,0 +1,42 @@
+use axum::{Router, extract::State, response::Json as ResponseJson, routing::post};
+use deployment::Deployment;
+use services::services::analytics_export::{self, AnalyticsExportError, AnalyticsExportReport};
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+fn map_analytics_export_error(error: AnalyticsExportError) -> ApiError {
+    match error {
+        AnalyticsExportError::Database(e) => ApiError::Database(e),
+        AnalyticsExportError::Io(e) => ApiError::Io(e),
+        e @ (AnalyticsExportError::NoDirectory
+        | AnalyticsExportError::DuckDbNotFound(_)
+        | AnalyticsExportError::DuckDb(_)) => ApiError::BadRequest(e.to_string()),
+    }
+}
+
+/// Copy the operational data into the configured analytics export now
+pub async fn run_analytics_export(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<AnalyticsExportReport>>, ApiError> {
+    let report =
+        analytics_export::export_configured_analytics(&deployment.db().pool, deployment.config())
+            .await
+            .map_err(map_analytics_export_error)?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "analytics_export_run",
+            serde_json::json!({
+                "format": report.format,
+                "tables": report.tables,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(report)))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new().route("/analytics-export", post(run_analytics_export))
+}
@@ -0,0 +1,55 @@
+use axum::{
+    Router,
+    extract::State,
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use deployment::Deployment;
+use services::services::anonymized_export::{self, AnonymizedExportError};
+
+use crate::{DeploymentImpl, error::ApiError};
+
+fn map_anonymized_export_error(error: AnonymizedExportError) -> ApiError {
+    match error {
+        AnonymizedExportError::Database(e) => ApiError::Database(e),
+        AnonymizedExportError::Io(e) => ApiError::Io(e),
+    }
+}
+
+/// Download an anonymized copy of the database, to attach to a bug report
+pub async fn download_anonymized_export(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<Response, ApiError> {
+    let (bytes, report) = anonymized_export::anonymized_database_bytes(&deployment.db().pool)
+        .await
+        .map_err(map_anonymized_export_error)?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "anonymized_export_downloaded",
+            serde_json::json!({
+                "tables": report.tables,
+                "values_anonymized": report.values_anonymized,
+                "values_stripped": report.values_stripped,
+            }),
+        )
+        .await;
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "application/vnd.sqlite3"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"vibe-kanban-anonymized.sqlite\"",
+            ),
+        ],
+        bytes,
+    )
+        .into_response())
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new().route("/anonymized-export", get(download_anonymized_export))
+}
@@ -9,7 +9,7 @@ use axum::{
     },
     http,
     response::{IntoResponse, Json as ResponseJson, Response},
-    routing::{get, put},
+    routing::{delete, get, post, put},
 };
 use deployment::{Deployment, DeploymentError};
 use executors::{
@@ -23,11 +23,13 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use services::services::{
     config::{
-        Config, ConfigError, SoundFile,
+        Config, ConfigError, ConfigMigrationReport, SoundFile, apply_config_patch,
         editor::{EditorConfig, EditorType},
         save_config_to_file,
     },
     container::ContainerService,
+    git_host::github::{GhCli, GitHubProvider},
+    rate_limit::ProviderCooldown,
 };
 use tokio::fs;
 use ts_rs::TS;
@@ -39,7 +41,9 @@ use crate::{DeploymentImpl, error::ApiError};
 pub fn router() -> Router<DeploymentImpl> {
     Router::new()
         .route("/info", get(get_user_system_info))
-        .route("/config", put(update_config))
+        .route("/config", put(update_config).patch(patch_config))
+        .route("/config/migration-report", get(get_config_migration_report))
+        .route("/github/rotate-token", post(rotate_github_token))
         .route("/sounds/{sound}", get(get_sound))
         .route("/mcp-config", get(get_mcp_servers).post(update_mcp_servers))
         .route("/profiles", get(get_profiles).put(update_profiles))
@@ -48,6 +52,11 @@ pub fn router() -> Router<DeploymentImpl> {
             get(check_editor_availability),
         )
         .route("/agents/check-availability", get(check_agent_availability))
+        .route("/agents/rate-limits", get(get_agent_rate_limits))
+        .route(
+            "/agents/rate-limits/{executor}",
+            delete(clear_agent_rate_limit),
+        )
         .route(
             "/agents/slash-commands/ws",
             get(stream_agent_slash_commands_ws),
@@ -123,7 +132,7 @@ async fn get_user_system_info(
 
 async fn update_config(
     State(deployment): State<DeploymentImpl>,
-    Json(new_config): Json<Config>,
+    Json(mut new_config): Json<Config>,
 ) -> ResponseJson<ApiResponse<Config>> {
     let config_path = config_path();
 
@@ -136,6 +145,7 @@ async fn update_config(
 
     // Get old config state before updating
     let old_config = deployment.config().read().await.clone();
+    keep_github_cli_token(&old_config, &mut new_config);
 
     match save_config_to_file(&new_config, &config_path).await {
         Ok(_) => {
@@ -152,6 +162,107 @@ async fn update_config(
     }
 }
 
+/// Apply a partial JSON merge patch to the current config.
+///
+/// The config lock is held across validation and save so concurrent writers (e.g. a Telegram
+/// link completing) are not overwritten by a stale full document.
+async fn patch_config(
+    State(deployment): State<DeploymentImpl>,
+    Json(patch): Json<Value>,
+) -> ResponseJson<ApiResponse<Config>> {
+    let mut config = deployment.config().write().await;
+    let old_config = config.clone();
+
+    let mut new_config = match apply_config_patch(&old_config, &patch) {
+        Ok(new_config) => new_config,
+        Err(e) => return ResponseJson(ApiResponse::error(&e.to_string())),
+    };
+    keep_github_cli_token(&old_config, &mut new_config);
+
+    if !git::is_valid_branch_prefix(&new_config.git_branch_prefix) {
+        return ResponseJson(ApiResponse::error(
+            "Invalid git branch prefix. Must be a valid git branch name component without slashes.",
+        ));
+    }
+
+    if let Err(e) = save_config_to_file(&new_config, &config_path()).await {
+        return ResponseJson(ApiResponse::error(&format!("Failed to save config: {}", e)));
+    }
+    *config = new_config.clone();
+    drop(config);
+
+    handle_config_events(&deployment, &old_config, &new_config).await;
+
+    ResponseJson(ApiResponse::success(new_config))
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct RotateGitHubTokenRequest {
+    /// The new personal access token
+    pub token: String,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct GitHubTokenRotation {
+    /// Login of the account the new token belongs to
+    pub username: String,
+}
+
+/// Validate a new GitHub token and swap it in as `github.cli_token`, which `gh` runs with
+/// from its next command on. GitHub keeps the old token valid until it is revoked or expires, so
+/// commands already running finish with it; revoke it once the rotation succeeds.
+async fn rotate_github_token(
+    State(deployment): State<DeploymentImpl>,
+    Json(request): Json<RotateGitHubTokenRequest>,
+) -> Result<ResponseJson<ApiResponse<GitHubTokenRotation>>, ApiError> {
+    let token = request.token.trim().to_string();
+    if token.is_empty() {
+        return Err(ApiError::BadRequest("Token must not be empty".to_string()));
+    }
+    let username = GitHubProvider::new()?
+        .token_login(&token)
+        .await
+        .map_err(|e| ApiError::BadRequest(format!("GitHub rejected the token: {e}")))?;
+
+    let mut config = deployment.config().write().await;
+    if let Some(current) = &config.github.cli_token_login
+        && !current.eq_ignore_ascii_case(&username)
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Token belongs to {username}, not {current}"
+        )));
+    }
+    let mut new_config = config.clone();
+    new_config.github.cli_token = Some(token);
+    new_config.github.cli_token_login = Some(username.clone());
+    save_config_to_file(&new_config, &config_path()).await?;
+    GhCli::set_token(new_config.github.cli_token.clone());
+    *config = new_config;
+    drop(config);
+
+    tracing::info!("Rotated the GitHub token of {}", username);
+    Ok(ResponseJson(ApiResponse::success(GitHubTokenRotation {
+        username,
+    })))
+}
+
+/// `github.cli_token` only changes through `rotate_github_token`; config writes keep the
+/// current one, so saving a copy fetched before a rotation does not undo it.
+fn keep_github_cli_token(old: &Config, new: &mut Config) {
+    new.github.cli_token = old.github.cli_token.clone();
+    new.github.cli_token_login = old.github.cli_token_login.clone();
+}
+
+/// Report of fields added, defaulted or dropped when the config was loaded at startup.
+/// Returns `null` when no config file existed (fresh install).
+async fn get_config_migration_report(
+    State(deployment): State<DeploymentImpl>,
+) -> ResponseJson<ApiResponse<Option<ConfigMigrationReport>>> {
+    ResponseJson(ApiResponse::success(
+        deployment.config_migration_report().cloned(),
+    ))
+}
+
 /// Track config events when fields transition from false → true
 async fn track_config_events(deployment: &DeploymentImpl, old: &Config, new: &Config) {
     let events = [
@@ -497,6 +608,23 @@ async fn check_agent_availability(
     ResponseJson(ApiResponse::success(info))
 }
 
+/// Providers currently paused after a rate-limit error
+async fn get_agent_rate_limits(
+    State(deployment): State<DeploymentImpl>,
+) -> ResponseJson<ApiResponse<Vec<ProviderCooldown>>> {
+    let cooldowns = deployment.container().provider_cooldowns().all_active();
+    ResponseJson(ApiResponse::success(cooldowns))
+}
+
+/// Lift a provider cooldown early, e.g. after upgrading the plan
+async fn clear_agent_rate_limit(
+    State(deployment): State<DeploymentImpl>,
+    Path(executor): Path<BaseCodingAgent>,
+) -> ResponseJson<ApiResponse<bool>> {
+    let cleared = deployment.container().provider_cooldowns().clear(executor);
+    ResponseJson(ApiResponse::success(cleared))
+}
+
 #[derive(Debug, Deserialize)]
 pub struct AgentSlashCommandsStreamQuery {
     executor: BaseCodingAgent,
@@ -0,0 +1,319 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    time::Duration,
+};
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    response::Json as ResponseJson,
+    routing::get,
+};
+use db::models::{
+    eval_run::{CreateEvalRun, EvalAttempt, EvalOutcome, EvalRun, EvalVariant},
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
+    project::Project,
+    task::{CreateTask, Task},
+    workspace::Workspace,
+};
+use deployment::Deployment;
+use executors::profile::ExecutorProfileId;
+use serde::Deserialize;
+use services::services::{
+    container::ContainerService,
+    eval::{self, EvalReport},
+    transcript::{ENTRY_IDLE_TIMEOUT, collect_normalized_entries},
+};
+use sqlx::Error as SqlxError;
+use tokio::task::JoinHandle;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    routes::task_attempts::{WorkspaceRepoInput, create_workspace},
+};
+
+const EVAL_POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Deserialize, TS)]
+pub struct EvalBenchmarkTask {
+    /// Identifies the benchmark across runs, e.g. `fix-login-redirect`
+    pub label: String,
+    pub prompt: String,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateEvalRunBody {
+    pub project_id: Uuid,
+    pub name: String,
+    pub profile_a: ExecutorProfileId,
+    pub profile_b: ExecutorProfileId,
+    /// Shell command run in each worktree after the agent finishes; exit code
+    /// 0 is a pass
+    pub test_command: Option<String>,
+    pub repos: Vec<WorkspaceRepoInput>,
+    pub tasks: Vec<EvalBenchmarkTask>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EvalRunQuery {
+    pub project_id: Uuid,
+}
+
+fn validate(payload: &CreateEvalRunBody) -> Result<(), ApiError> {
+    if payload.repos.is_empty() {
+        return Err(ApiError::BadRequest(
+            "At least one repository is required".to_string(),
+        ));
+    }
+    if payload.tasks.is_empty() {
+        return Err(ApiError::BadRequest(
+            "At least one benchmark task is required".to_string(),
+        ));
+    }
+    let mut labels = HashSet::new();
+    for task in &payload.tasks {
+        if task.label.trim().is_empty() {
+            return Err(ApiError::BadRequest(
+                "Benchmark labels must not be empty".to_string(),
+            ));
+        }
+        if !labels.insert(task.label.trim()) {
+            return Err(ApiError::BadRequest(format!(
+                "Duplicate benchmark label '{}'",
+                task.label.trim()
+            )));
+        }
+    }
+    Ok(())
+}
+
+pub async fn get_eval_runs(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<EvalRunQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<EvalRun>>>, ApiError> {
+    let runs = EvalRun::find_by_project(&deployment.db().pool, query.project_id).await?;
+    Ok(ResponseJson(ApiResponse::success(runs)))
+}
+
+/// Create a task per benchmark and start one attempt on it for each profile
+pub async fn create_eval_run(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateEvalRunBody>,
+) -> Result<ResponseJson<ApiResponse<EvalRun>>, ApiError> {
+    validate(&payload)?;
+    let pool = &deployment.db().pool;
+    Project::find_by_id(pool, payload.project_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+
+    let run = EvalRun::create(
+        pool,
+        &CreateEvalRun {
+            project_id: payload.project_id,
+            name: payload.name.trim().to_string(),
+            profile_a: payload.profile_a,
+            profile_b: payload.profile_b,
+            test_command: payload
+                .test_command
+                .filter(|command| !command.trim().is_empty()),
+        },
+    )
+    .await?;
+
+    for benchmark in &payload.tasks {
+        let label = benchmark.label.trim();
+        let task = Task::create(
+            pool,
+            &CreateTask::from_title_description(
+                run.project_id,
+                format!("[eval: {}] {label}", run.name),
+                Some(benchmark.prompt.clone()),
+            ),
+            Uuid::new_v4(),
+        )
+        .await?;
+
+        for variant in [EvalVariant::A, EvalVariant::B] {
+            let workspace = create_workspace(&deployment, &task, &payload.repos).await?;
+            let attempt =
+                EvalAttempt::create(pool, run.id, label, variant, task.id, workspace.id).await?;
+            if let Err(err) = deployment
+                .container()
+                .start_workspace(&workspace, run.profile(variant).clone())
+                .await
+            {
+                tracing::error!("Failed to start eval attempt {}: {}", attempt.id, err);
+                EvalAttempt::complete(pool, attempt.id, EvalOutcome::Failed, None, None).await?;
+            }
+        }
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "eval_run_created",
+            serde_json::json!({
+                "eval_run_id": run.id.to_string(),
+                "project_id": run.project_id.to_string(),
+                "benchmark_count": payload.tasks.len(),
+                "has_test_command": run.test_command.is_some(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(run)))
+}
+
+/// Side-by-side pass rate, duration and token usage for the two profiles
+pub async fn get_eval_report(
+    Path(id): Path<Uuid>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<EvalReport>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let run = EvalRun::find_by_id(pool, id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let attempts = EvalAttempt::find_by_run(pool, run.id).await?;
+    Ok(ResponseJson(ApiResponse::success(eval::build_report(
+        run, attempts,
+    ))))
+}
+
+/// Directory the agent ran in, where the test command runs too
+fn test_dir(workspace: &Workspace) -> Option<PathBuf> {
+    let root = PathBuf::from(workspace.container_ref.as_ref()?);
+    Some(match &workspace.agent_working_dir {
+        Some(dir) => root.join(dir),
+        None => root,
+    })
+}
+
+async fn judge_attempt(
+    deployment: &DeploymentImpl,
+    run: &EvalRun,
+    attempt: &EvalAttempt,
+) -> Result<(), SqlxError> {
+    let pool = &deployment.db().pool;
+    let workspace = match attempt.workspace_id {
+        Some(workspace_id) => Workspace::find_by_id(pool, workspace_id).await?,
+        None => None,
+    };
+    // The workspace was deleted before the agent finished
+    let Some(workspace) = workspace else {
+        return EvalAttempt::complete(pool, attempt.id, EvalOutcome::Failed, None, None).await;
+    };
+
+    let Some(process) = ExecutionProcess::find_latest_by_workspace_and_run_reason(
+        pool,
+        workspace.id,
+        &ExecutionProcessRunReason::CodingAgent,
+    )
+    .await?
+    else {
+        // The agent only starts once the setup script succeeds
+        let setup = ExecutionProcess::find_latest_by_workspace_and_run_reason(
+            pool,
+            workspace.id,
+            &ExecutionProcessRunReason::SetupScript,
+        )
+        .await?;
+        if setup.is_some_and(|setup| {
+            matches!(
+                setup.status,
+                ExecutionProcessStatus::Failed
+                    | ExecutionProcessStatus::Killed
+                    | ExecutionProcessStatus::Stalled
+            )
+        }) {
+            EvalAttempt::complete(pool, attempt.id, EvalOutcome::Failed, None, None).await?;
+        }
+        return Ok(());
+    };
+    if process.status == ExecutionProcessStatus::Running {
+        return Ok(());
+    }
+
+    let test_passed = match (&run.test_command, test_dir(&workspace)) {
+        (Some(command), Some(dir)) if process.status == ExecutionProcessStatus::Completed => {
+            match eval::run_test_command(&dir, command).await {
+                Ok(passed) => Some(passed),
+                Err(err) => {
+                    tracing::warn!(
+                        "Eval test command failed to run for {}: {}",
+                        attempt.id,
+                        err
+                    );
+                    Some(false)
+                }
+            }
+        }
+        _ => None,
+    };
+    let outcome = eval::judge(&process.status, process.exit_code, test_passed);
+
+    let entries = match deployment
+        .container()
+        .stream_normalized_logs(&process.id)
+        .await
+    {
+        Some(stream) => collect_normalized_entries(stream, ENTRY_IDLE_TIMEOUT).await,
+        None => Vec::new(),
+    };
+    let duration_secs = process
+        .completed_at
+        .map(|completed_at| (completed_at - process.started_at).num_seconds());
+
+    EvalAttempt::complete(
+        pool,
+        attempt.id,
+        outcome,
+        duration_secs,
+        eval::peak_total_tokens(&entries),
+    )
+    .await
+}
+
+async fn judge_pending_attempts(deployment: &DeploymentImpl) -> Result<(), SqlxError> {
+    let pool = &deployment.db().pool;
+    let mut runs: HashMap<Uuid, Option<EvalRun>> = HashMap::new();
+    for attempt in EvalAttempt::find_pending(pool).await? {
+        let run = match runs.get(&attempt.eval_run_id) {
+            Some(run) => run.clone(),
+            None => {
+                let run = EvalRun::find_by_id(pool, attempt.eval_run_id).await?;
+                runs.insert(attempt.eval_run_id, run.clone());
+                run
+            }
+        };
+        if let Some(run) = run {
+            judge_attempt(deployment, &run, &attempt).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Judge eval attempts as their agents finish. Attempts left pending by a
+/// restart are picked up again on the next tick.
+pub fn spawn_eval_watcher(deployment: DeploymentImpl) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(EVAL_POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(err) = judge_pending_attempts(&deployment).await {
+                tracing::error!("Error judging eval attempts: {}", err);
+            }
+        }
+    })
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    let inner = Router::new()
+        .route("/", get(get_eval_runs).post(create_eval_run))
+        .route("/{id}/report", get(get_eval_report));
+
+    Router::new().nest("/evals", inner)
+}
@@ -1,6 +1,18 @@
-use axum::response::Json;
+use axum::{extract::State, response::Json};
+use deployment::Deployment;
+use services::services::health::HealthSnapshot;
 use utils::response::ApiResponse;
 
+use crate::{DeploymentImpl, error::ApiError};
+
 pub async fn health_check() -> Json<ApiResponse<String>> {
     Json(ApiResponse::success("OK".to_string()))
 }
+
+/// Uptime, running attempts, queue depth and executor availability
+pub async fn health_snapshot(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<Json<ApiResponse<HealthSnapshot>>, ApiError> {
+    let snapshot = deployment.health().snapshot().await?;
+    Ok(Json(ApiResponse::success(snapshot)))
+}
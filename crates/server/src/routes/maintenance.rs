@@ -0,0 +1,57 @@
+//! Maintenance mode switch.
+//!
+//! Endpoints:
+//! - GET /api/maintenance - Whether maintenance mode is on
+//! - PUT /api/maintenance - Turn maintenance mode on or off
+//!
+//! These routes sit outside the maintenance middleware, so the switch can be
+//! turned off again while every other change is rejected.
+
+use axum::{Json, Router, extract::State, response::Json as ResponseJson, routing::get};
+use deployment::Deployment;
+use serde::{Deserialize, Serialize};
+use services::services::config::save_config_to_file;
+use ts_rs::TS;
+use utils::{assets::config_path, response::ApiResponse};
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct MaintenanceStatus {
+    pub enabled: bool,
+}
+
+/// GET /api/maintenance
+async fn get_maintenance(
+    State(deployment): State<DeploymentImpl>,
+) -> ResponseJson<ApiResponse<MaintenanceStatus>> {
+    let enabled = deployment.config().read().await.maintenance_mode;
+    ResponseJson(ApiResponse::success(MaintenanceStatus { enabled }))
+}
+
+/// PUT /api/maintenance
+///
+/// The setting is saved with the config, so it survives restarts during a
+/// migration.
+async fn set_maintenance(
+    State(deployment): State<DeploymentImpl>,
+    Json(request): Json<MaintenanceStatus>,
+) -> Result<ResponseJson<ApiResponse<MaintenanceStatus>>, ApiError> {
+    let mut config = deployment.config().write().await;
+    let mut new_config = config.clone();
+    new_config.maintenance_mode = request.enabled;
+    save_config_to_file(&new_config, &config_path()).await?;
+    *config = new_config;
+    drop(config);
+
+    tracing::info!(
+        "Maintenance mode turned {}",
+        if request.enabled { "on" } else { "off" }
+    );
+    Ok(ResponseJson(ApiResponse::success(request)))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new().route("/maintenance", get(get_maintenance).put(set_maintenance))
+}
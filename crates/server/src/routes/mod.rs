@@ -1,42 +1,72 @@
+use std::net::SocketAddr;
+
 use axum::{
-    Router,
-    routing::{IntoMakeService, get},
+    Router, extract::connect_info::IntoMakeServiceWithConnectInfo, middleware::from_fn_with_state,
+    routing::get,
 };
 use tower_http::validate_request::ValidateRequestHeaderLayer;
 
 use crate::{DeploymentImpl, middleware};
 
+pub mod analytics_export;
+pub mod anonymized_export;
 pub mod approvals;
 pub mod config;
 pub mod containers;
 pub mod filesystem;
 // pub mod github;
+pub mod evals;
 pub mod events;
 pub mod execution_processes;
 pub mod frontend;
 pub mod health;
 pub mod images;
+pub mod maintenance;
+pub mod notification_preferences;
+pub mod notification_rules;
+pub mod notifications;
 pub mod oauth;
 pub mod organizations;
+pub mod peer_sync;
+pub mod project_api;
 pub mod projects;
+pub mod prompts;
 pub mod repo;
 pub mod scratch;
 pub mod sessions;
+pub mod slack;
 pub mod tags;
 pub mod task_attempts;
 pub mod tasks;
 pub mod telegram;
 pub mod terminal;
+pub mod undo;
+pub mod vault_export;
+pub mod web_push;
+pub mod webhooks;
+pub mod whatsapp;
 
-pub fn router(deployment: DeploymentImpl) -> IntoMakeService<Router> {
-    // Routes that bypass origin validation (for external webhooks)
+pub fn router(deployment: DeploymentImpl) -> IntoMakeServiceWithConnectInfo<Router, SocketAddr> {
+    // Routes that bypass origin validation (for external webhooks and
+    // automation authenticated with project API keys). The Telegram, Slack
+    // and WhatsApp endpoints stay open in maintenance mode so the bots can
+    // explain why they are idle.
     let webhook_routes = Router::new()
-        .merge(telegram::webhook_router())
+        .merge(peer_sync::webhook_router())
+        .merge(project_api::router(&deployment))
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            middleware::maintenance_mode_middleware,
+        ))
+        .merge(telegram::webhook_router(&deployment))
+        .merge(slack::webhook_router())
+        .merge(whatsapp::webhook_router())
         .with_state(deployment.clone());
 
     // Create routers with origin validation
     let base_routes = Router::new()
         .route("/health", get(health::health_check))
+        .route("/health/snapshot", get(health::health_snapshot))
         .merge(config::router())
         .merge(containers::router(&deployment))
         .merge(projects::router(&deployment))
@@ -44,6 +74,8 @@ pub fn router(deployment: DeploymentImpl) -> IntoMakeService<Router> {
         .merge(task_attempts::router(&deployment))
         .merge(execution_processes::router(&deployment))
         .merge(tags::router(&deployment))
+        .merge(prompts::router())
+        .merge(evals::router())
         .merge(oauth::router())
         .merge(organizations::router())
         .merge(filesystem::router())
@@ -54,10 +86,29 @@ pub fn router(deployment: DeploymentImpl) -> IntoMakeService<Router> {
         .merge(sessions::router(&deployment))
         .merge(terminal::router())
         .merge(telegram::router(&deployment))
+        .merge(slack::router())
+        .merge(vault_export::router())
+        .merge(analytics_export::router())
+        .merge(anonymized_export::router())
+        .merge(undo::router())
+        .merge(webhooks::router())
+        .merge(web_push::router())
+        .merge(notification_rules::router())
+        .merge(notification_preferences::router())
+        .merge(notifications::router())
         .nest("/images", images::routes())
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            middleware::maintenance_mode_middleware,
+        ))
+        .merge(maintenance::router())
         .layer(ValidateRequestHeaderLayer::custom(
             middleware::validate_origin,
         ))
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            middleware::locale_middleware,
+        ))
         .with_state(deployment);
 
     Router::new()
@@ -67,5 +118,6 @@ pub fn router(deployment: DeploymentImpl) -> IntoMakeService<Router> {
         .nest("/api", webhook_routes)
         // Then base routes (with origin validation)
         .nest("/api", base_routes)
-        .into_make_service()
+        // Peer addresses are needed to check where webhook requests come from
+        .into_make_service_with_connect_info::<SocketAddr>()
 }
@@ -0,0 +1,47 @@
+//! Which channels each kind of task event is sent through.
+//!
+//! Notification rules can narrow an event's channels further, and a channel
+//! also has to be enabled and set up in its own settings.
+//!
+//! Endpoints:
+//! - GET /api/notification-preferences - Get the preference matrix
+//! - PUT /api/notification-preferences - Replace the preference matrix
+
+use axum::{Json, Router, extract::State, response::Json as ResponseJson, routing::get};
+use deployment::Deployment;
+use services::services::config::{NotificationPreferences, save_config_to_file};
+use utils::{assets::config_path, response::ApiResponse};
+
+use crate::{DeploymentImpl, error::ApiError};
+
+pub async fn get_preferences(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<NotificationPreferences>>, ApiError> {
+    let preferences = deployment
+        .config()
+        .read()
+        .await
+        .notification_preferences
+        .clone();
+    Ok(ResponseJson(ApiResponse::success(preferences)))
+}
+
+pub async fn update_preferences(
+    State(deployment): State<DeploymentImpl>,
+    Json(preferences): Json<NotificationPreferences>,
+) -> Result<ResponseJson<ApiResponse<NotificationPreferences>>, ApiError> {
+    let config = {
+        let mut config = deployment.config().write().await;
+        config.notification_preferences = preferences.clone();
+        config.clone()
+    };
+    save_config_to_file(&config, &config_path()).await?;
+    Ok(ResponseJson(ApiResponse::success(preferences)))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new().route(
+        "/notification-preferences",
+        get(get_preferences).put(update_preferences),
+    )
+}
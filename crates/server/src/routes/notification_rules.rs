@@ -0,0 +1,120 @@
+//! Rules routing task notifications to channels.
+//!
+//! While any rule is enabled, an event only goes to the channels of the rules
+//! it matches; with none, every channel follows its own settings.
+//!
+//! Endpoints:
+//! - GET /api/notification-rules - List rules
+//! - POST /api/notification-rules - Add a rule
+//! - PUT /api/notification-rules/{id} - Update a rule
+//! - DELETE /api/notification-rules/{id} - Remove a rule
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    response::Json as ResponseJson,
+    routing::{get, put},
+};
+use db::models::{
+    notification_rule::{
+        CreateNotificationRule, NotificationChannel, NotificationRule, UpdateNotificationRule,
+    },
+    project::Project,
+};
+use deployment::Deployment;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+fn validate_name(name: &str) -> Result<(), ApiError> {
+    if name.trim().is_empty() {
+        return Err(ApiError::BadRequest(
+            "Rule name cannot be empty".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// A rule without channels would silence every event it matches while
+/// looking like it routes them somewhere
+fn validate_channels(channels: &[NotificationChannel]) -> Result<(), ApiError> {
+    if channels.is_empty() {
+        return Err(ApiError::BadRequest(
+            "A rule needs at least one channel".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+async fn validate_project(
+    deployment: &DeploymentImpl,
+    project_id: Option<Uuid>,
+) -> Result<(), ApiError> {
+    if let Some(project_id) = project_id
+        && Project::find_by_id(&deployment.db().pool, project_id)
+            .await?
+            .is_none()
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Project {project_id} does not exist"
+        )));
+    }
+    Ok(())
+}
+
+pub async fn get_rules(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<NotificationRule>>>, ApiError> {
+    let rules = NotificationRule::find_all(&deployment.db().pool).await?;
+    Ok(ResponseJson(ApiResponse::success(rules)))
+}
+
+pub async fn create_rule(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateNotificationRule>,
+) -> Result<ResponseJson<ApiResponse<NotificationRule>>, ApiError> {
+    validate_name(&payload.name)?;
+    validate_channels(&payload.channels)?;
+    validate_project(&deployment, payload.project_id).await?;
+    let rule = NotificationRule::create(&deployment.db().pool, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(rule)))
+}
+
+pub async fn update_rule(
+    Path(id): Path<Uuid>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpdateNotificationRule>,
+) -> Result<ResponseJson<ApiResponse<NotificationRule>>, ApiError> {
+    if let Some(name) = &payload.name {
+        validate_name(name)?;
+    }
+    if let Some(channels) = &payload.channels {
+        validate_channels(channels)?;
+    }
+    if let Some(project_id) = payload.project_id {
+        validate_project(&deployment, project_id).await?;
+    }
+    let rule = NotificationRule::update(&deployment.db().pool, id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(rule)))
+}
+
+pub async fn delete_rule(
+    Path(id): Path<Uuid>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let rows_affected = NotificationRule::delete(&deployment.db().pool, id).await?;
+    if rows_affected == 0 {
+        Err(ApiError::Database(sqlx::Error::RowNotFound))
+    } else {
+        Ok(ResponseJson(ApiResponse::success(())))
+    }
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    let inner = Router::new()
+        .route("/", get(get_rules).post(create_rule))
+        .route("/{id}", put(update_rule).delete(delete_rule));
+
+    Router::new().nest("/notification-rules", inner)
+}
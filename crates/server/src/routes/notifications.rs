@@ -0,0 +1,163 @@
+//! History of the task notifications sent through each channel.
+//!
+//! Endpoints:
+//! - GET /api/notifications - Recent notifications, newest first, filtered by
+//!   project, task, channel, event type, status and whether they were read
+//!   or acknowledged
+//! - POST /api/notifications/{id}/resend - Send a failed notification again
+//! - POST /api/notifications/{id}/read - Mark a notification as read
+//! - POST /api/notifications/{id}/acknowledge - Mark a notification as seen
+//!   to, which stops the escalation of its failure
+//! - GET /api/notifications/dead-letters - Notifications that could not be
+//!   delivered even after retrying, newest first, filtered by project and
+//!   channel
+//! - DELETE /api/notifications/dead-letters/{id} - Discard a dead letter
+//! - POST /api/notifications/redeliver - Send the given dead letters, or all
+//!   of them, again
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    response::Json as ResponseJson,
+    routing::{delete, get, post},
+};
+use db::models::{
+    notification::{Notification, NotificationFilter, NotificationStatus},
+    notification_dead_letter::{DeadLetterFilter, NotificationDeadLetter},
+};
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::{
+    container::ContainerService,
+    notification_dispatcher::{RedeliveryReport, ResendError},
+};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 500;
+
+#[derive(Debug, Deserialize, TS)]
+pub struct NotificationHistoryQuery {
+    /// Most notifications to return, 50 by default
+    #[serde(default)]
+    pub limit: Option<i64>,
+}
+
+pub async fn get_notifications(
+    State(deployment): State<DeploymentImpl>,
+    Query(filter): Query<NotificationFilter>,
+    Query(query): Query<NotificationHistoryQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<Notification>>>, ApiError> {
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let notifications = Notification::find_filtered(&deployment.db().pool, &filter, limit).await?;
+    Ok(ResponseJson(ApiResponse::success(notifications)))
+}
+
+/// Send a failed notification again through its channel, e.g. after fixing
+/// the channel's settings. The entry is updated with the outcome.
+pub async fn resend_notification(
+    Path(id): Path<Uuid>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Notification>>, ApiError> {
+    let notification = Notification::find_by_id(&deployment.db().pool, id)
+        .await?
+        .ok_or(ApiError::Database(sqlx::Error::RowNotFound))?;
+    if notification.status != NotificationStatus::Failed {
+        return Err(ApiError::BadRequest(
+            "Only failed notifications can be sent again".to_string(),
+        ));
+    }
+    let notification = deployment
+        .container()
+        .notification_dispatcher()
+        .resend(&notification)
+        .await
+        .map_err(|e| match e {
+            ResendError::Database(e) => ApiError::Database(e),
+            e => ApiError::BadRequest(e.to_string()),
+        })?;
+    Ok(ResponseJson(ApiResponse::success(notification)))
+}
+
+pub async fn mark_notification_read(
+    Path(id): Path<Uuid>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Notification>>, ApiError> {
+    let notification = Notification::mark_read(&deployment.db().pool, id)
+        .await?
+        .ok_or(ApiError::Database(sqlx::Error::RowNotFound))?;
+    Ok(ResponseJson(ApiResponse::success(notification)))
+}
+
+pub async fn acknowledge_notification(
+    Path(id): Path<Uuid>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Notification>>, ApiError> {
+    let notification = Notification::acknowledge(&deployment.db().pool, id)
+        .await?
+        .ok_or(ApiError::Database(sqlx::Error::RowNotFound))?;
+    Ok(ResponseJson(ApiResponse::success(notification)))
+}
+
+#[derive(Debug, Default, Deserialize, TS)]
+pub struct RedeliverRequest {
+    /// Dead letters to send again; all of them when unset
+    #[serde(default)]
+    pub ids: Option<Vec<Uuid>>,
+}
+
+pub async fn get_dead_letters(
+    State(deployment): State<DeploymentImpl>,
+    Query(filter): Query<DeadLetterFilter>,
+    Query(query): Query<NotificationHistoryQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<NotificationDeadLetter>>>, ApiError> {
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let dead_letters =
+        NotificationDeadLetter::find_filtered(&deployment.db().pool, &filter, limit).await?;
+    Ok(ResponseJson(ApiResponse::success(dead_letters)))
+}
+
+/// Give up on a dead letter without sending it. Its notification stays in
+/// the history as failed.
+pub async fn delete_dead_letter(
+    Path(id): Path<Uuid>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let rows_affected = NotificationDeadLetter::delete(&deployment.db().pool, id).await?;
+    if rows_affected == 0 {
+        Err(ApiError::Database(sqlx::Error::RowNotFound))
+    } else {
+        Ok(ResponseJson(ApiResponse::success(())))
+    }
+}
+
+/// Send dead letters again through their channels. Delivered ones leave the
+/// queue; the rest stay with the new error.
+pub async fn redeliver_dead_letters(
+    State(deployment): State<DeploymentImpl>,
+    Json(request): Json<RedeliverRequest>,
+) -> Result<ResponseJson<ApiResponse<RedeliveryReport>>, ApiError> {
+    let report = deployment
+        .container()
+        .notification_dispatcher()
+        .redeliver(request.ids.as_deref())
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(report)))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    let inner = Router::new()
+        .route("/", get(get_notifications))
+        .route("/{id}/resend", post(resend_notification))
+        .route("/{id}/read", post(mark_notification_read))
+        .route("/{id}/acknowledge", post(acknowledge_notification))
+        .route("/dead-letters", get(get_dead_letters))
+        .route("/dead-letters/{id}", delete(delete_dead_letter))
+        .route("/redeliver", post(redeliver_dead_letters));
+
+    Router::new().nest("/notifications", inner)
+}
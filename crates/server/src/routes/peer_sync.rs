@@ -0,0 +1,70 @@
+//! Peer side of cross-instance project sync.
+//!
+//! Endpoints:
+//! - POST /api/peer-sync/projects/{id} - Apply a peer's tasks and answer with ours (bypasses origin validation)
+
+use axum::{
+    Router,
+    extract::{Json, Path, State},
+    http::{HeaderMap, header},
+    response::Json as ResponseJson,
+    routing::post,
+};
+use db::models::peer_sync::ProjectPeerSyncSettings;
+use deployment::Deployment;
+use services::services::peer_sync::{self, PeerSyncPayload};
+use subtle::ConstantTimeEq;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+pub fn webhook_router() -> Router<DeploymentImpl> {
+    Router::new().route("/peer-sync/projects/{id}", post(receive_sync))
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+/// POST /api/peer-sync/projects/{id}
+///
+/// Called by the paired instance with its copy of the project's tasks. The
+/// project must be paired here too, with the same token.
+async fn receive_sync(
+    State(deployment): State<DeploymentImpl>,
+    Path(project_id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(payload): Json<PeerSyncPayload>,
+) -> Result<ResponseJson<ApiResponse<PeerSyncPayload>>, ApiError> {
+    let pool = &deployment.db().pool;
+    // Unpaired projects look the same as a wrong token to the caller
+    let settings = ProjectPeerSyncSettings::find_by_project_id(pool, project_id)
+        .await?
+        .ok_or(ApiError::Unauthorized)?;
+    let authorized = bearer_token(&headers)
+        .is_some_and(|token| bool::from(token.as_bytes().ct_eq(settings.token.as_bytes())));
+    if !authorized {
+        return Err(ApiError::Unauthorized);
+    }
+
+    let applied = peer_sync::apply(pool, project_id, payload.tasks).await?;
+    if applied > 0 {
+        tracing::info!(
+            "Applied {} tasks from peer {} to project {}",
+            applied,
+            settings.peer_url,
+            project_id
+        );
+    }
+    ProjectPeerSyncSettings::mark_synced(pool, project_id).await?;
+
+    let tasks = peer_sync::snapshot(pool, project_id).await?;
+    Ok(ResponseJson(ApiResponse::success(PeerSyncPayload {
+        tasks,
+    })))
+}
@@ -0,0 +1,210 @@
+//! Project-scoped API for automation such as a repo's CI filing tasks on
+//! failure. Requests authenticate with a project API key
+//! (`Authorization: Bearer vkp_...`) and can only see and change the tasks of
+//! the key's project.
+//!
+//! Endpoints:
+//! - GET /api/project-api/tasks - List the project's tasks
+//! - POST /api/project-api/tasks - Create a task in the project
+//! - GET /api/project-api/tasks/{task_id} - Get one of the project's tasks
+//! - PUT /api/project-api/tasks/{task_id} - Update one of the project's tasks
+//! - POST /api/project-api/ci-failures - Report a failed CI run, filing or updating its task
+
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    middleware::from_fn_with_state,
+    response::Json as ResponseJson,
+    routing::{get, post},
+};
+use db::models::{
+    project_api_key::ProjectApiKey,
+    project_repo::ProjectRepo,
+    task::{CreateTask, Task, TaskWithAttemptStatus, UpdateTask},
+    workspace::Workspace,
+};
+use deployment::Deployment;
+use services::services::{
+    ci_failure::{self, CiFailureOutcome, CiFailureReport},
+    container::ContainerService,
+};
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    middleware::require_project_api_key,
+    routes::{
+        task_attempts::{WorkspaceRepoInput, create_workspace},
+        tasks::{self, TaskQuery},
+    },
+};
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let inner = Router::new()
+        .route("/tasks", get(get_tasks).post(create_task))
+        .route("/tasks/{task_id}", get(get_task).put(update_task))
+        .route("/ci-failures", post(report_ci_failure))
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            require_project_api_key,
+        ));
+
+    Router::new().nest("/project-api", inner)
+}
+
+/// The task, if it belongs to the key's project
+async fn find_project_task(
+    deployment: &DeploymentImpl,
+    key: &ProjectApiKey,
+    task_id: Uuid,
+) -> Result<Task, ApiError> {
+    let task = Task::find_by_id(&deployment.db().pool, task_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Task not found".to_string()))?;
+    if task.project_id != key.project_id {
+        return Err(ApiError::Forbidden(
+            "This API key cannot access other projects".to_string(),
+        ));
+    }
+    Ok(task)
+}
+
+/// Reject a parent workspace outside the key's project
+async fn check_parent_workspace(
+    deployment: &DeploymentImpl,
+    key: &ProjectApiKey,
+    parent_workspace_id: Option<Uuid>,
+) -> Result<(), ApiError> {
+    let Some(workspace_id) = parent_workspace_id else {
+        return Ok(());
+    };
+    let pool = &deployment.db().pool;
+    let workspace = Workspace::find_by_id(pool, workspace_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Parent workspace not found".to_string()))?;
+    let parent = workspace
+        .parent_task(pool)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Parent workspace not found".to_string()))?;
+    if parent.project_id != key.project_id {
+        return Err(ApiError::Forbidden(
+            "This API key cannot access other projects".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+async fn get_tasks(
+    State(deployment): State<DeploymentImpl>,
+    Extension(key): Extension<ProjectApiKey>,
+) -> Result<ResponseJson<ApiResponse<Vec<TaskWithAttemptStatus>>>, ApiError> {
+    tasks::get_tasks(
+        State(deployment),
+        Query(TaskQuery {
+            project_id: key.project_id,
+        }),
+    )
+    .await
+}
+
+async fn create_task(
+    State(deployment): State<DeploymentImpl>,
+    Extension(key): Extension<ProjectApiKey>,
+    Json(payload): Json<CreateTask>,
+) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
+    if payload.project_id != key.project_id {
+        return Err(ApiError::Forbidden(
+            "This API key cannot access other projects".to_string(),
+        ));
+    }
+    check_parent_workspace(&deployment, &key, payload.parent_workspace_id).await?;
+    tasks::create_task(State(deployment), Json(payload)).await
+}
+
+async fn get_task(
+    State(deployment): State<DeploymentImpl>,
+    Extension(key): Extension<ProjectApiKey>,
+    Path(task_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
+    let task = find_project_task(&deployment, &key, task_id).await?;
+    Ok(ResponseJson(ApiResponse::success(task)))
+}
+
+async fn update_task(
+    State(deployment): State<DeploymentImpl>,
+    Extension(key): Extension<ProjectApiKey>,
+    Path(task_id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(payload): Json<UpdateTask>,
+) -> Result<(StatusCode, ResponseJson<ApiResponse<Task>>), ApiError> {
+    let task = find_project_task(&deployment, &key, task_id).await?;
+    check_parent_workspace(&deployment, &key, payload.parent_workspace_id).await?;
+    tasks::update_task(Extension(task), State(deployment), headers, Json(payload)).await
+}
+
+/// File a task for a failed CI run on the default branch, or update the open
+/// task of the same workflow and branch. With `auto_start`, a new task gets an
+/// attempt on all the project's repos, using the default coding agent.
+async fn report_ci_failure(
+    State(deployment): State<DeploymentImpl>,
+    Extension(key): Extension<ProjectApiKey>,
+    Json(report): Json<CiFailureReport>,
+) -> Result<ResponseJson<ApiResponse<CiFailureOutcome>>, ApiError> {
+    if report.workflow.trim().is_empty() || report.branch.trim().is_empty() {
+        return Err(ApiError::BadRequest(
+            "workflow and branch must not be empty".to_string(),
+        ));
+    }
+
+    let mut outcome =
+        ci_failure::record_failure(&deployment.db().pool, key.project_id, &report).await?;
+    if let CiFailureOutcome::Created {
+        task,
+        attempt_started,
+    } = &mut outcome
+        && report.auto_start
+    {
+        *attempt_started =
+            start_fix_attempt(&deployment, task, ci_failure::branch_name(&report.branch))
+                .await
+                .inspect_err(|e| {
+                    tracing::error!("Failed to start a fix attempt for task {}: {}", task.id, e)
+                })
+                .is_ok();
+    }
+
+    Ok(ResponseJson(ApiResponse::success(outcome)))
+}
+
+/// Start an attempt at `task` on all the project's repos, based on `branch`
+async fn start_fix_attempt(
+    deployment: &DeploymentImpl,
+    task: &Task,
+    branch: &str,
+) -> Result<(), ApiError> {
+    let pool = &deployment.db().pool;
+    let repos: Vec<WorkspaceRepoInput> = ProjectRepo::find_repos_for_project(pool, task.project_id)
+        .await?
+        .into_iter()
+        .map(|repo| WorkspaceRepoInput {
+            repo_id: repo.id,
+            target_branch: branch.to_string(),
+        })
+        .collect();
+    if repos.is_empty() {
+        return Err(ApiError::BadRequest(
+            "The project has no repositories".to_string(),
+        ));
+    }
+
+    let executor_profile_id = deployment.config().read().await.executor_profile.clone();
+    let workspace = create_workspace(deployment, task, &repos).await?;
+    deployment
+        .container()
+        .start_workspace(&workspace, executor_profile_id)
+        .await?;
+    Ok(())
+}
@@ -10,20 +10,43 @@ use axum::{
     http::StatusCode,
     middleware::from_fn_with_state,
     response::{IntoResponse, Json as ResponseJson},
-    routing::{get, post},
+    routing::{delete, get, post},
 };
+use chrono::{DateTime, Utc};
 use db::models::{
+    ci_failure::CiTestFailure,
+    peer_sync::{ProjectPeerSyncSettings, UpsertProjectPeerSyncSettings},
     project::{CreateProject, Project, ProjectError, SearchResult, UpdateProject},
+    project_api_key::{CreateProjectApiKey, ProjectApiKey},
+    project_preview::{ProjectPreviewSettings, UpsertProjectPreviewSettings},
     project_repo::{CreateProjectRepo, ProjectRepo},
     repo::Repo,
+    sla_policy::{SlaPolicy, UpsertSlaPolicy},
+    task::{Task, TaskStatus, TaskStatusCounts},
+    task_assignee::TaskAssignee,
+    task_status_history::{TaskStatusAt, TaskStatusChange},
+    todo_sync::{ProjectTodoSyncSettings, UpsertProjectTodoSyncSettings},
 };
 use deployment::Deployment;
 use futures_util::{SinkExt, StreamExt, TryStreamExt};
-use services::services::{file_search::SearchQuery, project::ProjectServiceError};
+use serde::{Deserialize, Serialize};
+use services::services::{
+    file_search::SearchQuery,
+    peer_sync::{self, PeerSyncError, PeerSyncReport},
+    project::{BatchProjectRequest, BatchProjectResult, ProjectServiceError},
+    sla::{self, SlaViolation},
+    todo_sync::{self, TodoSyncError, TodoSyncReport},
+    workload::{self, AssigneeWorkload},
+};
+use ts_rs::TS;
 use utils::response::ApiResponse;
 use uuid::Uuid;
 
-use crate::{DeploymentImpl, error::ApiError, middleware::load_project_middleware};
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    middleware::{generate_api_key, load_project_middleware},
+};
 
 pub async fn get_projects(
     State(deployment): State<DeploymentImpl>,
@@ -176,6 +199,30 @@ pub async fn delete_project(
     }
 }
 
+/// Archive, soft-delete, restore or reconfigure several projects at once
+pub async fn batch_update_projects(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<BatchProjectRequest>,
+) -> Result<ResponseJson<ApiResponse<BatchProjectResult>>, ApiError> {
+    let result = deployment
+        .project()
+        .batch_update(&deployment.db().pool, &payload)
+        .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "projects_batch_updated",
+            serde_json::json!({
+                "operation": payload.operation.name(),
+                "updated_count": result.updated.len(),
+                "unchanged_count": result.unchanged.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(result)))
+}
+
 #[derive(serde::Deserialize)]
 pub struct OpenEditorRequest {
     pub editor_type: Option<String>,
@@ -433,6 +480,403 @@ pub async fn get_project_repository(
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct BoardHistoryQuery {
+    pub at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct BoardColumn {
+    pub status: TaskStatus,
+    pub tasks: Vec<TaskStatusAt>,
+}
+
+/// The project board as it looked at a past point in time
+#[derive(Debug, Serialize, TS)]
+pub struct BoardSnapshot {
+    pub at: DateTime<Utc>,
+    pub columns: Vec<BoardColumn>,
+}
+
+const BOARD_COLUMNS: [TaskStatus; 5] = [
+    TaskStatus::Todo,
+    TaskStatus::InProgress,
+    TaskStatus::InReview,
+    TaskStatus::Done,
+    TaskStatus::Cancelled,
+];
+
+/// Reconstruct the board at `at` from the task status history
+pub async fn get_board_history(
+    State(deployment): State<DeploymentImpl>,
+    Extension(project): Extension<Project>,
+    Query(query): Query<BoardHistoryQuery>,
+) -> Result<ResponseJson<ApiResponse<BoardSnapshot>>, ApiError> {
+    let states =
+        TaskStatusChange::find_project_state_at(&deployment.db().pool, project.id, query.at)
+            .await?;
+
+    let columns = BOARD_COLUMNS
+        .into_iter()
+        .map(|status| BoardColumn {
+            tasks: states
+                .iter()
+                .filter(|state| state.status == status)
+                .cloned()
+                .collect(),
+            status,
+        })
+        .collect();
+
+    Ok(ResponseJson(ApiResponse::success(BoardSnapshot {
+        at: query.at,
+        columns,
+    })))
+}
+
+pub async fn get_sla_policies(
+    State(deployment): State<DeploymentImpl>,
+    Extension(project): Extension<Project>,
+) -> Result<ResponseJson<ApiResponse<Vec<SlaPolicy>>>, ApiError> {
+    let policies = SlaPolicy::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(policies)))
+}
+
+/// Create or replace the project's SLA for a status
+pub async fn upsert_sla_policy(
+    State(deployment): State<DeploymentImpl>,
+    Extension(project): Extension<Project>,
+    Json(payload): Json<UpsertSlaPolicy>,
+) -> Result<ResponseJson<ApiResponse<SlaPolicy>>, ApiError> {
+    if matches!(payload.status, TaskStatus::Done | TaskStatus::Cancelled) {
+        return Err(ApiError::BadRequest(
+            "SLAs can only be set for todo, inprogress and inreview".to_string(),
+        ));
+    }
+    if payload.max_hours <= 0 {
+        return Err(ApiError::BadRequest(
+            "max_hours must be greater than zero".to_string(),
+        ));
+    }
+
+    let policy = SlaPolicy::upsert(&deployment.db().pool, project.id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(policy)))
+}
+
+pub async fn delete_sla_policy(
+    State(deployment): State<DeploymentImpl>,
+    Path((project_id, policy_id)): Path<(Uuid, Uuid)>,
+) -> Result<(StatusCode, ResponseJson<ApiResponse<()>>), ApiError> {
+    let rows_affected = SlaPolicy::delete(&deployment.db().pool, project_id, policy_id).await?;
+    if rows_affected == 0 {
+        return Err(ApiError::Database(sqlx::Error::RowNotFound));
+    }
+    Ok((StatusCode::OK, ResponseJson(ApiResponse::success(()))))
+}
+
+/// Tasks that have breached or are close to breaching one of the project's SLAs
+pub async fn get_sla_violations(
+    State(deployment): State<DeploymentImpl>,
+    Extension(project): Extension<Project>,
+) -> Result<ResponseJson<ApiResponse<Vec<SlaViolation>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let policies = SlaPolicy::find_by_project_id(pool, project.id).await?;
+    let violations = sla::find_violations(pool, &policies).await?;
+    Ok(ResponseJson(ApiResponse::success(violations)))
+}
+
+/// Preview screenshot settings, or null when the project is not a web app
+pub async fn get_preview_settings(
+    State(deployment): State<DeploymentImpl>,
+    Extension(project): Extension<Project>,
+) -> Result<ResponseJson<ApiResponse<Option<ProjectPreviewSettings>>>, ApiError> {
+    let settings =
+        ProjectPreviewSettings::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(settings)))
+}
+
+/// Flag the project as a web app so successful attempts get a preview screenshot
+pub async fn upsert_preview_settings(
+    State(deployment): State<DeploymentImpl>,
+    Extension(project): Extension<Project>,
+    Json(payload): Json<UpsertProjectPreviewSettings>,
+) -> Result<ResponseJson<ApiResponse<ProjectPreviewSettings>>, ApiError> {
+    if !payload.preview_url.starts_with("http://") && !payload.preview_url.starts_with("https://") {
+        return Err(ApiError::BadRequest(
+            "preview_url must be an http(s) URL".to_string(),
+        ));
+    }
+    if payload.startup_timeout_secs.is_some_and(|secs| secs <= 0) {
+        return Err(ApiError::BadRequest(
+            "startup_timeout_secs must be greater than zero".to_string(),
+        ));
+    }
+
+    let settings =
+        ProjectPreviewSettings::upsert(&deployment.db().pool, project.id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(settings)))
+}
+
+pub async fn delete_preview_settings(
+    State(deployment): State<DeploymentImpl>,
+    Extension(project): Extension<Project>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    ProjectPreviewSettings::delete(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+fn map_todo_sync_error(error: TodoSyncError) -> ApiError {
+    match error {
+        TodoSyncError::Database(e) => ApiError::Database(e),
+        TodoSyncError::Io(e) => ApiError::Io(e),
+        e @ (TodoSyncError::RepoNotFound | TodoSyncError::InvalidPath(_)) => {
+            ApiError::BadRequest(e.to_string())
+        }
+    }
+}
+
+/// TODO file import settings, or null when the project does not sync one
+pub async fn get_todo_sync_settings(
+    State(deployment): State<DeploymentImpl>,
+    Extension(project): Extension<Project>,
+) -> Result<ResponseJson<ApiResponse<Option<ProjectTodoSyncSettings>>>, ApiError> {
+    let settings =
+        ProjectTodoSyncSettings::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(settings)))
+}
+
+/// Import tasks from a markdown checklist in one of the project's repos
+pub async fn upsert_todo_sync_settings(
+    State(deployment): State<DeploymentImpl>,
+    Extension(project): Extension<Project>,
+    Json(payload): Json<UpsertProjectTodoSyncSettings>,
+) -> Result<ResponseJson<ApiResponse<ProjectTodoSyncSettings>>, ApiError> {
+    let pool = &deployment.db().pool;
+    if let Some(file_path) = &payload.file_path {
+        todo_sync::validate_file_path(file_path).map_err(map_todo_sync_error)?;
+    }
+    if ProjectRepo::find_by_project_and_repo(pool, project.id, payload.repo_id)
+        .await?
+        .is_none()
+    {
+        return Err(ApiError::BadRequest(
+            "Repository is not part of this project".to_string(),
+        ));
+    }
+
+    let settings = ProjectTodoSyncSettings::upsert(pool, project.id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(settings)))
+}
+
+pub async fn delete_todo_sync_settings(
+    State(deployment): State<DeploymentImpl>,
+    Extension(project): Extension<Project>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    ProjectTodoSyncSettings::delete(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+/// Sync the TODO file now instead of waiting for the next scheduled run
+pub async fn run_todo_sync(
+    State(deployment): State<DeploymentImpl>,
+    Extension(project): Extension<Project>,
+) -> Result<ResponseJson<ApiResponse<TodoSyncReport>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let settings = ProjectTodoSyncSettings::find_by_project_id(pool, project.id)
+        .await?
+        .ok_or_else(|| {
+            ApiError::BadRequest("TODO file sync is not configured for this project".to_string())
+        })?;
+    let report = todo_sync::sync_project(pool, &settings)
+        .await
+        .map_err(map_todo_sync_error)?;
+    Ok(ResponseJson(ApiResponse::success(report)))
+}
+
+fn map_peer_sync_error(error: PeerSyncError) -> ApiError {
+    match error {
+        PeerSyncError::Database(e) => ApiError::Database(e),
+        e @ (PeerSyncError::Http(_)
+        | PeerSyncError::PeerRejected(_)
+        | PeerSyncError::InvalidUrl(_)) => ApiError::BadRequest(e.to_string()),
+    }
+}
+
+/// Peer sync settings, or null when the project is not paired with another instance
+pub async fn get_peer_sync_settings(
+    State(deployment): State<DeploymentImpl>,
+    Extension(project): Extension<Project>,
+) -> Result<ResponseJson<ApiResponse<Option<ProjectPeerSyncSettings>>>, ApiError> {
+    let settings =
+        ProjectPeerSyncSettings::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(settings)))
+}
+
+/// Pair the project with the same project on another instance. The peer must
+/// be configured with this project's id and the same token.
+pub async fn upsert_peer_sync_settings(
+    State(deployment): State<DeploymentImpl>,
+    Extension(project): Extension<Project>,
+    Json(payload): Json<UpsertProjectPeerSyncSettings>,
+) -> Result<ResponseJson<ApiResponse<ProjectPeerSyncSettings>>, ApiError> {
+    peer_sync::validate_peer_url(&payload.peer_url).map_err(map_peer_sync_error)?;
+    if payload.token.trim().len() < 16 {
+        return Err(ApiError::BadRequest(
+            "token must be at least 16 characters".to_string(),
+        ));
+    }
+
+    let settings =
+        ProjectPeerSyncSettings::upsert(&deployment.db().pool, project.id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(settings)))
+}
+
+pub async fn delete_peer_sync_settings(
+    State(deployment): State<DeploymentImpl>,
+    Extension(project): Extension<Project>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    ProjectPeerSyncSettings::delete(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+/// Sync with the peer now instead of waiting for the next scheduled run
+pub async fn run_peer_sync(
+    State(deployment): State<DeploymentImpl>,
+    Extension(project): Extension<Project>,
+) -> Result<ResponseJson<ApiResponse<PeerSyncReport>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let settings = ProjectPeerSyncSettings::find_by_project_id(pool, project.id)
+        .await?
+        .ok_or_else(|| {
+            ApiError::BadRequest("Peer sync is not configured for this project".to_string())
+        })?;
+    let report = peer_sync::sync_project(pool, &reqwest::Client::new(), &settings)
+        .await
+        .map_err(map_peer_sync_error)?;
+    Ok(ResponseJson(ApiResponse::success(report)))
+}
+
+pub async fn get_api_keys(
+    State(deployment): State<DeploymentImpl>,
+    Extension(project): Extension<Project>,
+) -> Result<ResponseJson<ApiResponse<Vec<ProjectApiKey>>>, ApiError> {
+    let keys = ProjectApiKey::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(keys)))
+}
+
+/// A project API key along with its secret, which is only ever returned when
+/// the key is created or rotated
+#[derive(Debug, Serialize, TS)]
+pub struct ProjectApiKeyWithSecret {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub key: ProjectApiKey,
+    pub secret: String,
+}
+
+/// Create a key that can read and write this project's tasks only
+pub async fn create_api_key(
+    State(deployment): State<DeploymentImpl>,
+    Extension(project): Extension<Project>,
+    Json(payload): Json<CreateProjectApiKey>,
+) -> Result<ResponseJson<ApiResponse<ProjectApiKeyWithSecret>>, ApiError> {
+    let name = payload.name.trim();
+    if name.is_empty() {
+        return Err(ApiError::BadRequest("name must not be empty".to_string()));
+    }
+
+    let generated = generate_api_key();
+    let key = ProjectApiKey::create(
+        &deployment.db().pool,
+        project.id,
+        name,
+        &generated.prefix,
+        &generated.hash,
+    )
+    .await?;
+    let key = ProjectApiKeyWithSecret {
+        key,
+        secret: generated.secret,
+    };
+    Ok(ResponseJson(ApiResponse::success(key)))
+}
+
+/// Give the key a new secret; the old one stops working immediately
+pub async fn rotate_api_key(
+    State(deployment): State<DeploymentImpl>,
+    Path((project_id, key_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<ProjectApiKeyWithSecret>>, ApiError> {
+    let generated = generate_api_key();
+    let key = ProjectApiKey::rotate(
+        &deployment.db().pool,
+        project_id,
+        key_id,
+        &generated.prefix,
+        &generated.hash,
+    )
+    .await?
+    .ok_or(ApiError::Database(sqlx::Error::RowNotFound))?;
+    let key = ProjectApiKeyWithSecret {
+        key,
+        secret: generated.secret,
+    };
+    Ok(ResponseJson(ApiResponse::success(key)))
+}
+
+pub async fn delete_api_key(
+    State(deployment): State<DeploymentImpl>,
+    Path((project_id, key_id)): Path<(Uuid, Uuid)>,
+) -> Result<(StatusCode, ResponseJson<ApiResponse<()>>), ApiError> {
+    let rows_affected = ProjectApiKey::delete(&deployment.db().pool, project_id, key_id).await?;
+    if rows_affected == 0 {
+        return Err(ApiError::Database(sqlx::Error::RowNotFound));
+    }
+    Ok((StatusCode::OK, ResponseJson(ApiResponse::success(()))))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FlakyTestsQuery {
+    #[serde(default = "default_flaky_tests_limit")]
+    pub limit: i64,
+}
+
+fn default_flaky_tests_limit() -> i64 {
+    20
+}
+
+/// Tests the project's CI reported failing, most often failing first
+pub async fn get_flaky_tests(
+    State(deployment): State<DeploymentImpl>,
+    Extension(project): Extension<Project>,
+    Query(query): Query<FlakyTestsQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<CiTestFailure>>>, ApiError> {
+    let limit = query.limit.clamp(1, 200);
+    let tests = CiTestFailure::find_flakiest(&deployment.db().pool, project.id, limit).await?;
+    Ok(ResponseJson(ApiResponse::success(tests)))
+}
+
+/// Open tasks and running attempts per assignee
+pub async fn get_project_workload(
+    State(deployment): State<DeploymentImpl>,
+    Extension(project): Extension<Project>,
+) -> Result<ResponseJson<ApiResponse<Vec<AssigneeWorkload>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let tasks = Task::find_by_project_id_with_attempt_status(pool, project.id).await?;
+    let assignees = TaskAssignee::find_assignees_by_project_id(pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(workload::summarize(
+        &tasks, &assignees,
+    ))))
+}
+
+/// Task counts per board column, without loading the tasks
+pub async fn get_project_counts(
+    State(deployment): State<DeploymentImpl>,
+    Extension(project): Extension<Project>,
+) -> Result<ResponseJson<ApiResponse<TaskStatusCounts>>, ApiError> {
+    let counts = Task::count_by_status(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(counts)))
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let project_id_router = Router::new()
         .route(
@@ -440,6 +884,36 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
             get(get_project).put(update_project).delete(delete_project),
         )
         .route("/search", get(search_project_files))
+        .route("/board-history", get(get_board_history))
+        .route(
+            "/sla-policies",
+            get(get_sla_policies).put(upsert_sla_policy),
+        )
+        .route("/sla-violations", get(get_sla_violations))
+        .route("/workload", get(get_project_workload))
+        .route("/counts", get(get_project_counts))
+        .route(
+            "/preview-settings",
+            get(get_preview_settings)
+                .put(upsert_preview_settings)
+                .delete(delete_preview_settings),
+        )
+        .route(
+            "/todo-sync",
+            get(get_todo_sync_settings)
+                .put(upsert_todo_sync_settings)
+                .delete(delete_todo_sync_settings),
+        )
+        .route("/todo-sync/run", post(run_todo_sync))
+        .route(
+            "/peer-sync",
+            get(get_peer_sync_settings)
+                .put(upsert_peer_sync_settings)
+                .delete(delete_peer_sync_settings),
+        )
+        .route("/peer-sync/run", post(run_peer_sync))
+        .route("/api-keys", get(get_api_keys).post(create_api_key))
+        .route("/flaky-tests", get(get_flaky_tests))
         .route("/open-editor", post(open_project_in_editor))
         .route(
             "/repositories",
@@ -452,10 +926,20 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
 
     let projects_router = Router::new()
         .route("/", get(get_projects).post(create_project))
+        .route("/batch", post(batch_update_projects))
         .route(
             "/{project_id}/repositories/{repo_id}",
             get(get_project_repository).delete(delete_project_repository),
         )
+        .route(
+            "/{project_id}/sla-policies/{policy_id}",
+            delete(delete_sla_policy),
+        )
+        .route("/{project_id}/api-keys/{key_id}", delete(delete_api_key))
+        .route(
+            "/{project_id}/api-keys/{key_id}/rotate",
+            post(rotate_api_key),
+        )
         .route("/stream/ws", get(stream_projects_ws))
         .nest("/{id}", project_id_router);
 
@@ -0,0 +1,125 @@
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    response::Json as ResponseJson,
+    routing::{get, post, put},
+};
+use db::models::prompt_snippet::{CreatePromptSnippet, PromptSnippet, UpdatePromptSnippet};
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::prompt_library::{self, RenderPromptRequest, RenderedPrompt};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct PromptSnippetQuery {
+    /// Limit to snippets usable in this project (its own plus global ones)
+    #[serde(default)]
+    pub project_id: Option<Uuid>,
+}
+
+fn validate_name(name: &str) -> Result<(), ApiError> {
+    prompt_library::validate_name(name).map_err(|e| ApiError::BadRequest(e.to_string()))
+}
+
+/// Snippet names are unique per project (or globally)
+fn map_unique_violation(name: &str, error: sqlx::Error) -> ApiError {
+    match &error {
+        sqlx::Error::Database(db_error) if db_error.is_unique_violation() => {
+            ApiError::Conflict(format!("A prompt named '{name}' already exists"))
+        }
+        _ => ApiError::Database(error),
+    }
+}
+
+async fn find_snippet(deployment: &DeploymentImpl, id: Uuid) -> Result<PromptSnippet, ApiError> {
+    PromptSnippet::find_by_id(&deployment.db().pool, id)
+        .await?
+        .ok_or(ApiError::Database(sqlx::Error::RowNotFound))
+}
+
+pub async fn get_prompts(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<PromptSnippetQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<PromptSnippet>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let snippets = match query.project_id {
+        Some(project_id) => PromptSnippet::find_for_project(pool, project_id).await?,
+        None => PromptSnippet::find_all(pool).await?,
+    };
+    Ok(ResponseJson(ApiResponse::success(snippets)))
+}
+
+pub async fn create_prompt(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreatePromptSnippet>,
+) -> Result<ResponseJson<ApiResponse<PromptSnippet>>, ApiError> {
+    validate_name(&payload.name)?;
+    let snippet = PromptSnippet::create(&deployment.db().pool, &payload)
+        .await
+        .map_err(|e| map_unique_violation(&payload.name, e))?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "prompt_snippet_created",
+            serde_json::json!({
+                "prompt_id": snippet.id.to_string(),
+                "project_scoped": snippet.project_id.is_some(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(snippet)))
+}
+
+pub async fn update_prompt(
+    Path(id): Path<Uuid>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpdatePromptSnippet>,
+) -> Result<ResponseJson<ApiResponse<PromptSnippet>>, ApiError> {
+    let snippet = find_snippet(&deployment, id).await?;
+    if let Some(name) = &payload.name {
+        validate_name(name)?;
+    }
+    let name = payload.name.as_deref().unwrap_or(&snippet.name);
+    let updated = PromptSnippet::update(&deployment.db().pool, snippet.id, &payload)
+        .await
+        .map_err(|e| map_unique_violation(name, e))?;
+    Ok(ResponseJson(ApiResponse::success(updated)))
+}
+
+pub async fn delete_prompt(
+    Path(id): Path<Uuid>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let rows_affected = PromptSnippet::delete(&deployment.db().pool, id).await?;
+    if rows_affected == 0 {
+        Err(ApiError::Database(sqlx::Error::RowNotFound))
+    } else {
+        Ok(ResponseJson(ApiResponse::success(())))
+    }
+}
+
+/// Fill in a snippet's variables, e.g. before inserting it into a task or
+/// follow-up prompt
+pub async fn render_prompt(
+    Path(id): Path<Uuid>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<RenderPromptRequest>,
+) -> Result<ResponseJson<ApiResponse<RenderedPrompt>>, ApiError> {
+    let snippet = find_snippet(&deployment, id).await?;
+    let rendered = prompt_library::render(&snippet.content, &payload.variables);
+    Ok(ResponseJson(ApiResponse::success(rendered)))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    let inner = Router::new()
+        .route("/", get(get_prompts).post(create_prompt))
+        .route("/{id}", put(update_prompt).delete(delete_prompt))
+        .route("/{id}/render", post(render_prompt));
+
+    Router::new().nest("/prompts", inner)
+}
@@ -0,0 +1,292 @@
+//! Slack API routes for workspace linking and slash commands.
+//!
+//! Endpoints:
+//! - POST /api/slack/commands - Receive `/vk` slash commands (bypasses origin validation)
+//! - GET /api/slack/oauth/callback - Finish installing the app (bypasses origin validation)
+//! - GET /api/slack/install - Get the URL that installs the app into a workspace
+//! - GET /api/slack/status - Check link status
+//! - DELETE /api/slack/unlink - Unlink the Slack workspace
+//! - PATCH /api/slack/settings - Update notification settings
+
+use axum::{
+    Router,
+    body::Bytes,
+    extract::{Json, Query, State},
+    http::{HeaderMap, Response, StatusCode},
+    response::Json as ResponseJson,
+    routing::{delete, get, patch, post},
+};
+use db::models::notification_rule::NotificationChannel;
+use deployment::Deployment;
+use serde::{Deserialize, Serialize};
+use services::services::{
+    config::{NotificationEventType, NotificationPreferences, SlackConfig, save_config_to_file},
+    slack::{SlackCommandResponse, SlackError, SlackService, SlackSettingsUpdate, SlashCommand},
+};
+use ts_rs::TS;
+use utils::{assets::config_path, response::ApiResponse};
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError, routes::oauth::simple_html_response};
+
+/// Response containing the URL that installs the Slack app
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct SlackInstallInfo {
+    /// Slack's OAuth page; it redirects back to /api/slack/oauth/callback
+    pub authorize_url: String,
+}
+
+/// Response containing the current Slack link status
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct SlackStatusResponse {
+    /// Whether a workspace is currently linked
+    pub linked: bool,
+    /// Name of the linked workspace
+    pub team_name: Option<String>,
+    /// Channel task notifications are posted to
+    pub channel_id: Option<String>,
+    pub channel_name: Option<String>,
+    /// Project slash commands use when none is given
+    pub default_project_id: Option<Uuid>,
+    /// Whether notifications are enabled
+    pub notifications_enabled: bool,
+    /// Whether to notify on task completion
+    pub notify_on_task_done: bool,
+    /// Whether the Slack app credentials are configured
+    pub app_configured: bool,
+}
+
+impl SlackStatusResponse {
+    /// Whether completions are posted is the Slack cell of the task done row
+    /// of the notification preferences
+    fn new(config: SlackConfig, preferences: &NotificationPreferences) -> Self {
+        Self {
+            linked: config.bot_token.is_some(),
+            team_name: config.team_name,
+            channel_id: config.channel_id,
+            channel_name: config.channel_name,
+            default_project_id: config.default_project_id,
+            notifications_enabled: config.notifications_enabled,
+            notify_on_task_done: preferences
+                .allows(NotificationEventType::TaskDone, NotificationChannel::Slack),
+            app_configured: false, // Set by the handler
+        }
+    }
+}
+
+/// Request to update Slack notification settings
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct UpdateSlackSettingsRequest {
+    /// Whether to enable notifications (master switch)
+    pub notifications_enabled: Option<bool>,
+    /// Whether to notify on task completion
+    pub notify_on_task_done: Option<bool>,
+    /// Channel to post notifications to; empty to stop posting
+    pub channel_id: Option<String>,
+    /// Project slash commands use when none is given
+    pub default_project_id: Option<Uuid>,
+}
+
+impl From<UpdateSlackSettingsRequest> for SlackSettingsUpdate {
+    fn from(request: UpdateSlackSettingsRequest) -> Self {
+        Self {
+            notifications_enabled: request.notifications_enabled,
+            notify_on_task_done: request.notify_on_task_done,
+            channel_id: request.channel_id,
+            default_project_id: request.default_project_id,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct OAuthCallbackQuery {
+    #[serde(default)]
+    code: Option<String>,
+    #[serde(default)]
+    state: Option<String>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// Create the Slack router.
+///
+/// Note: The Slack-facing endpoints are registered separately without origin validation.
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new()
+        .route("/slack/install", get(get_install_info))
+        .route("/slack/status", get(get_status))
+        .route("/slack/unlink", delete(unlink))
+        .route("/slack/settings", patch(update_settings))
+}
+
+/// Create a router for the endpoints Slack calls, which bypass origin
+/// validation. Slash commands are authenticated by their signature.
+pub fn webhook_router() -> Router<DeploymentImpl> {
+    Router::new()
+        .route("/slack/commands", post(command))
+        .route("/slack/oauth/callback", get(oauth_callback))
+}
+
+/// Get the shared SlackService from the deployment, or return an error.
+fn get_slack_service(deployment: &DeploymentImpl) -> Result<&SlackService, ApiError> {
+    deployment
+        .slack_service()
+        .ok_or_else(|| ApiError::BadRequest("Slack app is not configured".to_string()))
+}
+
+/// POST /api/slack/commands
+///
+/// Run a `/vk` slash command. Errors are replied to the user who ran it,
+/// except for requests that fail the signature check.
+async fn command(
+    State(deployment): State<DeploymentImpl>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<ResponseJson<SlackCommandResponse>, ApiError> {
+    let service = get_slack_service(&deployment)?;
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+    };
+    service.verify_signature(
+        header("x-slack-request-timestamp"),
+        &body,
+        header("x-slack-signature"),
+    )?;
+
+    let command = SlashCommand::parse(&body);
+    let response = match service.handle_command(&command).await {
+        Ok(response) => response,
+        Err(e) => {
+            if let SlackError::Database(_) | SlackError::Http(_) = e {
+                tracing::error!("Error handling Slack command: {}", e);
+            }
+            SlackCommandResponse::ephemeral(format!(":warning: {e}"))
+        }
+    };
+    Ok(ResponseJson(response))
+}
+
+/// GET /api/slack/oauth/callback
+///
+/// Slack redirects here after the app is installed into a workspace.
+async fn oauth_callback(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<OAuthCallbackQuery>,
+) -> Result<Response<String>, ApiError> {
+    let service = get_slack_service(&deployment)?;
+    if let Some(error) = query.error {
+        return Ok(simple_html_response(
+            StatusCode::BAD_REQUEST,
+            format!("Slack installation failed: {error}"),
+        ));
+    }
+    let (Some(code), Some(state)) = (query.code, query.state) else {
+        return Ok(simple_html_response(
+            StatusCode::BAD_REQUEST,
+            "Missing code or state in callback".to_string(),
+        ));
+    };
+
+    let linked = match service.complete_oauth(&code, &state).await {
+        Ok(linked) => linked,
+        Err(e) => {
+            tracing::warn!("Failed to complete Slack installation: {}", e);
+            return Ok(simple_html_response(
+                StatusCode::BAD_REQUEST,
+                format!("Slack installation failed: {e}"),
+            ));
+        }
+    };
+
+    // Save the updated config to disk
+    let config = deployment.config().read().await.clone();
+    if let Err(e) = save_config_to_file(&config, &config_path()).await {
+        tracing::error!("Failed to save config after Slack install: {}", e);
+    }
+
+    let team = linked.team_name.unwrap_or_else(|| "Slack".to_string());
+    Ok(simple_html_response(
+        StatusCode::OK,
+        format!("Linked {team}. You can close this window."),
+    ))
+}
+
+/// GET /api/slack/install
+///
+/// Get the URL that installs the Slack app and links its workspace.
+async fn get_install_info(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<SlackInstallInfo>>, ApiError> {
+    let service = get_slack_service(&deployment)?;
+    Ok(ResponseJson(ApiResponse::success(SlackInstallInfo {
+        authorize_url: service.authorize_url(),
+    })))
+}
+
+/// GET /api/slack/status
+///
+/// Get the current Slack link status.
+async fn get_status(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<SlackStatusResponse>>, ApiError> {
+    let (status, is_configured) = if let Some(service) = deployment.slack_service() {
+        (service.get_link_status().await, true)
+    } else {
+        (SlackConfig::default(), false)
+    };
+
+    let preferences = deployment
+        .config()
+        .read()
+        .await
+        .notification_preferences
+        .clone();
+    let mut response = SlackStatusResponse::new(status, &preferences);
+    response.app_configured = is_configured;
+
+    Ok(ResponseJson(ApiResponse::success(response)))
+}
+
+/// DELETE /api/slack/unlink
+///
+/// Unlink the Slack workspace.
+async fn unlink(State(deployment): State<DeploymentImpl>) -> Result<StatusCode, ApiError> {
+    let service = get_slack_service(&deployment)?;
+    service.unlink().await;
+
+    // Save the updated config to disk
+    let config = deployment.config().read().await.clone();
+    if let Err(e) = save_config_to_file(&config, &config_path()).await {
+        tracing::error!("Failed to save config after Slack unlink: {}", e);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// PATCH /api/slack/settings
+///
+/// Update Slack notification settings.
+async fn update_settings(
+    State(deployment): State<DeploymentImpl>,
+    Json(request): Json<UpdateSlackSettingsRequest>,
+) -> Result<ResponseJson<ApiResponse<SlackStatusResponse>>, ApiError> {
+    let service = get_slack_service(&deployment)?;
+    let updated = service.update_settings(request.into()).await?;
+
+    // Save config to disk
+    let config = deployment.config().read().await.clone();
+    if let Err(e) = save_config_to_file(&config, &config_path()).await {
+        tracing::error!("Failed to save Slack settings: {}", e);
+    }
+
+    let mut response = SlackStatusResponse::new(updated, &config.notification_preferences);
+    response.app_configured = true;
+
+    Ok(ResponseJson(ApiResponse::success(response)))
+}
@@ -1,8 +1,10 @@
+pub mod artifacts;
 pub mod codex_setup;
 pub mod cursor_setup;
 pub mod gh_cli_setup;
 pub mod images;
 pub mod pr;
+pub mod transcript;
 pub mod util;
 pub mod workspace_summary;
 
@@ -46,7 +48,8 @@ use git::{ConflictOp, GitCliError, GitServiceError};
 use git2::BranchType;
 use serde::{Deserialize, Serialize};
 use services::services::{
-    container::ContainerService, file_search::SearchQuery, workspace_manager::WorkspaceManager,
+    container::ContainerService, file_search::SearchQuery, outbox::OutboxIntent,
+    telegram::TaskNotificationKind, workspace_manager::WorkspaceManager,
 };
 use sqlx::Error as SqlxError;
 use ts_rs::TS;
@@ -171,29 +174,19 @@ pub struct RunAgentSetupRequest {
 #[derive(Debug, Serialize, TS)]
 pub struct RunAgentSetupResponse {}
 
-#[axum::debug_handler]
-pub async fn create_task_attempt(
-    State(deployment): State<DeploymentImpl>,
-    Json(payload): Json<CreateTaskAttemptBody>,
-) -> Result<ResponseJson<ApiResponse<Workspace>>, ApiError> {
-    let executor_profile_id = payload.executor_profile_id.clone();
-
-    if payload.repos.is_empty() {
-        return Err(ApiError::BadRequest(
-            "At least one repository is required".to_string(),
-        ));
-    }
-
+/// Create a workspace for `task` over `repos`, ready to be started
+pub async fn create_workspace(
+    deployment: &DeploymentImpl,
+    task: &Task,
+    repos: &[WorkspaceRepoInput],
+) -> Result<Workspace, ApiError> {
     let pool = &deployment.db().pool;
-    let task = Task::find_by_id(&deployment.db().pool, payload.task_id)
-        .await?
-        .ok_or(SqlxError::RowNotFound)?;
 
     // Compute agent_working_dir based on repo count:
     // - Single repo: join repo name with default_working_dir (if set), or just repo name
     // - Multiple repos: use None (agent runs in workspace root)
-    let agent_working_dir = if payload.repos.len() == 1 {
-        let repo = Repo::find_by_id(pool, payload.repos[0].repo_id)
+    let agent_working_dir = if repos.len() == 1 {
+        let repo = Repo::find_by_id(pool, repos[0].repo_id)
             .await?
             .ok_or(RepoError::NotFound)?;
         match repo.default_working_dir {
@@ -220,12 +213,11 @@ pub async fn create_task_attempt(
             agent_working_dir,
         },
         attempt_id,
-        payload.task_id,
+        task.id,
     )
     .await?;
 
-    let workspace_repos: Vec<CreateWorkspaceRepo> = payload
-        .repos
+    let workspace_repos: Vec<CreateWorkspaceRepo> = repos
         .iter()
         .map(|r| CreateWorkspaceRepo {
             repo_id: r.repo_id,
@@ -234,6 +226,27 @@ pub async fn create_task_attempt(
         .collect();
 
     WorkspaceRepo::create_many(pool, workspace.id, &workspace_repos).await?;
+    Ok(workspace)
+}
+
+#[axum::debug_handler]
+pub async fn create_task_attempt(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateTaskAttemptBody>,
+) -> Result<ResponseJson<ApiResponse<Workspace>>, ApiError> {
+    let executor_profile_id = payload.executor_profile_id.clone();
+
+    if payload.repos.is_empty() {
+        return Err(ApiError::BadRequest(
+            "At least one repository is required".to_string(),
+        ));
+    }
+
+    let task = Task::find_by_id(&deployment.db().pool, payload.task_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+
+    let workspace = create_workspace(&deployment, &task, &payload.repos).await?;
     if let Err(err) = deployment
         .container()
         .start_workspace(&workspace, executor_profile_id.clone())
@@ -489,7 +502,15 @@ pub async fn merge_task_attempt(
         &merge_commit_id,
     )
     .await?;
-    Task::update_status(pool, task.id, TaskStatus::Done).await?;
+    let mut tx = pool.begin().await?;
+    Task::update_status(&mut *tx, task.id, TaskStatus::Done).await?;
+    OutboxIntent::TaskStatusNotification {
+        task_id: task.id,
+        kind: TaskNotificationKind::Done,
+    }
+    .enqueue(&mut *tx)
+    .await?;
+    tx.commit().await?;
     if !workspace.pinned {
         Workspace::set_archived(pool, workspace.id, true).await?;
     }
@@ -1787,6 +1808,7 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         .route("/repos", get(get_task_attempt_repos))
         .route("/search", get(search_workspace_files))
         .route("/first-message", get(get_first_user_message))
+        .route("/transcript", get(transcript::get_transcript))
         .route("/mark-seen", put(mark_seen))
         .layer(from_fn_with_state(
             deployment.clone(),
@@ -1800,7 +1822,8 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         .route("/stream/ws", get(stream_workspaces_ws))
         .route("/summary", post(workspace_summary::get_workspace_summaries))
         .nest("/{id}", task_attempt_id_router)
-        .nest("/{id}/images", images::router(deployment));
+        .nest("/{id}/images", images::router(deployment))
+        .nest("/{id}/artifacts", artifacts::router(deployment));
 
     Router::new().nest("/task-attempts", task_attempts_router)
 }
@@ -0,0 +1,151 @@
+use axum::{
+    Extension, Router,
+    body::Body,
+    extract::{DefaultBodyLimit, Multipart, Path, State},
+    http::{StatusCode, header},
+    middleware::from_fn_with_state,
+    response::{Json as ResponseJson, Response},
+    routing::{delete, get},
+};
+use db::models::{workspace::Workspace, workspace_artifact::WorkspaceArtifact};
+use deployment::Deployment;
+use services::services::artifact::ArtifactError;
+use tokio::fs::File;
+use tokio_util::io::ReaderStream;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError, middleware::load_workspace_middleware};
+
+/// Largest multipart request accepted for artifact uploads
+const MAX_UPLOAD_BYTES: usize = 100 * 1024 * 1024;
+
+pub async fn get_artifacts(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<WorkspaceArtifact>>>, ApiError> {
+    let artifacts =
+        WorkspaceArtifact::find_by_workspace_id(&deployment.db().pool, workspace.id).await?;
+    Ok(ResponseJson(ApiResponse::success(artifacts)))
+}
+
+/// Register output files for an attempt. Every `file` field of the multipart body
+/// is stored as a separate artifact.
+pub async fn upload_artifacts(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    mut multipart: Multipart,
+) -> Result<ResponseJson<ApiResponse<Vec<WorkspaceArtifact>>>, ApiError> {
+    let quota_bytes = deployment.config().read().await.artifact_quota_mb * 1024 * 1024;
+
+    let mut artifacts = Vec::new();
+    while let Some(field) = multipart.next_field().await? {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let filename = field
+            .file_name()
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| "artifact".to_string());
+        let mime_type = field.content_type().map(|s| s.to_string()).or_else(|| {
+            mime_guess::from_path(&filename)
+                .first()
+                .map(|m| m.essence_str().to_string())
+        });
+
+        let data = field.bytes().await?;
+        let artifact = deployment
+            .artifacts()
+            .store_artifact(&workspace, &data, &filename, mime_type, quota_bytes)
+            .await?;
+        artifacts.push(artifact);
+    }
+
+    if artifacts.is_empty() {
+        return Err(ApiError::BadRequest(
+            "Expected at least one 'file' field".to_string(),
+        ));
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "workspace_artifacts_uploaded",
+            serde_json::json!({
+                "workspace_id": workspace.id.to_string(),
+                "count": artifacts.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(artifacts)))
+}
+
+async fn find_workspace_artifact(
+    deployment: &DeploymentImpl,
+    workspace_id: Uuid,
+    artifact_id: Uuid,
+) -> Result<WorkspaceArtifact, ApiError> {
+    WorkspaceArtifact::find_by_id(&deployment.db().pool, artifact_id)
+        .await?
+        .filter(|artifact| artifact.workspace_id == workspace_id)
+        .ok_or(ApiError::Artifact(ArtifactError::NotFound))
+}
+
+pub async fn serve_artifact(
+    Path((workspace_id, artifact_id)): Path<(Uuid, Uuid)>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<Response, ApiError> {
+    let artifact = find_workspace_artifact(&deployment, workspace_id, artifact_id).await?;
+
+    let file = File::open(deployment.artifacts().get_absolute_path(&artifact))
+        .await
+        .map_err(|_| ApiError::Artifact(ArtifactError::NotFound))?;
+    let metadata = file.metadata().await?;
+    let body = Body::from_stream(ReaderStream::new(file));
+
+    let content_type = artifact
+        .mime_type
+        .as_deref()
+        .unwrap_or("application/octet-stream");
+    let disposition = format!(
+        "attachment; filename=\"{}\"",
+        artifact.original_name.replace(['"', '\\'], "_")
+    );
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::CONTENT_LENGTH, metadata.len())
+        .header(header::CONTENT_DISPOSITION, disposition)
+        .body(body)
+        .map_err(|e| ApiError::BadRequest(e.to_string()))
+}
+
+pub async fn delete_artifact(
+    Path((workspace_id, artifact_id)): Path<(Uuid, Uuid)>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let artifact = find_workspace_artifact(&deployment, workspace_id, artifact_id).await?;
+    deployment.artifacts().delete_artifact(&artifact).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    let list_router = Router::new()
+        .route(
+            "/",
+            get(get_artifacts)
+                .post(upload_artifacts)
+                .layer(DefaultBodyLimit::max(MAX_UPLOAD_BYTES)),
+        )
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            load_workspace_middleware,
+        ));
+
+    let artifact_router = Router::new()
+        .route("/{artifact_id}", delete(delete_artifact))
+        .route("/{artifact_id}/file", get(serve_artifact));
+
+    list_router.merge(artifact_router)
+}
@@ -29,6 +29,8 @@ use services::services::{
         self, CreatePrRequest, GitHostError, GitHostProvider, ProviderKind, UnifiedPrComment,
         github::GhCli,
     },
+    outbox::OutboxIntent,
+    telegram::TaskNotificationKind,
 };
 use ts_rs::TS;
 use utils::response::ApiResponse;
@@ -477,7 +479,15 @@ pub async fn attach_existing_pr(
 
         // If PR is merged, mark task as done and archive workspace
         if matches!(pr_info.status, MergeStatus::Merged) {
-            Task::update_status(pool, task.id, TaskStatus::Done).await?;
+            let mut tx = pool.begin().await?;
+            Task::update_status(&mut *tx, task.id, TaskStatus::Done).await?;
+            OutboxIntent::TaskStatusNotification {
+                task_id: task.id,
+                kind: TaskNotificationKind::Done,
+            }
+            .enqueue(&mut *tx)
+            .await?;
+            tx.commit().await?;
             if !workspace.pinned {
                 Workspace::set_archived(pool, workspace.id, true).await?;
             }
@@ -0,0 +1,122 @@
+use axum::{
+    Extension,
+    extract::{Query, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Json as ResponseJson, Response},
+};
+use chrono::Utc;
+use db::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason},
+    session::Session,
+    workspace::Workspace,
+};
+use deployment::Deployment;
+use executors::actions::ExecutorActionType;
+use serde::Deserialize;
+use services::services::{
+    container::ContainerService,
+    transcript::{
+        ENTRY_IDLE_TIMEOUT, Transcript, TranscriptFormat, collect_normalized_entries, render_html,
+        render_markdown, transcript_turn,
+    },
+};
+use sqlx::Error as SqlxError;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize)]
+pub struct TranscriptQuery {
+    #[serde(default)]
+    pub format: TranscriptFormat,
+}
+
+/// Prompt sent to the agent by a coding agent execution
+fn execution_prompt(process: &ExecutionProcess) -> Option<String> {
+    match process.executor_action().ok()?.typ() {
+        ExecutorActionType::CodingAgentInitialRequest(request) => Some(request.prompt.clone()),
+        ExecutorActionType::CodingAgentFollowUpRequest(request) => Some(request.prompt.clone()),
+        ExecutorActionType::ReviewRequest(request) => Some(request.prompt.clone()),
+        _ => None,
+    }
+}
+
+pub async fn build_transcript(
+    deployment: &DeploymentImpl,
+    workspace: &Workspace,
+) -> Result<Transcript, ApiError> {
+    let pool = &deployment.db().pool;
+    let task = workspace
+        .parent_task(pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+
+    let mut turns = Vec::new();
+    for session in Session::find_by_workspace_id(pool, workspace.id).await? {
+        for process in ExecutionProcess::find_by_session_id(pool, session.id, false).await? {
+            if process.dropped || process.run_reason != ExecutionProcessRunReason::CodingAgent {
+                continue;
+            }
+            let entries = match deployment
+                .container()
+                .stream_normalized_logs(&process.id)
+                .await
+            {
+                Some(stream) => collect_normalized_entries(stream, ENTRY_IDLE_TIMEOUT).await,
+                None => Vec::new(),
+            };
+            turns.push(transcript_turn(
+                process.id,
+                session.executor.clone(),
+                process.started_at,
+                execution_prompt(&process),
+                &entries,
+            ));
+        }
+    }
+    turns.sort_by_key(|turn| turn.started_at);
+
+    Ok(Transcript {
+        workspace_id: workspace.id,
+        task_title: task.title,
+        branch: workspace.branch.clone(),
+        generated_at: Utc::now(),
+        turns,
+    })
+}
+
+/// Shareable transcript of every agent turn in the attempt, for attaching to
+/// PRs or audits
+pub async fn get_transcript(
+    Extension(workspace): Extension<Workspace>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<TranscriptQuery>,
+) -> Result<Response, ApiError> {
+    let transcript = build_transcript(&deployment, &workspace).await?;
+
+    let (body, content_type, extension) = match query.format {
+        TranscriptFormat::Json => {
+            return Ok(ResponseJson(ApiResponse::success(transcript)).into_response());
+        }
+        TranscriptFormat::Markdown => (
+            render_markdown(&transcript),
+            "text/markdown; charset=utf-8",
+            "md",
+        ),
+        TranscriptFormat::Html => (render_html(&transcript), "text/html; charset=utf-8", "html"),
+    };
+    let disposition = format!(
+        "inline; filename=\"transcript-{}.{extension}\"",
+        workspace.branch.replace(['"', '\\', '/'], "_")
+    );
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response())
+}
@@ -7,7 +7,7 @@ use axum::{
         Query, State,
         ws::{WebSocket, WebSocketUpgrade},
     },
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     middleware::from_fn_with_state,
     response::{IntoResponse, Json as ResponseJson},
     routing::{delete, get, post, put},
@@ -16,6 +16,7 @@ use db::models::{
     image::TaskImage,
     repo::{Repo, RepoError},
     task::{CreateTask, Task, TaskWithAttemptStatus, UpdateTask},
+    task_mutation::{TaskMutation, TaskSnapshot},
     workspace::{CreateWorkspace, Workspace},
     workspace_repo::{CreateWorkspaceRepo, WorkspaceRepo},
 };
@@ -23,15 +24,23 @@ use deployment::Deployment;
 use executors::profile::ExecutorProfileId;
 use futures_util::{SinkExt, StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
-use services::services::{container::ContainerService, workspace_manager::WorkspaceManager};
+use services::services::{
+    container::ContainerService,
+    outbox::OutboxIntent,
+    task_order::{self, TaskMove, TaskPlacement},
+    telegram::TaskNotificationKind,
+    workspace_manager::WorkspaceManager,
+};
 use sqlx::Error as SqlxError;
 use ts_rs::TS;
 use utils::response::ApiResponse;
 use uuid::Uuid;
 
 use crate::{
-    DeploymentImpl, error::ApiError, middleware::load_task_middleware,
-    routes::task_attempts::WorkspaceRepoInput,
+    DeploymentImpl,
+    error::ApiError,
+    middleware::load_task_middleware,
+    routes::{task_attempts::WorkspaceRepoInput, undo::client_session},
 };
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -254,9 +263,10 @@ pub async fn create_task_and_start(
 pub async fn update_task(
     Extension(existing_task): Extension<Task>,
     State(deployment): State<DeploymentImpl>,
-
+    headers: HeaderMap,
     Json(payload): Json<UpdateTask>,
-) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
+) -> Result<(StatusCode, ResponseJson<ApiResponse<Task>>), ApiError> {
+    let before = TaskSnapshot::from(&existing_task);
     // Use existing values if not provided in update
     let title = payload.title.unwrap_or(existing_task.title);
     let description = match payload.description {
@@ -264,28 +274,64 @@ pub async fn update_task(
         Some(s) => Some(s),                     // Non-empty string = update description
         None => existing_task.description,      // Field omitted = keep existing
     };
+    let previous_status = existing_task.status.clone();
     let status = payload.status.unwrap_or(existing_task.status);
     let parent_workspace_id = payload
         .parent_workspace_id
         .or(existing_task.parent_workspace_id);
 
-    let task = Task::update(
-        &deployment.db().pool,
+    // The status notification intent is recorded in the same transaction as the update
+    let mut tx = deployment.db().pool.begin().await?;
+    let Some(task) = Task::update(
+        &mut *tx,
         existing_task.id,
         existing_task.project_id,
         title,
         description,
         status,
         parent_workspace_id,
+        payload.expected_updated_at,
     )
-    .await?;
+    .await?
+    else {
+        // The client's copy is stale: return the current state so it can reconcile
+        let current = Task::find_by_id(&deployment.db().pool, existing_task.id)
+            .await?
+            .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
+        return Ok((
+            StatusCode::CONFLICT,
+            ResponseJson(ApiResponse::error_with_data(current)),
+        ));
+    };
+
+    if task.status != previous_status
+        && let Some(kind) = TaskNotificationKind::from_status(&task.status)
+    {
+        OutboxIntent::TaskStatusNotification {
+            task_id: task.id,
+            kind,
+        }
+        .enqueue(&mut *tx)
+        .await?;
+    }
+
+    // Journal the edit so the client session can undo it
+    let after = TaskSnapshot::from(&task);
+    if let Some(session) = client_session(&headers)
+        && after != before
+    {
+        TaskMutation::delete_undone(&mut *tx, session).await?;
+        TaskMutation::create(&mut *tx, session, task.id, &before, &after).await?;
+        TaskMutation::prune(&mut *tx, session).await?;
+    }
+    tx.commit().await?;
 
     if let Some(image_ids) = &payload.image_ids {
         TaskImage::delete_by_task_id(&deployment.db().pool, task.id).await?;
         TaskImage::associate_many_dedup(&deployment.db().pool, task.id, image_ids).await?;
     }
 
-    Ok(ResponseJson(ApiResponse::success(task)))
+    Ok((StatusCode::OK, ResponseJson(ApiResponse::success(task))))
 }
 
 pub async fn delete_task(
@@ -395,10 +441,49 @@ pub async fn delete_task(
     Ok((StatusCode::ACCEPTED, ResponseJson(ApiResponse::success(()))))
 }
 
+/// POST /tasks/{task_id}/move
+///
+/// Put the task right before or after another task of its project. Returns
+/// the project's tasks in their new order.
+pub async fn move_task(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(placement): Json<TaskPlacement>,
+) -> Result<ResponseJson<ApiResponse<Vec<Task>>>, ApiError> {
+    let moves = [TaskMove {
+        task_id: task.id,
+        placement,
+    }];
+    let tasks = task_order::move_tasks(&deployment.db().pool, task.project_id, &moves).await?;
+    Ok(ResponseJson(ApiResponse::success(tasks)))
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct ReorderTasksRequest {
+    pub project_id: Uuid,
+    /// Applied in order, all or none of them
+    pub moves: Vec<TaskMove>,
+}
+
+/// POST /tasks/reorder
+///
+/// Move several tasks of a project at once. Returns the project's tasks in
+/// their new order.
+pub async fn reorder_tasks(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ReorderTasksRequest>,
+) -> Result<ResponseJson<ApiResponse<Vec<Task>>>, ApiError> {
+    let tasks =
+        task_order::move_tasks(&deployment.db().pool, payload.project_id, &payload.moves).await?;
+    Ok(ResponseJson(ApiResponse::success(tasks)))
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let task_actions_router = Router::new()
         .route("/", put(update_task))
-        .route("/", delete(delete_task));
+        .route("/", delete(delete_task))
+        .route("/move", post(move_task));
 
     let task_id_router = Router::new()
         .route("/", get(get_task))
@@ -409,6 +494,7 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         .route("/", get(get_tasks).post(create_task))
         .route("/stream/ws", get(stream_tasks_ws))
         .route("/create-and-start", post(create_task_and_start))
+        .route("/reorder", post(reorder_tasks))
         .nest("/{task_id}", task_id_router);
 
     // mount under /projects/:project_id/tasks
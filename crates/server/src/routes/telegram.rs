@@ -2,29 +2,66 @@
 //!
 //! Endpoints:
 //! - POST /api/telegram/webhook - Receive Telegram updates (bypasses origin validation)
-//! - GET /api/telegram/link - Get deep link for account linking
+//! - POST /api/telegram/webhook/{project_id} - Receive updates for a project's own bot
+//! - GET /api/telegram/link - Get deep link for account linking (`?format=qr` for a QR code)
 //! - DELETE /api/telegram/unlink - Unlink Telegram account
 //! - GET /api/telegram/status - Check link status
 //! - PATCH /api/telegram/settings - Update notification settings
+//! - GET /api/telegram/accounts - List linked accounts and their roles
+//! - PUT /api/telegram/accounts/{user_id}/role - Change an account's role
+//! - PUT /api/telegram/accounts/{user_id}/digest - Change an account's digest mode
+//! - POST /api/telegram/rotate-token - Swap in a new bot token without a restart
+//! - POST /api/telegram/preview - Render a notification without sending it
+//! - GET /api/telegram/setup-state - Which setup steps are done, and what to do next
+
+use std::io::Cursor;
 
 use axum::{
     Router,
-    extract::{Json, State},
-    http::StatusCode,
-    response::Json as ResponseJson,
-    routing::{delete, get, patch, post},
+    extract::{Json, Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    middleware::from_fn_with_state,
+    response::{IntoResponse, Json as ResponseJson, Response},
+    routing::{delete, get, patch, post, put},
+};
+use chrono::{DateTime, Utc};
+use db::models::{
+    execution_process::ExecutionProcess,
+    notification_rule::NotificationChannel,
+    session::Session,
+    task::Task,
+    telegram_account::{TelegramAccount, TelegramAccountRole, TelegramDigestMode},
+    workspace::Workspace,
+    workspace_repo::WorkspaceRepo,
 };
 use deployment::Deployment;
 use frankenstein::objects::Update;
+use qrcode::{QrCode, render::svg};
 use serde::{Deserialize, Serialize};
 use services::services::{
-    config::{TelegramConfig, save_config_to_file},
-    telegram::{TelegramError, TelegramService, UpdateResult},
+    config::{
+        NotificationEventType, NotificationPreferences, StatusTransitionRule, TelegramConfig,
+        save_config_to_file,
+    },
+    container::ContainerService,
+    i18n::BotMessage,
+    telegram::{
+        NotificationPreview, TaskNotificationKind, TelegramError, TelegramService,
+        TelegramSettingsUpdate, TelegramSetupState, UpdateResult, escape_html,
+    },
 };
+use sqlx::Error as SqlxError;
+use subtle::ConstantTimeEq;
 use ts_rs::TS;
 use utils::{assets::config_path, response::ApiResponse};
+use uuid::Uuid;
 
-use crate::{DeploymentImpl, error::ApiError};
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    middleware::telegram_webhook_source_middleware,
+    routes::task_attempts::{WorkspaceRepoInput, create_workspace},
+};
 
 /// Response containing the deep link URL for Telegram account linking
 #[derive(Debug, Serialize, TS)]
@@ -54,19 +91,55 @@ pub struct TelegramStatusResponse {
     pub include_llm_summary: bool,
     /// Whether real-time streaming is enabled
     pub stream_enabled: bool,
+    /// Whether to notify when a task starts
+    pub notify_on_started: bool,
+    /// Whether to notify when a task moves to review
+    pub notify_on_in_review: bool,
+    /// Whether to notify when an attempt fails
+    pub notify_on_failed: bool,
+    /// Whether LLM summaries are also sent as voice messages
+    pub voice_summary: bool,
+    /// Status transitions that notify, narrowing down the per-status toggles when non-empty
+    pub notify_transitions: Vec<StatusTransitionRule>,
+    /// Whether every notification is delivered without sound
+    pub silent: bool,
+    /// Notification kinds delivered without sound
+    pub silent_kinds: Vec<TaskNotificationKind>,
+    /// Task notifications are paused until this time (set with /mute)
+    pub muted_until: Option<DateTime<Utc>>,
+    /// Whether status changes edit the task's previous notification
+    pub edit_in_place: bool,
+    /// Whether questions agents end their turn with are forwarded to the chat
+    pub forward_questions: bool,
+    /// Telegram user IDs allowed to use the bot besides the linked accounts
+    pub allowed_user_ids: Vec<i64>,
     /// Whether the bot is configured (has a token)
     pub bot_configured: bool,
 }
 
-impl From<TelegramConfig> for TelegramStatusResponse {
-    fn from(config: TelegramConfig) -> Self {
+impl TelegramStatusResponse {
+    /// The per-status toggles are the Telegram cells of the notification
+    /// preferences
+    fn new(config: TelegramConfig, preferences: &NotificationPreferences) -> Self {
+        let sends = |event| preferences.allows(event, NotificationChannel::Telegram);
         Self {
             linked: config.chat_id.is_some(),
             username: config.username,
             notifications_enabled: config.notifications_enabled,
-            notify_on_task_done: config.notify_on_task_done,
+            notify_on_task_done: sends(NotificationEventType::TaskDone),
             include_llm_summary: config.include_llm_summary,
             stream_enabled: config.stream_enabled,
+            notify_on_started: sends(NotificationEventType::AttemptStarted),
+            notify_on_in_review: sends(NotificationEventType::TaskInReview),
+            notify_on_failed: sends(NotificationEventType::TaskFailed),
+            voice_summary: config.voice_summary,
+            notify_transitions: config.notify_transitions,
+            silent: config.silent,
+            silent_kinds: config.silent_kinds,
+            muted_until: config.muted_until.filter(|until| *until > Utc::now()),
+            edit_in_place: config.edit_in_place,
+            forward_questions: config.forward_questions,
+            allowed_user_ids: config.allowed_user_ids,
             bot_configured: false, // Set by the handler
         }
     }
@@ -84,6 +157,49 @@ pub struct UpdateTelegramSettingsRequest {
     pub include_llm_summary: Option<bool>,
     /// Whether to enable real-time streaming
     pub stream_enabled: Option<bool>,
+    /// Whether to notify when a task starts
+    pub notify_on_started: Option<bool>,
+    /// Whether to notify when a task moves to review
+    pub notify_on_in_review: Option<bool>,
+    /// Whether to notify when an attempt fails
+    pub notify_on_failed: Option<bool>,
+    /// Whether to send LLM summaries as voice messages
+    pub voice_summary: Option<bool>,
+    /// Replace the status transitions that notify; an empty list sends every
+    /// status the toggles allow
+    pub notify_transitions: Option<Vec<StatusTransitionRule>>,
+    /// Whether to deliver every notification without sound
+    pub silent: Option<bool>,
+    /// Notification kinds to deliver without sound
+    pub silent_kinds: Option<Vec<TaskNotificationKind>>,
+    /// Whether status changes edit the task's previous notification
+    pub edit_in_place: Option<bool>,
+    /// Whether to forward questions agents end their turn with to the chat
+    pub forward_questions: Option<bool>,
+    /// Replace the Telegram user IDs allowed to use the bot besides the
+    /// linked accounts
+    pub allowed_user_ids: Option<Vec<i64>>,
+}
+
+impl From<UpdateTelegramSettingsRequest> for TelegramSettingsUpdate {
+    fn from(request: UpdateTelegramSettingsRequest) -> Self {
+        Self {
+            notifications_enabled: request.notifications_enabled,
+            notify_on_task_done: request.notify_on_task_done,
+            include_llm_summary: request.include_llm_summary,
+            stream_enabled: request.stream_enabled,
+            notify_on_started: request.notify_on_started,
+            notify_on_in_review: request.notify_on_in_review,
+            notify_on_failed: request.notify_on_failed,
+            voice_summary: request.voice_summary,
+            notify_transitions: request.notify_transitions,
+            silent: request.silent,
+            silent_kinds: request.silent_kinds,
+            edit_in_place: request.edit_in_place,
+            forward_questions: request.forward_questions,
+            allowed_user_ids: request.allowed_user_ids,
+        }
+    }
 }
 
 /// Create the Telegram router.
@@ -95,13 +211,28 @@ pub fn router(_deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         .route("/telegram/unlink", delete(unlink))
         .route("/telegram/status", get(get_status))
         .route("/telegram/settings", patch(update_settings))
+        .route("/telegram/accounts", get(list_accounts))
+        .route("/telegram/accounts/{user_id}/role", put(set_account_role))
+        .route(
+            "/telegram/accounts/{user_id}/digest",
+            put(set_account_digest_mode),
+        )
+        .route("/telegram/rotate-token", post(rotate_token))
+        .route("/telegram/preview", post(preview_notification))
+        .route("/telegram/setup-state", get(get_setup_state))
 }
 
 /// Create a router for the webhook endpoint that bypasses origin validation.
 ///
 /// This should be merged before the origin validation layer is applied.
-pub fn webhook_router() -> Router<DeploymentImpl> {
-    Router::new().route("/telegram/webhook", post(webhook))
+pub fn webhook_router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    Router::new()
+        .route("/telegram/webhook", post(webhook))
+        .route("/telegram/webhook/{project_id}", post(project_webhook))
+        .layer(from_fn_with_state(
+            deployment.clone(),
+            telegram_webhook_source_middleware,
+        ))
 }
 
 /// Get the shared TelegramService from the deployment, or return an error.
@@ -117,28 +248,164 @@ fn get_telegram_service(deployment: &DeploymentImpl) -> Result<&TelegramService,
 /// This endpoint bypasses origin validation since Telegram sends webhooks.
 async fn webhook(
     State(deployment): State<DeploymentImpl>,
+    headers: HeaderMap,
     Json(update): Json<Update>,
 ) -> Result<StatusCode, ApiError> {
     let Some(service) = deployment.telegram_service() else {
         tracing::warn!("Telegram webhook received but bot is not configured");
         return Ok(StatusCode::OK);
     };
+    check_webhook_secret(service, None, &headers).await?;
 
+    handle_update(&deployment, service, update).await;
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/telegram/webhook/{project_id}
+///
+/// Like [`webhook`], for the bot configured for one project in
+/// `TelegramConfig::project_bots`. Replies go out through that bot.
+async fn project_webhook(
+    State(deployment): State<DeploymentImpl>,
+    Path(project_id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(update): Json<Update>,
+) -> Result<StatusCode, ApiError> {
+    let Some(service) = deployment.telegram_service() else {
+        tracing::warn!("Telegram webhook received but bot is not configured");
+        return Ok(StatusCode::OK);
+    };
+    check_webhook_secret(service, Some(project_id), &headers).await?;
+
+    let service = service.for_project(project_id).await;
+    handle_update(&deployment, &service, update).await;
+    Ok(StatusCode::OK)
+}
+
+/// Header Telegram sends the `secret_token` of `setWebhook` in
+const WEBHOOK_SECRET_HEADER: &str = "x-telegram-bot-api-secret-token";
+
+/// Reject a webhook request without the secret the bot's webhook was
+/// registered with (see `TelegramService::register_webhooks`). A bot whose
+/// webhook this server never registered has no secret to check.
+async fn check_webhook_secret(
+    service: &TelegramService,
+    project_id: Option<Uuid>,
+    headers: &HeaderMap,
+) -> Result<(), ApiError> {
+    let Some(secret) = service.webhook_secret(project_id).await else {
+        return Ok(());
+    };
+    let matches = headers
+        .get(WEBHOOK_SECRET_HEADER)
+        .is_some_and(|value| bool::from(value.as_bytes().ct_eq(secret.as_bytes())));
+    if !matches {
+        tracing::warn!("Rejected a Telegram webhook request without the bot's secret");
+        return Err(ApiError::Unauthorized);
+    }
+    Ok(())
+}
+
+/// Handle an update and send the reply through the bot it came from
+async fn handle_update(deployment: &DeploymentImpl, service: &TelegramService, update: Update) {
+    let reply_target = service.reply_target(&update).await;
     match service.handle_update(update).await {
         Ok(UpdateResult::Response(text)) => {
-            // Get chat_id from the config to send response
-            let config = deployment.config().read().await;
-            if let Some(chat_id) = config.telegram.chat_id {
-                drop(config);
-                if let Err(e) = service.send_message(chat_id, &text).await {
-                    tracing::error!("Failed to send Telegram response: {}", e);
+            if let Some(target) = reply_target
+                && let Err(e) = service.send_reply(target, &text).await
+            {
+                tracing::error!("Failed to send Telegram response: {}", e);
+            }
+        }
+        Ok(UpdateResult::Responses(messages)) => {
+            if let Some(target) = reply_target {
+                for text in messages {
+                    if let Err(e) = service.send_reply(target, &text).await {
+                        tracing::error!("Failed to send Telegram response: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(UpdateResult::ResponseWithButtons { text, buttons }) => {
+            if let Some(target) = reply_target
+                && let Err(e) = service
+                    .send_message_with_buttons(target, &text, &buttons)
+                    .await
+            {
+                tracing::error!("Failed to send Telegram response: {}", e);
+            }
+        }
+        Ok(UpdateResult::ResponseWithMenu { text, show }) => {
+            if let Some(target) = reply_target
+                && let Err(e) = service.send_menu(target, &text, show).await
+            {
+                tracing::error!("Failed to send Telegram response: {}", e);
+            }
+        }
+        Ok(UpdateResult::FollowUpRequested {
+            session_id,
+            follow_up,
+            text,
+        }) => {
+            let text = match deployment
+                .container()
+                .start_follow_up_or_queue(session_id, follow_up)
+                .await
+            {
+                Ok(true) => text,
+                Ok(false) => format!("{text}\n\nIt will be sent when the current run finishes."),
+                Err(e) => {
+                    tracing::error!("Failed to start Telegram follow-up: {}", e);
+                    format!("{text}\n\n⚠️ Failed to start the agent: {e}")
+                }
+            };
+            if let Some(target) = reply_target
+                && let Err(e) = service.send_reply(target, &text).await
+            {
+                tracing::error!("Failed to send Telegram response: {}", e);
+            }
+        }
+        Ok(UpdateResult::RetryRequested { task_id }) => {
+            let text = match retry_task(deployment, task_id).await {
+                Ok((title, attempt_id)) => format!(
+                    "🔁 Retrying <b>{}</b> in a new attempt: <code>{}</code>",
+                    escape_html(&title),
+                    attempt_id
+                ),
+                Err(e) => {
+                    tracing::error!("Failed to retry task {} from Telegram: {}", task_id, e);
+                    format!("⚠️ Failed to retry the task: {e}")
+                }
+            };
+            if let Some(target) = reply_target
+                && let Err(e) = service.send_reply(target, &text).await
+            {
+                tracing::error!("Failed to send Telegram response: {}", e);
+            }
+        }
+        Ok(UpdateResult::StopRequested { task_id }) => {
+            let text = match stop_task(deployment, task_id).await {
+                Ok(title) => format!(
+                    "⏹ Stopped <b>{}</b>; its attempt was cancelled.",
+                    escape_html(&title)
+                ),
+                Err(e) => {
+                    tracing::error!("Failed to stop task {} from Telegram: {}", task_id, e);
+                    format!("⚠️ Failed to stop the task: {e}")
                 }
+            };
+            if let Some(target) = reply_target
+                && let Err(e) = service.send_reply(target, &text).await
+            {
+                tracing::error!("Failed to send Telegram response: {}", e);
             }
         }
         Ok(UpdateResult::LinkCompleted {
             chat_id,
             user_id: _,
             username,
+            locale,
         }) => {
             // Save the updated config to disk
             let config = deployment.config().read().await.clone();
@@ -147,14 +414,26 @@ async fn webhook(
             }
 
             // Send confirmation message
-            let message = format!(
-                "✅ <b>Account linked successfully!</b>\n\nWelcome{}! You will now receive notifications for task completions.",
-                username.as_ref().map(|u| format!(", @{}", u)).unwrap_or_default()
-            );
+            let message = BotMessage::LinkCompleted {
+                username: username.as_deref(),
+            }
+            .render(locale);
             if let Err(e) = service.send_message(chat_id, &message).await {
                 tracing::error!("Failed to send link confirmation: {}", e);
             }
         }
+        Ok(UpdateResult::SettingsChanged(text)) => {
+            let config = deployment.config().read().await.clone();
+            if let Err(e) = save_config_to_file(&config, &config_path()).await {
+                tracing::error!("Failed to save config after Telegram command: {}", e);
+            }
+
+            if let Some(target) = reply_target
+                && let Err(e) = service.send_reply(target, &text).await
+            {
+                tracing::error!("Failed to send Telegram response: {}", e);
+            }
+        }
         Ok(UpdateResult::NoResponse) => {
             // No response needed
         }
@@ -163,33 +442,160 @@ async fn webhook(
             // Don't return error to Telegram - just log it
         }
     }
+}
 
-    Ok(StatusCode::OK)
+/// Response format for GET /api/telegram/link: `TelegramLinkInfo` as JSON, or
+/// a PNG (`qr`) or SVG (`qr_svg`) QR code of the deep link
+#[derive(Debug, Clone, Copy, Default, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum TelegramLinkFormat {
+    #[default]
+    Json,
+    Qr,
+    QrSvg,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct TelegramLinkQuery {
+    #[serde(default)]
+    pub format: TelegramLinkFormat,
+}
+
+/// Smallest rendered QR code edge, in pixels
+const QR_MIN_SIZE: u32 = 256;
+
+fn qr_response(deep_link: &str, format: TelegramLinkFormat) -> Result<Response, ApiError> {
+    let code = QrCode::new(deep_link.as_bytes())
+        .map_err(|e| ApiError::BadRequest(format!("Failed to encode QR code: {e}")))?;
+
+    let (content_type, body) = match format {
+        TelegramLinkFormat::QrSvg => {
+            let svg = code
+                .render::<svg::Color>()
+                .min_dimensions(QR_MIN_SIZE, QR_MIN_SIZE)
+                .build();
+            ("image/svg+xml", svg.into_bytes())
+        }
+        _ => {
+            let image = code
+                .render::<image::Luma<u8>>()
+                .min_dimensions(QR_MIN_SIZE, QR_MIN_SIZE)
+                .build();
+            let mut png = Vec::new();
+            image
+                .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+                .map_err(|e| ApiError::BadRequest(format!("Failed to render QR code: {e}")))?;
+            ("image/png", png)
+        }
+    };
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type),
+            // Each request mints a new link token
+            (header::CACHE_CONTROL, "no-store"),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// Start a new attempt of `task_id` on the repositories and with the agent of
+/// its latest attempt, returning the task's title and the new attempt's ID
+async fn retry_task(
+    deployment: &DeploymentImpl,
+    task_id: Uuid,
+) -> Result<(String, Uuid), ApiError> {
+    let pool = &deployment.db().pool;
+    let task = Task::find_by_id(pool, task_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let previous = Workspace::fetch_all(pool, Some(task.id))
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| ApiError::BadRequest("The task has no attempt to retry".to_string()))?;
+
+    let repos: Vec<WorkspaceRepoInput> = WorkspaceRepo::find_by_workspace_id(pool, previous.id)
+        .await?
+        .into_iter()
+        .map(|repo| WorkspaceRepoInput {
+            repo_id: repo.repo_id,
+            target_branch: repo.target_branch,
+        })
+        .collect();
+    let session = Session::find_latest_by_workspace_id(pool, previous.id).await?;
+    let executor_profile_id = match session {
+        Some(session) => {
+            ExecutionProcess::latest_executor_profile_for_session(pool, session.id).await?
+        }
+        None => None,
+    };
+    let executor_profile_id = match executor_profile_id {
+        Some(profile) => profile,
+        None => deployment.config().read().await.executor_profile.clone(),
+    };
+
+    let workspace = create_workspace(deployment, &task, &repos).await?;
+    deployment
+        .container()
+        .start_workspace(&workspace, executor_profile_id)
+        .await?;
+    Ok((task.title, workspace.id))
+}
+
+/// Kill the running agent processes of `task_id`'s attempts, marking them as
+/// killed, and return the task's title
+async fn stop_task(deployment: &DeploymentImpl, task_id: Uuid) -> Result<String, ApiError> {
+    let pool = &deployment.db().pool;
+    let task = Task::find_by_id(pool, task_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    for workspace in Workspace::fetch_all(pool, Some(task.id)).await? {
+        if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(pool, workspace.id)
+            .await?
+        {
+            deployment.container().try_stop(&workspace, false).await;
+        }
+    }
+    Ok(task.title)
 }
 
 /// GET /api/telegram/link
 ///
-/// Generate a deep link for Telegram account linking.
+/// Generate a deep link for Telegram account linking. With `?format=qr` (PNG)
+/// or `?format=qr_svg` the link is returned as a QR code image instead.
 async fn get_link(
     State(deployment): State<DeploymentImpl>,
-) -> Result<ResponseJson<ApiResponse<TelegramLinkInfo>>, ApiError> {
+    Query(query): Query<TelegramLinkQuery>,
+) -> Result<Response, ApiError> {
     let Some(service) = deployment.telegram_service() else {
+        if !matches!(query.format, TelegramLinkFormat::Json) {
+            return Err(ApiError::BadRequest(
+                "Telegram bot is not configured".to_string(),
+            ));
+        }
         return Ok(ResponseJson(ApiResponse::success(TelegramLinkInfo {
             token: String::new(),
             deep_link: String::new(),
             bot_configured: false,
-        })));
+        }))
+        .into_response());
     };
 
     let (token, deep_link) = service
         .generate_link_token()
         .map_err(|e| ApiError::BadRequest(e.to_string()))?;
 
-    Ok(ResponseJson(ApiResponse::success(TelegramLinkInfo {
-        token,
-        deep_link,
-        bot_configured: true,
-    })))
+    match query.format {
+        TelegramLinkFormat::Json => Ok(ResponseJson(ApiResponse::success(TelegramLinkInfo {
+            token,
+            deep_link,
+            bot_configured: true,
+        }))
+        .into_response()),
+        format => qr_response(&deep_link, format),
+    }
 }
 
 /// DELETE /api/telegram/unlink
@@ -224,7 +630,13 @@ async fn get_status(
         (TelegramConfig::default(), false)
     };
 
-    let mut response = TelegramStatusResponse::from(status);
+    let preferences = deployment
+        .config()
+        .read()
+        .await
+        .notification_preferences
+        .clone();
+    let mut response = TelegramStatusResponse::new(status, &preferences);
     response.bot_configured = is_configured;
 
     Ok(ResponseJson(ApiResponse::success(response)))
@@ -240,12 +652,7 @@ async fn update_settings(
     let service = get_telegram_service(&deployment)?;
 
     let updated = service
-        .update_settings(
-            request.notifications_enabled,
-            request.notify_on_task_done,
-            request.include_llm_summary,
-            request.stream_enabled,
-        )
+        .update_settings(request.into())
         .await
         .map_err(|e| ApiError::BadRequest(e.to_string()))?;
 
@@ -255,12 +662,201 @@ async fn update_settings(
         tracing::error!("Failed to save Telegram settings: {}", e);
     }
 
-    let mut response = TelegramStatusResponse::from(updated);
+    let mut response = TelegramStatusResponse::new(updated, &config.notification_preferences);
     response.bot_configured = true;
 
     Ok(ResponseJson(ApiResponse::success(response)))
 }
 
+/// Request body for changing a linked account's role
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct SetTelegramAccountRoleRequest {
+    pub role: TelegramAccountRole,
+}
+
+/// GET /api/telegram/accounts
+///
+/// List the Telegram accounts linked to the bot.
+async fn list_accounts(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<TelegramAccount>>>, ApiError> {
+    let accounts = TelegramAccount::find_all(&deployment.db().pool).await?;
+    Ok(ResponseJson(ApiResponse::success(accounts)))
+}
+
+/// PUT /api/telegram/accounts/{user_id}/role
+///
+/// Change a linked account's role. The primary and allowlisted users stay
+/// admins regardless.
+async fn set_account_role(
+    State(deployment): State<DeploymentImpl>,
+    Path(user_id): Path<i64>,
+    Json(request): Json<SetTelegramAccountRoleRequest>,
+) -> Result<StatusCode, ApiError> {
+    if !TelegramAccount::set_role(&deployment.db().pool, user_id, request.role).await? {
+        return Err(ApiError::NotFound(format!(
+            "Telegram user {} has not linked an account",
+            user_id
+        )));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Request body for changing how a linked account gets its notifications
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct SetTelegramDigestModeRequest {
+    pub digest_mode: TelegramDigestMode,
+}
+
+/// PUT /api/telegram/accounts/{user_id}/digest
+///
+/// Change whether a linked account gets each notification as it happens or
+/// an hourly or daily digest.
+async fn set_account_digest_mode(
+    State(deployment): State<DeploymentImpl>,
+    Path(user_id): Path<i64>,
+    Json(request): Json<SetTelegramDigestModeRequest>,
+) -> Result<StatusCode, ApiError> {
+    if !TelegramAccount::set_digest_mode(&deployment.db().pool, user_id, request.digest_mode)
+        .await?
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Telegram user {} has not linked an account",
+            user_id
+        )));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Request body for swapping in a new bot token
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct RotateTelegramTokenRequest {
+    /// The new token from BotFather
+    pub token: String,
+    /// Rotate this project's own bot instead of the default one
+    #[serde(default)]
+    pub project_id: Option<Uuid>,
+}
+
+/// Response to a token rotation
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct TelegramTokenRotation {
+    /// Username of the bot the new token belongs to
+    pub bot_username: String,
+}
+
+/// POST /api/telegram/rotate-token
+///
+/// Validate a new bot token and swap it into the running bot. The default
+/// bot's token comes from `TELEGRAM_BOT_TOKEN`, which must be updated as well
+/// for the new token to survive a restart; project bots are saved to the
+/// config, and the request fails if that does not work.
+async fn rotate_token(
+    State(deployment): State<DeploymentImpl>,
+    Json(request): Json<RotateTelegramTokenRequest>,
+) -> Result<ResponseJson<ApiResponse<TelegramTokenRotation>>, ApiError> {
+    let service = get_telegram_service(&deployment)?;
+    let token = request.token.trim().to_string();
+    if token.is_empty() {
+        return Err(ApiError::BadRequest("Token must not be empty".to_string()));
+    }
+
+    let bot_username = match request.project_id {
+        Some(project_id) => service.rotate_project_token(project_id, token).await?,
+        None => service.rotate_token(token).await?,
+    };
+    // Keeps a rotated project token, and a webhook secret generated while
+    // registering the webhook again
+    let config = deployment.config().read().await.clone();
+    if let Err(e) = save_config_to_file(&config, &config_path()).await {
+        if request.project_id.is_some() {
+            return Err(ApiError::Io(std::io::Error::other(format!(
+                "The new token is in use but was not saved to the config, so it will be lost \
+                 on restart: {e}"
+            ))));
+        }
+        tracing::error!("Failed to save config after Telegram token rotation: {}", e);
+    }
+
+    Ok(ResponseJson(ApiResponse::success(TelegramTokenRotation {
+        bot_username,
+    })))
+}
+
+/// Request body for previewing a notification. Unset fields are filled with
+/// sample text.
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct TelegramPreviewRequest {
+    pub kind: TaskNotificationKind,
+    /// Take the title from this task
+    #[serde(default)]
+    pub task_id: Option<Uuid>,
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Shown when LLM summaries are on
+    #[serde(default)]
+    pub summary: Option<String>,
+    /// Shown for failures
+    #[serde(default)]
+    pub error_excerpt: Option<String>,
+}
+
+/// POST /api/telegram/preview
+///
+/// Render a task notification with the current settings, returning exactly
+/// what would be sent, without sending anything.
+async fn preview_notification(
+    State(deployment): State<DeploymentImpl>,
+    Json(request): Json<TelegramPreviewRequest>,
+) -> Result<ResponseJson<ApiResponse<NotificationPreview>>, ApiError> {
+    let service = get_telegram_service(&deployment)?;
+
+    let task_title = match request.task_id {
+        Some(task_id) => Some(
+            Task::find_by_id(&deployment.db().pool, task_id)
+                .await?
+                .ok_or(TelegramError::TaskNotFound(task_id))?
+                .title,
+        ),
+        None => None,
+    };
+    let title = request
+        .title
+        .or(task_title)
+        .unwrap_or_else(|| "Add dark mode to the settings page".to_string());
+    let summary = request.summary.unwrap_or_else(|| {
+        "Added a theme toggle to the settings page and persisted the choice.".to_string()
+    });
+    let error_excerpt = request
+        .error_excerpt
+        .unwrap_or_else(|| "error: test suite failed (2 failures)".to_string());
+
+    let preview = service
+        .preview_notification(request.kind, &title, Some(&summary), Some(&error_excerpt))
+        .await;
+    Ok(ResponseJson(ApiResponse::success(preview)))
+}
+
+/// GET /api/telegram/setup-state
+///
+/// Check each step of the bot setup (token, username, webhook, linked chat)
+/// against Telegram, with what to do next for the ones not done. Works before
+/// a bot token is configured, reporting every step as missing.
+async fn get_setup_state(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<TelegramSetupState>>, ApiError> {
+    let state = match deployment.telegram_service() {
+        Some(service) => service.setup_state().await,
+        None => TelegramSetupState::without_bot(&deployment.config().read().await.telegram),
+    };
+    Ok(ResponseJson(ApiResponse::success(state)))
+}
+
 // Convert TelegramError to ApiError
 impl From<TelegramError> for ApiError {
     fn from(err: TelegramError) -> Self {
@@ -271,6 +867,10 @@ impl From<TelegramError> for ApiError {
             TelegramError::NotLinked => {
                 ApiError::BadRequest("Telegram account is not linked".to_string())
             }
+            e @ (TelegramError::NotAuthorized(_) | TelegramError::ReadOnly(_)) => {
+                ApiError::Forbidden(e.to_string())
+            }
+            e @ TelegramError::Maintenance => ApiError::ServiceUnavailable(e.to_string()),
             TelegramError::InvalidLinkToken => {
                 ApiError::BadRequest("Invalid link token".to_string())
             }
@@ -289,6 +889,22 @@ impl From<TelegramError> for ApiError {
                 ApiError::BadRequest("No active project set".to_string())
             }
             TelegramError::InvalidCommand(msg) => ApiError::BadRequest(msg),
+            TelegramError::Attachment(msg) => {
+                ApiError::BadRequest(format!("Failed to store attachment: {}", msg))
+            }
+            TelegramError::QueueMessage(msg) => {
+                ApiError::BadRequest(format!("Failed to queue message: {}", msg))
+            }
+            TelegramError::Logs(msg) => {
+                ApiError::BadRequest(format!("Failed to read logs: {}", msg))
+            }
+            TelegramError::Diff(msg) => {
+                ApiError::BadRequest(format!("Failed to compute diff: {}", msg))
+            }
+            TelegramError::Merge(msg) => {
+                ApiError::BadRequest(format!("Failed to merge PR: {}", msg))
+            }
+            e @ TelegramError::WrongBot { .. } => ApiError::BadRequest(e.to_string()),
         }
     }
 }
@@ -0,0 +1,141 @@
+//! Undo and redo of task edits.
+//!
+//! Clients that send an `X-Client-Session` header with their task edits get
+//! those edits journaled per session, and can then step back and forth
+//! through them without keeping any state of their own.
+//!
+//! Endpoints:
+//! - POST /api/undo - Revert the session's latest edit
+//! - POST /api/redo - Re-apply the session's latest undone edit
+
+use axum::{
+    Router, extract::State, http::HeaderMap, response::Json as ResponseJson, routing::post,
+};
+use db::models::{
+    task::Task,
+    task_mutation::{TaskMutation, TaskSnapshot},
+};
+use deployment::Deployment;
+use services::services::{outbox::OutboxIntent, telegram::TaskNotificationKind};
+use sqlx::Error as SqlxError;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Header naming the client session an edit belongs to
+pub const CLIENT_SESSION_HEADER: &str = "x-client-session";
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new()
+        .route("/undo", post(undo))
+        .route("/redo", post(redo))
+}
+
+/// The client session of a request, if it names one
+pub fn client_session(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(CLIENT_SESSION_HEADER)?
+        .to_str()
+        .ok()
+        .map(str::trim)
+        .filter(|session| !session.is_empty())
+}
+
+fn required_client_session(headers: &HeaderMap) -> Result<&str, ApiError> {
+    client_session(headers).ok_or_else(|| {
+        ApiError::BadRequest(format!("The {CLIENT_SESSION_HEADER} header is required"))
+    })
+}
+
+/// POST /api/undo
+///
+/// Revert the session's latest edit and return the restored task, or null
+/// when there is nothing to undo.
+async fn undo(
+    State(deployment): State<DeploymentImpl>,
+    headers: HeaderMap,
+) -> Result<ResponseJson<ApiResponse<Option<Task>>>, ApiError> {
+    let session = required_client_session(&headers)?;
+    let Some(mutation) = TaskMutation::find_latest_undoable(&deployment.db().pool, session).await?
+    else {
+        return Ok(ResponseJson(ApiResponse::success(None)));
+    };
+
+    let task = apply(&deployment, &mutation, true).await?;
+    Ok(ResponseJson(ApiResponse::success(Some(task))))
+}
+
+/// POST /api/redo
+///
+/// Re-apply the session's latest undone edit and return the task, or null
+/// when there is nothing to redo.
+async fn redo(
+    State(deployment): State<DeploymentImpl>,
+    headers: HeaderMap,
+) -> Result<ResponseJson<ApiResponse<Option<Task>>>, ApiError> {
+    let session = required_client_session(&headers)?;
+    let Some(mutation) = TaskMutation::find_latest_redoable(&deployment.db().pool, session).await?
+    else {
+        return Ok(ResponseJson(ApiResponse::success(None)));
+    };
+
+    let task = apply(&deployment, &mutation, false).await?;
+    Ok(ResponseJson(ApiResponse::success(Some(task))))
+}
+
+/// Undo or redo `mutation`, refusing when its task was changed in the meantime
+async fn apply(
+    deployment: &DeploymentImpl,
+    mutation: &TaskMutation,
+    undone: bool,
+) -> Result<Task, ApiError> {
+    let pool = &deployment.db().pool;
+    let (from, to) = if undone {
+        (&mutation.after.0, &mutation.before.0)
+    } else {
+        (&mutation.before.0, &mutation.after.0)
+    };
+    let conflict = |title: &str| {
+        ApiError::Conflict(format!(
+            "'{}' was changed after this edit, so it cannot be {}",
+            title,
+            if undone { "undone" } else { "redone" }
+        ))
+    };
+
+    let current = Task::find_by_id(pool, mutation.task_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    if TaskSnapshot::from(&current) != *from {
+        return Err(conflict(&current.title));
+    }
+
+    let mut tx = pool.begin().await?;
+    let task = Task::update(
+        &mut *tx,
+        current.id,
+        current.project_id,
+        to.title.clone(),
+        to.description.clone(),
+        to.status.clone(),
+        to.parent_workspace_id,
+        Some(current.updated_at),
+    )
+    .await?
+    .ok_or_else(|| conflict(&current.title))?;
+
+    if task.status != current.status
+        && let Some(kind) = TaskNotificationKind::from_status(&task.status)
+    {
+        OutboxIntent::TaskStatusNotification {
+            task_id: task.id,
+            kind,
+        }
+        .enqueue(&mut *tx)
+        .await?;
+    }
+    TaskMutation::set_undone(&mut *tx, mutation.id, undone).await?;
+    tx.commit().await?;
+
+    Ok(task)
+}
@@ -0,0 +1,38 @@
+use axum::{Router, extract::State, response::Json as ResponseJson, routing::post};
+use deployment::Deployment;
+use services::services::vault_export::{self, VaultExportError, VaultExportReport};
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+fn map_vault_export_error(error: VaultExportError) -> ApiError {
+    match error {
+        VaultExportError::Database(e) => ApiError::Database(e),
+        VaultExportError::Io(e) => ApiError::Io(e),
+        e @ VaultExportError::NoDirectory => ApiError::BadRequest(e.to_string()),
+    }
+}
+
+/// Export every task as a markdown note into the configured directory now
+pub async fn run_vault_export(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<VaultExportReport>>, ApiError> {
+    let report = vault_export::export_configured_vault(&deployment.db().pool, deployment.config())
+        .await
+        .map_err(map_vault_export_error)?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "vault_export_run",
+            serde_json::json!({
+                "notes_written": report.notes_written,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(report)))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new().route("/vault-export", post(run_vault_export))
+}
@@ -0,0 +1,114 @@
+//! Browser push subscriptions for task notifications.
+//!
+//! Endpoints:
+//! - GET /api/web-push/vapid-public-key - Key to pass as `applicationServerKey` when subscribing
+//! - GET /api/web-push/subscriptions - List subscribed browsers
+//! - POST /api/web-push/subscriptions - Register a browser's `PushSubscription`
+//! - DELETE /api/web-push/subscriptions - Unregister a browser by its endpoint
+//! - DELETE /api/web-push/subscriptions/{id} - Unregister a browser by ID
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    response::Json as ResponseJson,
+    routing::{delete, get},
+};
+use db::models::web_push_subscription::{CreateWebPushSubscription, WebPushSubscription};
+use deployment::Deployment;
+use serde::{Deserialize, Serialize};
+use services::services::{
+    config::save_config_to_file, container::ContainerService, web_push::validate_subscription,
+};
+use ts_rs::TS;
+use url::Url;
+use utils::{assets::config_path, response::ApiResponse};
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Serialize, TS)]
+pub struct WebPushPublicKey {
+    /// Uncompressed P-256 public key, base64url
+    pub public_key: String,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct DeleteWebPushSubscriptionRequest {
+    pub endpoint: String,
+}
+
+pub async fn get_vapid_public_key(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<WebPushPublicKey>>, ApiError> {
+    let (public_key, generated) = deployment
+        .container()
+        .web_push_notifier()
+        .vapid_public_key()
+        .await?;
+    // Subscriptions are bound to the key, so it must survive restarts
+    if generated {
+        let config = deployment.config().read().await.clone();
+        save_config_to_file(&config, &config_path()).await?;
+    }
+    Ok(ResponseJson(ApiResponse::success(WebPushPublicKey {
+        public_key,
+    })))
+}
+
+pub async fn get_subscriptions(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<WebPushSubscription>>>, ApiError> {
+    let subscriptions = WebPushSubscription::find_all(&deployment.db().pool).await?;
+    Ok(ResponseJson(ApiResponse::success(subscriptions)))
+}
+
+pub async fn create_subscription(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateWebPushSubscription>,
+) -> Result<ResponseJson<ApiResponse<WebPushSubscription>>, ApiError> {
+    if !matches!(Url::parse(&payload.endpoint), Ok(url) if url.scheme() == "https") {
+        return Err(ApiError::BadRequest(format!(
+            "'{}' is not an https URL",
+            payload.endpoint
+        )));
+    }
+    validate_subscription(&payload.keys)?;
+    let subscription = WebPushSubscription::upsert(&deployment.db().pool, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(subscription)))
+}
+
+/// Unregister the browser that unsubscribed from `endpoint`; the browser
+/// does not know the subscription's ID
+pub async fn delete_subscription_by_endpoint(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<DeleteWebPushSubscriptionRequest>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    WebPushSubscription::delete_by_endpoint(&deployment.db().pool, &payload.endpoint).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub async fn delete_subscription(
+    Path(id): Path<Uuid>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let rows_affected = WebPushSubscription::delete(&deployment.db().pool, id).await?;
+    if rows_affected == 0 {
+        Err(ApiError::Database(sqlx::Error::RowNotFound))
+    } else {
+        Ok(ResponseJson(ApiResponse::success(())))
+    }
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    let inner = Router::new()
+        .route("/vapid-public-key", get(get_vapid_public_key))
+        .route(
+            "/subscriptions",
+            get(get_subscriptions)
+                .post(create_subscription)
+                .delete(delete_subscription_by_endpoint),
+        )
+        .route("/subscriptions/{id}", delete(delete_subscription));
+
+    Router::new().nest("/web-push", inner)
+}
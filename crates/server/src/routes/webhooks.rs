@@ -0,0 +1,208 @@
+//! Outgoing webhook endpoints and their delivery log.
+//!
+//! Endpoints:
+//! - GET /api/webhooks - List endpoints
+//! - POST /api/webhooks - Add an endpoint; the response holds its signing secret
+//! - PUT /api/webhooks/{id} - Update an endpoint
+//! - DELETE /api/webhooks/{id} - Remove an endpoint and its delivery log
+//! - POST /api/webhooks/{id}/rotate-secret - Replace the signing secret
+//! - GET /api/webhooks/{id}/deliveries - Recent deliveries, newest first
+//! - POST /api/webhooks/{id}/deliveries/{delivery_id}/redeliver - Send a delivery again
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    response::Json as ResponseJson,
+    routing::{get, post, put},
+};
+use db::models::{
+    webhook_delivery::WebhookDelivery,
+    webhook_endpoint::{CreateWebhookEndpoint, UpdateWebhookEndpoint, WebhookEndpoint},
+};
+use deployment::Deployment;
+use rand::{Rng, distributions::Alphanumeric};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use url::Url;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+const SECRET_PREFIX: &str = "whsec_";
+const SECRET_LEN: usize = 32;
+const DEFAULT_DELIVERY_LIMIT: i64 = 50;
+const MAX_DELIVERY_LIMIT: i64 = 500;
+/// How long the previous secret keeps signing requests after a rotation
+const DEFAULT_SECRET_GRACE_SECS: i64 = 24 * 60 * 60;
+const MAX_SECRET_GRACE_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// An endpoint along with its signing secret, which is only ever returned
+/// when the endpoint is created or its secret rotated
+#[derive(Debug, Serialize, TS)]
+pub struct WebhookEndpointWithSecret {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub endpoint: WebhookEndpoint,
+    pub secret: String,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct WebhookDeliveryQuery {
+    /// Most deliveries to return, 50 by default
+    #[serde(default)]
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct RotateWebhookSecretRequest {
+    /// Seconds the previous secret keeps signing requests alongside the new
+    /// one, a day by default; 0 drops it right away
+    #[serde(default)]
+    pub grace_period_secs: Option<i64>,
+}
+
+fn generate_secret() -> String {
+    let random: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(SECRET_LEN)
+        .map(char::from)
+        .collect();
+    format!("{SECRET_PREFIX}{random}")
+}
+
+fn validate_url(url: &str) -> Result<(), ApiError> {
+    match Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(()),
+        _ => Err(ApiError::BadRequest(format!(
+            "'{url}' is not an http or https URL"
+        ))),
+    }
+}
+
+async fn find_endpoint(deployment: &DeploymentImpl, id: Uuid) -> Result<WebhookEndpoint, ApiError> {
+    WebhookEndpoint::find_by_id(&deployment.db().pool, id)
+        .await?
+        .ok_or(ApiError::Database(sqlx::Error::RowNotFound))
+}
+
+pub async fn get_webhooks(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<WebhookEndpoint>>>, ApiError> {
+    let endpoints = WebhookEndpoint::find_all(&deployment.db().pool).await?;
+    Ok(ResponseJson(ApiResponse::success(endpoints)))
+}
+
+pub async fn create_webhook(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateWebhookEndpoint>,
+) -> Result<ResponseJson<ApiResponse<WebhookEndpointWithSecret>>, ApiError> {
+    validate_url(&payload.url)?;
+    let secret = generate_secret();
+    let endpoint = WebhookEndpoint::create(&deployment.db().pool, &payload, &secret).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "webhook_endpoint_created",
+            serde_json::json!({
+                "format": endpoint.format,
+                "events": endpoint.events.0,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(
+        WebhookEndpointWithSecret { endpoint, secret },
+    )))
+}
+
+pub async fn update_webhook(
+    Path(id): Path<Uuid>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpdateWebhookEndpoint>,
+) -> Result<ResponseJson<ApiResponse<WebhookEndpoint>>, ApiError> {
+    if let Some(url) = &payload.url {
+        validate_url(url)?;
+    }
+    let endpoint = find_endpoint(&deployment, id).await?;
+    let updated = WebhookEndpoint::update(&deployment.db().pool, endpoint.id, &payload).await?;
+    Ok(ResponseJson(ApiResponse::success(updated)))
+}
+
+pub async fn delete_webhook(
+    Path(id): Path<Uuid>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let rows_affected = WebhookEndpoint::delete(&deployment.db().pool, id).await?;
+    if rows_affected == 0 {
+        Err(ApiError::Database(sqlx::Error::RowNotFound))
+    } else {
+        Ok(ResponseJson(ApiResponse::success(())))
+    }
+}
+
+/// Replace the endpoint's signing secret. Until the grace period ends, each
+/// request carries signatures under both secrets, so the receiver can be
+/// switched to the new one without rejecting any deliveries.
+pub async fn rotate_secret(
+    Path(id): Path<Uuid>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<RotateWebhookSecretRequest>,
+) -> Result<ResponseJson<ApiResponse<WebhookEndpointWithSecret>>, ApiError> {
+    let endpoint = find_endpoint(&deployment, id).await?;
+    let grace_secs = payload
+        .grace_period_secs
+        .unwrap_or(DEFAULT_SECRET_GRACE_SECS)
+        .clamp(0, MAX_SECRET_GRACE_SECS);
+    let secret = generate_secret();
+    let endpoint =
+        WebhookEndpoint::rotate_secret(&deployment.db().pool, endpoint.id, &secret, grace_secs)
+            .await?;
+    Ok(ResponseJson(ApiResponse::success(
+        WebhookEndpointWithSecret { endpoint, secret },
+    )))
+}
+
+pub async fn get_deliveries(
+    Path(id): Path<Uuid>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<WebhookDeliveryQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<WebhookDelivery>>>, ApiError> {
+    let endpoint = find_endpoint(&deployment, id).await?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_DELIVERY_LIMIT)
+        .clamp(1, MAX_DELIVERY_LIMIT);
+    let deliveries =
+        WebhookDelivery::find_by_endpoint_id(&deployment.db().pool, endpoint.id, limit).await?;
+    Ok(ResponseJson(ApiResponse::success(deliveries)))
+}
+
+/// Queue a delivery to be sent again with its original payload, e.g. after
+/// fixing the receiving end
+pub async fn redeliver(
+    Path((id, delivery_id)): Path<(Uuid, Uuid)>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<WebhookDelivery>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let delivery = WebhookDelivery::find_by_id(pool, delivery_id)
+        .await?
+        .filter(|delivery| delivery.endpoint_id == id)
+        .ok_or(ApiError::Database(sqlx::Error::RowNotFound))?;
+    WebhookDelivery::redeliver(pool, delivery.id).await?;
+    let delivery = WebhookDelivery::find_by_id(pool, delivery.id)
+        .await?
+        .ok_or(ApiError::Database(sqlx::Error::RowNotFound))?;
+    Ok(ResponseJson(ApiResponse::success(delivery)))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    let inner = Router::new()
+        .route("/", get(get_webhooks).post(create_webhook))
+        .route("/{id}", put(update_webhook).delete(delete_webhook))
+        .route("/{id}/rotate-secret", post(rotate_secret))
+        .route("/{id}/deliveries", get(get_deliveries))
+        .route("/{id}/deliveries/{delivery_id}/redeliver", post(redeliver));
+
+    Router::new().nest("/webhooks", inner)
+}
@@ -0,0 +1,73 @@
+//! WhatsApp Cloud API webhook.
+//!
+//! Endpoints:
+//! - GET /api/whatsapp/webhook - Answer Meta's check when the webhook is subscribed (bypasses origin validation)
+//! - POST /api/whatsapp/webhook - Receive incoming messages (bypasses origin validation)
+
+use axum::{
+    Router,
+    body::Bytes,
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::get,
+};
+use serde::Deserialize;
+use services::services::{container::ContainerService, whatsapp::WebhookPayload};
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize)]
+struct VerifyQuery {
+    #[serde(rename = "hub.mode", default)]
+    mode: String,
+    #[serde(rename = "hub.verify_token", default)]
+    verify_token: String,
+    #[serde(rename = "hub.challenge", default)]
+    challenge: String,
+}
+
+/// Create a router for the endpoints Meta calls, which bypass origin
+/// validation. Incoming messages are authenticated by their signature.
+pub fn webhook_router() -> Router<DeploymentImpl> {
+    Router::new().route("/whatsapp/webhook", get(verify).post(receive))
+}
+
+/// GET /api/whatsapp/webhook
+async fn verify(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<VerifyQuery>,
+) -> Result<String, ApiError> {
+    let challenge = deployment
+        .container()
+        .whatsapp_service()
+        .verify_subscription(&query.mode, &query.verify_token, &query.challenge)
+        .await?;
+    Ok(challenge)
+}
+
+/// POST /api/whatsapp/webhook
+///
+/// Run the commands in incoming messages. Meta retries requests that do not
+/// succeed, so only a failed signature check is reported as an error.
+async fn receive(
+    State(deployment): State<DeploymentImpl>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<StatusCode, ApiError> {
+    let service = deployment.container().whatsapp_service();
+    let signature = headers
+        .get("x-hub-signature-256")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    service.verify_signature(&body, signature).await?;
+
+    match serde_json::from_slice::<WebhookPayload>(&body) {
+        Ok(payload) => {
+            if let Err(e) = service.handle_webhook(&payload).await {
+                tracing::warn!("Failed to handle WhatsApp webhook: {}", e);
+            }
+        }
+        Err(e) => tracing::warn!("Invalid WhatsApp webhook payload: {}", e),
+    }
+    Ok(StatusCode::OK)
+}
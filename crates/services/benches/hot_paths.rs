@@ -0,0 +1,273 @@
+//! Benchmarks of the server's hot paths, as a baseline for performance work.
+//!
+//! Run them with `cargo bench -p services --bench hot_paths`. By default they
+//! use a small generated dataset; build with `--features bench-large-dataset`
+//! to generate one closer to a long-lived instance. To compare a change
+//! against the current tree, save a baseline first:
+//!
+//! ```sh
+//! cargo bench -p services --bench hot_paths -- --save-baseline before
+//! # apply the change
+//! cargo bench -p services --bench hot_paths -- --baseline before
+//! ```
+
+use std::{path::Path, sync::Arc};
+
+use criterion::{BatchSize, BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use db::{
+    DBService,
+    models::{
+        execution_process::{CreateExecutionProcess, ExecutionProcess, ExecutionProcessRunReason},
+        execution_process_logs::ExecutionProcessLogs,
+        project::{CreateProject, Project},
+        session::{CreateSession, Session},
+        task::{CreateTask, Task},
+        workspace::{CreateWorkspace, Workspace},
+    },
+};
+use executors::actions::{
+    ExecutorAction, ExecutorActionType,
+    script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
+};
+use frankenstein::Update;
+use services::services::{
+    config::{Config, load_config_from_file},
+    telegram::TelegramService,
+};
+use tokio::{runtime::Runtime, sync::RwLock};
+use utils::{log_msg::LogMsg, msg_store::MsgStore};
+use uuid::Uuid;
+
+/// Shape of the generated dataset
+struct Dataset {
+    projects: usize,
+    tasks_per_project: usize,
+    /// Every n-th task gets a workspace and a session
+    attempt_every: usize,
+}
+
+#[cfg(not(feature = "bench-large-dataset"))]
+const DATASET: Dataset = Dataset {
+    projects: 2,
+    tasks_per_project: 200,
+    attempt_every: 4,
+};
+
+#[cfg(feature = "bench-large-dataset")]
+const DATASET: Dataset = Dataset {
+    projects: 20,
+    tasks_per_project: 2_000,
+    attempt_every: 2,
+};
+
+/// Log lines ingested per iteration
+const LOG_LINES: usize = 1_000;
+
+struct Fixture {
+    db: DBService,
+    /// The project listed by the benchmarks
+    project_id: Uuid,
+    /// An execution process to ingest logs for
+    execution_id: Uuid,
+}
+
+async fn generate_dataset(path: &Path) -> Fixture {
+    let db = DBService::new_at(path)
+        .await
+        .expect("open benchmark database");
+    let pool = &db.pool;
+
+    let mut project_id = Uuid::nil();
+    let mut session_id = Uuid::nil();
+    for p in 0..DATASET.projects {
+        let project = Project::create(
+            pool,
+            &CreateProject {
+                name: format!("Project {p}"),
+                repositories: Vec::new(),
+            },
+            Uuid::new_v4(),
+        )
+        .await
+        .expect("create project");
+        project_id = project.id;
+
+        for t in 0..DATASET.tasks_per_project {
+            let task = Task::create(
+                pool,
+                &CreateTask::from_title_description(
+                    project.id,
+                    format!("Task {t} of project {p}"),
+                    Some(format!(
+                        "Description of task {t}\n\nWith a second paragraph."
+                    )),
+                ),
+                Uuid::new_v4(),
+            )
+            .await
+            .expect("create task");
+            if t % DATASET.attempt_every != 0 {
+                continue;
+            }
+
+            let workspace = Workspace::create(
+                pool,
+                &CreateWorkspace {
+                    branch: format!("vk/bench-{p}-{t}"),
+                    agent_working_dir: None,
+                },
+                Uuid::new_v4(),
+                task.id,
+            )
+            .await
+            .expect("create workspace");
+            let session = Session::create(
+                pool,
+                &CreateSession {
+                    executor: Some("CLAUDE_CODE".to_string()),
+                },
+                Uuid::new_v4(),
+                workspace.id,
+            )
+            .await
+            .expect("create session");
+            session_id = session.id;
+        }
+    }
+
+    let execution = ExecutionProcess::create(
+        pool,
+        &CreateExecutionProcess {
+            session_id,
+            executor_action: ExecutorAction::new(
+                ExecutorActionType::ScriptRequest(ScriptRequest {
+                    script: "echo bench".to_string(),
+                    language: ScriptRequestLanguage::Bash,
+                    context: ScriptContext::SetupScript,
+                    working_dir: None,
+                }),
+                None,
+            ),
+            run_reason: ExecutionProcessRunReason::SetupScript,
+        },
+        Uuid::new_v4(),
+        &[],
+    )
+    .await
+    .expect("create execution process");
+
+    Fixture {
+        db,
+        project_id,
+        execution_id: execution.id,
+    }
+}
+
+fn telegram_update(update_id: u32, text: &str) -> Update {
+    serde_json::from_value(serde_json::json!({
+        "update_id": update_id,
+        "message": {
+            "message_id": update_id,
+            "date": 0,
+            "chat": { "id": 1, "type": "private" },
+            "from": { "id": 1, "is_bot": false, "first_name": "Bench" },
+            "text": text,
+        },
+    }))
+    .expect("valid Telegram update")
+}
+
+fn hot_paths(c: &mut Criterion) {
+    let runtime = Runtime::new().expect("tokio runtime");
+    let dir = tempfile::tempdir().expect("temporary directory");
+    let fixture = runtime.block_on(generate_dataset(&dir.path().join("bench.sqlite")));
+    let pool = &fixture.db.pool;
+    let project_id = fixture.project_id;
+
+    c.bench_function("task_listing", |b| {
+        b.to_async(&runtime).iter(|| async move {
+            Task::find_by_project_id_with_attempt_status(pool, project_id)
+                .await
+                .expect("list tasks")
+        })
+    });
+
+    let mut config = Config::default();
+    config.telegram.user_id = Some(1);
+    config.telegram.chat_id = Some(1);
+    let config = Arc::new(RwLock::new(config));
+    let telegram = &TelegramService::new(None, config, pool.clone());
+    let mut update_id = 0;
+    let mut group = c.benchmark_group("webhook_command_dispatch");
+    for command in ["/help".to_string(), format!("/tasks {project_id}")] {
+        let name = command[1..]
+            .split(' ')
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.to_async(&runtime).iter_batched(
+                || {
+                    update_id += 1;
+                    telegram_update(update_id, &command)
+                },
+                |update| async move {
+                    telegram
+                        .handle_update(update)
+                        .await
+                        .expect("handle command")
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+
+    let lines = &(0..LOG_LINES)
+        .map(|i| LogMsg::Stdout(format!("line {i}: compiling crate {i} of {LOG_LINES}")))
+        .collect::<Vec<_>>();
+    let execution_id = fixture.execution_id;
+    let mut group = c.benchmark_group("log_ingestion");
+    group.throughput(Throughput::Elements(LOG_LINES as u64));
+    group.bench_function("msg_store_push", |b| {
+        b.iter_batched(
+            MsgStore::new,
+            |store| {
+                for line in lines {
+                    store.push(line.clone());
+                }
+                store
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("append_to_database", |b| {
+        b.to_async(&runtime).iter(|| async move {
+            for line in lines {
+                let jsonl_line = serde_json::to_string(line).expect("serialize log line");
+                ExecutionProcessLogs::append_log_line(
+                    pool,
+                    execution_id,
+                    &format!("{jsonl_line}\n"),
+                )
+                .await
+                .expect("append log line");
+            }
+        })
+    });
+    group.finish();
+
+    let config_path = &dir.path().join("config.json");
+    std::fs::write(
+        config_path,
+        serde_json::to_string_pretty(&Config::default()).expect("serialize config"),
+    )
+    .expect("write config");
+    c.bench_function("config_load", |b| {
+        b.to_async(&runtime)
+            .iter(move || load_config_from_file(config_path))
+    });
+}
+
+criterion_group!(benches, hot_paths);
+criterion_main!(benches);
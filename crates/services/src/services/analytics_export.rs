@@ -0,0 +1,508 @@
+//! Analytics export.
+//!
+//! Copies the operational data into `AnalyticsExportConfig::directory` so
+//! heavy reporting queries run against the copy instead of the live SQLite
+//! database. The data is first snapshotted with `VACUUM INTO`, which gives a
+//! consistent copy without blocking writers for long; the Parquet and DuckDB
+//! formats then convert the snapshot with the DuckDB CLI, which reads SQLite
+//! through its `sqlite` extension.
+//!
+//! Credentials never leave the database: columns the anonymized export strips
+//! are blanked in the snapshot and left out of the DuckDB and Parquet files.
+//!
+//! Every export replaces the previous one.
+
+use std::{
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::Arc,
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use db::DBService;
+use serde::Serialize;
+use sqlx::{SqlitePool, sqlite::SqliteConnectOptions};
+use thiserror::Error;
+use tokio::{io::AsyncWriteExt, process::Command, sync::RwLock, time::interval};
+use tracing::{error, info};
+use ts_rs::TS;
+use utils::path::expand_tilde;
+
+use crate::services::{
+    anonymized_export::is_secret_column,
+    config::{AnalyticsExportFormat, Config},
+    vault_export::is_due,
+};
+
+/// Base name of the single-file formats
+const EXPORT_FILE_STEM: &str = "vibe-kanban";
+
+#[derive(Debug, Error)]
+pub enum AnalyticsExportError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("No analytics export directory is configured")]
+    NoDirectory,
+    #[error("DuckDB CLI '{0}' not found; install DuckDB or set analytics_export.duckdb_path")]
+    DuckDbNotFound(String),
+    #[error("DuckDB failed: {0}")]
+    DuckDb(String),
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct AnalyticsExportReport {
+    pub directory: String,
+    pub format: AnalyticsExportFormat,
+    /// Tables copied into the export
+    pub tables: usize,
+}
+
+/// Tables worth exporting, given `(name, sql)` rows of `sqlite_master`.
+/// Internal tables, full-text indexes and their shadow tables are skipped.
+pub fn exported_tables(tables: &[(String, Option<String>)]) -> Vec<String> {
+    let virtual_tables: Vec<&str> = tables
+        .iter()
+        .filter(|(_, sql)| {
+            sql.as_deref().is_some_and(|sql| {
+                sql.trim_start()
+                    .to_uppercase()
+                    .starts_with("CREATE VIRTUAL")
+            })
+        })
+        .map(|(name, _)| name.as_str())
+        .collect();
+    tables
+        .iter()
+        .map(|(name, _)| name)
+        .filter(|name| !name.starts_with("sqlite_") && !name.starts_with("_sqlx"))
+        .filter(|name| {
+            !virtual_tables
+                .iter()
+                .any(|v| *name == v || name.starts_with(&format!("{v}_")))
+        })
+        .cloned()
+        .collect()
+}
+
+/// A credential column of an exported table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretColumn {
+    pub table: String,
+    pub column: String,
+    pub not_null: bool,
+}
+
+/// The credential columns of `tables`. Numeric columns are never
+/// credentials, whatever their name, e.g. `total_tokens`.
+async fn find_secret_columns(
+    pool: &SqlitePool,
+    tables: &[String],
+) -> Result<Vec<SecretColumn>, sqlx::Error> {
+    let mut secrets = Vec::new();
+    for table in tables {
+        let columns: Vec<(String, String, bool)> = sqlx::query_as(&format!(
+            "SELECT name, type, \"notnull\" FROM pragma_table_info({})",
+            sql_string(table)
+        ))
+        .fetch_all(pool)
+        .await?;
+        secrets.extend(
+            columns
+                .into_iter()
+                .filter(|(column, declared_type, _)| {
+                    let declared_type = declared_type.to_uppercase();
+                    !["INT", "REAL", "FLOA", "DOUB"]
+                        .iter()
+                        .any(|numeric| declared_type.contains(numeric))
+                        && is_secret_column(column)
+                })
+                .map(|(column, _, not_null)| SecretColumn {
+                    table: table.clone(),
+                    column,
+                    not_null,
+                }),
+        );
+    }
+    Ok(secrets)
+}
+
+fn sql_string(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+pub(crate) fn sql_identifier(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+/// DuckDB script converting the `snapshot` database into `target`, a
+/// directory of Parquet files or a DuckDB database file, without the
+/// `secrets` columns
+pub fn duckdb_script(
+    format: AnalyticsExportFormat,
+    snapshot: &Path,
+    target: &Path,
+    tables: &[String],
+    secrets: &[SecretColumn],
+) -> String {
+    let mut script = format!(
+        "ATTACH {} AS src (TYPE sqlite, READ_ONLY);\n",
+        sql_string(&snapshot.to_string_lossy())
+    );
+    if format == AnalyticsExportFormat::DuckDb {
+        script.push_str(&format!(
+            "ATTACH {} AS dst;\n",
+            sql_string(&target.to_string_lossy())
+        ));
+    }
+    for table in tables {
+        let excluded: Vec<String> = secrets
+            .iter()
+            .filter(|secret| &secret.table == table)
+            .map(|secret| sql_identifier(&secret.column))
+            .collect();
+        let columns = if excluded.is_empty() {
+            "*".to_string()
+        } else {
+            format!("* EXCLUDE ({})", excluded.join(", "))
+        };
+        let source = format!("src.{}", sql_identifier(table));
+        let statement = match format {
+            AnalyticsExportFormat::DuckDb => format!(
+                "CREATE TABLE dst.{} AS SELECT {columns} FROM {source};\n",
+                sql_identifier(table)
+            ),
+            _ => format!(
+                "COPY (SELECT {columns} FROM {source}) TO {} (FORMAT parquet);\n",
+                sql_string(&target.join(format!("{table}.parquet")).to_string_lossy())
+            ),
+        };
+        script.push_str(&statement);
+    }
+    script
+}
+
+/// Write a consistent copy of the live database to `target`, replacing it,
+/// with the `secrets` columns blanked
+async fn snapshot_database(
+    pool: &SqlitePool,
+    target: &Path,
+    secrets: &[SecretColumn],
+) -> Result<(), AnalyticsExportError> {
+    let partial = target.with_extension("partial");
+    // VACUUM INTO refuses to overwrite an existing file
+    if tokio::fs::try_exists(&partial).await? {
+        tokio::fs::remove_file(&partial).await?;
+    }
+    sqlx::query("VACUUM INTO $1")
+        .bind(partial.to_string_lossy().to_string())
+        .execute(pool)
+        .await?;
+    blank_secret_columns(&partial, secrets).await?;
+    tokio::fs::rename(&partial, target).await?;
+    Ok(())
+}
+
+/// Overwrite the `secrets` columns of the database at `path`, then vacuum it
+/// so the old values do not linger in free pages. Values of NOT NULL columns
+/// stay distinct, as some are UNIQUE.
+async fn blank_secret_columns(path: &Path, secrets: &[SecretColumn]) -> Result<(), sqlx::Error> {
+    if secrets.is_empty() {
+        return Ok(());
+    }
+    let snapshot = SqlitePool::connect_with(SqliteConnectOptions::new().filename(path)).await?;
+    for secret in secrets {
+        let blank = if secret.not_null {
+            "printf('redacted-%d', rowid)"
+        } else {
+            "NULL"
+        };
+        sqlx::query(&format!(
+            "UPDATE {} SET {} = {blank}",
+            sql_identifier(&secret.table),
+            sql_identifier(&secret.column)
+        ))
+        .execute(&snapshot)
+        .await?;
+    }
+    sqlx::query("VACUUM").execute(&snapshot).await?;
+    snapshot.close().await;
+    Ok(())
+}
+
+async fn run_duckdb(binary: &str, script: &str) -> Result<(), AnalyticsExportError> {
+    let mut child = Command::new(binary)
+        .args(["-batch", "-bail"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                AnalyticsExportError::DuckDbNotFound(binary.to_string())
+            }
+            _ => e.into(),
+        })?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(script.as_bytes()).await?;
+    }
+
+    let output = child.wait_with_output().await?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(AnalyticsExportError::DuckDb(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+pub async fn export_analytics(
+    pool: &SqlitePool,
+    directory: &Path,
+    format: AnalyticsExportFormat,
+    duckdb_path: Option<&str>,
+) -> Result<AnalyticsExportReport, AnalyticsExportError> {
+    tokio::fs::create_dir_all(directory).await?;
+    let rows: Vec<(String, Option<String>)> =
+        sqlx::query_as("SELECT name, sql FROM sqlite_master WHERE type = 'table' ORDER BY name")
+            .fetch_all(pool)
+            .await?;
+    let tables = exported_tables(&rows);
+    let secrets = find_secret_columns(pool, &tables).await?;
+
+    if format == AnalyticsExportFormat::Sqlite {
+        let target = directory.join(format!("{EXPORT_FILE_STEM}.sqlite"));
+        snapshot_database(pool, &target, &secrets).await?;
+    } else {
+        let staging = tempfile::tempdir()?;
+        let snapshot = staging.path().join("snapshot.sqlite");
+        snapshot_database(pool, &snapshot, &secrets).await?;
+
+        let binary = duckdb_path.unwrap_or("duckdb");
+        if format == AnalyticsExportFormat::DuckDb {
+            // Built next to the target and swapped in, so readers never see a partial file
+            let target = directory.join(format!("{EXPORT_FILE_STEM}.duckdb"));
+            let partial = target.with_extension("partial");
+            if tokio::fs::try_exists(&partial).await? {
+                tokio::fs::remove_file(&partial).await?;
+            }
+            let script = duckdb_script(format, &snapshot, &partial, &tables, &secrets);
+            run_duckdb(binary, &script).await?;
+            tokio::fs::rename(&partial, &target).await?;
+        } else {
+            let script = duckdb_script(format, &snapshot, directory, &tables, &secrets);
+            run_duckdb(binary, &script).await?;
+        }
+    }
+
+    Ok(AnalyticsExportReport {
+        directory: directory.to_string_lossy().to_string(),
+        format,
+        tables: tables.len(),
+    })
+}
+
+/// Export into the configured directory, in the configured format
+pub async fn export_configured_analytics(
+    pool: &SqlitePool,
+    config: &RwLock<Config>,
+) -> Result<AnalyticsExportReport, AnalyticsExportError> {
+    let directory = configured_directory(config)
+        .await
+        .ok_or(AnalyticsExportError::NoDirectory)?;
+    let (format, duckdb_path) = {
+        let config = config.read().await;
+        (
+            config.analytics_export.format,
+            config
+                .analytics_export
+                .duckdb_path
+                .as_deref()
+                .map(str::trim)
+                .filter(|path| !path.is_empty())
+                .map(str::to_string),
+        )
+    };
+    export_analytics(pool, &directory, format, duckdb_path.as_deref()).await
+}
+
+async fn configured_directory(config: &RwLock<Config>) -> Option<PathBuf> {
+    let config = config.read().await;
+    config
+        .analytics_export
+        .directory
+        .as_deref()
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(expand_tilde)
+}
+
+/// Service that runs the analytics export on its configured schedule
+pub struct AnalyticsExportService {
+    db: DBService,
+    config: Arc<RwLock<Config>>,
+    poll_interval: Duration,
+    last_export: Option<DateTime<Utc>>,
+}
+
+impl AnalyticsExportService {
+    pub async fn spawn(db: DBService, config: Arc<RwLock<Config>>) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            db,
+            config,
+            poll_interval: Duration::from_secs(60),
+            last_export: None,
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(mut self) {
+        info!(
+            "Starting analytics export service with interval {:?}",
+            self.poll_interval
+        );
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            let schedule = self.config.read().await.analytics_export.schedule;
+            let now = Utc::now();
+            if !is_due(schedule, now, self.last_export)
+                || configured_directory(&self.config).await.is_none()
+            {
+                continue;
+            }
+
+            self.last_export = Some(now);
+            match export_configured_analytics(&self.db.pool, &self.config).await {
+                Ok(report) => info!(
+                    "Exported {} tables as {:?} to {}",
+                    report.tables, report.format, report.directory
+                ),
+                Err(e) => error!("Error exporting analytics data: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exported_tables_skips_internal_and_fts_tables() {
+        let table = |name: &str, sql: &str| (name.to_string(), Some(sql.to_string()));
+        let rows = vec![
+            table("_sqlx_migrations", "CREATE TABLE _sqlx_migrations (...)"),
+            table("projects", "CREATE TABLE projects (...)"),
+            table("sqlite_sequence", "CREATE TABLE sqlite_sequence(name,seq)"),
+            table("tasks", "CREATE TABLE tasks (...)"),
+            table(
+                "tasks_fts",
+                "CREATE VIRTUAL TABLE tasks_fts USING fts5(...)",
+            ),
+            table("tasks_fts_data", "CREATE TABLE 'tasks_fts_data'(...)"),
+            table("tasks_fts_idx", "CREATE TABLE 'tasks_fts_idx'(...)"),
+        ];
+
+        assert_eq!(exported_tables(&rows), vec!["projects", "tasks"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_duckdb_script_for_parquet() {
+        let script = duckdb_script(
+            AnalyticsExportFormat::Parquet,
+            Path::new("/tmp/snap's.sqlite"),
+            Path::new("/exports"),
+            &["tasks".to_string()],
+            &[],
+        );
+
+        assert_eq!(
+            script,
+            "ATTACH '/tmp/snap''s.sqlite' AS src (TYPE sqlite, READ_ONLY);\n\
+             COPY (SELECT * FROM src.\"tasks\") TO '/exports/tasks.parquet' (FORMAT parquet);\n"
+        );
+    }
+
+    #[test]
+    fn test_duckdb_script_for_duckdb() {
+        let script = duckdb_script(
+            AnalyticsExportFormat::DuckDb,
+            Path::new("/tmp/snap.sqlite"),
+            Path::new("/exports/vibe-kanban.partial"),
+            &["tasks".to_string()],
+            &[],
+        );
+
+        assert!(script.contains("ATTACH '/exports/vibe-kanban.partial' AS dst;\n"));
+        assert!(script.ends_with("CREATE TABLE dst.\"tasks\" AS SELECT * FROM src.\"tasks\";\n"));
+    }
+
+    #[test]
+    fn test_duckdb_script_excludes_secret_columns() {
+        let secret = |column: &str| SecretColumn {
+            table: "web_push_subscriptions".to_string(),
+            column: column.to_string(),
+            not_null: true,
+        };
+        let script = duckdb_script(
+            AnalyticsExportFormat::DuckDb,
+            Path::new("/tmp/snap.sqlite"),
+            Path::new("/exports/vibe-kanban.partial"),
+            &["web_push_subscriptions".to_string()],
+            &[secret("auth"), secret("p256dh")],
+        );
+
+        assert!(script.contains("SELECT * EXCLUDE (\"auth\", \"p256dh\") FROM"));
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_export_has_no_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = SqliteConnectOptions::new()
+            .filename(dir.path().join("live.sqlite"))
+            .create_if_missing(true);
+        let pool = SqlitePool::connect_with(options).await.unwrap();
+        for statement in [
+            "CREATE TABLE project_peer_sync (project_id TEXT, peer_url TEXT, token TEXT NOT NULL, total_tokens INTEGER)",
+            "CREATE TABLE web_push_subscriptions (endpoint TEXT NOT NULL UNIQUE, p256dh TEXT NOT NULL, auth TEXT NOT NULL)",
+            "INSERT INTO project_peer_sync VALUES ('p', 'https://peer', 'sync-token', 1234)",
+            "INSERT INTO web_push_subscriptions VALUES ('https://push/1', 'key-1', 'auth-1'), ('https://push/2', 'key-2', 'auth-2')",
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        let target = dir.path().join("export");
+        export_analytics(&pool, &target, AnalyticsExportFormat::Sqlite, None)
+            .await
+            .unwrap();
+
+        let exported = std::fs::read(target.join("vibe-kanban.sqlite")).unwrap();
+        let exported = String::from_utf8_lossy(&exported);
+        for secret in ["sync-token", "https://push/", "key-1", "auth-2"] {
+            assert!(!exported.contains(secret), "{secret} was exported");
+        }
+        assert!(exported.contains("https://peer"));
+
+        let secrets = find_secret_columns(&pool, &["project_peer_sync".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(
+            secrets,
+            vec![SecretColumn {
+                table: "project_peer_sync".to_string(),
+                column: "token".to_string(),
+                not_null: true,
+            }]
+        );
+    }
+}
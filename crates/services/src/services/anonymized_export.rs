@@ -0,0 +1,505 @@
+//! Anonymized database export for bug reports.
+//!
+//! Produces a copy of the database that can be shared with maintainers to
+//! reproduce a problem without leaking project content. The schema, IDs,
+//! timestamps, numbers and enum-like columns such as `status` are kept as
+//! they are, so the copy behaves like the original. Every other text value is
+//! replaced:
+//!
+//! - Short single-line values become `anon-` followed by a salted hash, so
+//!   equal values stay equal across tables
+//! - Longer values become lorem ipsum text with the same line lengths
+//! - JSON values keep their keys, IDs and `type`-like tags; only their other
+//!   strings are replaced
+//! - Tokens, secrets and key hashes are removed
+//!
+//! The salt is random per export, so short values like branch names cannot
+//! be recovered by hashing guesses.
+
+use std::path::Path;
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use sqlx::{Connection, SqliteConnection, SqlitePool, sqlite::SqliteConnectOptions};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::services::analytics_export::{exported_tables, sql_identifier};
+
+/// Text values longer than this, or spanning lines, are lorem-ized instead of
+/// hashed
+const MAX_HASHED_CHARS: usize = 48;
+
+/// Hex digits of the salted hash kept in `anon-` values
+const HASH_CHARS: usize = 12;
+
+/// Rows rewritten per query
+const BATCH_SIZE: i64 = 500;
+
+/// Columns, and JSON keys, whose text is an enum or another value that does
+/// not describe project content
+const STRUCTURAL_NAMES: &[&str] = &[
+    "digest_mode",
+    "executor",
+    "hash",
+    "kind",
+    "merge_type",
+    "mime_type",
+    "mode",
+    "op",
+    "outcome",
+    "pr_status",
+    "profile_a",
+    "profile_b",
+    "role",
+    "run_reason",
+    "scratch_type",
+    "status",
+    "type",
+    "variant",
+];
+
+/// Column name fragments of credentials, which are removed outright
+const SECRET_FRAGMENTS: &[&str] = &["token", "secret", "password", "key_hash", "key_prefix"];
+/// Whole column names of credentials too short to match as fragments: the
+/// push service URL and encryption keys of a web push subscription
+const SECRET_COLUMNS: &[&str] = &["auth", "endpoint", "p256dh"];
+
+const LOREM_WORDS: &[&str] = &[
+    "lorem",
+    "ipsum",
+    "dolor",
+    "sit",
+    "amet",
+    "consectetur",
+    "adipiscing",
+    "elit",
+    "sed",
+    "do",
+    "eiusmod",
+    "tempor",
+    "incididunt",
+    "ut",
+    "labore",
+    "et",
+    "dolore",
+    "magna",
+    "aliqua",
+];
+
+#[derive(Debug, Error)]
+pub enum AnonymizedExportError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AnonymizedExportReport {
+    /// Tables in the export
+    pub tables: usize,
+    /// Text values replaced by hashes or lorem ipsum
+    pub values_anonymized: u64,
+    /// Credential values removed
+    pub values_stripped: u64,
+}
+
+/// What happens to a column's values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnTreatment {
+    Keep,
+    Strip,
+    Anonymize,
+}
+
+fn is_id_name(name: &str) -> bool {
+    name == "id" || name.ends_with("_id") || name.ends_with("_ids")
+}
+
+/// Whether the column holds credentials, which are never exported
+pub(crate) fn is_secret_column(name: &str) -> bool {
+    let name = name.to_lowercase();
+    SECRET_COLUMNS.contains(&name.as_str())
+        || SECRET_FRAGMENTS
+            .iter()
+            .any(|fragment| name.contains(fragment))
+}
+
+fn column_treatment(name: &str, declared_type: &str) -> ColumnTreatment {
+    let name = name.to_lowercase();
+    let declared_type = declared_type.to_uppercase();
+    let non_text = ["INT", "REAL", "FLOA", "DOUB", "BLOB"]
+        .iter()
+        .any(|fragment| declared_type.contains(fragment));
+    if is_secret_column(&name) {
+        ColumnTreatment::Strip
+    } else if non_text
+        || is_id_name(&name)
+        || name.ends_with("_at")
+        || STRUCTURAL_NAMES.contains(&name.as_str())
+    {
+        ColumnTreatment::Keep
+    } else {
+        ColumnTreatment::Anonymize
+    }
+}
+
+/// Rewrites text values, deterministically for one salt
+struct Anonymizer {
+    salt: Vec<u8>,
+}
+
+impl Anonymizer {
+    fn new(salt: &[u8]) -> Self {
+        Self {
+            salt: salt.to_vec(),
+        }
+    }
+
+    fn digest(&self, value: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(&self.salt);
+        hasher.update(value.as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Replace a column value, keeping the structure of JSON and JSON lines
+    fn anonymize_value(&self, value: &str) -> String {
+        let trimmed = value.trim_start();
+        if (trimmed.starts_with('{') || trimmed.starts_with('['))
+            && let Ok(json) = serde_json::from_str::<Value>(value)
+        {
+            return self.anonymize_json(json, None).to_string();
+        }
+
+        let lines: Vec<&str> = value.lines().collect();
+        let json_lines: Option<Vec<Value>> = (lines.len() > 1)
+            .then(|| {
+                lines
+                    .iter()
+                    .filter(|line| !line.trim().is_empty())
+                    .map(|line| serde_json::from_str::<Value>(line).ok())
+                    .collect::<Option<Vec<_>>>()
+            })
+            .flatten()
+            .filter(|values| values.iter().all(|v| v.is_object() || v.is_array()));
+        if let Some(json_lines) = json_lines {
+            let mut text = json_lines
+                .into_iter()
+                .map(|json| self.anonymize_json(json, None).to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
+            if value.ends_with('\n') {
+                text.push('\n');
+            }
+            return text;
+        }
+
+        self.anonymize_text(value)
+    }
+
+    fn anonymize_json(&self, value: Value, key: Option<&str>) -> Value {
+        match value {
+            Value::String(text) => {
+                let keep = key.is_some_and(|key| {
+                    let key = key.to_lowercase();
+                    is_id_name(&key) || STRUCTURAL_NAMES.contains(&key.as_str())
+                }) || Uuid::parse_str(&text).is_ok();
+                if keep {
+                    Value::String(text)
+                } else {
+                    Value::String(self.anonymize_text(&text))
+                }
+            }
+            Value::Array(items) => Value::Array(
+                items
+                    .into_iter()
+                    .map(|item| self.anonymize_json(item, key))
+                    .collect(),
+            ),
+            Value::Object(map) => Value::Object(
+                map.into_iter()
+                    .map(|(k, v)| {
+                        let v = self.anonymize_json(v, Some(&k));
+                        (k, v)
+                    })
+                    .collect(),
+            ),
+            other => other,
+        }
+    }
+
+    /// Hash short single-line text; lorem-ize the rest line by line
+    fn anonymize_text(&self, text: &str) -> String {
+        if text.is_empty() {
+            return String::new();
+        }
+        let digest = self.digest(text);
+        if !text.contains('\n') && text.chars().count() <= MAX_HASHED_CHARS {
+            return format!("anon-{}", &digest[..HASH_CHARS]);
+        }
+
+        let start = usize::from_str_radix(&digest[..8], 16).unwrap_or(0);
+        text.split('\n')
+            .enumerate()
+            .map(|(index, line)| lorem(line.chars().count(), start + index))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Lorem ipsum text exactly `len` characters long
+fn lorem(len: usize, start: usize) -> String {
+    let mut text = String::with_capacity(len + 12);
+    let mut index = start;
+    while text.len() < len {
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(LOREM_WORDS[index % LOREM_WORDS.len()]);
+        index += 1;
+    }
+    text.truncate(len);
+    text
+}
+
+/// Write an anonymized copy of the live database to `target`, which must not
+/// exist yet
+pub async fn export_anonymized(
+    pool: &SqlitePool,
+    target: &Path,
+) -> Result<AnonymizedExportReport, AnonymizedExportError> {
+    sqlx::query("VACUUM INTO $1")
+        .bind(target.to_string_lossy().to_string())
+        .execute(pool)
+        .await?;
+
+    let mut conn =
+        SqliteConnection::connect_with(&SqliteConnectOptions::new().filename(target)).await?;
+    // Overwrite replaced values on disk rather than leaving them in free pages
+    sqlx::query("PRAGMA secure_delete = ON")
+        .execute(&mut conn)
+        .await?;
+
+    let salt = Uuid::new_v4();
+    let anonymizer = Anonymizer::new(salt.as_bytes());
+    let report = anonymize_database(&mut conn, &anonymizer).await?;
+
+    sqlx::query("VACUUM").execute(&mut conn).await?;
+    conn.close().await?;
+    Ok(report)
+}
+
+/// Anonymized copy of the live database as the bytes of a SQLite file
+pub async fn anonymized_database_bytes(
+    pool: &SqlitePool,
+) -> Result<(Vec<u8>, AnonymizedExportReport), AnonymizedExportError> {
+    let staging = tempfile::tempdir()?;
+    let target = staging.path().join("anonymized.sqlite");
+    let report = export_anonymized(pool, &target).await?;
+    Ok((tokio::fs::read(&target).await?, report))
+}
+
+async fn anonymize_database(
+    conn: &mut SqliteConnection,
+    anonymizer: &Anonymizer,
+) -> Result<AnonymizedExportReport, AnonymizedExportError> {
+    let rows: Vec<(String, Option<String>)> =
+        sqlx::query_as("SELECT name, sql FROM sqlite_master WHERE type = 'table' ORDER BY name")
+            .fetch_all(&mut *conn)
+            .await?;
+    let tables = exported_tables(&rows);
+    let mut report = AnonymizedExportReport {
+        tables: tables.len(),
+        ..Default::default()
+    };
+
+    let mut tx = conn.begin().await?;
+    for table in &tables {
+        let columns: Vec<(String, String, bool)> =
+            sqlx::query_as("SELECT name, type, \"notnull\" FROM pragma_table_info($1)")
+                .bind(table)
+                .fetch_all(&mut *tx)
+                .await?;
+        for (column, declared_type, not_null) in columns {
+            let table_sql = sql_identifier(table);
+            let column_sql = sql_identifier(&column);
+            match column_treatment(&column, &declared_type) {
+                ColumnTreatment::Keep => {}
+                ColumnTreatment::Strip => {
+                    let replacement = if not_null { "''" } else { "NULL" };
+                    let result = sqlx::query(&format!(
+                        "UPDATE {table_sql} SET {column_sql} = {replacement} \
+                         WHERE {column_sql} IS NOT NULL AND {column_sql} != ''"
+                    ))
+                    .execute(&mut *tx)
+                    .await?;
+                    report.values_stripped += result.rows_affected();
+                }
+                ColumnTreatment::Anonymize => {
+                    let select = format!(
+                        "SELECT rowid, {column_sql} FROM {table_sql} \
+                         WHERE rowid > $1 AND typeof({column_sql}) = 'text' \
+                         ORDER BY rowid LIMIT $2"
+                    );
+                    let update =
+                        format!("UPDATE {table_sql} SET {column_sql} = $1 WHERE rowid = $2");
+                    let mut last_rowid = i64::MIN;
+                    loop {
+                        let batch: Vec<(i64, String)> = sqlx::query_as(&select)
+                            .bind(last_rowid)
+                            .bind(BATCH_SIZE)
+                            .fetch_all(&mut *tx)
+                            .await?;
+                        let Some((rowid, _)) = batch.last() else {
+                            break;
+                        };
+                        last_rowid = *rowid;
+                        for (rowid, value) in batch {
+                            sqlx::query(&update)
+                                .bind(anonymizer.anonymize_value(&value))
+                                .bind(rowid)
+                                .execute(&mut *tx)
+                                .await?;
+                            report.values_anonymized += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Full-text indexes follow their tables through triggers; merging their
+    // segments drops the index entries of the original text
+    let fts_tables: Vec<&String> = rows
+        .iter()
+        .filter(|(_, sql)| {
+            sql.as_deref()
+                .is_some_and(|sql| sql.to_lowercase().contains("using fts5"))
+        })
+        .map(|(name, _)| name)
+        .collect();
+    for table in fts_tables {
+        let table_sql = sql_identifier(table);
+        sqlx::query(&format!(
+            "INSERT INTO {table_sql}({table_sql}) VALUES ('optimize')"
+        ))
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_treatment() {
+        assert_eq!(
+            column_treatment("title", "TEXT"),
+            ColumnTreatment::Anonymize
+        );
+        assert_eq!(column_treatment("logs", ""), ColumnTreatment::Anonymize);
+        assert_eq!(
+            column_treatment("project_id", "BLOB"),
+            ColumnTreatment::Keep
+        );
+        assert_eq!(column_treatment("remote_id", "TEXT"), ColumnTreatment::Keep);
+        assert_eq!(
+            column_treatment("created_at", "TEXT"),
+            ColumnTreatment::Keep
+        );
+        assert_eq!(column_treatment("status", "TEXT"), ColumnTreatment::Keep);
+        assert_eq!(
+            column_treatment("position", "INTEGER"),
+            ColumnTreatment::Keep
+        );
+        assert_eq!(column_treatment("token", "TEXT"), ColumnTreatment::Strip);
+        assert_eq!(column_treatment("key_hash", "TEXT"), ColumnTreatment::Strip);
+        for column in ["endpoint", "p256dh", "auth"] {
+            assert_eq!(column_treatment(column, "TEXT"), ColumnTreatment::Strip);
+        }
+        assert_eq!(
+            column_treatment("author", "TEXT"),
+            ColumnTreatment::Anonymize
+        );
+    }
+
+    #[test]
+    fn test_short_text_is_hashed_consistently() {
+        let anonymizer = Anonymizer::new(b"salt");
+
+        let branch = anonymizer.anonymize_value("vk/fix-login");
+        assert!(branch.starts_with("anon-"));
+        assert_eq!(branch.len(), "anon-".len() + HASH_CHARS);
+        assert_eq!(anonymizer.anonymize_value("vk/fix-login"), branch);
+        assert_ne!(anonymizer.anonymize_value("vk/fix-logout"), branch);
+        assert_ne!(
+            Anonymizer::new(b"other").anonymize_value("vk/fix-login"),
+            branch
+        );
+    }
+
+    #[test]
+    fn test_long_text_keeps_line_lengths() {
+        let anonymizer = Anonymizer::new(b"salt");
+        let description = "Users are logged out after a refresh.\n\nSteps: open the board, reload the page and watch the session cookie";
+
+        let anonymized = anonymizer.anonymize_value(description);
+
+        let lengths = |text: &str| text.split('\n').map(str::len).collect::<Vec<_>>();
+        assert_eq!(lengths(&anonymized), lengths(description));
+        assert!(!anonymized.contains("logged"));
+        assert!(
+            anonymized
+                .split_whitespace()
+                .all(|word| LOREM_WORDS.iter().any(|lorem| lorem.starts_with(word)))
+        );
+    }
+
+    #[test]
+    fn test_json_keeps_keys_ids_and_tags() {
+        let anonymizer = Anonymizer::new(b"salt");
+        let task_id = Uuid::new_v4().to_string();
+        let action = serde_json::json!({
+            "typ": { "type": "CodingAgentInitialRequest", "prompt": "Fix the login bug" },
+            "task_id": task_id,
+            "retries": 2,
+            "repos": [task_id, "backend"],
+        });
+
+        let anonymized: Value =
+            serde_json::from_str(&anonymizer.anonymize_value(&action.to_string())).unwrap();
+
+        assert_eq!(anonymized["typ"]["type"], "CodingAgentInitialRequest");
+        assert_eq!(
+            anonymized["typ"]["prompt"],
+            Value::String(anonymizer.anonymize_text("Fix the login bug"))
+        );
+        assert_eq!(anonymized["task_id"], Value::String(task_id.clone()));
+        assert_eq!(anonymized["retries"], 2);
+        assert_eq!(anonymized["repos"][0], Value::String(task_id));
+        assert_ne!(anonymized["repos"][1], "backend");
+    }
+
+    #[test]
+    fn test_json_lines_are_anonymized_per_line() {
+        let anonymizer = Anonymizer::new(b"salt");
+        let logs = "{\"Stdout\":\"secret output\"}\n{\"Finished\":\"done\"}\n";
+
+        let anonymized = anonymizer.anonymize_value(logs);
+
+        assert!(anonymized.ends_with('\n'));
+        let lines: Vec<Value> = anonymized
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0]["Stdout"].as_str().unwrap().starts_with("anon-"));
+        assert!(!anonymized.contains("secret"));
+    }
+}
@@ -8,7 +8,9 @@ use tokio_util::sync::CancellationToken;
 use utils::approvals::{ApprovalRequest, ApprovalStatus, CreateApprovalRequest};
 use uuid::Uuid;
 
-use crate::services::{approvals::Approvals, notification::NotificationService};
+use crate::services::{
+    approvals::Approvals, i18n::ServerMessage, notification::NotificationService,
+};
 
 pub struct ExecutorApprovalBridge {
     approvals: Approvals,
@@ -67,9 +69,9 @@ impl ExecutorApprovalService for ExecutorApprovalBridge {
             .unwrap_or_else(|_| "Unknown task".to_string());
 
         self.notification_service
-            .notify(
-                &format!("Approval Needed: {}", task_name),
-                &format!("Tool '{}' requires approval", tool_name),
+            .notify_localized(
+                ServerMessage::ApprovalNeededTitle(&task_name),
+                ServerMessage::ApprovalNeeded { tool: tool_name },
             )
             .await;
 
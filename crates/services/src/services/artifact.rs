@@ -0,0 +1,182 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use db::models::{
+    task::Task,
+    workspace::Workspace,
+    workspace_artifact::{CreateWorkspaceArtifact, WorkspaceArtifact},
+};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ArtifactError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("Artifact too large: {0} bytes (max: {1} bytes)")]
+    TooLarge(u64, u64),
+
+    #[error("Project artifact quota exceeded: {used} of {quota} bytes used, {requested} requested")]
+    QuotaExceeded { used: u64, quota: u64, requested: u64 },
+
+    #[error("Artifact not found")]
+    NotFound,
+}
+
+/// Keep the extension and a filesystem-safe stem of the uploaded name
+fn sanitize_filename(name: &str) -> String {
+    let path = Path::new(name);
+    let stem: String = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("artifact")
+        .chars()
+        .map(|c| if c.is_whitespace() { '_' } else { c })
+        .filter(|c| c.is_alphanumeric() || *c == '_' || *c == '-')
+        .take(50)
+        .collect();
+    let stem = if stem.is_empty() {
+        "artifact".to_string()
+    } else {
+        stem
+    };
+
+    match path
+        .extension()
+        .and_then(|e| e.to_str())
+        .filter(|e| e.chars().all(|c| c.is_ascii_alphanumeric()))
+    {
+        Some(ext) => format!("{stem}.{}", ext.to_lowercase()),
+        None => stem,
+    }
+}
+
+/// Whether storing `requested` more bytes stays within `quota`
+fn check_quota(used: u64, quota: u64, requested: u64) -> Result<(), ArtifactError> {
+    if used.saturating_add(requested) > quota {
+        return Err(ArtifactError::QuotaExceeded {
+            used,
+            quota,
+            requested,
+        });
+    }
+    Ok(())
+}
+
+/// Stores attempt output files (screenshots, coverage reports, binaries) next to
+/// the image cache and enforces a per-project size quota
+#[derive(Clone)]
+pub struct ArtifactService {
+    cache_dir: PathBuf,
+    pool: SqlitePool,
+    max_size_bytes: u64,
+}
+
+impl ArtifactService {
+    pub fn new(pool: SqlitePool) -> Result<Self, ArtifactError> {
+        let cache_dir = utils::cache_dir().join("artifacts");
+        fs::create_dir_all(&cache_dir)?;
+        Ok(Self {
+            cache_dir,
+            pool,
+            max_size_bytes: 100 * 1024 * 1024, // 100MB per file
+        })
+    }
+
+    /// Store an artifact for `workspace`, rejecting it if the project would exceed
+    /// `project_quota_bytes`
+    pub async fn store_artifact(
+        &self,
+        workspace: &Workspace,
+        data: &[u8],
+        original_filename: &str,
+        mime_type: Option<String>,
+        project_quota_bytes: u64,
+    ) -> Result<WorkspaceArtifact, ArtifactError> {
+        let file_size = data.len() as u64;
+        if file_size > self.max_size_bytes {
+            return Err(ArtifactError::TooLarge(file_size, self.max_size_bytes));
+        }
+
+        let task = Task::find_by_id(&self.pool, workspace.task_id)
+            .await?
+            .ok_or(ArtifactError::NotFound)?;
+
+        let hash = format!("{:x}", Sha256::digest(data));
+        let new_filename = format!("{}_{}", Uuid::new_v4(), sanitize_filename(original_filename));
+        let file_path = self.cache_dir.join(&new_filename);
+
+        // Inserting first takes the write lock, so concurrent uploads see each
+        // other's rows when the quota is checked; over quota, the insert is
+        // rolled back
+        let mut tx = self.pool.begin().await?;
+        let artifact = WorkspaceArtifact::create(
+            &mut *tx,
+            &CreateWorkspaceArtifact {
+                workspace_id: workspace.id,
+                file_path: new_filename,
+                original_name: original_filename.to_string(),
+                mime_type,
+                size_bytes: file_size as i64,
+                hash,
+            },
+        )
+        .await?;
+        let total = WorkspaceArtifact::total_size_for_project(&mut *tx, task.project_id).await?;
+        let used = (total.max(0) as u64).saturating_sub(file_size);
+        check_quota(used, project_quota_bytes, file_size)?;
+
+        fs::write(&file_path, data)?;
+        if let Err(e) = tx.commit().await {
+            let _ = fs::remove_file(&file_path);
+            return Err(e.into());
+        }
+        Ok(artifact)
+    }
+
+    pub fn get_absolute_path(&self, artifact: &WorkspaceArtifact) -> PathBuf {
+        self.cache_dir.join(&artifact.file_path)
+    }
+
+    pub async fn delete_artifact(&self, artifact: &WorkspaceArtifact) -> Result<(), ArtifactError> {
+        let file_path = self.get_absolute_path(artifact);
+        if file_path.exists() {
+            fs::remove_file(file_path)?;
+        }
+        WorkspaceArtifact::delete(&self.pool, artifact.id).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_filename_keeps_extension() {
+        assert_eq!(sanitize_filename("Coverage Report.HTML"), "Coverage_Report.html");
+        assert_eq!(sanitize_filename("../../etc/passwd"), "passwd");
+        assert_eq!(sanitize_filename("app.tar.gz"), "apptar.gz");
+        assert_eq!(sanitize_filename("$$$"), "artifact");
+    }
+
+    #[test]
+    fn test_check_quota() {
+        assert!(check_quota(90, 100, 10).is_ok());
+        assert!(matches!(
+            check_quota(90, 100, 11),
+            Err(ArtifactError::QuotaExceeded {
+                used: 90,
+                quota: 100,
+                requested: 11
+            })
+        ));
+    }
+}
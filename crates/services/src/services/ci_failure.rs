@@ -0,0 +1,382 @@
+//! Tasks filed from CI failures.
+//!
+//! A project's CI reports failed workflow runs through the project API. A
+//! failure on the default branch files a task, and later failures of the same
+//! workflow on that branch update the task while it is still open instead of
+//! filing duplicates. Once the task is done or cancelled, the next failure
+//! files a new one.
+//!
+//! Reports may also name the tests that failed. Each test's failures are
+//! counted, and a test that keeps failing gets a task of its own, updated with
+//! the count on every further failure.
+
+use db::models::{
+    ci_failure::{CiFailureTask, CiTestFailure},
+    project_repo::ProjectRepo,
+    repo::Repo,
+    task::{CreateTask, Task, TaskStatus},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Log excerpts are cut to their last this many characters
+const MAX_LOG_EXCERPT_CHARS: usize = 4000;
+/// A test failing this many times gets a task of its own
+const RECURRING_TEST_THRESHOLD: i64 = 2;
+/// Failed tests beyond this many in one report are not tracked
+const MAX_FAILED_TESTS: usize = 50;
+
+/// A failed CI workflow run
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct CiFailureReport {
+    /// Workflow name, e.g. `CI` or `release.yml`
+    pub workflow: String,
+    /// Branch the run was on; a `refs/heads/` prefix is ignored
+    pub branch: String,
+    /// The repository's default branch, when the CI knows it. Otherwise the
+    /// project repos' default target branches are used, or `main`/`master`.
+    #[serde(default)]
+    #[ts(optional)]
+    pub default_branch: Option<String>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub run_url: Option<String>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub commit_sha: Option<String>,
+    /// End of the failing job's log
+    #[serde(default)]
+    #[ts(optional)]
+    pub log_excerpt: Option<String>,
+    /// Start an attempt at fixing the failure when a new task is filed
+    #[serde(default)]
+    pub auto_start: bool,
+    /// Names of the tests that failed in the run
+    #[serde(default)]
+    pub failed_tests: Vec<String>,
+}
+
+/// What a reported failure did
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum CiFailureOutcome {
+    /// The run was not on the default branch
+    Ignored,
+    /// A task was filed for the failure
+    Created {
+        task: Task,
+        /// Whether a fix attempt was started (see `CiFailureReport::auto_start`)
+        attempt_started: bool,
+        /// Failed tests that have failed before, each with a task of its own
+        recurring_tests: Vec<CiTestFailure>,
+    },
+    /// The workflow's open task was updated with the failure
+    Updated {
+        task: Task,
+        failure_count: i64,
+        /// Failed tests that have failed before, each with a task of its own
+        recurring_tests: Vec<CiTestFailure>,
+    },
+}
+
+/// File or update the task for a failed run of `report.workflow` in
+/// `project_id`. Starting a fix attempt is left to the caller.
+pub async fn record_failure(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    report: &CiFailureReport,
+) -> Result<CiFailureOutcome, sqlx::Error> {
+    let branch = branch_name(&report.branch);
+    let repos = ProjectRepo::find_repos_for_project(pool, project_id).await?;
+    if !is_default_branch(branch, report.default_branch.as_deref(), &repos) {
+        return Ok(CiFailureOutcome::Ignored);
+    }
+
+    let recurring_tests = record_test_failures(pool, project_id, report).await?;
+    let open_task = match CiFailureTask::find(pool, project_id, &report.workflow, branch).await? {
+        Some(link) => find_open_task(pool, link.task_id).await?,
+        None => None,
+    };
+
+    match open_task {
+        Some(task) => {
+            let link = CiFailureTask::record(
+                pool,
+                project_id,
+                &report.workflow,
+                branch,
+                task.id,
+                report.run_url.as_deref(),
+            )
+            .await?;
+            let task = Task::update(
+                pool,
+                task.id,
+                task.project_id,
+                task.title,
+                Some(format_description(report, branch, link.failure_count)),
+                task.status,
+                task.parent_workspace_id,
+                None,
+            )
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+            Ok(CiFailureOutcome::Updated {
+                task,
+                failure_count: link.failure_count,
+                recurring_tests,
+            })
+        }
+        None => {
+            let task = Task::create(
+                pool,
+                &CreateTask::from_title_description(
+                    project_id,
+                    format!("Fix CI: {} failing on {}", report.workflow, branch),
+                    Some(format_description(report, branch, 1)),
+                ),
+                Uuid::new_v4(),
+            )
+            .await?;
+            CiFailureTask::record(
+                pool,
+                project_id,
+                &report.workflow,
+                branch,
+                task.id,
+                report.run_url.as_deref(),
+            )
+            .await?;
+            Ok(CiFailureOutcome::Created {
+                task,
+                attempt_started: false,
+                recurring_tests,
+            })
+        }
+    }
+}
+
+/// Count the report's failed tests, filing or updating the task of each one
+/// that failed before
+async fn record_test_failures(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    report: &CiFailureReport,
+) -> Result<Vec<CiTestFailure>, sqlx::Error> {
+    let mut test_names: Vec<&str> = report
+        .failed_tests
+        .iter()
+        .map(|name| name.trim())
+        .filter(|name| !name.is_empty())
+        .collect();
+    test_names.sort_unstable();
+    test_names.dedup();
+    test_names.truncate(MAX_FAILED_TESTS);
+
+    let mut recurring = Vec::new();
+    for test_name in test_names {
+        let mut failure =
+            CiTestFailure::record(pool, project_id, test_name, report.run_url.as_deref()).await?;
+        if failure.occurrences < RECURRING_TEST_THRESHOLD {
+            continue;
+        }
+
+        let description = format_test_description(&failure, &report.workflow);
+        let open_task = match failure.task_id {
+            Some(task_id) => find_open_task(pool, task_id).await?,
+            None => None,
+        };
+        match open_task {
+            Some(task) => {
+                Task::update(
+                    pool,
+                    task.id,
+                    task.project_id,
+                    task.title,
+                    Some(description),
+                    task.status,
+                    task.parent_workspace_id,
+                    None,
+                )
+                .await?;
+            }
+            None => {
+                let task = Task::create(
+                    pool,
+                    &CreateTask::from_title_description(
+                        project_id,
+                        format!("Fix flaky test: {test_name}"),
+                        Some(description),
+                    ),
+                    Uuid::new_v4(),
+                )
+                .await?;
+                CiTestFailure::set_task(pool, project_id, test_name, task.id).await?;
+                failure.task_id = Some(task.id);
+            }
+        }
+        recurring.push(failure);
+    }
+    Ok(recurring)
+}
+
+/// The task, unless it was deleted, done or cancelled
+async fn find_open_task(pool: &SqlitePool, task_id: Uuid) -> Result<Option<Task>, sqlx::Error> {
+    Ok(Task::find_by_id(pool, task_id)
+        .await?
+        .filter(|task| !matches!(task.status, TaskStatus::Done | TaskStatus::Cancelled)))
+}
+
+/// `branch` without the `refs/heads/` prefix CI systems often report
+pub fn branch_name(branch: &str) -> &str {
+    branch.strip_prefix("refs/heads/").unwrap_or(branch)
+}
+
+fn is_default_branch(branch: &str, reported_default: Option<&str>, repos: &[Repo]) -> bool {
+    if let Some(default_branch) = reported_default {
+        return branch == branch_name(default_branch);
+    }
+    let defaults: Vec<&str> = repos
+        .iter()
+        .filter_map(|repo| repo.default_target_branch.as_deref())
+        .collect();
+    if defaults.is_empty() {
+        matches!(branch, "main" | "master")
+    } else {
+        defaults.contains(&branch)
+    }
+}
+
+fn format_description(report: &CiFailureReport, branch: &str, failure_count: i64) -> String {
+    let mut description = format!("The `{}` workflow failed on `{}`", report.workflow, branch);
+    if let Some(sha) = &report.commit_sha {
+        description.push_str(&format!(" at commit `{sha}`"));
+    }
+    description.push('.');
+    if failure_count > 1 {
+        description.push_str(&format!(
+            " It has failed {failure_count} times since this task was filed."
+        ));
+    }
+    if let Some(url) = &report.run_url {
+        description.push_str(&format!("\n\nRun: {url}"));
+    }
+    if let Some(log) = report.log_excerpt.as_deref().map(str::trim_end)
+        && !log.is_empty()
+    {
+        description.push_str(&format!(
+            "\n\nLog excerpt:\n```\n{}\n```",
+            log_tail(log, MAX_LOG_EXCERPT_CHARS)
+        ));
+    }
+    description
+}
+
+fn format_test_description(failure: &CiTestFailure, workflow: &str) -> String {
+    let mut description = format!(
+        "`{}` has failed {} times in CI, most recently in the `{}` workflow.\n\nFirst seen: {}\nLast seen: {}",
+        failure.test_name,
+        failure.occurrences,
+        workflow,
+        failure.first_seen_at.format("%Y-%m-%d %H:%M UTC"),
+        failure.last_seen_at.format("%Y-%m-%d %H:%M UTC"),
+    );
+    if let Some(url) = &failure.last_run_url {
+        description.push_str(&format!("\nLast failing run: {url}"));
+    }
+    description
+}
+
+/// The last `max_chars` characters of `log`, starting at a line boundary when
+/// the log had to be cut
+fn log_tail(log: &str, max_chars: usize) -> &str {
+    let Some((start, _)) = log.char_indices().rev().nth(max_chars.saturating_sub(1)) else {
+        return log;
+    };
+    let tail = &log[start..];
+    if start == 0 || log[..start].ends_with('\n') {
+        return tail;
+    }
+    match tail.find('\n') {
+        Some(newline) if newline + 1 < tail.len() => &tail[newline + 1..],
+        _ => tail,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+
+    use super::*;
+
+    fn report() -> CiFailureReport {
+        CiFailureReport {
+            workflow: "CI".to_string(),
+            branch: "refs/heads/main".to_string(),
+            default_branch: None,
+            run_url: Some("https://github.com/acme/app/actions/runs/1".to_string()),
+            commit_sha: Some("abc123".to_string()),
+            log_excerpt: Some("error[E0308]: mismatched types\n".to_string()),
+            auto_start: false,
+            failed_tests: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_is_default_branch() {
+        assert!(is_default_branch("main", None, &[]));
+        assert!(!is_default_branch("feature", None, &[]));
+        assert!(is_default_branch(
+            "develop",
+            Some("refs/heads/develop"),
+            &[]
+        ));
+        assert!(!is_default_branch("main", Some("develop"), &[]));
+    }
+
+    #[test]
+    fn test_format_description() {
+        let report = report();
+        let branch = branch_name(&report.branch);
+
+        let first = format_description(&report, branch, 1);
+        assert!(first.starts_with("The `CI` workflow failed on `main` at commit `abc123`."));
+        assert!(first.contains("Run: https://github.com/acme/app/actions/runs/1"));
+        assert!(first.ends_with("```\nerror[E0308]: mismatched types\n```"));
+        assert!(!first.contains("times since"));
+
+        let again = format_description(&report, branch, 3);
+        assert!(again.contains("It has failed 3 times since this task was filed."));
+    }
+
+    #[test]
+    fn test_format_test_description() {
+        let at = DateTime::parse_from_rfc3339("2026-02-25T09:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let failure = CiTestFailure {
+            project_id: Uuid::nil(),
+            test_name: "api::tests::test_login".to_string(),
+            occurrences: 4,
+            task_id: None,
+            last_run_url: None,
+            first_seen_at: at,
+            last_seen_at: at,
+        };
+
+        assert_eq!(
+            format_test_description(&failure, "CI"),
+            "`api::tests::test_login` has failed 4 times in CI, most recently in the `CI` workflow.\n\nFirst seen: 2026-02-25 09:30 UTC\nLast seen: 2026-02-25 09:30 UTC"
+        );
+    }
+
+    #[test]
+    fn test_log_tail_cuts_at_line_boundary() {
+        assert_eq!(log_tail("short", 10), "short");
+        assert_eq!(log_tail("first line\nsecond\nthird", 12), "second\nthird");
+        assert_eq!(log_tail("first line\nsecond\nthird", 10), "third");
+        assert_eq!(log_tail("no newlines at all", 5), "t all");
+    }
+}
@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use ts_rs::TS;
+
+/// Field-level summary of what changed when a config file was loaded and upgraded.
+///
+/// Paths use dot notation (e.g. `telegram.stream_enabled`). Arrays are compared as
+/// whole values rather than element by element.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS, PartialEq, Eq)]
+pub struct ConfigMigrationReport {
+    /// Version found in the config file on disk, if any
+    pub from_version: Option<String>,
+    /// Version the config was loaded as
+    pub to_version: String,
+    /// Fields missing from the file that were filled in with defaults
+    pub added: Vec<String>,
+    /// Fields present in the file whose value was replaced during migration
+    pub defaulted: Vec<String>,
+    /// Fields present in the file that no longer exist in the current schema
+    pub dropped: Vec<String>,
+}
+
+impl ConfigMigrationReport {
+    /// Compare the raw JSON read from disk with the serialized result of loading it.
+    pub fn diff(raw_config: &str, migrated: &Value) -> Self {
+        let raw = serde_json::from_str::<Value>(raw_config).unwrap_or(Value::Null);
+
+        let mut report = Self {
+            from_version: raw
+                .get("config_version")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            to_version: migrated
+                .get("config_version")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            ..Default::default()
+        };
+        report.walk("", &raw, migrated);
+        report
+    }
+
+    /// Whether a version change happened or any field was touched
+    pub fn has_changes(&self) -> bool {
+        self.from_version.as_deref() != Some(self.to_version.as_str())
+            || !self.added.is_empty()
+            || !self.defaulted.is_empty()
+            || !self.dropped.is_empty()
+    }
+
+    fn walk(&mut self, prefix: &str, old: &Value, new: &Value) {
+        match (old, new) {
+            (Value::Object(old_map), Value::Object(new_map)) => {
+                for (key, new_value) in new_map {
+                    let path = join_path(prefix, key);
+                    match old_map.get(key) {
+                        Some(old_value) => self.walk(&path, old_value, new_value),
+                        None => self.added.push(path),
+                    }
+                }
+                for key in old_map.keys() {
+                    if !new_map.contains_key(key) {
+                        self.dropped.push(join_path(prefix, key));
+                    }
+                }
+            }
+            // The version bump is reported through from_version/to_version
+            _ if prefix == "config_version" => {}
+            // Root-level mismatch means the file was unreadable and replaced wholesale
+            _ if prefix.is_empty() => {
+                if let Value::Object(new_map) = new {
+                    self.added.extend(new_map.keys().cloned());
+                }
+            }
+            _ if old != new => self.defaulted.push(prefix.to_string()),
+            _ => {}
+        }
+    }
+}
+
+fn join_path(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{prefix}.{key}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_diff_reports_added_defaulted_and_dropped_fields() {
+        let raw = json!({
+            "config_version": "v8",
+            "theme": "DARK",
+            "legacy_flag": true,
+            "telegram": { "chat_id": 1, "stream_enabled": "yes" }
+        })
+        .to_string();
+        let migrated = json!({
+            "config_version": "v9",
+            "theme": "DARK",
+            "telegram": { "chat_id": 1, "stream_enabled": false, "notify_on_failed": false }
+        });
+
+        let report = ConfigMigrationReport::diff(&raw, &migrated);
+
+        assert_eq!(report.from_version.as_deref(), Some("v8"));
+        assert_eq!(report.to_version, "v9");
+        assert_eq!(report.added, vec!["telegram.notify_on_failed"]);
+        assert_eq!(report.defaulted, vec!["telegram.stream_enabled"]);
+        assert_eq!(report.dropped, vec!["legacy_flag"]);
+        assert!(report.has_changes());
+    }
+
+    #[test]
+    fn test_diff_unchanged_config_has_no_changes() {
+        let value = json!({ "config_version": "v9", "theme": "LIGHT" });
+
+        let report = ConfigMigrationReport::diff(&value.to_string(), &value);
+
+        assert!(!report.has_changes());
+    }
+
+    #[test]
+    fn test_diff_invalid_json_reports_all_fields_added() {
+        let migrated = json!({ "config_version": "v9", "theme": "SYSTEM" });
+
+        let report = ConfigMigrationReport::diff("not json", &migrated);
+
+        assert_eq!(report.from_version, None);
+        assert_eq!(report.added, vec!["config_version", "theme"]);
+        assert!(report.defaulted.is_empty());
+        assert!(report.dropped.is_empty());
+    }
+}
@@ -3,9 +3,13 @@ use std::path::PathBuf;
 use thiserror::Error;
 
 pub mod editor;
+mod migration_report;
+mod patch;
 mod versions;
 
 pub use editor::EditorOpenError;
+pub use migration_report::ConfigMigrationReport;
+pub use patch::apply_config_patch;
 
 #[derive(Debug, Error)]
 pub enum ConfigError {
@@ -17,25 +21,77 @@ pub enum ConfigError {
     ValidationError(String),
 }
 
-pub type Config = versions::v9::Config;
-pub type NotificationConfig = versions::v9::NotificationConfig;
-pub type EditorConfig = versions::v9::EditorConfig;
-pub type ThemeMode = versions::v9::ThemeMode;
-pub type SoundFile = versions::v9::SoundFile;
-pub type EditorType = versions::v9::EditorType;
-pub type GitHubConfig = versions::v9::GitHubConfig;
-pub type UiLanguage = versions::v9::UiLanguage;
-pub type ShowcaseState = versions::v9::ShowcaseState;
-pub type SendMessageShortcut = versions::v9::SendMessageShortcut;
-pub type TelegramConfig = versions::v9::TelegramConfig;
+pub type Config = versions::v10::Config;
+pub type NotificationConfig = versions::v10::NotificationConfig;
+pub type PushoverConfig = versions::v10::PushoverConfig;
+pub type EditorConfig = versions::v10::EditorConfig;
+pub type ThemeMode = versions::v10::ThemeMode;
+pub type SoundFile = versions::v10::SoundFile;
+pub type EditorType = versions::v10::EditorType;
+pub type GitHubConfig = versions::v10::GitHubConfig;
+pub type UiLanguage = versions::v10::UiLanguage;
+pub type ShowcaseState = versions::v10::ShowcaseState;
+pub type SendMessageShortcut = versions::v10::SendMessageShortcut;
+pub type NotificationPreferences = versions::v10::NotificationPreferences;
+pub type NotificationEventType = versions::v10::NotificationEventType;
+pub type QuietHours = versions::v10::QuietHours;
+pub type EscalationStep = versions::v10::EscalationStep;
+pub type EscalationPolicy = versions::v10::EscalationPolicy;
+pub type TelegramConfig = versions::v10::TelegramConfig;
+pub type TelegramProjectBot = versions::v10::TelegramProjectBot;
+pub type SlackConfig = versions::v10::SlackConfig;
+pub type EmailConfig = versions::v10::EmailConfig;
+pub type SmtpTls = versions::v10::SmtpTls;
+pub type NtfyConfig = versions::v10::NtfyConfig;
+pub type GotifyConfig = versions::v10::GotifyConfig;
+pub type WebPushConfig = versions::v10::WebPushConfig;
+pub type AppriseConfig = versions::v10::AppriseConfig;
+pub type TwilioConfig = versions::v10::TwilioConfig;
+pub type WhatsAppConfig = versions::v10::WhatsAppConfig;
+pub type WeeklyDigestConfig = versions::v10::WeeklyDigestConfig;
+pub type DigestWeekday = versions::v10::DigestWeekday;
+pub type DigestSchedule = versions::v10::DigestSchedule;
+pub type TelegramParseMode = versions::v10::TelegramParseMode;
+pub type TtsConfig = versions::v10::TtsConfig;
+pub type TtsBackend = versions::v10::TtsBackend;
+pub type TelegramRetryConfig = versions::v10::TelegramRetryConfig;
+pub type StatusTransitionRule = versions::v10::StatusTransitionRule;
+pub type VaultExportConfig = versions::v10::VaultExportConfig;
+pub type VaultExportSchedule = versions::v10::VaultExportSchedule;
+pub type PostMergeVerificationConfig = versions::v10::PostMergeVerificationConfig;
+pub type AnalyticsExportConfig = versions::v10::AnalyticsExportConfig;
+pub type AnalyticsExportFormat = versions::v10::AnalyticsExportFormat;
 
 /// Will always return config, trying old schemas or eventually returning default
 pub async fn load_config_from_file(config_path: &PathBuf) -> Config {
+    load_config_with_report(config_path).await.0
+}
+
+/// Like [`load_config_from_file`], but also reports which fields were added, defaulted
+/// or dropped while loading. No report is produced when there was no config file.
+pub async fn load_config_with_report(
+    config_path: &PathBuf,
+) -> (Config, Option<ConfigMigrationReport>) {
     match std::fs::read_to_string(config_path) {
-        Ok(raw_config) => Config::from(raw_config),
+        Ok(raw_config) => {
+            let config = Config::from(raw_config.clone());
+            let report = serde_json::to_value(&config)
+                .ok()
+                .map(|migrated| ConfigMigrationReport::diff(&raw_config, &migrated));
+            if let Some(report) = report.as_ref().filter(|r| r.has_changes()) {
+                tracing::info!(
+                    "Config loaded as {}: {} added, {} defaulted, {} dropped",
+                    report.to_version,
+                    report.added.len(),
+                    report.defaulted.len(),
+                    report.dropped.len()
+                );
+            }
+            (config, report)
+        }
         Err(_) => {
             tracing::info!("No config file found, creating one");
-            Config::default()
+            (Config::default(), None)
         }
     }
 }
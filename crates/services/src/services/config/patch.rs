@@ -0,0 +1,116 @@
+use serde_json::{Map, Value};
+
+use super::{Config, ConfigError};
+
+/// Apply a JSON merge patch (RFC 7396) to the config, validating one top-level field at a time
+/// so errors name the field that was rejected.
+///
+/// `config_version` is managed by the server and cannot be patched.
+pub fn apply_config_patch(config: &Config, patch: &Value) -> Result<Config, ConfigError> {
+    let Value::Object(fields) = patch else {
+        return Err(ConfigError::ValidationError(
+            "Config patch must be a JSON object".to_string(),
+        ));
+    };
+
+    let mut merged = serde_json::to_value(config)?;
+    for (field, value) in fields {
+        if field == "config_version" {
+            return Err(ConfigError::ValidationError(
+                "config_version cannot be changed".to_string(),
+            ));
+        }
+
+        let Value::Object(target) = &mut merged else {
+            unreachable!("Config always serializes to an object");
+        };
+        if !target.contains_key(field) {
+            return Err(ConfigError::ValidationError(format!(
+                "Unknown config field '{field}'"
+            )));
+        }
+        merge_field(target, field, value);
+
+        serde_json::from_value::<Config>(merged.clone()).map_err(|e| {
+            ConfigError::ValidationError(format!("Invalid value for '{field}': {e}"))
+        })?;
+    }
+
+    Ok(serde_json::from_value(merged)?)
+}
+
+fn merge_field(target: &mut Map<String, Value>, key: &str, patch: &Value) {
+    match patch {
+        Value::Null => {
+            target.remove(key);
+        }
+        Value::Object(patch_fields) => {
+            let entry = target
+                .entry(key.to_string())
+                .or_insert_with(|| Value::Object(Map::new()));
+            if !entry.is_object() {
+                *entry = Value::Object(Map::new());
+            }
+            if let Value::Object(nested) = entry {
+                for (nested_key, nested_patch) in patch_fields {
+                    merge_field(nested, nested_key, nested_patch);
+                }
+            }
+        }
+        _ => {
+            target.insert(key.to_string(), patch.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_patch_updates_only_given_fields() {
+        let mut config = Config::default();
+        config.telegram.chat_id = Some(42);
+
+        let patched = apply_config_patch(
+            &config,
+            &json!({ "analytics_enabled": false, "telegram": { "stream_enabled": true } }),
+        )
+        .unwrap();
+
+        assert!(!patched.analytics_enabled);
+        assert!(patched.telegram.stream_enabled);
+        // Untouched nested fields survive the merge
+        assert_eq!(patched.telegram.chat_id, Some(42));
+        assert_eq!(patched.git_branch_prefix, config.git_branch_prefix);
+    }
+
+    #[test]
+    fn test_patch_null_resets_optional_field() {
+        let mut config = Config::default();
+        config.workspace_dir = Some("/tmp/work".to_string());
+
+        let patched = apply_config_patch(&config, &json!({ "workspace_dir": null })).unwrap();
+
+        assert_eq!(patched.workspace_dir, None);
+    }
+
+    #[test]
+    fn test_patch_rejects_invalid_field_value() {
+        let err = apply_config_patch(&Config::default(), &json!({ "analytics_enabled": "yes" }))
+            .unwrap_err();
+
+        assert!(err.to_string().contains("'analytics_enabled'"));
+    }
+
+    #[test]
+    fn test_patch_rejects_unknown_field_and_version_change() {
+        let config = Config::default();
+
+        assert!(apply_config_patch(&config, &json!({ "not_a_field": 1 })).is_err());
+        assert!(apply_config_patch(&config, &json!({ "config_version": "v1" })).is_err());
+        assert!(apply_config_patch(&config, &json!([1, 2])).is_err());
+    }
+}
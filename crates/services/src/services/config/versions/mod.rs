@@ -1,4 +1,5 @@
 pub(super) mod v1;
+pub(super) mod v10;
 pub(super) mod v2;
 pub(super) mod v3;
 pub(super) mod v4;
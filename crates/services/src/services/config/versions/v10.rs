@@ -0,0 +1,1413 @@
+use std::collections::HashMap;
+
+use anyhow::Error;
+use chrono::{DateTime, FixedOffset, Local, Timelike, Utc, Weekday};
+use db::models::notification_rule::{NotificationChannel, TaskNotificationKind};
+use executors::{executors::BaseCodingAgent, profile::ExecutorProfileId};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use uuid::Uuid;
+pub use v9::{
+    AnalyticsExportConfig, AnalyticsExportFormat, DigestSchedule, EditorConfig, EditorType,
+    PostMergeVerificationConfig, SendMessageShortcut, ShowcaseState, SmtpTls, SoundFile,
+    StatusTransitionRule, TelegramParseMode, TelegramRetryConfig, ThemeMode, TtsBackend, TtsConfig,
+    UiLanguage, VaultExportConfig, VaultExportSchedule,
+};
+
+use crate::services::config::versions::v9;
+
+fn default_git_branch_prefix() -> String {
+    "vk".to_string()
+}
+
+fn default_pr_auto_description_enabled() -> bool {
+    true
+}
+
+fn default_digest_hour() -> u8 {
+    8
+}
+
+/// The address ranges Telegram sends webhook requests from
+fn default_webhook_allowed_cidrs() -> Vec<String> {
+    vec!["149.154.160.0/20".to_string(), "91.108.4.0/22".to_string()]
+}
+
+fn default_artifact_quota_mb() -> u64 {
+    1024
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+fn default_weekly_digest_hour() -> u8 {
+    9
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Events the notification preferences pick channels for
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationEventType {
+    AttemptStarted,
+    TaskInReview,
+    TaskDone,
+    TaskFailed,
+    TaskCancelled,
+    /// An attempt's pull request was merged, which also marks the task done
+    PrMerged,
+}
+
+impl NotificationEventType {
+    pub const ALL: [Self; 6] = [
+        Self::AttemptStarted,
+        Self::TaskInReview,
+        Self::TaskDone,
+        Self::TaskFailed,
+        Self::TaskCancelled,
+        Self::PrMerged,
+    ];
+
+    pub fn from_kind(kind: TaskNotificationKind) -> Self {
+        match kind {
+            TaskNotificationKind::Started => Self::AttemptStarted,
+            TaskNotificationKind::InReview => Self::TaskInReview,
+            TaskNotificationKind::Done => Self::TaskDone,
+            TaskNotificationKind::Failed => Self::TaskFailed,
+            TaskNotificationKind::Cancelled => Self::TaskCancelled,
+        }
+    }
+
+    /// The task notification this event is rendered as
+    pub fn kind(self) -> TaskNotificationKind {
+        match self {
+            Self::AttemptStarted => TaskNotificationKind::Started,
+            Self::TaskInReview => TaskNotificationKind::InReview,
+            Self::TaskDone | Self::PrMerged => TaskNotificationKind::Done,
+            Self::TaskFailed => TaskNotificationKind::Failed,
+            Self::TaskCancelled => TaskNotificationKind::Cancelled,
+        }
+    }
+
+    /// Name of the event as serialized, e.g. in the notification history
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::AttemptStarted => "attempt_started",
+            Self::TaskInReview => "task_in_review",
+            Self::TaskDone => "task_done",
+            Self::TaskFailed => "task_failed",
+            Self::TaskCancelled => "task_cancelled",
+            Self::PrMerged => "pr_merged",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|event| event.as_str() == value)
+    }
+}
+
+/// Channels each kind of event is sent through. A channel also has to be
+/// enabled and set up in its own settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct NotificationPreferences {
+    pub attempt_started: Vec<NotificationChannel>,
+    pub task_in_review: Vec<NotificationChannel>,
+    pub task_done: Vec<NotificationChannel>,
+    pub task_failed: Vec<NotificationChannel>,
+    pub task_cancelled: Vec<NotificationChannel>,
+    pub pr_merged: Vec<NotificationChannel>,
+}
+
+impl NotificationPreferences {
+    /// Preferences that send nothing anywhere
+    fn none() -> Self {
+        Self {
+            attempt_started: Vec::new(),
+            task_in_review: Vec::new(),
+            task_done: Vec::new(),
+            task_failed: Vec::new(),
+            task_cancelled: Vec::new(),
+            pr_merged: Vec::new(),
+        }
+    }
+
+    pub fn channels(&self, event: NotificationEventType) -> &[NotificationChannel] {
+        match event {
+            NotificationEventType::AttemptStarted => &self.attempt_started,
+            NotificationEventType::TaskInReview => &self.task_in_review,
+            NotificationEventType::TaskDone => &self.task_done,
+            NotificationEventType::TaskFailed => &self.task_failed,
+            NotificationEventType::TaskCancelled => &self.task_cancelled,
+            NotificationEventType::PrMerged => &self.pr_merged,
+        }
+    }
+
+    fn channels_mut(&mut self, event: NotificationEventType) -> &mut Vec<NotificationChannel> {
+        match event {
+            NotificationEventType::AttemptStarted => &mut self.attempt_started,
+            NotificationEventType::TaskInReview => &mut self.task_in_review,
+            NotificationEventType::TaskDone => &mut self.task_done,
+            NotificationEventType::TaskFailed => &mut self.task_failed,
+            NotificationEventType::TaskCancelled => &mut self.task_cancelled,
+            NotificationEventType::PrMerged => &mut self.pr_merged,
+        }
+    }
+
+    pub fn allows(&self, event: NotificationEventType, channel: NotificationChannel) -> bool {
+        self.channels(event).contains(&channel)
+    }
+
+    /// Turn sending `event` through `channel` on or off
+    pub fn set(&mut self, event: NotificationEventType, channel: NotificationChannel, on: bool) {
+        let channels = self.channels_mut(event);
+        channels.retain(|c| *c != channel);
+        if on {
+            channels.push(channel);
+        }
+    }
+
+    /// Carry over the per-event switches of the v9 channel settings. PR
+    /// merges used to be announced like completed runs.
+    fn from_v9_config(old: &v9::Config) -> Self {
+        let mut preferences = Self::none();
+        let mut add = |channel: NotificationChannel, kinds: &[TaskNotificationKind]| {
+            for &kind in kinds {
+                preferences.set(NotificationEventType::from_kind(kind), channel, true);
+                if kind == TaskNotificationKind::Done {
+                    preferences.set(NotificationEventType::PrMerged, channel, true);
+                }
+            }
+        };
+        let when = |on: bool, kind: TaskNotificationKind| on.then_some(kind);
+
+        add(
+            NotificationChannel::Desktop,
+            &[TaskNotificationKind::Done, TaskNotificationKind::Failed],
+        );
+
+        // Custom transitions replaced the per-status toggles, except for failures
+        let telegram = &old.telegram;
+        let mut telegram_kinds: Vec<_> = if telegram.notify_transitions.is_empty() {
+            [
+                when(telegram.notify_on_started, TaskNotificationKind::Started),
+                when(telegram.notify_on_in_review, TaskNotificationKind::InReview),
+                when(telegram.notify_on_task_done, TaskNotificationKind::Done),
+            ]
+            .into_iter()
+            .flatten()
+            .collect()
+        } else {
+            telegram
+                .notify_transitions
+                .iter()
+                .filter_map(|rule| TaskNotificationKind::from_status(&rule.to))
+                .collect()
+        };
+        telegram_kinds.extend(when(
+            telegram.notify_on_failed,
+            TaskNotificationKind::Failed,
+        ));
+        add(NotificationChannel::Telegram, &telegram_kinds);
+
+        let slack_kinds: Vec<_> = when(old.slack.notify_on_task_done, TaskNotificationKind::Done)
+            .into_iter()
+            .collect();
+        add(NotificationChannel::Slack, &slack_kinds);
+
+        let email_kinds: Vec<_> = [
+            when(old.email.notify_on_task_done, TaskNotificationKind::Done),
+            when(old.email.notify_on_failed, TaskNotificationKind::Failed),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        add(NotificationChannel::Email, &email_kinds);
+
+        add(NotificationChannel::Ntfy, &old.ntfy.notify_kinds);
+        add(
+            NotificationChannel::Pushover,
+            &old.notifications.pushover.notify_kinds,
+        );
+        add(NotificationChannel::Gotify, &old.gotify.notify_kinds);
+        add(NotificationChannel::WebPush, &old.web_push.notify_kinds);
+        // Apprise URLs, SMS and WhatsApp are new; they get the defaults
+        add(
+            NotificationChannel::Apprise,
+            &[TaskNotificationKind::Done, TaskNotificationKind::Failed],
+        );
+        add(
+            NotificationChannel::WhatsApp,
+            &[TaskNotificationKind::Done, TaskNotificationKind::Failed],
+        );
+        add(NotificationChannel::Sms, &[TaskNotificationKind::Failed]);
+
+        preferences
+    }
+}
+
+impl Default for NotificationPreferences {
+    /// Finished runs and merges everywhere, reviews to the browser and
+    /// failures also by SMS
+    fn default() -> Self {
+        let everywhere = vec![
+            NotificationChannel::Desktop,
+            NotificationChannel::Telegram,
+            NotificationChannel::Slack,
+            NotificationChannel::Email,
+            NotificationChannel::Ntfy,
+            NotificationChannel::Pushover,
+            NotificationChannel::Gotify,
+            NotificationChannel::WebPush,
+            NotificationChannel::Apprise,
+            NotificationChannel::WhatsApp,
+        ];
+        Self {
+            attempt_started: Vec::new(),
+            task_in_review: vec![NotificationChannel::WebPush],
+            task_done: everywhere.clone(),
+            task_failed: [everywhere.as_slice(), &[NotificationChannel::Sms]].concat(),
+            task_cancelled: Vec::new(),
+            pr_merged: everywhere,
+        }
+    }
+}
+
+/// A daily window during which notifications to some channels are held
+/// back, to be sent as one catch-up message once it ends
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct QuietHours {
+    pub enabled: bool,
+    /// Channels the window applies to; empty for all of them
+    pub channels: Vec<NotificationChannel>,
+    /// Hour (0-23) the window starts at
+    pub start_hour: u8,
+    /// Hour (0-23) the window ends at; earlier than `start_hour` for a window
+    /// spanning midnight
+    pub end_hour: u8,
+    /// Offset from UTC the hours are in, in minutes; the server's local time
+    /// when unset
+    pub utc_offset_minutes: Option<i32>,
+    /// Whether failures are sent right away instead of being held
+    pub failures_break_through: bool,
+}
+
+impl QuietHours {
+    pub fn applies_to(&self, channel: NotificationChannel) -> bool {
+        self.enabled && (self.channels.is_empty() || self.channels.contains(&channel))
+    }
+
+    /// Whether `now` falls within the window
+    pub fn is_quiet_at(&self, now: DateTime<Utc>) -> bool {
+        let hour = match self
+            .utc_offset_minutes
+            .and_then(|minutes| FixedOffset::east_opt(minutes * 60))
+        {
+            Some(offset) => now.with_timezone(&offset).hour(),
+            None => now.with_timezone(&Local).hour(),
+        };
+        let (start, end) = (u32::from(self.start_hour), u32::from(self.end_hour));
+        if start <= end {
+            (start..end).contains(&hour)
+        } else {
+            hour >= start || hour < end
+        }
+    }
+}
+
+impl Default for QuietHours {
+    /// Overnight, for every channel, letting failures through
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            channels: Vec::new(),
+            start_hour: 22,
+            end_hour: 7,
+            utc_offset_minutes: None,
+            failures_break_through: true,
+        }
+    }
+}
+
+/// One step of an escalation: a channel an unacknowledged failure is sent
+/// through next
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct EscalationStep {
+    pub channel: NotificationChannel,
+    /// Minutes after the failure at which it is sent here, if nobody has
+    /// acknowledged it by then
+    pub after_minutes: u32,
+}
+
+/// Task failures nobody acknowledges are sent through more channels as time
+/// passes, e.g. Telegram after a quarter of an hour, then SMS
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct EscalationPolicy {
+    pub enabled: bool,
+    /// Steps in the order they are taken
+    pub steps: Vec<EscalationStep>,
+}
+
+impl Default for EscalationPolicy {
+    /// Off; when turned on, Telegram after 15 minutes, then SMS after 30
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            steps: vec![
+                EscalationStep {
+                    channel: NotificationChannel::Telegram,
+                    after_minutes: 15,
+                },
+                EscalationStep {
+                    channel: NotificationChannel::Sms,
+                    after_minutes: 30,
+                },
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct GitHubConfig {
+    pub pat: Option<String>,
+    pub oauth_token: Option<String>,
+    pub username: Option<String>,
+    pub primary_email: Option<String>,
+    pub default_pr_base: Option<String>,
+    /// Token `gh` runs with instead of its own login. Only
+    /// `POST /api/github/rotate-token` sets it.
+    #[serde(default)]
+    pub cli_token: Option<String>,
+    /// Account `cli_token` belongs to; later rotations must be to a token of
+    /// the same account
+    #[serde(default)]
+    pub cli_token_login: Option<String>,
+}
+
+impl From<v9::GitHubConfig> for GitHubConfig {
+    fn from(old: v9::GitHubConfig) -> Self {
+        Self {
+            pat: old.pat,
+            oauth_token: old.oauth_token,
+            username: old.username,
+            primary_email: old.primary_email,
+            default_pr_base: old.default_pr_base,
+            cli_token: None,
+            cli_token_login: None,
+        }
+    }
+}
+
+impl Default for GitHubConfig {
+    fn default() -> Self {
+        Self {
+            pat: None,
+            oauth_token: None,
+            username: None,
+            primary_email: None,
+            default_pr_base: Some("main".to_string()),
+            cli_token: None,
+            cli_token_login: None,
+        }
+    }
+}
+
+impl GitHubConfig {
+    pub fn token(&self) -> Option<String> {
+        self.pat
+            .as_deref()
+            .or(self.oauth_token.as_deref())
+            .map(|s| s.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct NotificationConfig {
+    pub sound_enabled: bool,
+    pub push_enabled: bool,
+    pub sound_file: SoundFile,
+    /// Leave emoji out of push notifications, for screen readers
+    #[serde(default)]
+    pub plain_text: bool,
+    /// Task notifications sent to the Pushover apps, alongside the desktop ones
+    #[serde(default)]
+    pub pushover: PushoverConfig,
+}
+
+impl From<v9::NotificationConfig> for NotificationConfig {
+    fn from(old: v9::NotificationConfig) -> Self {
+        Self {
+            sound_enabled: old.sound_enabled,
+            push_enabled: old.push_enabled,
+            sound_file: old.sound_file,
+            plain_text: old.plain_text,
+            pushover: PushoverConfig {
+                enabled: old.pushover.enabled,
+                user_key: old.pushover.user_key,
+                app_token: old.pushover.app_token,
+            },
+        }
+    }
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            sound_enabled: true,
+            push_enabled: true,
+            sound_file: SoundFile::CowMooing,
+            plain_text: false,
+            pushover: PushoverConfig::default(),
+        }
+    }
+}
+
+/// Credentials for Pushover notifications
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+pub struct PushoverConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// User or group key the notifications are sent to
+    #[serde(default)]
+    pub user_key: Option<String>,
+    /// API token of the Pushover application sending them
+    #[serde(default)]
+    pub app_token: Option<String>,
+}
+
+/// Slack workspace linked through the app's OAuth install flow
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+pub struct SlackConfig {
+    pub team_id: Option<String>,
+    pub team_name: Option<String>,
+    /// Bot token issued by the install
+    #[serde(default)]
+    pub bot_token: Option<String>,
+    /// Channel task notifications are posted to, the one picked during the
+    /// install unless changed later
+    #[serde(default)]
+    pub channel_id: Option<String>,
+    #[serde(default)]
+    pub channel_name: Option<String>,
+    /// Project `/vk tasks` and `/vk newtask` use when none is given
+    #[serde(default)]
+    pub default_project_id: Option<Uuid>,
+    #[serde(default)]
+    pub notifications_enabled: bool,
+}
+
+impl From<v9::SlackConfig> for SlackConfig {
+    fn from(old: v9::SlackConfig) -> Self {
+        Self {
+            team_id: old.team_id,
+            team_name: old.team_name,
+            bot_token: old.bot_token,
+            channel_id: old.channel_id,
+            channel_name: old.channel_name,
+            default_project_id: old.default_project_id,
+            notifications_enabled: old.notifications_enabled,
+        }
+    }
+}
+
+/// Task notifications sent by email
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct EmailConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub smtp_host: Option<String>,
+    #[serde(default = "default_smtp_port")]
+    pub smtp_port: u16,
+    #[serde(default)]
+    pub tls: SmtpTls,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Sender, e.g. `Vibe Kanban <kanban@example.com>`
+    #[serde(default)]
+    pub from_address: Option<String>,
+    #[serde(default)]
+    pub to_addresses: Vec<String>,
+    #[serde(default)]
+    pub weekly_digest: WeeklyDigestConfig,
+}
+
+impl From<v9::EmailConfig> for EmailConfig {
+    fn from(old: v9::EmailConfig) -> Self {
+        Self {
+            enabled: old.enabled,
+            smtp_host: old.smtp_host,
+            smtp_port: old.smtp_port,
+            tls: old.tls,
+            username: old.username,
+            password: old.password,
+            from_address: old.from_address,
+            to_addresses: old.to_addresses,
+            weekly_digest: WeeklyDigestConfig::default(),
+        }
+    }
+}
+
+impl Default for EmailConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            smtp_host: None,
+            smtp_port: default_smtp_port(),
+            tls: SmtpTls::default(),
+            username: None,
+            password: None,
+            from_address: None,
+            to_addresses: Vec::new(),
+            weekly_digest: WeeklyDigestConfig::default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, TS, PartialEq, Eq)]
+pub enum DigestWeekday {
+    #[default]
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl DigestWeekday {
+    pub fn weekday(self) -> Weekday {
+        match self {
+            Self::Monday => Weekday::Mon,
+            Self::Tuesday => Weekday::Tue,
+            Self::Wednesday => Weekday::Wed,
+            Self::Thursday => Weekday::Thu,
+            Self::Friday => Weekday::Fri,
+            Self::Saturday => Weekday::Sat,
+            Self::Sunday => Weekday::Sun,
+        }
+    }
+}
+
+/// Weekly report emailed for each project, sent over the SMTP server of the
+/// email notifications
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct WeeklyDigestConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub day: DigestWeekday,
+    /// Local hour the report is sent at, 0-23
+    #[serde(default = "default_weekly_digest_hour")]
+    pub hour: u8,
+    /// Who gets the report; the notification recipients when empty
+    #[serde(default)]
+    pub recipients: Vec<String>,
+}
+
+impl Default for WeeklyDigestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            day: DigestWeekday::default(),
+            hour: default_weekly_digest_hour(),
+            recipients: Vec::new(),
+        }
+    }
+}
+
+/// Task notifications pushed through an ntfy server, e.g. ntfy.sh, whose
+/// mobile apps need no bot of their own
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+pub struct NtfyConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Topic to publish to, e.g. `https://ntfy.sh/my-kanban`
+    #[serde(default)]
+    pub topic_url: Option<String>,
+    /// Access token for protected topics, sent as a bearer token
+    #[serde(default)]
+    pub access_token: Option<String>,
+}
+
+impl From<v9::NtfyConfig> for NtfyConfig {
+    fn from(old: v9::NtfyConfig) -> Self {
+        Self {
+            enabled: old.enabled,
+            topic_url: old.topic_url,
+            access_token: old.access_token,
+        }
+    }
+}
+
+/// Task notifications pushed through a self-hosted Gotify server
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct GotifyConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Base URL of the Gotify server, e.g. `https://gotify.example.com`
+    #[serde(default)]
+    pub server_url: Option<String>,
+    /// Token of the Gotify application the messages are posted as
+    #[serde(default)]
+    pub app_token: Option<String>,
+    /// Have the Gotify clients render messages as markdown
+    #[serde(default = "default_true")]
+    pub markdown: bool,
+}
+
+impl From<v9::GotifyConfig> for GotifyConfig {
+    fn from(old: v9::GotifyConfig) -> Self {
+        Self {
+            enabled: old.enabled,
+            server_url: old.server_url,
+            app_token: old.app_token,
+            markdown: old.markdown,
+        }
+    }
+}
+
+impl Default for GotifyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            server_url: None,
+            app_token: None,
+            markdown: true,
+        }
+    }
+}
+
+/// Task notifications pushed to the browsers that subscribed in the web UI
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct WebPushConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Contact for push services, a `mailto:` or `https:` URL
+    #[serde(default)]
+    pub subject: Option<String>,
+    /// VAPID signing key, base64url; generated when the web UI first asks for
+    /// the public key
+    #[serde(default)]
+    pub vapid_private_key: Option<String>,
+}
+
+impl From<v9::WebPushConfig> for WebPushConfig {
+    fn from(old: v9::WebPushConfig) -> Self {
+        Self {
+            enabled: old.enabled,
+            subject: old.subject,
+            vapid_private_key: old.vapid_private_key,
+        }
+    }
+}
+
+impl Default for WebPushConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            subject: None,
+            vapid_private_key: None,
+        }
+    }
+}
+
+/// Task notifications sent to Apprise-style URLs, e.g. `tgram://`,
+/// `slack://` or `mailto://`; see `services::apprise` for the schemes
+/// supported
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+pub struct AppriseConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub urls: Vec<String>,
+}
+
+fn default_sms_max_per_hour() -> u32 {
+    5
+}
+
+/// Text messages sent through Twilio for critical events. Each message
+/// costs money, so only failures are sent and at most `max_per_hour` go out.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct TwilioConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub account_sid: Option<String>,
+    #[serde(default)]
+    pub auth_token: Option<String>,
+    /// Twilio number the messages are sent from, in E.164 format
+    #[serde(default)]
+    pub from_number: Option<String>,
+    /// Numbers the messages are sent to, in E.164 format
+    #[serde(default)]
+    pub to_numbers: Vec<String>,
+    /// Tasks whose failures are texted; failures of any task when empty
+    #[serde(default)]
+    pub watched_task_ids: Vec<Uuid>,
+    /// Messages sent in any hour, counting every recipient; the rest are
+    /// dropped
+    #[serde(default = "default_sms_max_per_hour")]
+    pub max_per_hour: u32,
+}
+
+impl Default for TwilioConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            account_sid: None,
+            auth_token: None,
+            from_number: None,
+            to_numbers: Vec::new(),
+            watched_task_ids: Vec::new(),
+            max_per_hour: default_sms_max_per_hour(),
+        }
+    }
+}
+
+fn default_whatsapp_template_name() -> String {
+    "task_update".to_string()
+}
+
+fn default_whatsapp_template_language() -> String {
+    "en_US".to_string()
+}
+
+/// WhatsApp Business number, through the Cloud API, that sends task
+/// notifications and answers commands
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct WhatsAppConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// ID of the business phone number messages are sent from
+    #[serde(default)]
+    pub phone_number_id: Option<String>,
+    #[serde(default)]
+    pub access_token: Option<String>,
+    /// Secret of the Meta app, which signs webhook requests
+    #[serde(default)]
+    pub app_secret: Option<String>,
+    /// Token Meta sends back when the webhook is subscribed
+    #[serde(default)]
+    pub verify_token: Option<String>,
+    /// Numbers notified, with country code and digits only. Commands are
+    /// only taken from these numbers.
+    #[serde(default)]
+    pub recipients: Vec<String>,
+    /// Approved message template for notifications. Its body takes the
+    /// task title as `{{1}}` and what happened, e.g. "completed", as `{{2}}`.
+    #[serde(default = "default_whatsapp_template_name")]
+    pub template_name: String,
+    #[serde(default = "default_whatsapp_template_language")]
+    pub template_language: String,
+    /// Project `tasks` and `newtask` use when none is given
+    #[serde(default)]
+    pub default_project_id: Option<Uuid>,
+}
+
+impl Default for WhatsAppConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            phone_number_id: None,
+            access_token: None,
+            app_secret: None,
+            verify_token: None,
+            recipients: Vec::new(),
+            template_name: default_whatsapp_template_name(),
+            template_language: default_whatsapp_template_language(),
+            default_project_id: None,
+        }
+    }
+}
+
+/// A bot of its own for one project, e.g. a client-facing bot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+pub struct TelegramProjectBot {
+    pub project_id: Uuid,
+    pub bot_token: String,
+    /// Chat the project's notifications go to, or the linked chat when unset
+    #[serde(default)]
+    pub chat_id: Option<i64>,
+    /// Secret Telegram sends with each webhook request of this bot, generated
+    /// when the webhook is first registered
+    #[serde(default)]
+    pub webhook_secret: Option<String>,
+}
+
+impl From<v9::TelegramProjectBot> for TelegramProjectBot {
+    fn from(old: v9::TelegramProjectBot) -> Self {
+        Self {
+            project_id: old.project_id,
+            bot_token: old.bot_token,
+            chat_id: old.chat_id,
+            webhook_secret: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct TelegramConfig {
+    pub chat_id: Option<i64>,
+    pub user_id: Option<i64>,
+    pub username: Option<String>,
+    pub notifications_enabled: bool,
+    pub include_llm_summary: bool,
+    #[serde(default)]
+    pub stream_enabled: bool,
+    #[serde(default)]
+    pub digest_schedule: DigestSchedule,
+    #[serde(default = "default_digest_hour")]
+    pub digest_hour: u8,
+    #[serde(default)]
+    pub parse_mode: TelegramParseMode,
+    /// Send the LLM summary of finished attempts as a voice message
+    #[serde(default)]
+    pub voice_summary: bool,
+    #[serde(default)]
+    pub tts: TtsConfig,
+    #[serde(default)]
+    pub retry: TelegramRetryConfig,
+    /// When non-empty, of the events the notification preferences send to
+    /// Telegram, only these status transitions are sent. Attempt failures are
+    /// not filtered.
+    #[serde(default)]
+    pub notify_transitions: Vec<StatusTransitionRule>,
+    /// Deliver every notification without sound
+    #[serde(default)]
+    pub silent: bool,
+    /// Notification kinds delivered without sound even when `silent` is off
+    #[serde(default)]
+    pub silent_kinds: Vec<TaskNotificationKind>,
+    /// Task notifications are paused until this time (set with /mute)
+    #[serde(default)]
+    pub muted_until: Option<DateTime<Utc>>,
+    /// Keep one message per task and edit it on each status change instead of
+    /// sending a new one. Edits arrive without sound.
+    #[serde(default)]
+    pub edit_in_place: bool,
+    /// Forward questions an agent ends its turn with to the chat, so the
+    /// reply can be sent back as a follow-up
+    #[serde(default)]
+    pub forward_questions: bool,
+    /// Telegram user IDs allowed to use the bot besides the linked accounts.
+    /// Everyone else can only run /start and /help.
+    #[serde(default)]
+    pub allowed_user_ids: Vec<i64>,
+    /// Bot command aliases, without the leading slash. A value may carry
+    /// leading arguments, e.g. `"bug": "newtask #bug"`.
+    #[serde(default)]
+    pub command_aliases: HashMap<String, String>,
+    /// Projects that send their notifications through a bot of their own.
+    /// Each one receives updates at `/api/telegram/webhook/{project_id}`.
+    #[serde(default)]
+    pub project_bots: Vec<TelegramProjectBot>,
+    /// Task notifications a chat gets within this many seconds of each
+    /// other are combined into one message. 0 sends each right away.
+    #[serde(default)]
+    pub batch_window_secs: u64,
+    /// Public URL this server is reachable at, e.g. `https://kanban.example.com`.
+    /// When set, the bots' webhooks are registered with Telegram on startup and
+    /// removed on shutdown.
+    #[serde(default)]
+    pub webhook_base_url: Option<String>,
+    /// Secret Telegram sends with each webhook request of the default bot,
+    /// generated when the webhook is first registered
+    #[serde(default)]
+    pub webhook_secret: Option<String>,
+    /// Only accept webhook requests from `webhook_allowed_cidrs`, on top of
+    /// the webhook secret. Behind a reverse proxy on the same host, the
+    /// address the proxy appended to `X-Forwarded-For` is checked instead of
+    /// the proxy's own.
+    #[serde(default = "default_true")]
+    pub verify_webhook_source: bool,
+    /// Address ranges webhook requests may come from, Telegram's published
+    /// ranges by default
+    #[serde(default = "default_webhook_allowed_cidrs")]
+    pub webhook_allowed_cidrs: Vec<String>,
+}
+
+impl TelegramConfig {
+    pub fn is_muted(&self, now: DateTime<Utc>) -> bool {
+        self.muted_until.is_some_and(|until| until > now)
+    }
+
+    pub fn project_bot(&self, project_id: Uuid) -> Option<&TelegramProjectBot> {
+        self.project_bots
+            .iter()
+            .find(|bot| bot.project_id == project_id)
+    }
+
+    /// Whether `user_id` is the primary linked user or on `allowed_user_ids`,
+    /// who are admins without a linked account
+    pub fn is_configured_admin(&self, user_id: i64) -> bool {
+        self.user_id == Some(user_id) || self.allowed_user_ids.contains(&user_id)
+    }
+}
+
+impl From<v9::TelegramConfig> for TelegramConfig {
+    fn from(old: v9::TelegramConfig) -> Self {
+        Self {
+            chat_id: old.chat_id,
+            user_id: old.user_id,
+            username: old.username,
+            notifications_enabled: old.notifications_enabled,
+            include_llm_summary: old.include_llm_summary,
+            stream_enabled: old.stream_enabled,
+            digest_schedule: old.digest_schedule,
+            digest_hour: old.digest_hour,
+            parse_mode: old.parse_mode,
+            voice_summary: old.voice_summary,
+            tts: old.tts,
+            retry: old.retry,
+            notify_transitions: old.notify_transitions,
+            silent: old.silent,
+            silent_kinds: old.silent_kinds,
+            muted_until: old.muted_until,
+            edit_in_place: old.edit_in_place,
+            forward_questions: old.forward_questions,
+            allowed_user_ids: old.allowed_user_ids,
+            command_aliases: old.command_aliases,
+            project_bots: old.project_bots.into_iter().map(Into::into).collect(),
+            batch_window_secs: old.batch_window_secs,
+            webhook_base_url: old.webhook_base_url,
+            webhook_secret: None,
+            // v9 could not tell an explicit `false` from the old default
+            verify_webhook_source: true,
+            webhook_allowed_cidrs: old.webhook_allowed_cidrs,
+        }
+    }
+}
+
+impl Default for TelegramConfig {
+    fn default() -> Self {
+        Self {
+            chat_id: None,
+            user_id: None,
+            username: None,
+            notifications_enabled: false,
+            include_llm_summary: false,
+            stream_enabled: false,
+            digest_schedule: DigestSchedule::Off,
+            digest_hour: default_digest_hour(),
+            parse_mode: TelegramParseMode::Html,
+            voice_summary: false,
+            tts: TtsConfig::default(),
+            retry: TelegramRetryConfig::default(),
+            notify_transitions: Vec::new(),
+            silent: false,
+            silent_kinds: Vec::new(),
+            muted_until: None,
+            edit_in_place: false,
+            forward_questions: false,
+            allowed_user_ids: Vec::new(),
+            command_aliases: HashMap::new(),
+            project_bots: Vec::new(),
+            batch_window_secs: 0,
+            webhook_base_url: None,
+            webhook_secret: None,
+            verify_webhook_source: true,
+            webhook_allowed_cidrs: default_webhook_allowed_cidrs(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
+pub struct Config {
+    pub config_version: String,
+    pub theme: ThemeMode,
+    pub executor_profile: ExecutorProfileId,
+    pub disclaimer_acknowledged: bool,
+    pub onboarding_acknowledged: bool,
+    pub notifications: NotificationConfig,
+    pub editor: EditorConfig,
+    pub github: GitHubConfig,
+    pub analytics_enabled: bool,
+    pub workspace_dir: Option<String>,
+    pub last_app_version: Option<String>,
+    pub show_release_notes: bool,
+    #[serde(default)]
+    pub language: UiLanguage,
+    #[serde(default = "default_git_branch_prefix")]
+    pub git_branch_prefix: String,
+    #[serde(default)]
+    pub showcases: ShowcaseState,
+    #[serde(default = "default_pr_auto_description_enabled")]
+    pub pr_auto_description_enabled: bool,
+    #[serde(default)]
+    pub pr_auto_description_prompt: Option<String>,
+    #[serde(default)]
+    pub beta_workspaces: bool,
+    #[serde(default)]
+    pub beta_workspaces_invitation_sent: bool,
+    #[serde(default)]
+    pub commit_reminder: bool,
+    #[serde(default)]
+    pub send_message_shortcut: SendMessageShortcut,
+    /// Which channels each kind of task event is sent through
+    #[serde(default)]
+    pub notification_preferences: NotificationPreferences,
+    /// Windows during which notifications are held back
+    #[serde(default)]
+    pub quiet_hours: Vec<QuietHours>,
+    /// Where failures nobody acknowledges are sent next
+    #[serde(default)]
+    pub escalation: EscalationPolicy,
+    #[serde(default)]
+    pub telegram: TelegramConfig,
+    #[serde(default)]
+    pub slack: SlackConfig,
+    #[serde(default)]
+    pub email: EmailConfig,
+    #[serde(default)]
+    pub ntfy: NtfyConfig,
+    #[serde(default)]
+    pub gotify: GotifyConfig,
+    #[serde(default)]
+    pub web_push: WebPushConfig,
+    #[serde(default)]
+    pub apprise: AppriseConfig,
+    #[serde(default)]
+    pub twilio: TwilioConfig,
+    #[serde(default)]
+    pub whatsapp: WhatsAppConfig,
+    /// Maximum total size of attempt artifacts stored per project
+    #[serde(default = "default_artifact_quota_mb")]
+    pub artifact_quota_mb: u64,
+    #[serde(default)]
+    pub vault_export: VaultExportConfig,
+    #[serde(default)]
+    pub post_merge_verification: PostMergeVerificationConfig,
+    #[serde(default)]
+    pub analytics_export: AnalyticsExportConfig,
+    /// Reject every change with 503 while reads keep working, e.g. during
+    /// backups and migrations
+    #[serde(default)]
+    pub maintenance_mode: bool,
+}
+
+impl Config {
+    fn from_v9_config(old_config: v9::Config) -> Self {
+        let notification_preferences = NotificationPreferences::from_v9_config(&old_config);
+        Self {
+            config_version: "v10".to_string(),
+            theme: old_config.theme,
+            executor_profile: old_config.executor_profile,
+            disclaimer_acknowledged: old_config.disclaimer_acknowledged,
+            onboarding_acknowledged: old_config.onboarding_acknowledged,
+            notifications: old_config.notifications.into(),
+            editor: old_config.editor,
+            github: old_config.github.into(),
+            analytics_enabled: old_config.analytics_enabled,
+            workspace_dir: old_config.workspace_dir,
+            last_app_version: old_config.last_app_version,
+            show_release_notes: old_config.show_release_notes,
+            language: old_config.language,
+            git_branch_prefix: old_config.git_branch_prefix,
+            showcases: old_config.showcases,
+            pr_auto_description_enabled: old_config.pr_auto_description_enabled,
+            pr_auto_description_prompt: old_config.pr_auto_description_prompt,
+            beta_workspaces: old_config.beta_workspaces,
+            beta_workspaces_invitation_sent: old_config.beta_workspaces_invitation_sent,
+            commit_reminder: old_config.commit_reminder,
+            send_message_shortcut: old_config.send_message_shortcut,
+            notification_preferences,
+            quiet_hours: Vec::new(),
+            escalation: EscalationPolicy::default(),
+            telegram: old_config.telegram.into(),
+            slack: old_config.slack.into(),
+            email: old_config.email.into(),
+            ntfy: old_config.ntfy.into(),
+            gotify: old_config.gotify.into(),
+            web_push: old_config.web_push.into(),
+            apprise: AppriseConfig::default(),
+            twilio: TwilioConfig::default(),
+            whatsapp: WhatsAppConfig::default(),
+            artifact_quota_mb: old_config.artifact_quota_mb,
+            vault_export: old_config.vault_export,
+            post_merge_verification: old_config.post_merge_verification,
+            analytics_export: old_config.analytics_export,
+            maintenance_mode: old_config.maintenance_mode,
+        }
+    }
+
+    pub fn from_previous_version(raw_config: &str) -> Result<Self, Error> {
+        let old_config = v9::Config::from(raw_config.to_string());
+        Ok(Self::from_v9_config(old_config))
+    }
+}
+
+impl From<String> for Config {
+    fn from(raw_config: String) -> Self {
+        if let Ok(config) = serde_json::from_str::<Config>(&raw_config)
+            && config.config_version == "v10"
+        {
+            return config;
+        }
+
+        match Self::from_previous_version(&raw_config) {
+            Ok(config) => {
+                tracing::info!("Config upgraded to v10");
+                config
+            }
+            Err(e) => {
+                tracing::warn!("Config migration failed: {}, using default", e);
+                Self::default()
+            }
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            config_version: "v10".to_string(),
+            theme: ThemeMode::System,
+            executor_profile: ExecutorProfileId::new(BaseCodingAgent::ClaudeCode),
+            disclaimer_acknowledged: false,
+            onboarding_acknowledged: false,
+            notifications: NotificationConfig::default(),
+            editor: EditorConfig::default(),
+            github: GitHubConfig::default(),
+            analytics_enabled: true,
+            workspace_dir: None,
+            last_app_version: None,
+            show_release_notes: false,
+            language: UiLanguage::default(),
+            git_branch_prefix: default_git_branch_prefix(),
+            showcases: ShowcaseState::default(),
+            pr_auto_description_enabled: true,
+            pr_auto_description_prompt: None,
+            beta_workspaces: false,
+            beta_workspaces_invitation_sent: false,
+            commit_reminder: false,
+            send_message_shortcut: SendMessageShortcut::default(),
+            notification_preferences: NotificationPreferences::default(),
+            quiet_hours: Vec::new(),
+            escalation: EscalationPolicy::default(),
+            telegram: TelegramConfig::default(),
+            slack: SlackConfig::default(),
+            email: EmailConfig::default(),
+            ntfy: NtfyConfig::default(),
+            gotify: GotifyConfig::default(),
+            web_push: WebPushConfig::default(),
+            apprise: AppriseConfig::default(),
+            twilio: TwilioConfig::default(),
+            whatsapp: WhatsAppConfig::default(),
+            artifact_quota_mb: default_artifact_quota_mb(),
+            vault_export: VaultExportConfig::default(),
+            post_merge_verification: PostMergeVerificationConfig::default(),
+            analytics_export: AnalyticsExportConfig::default(),
+            maintenance_mode: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use db::models::task::TaskStatus;
+
+    use super::*;
+
+    #[test]
+    fn test_quiet_hours_window() {
+        let overnight = QuietHours {
+            utc_offset_minutes: Some(120),
+            ..QuietHours::default()
+        };
+        let at = |hour, minute| Utc.with_ymd_and_hms(2026, 3, 5, hour, minute, 0).unwrap();
+        // 22:00 to 07:00 at UTC+2 is 20:00 to 05:00 UTC
+        assert!(overnight.is_quiet_at(at(20, 0)));
+        assert!(overnight.is_quiet_at(at(4, 59)));
+        assert!(!overnight.is_quiet_at(at(5, 0)));
+        assert!(!overnight.is_quiet_at(at(19, 59)));
+
+        let lunch = QuietHours {
+            start_hour: 12,
+            end_hour: 13,
+            utc_offset_minutes: Some(0),
+            channels: vec![NotificationChannel::Slack],
+            ..QuietHours::default()
+        };
+        assert!(lunch.is_quiet_at(at(12, 30)));
+        assert!(!lunch.is_quiet_at(at(13, 0)));
+        assert!(lunch.applies_to(NotificationChannel::Slack));
+        assert!(!lunch.applies_to(NotificationChannel::Email));
+
+        let empty = QuietHours {
+            start_hour: 9,
+            end_hour: 9,
+            ..overnight
+        };
+        assert!(!empty.is_quiet_at(at(9, 0)));
+    }
+
+    #[test]
+    fn test_event_type_names_match_serde() {
+        for event in NotificationEventType::ALL {
+            assert_eq!(
+                serde_json::to_value(event).unwrap(),
+                serde_json::json!(event.as_str())
+            );
+            assert_eq!(NotificationEventType::parse(event.as_str()), Some(event));
+        }
+        assert_eq!(NotificationEventType::parse("done"), None);
+    }
+
+    #[test]
+    fn test_preferences_set_and_allows() {
+        let mut preferences = NotificationPreferences::default();
+        assert!(preferences.allows(NotificationEventType::TaskDone, NotificationChannel::Ntfy));
+        assert!(!preferences.allows(
+            NotificationEventType::AttemptStarted,
+            NotificationChannel::Ntfy
+        ));
+
+        preferences.set(
+            NotificationEventType::AttemptStarted,
+            NotificationChannel::Ntfy,
+            true,
+        );
+        preferences.set(
+            NotificationEventType::AttemptStarted,
+            NotificationChannel::Ntfy,
+            true,
+        );
+        assert_eq!(
+            preferences.channels(NotificationEventType::AttemptStarted),
+            &[NotificationChannel::Ntfy]
+        );
+
+        preferences.set(
+            NotificationEventType::TaskDone,
+            NotificationChannel::Ntfy,
+            false,
+        );
+        assert!(!preferences.allows(NotificationEventType::TaskDone, NotificationChannel::Ntfy));
+        assert!(preferences.allows(NotificationEventType::PrMerged, NotificationChannel::Ntfy));
+    }
+
+    #[test]
+    fn test_v9_toggles_migrate_to_preferences() {
+        let mut old = v9::Config::default();
+        old.telegram.notifications_enabled = true;
+        old.telegram.notify_on_started = true;
+        old.telegram.notify_on_task_done = true;
+        old.slack.notify_on_task_done = false;
+        old.email.notify_on_failed = false;
+        old.ntfy.notify_kinds = vec![TaskNotificationKind::Failed];
+
+        let config = Config::from(serde_json::to_string(&old).unwrap());
+        let preferences = &config.notification_preferences;
+
+        assert_eq!(config.config_version, "v10");
+        assert!(config.telegram.notifications_enabled);
+        assert_eq!(
+            preferences.attempt_started,
+            vec![NotificationChannel::Telegram]
+        );
+        assert!(preferences.allows(
+            NotificationEventType::TaskDone,
+            NotificationChannel::Telegram
+        ));
+        assert!(preferences.allows(
+            NotificationEventType::PrMerged,
+            NotificationChannel::Telegram
+        ));
+        assert!(!preferences.allows(
+            NotificationEventType::TaskFailed,
+            NotificationChannel::Telegram
+        ));
+        assert!(!preferences.allows(NotificationEventType::TaskDone, NotificationChannel::Slack));
+        assert!(preferences.allows(NotificationEventType::TaskDone, NotificationChannel::Email));
+        assert!(!preferences.allows(
+            NotificationEventType::TaskFailed,
+            NotificationChannel::Email
+        ));
+        assert!(!preferences.allows(NotificationEventType::TaskDone, NotificationChannel::Ntfy));
+        assert!(preferences.allows(NotificationEventType::TaskFailed, NotificationChannel::Ntfy));
+        assert_eq!(
+            preferences.task_in_review,
+            vec![NotificationChannel::WebPush]
+        );
+    }
+
+    #[test]
+    fn test_v9_transitions_migrate_to_telegram_preferences() {
+        let mut old = v9::Config::default();
+        old.telegram.notify_on_task_done = true;
+        old.telegram.notify_on_failed = true;
+        old.telegram.notify_transitions = vec![StatusTransitionRule {
+            from: Some(TaskStatus::InProgress),
+            to: TaskStatus::Cancelled,
+        }];
+
+        let config = Config::from(serde_json::to_string(&old).unwrap());
+        let preferences = &config.notification_preferences;
+
+        assert!(preferences.allows(
+            NotificationEventType::TaskCancelled,
+            NotificationChannel::Telegram
+        ));
+        assert!(preferences.allows(
+            NotificationEventType::TaskFailed,
+            NotificationChannel::Telegram
+        ));
+        assert!(!preferences.allows(
+            NotificationEventType::TaskDone,
+            NotificationChannel::Telegram
+        ));
+        assert_eq!(config.telegram.notify_transitions.len(), 1);
+    }
+
+    #[test]
+    fn test_config_v10_keeps_preferences() {
+        let mut config = Config::default();
+        config.notification_preferences.task_done = vec![NotificationChannel::Gotify];
+
+        let loaded = Config::from(serde_json::to_string(&config).unwrap());
+
+        assert_eq!(loaded.config_version, "v10");
+        assert_eq!(
+            loaded.notification_preferences.task_done,
+            vec![NotificationChannel::Gotify]
+        );
+        assert_eq!(loaded.notification_preferences.pr_merged.len(), 10);
+    }
+
+    #[test]
+    fn test_configured_admins() {
+        let config = TelegramConfig {
+            user_id: Some(1),
+            allowed_user_ids: vec![2],
+            ..TelegramConfig::default()
+        };
+
+        assert!(config.is_configured_admin(1));
+        assert!(config.is_configured_admin(2));
+        assert!(!config.is_configured_admin(3));
+        assert!(!TelegramConfig::default().is_configured_admin(1));
+    }
+
+    #[test]
+    fn test_webhook_source_is_verified_by_default() {
+        assert!(TelegramConfig::default().verify_webhook_source);
+        let missing: TelegramConfig = serde_json::from_str(
+            r#"{"chat_id": null, "user_id": null, "username": null, "notifications_enabled": false, "include_llm_summary": false}"#,
+        )
+        .unwrap();
+        assert!(missing.verify_webhook_source);
+        assert!(TelegramConfig::from(v9::TelegramConfig::default()).verify_webhook_source);
+    }
+}
@@ -8,7 +8,7 @@ use utils::{assets::SoundAssets, cache_dir};
 
 // Re-export editor config from the dedicated editor module
 pub use crate::services::config::editor::{EditorConfig, EditorType};
-use crate::services::config::versions::v1;
+use crate::services::{config::versions::v1, telegram::TaskNotificationKind};
 
 // Keep the From conversions here since v1 types are only accessible within versions module
 impl From<v1::EditorConfig> for EditorConfig {
@@ -156,6 +156,43 @@ pub struct NotificationConfig {
     pub sound_enabled: bool,
     pub push_enabled: bool,
     pub sound_file: SoundFile,
+    /// Leave emoji out of push notifications, for screen readers
+    #[serde(default)]
+    pub plain_text: bool,
+    /// Task notifications sent to the Pushover apps, alongside the desktop ones
+    #[serde(default)]
+    pub pushover: PushoverConfig,
+}
+
+fn default_pushover_kinds() -> Vec<TaskNotificationKind> {
+    vec![TaskNotificationKind::Done, TaskNotificationKind::Failed]
+}
+
+/// Credentials and events for Pushover notifications
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct PushoverConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// User or group key the notifications are sent to
+    #[serde(default)]
+    pub user_key: Option<String>,
+    /// API token of the Pushover application sending them
+    #[serde(default)]
+    pub app_token: Option<String>,
+    /// Task events that are sent
+    #[serde(default = "default_pushover_kinds")]
+    pub notify_kinds: Vec<TaskNotificationKind>,
+}
+
+impl Default for PushoverConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            user_key: None,
+            app_token: None,
+            notify_kinds: default_pushover_kinds(),
+        }
+    }
 }
 
 impl From<v1::Config> for NotificationConfig {
@@ -164,6 +201,8 @@ impl From<v1::Config> for NotificationConfig {
             sound_enabled: old.sound_alerts,
             push_enabled: old.push_notifications,
             sound_file: SoundFile::from(old.sound_file), // Now SCREAMING_SNAKE_CASE
+            plain_text: false,
+            pushover: PushoverConfig::default(),
         }
     }
 }
@@ -174,6 +213,8 @@ impl Default for NotificationConfig {
             sound_enabled: true,
             push_enabled: true,
             sound_file: SoundFile::CowMooing,
+            plain_text: false,
+            pushover: PushoverConfig::default(),
         }
     }
 }
@@ -1,13 +1,18 @@
+use std::collections::HashMap;
+
 use anyhow::Error;
+use chrono::{DateTime, Utc};
+use db::models::task::TaskStatus;
 use executors::{executors::BaseCodingAgent, profile::ExecutorProfileId};
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
+use uuid::Uuid;
 pub use v8::{
     EditorConfig, EditorType, GitHubConfig, NotificationConfig, SendMessageShortcut, ShowcaseState,
     SoundFile, ThemeMode, UiLanguage,
 };
 
-use crate::services::config::versions::v8;
+use crate::services::{config::versions::v8, telegram::TaskNotificationKind};
 
 fn default_git_branch_prefix() -> String {
     "vk".to_string()
@@ -17,7 +22,389 @@ fn default_pr_auto_description_enabled() -> bool {
     true
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, TS, Default)]
+fn default_digest_hour() -> u8 {
+    8
+}
+
+/// The address ranges Telegram sends webhook requests from
+fn default_webhook_allowed_cidrs() -> Vec<String> {
+    vec!["149.154.160.0/20".to_string(), "91.108.4.0/22".to_string()]
+}
+
+fn default_artifact_quota_mb() -> u64 {
+    1024
+}
+
+fn default_send_attempts() -> u32 {
+    3
+}
+
+fn default_initial_backoff_ms() -> u64 {
+    500
+}
+
+fn default_max_backoff_ms() -> u64 {
+    30_000
+}
+
+fn default_tts_voice() -> String {
+    "alloy".to_string()
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, TS, PartialEq, Eq)]
+pub enum DigestSchedule {
+    #[default]
+    Off,
+    Daily,
+    Weekdays,
+}
+
+/// How often the markdown vault export runs on its own
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, TS, PartialEq, Eq)]
+pub enum VaultExportSchedule {
+    /// Only when triggered from the API
+    #[default]
+    Manual,
+    Hourly,
+    Daily,
+}
+
+/// Formatting used for outgoing Telegram messages
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, TS, PartialEq, Eq)]
+pub enum TelegramParseMode {
+    #[default]
+    Html,
+    MarkdownV2,
+    PlainText,
+    /// Plain text without emoji, for screen readers
+    Accessible,
+}
+
+/// Text-to-speech engine used for voice summaries. `Command` runs a local shell
+/// command that reads text on stdin and writes OGG/Opus audio to stdout.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, TS, PartialEq, Eq)]
+pub enum TtsBackend {
+    #[default]
+    Command,
+    OpenAi,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct TtsConfig {
+    #[serde(default)]
+    pub backend: TtsBackend,
+    /// Shell command for the `Command` backend
+    #[serde(default)]
+    pub command: Option<String>,
+    /// API key for the `OpenAi` backend
+    #[serde(default)]
+    pub openai_api_key: Option<String>,
+    /// Voice name passed to the `OpenAi` backend
+    #[serde(default = "default_tts_voice")]
+    pub voice: String,
+}
+
+impl Default for TtsConfig {
+    fn default() -> Self {
+        Self {
+            backend: TtsBackend::default(),
+            command: None,
+            openai_api_key: None,
+            voice: default_tts_voice(),
+        }
+    }
+}
+
+/// Export of tasks as markdown notes, e.g. into an Obsidian vault
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+pub struct VaultExportConfig {
+    /// Directory notes are written to; exporting is disabled while unset
+    #[serde(default)]
+    pub directory: Option<String>,
+    #[serde(default)]
+    pub schedule: VaultExportSchedule,
+}
+
+/// File format of the analytics export
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, TS, PartialEq, Eq)]
+pub enum AnalyticsExportFormat {
+    /// One `<table>.parquet` file per table
+    #[default]
+    Parquet,
+    /// A single `vibe-kanban.duckdb` database
+    DuckDb,
+    /// A plain SQLite snapshot, `vibe-kanban.sqlite`
+    Sqlite,
+}
+
+/// Copy of the operational data for reporting queries, so they do not
+/// contend with the live database
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+pub struct AnalyticsExportConfig {
+    /// Directory the export is written to; exporting is disabled while unset
+    #[serde(default)]
+    pub directory: Option<String>,
+    #[serde(default)]
+    pub format: AnalyticsExportFormat,
+    #[serde(default)]
+    pub schedule: VaultExportSchedule,
+    /// DuckDB CLI used for the Parquet and DuckDB formats; `duckdb` from the
+    /// PATH when unset
+    #[serde(default)]
+    pub duckdb_path: Option<String>,
+}
+
+/// Build and test run on the updated base branch after an attempt's PR merges
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+pub struct PostMergeVerificationConfig {
+    /// Shell command run from the repository root, e.g. `cargo build && cargo
+    /// test`; verification is disabled while unset
+    #[serde(default)]
+    pub command: Option<String>,
+}
+
+/// Slack workspace linked through the app's OAuth install flow
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+pub struct SlackConfig {
+    pub team_id: Option<String>,
+    pub team_name: Option<String>,
+    /// Bot token issued by the install
+    #[serde(default)]
+    pub bot_token: Option<String>,
+    /// Channel task notifications are posted to, the one picked during the
+    /// install unless changed later
+    #[serde(default)]
+    pub channel_id: Option<String>,
+    #[serde(default)]
+    pub channel_name: Option<String>,
+    /// Project `/vk tasks` and `/vk newtask` use when none is given
+    #[serde(default)]
+    pub default_project_id: Option<Uuid>,
+    #[serde(default)]
+    pub notifications_enabled: bool,
+    #[serde(default)]
+    pub notify_on_task_done: bool,
+}
+
+/// How the SMTP connection is secured
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, TS, PartialEq, Eq)]
+pub enum SmtpTls {
+    /// Plain connection, e.g. to a relay on localhost
+    None,
+    /// Upgrade a plain connection with STARTTLS, usually on port 587
+    #[default]
+    StartTls,
+    /// TLS from the start, usually on port 465
+    Tls,
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Task notifications sent by email
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct EmailConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub smtp_host: Option<String>,
+    #[serde(default = "default_smtp_port")]
+    pub smtp_port: u16,
+    #[serde(default)]
+    pub tls: SmtpTls,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Sender, e.g. `Vibe Kanban <kanban@example.com>`
+    #[serde(default)]
+    pub from_address: Option<String>,
+    #[serde(default)]
+    pub to_addresses: Vec<String>,
+    #[serde(default = "default_true")]
+    pub notify_on_task_done: bool,
+    #[serde(default = "default_true")]
+    pub notify_on_failed: bool,
+}
+
+impl Default for EmailConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            smtp_host: None,
+            smtp_port: default_smtp_port(),
+            tls: SmtpTls::default(),
+            username: None,
+            password: None,
+            from_address: None,
+            to_addresses: Vec::new(),
+            notify_on_task_done: true,
+            notify_on_failed: true,
+        }
+    }
+}
+
+fn default_ntfy_kinds() -> Vec<TaskNotificationKind> {
+    vec![TaskNotificationKind::Done, TaskNotificationKind::Failed]
+}
+
+/// Task notifications pushed through an ntfy server, e.g. ntfy.sh, whose
+/// mobile apps need no bot of their own
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct NtfyConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Topic to publish to, e.g. `https://ntfy.sh/my-kanban`
+    #[serde(default)]
+    pub topic_url: Option<String>,
+    /// Access token for protected topics, sent as a bearer token
+    #[serde(default)]
+    pub access_token: Option<String>,
+    /// Task events that are pushed
+    #[serde(default = "default_ntfy_kinds")]
+    pub notify_kinds: Vec<TaskNotificationKind>,
+}
+
+impl Default for NtfyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            topic_url: None,
+            access_token: None,
+            notify_kinds: default_ntfy_kinds(),
+        }
+    }
+}
+
+fn default_gotify_kinds() -> Vec<TaskNotificationKind> {
+    vec![TaskNotificationKind::Done, TaskNotificationKind::Failed]
+}
+
+/// Task notifications pushed through a self-hosted Gotify server
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct GotifyConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Base URL of the Gotify server, e.g. `https://gotify.example.com`
+    #[serde(default)]
+    pub server_url: Option<String>,
+    /// Token of the Gotify application the messages are posted as
+    #[serde(default)]
+    pub app_token: Option<String>,
+    /// Task events that are pushed
+    #[serde(default = "default_gotify_kinds")]
+    pub notify_kinds: Vec<TaskNotificationKind>,
+    /// Have the Gotify clients render messages as markdown
+    #[serde(default = "default_true")]
+    pub markdown: bool,
+}
+
+impl Default for GotifyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            server_url: None,
+            app_token: None,
+            notify_kinds: default_gotify_kinds(),
+            markdown: true,
+        }
+    }
+}
+
+fn default_web_push_kinds() -> Vec<TaskNotificationKind> {
+    vec![
+        TaskNotificationKind::InReview,
+        TaskNotificationKind::Done,
+        TaskNotificationKind::Failed,
+    ]
+}
+
+/// Task notifications pushed to the browsers that subscribed in the web UI
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct WebPushConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Contact for push services, a `mailto:` or `https:` URL
+    #[serde(default)]
+    pub subject: Option<String>,
+    /// VAPID signing key, base64url; generated when the web UI first asks for
+    /// the public key
+    #[serde(default)]
+    pub vapid_private_key: Option<String>,
+    /// Task events that are pushed
+    #[serde(default = "default_web_push_kinds")]
+    pub notify_kinds: Vec<TaskNotificationKind>,
+}
+
+impl Default for WebPushConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            subject: None,
+            vapid_private_key: None,
+            notify_kinds: default_web_push_kinds(),
+        }
+    }
+}
+
+/// Retry policy for Bot API sends
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct TelegramRetryConfig {
+    /// Total attempts per message, including the first one
+    #[serde(default = "default_send_attempts")]
+    pub max_attempts: u32,
+    /// Backoff after the first failure, doubled on each further failure
+    #[serde(default = "default_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+    #[serde(default = "default_max_backoff_ms")]
+    pub max_backoff_ms: u64,
+}
+
+impl Default for TelegramRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_send_attempts(),
+            initial_backoff_ms: default_initial_backoff_ms(),
+            max_backoff_ms: default_max_backoff_ms(),
+        }
+    }
+}
+
+/// A task status change that sends a Telegram notification
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+pub struct StatusTransitionRule {
+    /// Previous status, or any status when unset
+    #[serde(default)]
+    pub from: Option<TaskStatus>,
+    pub to: TaskStatus,
+}
+
+impl StatusTransitionRule {
+    pub fn matches(&self, from: Option<&TaskStatus>, to: &TaskStatus) -> bool {
+        &self.to == to
+            && self
+                .from
+                .as_ref()
+                .is_none_or(|rule_from| Some(rule_from) == from)
+    }
+}
+
+/// A bot of its own for one project, e.g. a client-facing bot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+pub struct TelegramProjectBot {
+    pub project_id: Uuid,
+    pub bot_token: String,
+    /// Chat the project's notifications go to, or the linked chat when unset
+    #[serde(default)]
+    pub chat_id: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
 pub struct TelegramConfig {
     pub chat_id: Option<i64>,
     pub user_id: Option<i64>,
@@ -27,6 +414,125 @@ pub struct TelegramConfig {
     pub include_llm_summary: bool,
     #[serde(default)]
     pub stream_enabled: bool,
+    #[serde(default)]
+    pub notify_on_started: bool,
+    #[serde(default)]
+    pub notify_on_in_review: bool,
+    #[serde(default)]
+    pub notify_on_failed: bool,
+    #[serde(default)]
+    pub digest_schedule: DigestSchedule,
+    #[serde(default = "default_digest_hour")]
+    pub digest_hour: u8,
+    #[serde(default)]
+    pub parse_mode: TelegramParseMode,
+    /// Send the LLM summary of finished attempts as a voice message
+    #[serde(default)]
+    pub voice_summary: bool,
+    #[serde(default)]
+    pub tts: TtsConfig,
+    #[serde(default)]
+    pub retry: TelegramRetryConfig,
+    /// When non-empty, only these status transitions send notifications,
+    /// replacing the per-status toggles. Attempt failures still follow
+    /// `notify_on_failed`.
+    #[serde(default)]
+    pub notify_transitions: Vec<StatusTransitionRule>,
+    /// Deliver every notification without sound
+    #[serde(default)]
+    pub silent: bool,
+    /// Notification kinds delivered without sound even when `silent` is off
+    #[serde(default)]
+    pub silent_kinds: Vec<TaskNotificationKind>,
+    /// Task notifications are paused until this time (set with /mute)
+    #[serde(default)]
+    pub muted_until: Option<DateTime<Utc>>,
+    /// Keep one message per task and edit it on each status change instead of
+    /// sending a new one. Edits arrive without sound.
+    #[serde(default)]
+    pub edit_in_place: bool,
+    /// Forward questions an agent ends its turn with to the chat, so the
+    /// reply can be sent back as a follow-up
+    #[serde(default)]
+    pub forward_questions: bool,
+    /// Telegram user IDs allowed to use the bot besides the linked accounts.
+    /// Everyone else can only run /start and /help.
+    #[serde(default)]
+    pub allowed_user_ids: Vec<i64>,
+    /// Bot command aliases, without the leading slash. A value may carry
+    /// leading arguments, e.g. `"bug": "newtask #bug"`.
+    #[serde(default)]
+    pub command_aliases: HashMap<String, String>,
+    /// Projects that send their notifications through a bot of their own.
+    /// Each one receives updates at `/api/telegram/webhook/{project_id}`.
+    #[serde(default)]
+    pub project_bots: Vec<TelegramProjectBot>,
+    /// Task notifications a chat gets within this many seconds of each
+    /// other are combined into one message. 0 sends each right away.
+    #[serde(default)]
+    pub batch_window_secs: u64,
+    /// Public URL this server is reachable at, e.g. `https://kanban.example.com`.
+    /// When set, the bots' webhooks are registered with Telegram on startup and
+    /// removed on shutdown.
+    #[serde(default)]
+    pub webhook_base_url: Option<String>,
+    /// Only accept webhook requests from `webhook_allowed_cidrs`. Behind a
+    /// reverse proxy on the same host, the address the proxy appended to
+    /// `X-Forwarded-For` is checked instead of the proxy's own.
+    #[serde(default)]
+    pub verify_webhook_source: bool,
+    /// Address ranges webhook requests may come from, Telegram's published
+    /// ranges by default
+    #[serde(default = "default_webhook_allowed_cidrs")]
+    pub webhook_allowed_cidrs: Vec<String>,
+}
+
+impl TelegramConfig {
+    pub fn is_muted(&self, now: DateTime<Utc>) -> bool {
+        self.muted_until.is_some_and(|until| until > now)
+    }
+
+    pub fn project_bot(&self, project_id: Uuid) -> Option<&TelegramProjectBot> {
+        self.project_bots
+            .iter()
+            .find(|bot| bot.project_id == project_id)
+    }
+}
+
+impl Default for TelegramConfig {
+    fn default() -> Self {
+        Self {
+            chat_id: None,
+            user_id: None,
+            username: None,
+            notifications_enabled: false,
+            notify_on_task_done: false,
+            include_llm_summary: false,
+            stream_enabled: false,
+            notify_on_started: false,
+            notify_on_in_review: false,
+            notify_on_failed: false,
+            digest_schedule: DigestSchedule::Off,
+            digest_hour: default_digest_hour(),
+            parse_mode: TelegramParseMode::Html,
+            voice_summary: false,
+            tts: TtsConfig::default(),
+            retry: TelegramRetryConfig::default(),
+            notify_transitions: Vec::new(),
+            silent: false,
+            silent_kinds: Vec::new(),
+            muted_until: None,
+            edit_in_place: false,
+            forward_questions: false,
+            allowed_user_ids: Vec::new(),
+            command_aliases: HashMap::new(),
+            project_bots: Vec::new(),
+            batch_window_secs: 0,
+            webhook_base_url: None,
+            verify_webhook_source: false,
+            webhook_allowed_cidrs: default_webhook_allowed_cidrs(),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, TS)]
@@ -63,6 +569,29 @@ pub struct Config {
     pub send_message_shortcut: SendMessageShortcut,
     #[serde(default)]
     pub telegram: TelegramConfig,
+    #[serde(default)]
+    pub slack: SlackConfig,
+    #[serde(default)]
+    pub email: EmailConfig,
+    #[serde(default)]
+    pub ntfy: NtfyConfig,
+    #[serde(default)]
+    pub gotify: GotifyConfig,
+    #[serde(default)]
+    pub web_push: WebPushConfig,
+    /// Maximum total size of attempt artifacts stored per project
+    #[serde(default = "default_artifact_quota_mb")]
+    pub artifact_quota_mb: u64,
+    #[serde(default)]
+    pub vault_export: VaultExportConfig,
+    #[serde(default)]
+    pub post_merge_verification: PostMergeVerificationConfig,
+    #[serde(default)]
+    pub analytics_export: AnalyticsExportConfig,
+    /// Reject every change with 503 while reads keep working, e.g. during
+    /// backups and migrations
+    #[serde(default)]
+    pub maintenance_mode: bool,
 }
 
 impl Config {
@@ -90,6 +619,16 @@ impl Config {
             commit_reminder: old_config.commit_reminder,
             send_message_shortcut: old_config.send_message_shortcut,
             telegram: TelegramConfig::default(),
+            slack: SlackConfig::default(),
+            email: EmailConfig::default(),
+            ntfy: NtfyConfig::default(),
+            gotify: GotifyConfig::default(),
+            web_push: WebPushConfig::default(),
+            artifact_quota_mb: default_artifact_quota_mb(),
+            vault_export: VaultExportConfig::default(),
+            post_merge_verification: PostMergeVerificationConfig::default(),
+            analytics_export: AnalyticsExportConfig::default(),
+            maintenance_mode: false,
         }
     }
 
@@ -145,6 +684,16 @@ impl Default for Config {
             commit_reminder: false,
             send_message_shortcut: SendMessageShortcut::default(),
             telegram: TelegramConfig::default(),
+            slack: SlackConfig::default(),
+            email: EmailConfig::default(),
+            ntfy: NtfyConfig::default(),
+            gotify: GotifyConfig::default(),
+            web_push: WebPushConfig::default(),
+            artifact_quota_mb: default_artifact_quota_mb(),
+            vault_export: VaultExportConfig::default(),
+            post_merge_verification: PostMergeVerificationConfig::default(),
+            analytics_export: AnalyticsExportConfig::default(),
+            maintenance_mode: false,
         }
     }
 }
@@ -179,6 +728,10 @@ mod tests {
             notify_on_task_done: true,
             include_llm_summary: false,
             stream_enabled: true,
+            notify_on_started: false,
+            notify_on_in_review: true,
+            notify_on_failed: true,
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&config).unwrap();
@@ -191,6 +744,90 @@ mod tests {
         assert!(deserialized.notify_on_task_done);
         assert!(!deserialized.include_llm_summary);
         assert!(deserialized.stream_enabled);
+        assert!(!deserialized.notify_on_started);
+        assert!(deserialized.notify_on_in_review);
+        assert!(deserialized.notify_on_failed);
+    }
+
+    #[test]
+    fn test_telegram_config_missing_transition_toggles_default_off() {
+        // Configs saved before the per-transition toggles existed must still load
+        let json = r#"{
+            "chat_id": 12345,
+            "user_id": 67890,
+            "username": null,
+            "notifications_enabled": true,
+            "notify_on_task_done": true,
+            "include_llm_summary": false
+        }"#;
+
+        let config: TelegramConfig = serde_json::from_str(json).unwrap();
+
+        assert!(config.notify_on_task_done);
+        assert!(!config.notify_on_started);
+        assert!(!config.notify_on_in_review);
+        assert!(!config.notify_on_failed);
+    }
+
+    #[test]
+    fn test_telegram_config_project_bot_lookup() {
+        let project_id = Uuid::new_v4();
+        let json = format!(
+            r#"{{
+                "chat_id": 12345,
+                "user_id": null,
+                "username": null,
+                "notifications_enabled": true,
+                "notify_on_task_done": true,
+                "include_llm_summary": false,
+                "project_bots": [{{ "project_id": "{project_id}", "bot_token": "123:abc" }}]
+            }}"#
+        );
+
+        let config: TelegramConfig = serde_json::from_str(&json).unwrap();
+
+        let bot = config.project_bot(project_id).unwrap();
+        assert_eq!(bot.bot_token, "123:abc");
+        assert_eq!(bot.chat_id, None);
+        assert!(config.project_bot(Uuid::new_v4()).is_none());
+    }
+
+    #[test]
+    fn test_status_transition_rule_matches() {
+        let review_to_done = StatusTransitionRule {
+            from: Some(TaskStatus::InReview),
+            to: TaskStatus::Done,
+        };
+        let any_to_cancelled = StatusTransitionRule {
+            from: None,
+            to: TaskStatus::Cancelled,
+        };
+
+        assert!(review_to_done.matches(Some(&TaskStatus::InReview), &TaskStatus::Done));
+        assert!(!review_to_done.matches(Some(&TaskStatus::InProgress), &TaskStatus::Done));
+        assert!(!review_to_done.matches(None, &TaskStatus::Done));
+        assert!(any_to_cancelled.matches(Some(&TaskStatus::Todo), &TaskStatus::Cancelled));
+        assert!(any_to_cancelled.matches(None, &TaskStatus::Cancelled));
+        assert!(!any_to_cancelled.matches(Some(&TaskStatus::Todo), &TaskStatus::Done));
+    }
+
+    #[test]
+    fn test_telegram_config_digest_defaults() {
+        let json = r#"{
+            "chat_id": 12345,
+            "user_id": null,
+            "username": null,
+            "notifications_enabled": true,
+            "notify_on_task_done": true,
+            "include_llm_summary": false
+        }"#;
+
+        let config: TelegramConfig = serde_json::from_str(json).unwrap();
+
+        assert_eq!(config.digest_schedule, DigestSchedule::Off);
+        assert_eq!(config.digest_hour, 8);
+        assert_eq!(TelegramConfig::default().digest_hour, 8);
+        assert_eq!(config.parse_mode, TelegramParseMode::Html);
     }
 
     // ========================================================================
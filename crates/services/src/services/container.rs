@@ -53,8 +53,18 @@ use utils::{
 use uuid::Uuid;
 
 use crate::services::{
-    notification::NotificationService, telegram::TelegramService,
-    workspace_manager::WorkspaceError as WorkspaceManagerError, worktree_manager::WorktreeError,
+    config::NotificationEventType,
+    notification::NotificationService,
+    notification_dispatcher::NotificationDispatcher,
+    outbox::OutboxIntent,
+    preview_capture::PreviewCaptureService,
+    rate_limit::{ProviderCooldown, ProviderCooldowns},
+    slack::SlackService,
+    telegram::{TaskNotificationKind, TelegramService, failure_excerpt},
+    web_push::WebPushNotifier,
+    whatsapp::WhatsAppService,
+    workspace_manager::WorkspaceError as WorkspaceManagerError,
+    worktree_manager::WorktreeError,
 };
 pub type ContainerRef = String;
 
@@ -80,6 +90,8 @@ pub enum ContainerError {
     Io(#[from] std::io::Error),
     #[error("Failed to kill process: {0}")]
     KillFailed(std::io::Error),
+    #[error("{} is rate limited until {}", .0.provider, .0.until)]
+    ProviderCoolingDown(ProviderCooldown),
     #[error(transparent)]
     Other(#[from] AnyhowError), // Catches any unclassified errors
 }
@@ -94,8 +106,20 @@ pub trait ContainerService {
 
     fn notification_service(&self) -> &NotificationService;
 
+    fn notification_dispatcher(&self) -> &NotificationDispatcher;
+
+    fn web_push_notifier(&self) -> &WebPushNotifier;
+
+    fn whatsapp_service(&self) -> &WhatsAppService;
+
     fn telegram_service(&self) -> Option<&TelegramService>;
 
+    fn slack_service(&self) -> Option<&SlackService>;
+
+    fn preview_capture(&self) -> &PreviewCaptureService;
+
+    fn provider_cooldowns(&self) -> &ProviderCooldowns;
+
     fn workspace_to_current_dir(&self, workspace: &Workspace) -> PathBuf;
 
     async fn available_agent_slash_commands(
@@ -223,54 +247,112 @@ pub trait ContainerService {
 
     /// Finalize task execution by updating status to InReview and sending notifications
     async fn finalize_task(&self, ctx: &ExecutionContext) {
-        if let Err(e) =
-            Task::update_status(&self.db().pool, ctx.task.id, TaskStatus::InReview).await
-        {
-            tracing::error!("Failed to update task status to InReview: {e}");
-        }
-
-        // Skip notification if process was intentionally killed by user
-        if matches!(ctx.execution_process.status, ExecutionProcessStatus::Killed) {
-            return;
-        }
-
-        let title = format!("Task Complete: {}", ctx.task.title);
-        let message = match ctx.execution_process.status {
-            ExecutionProcessStatus::Completed => format!(
-                "✅ '{}' completed successfully\nBranch: {:?}\nExecutor: {:?}",
-                ctx.task.title, ctx.workspace.branch, ctx.session.executor
-            ),
-            ExecutionProcessStatus::Failed => format!(
-                "❌ '{}' execution failed\nBranch: {:?}\nExecutor: {:?}",
-                ctx.task.title, ctx.workspace.branch, ctx.session.executor
-            ),
+        let kind = match ctx.execution_process.status {
+            ExecutionProcessStatus::Completed => Some(TaskNotificationKind::Done),
+            ExecutionProcessStatus::Failed => Some(TaskNotificationKind::Failed),
+            // Skip notification if process was intentionally killed by user
+            ExecutionProcessStatus::Killed => None,
             _ => {
                 tracing::warn!(
                     "Tried to notify workspace completion for {} but process is still running!",
                     ctx.workspace.id
                 );
-                return;
+                None
             }
         };
-        self.notification_service().notify(&title, &message).await;
+        let intent = match kind {
+            Some(kind) => Some(self.attempt_finished_intent(ctx, kind).await),
+            None => None,
+        };
 
-        // Send Telegram notification if configured
-        if let Some(telegram_service) = self.telegram_service() {
-            let summary = CodingAgentTurn::find_by_execution_process_id(
-                &self.db().pool,
-                ctx.execution_process.id,
-            )
-            .await
-            .ok()
-            .flatten()
-            .and_then(|t| t.summary);
+        // The notification is queued with the status change, so one is never
+        // sent or lost without the other
+        let pool = &self.db().pool;
+        let result: Result<(), SqlxError> = async {
+            let mut tx = pool.begin().await?;
+            Task::update_status(&mut *tx, ctx.task.id, TaskStatus::InReview).await?;
+            if let Some(intent) = &intent {
+                intent.enqueue(&mut *tx).await?;
+            }
+            tx.commit().await
+        }
+        .await;
+        if let Err(e) = result {
+            tracing::error!("Failed to update task status to InReview: {e}");
+            return;
+        }
 
-            if let Err(e) = telegram_service
-                .send_task_notification(&ctx.task, summary.as_deref())
-                .await
+        // Preview capture boots the dev server, so it runs off the exit
+        // monitor and the screenshot follows the notification
+        if kind != Some(TaskNotificationKind::Done) {
+            return;
+        }
+        let preview = self.preview_capture().clone();
+        let telegram = self.telegram_service().cloned();
+        let (task, workspace) = (ctx.task.clone(), ctx.workspace.clone());
+        tokio::spawn(async move {
+            let artifact = match preview.capture_for_workspace(&task, &workspace).await {
+                Ok(Some(artifact)) => artifact,
+                Ok(None) => return,
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to capture preview for workspace {}: {e}",
+                        workspace.id
+                    );
+                    return;
+                }
+            };
+            if let Some(telegram) = telegram
+                && let Err(e) = telegram
+                    .send_preview_screenshot(&task, &preview.artifact_path(&artifact))
+                    .await
             {
-                tracing::warn!("Failed to send Telegram notification: {e}");
+                tracing::warn!("Failed to send preview for task {}: {e}", task.id);
             }
+        });
+    }
+
+    /// The notification for a coding agent run that ended as `kind`, with
+    /// the agent's summary and, for failures, the tail of its output
+    async fn attempt_finished_intent(
+        &self,
+        ctx: &ExecutionContext,
+        kind: TaskNotificationKind,
+    ) -> OutboxIntent {
+        let summary = if self
+            .notification_dispatcher()
+            .wants(NotificationEventType::from_kind(kind))
+            .await
+        {
+            CodingAgentTurn::find_by_execution_process_id(&self.db().pool, ctx.execution_process.id)
+                .await
+                .ok()
+                .flatten()
+                .and_then(|t| t.summary)
+        } else {
+            None
+        };
+        // The run's output is still in memory until the exit monitor drops it
+        let error_excerpt = match kind {
+            TaskNotificationKind::Failed => self
+                .msg_stores()
+                .read()
+                .await
+                .get(&ctx.execution_process.id)
+                .and_then(|store| failure_excerpt(&store.get_history())),
+            _ => None,
+        };
+        OutboxIntent::AttemptFinishedNotification {
+            task_id: ctx.task.id,
+            workspace_id: ctx.workspace.id,
+            kind,
+            executor: ctx
+                .session
+                .executor
+                .clone()
+                .unwrap_or_else(|| "unknown".to_string()),
+            summary,
+            error_excerpt,
         }
     }
 
@@ -941,6 +1023,14 @@ pub trait ContainerService {
         workspace: &Workspace,
         executor_profile_id: ExecutorProfileId,
     ) -> Result<ExecutionProcess, ContainerError> {
+        // Hold new attempts back while the agent's provider is rate limited
+        if let Some(cooldown) = self
+            .provider_cooldowns()
+            .active(executor_profile_id.executor)
+        {
+            return Err(ContainerError::ProviderCoolingDown(cooldown));
+        }
+
         // Create container
         self.create(workspace).await?;
 
@@ -1043,7 +1133,15 @@ pub trait ContainerService {
         if task.status != TaskStatus::InProgress
             && run_reason != &ExecutionProcessRunReason::DevServer
         {
-            Task::update_status(&self.db().pool, task.id, TaskStatus::InProgress).await?;
+            let mut tx = self.db().pool.begin().await?;
+            Task::update_status(&mut *tx, task.id, TaskStatus::InProgress).await?;
+            OutboxIntent::TaskStatusNotification {
+                task_id: task.id,
+                kind: TaskNotificationKind::Started,
+            }
+            .enqueue(&mut *tx)
+            .await?;
+            tx.commit().await?;
         }
         // Create new execution process record
         // Capture current HEAD per repository as the "before" commit for this execution
@@ -1193,6 +1291,9 @@ pub trait ContainerService {
                 _ => None,
             }
         {
+            self.provider_cooldowns()
+                .watch_execution(executor_profile_id.executor, msg_store.clone());
+
             #[cfg(feature = "qa-mode")]
             {
                 let executor = QaMockExecutor;
@@ -1217,13 +1318,20 @@ pub trait ContainerService {
         self.store_db_stream_handle(execution_process.id, db_stream_handle)
             .await;
 
-        // Spawn Telegram streaming if enabled
+        // Spawn Telegram streaming and question forwarding if enabled
         if let Some(telegram) = self.telegram_service() {
             telegram.spawn_stream_to_telegram(
                 execution_process.id,
                 task.title.clone(),
                 self.msg_stores().clone(),
             );
+            if matches!(run_reason, ExecutionProcessRunReason::CodingAgent) {
+                telegram.spawn_question_forwarder(
+                    execution_process.id,
+                    &task,
+                    self.msg_stores().clone(),
+                );
+            }
         }
 
         Ok(execution_process)
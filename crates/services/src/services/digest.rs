@@ -0,0 +1,257 @@
+//! Scheduled Telegram digest.
+//!
+//! Sends a single morning summary of completed, in-progress and stuck tasks,
+//! and of failures nobody has acknowledged, according to
+//! `TelegramConfig::digest_schedule` and `digest_hour`.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, Local, NaiveDate, Timelike, Utc, Weekday};
+use db::{
+    DBService,
+    models::task::{Task, TaskStatus},
+};
+use sqlx::SqlitePool;
+use tokio::time::interval;
+use tracing::{debug, error, info};
+
+use crate::services::{
+    config::{DigestSchedule, TelegramConfig},
+    i18n::{Locale, ServerMessage},
+    telegram::{TelegramError, TelegramService, escape_html},
+};
+
+/// Active tasks untouched for longer than this are listed as stuck
+const STUCK_AFTER: chrono::Duration = chrono::Duration::hours(24);
+/// Maximum number of tasks listed per section
+const MAX_TASKS_PER_SECTION: usize = 10;
+
+/// Tasks grouped for a digest message
+#[derive(Debug, Default)]
+pub struct Digest {
+    pub completed: Vec<Task>,
+    pub in_progress: Vec<Task>,
+    pub stuck: Vec<Task>,
+    /// Tasks whose failure notifications nobody has acknowledged
+    pub unacknowledged: Vec<Task>,
+}
+
+impl Digest {
+    /// Group tasks into digest sections relative to `now`
+    pub fn from_tasks(tasks: Vec<Task>, now: DateTime<Utc>) -> Self {
+        let mut digest = Self::default();
+        for task in tasks {
+            match task.status {
+                TaskStatus::Done => digest.completed.push(task),
+                TaskStatus::InProgress | TaskStatus::InReview
+                    if now - task.updated_at > STUCK_AFTER =>
+                {
+                    digest.stuck.push(task)
+                }
+                TaskStatus::InProgress | TaskStatus::InReview => digest.in_progress.push(task),
+                TaskStatus::Todo | TaskStatus::Cancelled => {}
+            }
+        }
+        digest
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.completed.is_empty()
+            && self.in_progress.is_empty()
+            && self.stuck.is_empty()
+            && self.unacknowledged.is_empty()
+    }
+
+    /// Render the digest as a Telegram HTML message
+    pub fn format(&self, locale: Locale) -> String {
+        let mut message = ServerMessage::DigestTitle.render(locale);
+        if self.is_empty() {
+            message.push_str("\n\n");
+            message.push_str(&ServerMessage::DigestEmpty.render(locale));
+            return message;
+        }
+        let sections = [
+            (ServerMessage::DigestCompleted, &self.completed),
+            (ServerMessage::DigestInProgress, &self.in_progress),
+            (ServerMessage::DigestStuck, &self.stuck),
+            (ServerMessage::DigestUnacknowledged, &self.unacknowledged),
+        ];
+        for (heading, tasks) in sections {
+            push_section(&mut message, &heading.render(locale), tasks, locale);
+        }
+        message
+    }
+}
+
+fn push_section(message: &mut String, heading: &str, tasks: &[Task], locale: Locale) {
+    if tasks.is_empty() {
+        return;
+    }
+    message.push_str(&format!("\n\n<b>{} ({})</b>", heading, tasks.len()));
+    for task in tasks.iter().take(MAX_TASKS_PER_SECTION) {
+        message.push_str(&format!("\n• {}", escape_html(&task.title)));
+    }
+    if tasks.len() > MAX_TASKS_PER_SECTION {
+        message.push('\n');
+        message.push_str(
+            &ServerMessage::DigestMore(tasks.len() - MAX_TASKS_PER_SECTION).render(locale),
+        );
+    }
+}
+
+/// Digest of the last 24 hours, as sent on schedule and by /report
+pub async fn build_digest(pool: &SqlitePool, now: DateTime<Utc>) -> Result<Digest, sqlx::Error> {
+    let since = now - chrono::Duration::hours(24);
+    let tasks = Task::find_for_digest(pool, since).await?;
+    let mut digest = Digest::from_tasks(tasks, now);
+    digest.unacknowledged = Task::find_with_unacknowledged_failures(pool, since).await?;
+    Ok(digest)
+}
+
+/// Whether a digest should be sent at local time `now`, given the date it was last sent
+fn is_due(config: &TelegramConfig, now: DateTime<Local>, last_sent: Option<NaiveDate>) -> bool {
+    let scheduled_today = match config.digest_schedule {
+        DigestSchedule::Off => false,
+        DigestSchedule::Daily => true,
+        DigestSchedule::Weekdays => !matches!(now.weekday(), Weekday::Sat | Weekday::Sun),
+    };
+    scheduled_today
+        && now.hour() == u32::from(config.digest_hour)
+        && last_sent != Some(now.date_naive())
+}
+
+/// Service that sends the scheduled Telegram digest
+pub struct DigestService {
+    db: DBService,
+    telegram: TelegramService,
+    poll_interval: Duration,
+    last_sent: Option<NaiveDate>,
+}
+
+impl DigestService {
+    pub async fn spawn(db: DBService, telegram: TelegramService) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            db,
+            telegram,
+            poll_interval: Duration::from_secs(60),
+            last_sent: None,
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(mut self) {
+        info!(
+            "Starting Telegram digest service with interval {:?}",
+            self.poll_interval
+        );
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+
+            let config = self.telegram.get_link_status().await;
+            let now = Local::now();
+            if !config.notifications_enabled || !is_due(&config, now, self.last_sent) {
+                continue;
+            }
+
+            match self.send_digest(&config).await {
+                Ok(()) => {
+                    debug!("Sent Telegram digest");
+                    self.last_sent = Some(now.date_naive());
+                }
+                Err(e) => error!("Failed to send Telegram digest: {}", e),
+            }
+        }
+    }
+
+    async fn send_digest(&self, config: &TelegramConfig) -> Result<(), TelegramError> {
+        let chat_id = config.chat_id.ok_or(TelegramError::NotLinked)?;
+        let digest = build_digest(&self.db.pool, Utc::now()).await?;
+        let locale = self.telegram.notification_locale().await;
+        self.telegram
+            .send_message(chat_id, &digest.format(locale))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::services::test_fixtures;
+
+    fn task(title: &str, status: TaskStatus, updated_at: DateTime<Utc>) -> Task {
+        Task {
+            status,
+            created_at: updated_at,
+            updated_at,
+            ..test_fixtures::task(title)
+        }
+    }
+
+    #[test]
+    fn test_digest_groups_tasks() {
+        let now = Utc::now();
+        let tasks = vec![
+            task("shipped", TaskStatus::Done, now),
+            task("working", TaskStatus::InProgress, now - chrono::Duration::hours(2)),
+            task("waiting", TaskStatus::InReview, now - chrono::Duration::hours(30)),
+            task("backlog", TaskStatus::Todo, now),
+        ];
+
+        let digest = Digest::from_tasks(tasks, now);
+
+        assert_eq!(digest.completed.len(), 1);
+        assert_eq!(digest.in_progress.len(), 1);
+        assert_eq!(digest.stuck.len(), 1);
+        assert_eq!(digest.stuck[0].title, "waiting");
+    }
+
+    #[test]
+    fn test_digest_format_escapes_titles() {
+        let digest = Digest::from_tasks(vec![task("<b>", TaskStatus::Done, Utc::now())], Utc::now());
+
+        let message = digest.format(Locale::En);
+
+        assert!(message.contains("Completed (1)"));
+        assert!(message.contains("&lt;b&gt;"));
+        assert!(digest.format(Locale::Fr).contains("Terminées (1)"));
+    }
+
+    #[test]
+    fn test_digest_lists_unacknowledged_failures() {
+        let mut digest = Digest::default();
+        assert!(digest.is_empty());
+
+        digest.unacknowledged = vec![task("flaky", TaskStatus::InReview, Utc::now())];
+        assert!(!digest.is_empty());
+        let message = digest.format(Locale::En);
+        assert!(message.contains("Unacknowledged failures (1)"));
+        assert!(message.contains("• flaky"));
+    }
+
+    #[test]
+    fn test_is_due_respects_schedule_hour_and_last_sent() {
+        let config = TelegramConfig {
+            digest_schedule: DigestSchedule::Weekdays,
+            digest_hour: 8,
+            ..Default::default()
+        };
+        // 2025-01-06 is a Monday, 2025-01-04 a Saturday
+        let monday = Local.with_ymd_and_hms(2025, 1, 6, 8, 15, 0).unwrap();
+        let saturday = Local.with_ymd_and_hms(2025, 1, 4, 8, 15, 0).unwrap();
+
+        assert!(is_due(&config, monday, None));
+        assert!(!is_due(&config, monday, Some(monday.date_naive())));
+        assert!(!is_due(&config, saturday, None));
+        assert!(!is_due(&config, monday.with_hour(9).unwrap(), None));
+
+        let off = TelegramConfig::default();
+        assert!(!is_due(&off, monday, None));
+    }
+}
@@ -0,0 +1,446 @@
+//! Task notifications sent by email over SMTP.
+//!
+//! The SMTP server, credentials and recipients are kept in `Config::email`.
+//! Each message has a plain-text and an HTML body with the task's outcome,
+//! the agent's summary and the diff stats of the attempt.
+
+use std::{path::PathBuf, sync::Arc};
+
+use async_trait::async_trait;
+use db::models::{
+    notification_rule::NotificationChannel, project::Project, task::Task, workspace::Workspace,
+    workspace_repo::WorkspaceRepo,
+};
+use git::{DiffTarget, GitService};
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+    message::{Mailbox, MultiPart},
+    transport::smtp::authentication::Credentials,
+};
+use sqlx::SqlitePool;
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+use crate::services::{
+    config::{Config, EmailConfig, SmtpTls},
+    notification_dispatcher::{NotificationEvent, Notifier, NotifierError},
+    telegram::{TaskNotificationKind, escape_html},
+};
+
+#[derive(Debug, Error)]
+pub enum EmailError {
+    #[error("Email notifications are not configured: {0}")]
+    NotConfigured(&'static str),
+
+    #[error("Invalid email address '{0}'")]
+    InvalidAddress(String),
+
+    #[error("Failed to build email: {0}")]
+    Message(#[from] lettre::error::Error),
+
+    #[error("SMTP error: {0}")]
+    Smtp(#[from] lettre::transport::smtp::Error),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// Size of an attempt's changes across all of its repos
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EmailDiffStats {
+    pub files_changed: usize,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+}
+
+impl EmailDiffStats {
+    fn describe(&self) -> String {
+        let files = if self.files_changed == 1 {
+            "file"
+        } else {
+            "files"
+        };
+        format!(
+            "{} {files} changed, +{} -{}",
+            self.files_changed, self.lines_added, self.lines_removed
+        )
+    }
+}
+
+/// What an email says about a finished task
+struct TaskEmail<'a> {
+    task: &'a Task,
+    project_name: Option<&'a str>,
+    branch: &'a str,
+    kind: TaskNotificationKind,
+    summary: Option<&'a str>,
+    diff_stats: Option<EmailDiffStats>,
+}
+
+impl TaskEmail<'_> {
+    fn outcome(&self) -> &'static str {
+        match self.kind {
+            TaskNotificationKind::Failed => "failed",
+            _ => "finished",
+        }
+    }
+
+    fn subject(&self) -> String {
+        format!("[vibe-kanban] Task {}: {}", self.outcome(), self.task.title)
+    }
+
+    fn plain_body(&self) -> String {
+        let mut body = format!("Task {}: {}\n\n", self.outcome(), self.task.title);
+        if let Some(project) = self.project_name {
+            body.push_str(&format!("Project: {project}\n"));
+        }
+        body.push_str(&format!("Branch: {}\n", self.branch));
+        if let Some(stats) = self.diff_stats {
+            body.push_str(&format!("Changes: {}\n", stats.describe()));
+        }
+        if let Some(summary) = self.summary {
+            body.push_str(&format!("\nSummary:\n{summary}\n"));
+        }
+        body.push_str(&format!("\nTask ID: {}\n", self.task.id));
+        body
+    }
+
+    fn html_body(&self) -> String {
+        let mut rows = String::new();
+        if let Some(project) = self.project_name {
+            rows.push_str(&format!(
+                "<tr><th align=\"left\">Project</th><td>{}</td></tr>",
+                escape_html(project)
+            ));
+        }
+        rows.push_str(&format!(
+            "<tr><th align=\"left\">Branch</th><td><code>{}</code></td></tr>",
+            escape_html(self.branch)
+        ));
+        if let Some(stats) = self.diff_stats {
+            rows.push_str(&format!(
+                "<tr><th align=\"left\">Changes</th><td>{}</td></tr>",
+                stats.describe()
+            ));
+        }
+
+        let mut html = format!(
+            "<html><body><h2>Task {}: {}</h2><table>{rows}</table>",
+            self.outcome(),
+            escape_html(&self.task.title)
+        );
+        if let Some(summary) = self.summary {
+            html.push_str(&format!(
+                "<h3>Summary</h3><p style=\"white-space: pre-wrap\">{}</p>",
+                escape_html(summary)
+            ));
+        }
+        html.push_str(&format!(
+            "<p style=\"color: #888\">Task ID: {}</p></body></html>",
+            self.task.id
+        ));
+        html
+    }
+}
+
+/// Sends task notifications by email
+#[derive(Clone)]
+pub struct EmailNotifier {
+    config: Arc<RwLock<Config>>,
+    pool: SqlitePool,
+    git: GitService,
+}
+
+impl EmailNotifier {
+    pub fn new(config: Arc<RwLock<Config>>, pool: SqlitePool, git: GitService) -> Self {
+        Self { config, pool, git }
+    }
+
+    fn wants_kind(config: &EmailConfig, kind: TaskNotificationKind) -> bool {
+        config.enabled
+            && matches!(
+                kind,
+                TaskNotificationKind::Done | TaskNotificationKind::Failed
+            )
+    }
+
+    /// Email the configured recipients that an attempt at `task` finished.
+    /// Does nothing if email notifications are off or `kind` is not the end
+    /// of a run.
+    pub async fn send_task_notification(
+        &self,
+        task: &Task,
+        workspace: &Workspace,
+        kind: TaskNotificationKind,
+        summary: Option<&str>,
+    ) -> Result<(), EmailError> {
+        let config = self.config.read().await.email.clone();
+        if !Self::wants_kind(&config, kind) {
+            return Ok(());
+        }
+
+        let project_name = Project::find_by_id(&self.pool, task.project_id)
+            .await?
+            .map(|project| project.name);
+        let diff_stats = match self.diff_stats(workspace).await {
+            Ok(stats) => stats,
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to compute diff stats for workspace {}: {e}",
+                    workspace.id
+                );
+                None
+            }
+        };
+        let email = TaskEmail {
+            task,
+            project_name: project_name.as_deref(),
+            branch: &workspace.branch,
+            kind,
+            summary,
+            diff_stats,
+        };
+
+        self.send(
+            &config,
+            &config.to_addresses,
+            &email.subject(),
+            email.plain_body(),
+            email.html_body(),
+        )
+        .await
+    }
+
+    /// Email a report to `to`, or to the notification recipients when it is
+    /// empty, through the configured SMTP server
+    pub async fn send_report(
+        &self,
+        to: &[String],
+        subject: &str,
+        plain: String,
+        html: String,
+    ) -> Result<(), EmailError> {
+        let config = self.config.read().await.email.clone();
+        let to = if to.is_empty() {
+            &config.to_addresses
+        } else {
+            to
+        };
+        self.send(&config, to, subject, plain, html).await
+    }
+
+    async fn send(
+        &self,
+        config: &EmailConfig,
+        to: &[String],
+        subject: &str,
+        plain: String,
+        html: String,
+    ) -> Result<(), EmailError> {
+        let host = config
+            .smtp_host
+            .as_deref()
+            .filter(|host| !host.is_empty())
+            .ok_or(EmailError::NotConfigured("no SMTP host"))?;
+        let from = config
+            .from_address
+            .as_deref()
+            .ok_or(EmailError::NotConfigured("no sender address"))?;
+        if to.is_empty() {
+            return Err(EmailError::NotConfigured("no recipients"));
+        }
+
+        let mut builder = Message::builder()
+            .from(parse_mailbox(from)?)
+            .subject(subject);
+        for to in to {
+            builder = builder.to(parse_mailbox(to)?);
+        }
+        let message = builder.multipart(MultiPart::alternative_plain_html(plain, html))?;
+
+        let mut transport = match config.tls {
+            SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+            SmtpTls::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?,
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host)?,
+        }
+        .port(config.smtp_port);
+        if let Some(username) = config.username.clone() {
+            transport = transport.credentials(Credentials::new(
+                username,
+                config.password.clone().unwrap_or_default(),
+            ));
+        }
+
+        transport.build().send(message).await?;
+        Ok(())
+    }
+
+    /// Diff stats of the workspace against each repo's target branch, or
+    /// `None` if its worktree is gone
+    async fn diff_stats(
+        &self,
+        workspace: &Workspace,
+    ) -> Result<Option<EmailDiffStats>, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(container_ref) = workspace.container_ref.clone().filter(|c| !c.is_empty()) else {
+            return Ok(None);
+        };
+
+        let repos =
+            WorkspaceRepo::find_repos_with_target_branch_for_workspace(&self.pool, workspace.id)
+                .await?;
+        let git = self.git.clone();
+        let branch = workspace.branch.clone();
+        let stats = tokio::task::spawn_blocking(move || {
+            let mut stats = EmailDiffStats::default();
+            for repo_with_branch in repos {
+                let repo = &repo_with_branch.repo;
+                let worktree_path = PathBuf::from(&container_ref).join(&repo.name);
+                let base_commit =
+                    git.get_base_commit(&repo.path, &branch, &repo_with_branch.target_branch)?;
+                let diffs = git.get_diffs(
+                    DiffTarget::Worktree {
+                        worktree_path: &worktree_path,
+                        base_commit: &base_commit,
+                    },
+                    None,
+                )?;
+                for diff in diffs {
+                    stats.files_changed += 1;
+                    stats.lines_added += diff.additions.unwrap_or(0);
+                    stats.lines_removed += diff.deletions.unwrap_or(0);
+                }
+            }
+            Ok::<_, git::GitServiceError>(stats)
+        })
+        .await??;
+
+        Ok(Some(stats))
+    }
+}
+
+#[async_trait]
+impl Notifier for EmailNotifier {
+    fn channel(&self) -> NotificationChannel {
+        NotificationChannel::Email
+    }
+
+    async fn wants(&self, kind: TaskNotificationKind) -> bool {
+        let config = self.config.read().await;
+        Self::wants_kind(&config.email, kind)
+    }
+
+    /// Only events that ended a run are emailed, as the message reports on
+    /// the run's changes
+    async fn send(&self, event: &NotificationEvent) -> Result<(), NotifierError> {
+        let Some(run) = &event.run else {
+            return Ok(());
+        };
+        self.send_task_notification(
+            &event.task,
+            &run.workspace,
+            event.kind,
+            event.summary.as_deref(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn send_text(&self, title: &str, body: &str) -> Result<(), NotifierError> {
+        let config = self.config.read().await.email.clone();
+        let html = format!(
+            "<html><body><h2>{}</h2><p style=\"white-space: pre-wrap\">{}</p></body></html>",
+            escape_html(title),
+            escape_html(body)
+        );
+        self.send(&config, &config.to_addresses, title, body.to_string(), html)
+            .await?;
+        Ok(())
+    }
+}
+
+fn parse_mailbox(address: &str) -> Result<Mailbox, EmailError> {
+    address
+        .trim()
+        .parse()
+        .map_err(|_| EmailError::InvalidAddress(address.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_fixtures::task;
+
+    #[test]
+    fn plain_body_includes_summary_and_stats() {
+        let task = task("Fix login");
+        let email = TaskEmail {
+            task: &task,
+            project_name: Some("Web"),
+            branch: "vk/fix-login",
+            kind: TaskNotificationKind::Done,
+            summary: Some("Fixed the redirect loop."),
+            diff_stats: Some(EmailDiffStats {
+                files_changed: 2,
+                lines_added: 10,
+                lines_removed: 3,
+            }),
+        };
+
+        assert_eq!(email.subject(), "[vibe-kanban] Task finished: Fix login");
+        let body = email.plain_body();
+        assert!(body.contains("Project: Web\n"));
+        assert!(body.contains("Changes: 2 files changed, +10 -3\n"));
+        assert!(body.contains("Summary:\nFixed the redirect loop.\n"));
+    }
+
+    #[test]
+    fn html_body_escapes_task_text() {
+        let task = task("Render <b>bold</b> & more");
+        let email = TaskEmail {
+            task: &task,
+            project_name: None,
+            branch: "vk/bold",
+            kind: TaskNotificationKind::Failed,
+            summary: Some("Used <script>"),
+            diff_stats: None,
+        };
+
+        let html = email.html_body();
+        assert!(html.contains("Task failed: Render &lt;b&gt;bold&lt;/b&gt; &amp; more"));
+        assert!(html.contains("Used &lt;script&gt;"));
+        assert!(!html.contains("Changes"));
+    }
+
+    #[test]
+    fn wants_only_enabled_finished_events() {
+        let mut config = EmailConfig::default();
+        assert!(!EmailNotifier::wants_kind(
+            &config,
+            TaskNotificationKind::Done
+        ));
+
+        config.enabled = true;
+        assert!(EmailNotifier::wants_kind(
+            &config,
+            TaskNotificationKind::Done
+        ));
+        assert!(EmailNotifier::wants_kind(
+            &config,
+            TaskNotificationKind::Failed
+        ));
+        assert!(!EmailNotifier::wants_kind(
+            &config,
+            TaskNotificationKind::Started
+        ));
+    }
+
+    #[test]
+    fn single_file_stats_are_singular() {
+        let stats = EmailDiffStats {
+            files_changed: 1,
+            lines_added: 4,
+            lines_removed: 0,
+        };
+        assert_eq!(stats.describe(), "1 file changed, +4 -0");
+    }
+}
@@ -0,0 +1,124 @@
+//! Escalation: task failures nobody acknowledges are sent through more
+//! channels as time passes.
+//!
+//! The dispatcher starts an escalation in `notification_escalations` when it
+//! sends a failure. [`EscalationWorker`] checks every minute and takes each
+//! step of `Config::escalation` once its time has come, until one of the
+//! failure's notifications is acknowledged or no steps are left. Steps go
+//! out even during quiet hours, as being woken up is the point.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tokio::time::interval;
+use tracing::{error, info};
+
+use crate::services::{
+    config::{EscalationPolicy, EscalationStep},
+    notification_dispatcher::NotificationDispatcher,
+};
+
+/// Where an escalation stands
+#[derive(Debug, PartialEq, Eq)]
+pub enum Progress<'a> {
+    /// The next step's time has come
+    Due(&'a EscalationStep),
+    /// The next step's time has not come yet
+    Waiting,
+    /// Every step has been taken, or escalation is off
+    Finished,
+}
+
+impl<'a> Progress<'a> {
+    /// Where an escalation started at `started_at` that has taken
+    /// `steps_taken` steps stands at `now`
+    pub fn of(
+        policy: &'a EscalationPolicy,
+        steps_taken: i64,
+        started_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let step = usize::try_from(steps_taken)
+            .ok()
+            .and_then(|taken| policy.steps.get(taken));
+        match step {
+            Some(step) if policy.enabled => {
+                let due_at = started_at + chrono::Duration::minutes(step.after_minutes.into());
+                if now >= due_at {
+                    Self::Due(step)
+                } else {
+                    Self::Waiting
+                }
+            }
+            _ => Self::Finished,
+        }
+    }
+}
+
+/// Background worker that takes the escalation steps that are due
+pub struct EscalationWorker {
+    dispatcher: NotificationDispatcher,
+    poll_interval: Duration,
+}
+
+impl EscalationWorker {
+    pub async fn spawn(dispatcher: NotificationDispatcher) -> tokio::task::JoinHandle<()> {
+        let worker = Self {
+            dispatcher,
+            poll_interval: Duration::from_secs(60),
+        };
+        tokio::spawn(async move {
+            worker.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        info!(
+            "Starting escalation worker with interval {:?}",
+            self.poll_interval
+        );
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.dispatcher.escalate().await {
+                error!("Error escalating notifications: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use db::models::notification_rule::NotificationChannel;
+
+    use super::*;
+
+    #[test]
+    fn test_steps_are_taken_in_order_once_due() {
+        let mut policy = EscalationPolicy {
+            enabled: true,
+            ..EscalationPolicy::default()
+        };
+        let start = Utc.with_ymd_and_hms(2026, 3, 7, 12, 0, 0).unwrap();
+        let at = |minutes| start + chrono::Duration::minutes(minutes);
+
+        assert_eq!(Progress::of(&policy, 0, start, at(14)), Progress::Waiting);
+        assert_eq!(
+            Progress::of(&policy, 0, start, at(15)),
+            Progress::Due(&policy.steps[0])
+        );
+        assert_eq!(policy.steps[0].channel, NotificationChannel::Telegram);
+        assert_eq!(Progress::of(&policy, 1, start, at(20)), Progress::Waiting);
+        assert_eq!(
+            Progress::of(&policy, 1, start, at(30)),
+            Progress::Due(&policy.steps[1])
+        );
+        assert_eq!(Progress::of(&policy, 2, start, at(60)), Progress::Finished);
+
+        policy.enabled = false;
+        assert_eq!(Progress::of(&policy, 0, start, at(60)), Progress::Finished);
+    }
+}
@@ -0,0 +1,275 @@
+//! A/B evaluation of executor profiles.
+//!
+//! An [`EvalRun`] starts one attempt per benchmark task for each of two
+//! profiles. When an attempt's coding agent finishes it is judged by the run's
+//! test command, and [`build_report`] compares pass rate, duration and token
+//! usage between the two profiles.
+
+use std::{path::Path, process::Stdio, time::Duration};
+
+use db::models::{
+    eval_run::{EvalAttempt, EvalOutcome, EvalRun, EvalVariant},
+    execution_process::ExecutionProcessStatus,
+};
+use executors::{
+    logs::{NormalizedEntry, NormalizedEntryType},
+    profile::ExecutorProfileId,
+};
+use serde::Serialize;
+use tokio::process::Command;
+use ts_rs::TS;
+use utils::shell::get_shell_command;
+
+/// Upper bound for a single test command run
+pub const TEST_COMMAND_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+pub struct EvalVariantSummary {
+    pub variant: EvalVariant,
+    pub profile: ExecutorProfileId,
+    pub attempts: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub pending: usize,
+    /// Passed share of finished attempts, `None` until one finishes
+    pub pass_rate: Option<f64>,
+    pub avg_duration_secs: Option<f64>,
+    pub total_tokens: i64,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct EvalBenchmarkRow {
+    pub label: String,
+    pub a: Option<EvalAttempt>,
+    pub b: Option<EvalAttempt>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct EvalReport {
+    pub run: EvalRun,
+    pub a: EvalVariantSummary,
+    pub b: EvalVariantSummary,
+    /// One row per benchmark label, in the order the tasks were given
+    pub benchmarks: Vec<EvalBenchmarkRow>,
+}
+
+fn summarize(run: &EvalRun, variant: EvalVariant, attempts: &[EvalAttempt]) -> EvalVariantSummary {
+    let attempts: Vec<&EvalAttempt> = attempts.iter().filter(|a| a.variant == variant).collect();
+    let count = |outcome| attempts.iter().filter(|a| a.outcome == outcome).count();
+    let passed = count(EvalOutcome::Passed);
+    let failed = count(EvalOutcome::Failed);
+    let finished = passed + failed;
+
+    let durations: Vec<i64> = attempts.iter().filter_map(|a| a.duration_secs).collect();
+    let avg_duration_secs = (!durations.is_empty())
+        .then(|| durations.iter().sum::<i64>() as f64 / durations.len() as f64);
+
+    EvalVariantSummary {
+        variant,
+        profile: run.profile(variant).clone(),
+        attempts: attempts.len(),
+        passed,
+        failed,
+        pending: count(EvalOutcome::Pending),
+        pass_rate: (finished > 0).then(|| passed as f64 / finished as f64),
+        avg_duration_secs,
+        total_tokens: attempts.iter().filter_map(|a| a.total_tokens).sum(),
+    }
+}
+
+pub fn build_report(run: EvalRun, attempts: Vec<EvalAttempt>) -> EvalReport {
+    let a = summarize(&run, EvalVariant::A, &attempts);
+    let b = summarize(&run, EvalVariant::B, &attempts);
+
+    let mut benchmarks: Vec<EvalBenchmarkRow> = Vec::new();
+    for attempt in attempts {
+        let index = match benchmarks
+            .iter()
+            .position(|row| row.label == attempt.benchmark_label)
+        {
+            Some(index) => index,
+            None => {
+                benchmarks.push(EvalBenchmarkRow {
+                    label: attempt.benchmark_label.clone(),
+                    a: None,
+                    b: None,
+                });
+                benchmarks.len() - 1
+            }
+        };
+        let row = &mut benchmarks[index];
+        match attempt.variant {
+            EvalVariant::A => row.a = Some(attempt),
+            EvalVariant::B => row.b = Some(attempt),
+        }
+    }
+
+    EvalReport {
+        run,
+        a,
+        b,
+        benchmarks,
+    }
+}
+
+/// Highest context usage the agent reported during a run
+pub fn peak_total_tokens(entries: &[NormalizedEntry]) -> Option<i64> {
+    entries
+        .iter()
+        .filter_map(|entry| match &entry.entry_type {
+            NormalizedEntryType::TokenUsageInfo(usage) => Some(i64::from(usage.total_tokens)),
+            _ => None,
+        })
+        .max()
+}
+
+/// Outcome of a finished coding agent run; `test_passed` is the test command
+/// result, if the run has one
+pub fn judge(
+    status: &ExecutionProcessStatus,
+    exit_code: Option<i64>,
+    test_passed: Option<bool>,
+) -> EvalOutcome {
+    match status {
+        ExecutionProcessStatus::Running => EvalOutcome::Pending,
+        ExecutionProcessStatus::Failed
+        | ExecutionProcessStatus::Killed
+        | ExecutionProcessStatus::Stalled => EvalOutcome::Failed,
+        ExecutionProcessStatus::Completed => {
+            let passed = test_passed.unwrap_or(exit_code.is_none_or(|code| code == 0));
+            if passed {
+                EvalOutcome::Passed
+            } else {
+                EvalOutcome::Failed
+            }
+        }
+    }
+}
+
+/// Run `command` in `dir`; a timeout counts as a failure
+pub async fn run_test_command(dir: &Path, command: &str) -> std::io::Result<bool> {
+    let (shell_cmd, shell_arg) = get_shell_command();
+    let mut child = Command::new(shell_cmd)
+        .arg(shell_arg)
+        .arg(command)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    match tokio::time::timeout(TEST_COMMAND_TIMEOUT, child.wait()).await {
+        Ok(status) => Ok(status?.success()),
+        Err(_) => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use executors::executors::BaseCodingAgent;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn run() -> EvalRun {
+        EvalRun {
+            id: Uuid::new_v4(),
+            project_id: Uuid::new_v4(),
+            name: "claude vs codex".to_string(),
+            profile_a: sqlx::types::Json(ExecutorProfileId::new(BaseCodingAgent::ClaudeCode)),
+            profile_b: sqlx::types::Json(ExecutorProfileId::new(BaseCodingAgent::Codex)),
+            test_command: Some("cargo test".to_string()),
+            created_at: Utc::now(),
+        }
+    }
+
+    fn attempt(
+        run: &EvalRun,
+        label: &str,
+        variant: EvalVariant,
+        outcome: EvalOutcome,
+        duration_secs: Option<i64>,
+        total_tokens: Option<i64>,
+    ) -> EvalAttempt {
+        EvalAttempt {
+            id: Uuid::new_v4(),
+            eval_run_id: run.id,
+            benchmark_label: label.to_string(),
+            variant,
+            task_id: Uuid::new_v4(),
+            workspace_id: None,
+            outcome,
+            duration_secs,
+            total_tokens,
+            created_at: Utc::now(),
+            completed_at: None,
+        }
+    }
+
+    #[test]
+    fn test_build_report_compares_variants() {
+        use EvalOutcome::*;
+        use EvalVariant::*;
+
+        let run = run();
+        let attempts = vec![
+            attempt(&run, "fix-login", A, Passed, Some(60), Some(1000)),
+            attempt(&run, "fix-login", B, Failed, Some(30), Some(400)),
+            attempt(&run, "add-docs", A, Failed, Some(120), Some(2000)),
+            attempt(&run, "add-docs", B, Pending, None, None),
+        ];
+
+        let report = build_report(run, attempts);
+
+        assert_eq!(report.a.attempts, 2);
+        assert_eq!(report.a.pass_rate, Some(0.5));
+        assert_eq!(report.a.avg_duration_secs, Some(90.0));
+        assert_eq!(report.a.total_tokens, 3000);
+        assert_eq!(report.b.pending, 1);
+        assert_eq!(report.b.pass_rate, Some(0.0));
+        assert_eq!(report.b.avg_duration_secs, Some(30.0));
+
+        let labels: Vec<&str> = report.benchmarks.iter().map(|r| r.label.as_str()).collect();
+        assert_eq!(labels, vec!["fix-login", "add-docs"]);
+        assert!(
+            report
+                .benchmarks
+                .iter()
+                .all(|r| r.a.is_some() && r.b.is_some())
+        );
+    }
+
+    #[test]
+    fn test_pass_rate_empty_until_finished() {
+        let run = run();
+        let attempts = vec![attempt(
+            &run,
+            "fix-login",
+            EvalVariant::A,
+            EvalOutcome::Pending,
+            None,
+            None,
+        )];
+
+        let report = build_report(run, attempts);
+
+        assert_eq!(report.a.pass_rate, None);
+        assert_eq!(report.a.avg_duration_secs, None);
+        assert_eq!(report.b.attempts, 0);
+    }
+
+    #[test]
+    fn test_judge() {
+        use ExecutionProcessStatus::*;
+
+        assert_eq!(judge(&Running, None, None), EvalOutcome::Pending);
+        assert_eq!(judge(&Killed, None, Some(true)), EvalOutcome::Failed);
+        assert_eq!(judge(&Stalled, None, None), EvalOutcome::Failed);
+        assert_eq!(judge(&Completed, Some(0), Some(false)), EvalOutcome::Failed);
+        assert_eq!(judge(&Completed, Some(1), Some(true)), EvalOutcome::Passed);
+        assert_eq!(judge(&Completed, Some(0), None), EvalOutcome::Passed);
+        assert_eq!(judge(&Completed, Some(2), None), EvalOutcome::Failed);
+    }
+}
@@ -202,9 +202,15 @@ impl EventService {
                                 (HookTables::Projects, _) => {
                                     match Project::find_by_rowid(&db.pool, rowid).await {
                                         Ok(Some(project)) => RecordTypes::Project(project),
+                                        // A soft delete is an update that hides the row
                                         Ok(None) => RecordTypes::DeletedProject {
                                             rowid,
-                                            project_id: None,
+                                            project_id: Project::find_deleted_id_by_rowid(
+                                                &db.pool, rowid,
+                                            )
+                                            .await
+                                            .ok()
+                                            .flatten(),
                                         },
                                         Err(e) => {
                                             tracing::error!("Failed to fetch project: {:?}", e);
@@ -302,6 +308,14 @@ impl EventService {
                                     msg_store_for_hook.push_patch(patch);
                                     return;
                                 }
+                                RecordTypes::DeletedProject {
+                                    project_id: Some(project_id),
+                                    ..
+                                } => {
+                                    let patch = project_patch::remove(*project_id);
+                                    msg_store_for_hook.push_patch(patch);
+                                    return;
+                                }
                                 RecordTypes::Project(project) => {
                                     let patch = match hook.operation {
                                         SqliteOperation::Insert => project_patch::add(project),
@@ -333,6 +333,34 @@ impl AzCli {
         Self::parse_pr_response(&raw)
     }
 
+    /// Complete (merge) a pull request by URL.
+    pub fn complete_pr(&self, pr_url: &str) -> Result<PullRequestInfo, AzCliError> {
+        let (organization, pr_id) = Self::parse_pr_url(pr_url).ok_or_else(|| {
+            AzCliError::UnexpectedOutput(format!("Could not parse Azure DevOps PR URL: {pr_url}"))
+        })?;
+
+        let org_url = format!("https://dev.azure.com/{}", organization);
+
+        let raw = self.run(
+            [
+                "repos",
+                "pr",
+                "update",
+                "--id",
+                &pr_id.to_string(),
+                "--organization",
+                &org_url,
+                "--status",
+                "completed",
+                "--output",
+                "json",
+            ],
+            None,
+        )?;
+
+        Self::parse_pr_response(&raw)
+    }
+
     pub fn list_prs_for_branch(
         &self,
         organization_url: &str,
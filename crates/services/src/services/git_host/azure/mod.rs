@@ -159,6 +159,21 @@ impl GitHostProvider for AzureDevOpsProvider {
         .await
     }
 
+    async fn merge_pr(&self, pr_url: &str) -> Result<PullRequestInfo, GitHostError> {
+        let cli = self.az_cli.clone();
+        let url = pr_url.to_string();
+
+        // Not retried: a merge that failed mid-way may still have gone through
+        task::spawn_blocking(move || cli.complete_pr(&url))
+            .await
+            .map_err(|err| {
+                GitHostError::PullRequest(format!(
+                    "Failed to execute Azure CLI for merging PR: {err}"
+                ))
+            })?
+            .map_err(GitHostError::from)
+    }
+
     async fn list_prs_for_branch(
         &self,
         repo_path: &Path,
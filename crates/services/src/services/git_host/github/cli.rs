@@ -8,6 +8,7 @@ use std::{
     io::Write,
     path::Path,
     process::Command,
+    sync::RwLock,
 };
 
 use chrono::{DateTime, Utc};
@@ -21,6 +22,10 @@ use crate::services::git_host::types::{
     CreatePrRequest, OpenPrInfo, PrComment, PrCommentAuthor, PrReviewComment, ReviewCommentUser,
 };
 
+/// Token `gh` runs with instead of its own login, from `GitHubConfig::cli_token`.
+/// Read on every invocation, so a rotated token applies to the next command.
+static TOKEN_OVERRIDE: RwLock<Option<String>> = RwLock::new(None);
+
 #[derive(Debug, Clone)]
 pub struct GitHubRepoInfo {
     pub owner: String,
@@ -134,7 +139,43 @@ impl GhCli {
         Ok(())
     }
 
+    /// Run `gh` as the configured token, or with its own login when none is set
+    pub fn set_token(token: Option<String>) {
+        *TOKEN_OVERRIDE
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = token;
+    }
+
+    /// Login of the account `token` belongs to, failing if GitHub rejects it
+    pub fn token_login(&self, token: &str) -> Result<String, GhCliError> {
+        let raw = self.run_as(["api", "user", "--jq", ".login"], None, Some(token))?;
+        let login = raw.trim();
+        if login.is_empty() {
+            return Err(GhCliError::UnexpectedOutput(
+                "gh api user returned no login".to_string(),
+            ));
+        }
+        Ok(login.to_string())
+    }
+
     fn run<I, S>(&self, args: I, dir: Option<&Path>) -> Result<String, GhCliError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let token = TOKEN_OVERRIDE
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+        self.run_as(args, dir, token.as_deref())
+    }
+
+    fn run_as<I, S>(
+        &self,
+        args: I,
+        dir: Option<&Path>,
+        token: Option<&str>,
+    ) -> Result<String, GhCliError>
     where
         I: IntoIterator<Item = S>,
         S: AsRef<OsStr>,
@@ -145,6 +186,9 @@ impl GhCli {
         if let Some(d) = dir {
             cmd.current_dir(d);
         }
+        if let Some(token) = token {
+            cmd.env("GH_TOKEN", token);
+        }
         for arg in args {
             cmd.arg(arg);
         }
@@ -257,6 +301,12 @@ impl GhCli {
         Self::parse_pr_view(&raw)
     }
 
+    /// Merge a pull request by URL with a merge commit.
+    pub fn merge_pr(&self, pr_url: &str) -> Result<PullRequestInfo, GhCliError> {
+        self.run(["pr", "merge", pr_url, "--merge"], None)?;
+        self.view_pr(pr_url)
+    }
+
     /// List pull requests for a branch (includes closed/merged).
     pub fn list_prs_for_branch(
         &self,
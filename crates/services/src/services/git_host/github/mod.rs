@@ -29,6 +29,18 @@ impl GitHubProvider {
         })
     }
 
+    /// Login of the account `token` belongs to, failing if GitHub rejects it
+    pub async fn token_login(&self, token: &str) -> Result<String, GitHostError> {
+        let cli = self.gh_cli.clone();
+        let token = token.to_string();
+        task::spawn_blocking(move || cli.token_login(&token))
+            .await
+            .map_err(|err| {
+                GitHostError::UnexpectedOutput(format!("Failed to check the GitHub token: {err}"))
+            })?
+            .map_err(Into::into)
+    }
+
     async fn get_repo_info(
         &self,
         remote_url: &str,
@@ -259,6 +271,21 @@ impl GitHostProvider for GitHubProvider {
         .await
     }
 
+    async fn merge_pr(&self, pr_url: &str) -> Result<PullRequestInfo, GitHostError> {
+        let cli = self.gh_cli.clone();
+        let url = pr_url.to_string();
+
+        // Not retried: a merge that failed mid-way may still have gone through
+        task::spawn_blocking(move || cli.merge_pr(&url))
+            .await
+            .map_err(|err| {
+                GitHostError::PullRequest(format!(
+                    "Failed to execute GitHub CLI for merging PR: {err}"
+                ))
+            })?
+            .map_err(GitHostError::from)
+    }
+
     async fn list_prs_for_branch(
         &self,
         repo_path: &Path,
@@ -29,6 +29,9 @@ pub trait GitHostProvider: Send + Sync {
 
     async fn get_pr_status(&self, pr_url: &str) -> Result<PullRequestInfo, GitHostError>;
 
+    /// Merge an open pull request and return its updated state
+    async fn merge_pr(&self, pr_url: &str) -> Result<PullRequestInfo, GitHostError>;
+
     async fn list_prs_for_branch(
         &self,
         repo_path: &Path,
@@ -0,0 +1,216 @@
+//! Task notifications pushed through a self-hosted Gotify server.
+//!
+//! Messages are posted to `/message` on the server in `Config::gotify`, as the
+//! application whose token is configured there. With `markdown` on, the
+//! `client::display` extra tells the Gotify clients to render the message as
+//! markdown.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use db::models::{notification_rule::NotificationChannel, task::Task};
+use serde::Serialize;
+use serde_json::{Value, json};
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+use crate::services::{
+    config::{Config, GotifyConfig},
+    http_notifier::{self, StatusError},
+    notification_dispatcher::{NotificationEvent, Notifier, NotifierError},
+    telegram::TaskNotificationKind,
+};
+
+#[derive(Debug, Error)]
+pub enum GotifyError {
+    #[error("Gotify notifications are not configured: {0}")]
+    NotConfigured(&'static str),
+
+    #[error("Gotify request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("Gotify returned {0}")]
+    Status(#[from] StatusError),
+}
+
+/// Body of `POST /message`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct GotifyMessage {
+    title: String,
+    message: String,
+    /// 0 to 10. The Android app stays silent below 4 and pops the message
+    /// up from 8.
+    priority: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    extras: Option<Value>,
+}
+
+impl GotifyMessage {
+    fn for_task(
+        task: &Task,
+        kind: TaskNotificationKind,
+        summary: Option<&str>,
+        markdown: bool,
+    ) -> Self {
+        let (event, priority) = match kind {
+            TaskNotificationKind::Started => ("started", 2),
+            TaskNotificationKind::InReview => ("ready for review", 5),
+            TaskNotificationKind::Failed => ("failed", 8),
+            TaskNotificationKind::Done => ("completed", 5),
+            TaskNotificationKind::Cancelled => ("cancelled", 2),
+        };
+        let summary = summary.map(str::trim).filter(|summary| !summary.is_empty());
+        let message = match (summary, markdown) {
+            (Some(summary), true) => format!("**{}**\n\n{summary}", escape_markdown(&task.title)),
+            (Some(summary), false) => summary.to_string(),
+            (None, true) => escape_markdown(&task.title),
+            (None, false) => task.title.clone(),
+        };
+        Self {
+            title: format!("Task {event}: {}", task.title),
+            message,
+            priority,
+            extras: markdown.then(|| {
+                json!({
+                    "client::display": { "contentType": "text/markdown" }
+                })
+            }),
+        }
+    }
+}
+
+/// Backslash-escape the characters markdown would treat as formatting
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '#' | '<' | '>' | '|' | '~'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Posts task notifications to a Gotify application
+#[derive(Clone)]
+pub struct GotifyNotifier {
+    config: Arc<RwLock<Config>>,
+    http: reqwest::Client,
+}
+
+impl GotifyNotifier {
+    pub fn new(config: Arc<RwLock<Config>>) -> Self {
+        Self {
+            config,
+            http: http_notifier::client(),
+        }
+    }
+
+    /// Push a notification about `task` to the configured server. Does
+    /// nothing if Gotify is off.
+    pub async fn send_task_notification(
+        &self,
+        task: &Task,
+        kind: TaskNotificationKind,
+        summary: Option<&str>,
+    ) -> Result<(), GotifyError> {
+        let config = self.config.read().await.gotify.clone();
+        if !config.enabled {
+            return Ok(());
+        }
+        let message = GotifyMessage::for_task(task, kind, summary, config.markdown);
+        self.post(&config, &message).await
+    }
+
+    async fn post(
+        &self,
+        config: &GotifyConfig,
+        message: &GotifyMessage,
+    ) -> Result<(), GotifyError> {
+        let server_url = config
+            .server_url
+            .as_deref()
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .ok_or(GotifyError::NotConfigured("no server URL"))?;
+        let app_token = config
+            .app_token
+            .as_deref()
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+            .ok_or(GotifyError::NotConfigured("no app token"))?;
+
+        let response = self
+            .http
+            .post(format!("{}/message", server_url.trim_end_matches('/')))
+            .header("X-Gotify-Key", app_token)
+            .json(message)
+            .send()
+            .await?;
+        http_notifier::check_status(response).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Notifier for GotifyNotifier {
+    fn channel(&self) -> NotificationChannel {
+        NotificationChannel::Gotify
+    }
+
+    async fn wants(&self, _kind: TaskNotificationKind) -> bool {
+        self.config.read().await.gotify.enabled
+    }
+
+    async fn send(&self, event: &NotificationEvent) -> Result<(), NotifierError> {
+        self.send_task_notification(&event.task, event.kind, event.summary.as_deref())
+            .await?;
+        Ok(())
+    }
+
+    async fn send_text(&self, title: &str, body: &str) -> Result<(), NotifierError> {
+        let config = self.config.read().await.gotify.clone();
+        let message = GotifyMessage {
+            title: title.to_string(),
+            message: body.to_string(),
+            priority: 5,
+            extras: None,
+        };
+        self.post(&config, &message).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_fixtures::task;
+
+    #[test]
+    fn test_message_priority_and_markdown() {
+        let task = task("Fix *all* the bugs");
+
+        let failed = GotifyMessage::for_task(&task, TaskNotificationKind::Failed, None, true);
+        assert_eq!(failed.title, "Task failed: Fix *all* the bugs");
+        assert_eq!(failed.message, "Fix \\*all\\* the bugs");
+        assert_eq!(failed.priority, 8);
+
+        let done = GotifyMessage::for_task(&task, TaskNotificationKind::Done, Some("Fixed."), true);
+        assert_eq!(done.priority, 5);
+        assert_eq!(done.message, "**Fix \\*all\\* the bugs**\n\nFixed.");
+        let body = serde_json::to_value(&done).unwrap();
+        assert_eq!(
+            body["extras"]["client::display"]["contentType"],
+            "text/markdown"
+        );
+
+        let plain =
+            GotifyMessage::for_task(&task, TaskNotificationKind::Done, Some("Fixed."), false);
+        assert_eq!(plain.message, "Fixed.");
+        let body = serde_json::to_value(&plain).unwrap();
+        assert!(body.get("extras").is_none());
+    }
+}
@@ -0,0 +1,109 @@
+//! Point-in-time view of server and executor health, shared by the API and the
+//! Telegram `/status` command.
+
+use std::{sync::Arc, time::Instant};
+
+use chrono::{DateTime, Utc};
+use db::models::execution_process::ExecutionProcess;
+use executors::{
+    executors::{BaseCodingAgent, StandardCodingAgentExecutor},
+    profile::ExecutorConfigs,
+};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tokio::sync::RwLock;
+use ts_rs::TS;
+
+use crate::services::{
+    config::Config,
+    queued_message::QueuedMessageService,
+    rate_limit::{ProviderCooldown, ProviderCooldowns},
+};
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct HealthSnapshot {
+    pub started_at: DateTime<Utc>,
+    pub uptime_secs: u64,
+    /// Coding agent attempts currently running
+    pub running_attempts: usize,
+    /// Sessions with a follow-up message waiting for the current run to finish
+    pub queued_follow_ups: usize,
+    /// Executor of the default profile in the user config
+    pub executor: BaseCodingAgent,
+    /// Whether that executor's binary or login was found on this machine
+    pub executor_available: bool,
+    /// Providers paused after a rate-limit error
+    pub provider_cooldowns: Vec<ProviderCooldown>,
+}
+
+/// Collects [`HealthSnapshot`]s
+#[derive(Clone)]
+pub struct HealthService {
+    started: Instant,
+    started_at: DateTime<Utc>,
+    pool: SqlitePool,
+    config: Arc<RwLock<Config>>,
+    queued_messages: QueuedMessageService,
+    cooldowns: ProviderCooldowns,
+}
+
+impl HealthService {
+    pub fn new(
+        pool: SqlitePool,
+        config: Arc<RwLock<Config>>,
+        queued_messages: QueuedMessageService,
+        cooldowns: ProviderCooldowns,
+    ) -> Self {
+        Self {
+            started: Instant::now(),
+            started_at: Utc::now(),
+            pool,
+            config,
+            queued_messages,
+            cooldowns,
+        }
+    }
+
+    pub async fn snapshot(&self) -> Result<HealthSnapshot, sqlx::Error> {
+        let running_attempts = ExecutionProcess::count_running_coding_agents(&self.pool).await?;
+        let profile = self.config.read().await.executor_profile.clone();
+        let executor_available = ExecutorConfigs::get_cached()
+            .get_coding_agent(&profile)
+            .is_some_and(|agent| agent.get_availability_info().is_available());
+
+        Ok(HealthSnapshot {
+            started_at: self.started_at,
+            uptime_secs: self.started.elapsed().as_secs(),
+            running_attempts: running_attempts.max(0) as usize,
+            queued_follow_ups: self.queued_messages.len(),
+            executor: profile.executor,
+            executor_available,
+            provider_cooldowns: self.cooldowns.all_active(),
+        })
+    }
+}
+
+/// Compact uptime such as `3d 4h 12m`, dropping leading zero units
+pub fn format_uptime(secs: u64) -> String {
+    let days = secs / 86_400;
+    let hours = secs % 86_400 / 3600;
+    let minutes = secs % 3600 / 60;
+    match (days, hours) {
+        (0, 0) => format!("{minutes}m"),
+        (0, _) => format!("{hours}h {minutes}m"),
+        _ => format!("{days}d {hours}h {minutes}m"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_uptime() {
+        assert_eq!(format_uptime(59), "0m");
+        assert_eq!(format_uptime(3 * 60), "3m");
+        assert_eq!(format_uptime(2 * 3600 + 5 * 60), "2h 5m");
+        assert_eq!(format_uptime(86_400 + 60), "1d 0h 1m");
+    }
+}
@@ -0,0 +1,44 @@
+//! HTTP plumbing shared by the notifiers that post to a web service.
+//!
+//! They all wait the same time for an answer, and a refused request becomes a
+//! [`StatusError`] with the status and the start of the response body, which
+//! is usually where the service says what was wrong.
+
+use std::time::Duration;
+
+use thiserror::Error;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Longest response body excerpt kept in errors
+const ERROR_BODY_MAX_CHARS: usize = 300;
+
+/// A response whose status is not a success
+#[derive(Debug, Error)]
+#[error("HTTP {status}: {body}")]
+pub struct StatusError {
+    pub status: u16,
+    /// The start of the response body
+    pub body: String,
+}
+
+/// A client for notifier requests, which gives up after [`REQUEST_TIMEOUT`]
+pub fn client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .unwrap_or_default()
+}
+
+/// `response` if its status is a success, otherwise its status and an
+/// excerpt of its body
+pub async fn check_status(response: reqwest::Response) -> Result<reqwest::Response, StatusError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    Err(StatusError {
+        status: status.as_u16(),
+        body: body.chars().take(ERROR_BODY_MAX_CHARS).collect(),
+    })
+}
@@ -0,0 +1,1452 @@
+//! Localized text for bot replies and other server-generated strings.
+//!
+//! The language comes from the configured [`UiLanguage`]. When that is left
+//! on `Browser` (auto-detect), bot replies use the Telegram user's
+//! `language_code` and API errors the request's `Accept-Language`, both
+//! falling back to English. Notifications and the digest have no reader to
+//! detect, so they follow the configured language only.
+//!
+//! [`BotMessage`] holds the bot replies and [`ServerMessage`] everything
+//! else, so adding a language means filling in these two catalogues.
+
+use std::future::Future;
+
+use uuid::Uuid;
+
+use crate::services::config::UiLanguage;
+
+tokio::task_local! {
+    static REQUEST_LOCALE: Locale;
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    En,
+    Fr,
+    Ja,
+    Es,
+    Ko,
+    ZhHans,
+    ZhHant,
+}
+
+impl Locale {
+    /// Map an IETF language tag such as `fr`, `pt-BR` or `zh-TW`
+    pub fn from_language_code(code: &str) -> Option<Self> {
+        let code = code.trim().to_ascii_lowercase().replace('_', "-");
+        let (language, region) = code.split_once('-').unwrap_or((&code, ""));
+        match language {
+            "en" => Some(Self::En),
+            "fr" => Some(Self::Fr),
+            "ja" => Some(Self::Ja),
+            "es" => Some(Self::Es),
+            "ko" => Some(Self::Ko),
+            "zh" => match region {
+                "hant" | "tw" | "hk" | "mo" => Some(Self::ZhHant),
+                _ => Some(Self::ZhHans),
+            },
+            _ => None,
+        }
+    }
+
+    /// The most preferred supported language of an `Accept-Language` header
+    pub fn from_accept_language(header: &str) -> Option<Self> {
+        let mut ranges: Vec<(&str, f32)> = header
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                let tag = parts.next()?.trim();
+                let quality = parts
+                    .find_map(|param| param.trim().strip_prefix("q="))
+                    .map_or(Some(1.0), |q| q.trim().parse().ok())?;
+                (!tag.is_empty() && quality > 0.0).then_some((tag, quality))
+            })
+            .collect();
+        // Stable, so equally weighted tags keep the client's order
+        ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+        ranges
+            .into_iter()
+            .find_map(|(tag, _)| Self::from_language_code(tag))
+    }
+
+    /// Language of the API request being handled, as set with [`Self::scope`].
+    /// English outside of a request.
+    pub fn current() -> Self {
+        REQUEST_LOCALE
+            .try_with(|locale| *locale)
+            .unwrap_or_default()
+    }
+
+    /// Run `f` with this as the [`Self::current`] locale
+    pub async fn scope<F: Future>(self, f: F) -> F::Output {
+        REQUEST_LOCALE.scope(self, f).await
+    }
+
+    pub fn resolve(language: &UiLanguage, language_code: Option<&str>) -> Self {
+        match language {
+            UiLanguage::Browser => language_code
+                .and_then(Self::from_language_code)
+                .unwrap_or_default(),
+            UiLanguage::En => Self::En,
+            UiLanguage::Fr => Self::Fr,
+            UiLanguage::Ja => Self::Ja,
+            UiLanguage::Es => Self::Es,
+            UiLanguage::Ko => Self::Ko,
+            UiLanguage::ZhHans => Self::ZhHans,
+            UiLanguage::ZhHant => Self::ZhHant,
+        }
+    }
+}
+
+/// A bot reply that is shown to the user in their language. Dynamic parts
+/// must already be HTML-escaped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BotMessage<'a> {
+    Welcome,
+    Help,
+    UnknownCommand(&'a str),
+    InvalidLinkToken,
+    LinkTokenExpired,
+    LinkCompleted { username: Option<&'a str> },
+    NotLinked,
+    ReadOnly,
+    NoActiveProject,
+    ProjectNotFound(Uuid),
+    TaskNotFound(Uuid),
+    InvalidCommand(&'a str),
+    Cancelled,
+}
+
+impl BotMessage<'_> {
+    pub fn render(&self, locale: Locale) -> String {
+        use Locale::*;
+
+        match self {
+            Self::Welcome => welcome(locale).to_string(),
+            Self::Help => help(locale).to_string(),
+            Self::UnknownCommand(command) => match locale {
+                En => format!("Unknown command: /{command}. Use /help to see available commands."),
+                Fr => format!(
+                    "Commande inconnue : /{command}. Utilisez /help pour voir les commandes disponibles."
+                ),
+                Ja => format!("不明なコマンドです: /{command}。/help で使用できるコマンドを確認できます。"),
+                Es => format!("Comando desconocido: /{command}. Usa /help para ver los comandos disponibles."),
+                Ko => format!("알 수 없는 명령어입니다: /{command}. /help로 사용 가능한 명령어를 확인하세요."),
+                ZhHans => format!("未知命令：/{command}。使用 /help 查看可用命令。"),
+                ZhHant => format!("未知指令：/{command}。使用 /help 查看可用指令。"),
+            },
+            Self::InvalidLinkToken => match locale {
+                En => "❌ Invalid or expired link token. Please generate a new link from the web interface.",
+                Fr => "❌ Jeton de liaison invalide ou expiré. Générez un nouveau lien depuis l'interface web.",
+                Ja => "❌ リンクトークンが無効か期限切れです。Web 画面から新しいリンクを作成してください。",
+                Es => "❌ Token de enlace no válido o caducado. Genera un nuevo enlace desde la interfaz web.",
+                Ko => "❌ 연결 토큰이 잘못되었거나 만료되었습니다. 웹 화면에서 새 링크를 생성하세요.",
+                ZhHans => "❌ 链接令牌无效或已过期。请在网页界面重新生成链接。",
+                ZhHant => "❌ 連結權杖無效或已過期。請在網頁介面重新產生連結。",
+            }
+            .to_string(),
+            Self::LinkTokenExpired => match locale {
+                En => "❌ This link has expired. Please generate a new link from the web interface.",
+                Fr => "❌ Ce lien a expiré. Générez un nouveau lien depuis l'interface web.",
+                Ja => "❌ このリンクは期限切れです。Web 画面から新しいリンクを作成してください。",
+                Es => "❌ Este enlace ha caducado. Genera un nuevo enlace desde la interfaz web.",
+                Ko => "❌ 이 링크는 만료되었습니다. 웹 화면에서 새 링크를 생성하세요.",
+                ZhHans => "❌ 此链接已过期。请在网页界面重新生成链接。",
+                ZhHant => "❌ 此連結已過期。請在網頁介面重新產生連結。",
+            }
+            .to_string(),
+            Self::LinkCompleted { username } => {
+                let name = username.map(|u| format!(", @{u}")).unwrap_or_default();
+                match locale {
+                    En => format!(
+                        "✅ <b>Account linked successfully!</b>\n\nWelcome{name}! You will now receive notifications for task completions."
+                    ),
+                    Fr => format!(
+                        "✅ <b>Compte lié avec succès !</b>\n\nBienvenue{name} ! Vous recevrez désormais les notifications de fin de tâche."
+                    ),
+                    Ja => format!(
+                        "✅ <b>アカウントの連携が完了しました！</b>\n\nようこそ{name}！タスク完了の通知が届くようになります。"
+                    ),
+                    Es => format!(
+                        "✅ <b>¡Cuenta vinculada correctamente!</b>\n\n¡Bienvenido{name}! A partir de ahora recibirás notificaciones cuando se completen las tareas."
+                    ),
+                    Ko => format!(
+                        "✅ <b>계정이 연결되었습니다!</b>\n\n환영합니다{name}! 이제 작업 완료 알림을 받게 됩니다."
+                    ),
+                    ZhHans => format!("✅ <b>账号关联成功！</b>\n\n欢迎{name}！你将收到任务完成通知。"),
+                    ZhHant => format!("✅ <b>帳號連結成功！</b>\n\n歡迎{name}！你將收到任務完成通知。"),
+                }
+            }
+            Self::NotLinked => match locale {
+                En => "❌ This chat is not linked. Use the link from the web interface first.",
+                Fr => "❌ Ce chat n'est pas lié. Utilisez d'abord le lien de l'interface web.",
+                Ja => "❌ このチャットは連携されていません。先に Web 画面のリンクを使用してください。",
+                Es => "❌ Este chat no está vinculado. Usa primero el enlace de la interfaz web.",
+                Ko => "❌ 이 채팅은 연결되어 있지 않습니다. 먼저 웹 화면의 링크를 사용하세요.",
+                ZhHans => "❌ 此聊天尚未关联。请先使用网页界面中的链接。",
+                ZhHant => "❌ 此聊天尚未連結。請先使用網頁介面中的連結。",
+            }
+            .to_string(),
+            Self::ReadOnly => match locale {
+                En => "🔒 Your account is read-only, so it can only look at projects and tasks. Ask an admin to change your role.",
+                Fr => "🔒 Votre compte est en lecture seule : il peut seulement consulter les projets et les tâches. Demandez à un administrateur de changer votre rôle.",
+                Ja => "🔒 このアカウントは閲覧専用のため、プロジェクトとタスクの確認のみできます。ロールの変更は管理者に依頼してください。",
+                Es => "🔒 Tu cuenta es de solo lectura, así que solo puede consultar proyectos y tareas. Pide a un administrador que cambie tu rol.",
+                Ko => "🔒 이 계정은 읽기 전용이라 프로젝트와 작업을 조회만 할 수 있습니다. 역할 변경은 관리자에게 요청하세요.",
+                ZhHans => "🔒 你的账号是只读的，只能查看项目和任务。请联系管理员更改你的角色。",
+                ZhHant => "🔒 你的帳號是唯讀的，只能查看專案和任務。請聯絡管理員變更你的角色。",
+            }
+            .to_string(),
+            Self::NoActiveProject => match locale {
+                En => "No active project set. Use /project &lt;id&gt; to set one.",
+                Fr => "Aucun projet actif. Utilisez /project &lt;id&gt; pour en choisir un.",
+                Ja => "アクティブなプロジェクトがありません。/project &lt;id&gt; で設定してください。",
+                Es => "No hay un proyecto activo. Usa /project &lt;id&gt; para elegir uno.",
+                Ko => "활성 프로젝트가 없습니다. /project &lt;id&gt;로 설정하세요.",
+                ZhHans => "尚未设置当前项目。使用 /project &lt;id&gt; 进行设置。",
+                ZhHant => "尚未設定目前專案。使用 /project &lt;id&gt; 進行設定。",
+            }
+            .to_string(),
+            Self::ProjectNotFound(id) => match locale {
+                En => format!("❌ Project not found: <code>{id}</code>"),
+                Fr => format!("❌ Projet introuvable : <code>{id}</code>"),
+                Ja => format!("❌ プロジェクトが見つかりません: <code>{id}</code>"),
+                Es => format!("❌ Proyecto no encontrado: <code>{id}</code>"),
+                Ko => format!("❌ 프로젝트를 찾을 수 없습니다: <code>{id}</code>"),
+                ZhHans => format!("❌ 未找到项目：<code>{id}</code>"),
+                ZhHant => format!("❌ 找不到專案：<code>{id}</code>"),
+            },
+            Self::TaskNotFound(id) => match locale {
+                En => format!("❌ Task not found: <code>{id}</code>"),
+                Fr => format!("❌ Tâche introuvable : <code>{id}</code>"),
+                Ja => format!("❌ タスクが見つかりません: <code>{id}</code>"),
+                Es => format!("❌ Tarea no encontrada: <code>{id}</code>"),
+                Ko => format!("❌ 작업을 찾을 수 없습니다: <code>{id}</code>"),
+                ZhHans => format!("❌ 未找到任务：<code>{id}</code>"),
+                ZhHant => format!("❌ 找不到任務：<code>{id}</code>"),
+            },
+            Self::InvalidCommand(details) => match locale {
+                En => format!("❌ Invalid command: {details}"),
+                Fr => format!("❌ Commande invalide : {details}"),
+                Ja => format!("❌ 無効なコマンドです: {details}"),
+                Es => format!("❌ Comando no válido: {details}"),
+                Ko => format!("❌ 잘못된 명령어입니다: {details}"),
+                ZhHans => format!("❌ 无效命令：{details}"),
+                ZhHant => format!("❌ 無效指令：{details}"),
+            },
+            Self::Cancelled => match locale {
+                En => "Cancelled.",
+                Fr => "Annulé.",
+                Ja => "キャンセルしました。",
+                Es => "Cancelado.",
+                Ko => "취소했습니다.",
+                ZhHans => "已取消。",
+                ZhHant => "已取消。",
+            }
+            .to_string(),
+        }
+    }
+}
+
+/// Text the server generates outside bot replies: task notifications, the
+/// digest and error messages shown in the UI. Like [`BotMessage`], dynamic
+/// parts of Telegram texts must already be HTML-escaped.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServerMessage<'a> {
+    // Telegram task notifications
+    TaskStarted,
+    TaskInReview,
+    TaskFailed,
+    TaskCompleted,
+    TaskCancelled,
+    SummaryHeading,
+    ErrorOutputHeading,
+    RetryButton,
+    AckButton,
+    MergePrButton(i64),
+    NotificationBatch(usize),
+    NotificationDigest(usize),
+    FailureAcknowledged,
+    FailureAlreadyAcknowledged,
+    // Desktop notifications
+    TaskFinishedTitle(&'a str),
+    TaskSucceeded {
+        title: &'a str,
+        branch: &'a str,
+        executor: &'a str,
+    },
+    TaskExecutionFailed {
+        title: &'a str,
+        branch: &'a str,
+        executor: &'a str,
+    },
+    ApprovalNeededTitle(&'a str),
+    ApprovalNeeded {
+        tool: &'a str,
+    },
+    // Digest
+    DigestTitle,
+    DigestEmpty,
+    DigestCompleted,
+    DigestInProgress,
+    DigestStuck,
+    DigestUnacknowledged,
+    DigestMore(usize),
+    // API errors
+    UnsupportedImageType,
+    ImageTooLarge {
+        size_mb: f64,
+        max_mb: f64,
+    },
+    ImageNotFound,
+    ImageProcessingFailed,
+    RebaseInProgress,
+    UploadFailed,
+    MaintenanceMode,
+    SignInAgain,
+    RemoteTimeout,
+    RemoteUnavailable,
+    RemoteError,
+    RemoteInvalidToken,
+    CredentialsNotSaved,
+    RemoteNotFound,
+    RemoteExpired,
+    AccessDenied,
+    UnsupportedAuthProvider,
+    InvalidReturnUrl,
+    InvalidAuthChallenge,
+    AuthProviderError,
+    RemoteInternalError,
+    AuthenticationError(&'a str),
+    UnexpectedRemoteResponse,
+    InvalidRemoteUrl,
+}
+
+impl ServerMessage<'_> {
+    pub fn render(&self, locale: Locale) -> String {
+        use Locale::*;
+
+        match self {
+            Self::TaskStarted => match locale {
+                En => "🔄 <b>Task Started</b>",
+                Fr => "🔄 <b>Tâche démarrée</b>",
+                Ja => "🔄 <b>タスク開始</b>",
+                Es => "🔄 <b>Tarea iniciada</b>",
+                Ko => "🔄 <b>작업 시작</b>",
+                ZhHans => "🔄 <b>任务已开始</b>",
+                ZhHant => "🔄 <b>任務已開始</b>",
+            }
+            .to_string(),
+            Self::TaskInReview => match locale {
+                En => "👀 <b>Task In Review</b>",
+                Fr => "👀 <b>Tâche en revue</b>",
+                Ja => "👀 <b>タスクレビュー中</b>",
+                Es => "👀 <b>Tarea en revisión</b>",
+                Ko => "👀 <b>작업 검토 중</b>",
+                ZhHans => "👀 <b>任务待审查</b>",
+                ZhHant => "👀 <b>任務待審查</b>",
+            }
+            .to_string(),
+            Self::TaskFailed => match locale {
+                En => "❌ <b>Task Failed</b>",
+                Fr => "❌ <b>Échec de la tâche</b>",
+                Ja => "❌ <b>タスク失敗</b>",
+                Es => "❌ <b>La tarea falló</b>",
+                Ko => "❌ <b>작업 실패</b>",
+                ZhHans => "❌ <b>任务失败</b>",
+                ZhHant => "❌ <b>任務失敗</b>",
+            }
+            .to_string(),
+            Self::TaskCompleted => match locale {
+                En => "✅ <b>Task Completed</b>",
+                Fr => "✅ <b>Tâche terminée</b>",
+                Ja => "✅ <b>タスク完了</b>",
+                Es => "✅ <b>Tarea completada</b>",
+                Ko => "✅ <b>작업 완료</b>",
+                ZhHans => "✅ <b>任务已完成</b>",
+                ZhHant => "✅ <b>任務已完成</b>",
+            }
+            .to_string(),
+            Self::TaskCancelled => match locale {
+                En => "🚫 <b>Task Cancelled</b>",
+                Fr => "🚫 <b>Tâche annulée</b>",
+                Ja => "🚫 <b>タスクキャンセル</b>",
+                Es => "🚫 <b>Tarea cancelada</b>",
+                Ko => "🚫 <b>작업 취소</b>",
+                ZhHans => "🚫 <b>任务已取消</b>",
+                ZhHant => "🚫 <b>任務已取消</b>",
+            }
+            .to_string(),
+            Self::SummaryHeading => match locale {
+                En => "Summary:",
+                Fr => "Résumé :",
+                Ja => "概要:",
+                Es => "Resumen:",
+                Ko => "요약:",
+                ZhHans => "摘要：",
+                ZhHant => "摘要：",
+            }
+            .to_string(),
+            Self::ErrorOutputHeading => match locale {
+                En => "Error output:",
+                Fr => "Sortie d'erreur :",
+                Ja => "エラー出力:",
+                Es => "Salida de error:",
+                Ko => "오류 출력:",
+                ZhHans => "错误输出：",
+                ZhHant => "錯誤輸出：",
+            }
+            .to_string(),
+            Self::RetryButton => match locale {
+                En => "🔁 Retry",
+                Fr => "🔁 Réessayer",
+                Ja => "🔁 再試行",
+                Es => "🔁 Reintentar",
+                Ko => "🔁 다시 시도",
+                ZhHans => "🔁 重试",
+                ZhHant => "🔁 重試",
+            }
+            .to_string(),
+            Self::AckButton => match locale {
+                En => "✅ Ack",
+                Fr => "✅ Pris en compte",
+                Ja => "✅ 確認済み",
+                Es => "✅ Visto",
+                Ko => "✅ 확인",
+                ZhHans => "✅ 已确认",
+                ZhHant => "✅ 已確認",
+            }
+            .to_string(),
+            Self::MergePrButton(number) => match locale {
+                En => format!("🔀 Merge PR #{number}"),
+                Fr => format!("🔀 Fusionner la PR #{number}"),
+                Ja => format!("🔀 PR #{number} をマージ"),
+                Es => format!("🔀 Fusionar PR #{number}"),
+                Ko => format!("🔀 PR #{number} 병합"),
+                ZhHans => format!("🔀 合并 PR #{number}"),
+                ZhHant => format!("🔀 合併 PR #{number}"),
+            },
+            Self::NotificationBatch(count) => match locale {
+                En => format!("🔔 <b>{count} notifications</b>"),
+                Fr => format!("🔔 <b>{count} notifications</b>"),
+                Ja => format!("🔔 <b>{count} 件の通知</b>"),
+                Es => format!("🔔 <b>{count} notificaciones</b>"),
+                Ko => format!("🔔 <b>알림 {count}개</b>"),
+                ZhHans => format!("🔔 <b>{count} 条通知</b>"),
+                ZhHant => format!("🔔 <b>{count} 則通知</b>"),
+            },
+            Self::NotificationDigest(count) => match locale {
+                En => format!("🗞 <b>Digest: {count} notifications</b>"),
+                Fr => format!("🗞 <b>Résumé : {count} notifications</b>"),
+                Ja => format!("🗞 <b>ダイジェスト: {count} 件の通知</b>"),
+                Es => format!("🗞 <b>Resumen: {count} notificaciones</b>"),
+                Ko => format!("🗞 <b>요약: 알림 {count}개</b>"),
+                ZhHans => format!("🗞 <b>摘要：{count} 条通知</b>"),
+                ZhHant => format!("🗞 <b>摘要：{count} 則通知</b>"),
+            },
+            Self::FailureAcknowledged => match locale {
+                En => "✅ Failure acknowledged.",
+                Fr => "✅ Échec pris en compte.",
+                Ja => "✅ 失敗を確認しました。",
+                Es => "✅ Fallo confirmado.",
+                Ko => "✅ 실패를 확인했습니다.",
+                ZhHans => "✅ 已确认失败。",
+                ZhHant => "✅ 已確認失敗。",
+            }
+            .to_string(),
+            Self::FailureAlreadyAcknowledged => match locale {
+                En => "This failure was already acknowledged.",
+                Fr => "Cet échec a déjà été pris en compte.",
+                Ja => "この失敗はすでに確認済みです。",
+                Es => "Este fallo ya se había confirmado.",
+                Ko => "이미 확인된 실패입니다.",
+                ZhHans => "此失败已被确认。",
+                ZhHant => "此失敗已被確認。",
+            }
+            .to_string(),
+            Self::TaskFinishedTitle(title) => match locale {
+                En => format!("Task Complete: {title}"),
+                Fr => format!("Tâche terminée : {title}"),
+                Ja => format!("タスク完了: {title}"),
+                Es => format!("Tarea terminada: {title}"),
+                Ko => format!("작업 완료: {title}"),
+                ZhHans => format!("任务完成：{title}"),
+                ZhHant => format!("任務完成：{title}"),
+            },
+            Self::TaskSucceeded {
+                title,
+                branch,
+                executor,
+            } => match locale {
+                En => format!(
+                    "✅ '{title}' completed successfully\nBranch: {branch}\nExecutor: {executor}"
+                ),
+                Fr => format!(
+                    "✅ « {title} » s'est terminée avec succès\nBranche : {branch}\nExécuteur : {executor}"
+                ),
+                Ja => format!("✅ 「{title}」が正常に完了しました\nブランチ: {branch}\nエグゼキューター: {executor}"),
+                Es => format!(
+                    "✅ «{title}» se completó correctamente\nRama: {branch}\nEjecutor: {executor}"
+                ),
+                Ko => format!("✅ '{title}' 작업이 완료되었습니다\n브랜치: {branch}\n실행기: {executor}"),
+                ZhHans => format!("✅ “{title}”已成功完成\n分支：{branch}\n执行器：{executor}"),
+                ZhHant => format!("✅ 「{title}」已成功完成\n分支：{branch}\n執行器：{executor}"),
+            },
+            Self::TaskExecutionFailed {
+                title,
+                branch,
+                executor,
+            } => match locale {
+                En => format!("❌ '{title}' execution failed\nBranch: {branch}\nExecutor: {executor}"),
+                Fr => format!(
+                    "❌ L'exécution de « {title} » a échoué\nBranche : {branch}\nExécuteur : {executor}"
+                ),
+                Ja => format!("❌ 「{title}」の実行に失敗しました\nブランチ: {branch}\nエグゼキューター: {executor}"),
+                Es => format!(
+                    "❌ La ejecución de «{title}» falló\nRama: {branch}\nEjecutor: {executor}"
+                ),
+                Ko => format!("❌ '{title}' 실행에 실패했습니다\n브랜치: {branch}\n실행기: {executor}"),
+                ZhHans => format!("❌ “{title}”执行失败\n分支：{branch}\n执行器：{executor}"),
+                ZhHant => format!("❌ 「{title}」執行失敗\n分支：{branch}\n執行器：{executor}"),
+            },
+            Self::ApprovalNeededTitle(task) => match locale {
+                En => format!("Approval Needed: {task}"),
+                Fr => format!("Approbation requise : {task}"),
+                Ja => format!("承認が必要です: {task}"),
+                Es => format!("Se necesita aprobación: {task}"),
+                Ko => format!("승인 필요: {task}"),
+                ZhHans => format!("需要审批：{task}"),
+                ZhHant => format!("需要核准：{task}"),
+            },
+            Self::ApprovalNeeded { tool } => match locale {
+                En => format!("Tool '{tool}' requires approval"),
+                Fr => format!("L'outil « {tool} » nécessite une approbation"),
+                Ja => format!("ツール「{tool}」には承認が必要です"),
+                Es => format!("La herramienta «{tool}» requiere aprobación"),
+                Ko => format!("'{tool}' 도구를 사용하려면 승인이 필요합니다"),
+                ZhHans => format!("工具“{tool}”需要审批"),
+                ZhHant => format!("工具「{tool}」需要核准"),
+            },
+            Self::DigestTitle => match locale {
+                En => "☀️ <b>Daily Digest</b>",
+                Fr => "☀️ <b>Résumé quotidien</b>",
+                Ja => "☀️ <b>デイリーダイジェスト</b>",
+                Es => "☀️ <b>Resumen diario</b>",
+                Ko => "☀️ <b>일일 요약</b>",
+                ZhHans => "☀️ <b>每日摘要</b>",
+                ZhHant => "☀️ <b>每日摘要</b>",
+            }
+            .to_string(),
+            Self::DigestEmpty => match locale {
+                En => "Nothing happened in the last 24 hours.",
+                Fr => "Rien ne s'est passé au cours des dernières 24 heures.",
+                Ja => "過去 24 時間の動きはありません。",
+                Es => "No ha pasado nada en las últimas 24 horas.",
+                Ko => "지난 24시간 동안 변경 사항이 없습니다.",
+                ZhHans => "过去 24 小时内没有任何动态。",
+                ZhHant => "過去 24 小時內沒有任何動態。",
+            }
+            .to_string(),
+            Self::DigestCompleted => match locale {
+                En => "✅ Completed",
+                Fr => "✅ Terminées",
+                Ja => "✅ 完了",
+                Es => "✅ Completadas",
+                Ko => "✅ 완료",
+                ZhHans => "✅ 已完成",
+                ZhHant => "✅ 已完成",
+            }
+            .to_string(),
+            Self::DigestInProgress => match locale {
+                En => "🔄 In progress",
+                Fr => "🔄 En cours",
+                Ja => "🔄 進行中",
+                Es => "🔄 En curso",
+                Ko => "🔄 진행 중",
+                ZhHans => "🔄 进行中",
+                ZhHant => "🔄 進行中",
+            }
+            .to_string(),
+            Self::DigestStuck => match locale {
+                En => "⚠️ Stuck",
+                Fr => "⚠️ Bloquées",
+                Ja => "⚠️ 停滞中",
+                Es => "⚠️ Atascadas",
+                Ko => "⚠️ 정체됨",
+                ZhHans => "⚠️ 停滞",
+                ZhHant => "⚠️ 停滯",
+            }
+            .to_string(),
+            Self::DigestUnacknowledged => match locale {
+                En => "❗ Unacknowledged failures",
+                Fr => "❗ Échecs non pris en compte",
+                Ja => "❗ 未確認の失敗",
+                Es => "❗ Fallos sin confirmar",
+                Ko => "❗ 확인되지 않은 실패",
+                ZhHans => "❗ 未确认的失败",
+                ZhHant => "❗ 未確認的失敗",
+            }
+            .to_string(),
+            Self::DigestMore(count) => match locale {
+                En => format!("… and {count} more"),
+                Fr => format!("… et {count} de plus"),
+                Ja => format!("… ほか {count} 件"),
+                Es => format!("… y {count} más"),
+                Ko => format!("… 외 {count}개"),
+                ZhHans => format!("… 还有 {count} 项"),
+                ZhHant => format!("… 還有 {count} 項"),
+            },
+            Self::UnsupportedImageType => match locale {
+                En => "This file type is not supported. Please upload an image file (PNG, JPG, GIF, WebP, or BMP).",
+                Fr => "Ce type de fichier n'est pas pris en charge. Importez une image (PNG, JPG, GIF, WebP ou BMP).",
+                Ja => "このファイル形式には対応していません。画像ファイル (PNG、JPG、GIF、WebP、BMP) をアップロードしてください。",
+                Es => "Este tipo de archivo no es compatible. Sube una imagen (PNG, JPG, GIF, WebP o BMP).",
+                Ko => "지원하지 않는 파일 형식입니다. 이미지 파일(PNG, JPG, GIF, WebP, BMP)을 업로드하세요.",
+                ZhHans => "不支持此文件类型。请上传图片文件（PNG、JPG、GIF、WebP 或 BMP）。",
+                ZhHant => "不支援此檔案類型。請上傳圖片檔案（PNG、JPG、GIF、WebP 或 BMP）。",
+            }
+            .to_string(),
+            Self::ImageTooLarge { size_mb, max_mb } => match locale {
+                En => format!(
+                    "This image is too large ({size_mb:.1} MB). Maximum file size is {max_mb:.1} MB."
+                ),
+                Fr => format!(
+                    "Cette image est trop volumineuse ({size_mb:.1} Mo). La taille maximale est de {max_mb:.1} Mo."
+                ),
+                Ja => format!(
+                    "画像が大きすぎます ({size_mb:.1} MB)。最大ファイルサイズは {max_mb:.1} MB です。"
+                ),
+                Es => format!(
+                    "Esta imagen es demasiado grande ({size_mb:.1} MB). El tamaño máximo es {max_mb:.1} MB."
+                ),
+                Ko => format!(
+                    "이미지가 너무 큽니다({size_mb:.1}MB). 최대 파일 크기는 {max_mb:.1}MB입니다."
+                ),
+                ZhHans => format!("图片过大（{size_mb:.1} MB）。文件大小上限为 {max_mb:.1} MB。"),
+                ZhHant => format!("圖片過大（{size_mb:.1} MB）。檔案大小上限為 {max_mb:.1} MB。"),
+            },
+            Self::ImageNotFound => match locale {
+                En => "Image not found.",
+                Fr => "Image introuvable.",
+                Ja => "画像が見つかりません。",
+                Es => "Imagen no encontrada.",
+                Ko => "이미지를 찾을 수 없습니다.",
+                ZhHans => "未找到图片。",
+                ZhHant => "找不到圖片。",
+            }
+            .to_string(),
+            Self::ImageProcessingFailed => match locale {
+                En => "Failed to process image. Please try again.",
+                Fr => "Impossible de traiter l'image. Veuillez réessayer.",
+                Ja => "画像を処理できませんでした。もう一度お試しください。",
+                Es => "No se pudo procesar la imagen. Inténtalo de nuevo.",
+                Ko => "이미지를 처리하지 못했습니다. 다시 시도하세요.",
+                ZhHans => "图片处理失败，请重试。",
+                ZhHant => "圖片處理失敗，請重試。",
+            }
+            .to_string(),
+            Self::RebaseInProgress => match locale {
+                En => "A rebase is already in progress. Resolve conflicts or abort the rebase, then retry.",
+                Fr => "Un rebase est déjà en cours. Résolvez les conflits ou annulez le rebase, puis réessayez.",
+                Ja => "リベースがすでに進行中です。競合を解決するかリベースを中止してから、再試行してください。",
+                Es => "Ya hay un rebase en curso. Resuelve los conflictos o cancela el rebase y vuelve a intentarlo.",
+                Ko => "이미 리베이스가 진행 중입니다. 충돌을 해결하거나 리베이스를 중단한 후 다시 시도하세요.",
+                ZhHans => "变基已在进行中。请解决冲突或中止变基后重试。",
+                ZhHant => "重定基底已在進行中。請解決衝突或中止重定基底後重試。",
+            }
+            .to_string(),
+            Self::UploadFailed => match locale {
+                En => "Failed to upload file. Please ensure the file is valid and try again.",
+                Fr => "Échec de l'envoi du fichier. Vérifiez que le fichier est valide et réessayez.",
+                Ja => "ファイルをアップロードできませんでした。ファイルが有効か確認して、もう一度お試しください。",
+                Es => "No se pudo subir el archivo. Comprueba que el archivo es válido e inténtalo de nuevo.",
+                Ko => "파일을 업로드하지 못했습니다. 파일이 올바른지 확인한 후 다시 시도하세요.",
+                ZhHans => "文件上传失败。请确认文件有效后重试。",
+                ZhHant => "檔案上傳失敗。請確認檔案有效後重試。",
+            }
+            .to_string(),
+            Self::MaintenanceMode => match locale {
+                En => "🛠 Maintenance mode is on: changes are paused, but you can still look around.",
+                Fr => "🛠 Le mode maintenance est activé : les modifications sont suspendues, mais la consultation reste possible.",
+                Ja => "🛠 メンテナンスモード中です。変更は一時停止していますが、閲覧はできます。",
+                Es => "🛠 El modo de mantenimiento está activado: los cambios están en pausa, pero puedes seguir consultando.",
+                Ko => "🛠 유지 관리 모드입니다. 변경은 일시 중지되었지만 조회는 계속할 수 있습니다.",
+                ZhHans => "🛠 维护模式已开启：更改已暂停，但仍可查看。",
+                ZhHant => "🛠 維護模式已開啟：變更已暫停，但仍可檢視。",
+            }
+            .to_string(),
+            Self::SignInAgain => match locale {
+                En => "Unauthorized. Please sign in again.",
+                Fr => "Non autorisé. Veuillez vous reconnecter.",
+                Ja => "認証されていません。もう一度サインインしてください。",
+                Es => "No autorizado. Vuelve a iniciar sesión.",
+                Ko => "인증되지 않았습니다. 다시 로그인하세요.",
+                ZhHans => "未授权。请重新登录。",
+                ZhHant => "未授權。請重新登入。",
+            }
+            .to_string(),
+            Self::RemoteTimeout => match locale {
+                En => "Remote service timeout. Please try again.",
+                Fr => "Le service distant ne répond pas. Veuillez réessayer.",
+                Ja => "リモートサービスがタイムアウトしました。もう一度お試しください。",
+                Es => "El servicio remoto no respondió a tiempo. Inténtalo de nuevo.",
+                Ko => "원격 서비스 응답 시간이 초과되었습니다. 다시 시도하세요.",
+                ZhHans => "远程服务超时，请重试。",
+                ZhHant => "遠端服務逾時，請重試。",
+            }
+            .to_string(),
+            Self::RemoteUnavailable => match locale {
+                En => "Remote service unavailable. Please try again.",
+                Fr => "Service distant indisponible. Veuillez réessayer.",
+                Ja => "リモートサービスを利用できません。もう一度お試しください。",
+                Es => "El servicio remoto no está disponible. Inténtalo de nuevo.",
+                Ko => "원격 서비스를 사용할 수 없습니다. 다시 시도하세요.",
+                ZhHans => "远程服务不可用，请重试。",
+                ZhHant => "遠端服務無法使用，請重試。",
+            }
+            .to_string(),
+            Self::RemoteError => match locale {
+                En => "Remote service error. Please try again.",
+                Fr => "Erreur du service distant. Veuillez réessayer.",
+                Ja => "リモートサービスでエラーが発生しました。もう一度お試しください。",
+                Es => "Error del servicio remoto. Inténtalo de nuevo.",
+                Ko => "원격 서비스 오류입니다. 다시 시도하세요.",
+                ZhHans => "远程服务出错，请重试。",
+                ZhHant => "遠端服務發生錯誤，請重試。",
+            }
+            .to_string(),
+            Self::RemoteInvalidToken => match locale {
+                En => "Remote service returned an invalid access token. Please sign in again.",
+                Fr => "Le service distant a renvoyé un jeton d'accès invalide. Veuillez vous reconnecter.",
+                Ja => "リモートサービスから無効なアクセストークンが返されました。もう一度サインインしてください。",
+                Es => "El servicio remoto devolvió un token de acceso no válido. Vuelve a iniciar sesión.",
+                Ko => "원격 서비스가 잘못된 액세스 토큰을 반환했습니다. 다시 로그인하세요.",
+                ZhHans => "远程服务返回了无效的访问令牌。请重新登录。",
+                ZhHant => "遠端服務傳回了無效的存取權杖。請重新登入。",
+            }
+            .to_string(),
+            Self::CredentialsNotSaved => match locale {
+                En => "Failed to persist credentials locally. Please retry.",
+                Fr => "Impossible d'enregistrer les identifiants localement. Veuillez réessayer.",
+                Ja => "認証情報をローカルに保存できませんでした。もう一度お試しください。",
+                Es => "No se pudieron guardar las credenciales localmente. Inténtalo de nuevo.",
+                Ko => "자격 증명을 로컬에 저장하지 못했습니다. 다시 시도하세요.",
+                ZhHans => "无法在本地保存凭据，请重试。",
+                ZhHant => "無法在本機儲存憑證，請重試。",
+            }
+            .to_string(),
+            Self::RemoteNotFound => match locale {
+                En => "The requested resource was not found.",
+                Fr => "La ressource demandée est introuvable.",
+                Ja => "要求されたリソースが見つかりません。",
+                Es => "No se encontró el recurso solicitado.",
+                Ko => "요청한 리소스를 찾을 수 없습니다.",
+                ZhHans => "未找到请求的资源。",
+                ZhHant => "找不到要求的資源。",
+            }
+            .to_string(),
+            Self::RemoteExpired => match locale {
+                En => "The link or token has expired.",
+                Fr => "Le lien ou le jeton a expiré.",
+                Ja => "リンクまたはトークンの有効期限が切れています。",
+                Es => "El enlace o el token ha caducado.",
+                Ko => "링크 또는 토큰이 만료되었습니다.",
+                ZhHans => "链接或令牌已过期。",
+                ZhHant => "連結或權杖已過期。",
+            }
+            .to_string(),
+            Self::AccessDenied => match locale {
+                En => "Access denied.",
+                Fr => "Accès refusé.",
+                Ja => "アクセスが拒否されました。",
+                Es => "Acceso denegado.",
+                Ko => "액세스가 거부되었습니다.",
+                ZhHans => "访问被拒绝。",
+                ZhHant => "存取遭拒。",
+            }
+            .to_string(),
+            Self::UnsupportedAuthProvider => match locale {
+                En => "Unsupported authentication provider.",
+                Fr => "Fournisseur d'authentification non pris en charge.",
+                Ja => "対応していない認証プロバイダーです。",
+                Es => "Proveedor de autenticación no compatible.",
+                Ko => "지원하지 않는 인증 공급자입니다.",
+                ZhHans => "不支持的身份验证提供方。",
+                ZhHant => "不支援的身分驗證提供者。",
+            }
+            .to_string(),
+            Self::InvalidReturnUrl => match locale {
+                En => "Invalid return URL.",
+                Fr => "URL de retour invalide.",
+                Ja => "戻り先 URL が無効です。",
+                Es => "URL de retorno no válida.",
+                Ko => "반환 URL이 잘못되었습니다.",
+                ZhHans => "返回 URL 无效。",
+                ZhHant => "返回 URL 無效。",
+            }
+            .to_string(),
+            Self::InvalidAuthChallenge => match locale {
+                En => "Invalid authentication challenge.",
+                Fr => "Défi d'authentification invalide.",
+                Ja => "認証チャレンジが無効です。",
+                Es => "Desafío de autenticación no válido.",
+                Ko => "인증 챌린지가 잘못되었습니다.",
+                ZhHans => "身份验证质询无效。",
+                ZhHant => "身分驗證挑戰無效。",
+            }
+            .to_string(),
+            Self::AuthProviderError => match locale {
+                En => "Authentication provider error. Please try again.",
+                Fr => "Erreur du fournisseur d'authentification. Veuillez réessayer.",
+                Ja => "認証プロバイダーでエラーが発生しました。もう一度お試しください。",
+                Es => "Error del proveedor de autenticación. Inténtalo de nuevo.",
+                Ko => "인증 공급자 오류입니다. 다시 시도하세요.",
+                ZhHans => "身份验证提供方出错，请重试。",
+                ZhHant => "身分驗證提供者發生錯誤，請重試。",
+            }
+            .to_string(),
+            Self::RemoteInternalError => match locale {
+                En => "Internal remote service error. Please try again.",
+                Fr => "Erreur interne du service distant. Veuillez réessayer.",
+                Ja => "リモートサービスで内部エラーが発生しました。もう一度お試しください。",
+                Es => "Error interno del servicio remoto. Inténtalo de nuevo.",
+                Ko => "원격 서비스 내부 오류입니다. 다시 시도하세요.",
+                ZhHans => "远程服务内部错误，请重试。",
+                ZhHant => "遠端服務內部錯誤，請重試。",
+            }
+            .to_string(),
+            Self::AuthenticationError(details) => match locale {
+                En => format!("Authentication error: {details}"),
+                Fr => format!("Erreur d'authentification : {details}"),
+                Ja => format!("認証エラー: {details}"),
+                Es => format!("Error de autenticación: {details}"),
+                Ko => format!("인증 오류: {details}"),
+                ZhHans => format!("身份验证错误：{details}"),
+                ZhHant => format!("身分驗證錯誤：{details}"),
+            },
+            Self::UnexpectedRemoteResponse => match locale {
+                En => "Unexpected response from remote service.",
+                Fr => "Réponse inattendue du service distant.",
+                Ja => "リモートサービスから予期しない応答がありました。",
+                Es => "Respuesta inesperada del servicio remoto.",
+                Ko => "원격 서비스에서 예상치 못한 응답을 받았습니다.",
+                ZhHans => "远程服务返回了意外的响应。",
+                ZhHant => "遠端服務傳回了非預期的回應。",
+            }
+            .to_string(),
+            Self::InvalidRemoteUrl => match locale {
+                En => "Remote service URL is invalid.",
+                Fr => "L'URL du service distant est invalide.",
+                Ja => "リモートサービスの URL が無効です。",
+                Es => "La URL del servicio remoto no es válida.",
+                Ko => "원격 서비스 URL이 잘못되었습니다.",
+                ZhHans => "远程服务 URL 无效。",
+                ZhHant => "遠端服務 URL 無效。",
+            }
+            .to_string(),
+        }
+    }
+}
+
+fn welcome(locale: Locale) -> &'static str {
+    match locale {
+        Locale::En => {
+            r#"👋 <b>Welcome to VibeKanban Bot!</b>
+
+I can help you manage your tasks and receive notifications.
+
+<b>Available commands:</b>
+/help - Show all commands
+/projects - List your projects
+/project &lt;id&gt; - Set active project
+/tasks - List tasks in active project
+/task &lt;id&gt; - Get task details
+/newtask &lt;title&gt; - Create a new task
+/message &lt;task_id&gt; &lt;text&gt; - Send message to a task
+
+To link your account, use the link from the web interface."#
+        }
+        Locale::Fr => {
+            r#"👋 <b>Bienvenue sur le bot VibeKanban !</b>
+
+Je vous aide à gérer vos tâches et à recevoir des notifications.
+
+<b>Commandes disponibles :</b>
+/help - Afficher toutes les commandes
+/projects - Lister vos projets
+/project &lt;id&gt; - Définir le projet actif
+/tasks - Lister les tâches du projet actif
+/task &lt;id&gt; - Détails d'une tâche
+/newtask &lt;titre&gt; - Créer une tâche
+/message &lt;task_id&gt; &lt;texte&gt; - Envoyer un message à une tâche
+
+Pour lier votre compte, utilisez le lien de l'interface web."#
+        }
+        Locale::Ja => {
+            r#"👋 <b>VibeKanban Bot へようこそ！</b>
+
+タスクの管理や通知の受信をお手伝いします。
+
+<b>使用できるコマンド:</b>
+/help - すべてのコマンドを表示
+/projects - プロジェクト一覧
+/project &lt;id&gt; - アクティブなプロジェクトを設定
+/tasks - アクティブなプロジェクトのタスク一覧
+/task &lt;id&gt; - タスクの詳細
+/newtask &lt;タイトル&gt; - タスクを作成
+/message &lt;task_id&gt; &lt;テキスト&gt; - タスクにメッセージを送信
+
+アカウントを連携するには、Web 画面のリンクを使用してください。"#
+        }
+        Locale::Es => {
+            r#"👋 <b>¡Bienvenido al bot de VibeKanban!</b>
+
+Te ayudo a gestionar tus tareas y a recibir notificaciones.
+
+<b>Comandos disponibles:</b>
+/help - Mostrar todos los comandos
+/projects - Listar tus proyectos
+/project &lt;id&gt; - Establecer el proyecto activo
+/tasks - Listar las tareas del proyecto activo
+/task &lt;id&gt; - Ver detalles de una tarea
+/newtask &lt;título&gt; - Crear una tarea
+/message &lt;task_id&gt; &lt;texto&gt; - Enviar un mensaje a una tarea
+
+Para vincular tu cuenta, usa el enlace de la interfaz web."#
+        }
+        Locale::Ko => {
+            r#"👋 <b>VibeKanban 봇에 오신 것을 환영합니다!</b>
+
+작업 관리와 알림 수신을 도와드립니다.
+
+<b>사용 가능한 명령어:</b>
+/help - 전체 명령어 보기
+/projects - 프로젝트 목록
+/project &lt;id&gt; - 활성 프로젝트 설정
+/tasks - 활성 프로젝트의 작업 목록
+/task &lt;id&gt; - 작업 상세 정보
+/newtask &lt;제목&gt; - 새 작업 만들기
+/message &lt;task_id&gt; &lt;내용&gt; - 작업에 메시지 보내기
+
+계정을 연결하려면 웹 화면의 링크를 사용하세요."#
+        }
+        Locale::ZhHans => {
+            r#"👋 <b>欢迎使用 VibeKanban 机器人！</b>
+
+我可以帮你管理任务并接收通知。
+
+<b>可用命令：</b>
+/help - 显示所有命令
+/projects - 列出项目
+/project &lt;id&gt; - 设置当前项目
+/tasks - 列出当前项目的任务
+/task &lt;id&gt; - 查看任务详情
+/newtask &lt;标题&gt; - 新建任务
+/message &lt;task_id&gt; &lt;内容&gt; - 向任务发送消息
+
+如需关联账号，请使用网页界面中的链接。"#
+        }
+        Locale::ZhHant => {
+            r#"👋 <b>歡迎使用 VibeKanban 機器人！</b>
+
+我可以幫你管理任務並接收通知。
+
+<b>可用指令：</b>
+/help - 顯示所有指令
+/projects - 列出專案
+/project &lt;id&gt; - 設定目前專案
+/tasks - 列出目前專案的任務
+/task &lt;id&gt; - 查看任務詳情
+/newtask &lt;標題&gt; - 新增任務
+/message &lt;task_id&gt; &lt;內容&gt; - 傳送訊息給任務
+
+如需連結帳號，請使用網頁介面中的連結。"#
+        }
+    }
+}
+
+fn help(locale: Locale) -> &'static str {
+    match locale {
+        Locale::En => {
+            r#"<b>VibeKanban Bot Commands</b>
+
+<b>Account:</b>
+/start - Welcome message & account linking
+/status - Server uptime, running attempts and executor health
+/whoami - Your chat and account details, settings and bot diagnostics
+/report - Digest of the last 24 hours
+/menu - Show quick-action buttons (/menu off hides them)
+/role [&lt;user&gt; admin|read_only] - List account roles, or change one (admins only)
+/setup [test] - Check the bot setup step by step, or send a test notification (admins only)
+/digest [immediate|hourly|daily] - Get your notifications as they happen, or in an hourly or daily digest
+
+<b>Projects:</b>
+/projects - List all projects
+/project &lt;id&gt; - Set active project for subsequent commands
+  In a forum topic, dedicates the topic to the project
+/shortcut &lt;name&gt; &lt;project_id&gt; - Save a command that switches this chat to the project
+  /shortcut &lt;name&gt; removes it, /shortcut lists them
+
+<b>Tasks:</b>
+/tasks - List tasks in active project
+/tasks &lt;project_id&gt; - List tasks in specific project
+/workload [project_id] - Open tasks and running attempts per assignee
+/search &lt;query&gt; - Search tasks in active project (or all projects)
+  Or type @botname &lt;query&gt; in any chat to share a task
+/task &lt;id&gt; - Get task details
+/logs &lt;task_id&gt; [lines] - Latest agent output for a task
+/diff &lt;task_id&gt; - Changes made by the latest attempt
+/merge &lt;task_id&gt; - Merge the task's open pull request
+/stop &lt;task_id&gt; - Stop the task's running attempt
+/retry &lt;task_id&gt; - Start a new attempt after a failed one
+/assign &lt;task_id&gt; &lt;user&gt; - Assign a task to a linked account (me, @username or user id)
+/unassign &lt;task_id&gt; &lt;user&gt; - Remove an assignee or watcher
+/watch &lt;task_id&gt; - Get a task's notifications without being assigned
+/newtask &lt;title&gt; - Create task in active project
+/newtask &lt;project_id&gt; &lt;title&gt; - Create task in specific project
+  Add #prompt-name to use a saved prompt as the description
+  Lines after the first become the task description
+
+<b>Messages:</b>
+/message &lt;task_id&gt; &lt;text&gt; - Send/queue a message for a task
+Reply to a task notification to queue your reply as a message for that task
+
+<b>Notifications:</b>
+/mute &lt;duration&gt; - Pause notifications, e.g. 30m, 2h or 1d
+/unmute - Resume notifications
+
+<b>Attachments:</b>
+Send a photo or image file with the task ID as the caption to attach it
+
+<b>Notes:</b>
+- Task and project IDs are UUIDs (can use short prefix)
+- Set an active project with /project to avoid typing IDs"#
+        }
+        Locale::Fr => {
+            r#"<b>Commandes du bot VibeKanban</b>
+
+<b>Compte :</b>
+/start - Message d'accueil et liaison du compte
+/status - Disponibilité du serveur, tentatives en cours et état de l'exécuteur
+/whoami - Détails de votre chat et de votre compte, paramètres et diagnostics du bot
+/report - Résumé des dernières 24 heures
+/menu - Afficher les boutons d'actions rapides (/menu off pour les masquer)
+/role [&lt;utilisateur&gt; admin|read_only] - Lister les rôles des comptes ou en changer un (administrateurs uniquement)
+/setup [test] - Vérifier la configuration du bot étape par étape, ou envoyer une notification de test (administrateurs uniquement)
+/digest [immediate|hourly|daily] - Recevoir vos notifications au fil de l'eau, ou dans un résumé horaire ou quotidien
+
+<b>Projets :</b>
+/projects - Lister tous les projets
+/project &lt;id&gt; - Définir le projet actif pour les commandes suivantes
+  Dans un sujet de forum, consacre le sujet au projet
+/shortcut &lt;nom&gt; &lt;project_id&gt; - Enregistrer une commande qui bascule ce chat sur le projet
+  /shortcut &lt;nom&gt; le supprime, /shortcut les liste
+
+<b>Tâches :</b>
+/tasks - Lister les tâches du projet actif
+/tasks &lt;project_id&gt; - Lister les tâches d'un projet
+/workload [project_id] - Tâches ouvertes et tentatives en cours par responsable
+/search &lt;requête&gt; - Rechercher dans le projet actif (ou tous les projets)
+  Ou tapez @nom_du_bot &lt;requête&gt; dans n'importe quel chat pour partager une tâche
+/task &lt;id&gt; - Détails d'une tâche
+/logs &lt;task_id&gt; [lignes] - Dernière sortie de l'agent pour une tâche
+/diff &lt;task_id&gt; - Modifications de la dernière tentative
+/merge &lt;task_id&gt; - Fusionner la pull request ouverte de la tâche
+/stop &lt;task_id&gt; - Arrêter la tentative en cours de la tâche
+/retry &lt;task_id&gt; - Relancer une nouvelle tentative après un échec
+/assign &lt;task_id&gt; &lt;utilisateur&gt; - Assigner une tâche à un compte lié (me, @pseudo ou id)
+/unassign &lt;task_id&gt; &lt;utilisateur&gt; - Retirer un assigné ou un observateur
+/watch &lt;task_id&gt; - Recevoir les notifications d'une tâche sans y être assigné
+/newtask &lt;titre&gt; - Créer une tâche dans le projet actif
+/newtask &lt;project_id&gt; &lt;titre&gt; - Créer une tâche dans un projet
+  Ajoutez #nom-du-prompt pour utiliser un prompt enregistré comme description
+  Les lignes après la première deviennent la description
+
+<b>Messages :</b>
+/message &lt;task_id&gt; &lt;texte&gt; - Envoyer ou mettre en file un message pour une tâche
+Répondez à une notification de tâche pour mettre votre réponse en file pour cette tâche
+
+<b>Notifications :</b>
+/mute &lt;durée&gt; - Suspendre les notifications, par ex. 30m, 2h ou 1d
+/unmute - Reprendre les notifications
+
+<b>Pièces jointes :</b>
+Envoyez une photo ou une image avec l'ID de la tâche en légende pour la joindre
+
+<b>Remarques :</b>
+- Les ID de tâche et de projet sont des UUID (un préfixe court suffit)
+- Définissez un projet actif avec /project pour éviter de saisir les ID"#
+        }
+        Locale::Ja => {
+            r#"<b>VibeKanban Bot コマンド</b>
+
+<b>アカウント:</b>
+/start - ウェルカムメッセージとアカウント連携
+/status - サーバーの稼働時間、実行中の試行、エグゼキューターの状態
+/whoami - チャットとアカウントの情報、設定、ボットの診断
+/report - 直近24時間のダイジェスト
+/menu - クイックアクションボタンを表示（/menu off で非表示）
+/role [&lt;ユーザー&gt; admin|read_only] - アカウントのロールを一覧表示・変更（管理者のみ）
+/setup [test] - ボットの設定を順に確認、またはテスト通知を送信（管理者のみ）
+/digest [immediate|hourly|daily] - 通知をすぐに受け取るか、1時間ごと・1日ごとのダイジェストで受け取る
+
+<b>プロジェクト:</b>
+/projects - すべてのプロジェクトを表示
+/project &lt;id&gt; - 以降のコマンドで使うプロジェクトを設定
+  フォーラムのトピック内では、そのトピックをプロジェクト専用にします
+/shortcut &lt;名前&gt; &lt;project_id&gt; - このチャットをプロジェクトに切り替えるコマンドを登録
+  削除は /shortcut &lt;名前&gt;、一覧は /shortcut
+
+<b>タスク:</b>
+/tasks - アクティブなプロジェクトのタスク一覧
+/tasks &lt;project_id&gt; - 指定したプロジェクトのタスク一覧
+/workload [project_id] - 担当者ごとの未完了タスクと実行中の試行
+/search &lt;検索語&gt; - アクティブなプロジェクト（またはすべて）のタスクを検索
+  任意のチャットで @ボット名 &lt;検索語&gt; と入力するとタスクを共有できます
+/task &lt;id&gt; - タスクの詳細
+/logs &lt;task_id&gt; [行数] - タスクの最新のエージェント出力
+/diff &lt;task_id&gt; - 最新の試行による変更
+/merge &lt;task_id&gt; - タスクのプルリクエストをマージ
+/stop &lt;task_id&gt; - タスクの実行中の試行を停止
+/retry &lt;task_id&gt; - 失敗した試行をやり直す
+/assign &lt;task_id&gt; &lt;ユーザー&gt; - リンク済みアカウントにタスクを割り当て（me、@ユーザー名、ユーザーID）
+/unassign &lt;task_id&gt; &lt;ユーザー&gt; - 担当者またはウォッチャーを外す
+/watch &lt;task_id&gt; - 担当せずにタスクの通知を受け取る
+/newtask &lt;タイトル&gt; - アクティブなプロジェクトにタスクを作成
+/newtask &lt;project_id&gt; &lt;タイトル&gt; - 指定したプロジェクトにタスクを作成
+  #プロンプト名 を付けると保存済みプロンプトを説明として使用します
+  2行目以降はタスクの説明になります
+
+<b>メッセージ:</b>
+/message &lt;task_id&gt; &lt;テキスト&gt; - タスクにメッセージを送信またはキューに追加
+タスク通知に返信すると、そのタスクへのメッセージとしてキューに追加されます
+
+<b>通知:</b>
+/mute &lt;期間&gt; - 通知を一時停止（例: 30m、2h、1d）
+/unmute - 通知を再開
+
+<b>添付ファイル:</b>
+タスク ID をキャプションにして写真や画像を送ると添付されます
+
+<b>メモ:</b>
+- タスクとプロジェクトの ID は UUID です（短いプレフィックスでも可）
+- /project でアクティブなプロジェクトを設定すると ID の入力を省略できます"#
+        }
+        Locale::Es => {
+            r#"<b>Comandos del bot de VibeKanban</b>
+
+<b>Cuenta:</b>
+/start - Mensaje de bienvenida y vinculación de la cuenta
+/status - Tiempo activo del servidor, intentos en curso y estado del ejecutor
+/whoami - Datos de tu chat y tu cuenta, ajustes y diagnóstico del bot
+/report - Resumen de las últimas 24 horas
+/menu - Mostrar botones de acciones rápidas (/menu off para ocultarlos)
+/role [&lt;usuario&gt; admin|read_only] - Listar los roles de las cuentas o cambiar uno (solo administradores)
+/setup [test] - Comprobar la configuración del bot paso a paso o enviar una notificación de prueba (solo administradores)
+/digest [immediate|hourly|daily] - Recibir tus notificaciones al momento, o en un resumen por hora o por día
+
+<b>Proyectos:</b>
+/projects - Listar todos los proyectos
+/project &lt;id&gt; - Establecer el proyecto activo para los siguientes comandos
+  En un tema de foro, dedica el tema al proyecto
+/shortcut &lt;nombre&gt; &lt;project_id&gt; - Guardar un comando que cambia este chat al proyecto
+  /shortcut &lt;nombre&gt; lo elimina, /shortcut los lista
+
+<b>Tareas:</b>
+/tasks - Listar las tareas del proyecto activo
+/tasks &lt;project_id&gt; - Listar las tareas de un proyecto
+/workload [project_id] - Tareas abiertas e intentos en curso por responsable
+/search &lt;búsqueda&gt; - Buscar tareas en el proyecto activo (o en todos)
+  O escribe @nombre_del_bot &lt;búsqueda&gt; en cualquier chat para compartir una tarea
+/task &lt;id&gt; - Ver detalles de una tarea
+/logs &lt;task_id&gt; [líneas] - Última salida del agente para una tarea
+/diff &lt;task_id&gt; - Cambios del último intento
+/merge &lt;task_id&gt; - Fusionar la pull request abierta de la tarea
+/stop &lt;task_id&gt; - Detener el intento en curso de la tarea
+/retry &lt;task_id&gt; - Iniciar un nuevo intento tras uno fallido
+/assign &lt;task_id&gt; &lt;usuario&gt; - Asignar una tarea a una cuenta vinculada (me, @usuario o id)
+/unassign &lt;task_id&gt; &lt;usuario&gt; - Quitar a un asignado u observador
+/watch &lt;task_id&gt; - Recibir las notificaciones de una tarea sin estar asignado
+/newtask &lt;título&gt; - Crear una tarea en el proyecto activo
+/newtask &lt;project_id&gt; &lt;título&gt; - Crear una tarea en un proyecto
+  Añade #nombre-del-prompt para usar un prompt guardado como descripción
+  Las líneas después de la primera se usan como descripción
+
+<b>Mensajes:</b>
+/message &lt;task_id&gt; &lt;texto&gt; - Enviar o encolar un mensaje para una tarea
+Responde a una notificación de tarea para encolar tu respuesta para esa tarea
+
+<b>Notificaciones:</b>
+/mute &lt;duración&gt; - Pausar las notificaciones, p. ej. 30m, 2h o 1d
+/unmute - Reanudar las notificaciones
+
+<b>Adjuntos:</b>
+Envía una foto o imagen con el ID de la tarea como pie para adjuntarla
+
+<b>Notas:</b>
+- Los ID de tareas y proyectos son UUID (basta con un prefijo corto)
+- Establece un proyecto activo con /project para no tener que escribir ID"#
+        }
+        Locale::Ko => {
+            r#"<b>VibeKanban 봇 명령어</b>
+
+<b>계정:</b>
+/start - 환영 메시지 및 계정 연결
+/status - 서버 가동 시간, 실행 중인 시도 및 실행기 상태
+/whoami - 채팅 및 계정 정보, 설정, 봇 진단
+/report - 최근 24시간 요약
+/menu - 빠른 실행 버튼 표시 (/menu off로 숨기기)
+/role [&lt;사용자&gt; admin|read_only] - 계정 역할 목록 보기 또는 변경 (관리자 전용)
+/setup [test] - 봇 설정을 단계별로 확인하거나 테스트 알림 보내기 (관리자 전용)
+/digest [immediate|hourly|daily] - 알림을 바로 받거나 시간별·일별 요약으로 받기
+
+<b>프로젝트:</b>
+/projects - 전체 프로젝트 목록
+/project &lt;id&gt; - 이후 명령어에 사용할 활성 프로젝트 설정
+  포럼 토픽에서 사용하면 해당 토픽을 프로젝트 전용으로 지정
+/shortcut &lt;이름&gt; &lt;project_id&gt; - 이 채팅을 프로젝트로 전환하는 명령어 저장
+  삭제는 /shortcut &lt;이름&gt;, 목록은 /shortcut
+
+<b>작업:</b>
+/tasks - 활성 프로젝트의 작업 목록
+/tasks &lt;project_id&gt; - 특정 프로젝트의 작업 목록
+/workload [project_id] - 담당자별 진행 중인 작업과 실행 중인 시도
+/search &lt;검색어&gt; - 활성 프로젝트(또는 전체 프로젝트)에서 작업 검색
+  아무 채팅에서나 @봇이름 &lt;검색어&gt;를 입력하면 작업을 공유할 수 있음
+/task &lt;id&gt; - 작업 상세 정보
+/logs &lt;task_id&gt; [줄 수] - 작업의 최근 에이전트 출력
+/diff &lt;task_id&gt; - 최근 시도의 변경 사항
+/merge &lt;task_id&gt; - 작업의 열린 풀 리퀘스트 병합
+/stop &lt;task_id&gt; - 작업의 실행 중인 시도 중지
+/retry &lt;task_id&gt; - 실패한 시도를 새 시도로 다시 실행
+/assign &lt;task_id&gt; &lt;사용자&gt; - 연결된 계정에 작업 할당(me, @사용자명 또는 사용자 ID)
+/unassign &lt;task_id&gt; &lt;사용자&gt; - 담당자 또는 관찰자 제거
+/watch &lt;task_id&gt; - 담당하지 않고 작업 알림 받기
+/newtask &lt;제목&gt; - 활성 프로젝트에 작업 만들기
+/newtask &lt;project_id&gt; &lt;제목&gt; - 특정 프로젝트에 작업 만들기
+  #프롬프트-이름 을 추가하면 저장된 프롬프트를 설명으로 사용합니다
+  첫 줄 이후의 내용은 작업 설명이 됩니다
+
+<b>메시지:</b>
+/message &lt;task_id&gt; &lt;내용&gt; - 작업에 메시지 보내기 또는 대기열에 추가
+작업 알림에 답장하면 해당 작업의 메시지로 대기열에 추가됩니다
+
+<b>알림:</b>
+/mute &lt;기간&gt; - 알림 일시 중지(예: 30m, 2h, 1d)
+/unmute - 알림 다시 받기
+
+<b>첨부 파일:</b>
+작업 ID를 캡션으로 넣어 사진이나 이미지를 보내면 첨부됩니다
+
+<b>참고:</b>
+- 작업 및 프로젝트 ID는 UUID입니다(짧은 접두사 사용 가능)
+- /project 명령으로 활성 프로젝트를 설정하면 ID를 입력하지 않아도 됩니다"#
+        }
+        Locale::ZhHans => {
+            r#"<b>VibeKanban 机器人命令</b>
+
+<b>账号：</b>
+/start - 欢迎信息与账号关联
+/status - 服务器运行时间、运行中的尝试和执行器状态
+/whoami - 聊天和账号信息、设置及机器人诊断
+/report - 最近 24 小时的摘要
+/menu - 显示快捷操作按钮（/menu off 隐藏）
+/role [&lt;用户&gt; admin|read_only] - 列出账号角色或更改角色（仅限管理员）
+/setup [test] - 逐步检查机器人设置，或发送测试通知（仅限管理员）
+/digest [immediate|hourly|daily] - 立即接收通知，或按小时、按天汇总接收
+
+<b>项目：</b>
+/projects - 列出所有项目
+/project &lt;id&gt; - 设置后续命令使用的当前项目
+  在论坛话题中使用时，将该话题指定给此项目
+/shortcut &lt;名称&gt; &lt;project_id&gt; - 保存一个将此聊天切换到该项目的命令
+  删除用 /shortcut &lt;名称&gt;，列出全部用 /shortcut
+
+<b>任务：</b>
+/tasks - 列出当前项目的任务
+/tasks &lt;project_id&gt; - 列出指定项目的任务
+/workload [project_id] - 每个负责人的未完成任务和运行中的尝试
+/search &lt;关键词&gt; - 在当前项目（或所有项目）中搜索任务
+  在任意聊天中输入 @机器人名 &lt;关键词&gt; 即可分享任务
+/task &lt;id&gt; - 查看任务详情
+/logs &lt;task_id&gt; [行数] - 任务最新的代理输出
+/diff &lt;task_id&gt; - 最新一次尝试的改动
+/merge &lt;task_id&gt; - 合并任务的拉取请求
+/stop &lt;task_id&gt; - 停止任务正在运行的尝试
+/retry &lt;task_id&gt; - 在失败后开始新的尝试
+/assign &lt;task_id&gt; &lt;用户&gt; - 将任务分配给已关联的账号（me、@用户名或用户 ID）
+/unassign &lt;task_id&gt; &lt;用户&gt; - 移除负责人或关注者
+/watch &lt;task_id&gt; - 不被分配也接收任务通知
+/newtask &lt;标题&gt; - 在当前项目中新建任务
+/newtask &lt;project_id&gt; &lt;标题&gt; - 在指定项目中新建任务
+  添加 #提示词名称 可将已保存的提示词用作描述
+  第一行之后的内容将作为任务描述
+
+<b>消息：</b>
+/message &lt;task_id&gt; &lt;内容&gt; - 向任务发送或排队一条消息
+回复任务通知即可将回复作为该任务的消息排队
+
+<b>通知：</b>
+/mute &lt;时长&gt; - 暂停通知，例如 30m、2h 或 1d
+/unmute - 恢复通知
+
+<b>附件：</b>
+发送照片或图片并以任务 ID 作为说明即可附加到任务
+
+<b>说明：</b>
+- 任务和项目 ID 为 UUID（可使用短前缀）
+- 使用 /project 设置当前项目即可省去输入 ID"#
+        }
+        Locale::ZhHant => {
+            r#"<b>VibeKanban 機器人指令</b>
+
+<b>帳號：</b>
+/start - 歡迎訊息與帳號連結
+/status - 伺服器運行時間、執行中的嘗試與執行器狀態
+/whoami - 聊天與帳號資訊、設定及機器人診斷
+/report - 最近 24 小時的摘要
+/menu - 顯示快捷操作按鈕（/menu off 隱藏）
+/role [&lt;使用者&gt; admin|read_only] - 列出帳號角色或變更角色（僅限管理員）
+/setup [test] - 逐步檢查機器人設定，或傳送測試通知（僅限管理員）
+/digest [immediate|hourly|daily] - 立即接收通知，或按小時、按天彙整接收
+
+<b>專案：</b>
+/projects - 列出所有專案
+/project &lt;id&gt; - 設定後續指令使用的目前專案
+  在論壇話題中使用時，將該話題指定給此專案
+/shortcut &lt;名稱&gt; &lt;project_id&gt; - 儲存一個將此聊天切換到該專案的指令
+  刪除用 /shortcut &lt;名稱&gt;，列出全部用 /shortcut
+
+<b>任務：</b>
+/tasks - 列出目前專案的任務
+/tasks &lt;project_id&gt; - 列出指定專案的任務
+/workload [project_id] - 每個負責人的未完成任務與執行中的嘗試
+/search &lt;關鍵字&gt; - 在目前專案（或所有專案）中搜尋任務
+  在任意聊天中輸入 @機器人名稱 &lt;關鍵字&gt; 即可分享任務
+/task &lt;id&gt; - 查看任務詳情
+/logs &lt;task_id&gt; [行數] - 任務最新的代理輸出
+/diff &lt;task_id&gt; - 最新一次嘗試的變更
+/merge &lt;task_id&gt; - 合併任務的拉取請求
+/stop &lt;task_id&gt; - 停止任務正在執行的嘗試
+/retry &lt;task_id&gt; - 在失敗後開始新的嘗試
+/assign &lt;task_id&gt; &lt;使用者&gt; - 將任務指派給已連結的帳號（me、@使用者名稱或使用者 ID）
+/unassign &lt;task_id&gt; &lt;使用者&gt; - 移除負責人或關注者
+/watch &lt;task_id&gt; - 不被指派也接收任務通知
+/newtask &lt;標題&gt; - 在目前專案中新增任務
+/newtask &lt;project_id&gt; &lt;標題&gt; - 在指定專案中新增任務
+  加上 #提示詞名稱 可將已儲存的提示詞作為描述
+  第一行之後的內容將作為任務描述
+
+<b>訊息：</b>
+/message &lt;task_id&gt; &lt;內容&gt; - 傳送或排入一則給任務的訊息
+回覆任務通知即可將回覆排入該任務的訊息佇列
+
+<b>通知：</b>
+/mute &lt;時長&gt; - 暫停通知，例如 30m、2h 或 1d
+/unmute - 恢復通知
+
+<b>附件：</b>
+傳送照片或圖片並以任務 ID 作為說明即可附加到任務
+
+<b>備註：</b>
+- 任務與專案 ID 為 UUID（可使用短前綴）
+- 使用 /project 設定目前專案即可省去輸入 ID"#
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [Locale; 7] = [
+        Locale::En,
+        Locale::Fr,
+        Locale::Ja,
+        Locale::Es,
+        Locale::Ko,
+        Locale::ZhHans,
+        Locale::ZhHant,
+    ];
+
+    fn commands(text: &str) -> Vec<&str> {
+        text.split_whitespace()
+            .filter(|word| word.starts_with('/'))
+            .collect()
+    }
+
+    #[test]
+    fn test_from_language_code() {
+        assert_eq!(Locale::from_language_code("fr"), Some(Locale::Fr));
+        assert_eq!(Locale::from_language_code("es-MX"), Some(Locale::Es));
+        assert_eq!(Locale::from_language_code("zh-hans"), Some(Locale::ZhHans));
+        assert_eq!(Locale::from_language_code("zh_TW"), Some(Locale::ZhHant));
+        assert_eq!(Locale::from_language_code("de"), None);
+    }
+
+    #[test]
+    fn test_from_accept_language() {
+        assert_eq!(
+            Locale::from_accept_language("de-DE,fr;q=0.8,en;q=0.9"),
+            Some(Locale::En)
+        );
+        assert_eq!(
+            Locale::from_accept_language("zh-TW, ja"),
+            Some(Locale::ZhHant)
+        );
+        assert_eq!(Locale::from_accept_language("en;q=0, ko"), Some(Locale::Ko));
+        assert_eq!(Locale::from_accept_language("de, *;q=0.5"), None);
+        assert_eq!(Locale::from_accept_language(""), None);
+    }
+
+    #[tokio::test]
+    async fn test_current_locale_follows_scope() {
+        assert_eq!(Locale::current(), Locale::En);
+        let inside = Locale::Fr.scope(async { Locale::current() }).await;
+        assert_eq!(inside, Locale::Fr);
+    }
+
+    #[test]
+    fn test_server_messages_keep_placeholders() {
+        for locale in ALL {
+            assert!(
+                ServerMessage::MergePrButton(12)
+                    .render(locale)
+                    .contains("#12")
+            );
+            assert!(ServerMessage::DigestMore(3).render(locale).contains('3'));
+            let message = ServerMessage::TaskSucceeded {
+                title: "Fix login",
+                branch: "vk/fix-login",
+                executor: "CLAUDE_CODE",
+            }
+            .render(locale);
+            assert!(message.contains("Fix login"));
+            assert!(message.contains("vk/fix-login"));
+            assert!(message.contains("CLAUDE_CODE"));
+        }
+    }
+
+    #[test]
+    fn test_configured_language_wins_over_telegram() {
+        assert_eq!(Locale::resolve(&UiLanguage::Ja, Some("fr")), Locale::Ja);
+        assert_eq!(
+            Locale::resolve(&UiLanguage::Browser, Some("ko")),
+            Locale::Ko
+        );
+        assert_eq!(
+            Locale::resolve(&UiLanguage::Browser, Some("de")),
+            Locale::En
+        );
+        assert_eq!(Locale::resolve(&UiLanguage::Browser, None), Locale::En);
+    }
+
+    #[test]
+    fn test_translations_list_the_same_commands() {
+        for locale in ALL {
+            assert_eq!(commands(welcome(locale)), commands(welcome(Locale::En)));
+            assert_eq!(commands(help(locale)), commands(help(Locale::En)));
+        }
+    }
+}
@@ -1,25 +1,68 @@
 pub mod analytics;
+pub mod analytics_export;
+pub mod anonymized_export;
+pub mod apprise;
 pub mod approvals;
+pub mod artifact;
 pub mod auth;
+pub mod ci_failure;
 pub mod config;
 pub mod container;
+pub mod digest;
 pub mod diff_stream;
+pub mod email;
+pub mod escalation;
+pub mod eval;
 pub mod events;
 pub mod file_ranker;
 pub mod file_search;
 pub mod filesystem;
 pub mod filesystem_watcher;
 pub mod git_host;
+pub mod gotify;
+pub mod health;
+pub mod http_notifier;
+pub mod i18n;
 pub mod image;
 pub mod notification;
+pub mod notification_dispatcher;
+pub mod notification_router;
+pub mod ntfy;
 pub mod oauth_credentials;
+pub mod outbox;
+pub mod peer_sync;
+pub mod post_merge;
 pub mod pr_monitor;
+pub mod preview_capture;
 pub mod project;
+pub mod pushover;
+pub mod prompt_library;
 #[cfg(feature = "qa-mode")]
 pub mod qa_repos;
 pub mod queued_message;
+pub mod quiet_hours;
+pub mod rate_limit;
 pub mod remote_client;
 pub mod repo;
+pub mod sla;
+pub mod slack;
+pub mod soak_data;
+pub mod stall_monitor;
+pub mod task_order;
+#[cfg(test)]
+mod test_fixtures;
 pub mod telegram;
+pub mod telegram_queue;
+pub mod todo_sync;
+pub mod transcript;
+pub mod tts;
+pub mod twilio;
+pub mod vault_export;
+pub mod web_push;
+pub mod webhook_payload;
+pub mod webhooks;
+pub mod weekly_digest;
+pub mod whatsapp;
 pub mod workspace_manager;
+pub mod workload;
 pub mod worktree_manager;
@@ -1,9 +1,15 @@
 use std::sync::{Arc, OnceLock};
 
+use async_trait::async_trait;
+use db::models::notification_rule::{NotificationChannel, TaskNotificationKind};
 use tokio::sync::RwLock;
-use utils;
+use utils::{self, text::strip_emoji};
 
-use crate::services::config::{Config, NotificationConfig, SoundFile};
+use crate::services::{
+    config::{Config, NotificationConfig, SoundFile},
+    i18n::{Locale, ServerMessage},
+    notification_dispatcher::{NotificationEvent, Notifier, NotifierError},
+};
 
 /// Service for handling cross-platform notifications including sound alerts and push notifications
 #[derive(Debug, Clone)]
@@ -25,6 +31,18 @@ impl NotificationService {
         Self::send_notification(&config, title, message).await;
     }
 
+    /// Like [`Self::notify`], rendering both texts in the configured language
+    pub async fn notify_localized(&self, title: ServerMessage<'_>, message: ServerMessage<'_>) {
+        let (config, locale) = {
+            let config = self.config.read().await;
+            (
+                config.notifications.clone(),
+                Locale::resolve(&config.language, None),
+            )
+        };
+        Self::send_notification(&config, &title.render(locale), &message.render(locale)).await;
+    }
+
     /// Internal method to send notifications with a given config
     async fn send_notification(config: &NotificationConfig, title: &str, message: &str) {
         if config.sound_enabled {
@@ -32,7 +50,11 @@ impl NotificationService {
         }
 
         if config.push_enabled {
-            Self::send_push_notification(title, message).await;
+            if config.plain_text {
+                Self::send_push_notification(&strip_emoji(title), &strip_emoji(message)).await;
+            } else {
+                Self::send_push_notification(title, message).await;
+            }
         }
     }
 
@@ -236,3 +258,52 @@ impl NotificationService {
         }
     }
 }
+
+#[async_trait]
+impl Notifier for NotificationService {
+    fn channel(&self) -> NotificationChannel {
+        NotificationChannel::Desktop
+    }
+
+    async fn wants(&self, kind: TaskNotificationKind) -> bool {
+        let finished = matches!(
+            kind,
+            TaskNotificationKind::Done | TaskNotificationKind::Failed
+        );
+        let config = self.config.read().await;
+        finished && (config.notifications.sound_enabled || config.notifications.push_enabled)
+    }
+
+    /// Only the end of a run is announced on the desktop
+    async fn send(&self, event: &NotificationEvent) -> Result<(), NotifierError> {
+        let Some(run) = &event.run else {
+            return Ok(());
+        };
+        let (title, branch, executor) = (
+            event.task.title.as_str(),
+            run.workspace.branch.as_str(),
+            run.executor.as_str(),
+        );
+        let message = match event.kind {
+            TaskNotificationKind::Done => ServerMessage::TaskSucceeded {
+                title,
+                branch,
+                executor,
+            },
+            TaskNotificationKind::Failed => ServerMessage::TaskExecutionFailed {
+                title,
+                branch,
+                executor,
+            },
+            _ => return Ok(()),
+        };
+        self.notify_localized(ServerMessage::TaskFinishedTitle(title), message)
+            .await;
+        Ok(())
+    }
+
+    async fn send_text(&self, title: &str, body: &str) -> Result<(), NotifierError> {
+        self.notify(title, body).await;
+        Ok(())
+    }
+}
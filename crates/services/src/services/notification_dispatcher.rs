@@ -0,0 +1,792 @@
+//! One path for delivering task notifications through every channel.
+//!
+//! Each channel implements [`Notifier`]. [`NotificationDispatcher`] routes an
+//! event by the notification rules, keeps the channels the notification
+//! preferences pick for its type, skips those whose settings do not want it,
+//! sends through the rest concurrently and retries transient failures, so
+//! callers only build a [`NotificationEvent`]. Every delivery is logged in the
+//! notification history, and failed ones are also kept as dead letters until
+//! they are redelivered. Channels in their quiet hours get the event later,
+//! in one catch-up message. Failures nobody acknowledges are escalated to
+//! more channels.
+
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use db::models::{
+    held_notification::{CreateHeldNotification, HeldNotification},
+    notification::{CreateNotification, Notification, NotificationPayload, NotificationStatus},
+    notification_dead_letter::NotificationDeadLetter,
+    notification_escalation::{CreateNotificationEscalation, NotificationEscalation},
+    notification_rule::{NotificationChannel, TaskNotificationKind},
+    task::Task,
+    workspace::Workspace,
+};
+use futures::future::join_all;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::services::{
+    apprise::AppriseError,
+    config::{Config, NotificationEventType},
+    email::EmailError,
+    escalation::Progress,
+    gotify::GotifyError,
+    http_notifier::StatusError,
+    notification_router::NotificationRouter,
+    ntfy::NtfyError,
+    pushover::PushoverError,
+    quiet_hours,
+    slack::SlackError,
+    telegram::TelegramError,
+    twilio::TwilioError,
+    web_push::WebPushError,
+    whatsapp::WhatsAppError,
+};
+
+/// Attempts per channel before a transient failure is given up on
+const MAX_ATTEMPTS: u32 = 3;
+/// Delay before the first retry, doubled for each one after it
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Error)]
+pub enum NotifierError {
+    #[error(transparent)]
+    Telegram(#[from] TelegramError),
+    #[error(transparent)]
+    Slack(#[from] SlackError),
+    #[error(transparent)]
+    Email(#[from] EmailError),
+    #[error(transparent)]
+    Ntfy(#[from] NtfyError),
+    #[error(transparent)]
+    Pushover(#[from] PushoverError),
+    #[error(transparent)]
+    Gotify(#[from] GotifyError),
+    #[error(transparent)]
+    WebPush(#[from] WebPushError),
+    #[error(transparent)]
+    Apprise(#[from] AppriseError),
+    #[error(transparent)]
+    Twilio(#[from] TwilioError),
+    #[error(transparent)]
+    WhatsApp(#[from] WhatsAppError),
+}
+
+impl NotifierError {
+    /// Whether sending again may succeed. Telegram retries on its own, and
+    /// Web Push and Apprise handle each browser or URL separately, so none of
+    /// them is retried here. Neither is SMS, as every message is billed.
+    pub fn is_transient(&self) -> bool {
+        let retryable_status = |status: u16| status == 429 || status >= 500;
+        match self {
+            Self::Slack(SlackError::Http(_))
+            | Self::Ntfy(NtfyError::Http(_))
+            | Self::Pushover(PushoverError::Http(_))
+            | Self::Gotify(GotifyError::Http(_))
+            | Self::WhatsApp(WhatsAppError::Http(_)) => true,
+            Self::Email(EmailError::Smtp(e)) => e.is_transient(),
+            Self::Ntfy(NtfyError::Status(StatusError { status, .. }))
+            | Self::Pushover(PushoverError::Rejected { status, .. })
+            | Self::Gotify(GotifyError::Status(StatusError { status, .. }))
+            | Self::WhatsApp(WhatsAppError::Api { status, .. }) => retryable_status(*status),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ResendError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("Task {0} no longer exists")]
+    TaskNotFound(Uuid),
+    #[error("Unknown notification event '{0}'")]
+    UnknownEvent(String),
+    #[error("{} notifications are not enabled", .0.label())]
+    ChannelUnavailable(NotificationChannel),
+}
+
+/// The coding agent run an event ended
+#[derive(Debug, Clone)]
+pub struct FinishedRun {
+    pub workspace: Workspace,
+    pub executor: String,
+    /// Tail of the run's error output, for failures
+    pub error_excerpt: Option<String>,
+}
+
+/// Something that happened to a task, as sent through every channel
+#[derive(Debug, Clone)]
+pub struct NotificationEvent {
+    pub task: Task,
+    pub kind: TaskNotificationKind,
+    /// Which row of the notification preferences applies
+    pub event_type: NotificationEventType,
+    /// The agent's summary of its work
+    pub summary: Option<String>,
+    /// Set when the event ended a coding agent run
+    pub run: Option<FinishedRun>,
+}
+
+impl NotificationEvent {
+    pub fn new(task: Task, kind: TaskNotificationKind) -> Self {
+        Self::of_type(task, NotificationEventType::from_kind(kind))
+    }
+
+    /// An event rendered as the task notification its type stands for, e.g.
+    /// a PR merge as a completed task
+    pub fn of_type(task: Task, event_type: NotificationEventType) -> Self {
+        Self {
+            task,
+            kind: event_type.kind(),
+            event_type,
+            summary: None,
+            run: None,
+        }
+    }
+}
+
+/// A channel task notifications are delivered through
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    fn channel(&self) -> NotificationChannel;
+
+    /// Whether the channel is set up to send notifications of `kind`. Which
+    /// events it gets is up to the notification preferences.
+    async fn wants(&self, kind: TaskNotificationKind) -> bool;
+
+    /// Deliver `event`. Events the channel cannot express, such as ones
+    /// missing the run it needs, are skipped without an error.
+    async fn send(&self, event: &NotificationEvent) -> Result<(), NotifierError>;
+
+    /// Deliver a message that is not about one task, such as the catch-up
+    /// sent once quiet hours end
+    async fn send_text(&self, title: &str, body: &str) -> Result<(), NotifierError>;
+
+    /// Called once an `event` the channel accepted is in the history as
+    /// `notification_id`. A channel that delivers later, such as from a
+    /// queue, ties the delivery to it here so a late failure can be recorded.
+    async fn recorded(&self, _event: &NotificationEvent, _notification_id: Uuid) {}
+}
+
+/// A channel that failed to deliver an event
+#[derive(Debug)]
+pub struct DeliveryFailure {
+    pub channel: NotificationChannel,
+    pub error: NotifierError,
+}
+
+/// Outcome of redelivering dead letters
+#[derive(Debug, Default, Serialize, TS)]
+pub struct RedeliveryReport {
+    pub delivered: u32,
+    /// Dead letters that failed again and stay queued
+    pub failed: u32,
+}
+
+/// Sends task notifications through every registered [`Notifier`]
+#[derive(Clone)]
+pub struct NotificationDispatcher {
+    pool: SqlitePool,
+    router: NotificationRouter,
+    config: Arc<RwLock<Config>>,
+    notifiers: Vec<Arc<dyn Notifier>>,
+}
+
+impl NotificationDispatcher {
+    pub fn new(pool: SqlitePool, config: Arc<RwLock<Config>>) -> Self {
+        Self {
+            router: NotificationRouter::new(pool.clone()),
+            pool,
+            config,
+            notifiers: Vec::new(),
+        }
+    }
+
+    /// Add a channel to dispatch through
+    pub fn with(mut self, notifier: impl Notifier + 'static) -> Self {
+        self.notifiers.push(Arc::new(notifier));
+        self
+    }
+
+    /// Whether events of `event_type` would be sent through any channel, so
+    /// callers can skip gathering what only a notification would use
+    pub async fn wants(&self, event_type: NotificationEventType) -> bool {
+        let preferences = self.config.read().await.notification_preferences.clone();
+        for notifier in &self.notifiers {
+            if preferences.allows(event_type, notifier.channel())
+                && notifier.wants(event_type.kind()).await
+            {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Send `event` through every channel the rules route it to. Failures
+    /// are logged and returned, and every delivery is recorded in the
+    /// notification history. A failure starts being escalated.
+    pub async fn dispatch(&self, event: &NotificationEvent) -> Vec<DeliveryFailure> {
+        let failures = self.dispatch_where(event, |_| true).await;
+        if event.kind == TaskNotificationKind::Failed {
+            self.start_escalation(event).await;
+        }
+        failures
+    }
+
+    /// Like [`Self::dispatch`], limited to the channels `include` accepts.
+    /// Used to retry some channels of an event, so it does not escalate.
+    pub async fn dispatch_where(
+        &self,
+        event: &NotificationEvent,
+        include: impl Fn(NotificationChannel) -> bool,
+    ) -> Vec<DeliveryFailure> {
+        let route = self.router.route(&event.task, event.kind).await;
+        let (preferences, quiet) = {
+            let config = self.config.read().await;
+            (
+                config.notification_preferences.clone(),
+                config.quiet_hours.clone(),
+            )
+        };
+        let (quiet, now) = (&quiet, Utc::now());
+        let deliveries = self
+            .notifiers
+            .iter()
+            .filter(|notifier| {
+                let channel = notifier.channel();
+                include(channel)
+                    && route.allows(channel)
+                    && preferences.allows(event.event_type, channel)
+            })
+            .map(|notifier| async move {
+                if !notifier.wants(event.kind).await {
+                    return None;
+                }
+                if quiet_hours::holds(quiet, notifier.channel(), event.kind, now)
+                    && self.hold(notifier.channel(), event).await
+                {
+                    return None;
+                }
+                let delivery = send_with_retries(notifier.as_ref(), event, RETRY_BASE_DELAY).await;
+                self.record(notifier.as_ref(), event, &delivery).await;
+                delivery.result.err().map(|error| DeliveryFailure {
+                    channel: notifier.channel(),
+                    error,
+                })
+            });
+
+        let failures: Vec<_> = join_all(deliveries).await.into_iter().flatten().collect();
+        for failure in &failures {
+            tracing::warn!(
+                "Failed to send {} notification for task {}: {}",
+                failure.channel.label(),
+                event.task.id,
+                failure.error
+            );
+        }
+        failures
+    }
+
+    /// Send a notification from the history again through its channel and
+    /// record the outcome on it and its dead letter. The rules and
+    /// preferences that applied when it was first sent are not consulted
+    /// again; the task is sent as it is now, without the error output of
+    /// its run.
+    pub async fn resend(&self, notification: &Notification) -> Result<Notification, ResendError> {
+        let event_type = NotificationEventType::parse(&notification.event_type)
+            .ok_or_else(|| ResendError::UnknownEvent(notification.event_type.clone()))?;
+        let task = Task::find_by_id(&self.pool, notification.task_id)
+            .await?
+            .ok_or(ResendError::TaskNotFound(notification.task_id))?;
+        let notifier = self
+            .notifiers
+            .iter()
+            .find(|notifier| notifier.channel() == notification.channel)
+            .ok_or(ResendError::ChannelUnavailable(notification.channel))?;
+        if !notifier.wants(event_type.kind()).await {
+            return Err(ResendError::ChannelUnavailable(notification.channel));
+        }
+
+        let mut event = NotificationEvent::of_type(task, event_type);
+        event.summary = notification.payload.summary.clone();
+        let delivery = send_with_retries(notifier.as_ref(), &event, RETRY_BASE_DELAY).await;
+        let error = delivery.result.err().map(|e| e.to_string());
+        match &error {
+            Some(error) => {
+                NotificationDeadLetter::record_failure(
+                    &self.pool,
+                    notification.id,
+                    delivery.attempts.into(),
+                    error,
+                )
+                .await?
+            }
+            None => {
+                notifier.recorded(&event, notification.id).await;
+                NotificationDeadLetter::delete_by_notification_id(&self.pool, notification.id)
+                    .await?
+            }
+        }
+        Ok(Notification::record_resend(&self.pool, notification.id, error.as_deref()).await?)
+    }
+
+    /// Send dead letters again through their channels, those with `ids` or
+    /// all of them. Delivered ones leave the queue; the rest stay with the
+    /// new error. Unknown IDs are skipped.
+    pub async fn redeliver(&self, ids: Option<&[Uuid]>) -> Result<RedeliveryReport, sqlx::Error> {
+        let dead_letters = match ids {
+            Some(ids) => {
+                let mut dead_letters = Vec::with_capacity(ids.len());
+                for &id in ids {
+                    dead_letters.extend(NotificationDeadLetter::find_by_id(&self.pool, id).await?);
+                }
+                dead_letters
+            }
+            None => NotificationDeadLetter::find_all(&self.pool).await?,
+        };
+
+        let mut report = RedeliveryReport::default();
+        for dead_letter in dead_letters {
+            let Some(notification) =
+                Notification::find_by_id(&self.pool, dead_letter.notification_id).await?
+            else {
+                continue;
+            };
+            match self.resend(&notification).await {
+                Ok(notification) if notification.status == NotificationStatus::Sent => {
+                    report.delivered += 1
+                }
+                Ok(_) => report.failed += 1,
+                Err(ResendError::Database(e)) => return Err(e),
+                Err(e) => {
+                    // Nothing was sent, so no attempt is counted
+                    NotificationDeadLetter::record_failure(
+                        &self.pool,
+                        notification.id,
+                        0,
+                        &e.to_string(),
+                    )
+                    .await?;
+                    report.failed += 1;
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Send what quiet hours held back, as one message per channel whose
+    /// quiet hours are over. Each held event is then logged in the history
+    /// with the outcome. A transient failure leaves them held to be tried
+    /// again.
+    pub async fn send_catch_ups(&self) -> Result<(), sqlx::Error> {
+        let quiet = self.config.read().await.quiet_hours.clone();
+        let now = Utc::now();
+        for channel in HeldNotification::find_channels(&self.pool).await? {
+            if quiet_hours::is_quiet(&quiet, channel, now) {
+                continue;
+            }
+            let notifier = self
+                .notifiers
+                .iter()
+                .find(|notifier| notifier.channel() == channel);
+            let mut held = Vec::new();
+            for notification in HeldNotification::find_by_channel(&self.pool, channel).await? {
+                let wanted = match (
+                    notifier,
+                    NotificationEventType::parse(&notification.event_type),
+                ) {
+                    (Some(notifier), Some(event_type)) => notifier.wants(event_type.kind()).await,
+                    _ => false,
+                };
+                if wanted {
+                    held.push(notification);
+                } else {
+                    // The channel was turned off since
+                    HeldNotification::delete(&self.pool, notification.id).await?;
+                }
+            }
+            let (Some(notifier), false) = (notifier, held.is_empty()) else {
+                continue;
+            };
+
+            let (title, body) = quiet_hours::format_catch_up(&held);
+            let error = match notifier.send_text(&title, &body).await {
+                Ok(()) => None,
+                Err(e) if e.is_transient() => {
+                    tracing::debug!(
+                        "Retrying {} quiet hours catch-up later: {}",
+                        channel.label(),
+                        e
+                    );
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to send {} quiet hours catch-up: {}",
+                        channel.label(),
+                        e
+                    );
+                    Some(e.to_string())
+                }
+            };
+            for notification in held {
+                let data = CreateNotification {
+                    task_id: notification.task_id,
+                    project_id: notification.project_id,
+                    channel,
+                    event_type: notification.event_type,
+                    payload: notification.payload.0,
+                    error: error.clone(),
+                };
+                let created = Notification::create(&self.pool, &data).await?;
+                if let Some(error) = &error {
+                    NotificationDeadLetter::record_failure(&self.pool, created.id, 1, error)
+                        .await?;
+                }
+                HeldNotification::delete(&self.pool, notification.id).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Take the escalation steps that are due, and stop the escalations of
+    /// failures that have been acknowledged or have run out of steps
+    pub async fn escalate(&self) -> Result<(), sqlx::Error> {
+        let policy = self.config.read().await.escalation.clone();
+        let now = Utc::now();
+        for escalation in NotificationEscalation::find_all(&self.pool).await? {
+            let step =
+                match Progress::of(&policy, escalation.steps_taken, escalation.created_at, now) {
+                    Progress::Due(step) => step,
+                    Progress::Waiting => continue,
+                    Progress::Finished => {
+                        NotificationEscalation::delete(&self.pool, escalation.id).await?;
+                        continue;
+                    }
+                };
+            let acknowledged = Notification::is_acknowledged_since(
+                &self.pool,
+                escalation.task_id,
+                &escalation.event_type,
+                escalation.created_at,
+            )
+            .await?;
+            let (false, Some(event_type), Some(task)) = (
+                acknowledged,
+                NotificationEventType::parse(&escalation.event_type),
+                Task::find_by_id(&self.pool, escalation.task_id).await?,
+            ) else {
+                NotificationEscalation::delete(&self.pool, escalation.id).await?;
+                continue;
+            };
+
+            let mut event = NotificationEvent::of_type(task, event_type);
+            event.summary = escalation.payload.summary.clone();
+            let notifier = self
+                .notifiers
+                .iter()
+                .find(|notifier| notifier.channel() == step.channel);
+            match notifier {
+                Some(notifier) if notifier.wants(event.kind).await => {
+                    let delivery =
+                        send_with_retries(notifier.as_ref(), &event, RETRY_BASE_DELAY).await;
+                    if let Err(e) = &delivery.result {
+                        tracing::warn!(
+                            "Failed to escalate task {} to {}: {}",
+                            event.task.id,
+                            step.channel.label(),
+                            e
+                        );
+                    }
+                    self.record(notifier.as_ref(), &event, &delivery).await;
+                }
+                _ => tracing::debug!(
+                    "Skipping escalation of task {} to {}, which is not enabled",
+                    event.task.id,
+                    step.channel.label()
+                ),
+            }
+            NotificationEscalation::record_step(&self.pool, escalation.id).await?;
+        }
+        Ok(())
+    }
+
+    /// Start escalating a failure if the policy is on. Failing to do so only
+    /// loses the escalation.
+    async fn start_escalation(&self, event: &NotificationEvent) {
+        let policy = self.config.read().await.escalation.clone();
+        if !policy.enabled || policy.steps.is_empty() {
+            return;
+        }
+        let data = CreateNotificationEscalation {
+            task_id: event.task.id,
+            project_id: event.task.project_id,
+            event_type: event.event_type.as_str().to_string(),
+            payload: payload_of(event),
+        };
+        if let Err(e) = NotificationEscalation::create(&self.pool, &data).await {
+            tracing::warn!(
+                "Failed to start escalation for task {}: {}",
+                event.task.id,
+                e
+            );
+        }
+    }
+
+    /// Hold `event` back until the channel's quiet hours end. Returns
+    /// whether it was held; if it could not be, it is sent right away.
+    async fn hold(&self, channel: NotificationChannel, event: &NotificationEvent) -> bool {
+        let data = CreateHeldNotification {
+            task_id: event.task.id,
+            project_id: event.task.project_id,
+            channel,
+            event_type: event.event_type.as_str().to_string(),
+            payload: payload_of(event),
+        };
+        match HeldNotification::create(&self.pool, &data).await {
+            Ok(_) => true,
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to hold {} notification for task {} during quiet hours: {}",
+                    channel.label(),
+                    event.task.id,
+                    e
+                );
+                false
+            }
+        }
+    }
+
+    /// Log a delivery in the notification history, and in the dead letters
+    /// if it failed. Failing to do so only loses the entry, not the
+    /// notification.
+    async fn record(
+        &self,
+        notifier: &dyn Notifier,
+        event: &NotificationEvent,
+        delivery: &Delivery,
+    ) {
+        let channel = notifier.channel();
+        let error = delivery.result.as_ref().err().map(|e| e.to_string());
+        let data = CreateNotification {
+            task_id: event.task.id,
+            project_id: event.task.project_id,
+            channel,
+            event_type: event.event_type.as_str().to_string(),
+            payload: payload_of(event),
+            error: error.clone(),
+        };
+        let recorded = match (Notification::create(&self.pool, &data).await, error) {
+            (Ok(notification), Some(error)) => {
+                NotificationDeadLetter::record_failure(
+                    &self.pool,
+                    notification.id,
+                    delivery.attempts.into(),
+                    &error,
+                )
+                .await
+            }
+            (Ok(notification), None) => {
+                notifier.recorded(event, notification.id).await;
+                Ok(())
+            }
+            (Err(e), _) => Err(e),
+        };
+        if let Err(e) = recorded {
+            tracing::warn!(
+                "Failed to record {} notification for task {}: {}",
+                channel.label(),
+                event.task.id,
+                e
+            );
+        }
+    }
+}
+
+/// What the history keeps of an event
+fn payload_of(event: &NotificationEvent) -> NotificationPayload {
+    NotificationPayload {
+        task_title: event.task.title.clone(),
+        summary: event.summary.clone(),
+    }
+}
+
+/// Outcome of sending an event through one channel
+struct Delivery {
+    result: Result<(), NotifierError>,
+    /// Sends tried, retries included
+    attempts: u32,
+}
+
+async fn send_with_retries(
+    notifier: &dyn Notifier,
+    event: &NotificationEvent,
+    base_delay: Duration,
+) -> Delivery {
+    let mut attempt = 1;
+    loop {
+        match notifier.send(event).await {
+            Err(e) if e.is_transient() && attempt < MAX_ATTEMPTS => {
+                let delay = base_delay * 2u32.pow(attempt - 1);
+                tracing::debug!(
+                    "Retrying {} notification in {:?}: {}",
+                    notifier.channel().label(),
+                    delay,
+                    e
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            result => {
+                return Delivery {
+                    result,
+                    attempts: attempt,
+                };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use db::{
+        DBService,
+        models::{
+            project::{CreateProject, Project},
+            task::CreateTask,
+        },
+    };
+
+    use super::*;
+    use crate::services::test_fixtures::task;
+
+    /// Fails with `error` the first `failures` times it is sent through
+    struct FlakyNotifier {
+        calls: Arc<AtomicU32>,
+        failures: u32,
+        error: fn() -> NotifierError,
+    }
+
+    #[async_trait]
+    impl Notifier for FlakyNotifier {
+        fn channel(&self) -> NotificationChannel {
+            NotificationChannel::Ntfy
+        }
+
+        async fn wants(&self, _kind: TaskNotificationKind) -> bool {
+            true
+        }
+
+        async fn send(&self, _event: &NotificationEvent) -> Result<(), NotifierError> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            if call < self.failures {
+                Err((self.error)())
+            } else {
+                Ok(())
+            }
+        }
+
+        async fn send_text(&self, _title: &str, _body: &str) -> Result<(), NotifierError> {
+            Ok(())
+        }
+    }
+
+    fn event() -> NotificationEvent {
+        NotificationEvent::new(task("Add dark mode"), TaskNotificationKind::Done)
+    }
+
+    #[test]
+    fn test_only_server_errors_and_throttling_are_transient() {
+        let status = |status| {
+            NotifierError::Ntfy(NtfyError::Status(StatusError {
+                status,
+                body: String::new(),
+            }))
+        };
+        assert!(status(503).is_transient());
+        assert!(status(429).is_transient());
+        assert!(!status(403).is_transient());
+        assert!(!NotifierError::Ntfy(NtfyError::NotConfigured("no topic URL")).is_transient());
+        assert!(!NotifierError::Telegram(TelegramError::NotLinked).is_transient());
+    }
+
+    #[tokio::test]
+    async fn test_transient_failures_are_retried() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let notifier = FlakyNotifier {
+            calls: calls.clone(),
+            failures: 2,
+            error: || {
+                NotifierError::Gotify(GotifyError::Status(StatusError {
+                    status: 502,
+                    body: String::new(),
+                }))
+            },
+        };
+        let delivery = send_with_retries(&notifier, &event(), Duration::ZERO).await;
+        assert!(delivery.result.is_ok());
+        assert_eq!(delivery.attempts, 3);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let calls = Arc::new(AtomicU32::new(0));
+        let notifier = FlakyNotifier {
+            calls: calls.clone(),
+            failures: 1,
+            error: || NotifierError::Gotify(GotifyError::NotConfigured("no app token")),
+        };
+        let delivery = send_with_retries(&notifier, &event(), Duration::ZERO).await;
+        assert!(delivery.result.is_err());
+        assert_eq!(delivery.attempts, 1);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retried_failure_is_escalated_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = DBService::new_at(&dir.path().join("db.sqlite"))
+            .await
+            .unwrap();
+        let project = CreateProject {
+            name: "Dark mode".to_string(),
+            repositories: Vec::new(),
+        };
+        let project = Project::create(&db.pool, &project, Uuid::new_v4())
+            .await
+            .unwrap();
+        let task =
+            CreateTask::from_title_description(project.id, "Add dark mode".to_string(), None);
+        let task = Task::create(&db.pool, &task, Uuid::new_v4()).await.unwrap();
+
+        let mut config = Config::default();
+        config.escalation.enabled = true;
+        let notifier = FlakyNotifier {
+            calls: Arc::new(AtomicU32::new(0)),
+            failures: 0,
+            error: || NotifierError::Gotify(GotifyError::NotConfigured("no app token")),
+        };
+        let dispatcher =
+            NotificationDispatcher::new(db.pool.clone(), Arc::new(RwLock::new(config)))
+                .with(notifier);
+        let event = NotificationEvent::new(task, TaskNotificationKind::Failed);
+
+        // As the outbox does: every channel first, then retries of some
+        dispatcher.dispatch(&event).await;
+        for _ in 0..2 {
+            dispatcher
+                .dispatch_where(&event, |channel| channel == NotificationChannel::Telegram)
+                .await;
+        }
+
+        let escalations = NotificationEscalation::find_all(&db.pool).await.unwrap();
+        assert_eq!(escalations.len(), 1);
+    }
+}
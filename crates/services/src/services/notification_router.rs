@@ -0,0 +1,151 @@
+//! Routing of task notifications to channels by the user's rules.
+//!
+//! While no [`NotificationRule`] is enabled, every channel follows its own
+//! settings, as before rules existed. Once one is, an event only goes to the
+//! channels of the rules it matches, e.g. failures of one project to Telegram
+//! and email. Rules choose among channels; they do not override a channel's
+//! own settings, so a channel that is off or skips an event stays silent.
+
+use std::collections::HashSet;
+
+use db::models::{
+    notification_rule::{NotificationChannel, NotificationRule, TaskNotificationKind},
+    task::Task,
+};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// The channels one event may be delivered through
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotificationRoute {
+    /// `None` while no rule is enabled
+    channels: Option<HashSet<NotificationChannel>>,
+}
+
+impl NotificationRoute {
+    /// A route leaving every channel to its own settings
+    pub fn unrestricted() -> Self {
+        Self { channels: None }
+    }
+
+    /// Route an event of `kind` about a task of `project_id` by `rules`
+    pub fn from_rules(
+        rules: &[NotificationRule],
+        project_id: Uuid,
+        kind: TaskNotificationKind,
+    ) -> Self {
+        if !rules.iter().any(|rule| rule.enabled) {
+            return Self::unrestricted();
+        }
+        let channels = rules
+            .iter()
+            .filter(|rule| rule.matches(project_id, kind))
+            .flat_map(|rule| rule.channels.iter().copied())
+            .collect();
+        Self {
+            channels: Some(channels),
+        }
+    }
+
+    /// Whether the event may go out through `channel`
+    pub fn allows(&self, channel: NotificationChannel) -> bool {
+        self.channels
+            .as_ref()
+            .is_none_or(|channels| channels.contains(&channel))
+    }
+}
+
+/// Looks up the notification rules for each event
+#[derive(Clone)]
+pub struct NotificationRouter {
+    pool: SqlitePool,
+}
+
+impl NotificationRouter {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Where a notification of `kind` about `task` may be delivered. If the
+    /// rules cannot be read, every channel is left to its own settings rather
+    /// than dropping the notification.
+    pub async fn route(&self, task: &Task, kind: TaskNotificationKind) -> NotificationRoute {
+        match NotificationRule::find_enabled(&self.pool).await {
+            Ok(rules) => NotificationRoute::from_rules(&rules, task.project_id, kind),
+            Err(e) => {
+                tracing::warn!("Failed to load notification rules: {e}");
+                NotificationRoute::unrestricted()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use sqlx::types::Json;
+
+    use super::*;
+
+    fn rule(
+        project_id: Option<Uuid>,
+        events: Vec<TaskNotificationKind>,
+        channels: Vec<NotificationChannel>,
+    ) -> NotificationRule {
+        NotificationRule {
+            id: Uuid::new_v4(),
+            name: "rule".to_string(),
+            project_id,
+            events: Json(events),
+            channels: Json(channels),
+            enabled: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_without_rules_every_channel_is_allowed() {
+        let route = NotificationRoute::from_rules(&[], Uuid::nil(), TaskNotificationKind::Done);
+        assert!(route.allows(NotificationChannel::Telegram));
+        assert!(route.allows(NotificationChannel::WebPush));
+
+        let mut disabled = rule(None, vec![], vec![NotificationChannel::Email]);
+        disabled.enabled = false;
+        let route =
+            NotificationRoute::from_rules(&[disabled], Uuid::nil(), TaskNotificationKind::Done);
+        assert!(route.allows(NotificationChannel::Telegram));
+    }
+
+    #[test]
+    fn test_rules_route_by_project_and_event() {
+        let project = Uuid::new_v4();
+        let other_project = Uuid::new_v4();
+        let rules = [
+            rule(
+                Some(project),
+                vec![TaskNotificationKind::Failed],
+                vec![NotificationChannel::Telegram, NotificationChannel::Email],
+            ),
+            rule(
+                None,
+                vec![TaskNotificationKind::Done],
+                vec![NotificationChannel::Telegram],
+            ),
+        ];
+
+        let failed = NotificationRoute::from_rules(&rules, project, TaskNotificationKind::Failed);
+        assert!(failed.allows(NotificationChannel::Telegram));
+        assert!(failed.allows(NotificationChannel::Email));
+        assert!(!failed.allows(NotificationChannel::Desktop));
+
+        let done = NotificationRoute::from_rules(&rules, other_project, TaskNotificationKind::Done);
+        assert!(done.allows(NotificationChannel::Telegram));
+        assert!(!done.allows(NotificationChannel::Email));
+
+        // Events no rule matches go nowhere
+        let other_failed =
+            NotificationRoute::from_rules(&rules, other_project, TaskNotificationKind::Failed);
+        assert!(!other_failed.allows(NotificationChannel::Telegram));
+    }
+}
@@ -0,0 +1,174 @@
+//! Task notifications pushed through ntfy.
+//!
+//! Messages are published to the topic URL in `Config::ntfy`, on ntfy.sh or a
+//! self-hosted server, so the ntfy mobile apps get push notifications without
+//! a Telegram bot. Title, priority and tags go in query parameters, which
+//! unlike headers may hold any UTF-8.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use db::models::{notification_rule::NotificationChannel, task::Task};
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+use crate::services::{
+    config::{Config, NtfyConfig},
+    http_notifier::{self, StatusError},
+    notification_dispatcher::{NotificationEvent, Notifier, NotifierError},
+    telegram::TaskNotificationKind,
+};
+
+#[derive(Debug, Error)]
+pub enum NtfyError {
+    #[error("ntfy notifications are not configured: {0}")]
+    NotConfigured(&'static str),
+
+    #[error("ntfy request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("ntfy returned {0}")]
+    Status(#[from] StatusError),
+}
+
+/// What a push says about a task
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct NtfyMessage {
+    title: String,
+    body: String,
+    /// 1 (min) to 5 (max), 3 being ntfy's default
+    priority: u8,
+    /// Emoji shortcodes shown next to the title
+    tags: &'static str,
+}
+
+impl NtfyMessage {
+    fn for_task(task: &Task, kind: TaskNotificationKind, summary: Option<&str>) -> Self {
+        let (event, priority, tags) = match kind {
+            TaskNotificationKind::Started => ("started", 2, "hourglass_flowing_sand"),
+            TaskNotificationKind::InReview => ("ready for review", 3, "eyes"),
+            TaskNotificationKind::Failed => ("failed", 4, "x"),
+            TaskNotificationKind::Done => ("completed", 3, "white_check_mark"),
+            TaskNotificationKind::Cancelled => ("cancelled", 2, "no_entry_sign"),
+        };
+        let body = match summary.map(str::trim).filter(|summary| !summary.is_empty()) {
+            Some(summary) => summary.to_string(),
+            None => task.title.clone(),
+        };
+        Self {
+            title: format!("Task {event}: {}", task.title),
+            body,
+            priority,
+            tags,
+        }
+    }
+}
+
+/// Publishes task notifications to an ntfy topic
+#[derive(Clone)]
+pub struct NtfyNotifier {
+    config: Arc<RwLock<Config>>,
+    http: reqwest::Client,
+}
+
+impl NtfyNotifier {
+    pub fn new(config: Arc<RwLock<Config>>) -> Self {
+        Self {
+            config,
+            http: http_notifier::client(),
+        }
+    }
+
+    /// Push a notification about `task` to the configured topic. Does nothing
+    /// if ntfy is off.
+    pub async fn send_task_notification(
+        &self,
+        task: &Task,
+        kind: TaskNotificationKind,
+        summary: Option<&str>,
+    ) -> Result<(), NtfyError> {
+        let config = self.config.read().await.ntfy.clone();
+        if !config.enabled {
+            return Ok(());
+        }
+        self.publish(&config, &NtfyMessage::for_task(task, kind, summary))
+            .await
+    }
+
+    async fn publish(&self, config: &NtfyConfig, message: &NtfyMessage) -> Result<(), NtfyError> {
+        let topic_url = config
+            .topic_url
+            .as_deref()
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .ok_or(NtfyError::NotConfigured("no topic URL"))?;
+
+        let priority = message.priority.to_string();
+        let mut request = self
+            .http
+            .post(topic_url)
+            .query(&[
+                ("title", message.title.as_str()),
+                ("priority", priority.as_str()),
+                ("tags", message.tags),
+            ])
+            .body(message.body.clone());
+        if let Some(token) = config.access_token.as_deref().filter(|t| !t.is_empty()) {
+            request = request.bearer_auth(token);
+        }
+
+        http_notifier::check_status(request.send().await?).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Notifier for NtfyNotifier {
+    fn channel(&self) -> NotificationChannel {
+        NotificationChannel::Ntfy
+    }
+
+    async fn wants(&self, _kind: TaskNotificationKind) -> bool {
+        self.config.read().await.ntfy.enabled
+    }
+
+    async fn send(&self, event: &NotificationEvent) -> Result<(), NotifierError> {
+        self.send_task_notification(&event.task, event.kind, event.summary.as_deref())
+            .await?;
+        Ok(())
+    }
+
+    async fn send_text(&self, title: &str, body: &str) -> Result<(), NotifierError> {
+        let config = self.config.read().await.ntfy.clone();
+        let message = NtfyMessage {
+            title: title.to_string(),
+            body: body.to_string(),
+            priority: 3,
+            tags: "bell",
+        };
+        self.publish(&config, &message).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_fixtures::task;
+
+    #[test]
+    fn test_message_uses_summary_and_raises_failures() {
+        let task = task("Add dark mode");
+
+        let done = NtfyMessage::for_task(&task, TaskNotificationKind::Done, Some(" Added it. "));
+        assert_eq!(done.title, "Task completed: Add dark mode");
+        assert_eq!(done.body, "Added it.");
+        assert_eq!(done.priority, 3);
+
+        let failed = NtfyMessage::for_task(&task, TaskNotificationKind::Failed, None);
+        assert_eq!(failed.title, "Task failed: Add dark mode");
+        assert_eq!(failed.body, "Add dark mode");
+        assert_eq!(failed.priority, 4);
+        assert_eq!(failed.tags, "x");
+    }
+}
@@ -0,0 +1,281 @@
+//! Transactional outbox for side effects of task mutations.
+//!
+//! Callers enqueue an [`OutboxIntent`] inside the same transaction as the state
+//! change that caused it. [`OutboxWorker`] then delivers intents at-least-once,
+//! retrying with exponential backoff, so a crash can no longer leave the DB
+//! updated with the side effect lost.
+
+use std::time::Duration;
+
+use db::{
+    DBService,
+    models::{
+        notification_rule::NotificationChannel, outbox_event::OutboxEvent, task::Task,
+        workspace::Workspace,
+    },
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, Sqlite};
+use thiserror::Error;
+use tokio::time::interval;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::services::{
+    config::NotificationEventType,
+    notification_dispatcher::{
+        FinishedRun, NotificationDispatcher, NotificationEvent, NotifierError,
+    },
+    telegram::TaskNotificationKind,
+};
+
+/// Events fetched per poll
+pub(crate) const BATCH_SIZE: i64 = 50;
+/// Deliveries are abandoned after this many failed attempts
+pub(crate) const MAX_ATTEMPTS: i64 = 10;
+/// Upper bound for the retry delay
+pub(crate) const MAX_BACKOFF_SECS: i64 = 60 * 60;
+
+#[derive(Debug, Error)]
+pub enum OutboxError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Serde(#[from] serde_json::Error),
+    #[error(transparent)]
+    Notifier(#[from] NotifierError),
+}
+
+/// A side effect to perform once the enclosing transaction commits
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OutboxIntent {
+    TaskStatusNotification {
+        task_id: Uuid,
+        kind: TaskNotificationKind,
+    },
+    /// A PR of the task was merged
+    PrMergedNotification { task_id: Uuid },
+    /// A coding agent run of the task ended, as `kind`
+    AttemptFinishedNotification {
+        task_id: Uuid,
+        workspace_id: Uuid,
+        kind: TaskNotificationKind,
+        executor: String,
+        summary: Option<String>,
+        error_excerpt: Option<String>,
+    },
+}
+
+impl OutboxIntent {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::TaskStatusNotification { .. } => "task_status_notification",
+            Self::PrMergedNotification { .. } => "pr_merged_notification",
+            Self::AttemptFinishedNotification { .. } => "attempt_finished_notification",
+        }
+    }
+
+    fn task_id(&self) -> Uuid {
+        match self {
+            Self::TaskStatusNotification { task_id, .. }
+            | Self::PrMergedNotification { task_id }
+            | Self::AttemptFinishedNotification { task_id, .. } => *task_id,
+        }
+    }
+
+    /// Record this intent using `executor`, typically an open transaction
+    pub async fn enqueue<'e, E>(&self, executor: E) -> Result<Uuid, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let payload =
+            serde_json::to_string(self).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+        OutboxEvent::enqueue(executor, self.kind(), &payload).await
+    }
+}
+
+/// Background worker that delivers pending outbox events
+pub struct OutboxWorker {
+    db: DBService,
+    dispatcher: NotificationDispatcher,
+    poll_interval: Duration,
+}
+
+impl OutboxWorker {
+    pub async fn spawn(
+        db: DBService,
+        dispatcher: NotificationDispatcher,
+    ) -> tokio::task::JoinHandle<()> {
+        let worker = Self {
+            db,
+            dispatcher,
+            poll_interval: Duration::from_secs(2),
+        };
+        tokio::spawn(async move {
+            worker.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        info!(
+            "Starting outbox worker with interval {:?}",
+            self.poll_interval
+        );
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.process_due().await {
+                error!("Error processing outbox events: {}", e);
+            }
+        }
+    }
+
+    async fn process_due(&self) -> Result<(), OutboxError> {
+        let events = OutboxEvent::find_due(&self.db.pool, BATCH_SIZE).await?;
+
+        for event in events {
+            match self.deliver(&event).await {
+                Ok(()) => {
+                    debug!("Delivered outbox event {} ({})", event.id, event.kind);
+                    OutboxEvent::mark_processed(&self.db.pool, event.id).await?;
+                }
+                Err(e) if event.attempts + 1 >= MAX_ATTEMPTS => {
+                    error!(
+                        "Giving up on outbox event {} after {} attempts: {}",
+                        event.id,
+                        event.attempts + 1,
+                        e
+                    );
+                    OutboxEvent::mark_failed(&self.db.pool, event.id, &e.to_string(), 0).await?;
+                    OutboxEvent::mark_processed(&self.db.pool, event.id).await?;
+                }
+                Err(e) => {
+                    let retry_in = backoff_secs(event.attempts);
+                    warn!(
+                        "Outbox event {} failed, retrying in {}s: {}",
+                        event.id, retry_in, e
+                    );
+                    OutboxEvent::mark_failed(&self.db.pool, event.id, &e.to_string(), retry_in)
+                        .await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn deliver(&self, event: &OutboxEvent) -> Result<(), OutboxError> {
+        let intent = serde_json::from_str::<OutboxIntent>(&event.payload)?;
+        // The task may have been deleted since the intent was recorded
+        let Some(notification) = self.notification_for(intent).await? else {
+            return Ok(());
+        };
+        // Other channels are sent on the first attempt only, so
+        // Telegram retries do not repeat them
+        let failures = if event.attempts == 0 {
+            self.dispatcher.dispatch(&notification).await
+        } else {
+            self.dispatcher
+                .dispatch_where(&notification, |channel| {
+                    channel == NotificationChannel::Telegram
+                })
+                .await
+        };
+        match failures
+            .into_iter()
+            .find(|failure| failure.channel == NotificationChannel::Telegram)
+        {
+            Some(failure) => Err(failure.error.into()),
+            None => Ok(()),
+        }
+    }
+
+    /// The notification `intent` stands for, or None once its task is gone
+    async fn notification_for(
+        &self,
+        intent: OutboxIntent,
+    ) -> Result<Option<NotificationEvent>, sqlx::Error> {
+        let Some(task) = Task::find_by_id(&self.db.pool, intent.task_id()).await? else {
+            return Ok(None);
+        };
+        let notification = match intent {
+            OutboxIntent::TaskStatusNotification { kind, .. } => NotificationEvent::new(task, kind),
+            OutboxIntent::PrMergedNotification { .. } => {
+                NotificationEvent::of_type(task, NotificationEventType::PrMerged)
+            }
+            OutboxIntent::AttemptFinishedNotification {
+                workspace_id,
+                kind,
+                executor,
+                summary,
+                error_excerpt,
+                ..
+            } => {
+                let workspace = Workspace::find_by_id(&self.db.pool, workspace_id).await?;
+                NotificationEvent {
+                    summary,
+                    run: workspace.map(|workspace| FinishedRun {
+                        workspace,
+                        executor,
+                        error_excerpt,
+                    }),
+                    ..NotificationEvent::new(task, kind)
+                }
+            }
+        };
+        Ok(Some(notification))
+    }
+}
+
+/// Exponential backoff starting at 5 seconds, capped at `MAX_BACKOFF_SECS`
+pub(crate) fn backoff_secs(attempts: i64) -> i64 {
+    let exponent = attempts.clamp(0, 20) as u32;
+    (5_i64 << exponent).min(MAX_BACKOFF_SECS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intent_payload_round_trip() {
+        let intent = OutboxIntent::TaskStatusNotification {
+            task_id: Uuid::nil(),
+            kind: TaskNotificationKind::InReview,
+        };
+
+        let payload = serde_json::to_string(&intent).unwrap();
+
+        assert!(payload.contains(r#""type":"task_status_notification""#));
+        assert!(payload.contains(r#""kind":"in_review""#));
+        assert_eq!(serde_json::from_str::<OutboxIntent>(&payload).unwrap(), intent);
+
+        let intent = OutboxIntent::AttemptFinishedNotification {
+            task_id: Uuid::nil(),
+            workspace_id: Uuid::nil(),
+            kind: TaskNotificationKind::Failed,
+            executor: "CLAUDE_CODE".to_string(),
+            summary: None,
+            error_excerpt: Some("error: boom".to_string()),
+        };
+
+        let payload = serde_json::to_string(&intent).unwrap();
+
+        assert!(payload.contains(r#""type":"attempt_finished_notification""#));
+        assert_eq!(
+            serde_json::from_str::<OutboxIntent>(&payload).unwrap(),
+            intent
+        );
+    }
+
+    #[test]
+    fn test_backoff_grows_and_caps() {
+        assert_eq!(backoff_secs(0), 5);
+        assert_eq!(backoff_secs(1), 10);
+        assert_eq!(backoff_secs(3), 40);
+        assert_eq!(backoff_secs(30), MAX_BACKOFF_SECS);
+    }
+}
@@ -0,0 +1,283 @@
+//! Task replication between two vibe-kanban instances.
+//!
+//! A project can be paired with the same project on another instance (see
+//! [`ProjectPeerSyncSettings`]), for example a laptop and a home server. Each
+//! sync sends this side's tasks to the peer, which applies them and answers
+//! with its own. Both sides keep whichever copy of a task was updated last.
+//! Deleted tasks are not replicated; delete on both instances.
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use db::{
+    DBService,
+    models::{
+        peer_sync::ProjectPeerSyncSettings,
+        task::{Task, TaskStatus},
+    },
+};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use thiserror::Error;
+use tokio::{sync::RwLock, time::interval};
+use tracing::{error, info};
+use ts_rs::TS;
+use url::Url;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::services::config::Config;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Error)]
+pub enum PeerSyncError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error("Peer rejected the sync: {0}")]
+    PeerRejected(String),
+    #[error("Invalid peer URL '{0}': must be an http(s) URL")]
+    InvalidUrl(String),
+}
+
+/// The replicated part of a task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncedTask {
+    pub id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub status: TaskStatus,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Task> for SyncedTask {
+    fn from(task: Task) -> Self {
+        Self {
+            id: task.id,
+            title: task.title,
+            description: task.description,
+            status: task.status,
+            updated_at: task.updated_at,
+        }
+    }
+}
+
+/// Body of a sync request and of the peer's answer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerSyncPayload {
+    pub tasks: Vec<SyncedTask>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, TS)]
+pub struct PeerSyncReport {
+    /// Tasks sent to the peer
+    pub sent: usize,
+    /// Tasks created or updated here from the peer's copy
+    pub applied: usize,
+}
+
+pub fn validate_peer_url(peer_url: &str) -> Result<(), PeerSyncError> {
+    match Url::parse(peer_url) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") && url.host().is_some() => Ok(()),
+        _ => Err(PeerSyncError::InvalidUrl(peer_url.to_string())),
+    }
+}
+
+/// Incoming tasks that are new here or newer than the local copy
+pub fn newer_tasks(local: &[SyncedTask], incoming: Vec<SyncedTask>) -> Vec<SyncedTask> {
+    let local: HashMap<Uuid, DateTime<Utc>> = local
+        .iter()
+        .map(|task| (task.id, task.updated_at))
+        .collect();
+    incoming
+        .into_iter()
+        .filter(|task| {
+            local
+                .get(&task.id)
+                .is_none_or(|updated_at| task.updated_at > *updated_at)
+        })
+        .collect()
+}
+
+/// Current copy of a project's tasks
+pub async fn snapshot(pool: &SqlitePool, project_id: Uuid) -> Result<Vec<SyncedTask>, sqlx::Error> {
+    let tasks = Task::find_by_project_id_with_attempt_status(pool, project_id).await?;
+    Ok(tasks.into_iter().map(|task| task.task.into()).collect())
+}
+
+/// Apply the peer's tasks to `project_id`, returning how many changed here
+pub async fn apply(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    incoming: Vec<SyncedTask>,
+) -> Result<usize, sqlx::Error> {
+    let local = snapshot(pool, project_id).await?;
+    let mut applied = 0;
+    for task in newer_tasks(&local, incoming) {
+        // The upsert re-checks updated_at in case the task changed meanwhile
+        if Task::upsert_replicated(
+            pool,
+            task.id,
+            project_id,
+            &task.title,
+            task.description.as_deref(),
+            task.status,
+            task.updated_at,
+        )
+        .await?
+        {
+            applied += 1;
+        }
+    }
+    Ok(applied)
+}
+
+/// Exchange tasks with the peer and keep the newest copy of each on both sides
+pub async fn sync_project(
+    pool: &SqlitePool,
+    http: &reqwest::Client,
+    settings: &ProjectPeerSyncSettings,
+) -> Result<PeerSyncReport, PeerSyncError> {
+    validate_peer_url(&settings.peer_url)?;
+    let tasks = snapshot(pool, settings.project_id).await?;
+    let sent = tasks.len();
+
+    let response: ApiResponse<PeerSyncPayload> = http
+        .post(format!(
+            "{}/api/peer-sync/projects/{}",
+            settings.peer_url.trim_end_matches('/'),
+            settings.remote_project_id
+        ))
+        .bearer_auth(&settings.token)
+        .timeout(REQUEST_TIMEOUT)
+        .json(&PeerSyncPayload { tasks })
+        .send()
+        .await?
+        .json()
+        .await?;
+    if !response.is_success() {
+        return Err(PeerSyncError::PeerRejected(
+            response.message().unwrap_or("unknown error").to_string(),
+        ));
+    }
+    let incoming = response
+        .into_data()
+        .map(|data| data.tasks)
+        .unwrap_or_default();
+
+    let applied = apply(pool, settings.project_id, incoming).await?;
+    ProjectPeerSyncSettings::mark_synced(pool, settings.project_id).await?;
+    Ok(PeerSyncReport { sent, applied })
+}
+
+/// Service that keeps every paired project in sync with its peer
+pub struct PeerSyncService {
+    db: DBService,
+    config: Arc<RwLock<Config>>,
+    http: reqwest::Client,
+    poll_interval: Duration,
+}
+
+impl PeerSyncService {
+    pub async fn spawn(db: DBService, config: Arc<RwLock<Config>>) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            db,
+            config,
+            http: reqwest::Client::new(),
+            poll_interval: Duration::from_secs(120),
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(self) {
+        info!(
+            "Starting peer sync service with interval {:?}",
+            self.poll_interval
+        );
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            // Syncing changes tasks, which maintenance mode holds back
+            if self.config.read().await.maintenance_mode {
+                continue;
+            }
+            if let Err(e) = self.sync_all().await {
+                error!("Error syncing projects with peers: {}", e);
+            }
+        }
+    }
+
+    async fn sync_all(&self) -> Result<(), sqlx::Error> {
+        for settings in ProjectPeerSyncSettings::find_all(&self.db.pool).await? {
+            match sync_project(&self.db.pool, &self.http, &settings).await {
+                Ok(report) if report.applied > 0 => info!(
+                    "Synced project {} with {}: {:?}",
+                    settings.project_id, settings.peer_url, report
+                ),
+                Ok(_) => {}
+                Err(e) => error!(
+                    "Failed to sync project {} with {}: {}",
+                    settings.project_id, settings.peer_url, e
+                ),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: Uuid, title: &str, updated_at: &str) -> SyncedTask {
+        SyncedTask {
+            id,
+            title: title.to_string(),
+            description: None,
+            status: TaskStatus::Todo,
+            updated_at: updated_at.parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_newer_tasks_keeps_last_writer() {
+        let (stale, fresh, same, new) = (
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        );
+        let local = vec![
+            task(stale, "Local stale", "2026-01-02T00:00:00Z"),
+            task(fresh, "Local fresh", "2026-01-03T00:00:00Z"),
+            task(same, "Local same", "2026-01-01T00:00:00Z"),
+        ];
+        let incoming = vec![
+            task(stale, "Remote newer", "2026-01-02T00:00:01Z"),
+            task(fresh, "Remote older", "2026-01-02T00:00:00Z"),
+            task(same, "Remote same", "2026-01-01T00:00:00Z"),
+            task(new, "Remote only", "2025-12-31T00:00:00Z"),
+        ];
+
+        let titles: Vec<String> = newer_tasks(&local, incoming)
+            .into_iter()
+            .map(|task| task.title)
+            .collect();
+        assert_eq!(titles, vec!["Remote newer", "Remote only"]);
+    }
+
+    #[test]
+    fn test_validate_peer_url() {
+        assert!(validate_peer_url("http://homeserver:3000").is_ok());
+        assert!(validate_peer_url("https://kanban.example.com/").is_ok());
+        assert!(validate_peer_url("ftp://homeserver").is_err());
+        assert!(validate_peer_url("homeserver:3000").is_err());
+        assert!(validate_peer_url("").is_err());
+    }
+}
@@ -0,0 +1,286 @@
+//! Post-merge verification.
+//!
+//! After an attempt's PR merges, [`PostMergeVerifier`] checks out the updated
+//! base branch in a throwaway worktree and runs the configured build and test
+//! command. When the command fails the task is reopened with the output
+//! appended to its description, so the next attempt's prompt starts from the
+//! breakage.
+
+use std::{path::Path, process::Stdio, time::Duration};
+
+use db::{
+    DBService,
+    models::{
+        merge::PrMerge,
+        repo::Repo,
+        task::{Task, TaskStatus},
+    },
+};
+use git::{GitService, GitServiceError};
+use sqlx::error::Error as SqlxError;
+use thiserror::Error;
+use tokio::process::Command;
+use tracing::{info, warn};
+use utils::shell::get_shell_command;
+use uuid::Uuid;
+
+use crate::services::{
+    telegram::{TelegramService, escape_html},
+    worktree_manager::WorktreeManager,
+};
+
+/// Upper bound for a single verification run
+pub const VERIFY_TIMEOUT: Duration = Duration::from_secs(20 * 60);
+
+/// Output lines kept in the reopened task's description
+const OUTPUT_TAIL_LINES: usize = 40;
+
+#[derive(Debug, Error)]
+pub enum PostMergeVerificationError {
+    #[error(transparent)]
+    Git(#[from] GitServiceError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Sqlx(#[from] SqlxError),
+    #[error("Repository {0} not found")]
+    RepoNotFound(Uuid),
+    #[error("Task join error: {0}")]
+    TaskJoin(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerificationResult {
+    pub passed: bool,
+    /// Combined stdout and stderr of the command
+    pub output: String,
+}
+
+/// Run `command` in `dir`, capturing its output; a timeout counts as a failure
+pub async fn run_verification_command(
+    dir: &Path,
+    command: &str,
+) -> std::io::Result<VerificationResult> {
+    let (shell_cmd, shell_arg) = get_shell_command();
+    let child = Command::new(shell_cmd)
+        .arg(shell_arg)
+        .arg(command)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    match tokio::time::timeout(VERIFY_TIMEOUT, child.wait_with_output()).await {
+        Ok(output) => {
+            let output = output?;
+            let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+            text.push_str(&String::from_utf8_lossy(&output.stderr));
+            Ok(VerificationResult {
+                passed: output.status.success(),
+                output: text,
+            })
+        }
+        Err(_) => Ok(VerificationResult {
+            passed: false,
+            output: format!("Timed out after {} minutes", VERIFY_TIMEOUT.as_secs() / 60),
+        }),
+    }
+}
+
+fn tail_lines(text: &str, count: usize) -> String {
+    let lines: Vec<&str> = text.trim_end().lines().collect();
+    lines[lines.len().saturating_sub(count)..].join("\n")
+}
+
+/// Markdown section appended to the reopened task's description
+fn format_failure_context(command: &str, branch: &str, pr_number: i64, output: &str) -> String {
+    format!(
+        "## Post-merge verification failed\n\n`{command}` failed on `{branch}` after PR #{pr_number} merged:\n\n```\n{}\n```",
+        tail_lines(output, OUTPUT_TAIL_LINES)
+    )
+}
+
+fn format_failure_alert(task_title: &str, command: &str, branch: &str, pr_number: i64) -> String {
+    format!(
+        "🔴 <b>Post-merge verification failed</b>\n\n<b>{}</b>\n<code>{}</code> failed on <code>{}</code> after PR #{} merged. The task was reopened.",
+        escape_html(task_title),
+        escape_html(command),
+        escape_html(branch),
+        pr_number
+    )
+}
+
+/// Runs the verification command against a merged PR's base branch
+#[derive(Clone)]
+pub struct PostMergeVerifier {
+    db: DBService,
+    git: GitService,
+    telegram: Option<TelegramService>,
+}
+
+impl PostMergeVerifier {
+    pub fn new(db: DBService, telegram: Option<TelegramService>) -> Self {
+        Self {
+            db,
+            git: GitService::new(),
+            telegram,
+        }
+    }
+
+    /// Verify `pr_merge`'s base branch and reopen `task_id` if it is broken.
+    /// Returns whether the verification passed.
+    pub async fn verify(
+        &self,
+        pr_merge: &PrMerge,
+        task_id: Uuid,
+        command: &str,
+    ) -> Result<bool, PostMergeVerificationError> {
+        let repo = Repo::find_by_id(&self.db.pool, pr_merge.repo_id)
+            .await?
+            .ok_or(PostMergeVerificationError::RepoNotFound(pr_merge.repo_id))?;
+        let worktree_path =
+            WorktreeManager::get_worktree_base_dir().join(format!("verify-{}", Uuid::new_v4()));
+
+        let git = self.git.clone();
+        let repo_path = repo.path.clone();
+        let branch = pr_merge.target_branch_name.clone();
+        let checkout_path = worktree_path.clone();
+        tokio::task::spawn_blocking(move || {
+            let rev = git.fetch_remote_tracking_branch(&repo_path, &branch)?;
+            git.add_detached_worktree(&repo_path, &checkout_path, &rev)
+        })
+        .await
+        .map_err(|e| PostMergeVerificationError::TaskJoin(e.to_string()))??;
+
+        let result = run_verification_command(&worktree_path, command).await;
+
+        let git = self.git.clone();
+        let repo_path = repo.path.clone();
+        let checkout_path = worktree_path.clone();
+        match tokio::task::spawn_blocking(move || {
+            git.remove_worktree(&repo_path, &checkout_path, true)
+        })
+        .await
+        {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => warn!(
+                "Failed to remove verification worktree {}: {}",
+                worktree_path.display(),
+                e
+            ),
+            Err(e) => warn!("Verification worktree cleanup panicked: {}", e),
+        }
+
+        let result = result?;
+        if result.passed {
+            info!(
+                "Post-merge verification passed for PR #{} on {}",
+                pr_merge.pr_info.number, pr_merge.target_branch_name
+            );
+            return Ok(true);
+        }
+
+        warn!(
+            "Post-merge verification failed for PR #{} on {}, reopening task {}",
+            pr_merge.pr_info.number, pr_merge.target_branch_name, task_id
+        );
+        self.reopen_task(pr_merge, task_id, command, &result.output)
+            .await?;
+        Ok(false)
+    }
+
+    async fn reopen_task(
+        &self,
+        pr_merge: &PrMerge,
+        task_id: Uuid,
+        command: &str,
+        output: &str,
+    ) -> Result<(), PostMergeVerificationError> {
+        let Some(task) = Task::find_by_id(&self.db.pool, task_id).await? else {
+            return Ok(());
+        };
+
+        let context = format_failure_context(
+            command,
+            &pr_merge.target_branch_name,
+            pr_merge.pr_info.number,
+            output,
+        );
+        let description = match task.description.as_deref() {
+            Some(description) if !description.trim().is_empty() => {
+                format!("{}\n\n{context}", description.trim_end())
+            }
+            _ => context,
+        };
+        Task::update(
+            &self.db.pool,
+            task.id,
+            task.project_id,
+            task.title.clone(),
+            Some(description),
+            TaskStatus::Todo,
+            task.parent_workspace_id,
+            None,
+        )
+        .await?;
+
+        if let Some(telegram) = &self.telegram
+            && let Err(e) = telegram
+                .send_critical_notification(&format_failure_alert(
+                    &task.title,
+                    command,
+                    &pr_merge.target_branch_name,
+                    pr_merge.pr_info.number,
+                ))
+                .await
+        {
+            warn!("Failed to send post-merge verification alert: {}", e);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_failure_context_keeps_output_tail() {
+        let output: String = (1..=50).map(|i| format!("line {i}\n")).collect();
+        let context = format_failure_context("cargo test", "main", 42, &output);
+
+        assert!(context.starts_with("## Post-merge verification failed"));
+        assert!(context.contains("`cargo test` failed on `main` after PR #42 merged"));
+        assert!(!context.contains("line 10\n"));
+        assert!(context.contains("line 11\n"));
+        assert!(context.ends_with("line 50\n```"));
+    }
+
+    #[test]
+    fn test_format_failure_alert_escapes_html() {
+        let alert = format_failure_alert("<fix>", "make && make test", "main", 7);
+        assert!(alert.contains("&lt;fix&gt;"));
+        assert!(alert.contains("make &amp;&amp; make test"));
+        assert!(alert.contains("PR #7"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_verification_command_captures_output() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let passed = run_verification_command(dir.path(), "echo ok")
+            .await
+            .unwrap();
+        assert!(passed.passed);
+        assert_eq!(passed.output.trim(), "ok");
+
+        let failed = run_verification_command(dir.path(), "echo broken >&2; exit 1")
+            .await
+            .unwrap();
+        assert!(!failed.passed);
+        assert_eq!(failed.output.trim(), "broken");
+    }
+}
@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
 use db::{
     DBService,
@@ -11,12 +11,16 @@ use db::{
 use serde_json::json;
 use sqlx::error::Error as SqlxError;
 use thiserror::Error;
-use tokio::time::interval;
+use tokio::{sync::RwLock, time::interval};
 use tracing::{debug, error, info};
 
 use crate::services::{
     analytics::AnalyticsContext,
+    config::Config,
     git_host::{self, GitHostError, GitHostProvider},
+    outbox::OutboxIntent,
+    post_merge::PostMergeVerifier,
+    telegram::TelegramService,
 };
 
 #[derive(Debug, Error)]
@@ -34,17 +38,23 @@ pub struct PrMonitorService {
     db: DBService,
     poll_interval: Duration,
     analytics: Option<AnalyticsContext>,
+    telegram: Option<TelegramService>,
+    config: Arc<RwLock<Config>>,
 }
 
 impl PrMonitorService {
     pub async fn spawn(
         db: DBService,
         analytics: Option<AnalyticsContext>,
+        telegram: Option<TelegramService>,
+        config: Arc<RwLock<Config>>,
     ) -> tokio::task::JoinHandle<()> {
         let service = Self {
             db,
             poll_interval: Duration::from_secs(60), // Check every minute
             analytics,
+            telegram,
+            config,
         };
         tokio::spawn(async move {
             service.start().await;
@@ -119,16 +129,48 @@ impl PrMonitorService {
                     "PR #{} was merged, updating task {} to done and archiving workspace",
                     pr_merge.pr_info.number, workspace.task_id
                 );
-                Task::update_status(&self.db.pool, workspace.task_id, TaskStatus::Done).await?;
+                let mut tx = self.db.pool.begin().await?;
+                Task::update_status(&mut *tx, workspace.task_id, TaskStatus::Done).await?;
+                OutboxIntent::PrMergedNotification {
+                    task_id: workspace.task_id,
+                }
+                .enqueue(&mut *tx)
+                .await?;
+                tx.commit().await?;
 
                 // Archive workspace unless pinned
                 if !workspace.pinned {
                     Workspace::set_archived(&self.db.pool, workspace.id, true).await?;
                 }
 
+                let task = Task::find_by_id(&self.db.pool, workspace.task_id).await?;
+
+                // Check the updated base branch in the background so a slow
+                // build does not hold up the other PRs
+                let command = self
+                    .config
+                    .read()
+                    .await
+                    .post_merge_verification
+                    .command
+                    .clone();
+                if let Some(command) = command.filter(|command| !command.trim().is_empty()) {
+                    let verifier = PostMergeVerifier::new(self.db.clone(), self.telegram.clone());
+                    let pr_merge = pr_merge.clone();
+                    let task_id = workspace.task_id;
+                    tokio::spawn(async move {
+                        if let Err(e) = verifier.verify(&pr_merge, task_id, &command).await {
+                            error!(
+                                "Post-merge verification for PR #{} could not run: {}",
+                                pr_merge.pr_info.number, e
+                            );
+                        }
+                    });
+                }
+
                 // Track analytics event
                 if let Some(analytics) = &self.analytics
-                    && let Ok(Some(task)) = Task::find_by_id(&self.db.pool, workspace.task_id).await
+                    && let Some(task) = task
                 {
                     analytics.analytics_service.track_event(
                         &analytics.user_id,
@@ -0,0 +1,281 @@
+//! Preview screenshots for web-app projects.
+//!
+//! Projects with [`ProjectPreviewSettings`] get a screenshot after each
+//! successful attempt: the workspace's dev server script is started in the
+//! attempt worktree, `preview_url` is polled until it responds and a headless
+//! Chromium captures the page. The PNG is stored as an attempt artifact.
+//!
+//! Nothing is captured when `preview_url` already answers before the dev
+//! server starts, or when the dev server exits before it answers, as the page
+//! would then be served by something else running on the machine.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use command_group::{AsyncCommandGroup, AsyncGroupChild};
+use db::models::{
+    project_preview::ProjectPreviewSettings, task::Task, workspace::Workspace,
+    workspace_artifact::WorkspaceArtifact, workspace_repo::WorkspaceRepo,
+};
+use sqlx::SqlitePool;
+use tokio::{process::Command, sync::RwLock};
+use utils::shell::{get_shell_command, resolve_executable_path};
+
+use crate::services::{
+    artifact::{ArtifactError, ArtifactService},
+    config::Config,
+};
+
+/// Browsers tried in order when looking for a headless screenshot tool
+const BROWSER_CANDIDATES: &[&str] = &[
+    "chromium",
+    "chromium-browser",
+    "google-chrome",
+    "google-chrome-stable",
+    "chrome",
+];
+/// Upper bound for the browser run itself, after the preview is reachable
+const SCREENSHOT_TIMEOUT: Duration = Duration::from_secs(60);
+const WINDOW_SIZE: &str = "1280,800";
+const SCREENSHOT_FILENAME: &str = "preview.png";
+
+#[derive(Debug, thiserror::Error)]
+pub enum PreviewError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error(transparent)]
+    Artifact(#[from] ArtifactError),
+
+    #[error("No Chromium or Chrome executable found")]
+    BrowserNotFound,
+
+    #[error("No dev server script configured for this workspace")]
+    NoDevServerScript,
+
+    #[error("Workspace has no worktree")]
+    NoWorktree,
+
+    #[error("Preview did not respond within {0}s")]
+    StartupTimeout(u64),
+
+    #[error("{0} is already served by another process")]
+    AlreadyServed(String),
+
+    #[error("Dev server exited before the preview responded")]
+    DevServerExited,
+
+    #[error("Browser failed to capture screenshot: {0}")]
+    Capture(String),
+}
+
+/// Command-line arguments for a headless Chromium screenshot of `url`
+fn browser_args(profile_dir: &Path, output: &Path, url: &str) -> Vec<String> {
+    vec![
+        "--headless=new".to_string(),
+        "--disable-gpu".to_string(),
+        "--hide-scrollbars".to_string(),
+        "--no-first-run".to_string(),
+        format!("--user-data-dir={}", profile_dir.display()),
+        format!("--window-size={WINDOW_SIZE}"),
+        format!("--screenshot={}", output.display()),
+        url.to_string(),
+    ]
+}
+
+async fn find_browser() -> Option<PathBuf> {
+    for candidate in BROWSER_CANDIDATES {
+        if let Some(path) = resolve_executable_path(candidate).await {
+            return Some(path);
+        }
+    }
+    None
+}
+
+#[derive(Clone)]
+pub struct PreviewCaptureService {
+    pool: SqlitePool,
+    artifacts: ArtifactService,
+    config: Arc<RwLock<Config>>,
+    http: reqwest::Client,
+}
+
+impl PreviewCaptureService {
+    pub fn new(pool: SqlitePool, artifacts: ArtifactService, config: Arc<RwLock<Config>>) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .unwrap_or_default();
+        Self {
+            pool,
+            artifacts,
+            config,
+            http,
+        }
+    }
+
+    pub fn artifact_path(&self, artifact: &WorkspaceArtifact) -> PathBuf {
+        self.artifacts.get_absolute_path(artifact)
+    }
+
+    /// Boot the dev server for a finished attempt and store a screenshot of the
+    /// preview. Returns `None` for projects that are not flagged as web apps.
+    pub async fn capture_for_workspace(
+        &self,
+        task: &Task,
+        workspace: &Workspace,
+    ) -> Result<Option<WorkspaceArtifact>, PreviewError> {
+        let Some(settings) =
+            ProjectPreviewSettings::find_by_project_id(&self.pool, task.project_id).await?
+        else {
+            return Ok(None);
+        };
+
+        let worktree = workspace
+            .container_ref
+            .as_deref()
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .ok_or(PreviewError::NoWorktree)?;
+        let repos = WorkspaceRepo::find_repos_for_workspace(&self.pool, workspace.id).await?;
+        let (repo_dir, script) = repos
+            .iter()
+            .find_map(|repo| {
+                repo.dev_server_script
+                    .as_deref()
+                    .filter(|script| !script.trim().is_empty())
+                    .map(|script| (worktree.join(&repo.name), script.to_string()))
+            })
+            .ok_or(PreviewError::NoDevServerScript)?;
+        let browser = find_browser().await.ok_or(PreviewError::BrowserNotFound)?;
+        if self.responds(&settings.preview_url).await {
+            return Err(PreviewError::AlreadyServed(settings.preview_url));
+        }
+
+        let mut server = spawn_dev_server(&repo_dir, &script)?;
+        let screenshot = self
+            .screenshot_when_ready(&mut server, &browser, &settings)
+            .await;
+        if let Err(e) = server.kill().await {
+            tracing::warn!("Failed to stop preview dev server: {}", e);
+        }
+        let _ = server.wait().await;
+        let png = screenshot?;
+
+        let quota_bytes = self.config.read().await.artifact_quota_mb * 1024 * 1024;
+        let artifact = self
+            .artifacts
+            .store_artifact(
+                workspace,
+                &png,
+                SCREENSHOT_FILENAME,
+                Some("image/png".to_string()),
+                quota_bytes,
+            )
+            .await?;
+        Ok(Some(artifact))
+    }
+
+    async fn screenshot_when_ready(
+        &self,
+        server: &mut AsyncGroupChild,
+        browser: &Path,
+        settings: &ProjectPreviewSettings,
+    ) -> Result<Vec<u8>, PreviewError> {
+        let startup_timeout = settings.startup_timeout_secs.max(1) as u64;
+        self.wait_for_url(
+            server,
+            &settings.preview_url,
+            Duration::from_secs(startup_timeout),
+        )
+        .await?;
+
+        let temp_dir = tempfile::tempdir()?;
+        let output_path = temp_dir.path().join(SCREENSHOT_FILENAME);
+        let args = browser_args(
+            &temp_dir.path().join("profile"),
+            &output_path,
+            &settings.preview_url,
+        );
+        let output = tokio::time::timeout(
+            SCREENSHOT_TIMEOUT,
+            Command::new(browser).args(&args).kill_on_drop(true).output(),
+        )
+        .await
+        .map_err(|_| PreviewError::Capture("browser timed out".to_string()))??;
+
+        if !output_path.exists() {
+            return Err(PreviewError::Capture(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        Ok(tokio::fs::read(&output_path).await?)
+    }
+
+    /// Whether `url` answers with any HTTP response
+    async fn responds(&self, url: &str) -> bool {
+        self.http.get(url).send().await.is_ok()
+    }
+
+    /// Poll `url` until it answers with any HTTP response, as long as the dev
+    /// server `server` is running
+    async fn wait_for_url(
+        &self,
+        server: &mut AsyncGroupChild,
+        url: &str,
+        timeout: Duration,
+    ) -> Result<(), PreviewError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if server.try_wait()?.is_some() {
+                return Err(PreviewError::DevServerExited);
+            }
+            if self.responds(url).await {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(PreviewError::StartupTimeout(timeout.as_secs()));
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    }
+}
+
+fn spawn_dev_server(dir: &Path, script: &str) -> Result<AsyncGroupChild, PreviewError> {
+    let (shell_cmd, shell_arg) = get_shell_command();
+    let child = Command::new(shell_cmd)
+        .kill_on_drop(true)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .arg(shell_arg)
+        .arg(script)
+        .current_dir(dir)
+        .group_spawn()?;
+    Ok(child)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_browser_args_end_with_url() {
+        let args = browser_args(
+            Path::new("/tmp/profile"),
+            Path::new("/tmp/shot.png"),
+            "http://localhost:3000",
+        );
+
+        assert_eq!(args.first().map(String::as_str), Some("--headless=new"));
+        assert!(args.contains(&"--screenshot=/tmp/shot.png".to_string()));
+        assert!(args.contains(&"--user-data-dir=/tmp/profile".to_string()));
+        assert_eq!(args.last().map(String::as_str), Some("http://localhost:3000"));
+    }
+}
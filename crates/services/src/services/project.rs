@@ -8,8 +8,10 @@ use db::models::{
     project_repo::{CreateProjectRepo, ProjectRepo},
     repo::Repo,
 };
+use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
 use thiserror::Error;
+use ts_rs::TS;
 use uuid::Uuid;
 
 use super::{
@@ -41,6 +43,8 @@ pub enum ProjectServiceError {
     GitError(String),
     #[error("Remote client error: {0}")]
     RemoteClient(String),
+    #[error("Projects not found: {0:?}")]
+    ProjectsNotFound(Vec<Uuid>),
 }
 
 pub type Result<T> = std::result::Result<T, ProjectServiceError>;
@@ -58,6 +62,50 @@ impl From<RepoError> for ProjectServiceError {
     }
 }
 
+/// Change applied to every project of a batch
+#[derive(Debug, Clone, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(tag = "type", rename_all = "snake_case")]
+pub enum BatchProjectOperation {
+    Archive,
+    Unarchive,
+    /// Hide the projects while keeping their tasks and history
+    Delete,
+    /// Bring back soft-deleted projects
+    Restore,
+    UpdateSettings {
+        /// Empty string clears the directory
+        default_agent_working_dir: String,
+    },
+}
+
+impl BatchProjectOperation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Archive => "archive",
+            Self::Unarchive => "unarchive",
+            Self::Delete => "delete",
+            Self::Restore => "restore",
+            Self::UpdateSettings { .. } => "update_settings",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct BatchProjectRequest {
+    pub project_ids: Vec<Uuid>,
+    pub operation: BatchProjectOperation,
+}
+
+/// Outcome of a batch; the batch is all-or-nothing, so either every project
+/// is listed here or none was touched
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, TS)]
+pub struct BatchProjectResult {
+    pub updated: Vec<Uuid>,
+    /// Projects that were already in the requested state
+    pub unchanged: Vec<Uuid>,
+}
+
 #[derive(Clone, Default)]
 pub struct ProjectService;
 
@@ -211,6 +259,59 @@ impl ProjectService {
         Ok(rows_affected)
     }
 
+    /// Apply `request.operation` to all of its projects in one transaction.
+    /// Fails without changing anything if any project does not exist.
+    pub async fn batch_update(
+        &self,
+        pool: &SqlitePool,
+        request: &BatchProjectRequest,
+    ) -> Result<BatchProjectResult> {
+        let mut project_ids = Vec::new();
+        for id in &request.project_ids {
+            if !project_ids.contains(id) {
+                project_ids.push(*id);
+            }
+        }
+        let include_deleted = matches!(request.operation, BatchProjectOperation::Restore);
+
+        let mut tx = pool.begin().await?;
+        let mut missing = Vec::new();
+        for id in &project_ids {
+            if !Project::exists(&mut *tx, *id, include_deleted).await? {
+                missing.push(*id);
+            }
+        }
+        if !missing.is_empty() {
+            return Err(ProjectServiceError::ProjectsNotFound(missing));
+        }
+
+        let mut result = BatchProjectResult::default();
+        for id in project_ids {
+            let changed = match &request.operation {
+                BatchProjectOperation::Archive => Project::set_archived(&mut *tx, id, true).await?,
+                BatchProjectOperation::Unarchive => {
+                    Project::set_archived(&mut *tx, id, false).await?
+                }
+                BatchProjectOperation::Delete => Project::set_deleted(&mut *tx, id, true).await?,
+                BatchProjectOperation::Restore => Project::set_deleted(&mut *tx, id, false).await?,
+                BatchProjectOperation::UpdateSettings {
+                    default_agent_working_dir,
+                } => {
+                    let dir = Some(default_agent_working_dir.trim()).filter(|d| !d.is_empty());
+                    Project::set_default_agent_working_dir(&mut *tx, id, dir).await?
+                }
+            };
+            if changed {
+                result.updated.push(id);
+            } else {
+                result.unchanged.push(id);
+            }
+        }
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
     pub async fn get_repositories(&self, pool: &SqlitePool, project_id: Uuid) -> Result<Vec<Repo>> {
         let repos = ProjectRepo::find_repos_for_project(pool, project_id).await?;
         Ok(repos)
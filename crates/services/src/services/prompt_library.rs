@@ -0,0 +1,188 @@
+//! Prompt snippet rendering.
+//!
+//! Snippets use `{{name}}` placeholders, optionally with a fallback value as
+//! `{{name|fallback}}`. Task text can pull a snippet in with `#snippet-name`.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::LazyLock,
+};
+
+use db::models::prompt_snippet::PromptSnippet;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use thiserror::Error;
+use ts_rs::TS;
+use uuid::Uuid;
+
+const MAX_NAME_LEN: usize = 64;
+
+static PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*(?:\|([^}]*))?\}\}").unwrap()
+});
+/// `#name` at the start of the text or after whitespace; names start with a
+/// letter so issue references like `#123` are left alone
+static SNIPPET_REFERENCE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|\s)#([a-z][a-z0-9_-]*)").unwrap());
+
+#[derive(Debug, Error)]
+pub enum PromptLibraryError {
+    #[error(
+        "Invalid snippet name '{0}': use lowercase letters, digits, '-' or '_', starting with a letter"
+    )]
+    InvalidName(String),
+}
+
+#[derive(Debug, Clone, Default, Deserialize, TS)]
+pub struct RenderPromptRequest {
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+pub struct RenderedPrompt {
+    pub text: String,
+    /// Placeholders without a value or fallback, left as-is in `text`
+    pub missing: Vec<String>,
+}
+
+pub fn validate_name(name: &str) -> Result<(), PromptLibraryError> {
+    let valid = name.len() <= MAX_NAME_LEN
+        && name.chars().next().is_some_and(|c| c.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(PromptLibraryError::InvalidName(name.to_string()))
+    }
+}
+
+/// Variable names used by `content`, in order of first use
+pub fn variables(content: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    PLACEHOLDER
+        .captures_iter(content)
+        .map(|captures| captures[1].to_string())
+        .filter(|name| seen.insert(name.clone()))
+        .collect()
+}
+
+/// Substitute `variables` into `content`
+pub fn render(content: &str, variables: &HashMap<String, String>) -> RenderedPrompt {
+    let mut missing = Vec::new();
+    let text = PLACEHOLDER
+        .replace_all(content, |captures: &Captures| {
+            let name = &captures[1];
+            match (variables.get(name), captures.get(2)) {
+                (Some(value), _) => value.clone(),
+                (None, Some(fallback)) => fallback.as_str().trim().to_string(),
+                (None, None) => {
+                    if !missing.iter().any(|m| m == name) {
+                        missing.push(name.to_string());
+                    }
+                    captures[0].to_string()
+                }
+            }
+        })
+        .into_owned();
+    RenderedPrompt { text, missing }
+}
+
+/// Snippet names referenced as `#name` in `text`, in order of first use
+pub fn snippet_references(text: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    SNIPPET_REFERENCE
+        .captures_iter(text)
+        .map(|captures| captures[1].to_string())
+        .filter(|name| seen.insert(name.clone()))
+        .collect()
+}
+
+/// Remove `#name` references for `names` from `text`, collapsing the spaces
+/// they leave behind
+pub fn strip_references(text: &str, names: &[String]) -> String {
+    text.split_whitespace()
+        .filter(|word| {
+            word.strip_prefix('#')
+                .is_none_or(|name| !names.iter().any(|n| n == name))
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Snippets referenced as `#name` in `text` that exist for the project.
+/// Unknown names are skipped so the caller can leave them in the text.
+pub async fn find_referenced_snippets(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    text: &str,
+) -> Result<Vec<PromptSnippet>, sqlx::Error> {
+    let mut snippets = Vec::new();
+    for name in snippet_references(text) {
+        if let Some(snippet) = PromptSnippet::find_by_name(pool, project_id, &name).await? {
+            snippets.push(snippet);
+        }
+    }
+    Ok(snippets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("bug-fix_2").is_ok());
+        assert!(validate_name("Bugfix").is_err());
+        assert!(validate_name("2fast").is_err());
+        assert!(validate_name("has space").is_err());
+        assert!(validate_name("").is_err());
+    }
+
+    #[test]
+    fn test_variables_in_order_without_duplicates() {
+        let content = "Fix {{ area }} in {{project_name}}. Touch only {{area|src}}.";
+        assert_eq!(variables(content), vec!["area", "project_name"]);
+    }
+
+    #[test]
+    fn test_render_uses_values_then_fallbacks() {
+        let rendered = render(
+            "Fix {{area}} using {{style|small commits}}. Ticket {{ticket}}.",
+            &vars(&[("area", "login")]),
+        );
+
+        assert_eq!(
+            rendered.text,
+            "Fix login using small commits. Ticket {{ticket}}."
+        );
+        assert_eq!(rendered.missing, vec!["ticket"]);
+    }
+
+    #[test]
+    fn test_snippet_references() {
+        assert_eq!(
+            snippet_references("#bugfix Login fails #123 a#b #tests #bugfix"),
+            vec!["bugfix", "tests"]
+        );
+    }
+
+    #[test]
+    fn test_strip_references_keeps_unresolved() {
+        let names = vec!["bugfix".to_string()];
+        assert_eq!(
+            strip_references("Fix login  #bugfix #unknown", &names),
+            "Fix login #unknown"
+        );
+    }
+}
@@ -0,0 +1,234 @@
+//! Task notifications sent through Pushover.
+//!
+//! Messages go to the user or group key in `NotificationConfig::pushover`,
+//! from the Pushover application whose API token is configured there.
+//! Failures are sent at high priority, which bypasses the user's quiet hours.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use db::models::{notification_rule::NotificationChannel, task::Task};
+use serde::Deserialize;
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+use crate::services::{
+    config::{Config, PushoverConfig},
+    http_notifier,
+    notification_dispatcher::{NotificationEvent, Notifier, NotifierError},
+    telegram::TaskNotificationKind,
+};
+
+const MESSAGES_URL: &str = "https://api.pushover.net/1/messages.json";
+/// Pushover rejects titles longer than this
+const MAX_TITLE_CHARS: usize = 250;
+/// Pushover rejects messages longer than this
+const MAX_MESSAGE_CHARS: usize = 1024;
+
+#[derive(Debug, Error)]
+pub enum PushoverError {
+    #[error("Pushover notifications are not configured: {0}")]
+    NotConfigured(&'static str),
+
+    #[error("Pushover request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("Pushover returned HTTP {status}: {errors}")]
+    Rejected { status: u16, errors: String },
+}
+
+/// Body of an API response; `errors` is set when the request was refused
+#[derive(Debug, Deserialize)]
+struct PushoverResponse {
+    #[serde(default)]
+    errors: Vec<String>,
+}
+
+/// Message priority, from -2 (no alert) to 2 (repeated until acknowledged)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Priority {
+    /// Delivered without sound or vibration
+    Low = -1,
+    Normal = 0,
+    /// Bypasses the user's quiet hours
+    High = 1,
+}
+
+impl Priority {
+    fn for_kind(kind: TaskNotificationKind) -> Self {
+        match kind {
+            TaskNotificationKind::Failed => Self::High,
+            TaskNotificationKind::Done | TaskNotificationKind::InReview => Self::Normal,
+            TaskNotificationKind::Started | TaskNotificationKind::Cancelled => Self::Low,
+        }
+    }
+}
+
+/// What a notification says about a task
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PushoverMessage {
+    title: String,
+    message: String,
+    priority: Priority,
+}
+
+impl PushoverMessage {
+    fn for_task(task: &Task, kind: TaskNotificationKind, summary: Option<&str>) -> Self {
+        let event = match kind {
+            TaskNotificationKind::Started => "started",
+            TaskNotificationKind::InReview => "ready for review",
+            TaskNotificationKind::Failed => "failed",
+            TaskNotificationKind::Done => "completed",
+            TaskNotificationKind::Cancelled => "cancelled",
+        };
+        let message = match summary.map(str::trim).filter(|summary| !summary.is_empty()) {
+            Some(summary) => summary,
+            None => task.title.as_str(),
+        };
+        Self {
+            title: format!("Task {event}: {}", task.title)
+                .chars()
+                .take(MAX_TITLE_CHARS)
+                .collect(),
+            message: message.chars().take(MAX_MESSAGE_CHARS).collect(),
+            priority: Priority::for_kind(kind),
+        }
+    }
+}
+
+/// Sends task notifications to a Pushover user or group
+#[derive(Clone)]
+pub struct PushoverNotifier {
+    config: Arc<RwLock<Config>>,
+    http: reqwest::Client,
+}
+
+impl PushoverNotifier {
+    pub fn new(config: Arc<RwLock<Config>>) -> Self {
+        Self {
+            config,
+            http: http_notifier::client(),
+        }
+    }
+
+    /// Send a notification about `task`. Does nothing if Pushover is off.
+    pub async fn send_task_notification(
+        &self,
+        task: &Task,
+        kind: TaskNotificationKind,
+        summary: Option<&str>,
+    ) -> Result<(), PushoverError> {
+        let config = self.config.read().await.notifications.pushover.clone();
+        if !config.enabled {
+            return Ok(());
+        }
+        self.send(&config, &PushoverMessage::for_task(task, kind, summary))
+            .await
+    }
+
+    async fn send(
+        &self,
+        config: &PushoverConfig,
+        message: &PushoverMessage,
+    ) -> Result<(), PushoverError> {
+        let non_empty = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        let app_token =
+            non_empty(&config.app_token).ok_or(PushoverError::NotConfigured("no app token"))?;
+        let user_key =
+            non_empty(&config.user_key).ok_or(PushoverError::NotConfigured("no user key"))?;
+
+        let priority = (message.priority as i8).to_string();
+        let response = self
+            .http
+            .post(MESSAGES_URL)
+            .form(&[
+                ("token", app_token.as_str()),
+                ("user", user_key.as_str()),
+                ("title", message.title.as_str()),
+                ("message", message.message.as_str()),
+                ("priority", priority.as_str()),
+            ])
+            .send()
+            .await?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let errors = match response.json::<PushoverResponse>().await {
+            Ok(body) if !body.errors.is_empty() => body.errors.join("; "),
+            _ => "no error details".to_string(),
+        };
+        Err(PushoverError::Rejected {
+            status: status.as_u16(),
+            errors,
+        })
+    }
+}
+
+#[async_trait]
+impl Notifier for PushoverNotifier {
+    fn channel(&self) -> NotificationChannel {
+        NotificationChannel::Pushover
+    }
+
+    async fn wants(&self, _kind: TaskNotificationKind) -> bool {
+        self.config.read().await.notifications.pushover.enabled
+    }
+
+    async fn send(&self, event: &NotificationEvent) -> Result<(), NotifierError> {
+        self.send_task_notification(&event.task, event.kind, event.summary.as_deref())
+            .await?;
+        Ok(())
+    }
+
+    async fn send_text(&self, title: &str, body: &str) -> Result<(), NotifierError> {
+        let config = self.config.read().await.notifications.pushover.clone();
+        let message = PushoverMessage {
+            title: title.chars().take(MAX_TITLE_CHARS).collect(),
+            message: body.chars().take(MAX_MESSAGE_CHARS).collect(),
+            priority: Priority::Normal,
+        };
+        self.send(&config, &message).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_fixtures::task;
+
+    #[test]
+    fn test_failures_are_high_priority() {
+        let task = task("Add dark mode");
+
+        let done = PushoverMessage::for_task(&task, TaskNotificationKind::Done, Some("Added it."));
+        assert_eq!(done.title, "Task completed: Add dark mode");
+        assert_eq!(done.message, "Added it.");
+        assert_eq!(done.priority, Priority::Normal);
+
+        let failed = PushoverMessage::for_task(&task, TaskNotificationKind::Failed, Some("  "));
+        assert_eq!(failed.title, "Task failed: Add dark mode");
+        assert_eq!(failed.message, "Add dark mode");
+        assert_eq!(failed.priority, Priority::High);
+        assert_eq!(failed.priority as i8, 1);
+
+        let started = PushoverMessage::for_task(&task, TaskNotificationKind::Started, None);
+        assert_eq!(started.priority as i8, -1);
+    }
+
+    #[test]
+    fn test_long_messages_are_truncated() {
+        let task = task(&"x".repeat(300));
+        let summary = "y".repeat(2000);
+        let message = PushoverMessage::for_task(&task, TaskNotificationKind::Done, Some(&summary));
+        assert_eq!(message.title.chars().count(), MAX_TITLE_CHARS);
+        assert_eq!(message.message.chars().count(), MAX_MESSAGE_CHARS);
+    }
+}
@@ -55,6 +55,23 @@ impl QueuedMessageService {
         queued
     }
 
+    /// Queue a message for a session after the one already queued, if any.
+    /// The messages are sent together, with the settings of the first.
+    pub fn append_message(&self, session_id: Uuid, data: DraftFollowUpData) -> QueuedMessage {
+        let entry = self
+            .queue
+            .entry(session_id)
+            .and_modify(|queued| {
+                queued.data.message = format!("{}\n\n{}", queued.data.message, data.message);
+            })
+            .or_insert_with(|| QueuedMessage {
+                session_id,
+                data,
+                queued_at: Utc::now(),
+            });
+        entry.value().clone()
+    }
+
     /// Cancel/remove a queued message for a session
     pub fn cancel_queued(&self, session_id: Uuid) -> Option<QueuedMessage> {
         self.queue.remove(&session_id).map(|(_, v)| v)
@@ -71,6 +88,15 @@ impl QueuedMessageService {
         self.queue.remove(&session_id).map(|(_, v)| v)
     }
 
+    /// Number of sessions with a queued message
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
     /// Check if a session has a queued message
     pub fn has_queued(&self, session_id: Uuid) -> bool {
         self.queue.contains_key(&session_id)
@@ -90,3 +116,33 @@ impl Default for QueuedMessageService {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use executors::{executors::BaseCodingAgent, profile::ExecutorProfileId};
+
+    use super::*;
+
+    fn follow_up(message: &str) -> DraftFollowUpData {
+        DraftFollowUpData {
+            message: message.to_string(),
+            executor_profile_id: ExecutorProfileId::new(BaseCodingAgent::ClaudeCode),
+        }
+    }
+
+    #[test]
+    fn test_append_message_keeps_earlier_messages() {
+        let service = QueuedMessageService::new();
+        let session_id = Uuid::new_v4();
+
+        let queued = service.append_message(session_id, follow_up("first"));
+        assert_eq!(queued.data.message, "first");
+
+        let queued = service.append_message(session_id, follow_up("second"));
+        assert_eq!(queued.data.message, "first\n\nsecond");
+        assert_eq!(
+            service.get_queued(session_id).unwrap().data.message,
+            "first\n\nsecond"
+        );
+    }
+}
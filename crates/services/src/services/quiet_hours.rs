@@ -0,0 +1,191 @@
+//! Quiet hours: daily windows during which task notifications to some
+//! channels are held back.
+//!
+//! The dispatcher stores what it holds in `held_notifications`.
+//! [`QuietHoursWorker`] checks every minute for channels whose quiet hours
+//! are over and sends each of them one catch-up message listing the held
+//! events. Failures can be set to break through and go out right away.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use db::models::{
+    held_notification::HeldNotification,
+    notification_rule::{NotificationChannel, TaskNotificationKind},
+};
+use tokio::time::interval;
+use tracing::{error, info};
+
+use crate::services::{
+    config::{NotificationEventType, QuietHours},
+    notification_dispatcher::NotificationDispatcher,
+};
+
+/// Events listed in a catch-up message before the rest are only counted
+const MAX_CATCH_UP_LINES: usize = 20;
+
+/// Whether any of `quiet_hours` covers `channel` at `now`
+pub fn is_quiet(
+    quiet_hours: &[QuietHours],
+    channel: NotificationChannel,
+    now: DateTime<Utc>,
+) -> bool {
+    quiet_hours
+        .iter()
+        .any(|quiet| quiet.applies_to(channel) && quiet.is_quiet_at(now))
+}
+
+/// Whether a notification of `kind` to `channel` is held back at `now`
+pub fn holds(
+    quiet_hours: &[QuietHours],
+    channel: NotificationChannel,
+    kind: TaskNotificationKind,
+    now: DateTime<Utc>,
+) -> bool {
+    quiet_hours.iter().any(|quiet| {
+        quiet.applies_to(channel)
+            && quiet.is_quiet_at(now)
+            && !(quiet.failures_break_through && kind == TaskNotificationKind::Failed)
+    })
+}
+
+/// Title and body of the message sent once quiet hours end, oldest event
+/// first
+pub fn format_catch_up(held: &[HeldNotification]) -> (String, String) {
+    let title = match held.len() {
+        1 => "1 notification during quiet hours".to_string(),
+        n => format!("{n} notifications during quiet hours"),
+    };
+    let mut lines: Vec<String> = held
+        .iter()
+        .take(MAX_CATCH_UP_LINES)
+        .map(|notification| {
+            let event = match NotificationEventType::parse(&notification.event_type) {
+                Some(NotificationEventType::AttemptStarted) => "Task started",
+                Some(NotificationEventType::TaskInReview) => "Task ready for review",
+                Some(NotificationEventType::TaskDone) => "Task completed",
+                Some(NotificationEventType::TaskFailed) => "Task failed",
+                Some(NotificationEventType::TaskCancelled) => "Task cancelled",
+                Some(NotificationEventType::PrMerged) => "Pull request merged",
+                None => "Task updated",
+            };
+            format!("{event}: {}", notification.payload.task_title)
+        })
+        .collect();
+    if held.len() > MAX_CATCH_UP_LINES {
+        lines.push(format!("…and {} more", held.len() - MAX_CATCH_UP_LINES));
+    }
+    (title, lines.join("\n"))
+}
+
+/// Background worker that sends the catch-up messages
+pub struct QuietHoursWorker {
+    dispatcher: NotificationDispatcher,
+    poll_interval: Duration,
+}
+
+impl QuietHoursWorker {
+    pub async fn spawn(dispatcher: NotificationDispatcher) -> tokio::task::JoinHandle<()> {
+        let worker = Self {
+            dispatcher,
+            poll_interval: Duration::from_secs(60),
+        };
+        tokio::spawn(async move {
+            worker.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        info!(
+            "Starting quiet hours worker with interval {:?}",
+            self.poll_interval
+        );
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.dispatcher.send_catch_ups().await {
+                error!("Error sending quiet hours catch-ups: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use db::models::notification::NotificationPayload;
+    use sqlx::types::Json;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn held(event_type: &str, title: &str) -> HeldNotification {
+        HeldNotification {
+            id: Uuid::new_v4(),
+            task_id: Uuid::nil(),
+            project_id: Uuid::nil(),
+            channel: NotificationChannel::Ntfy,
+            event_type: event_type.to_string(),
+            payload: Json(NotificationPayload {
+                task_title: title.to_string(),
+                summary: None,
+            }),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_failures_can_break_through() {
+        let mut quiet_hours = vec![QuietHours {
+            start_hour: 0,
+            end_hour: 23,
+            utc_offset_minutes: Some(0),
+            channels: vec![NotificationChannel::Ntfy],
+            ..QuietHours::default()
+        }];
+        let now = Utc.with_ymd_and_hms(2026, 3, 5, 12, 0, 0).unwrap();
+        let ntfy = NotificationChannel::Ntfy;
+
+        assert!(holds(&quiet_hours, ntfy, TaskNotificationKind::Done, now));
+        assert!(!holds(
+            &quiet_hours,
+            ntfy,
+            TaskNotificationKind::Failed,
+            now
+        ));
+        assert!(!holds(
+            &quiet_hours,
+            NotificationChannel::Email,
+            TaskNotificationKind::Done,
+            now
+        ));
+
+        quiet_hours[0].failures_break_through = false;
+        assert!(holds(&quiet_hours, ntfy, TaskNotificationKind::Failed, now));
+        quiet_hours[0].enabled = false;
+        assert!(!is_quiet(&quiet_hours, ntfy, now));
+    }
+
+    #[test]
+    fn test_format_catch_up() {
+        let (title, body) = format_catch_up(&[
+            held("task_done", "Add dark mode"),
+            held("pr_merged", "Fix login"),
+        ]);
+        assert_eq!(title, "2 notifications during quiet hours");
+        assert_eq!(
+            body,
+            "Task completed: Add dark mode\nPull request merged: Fix login"
+        );
+
+        let many: Vec<_> = (0..25)
+            .map(|i| held("task_failed", &i.to_string()))
+            .collect();
+        let (title, body) = format_catch_up(&many);
+        assert_eq!(title, "25 notifications during quiet hours");
+        assert_eq!(body.lines().count(), MAX_CATCH_UP_LINES + 1);
+        assert!(body.ends_with("…and 5 more"));
+    }
+}
@@ -0,0 +1,261 @@
+//! Provider rate-limit backpressure.
+//!
+//! Coding agent output is watched for 429 / usage-limit errors. A hit puts the
+//! agent's provider on a cooldown shared by every attempt; new attempts for that
+//! agent are refused until it expires. Repeated hits while cooling down double
+//! the cooldown, up to [`MAX_COOLDOWN`].
+
+use std::{
+    sync::{Arc, LazyLock},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use executors::{
+    executors::BaseCodingAgent,
+    logs::{NormalizedEntryType, utils::patch::extract_normalized_entry_from_patch},
+};
+use futures::StreamExt;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use ts_rs::TS;
+use utils::{log_msg::LogMsg, msg_store::MsgStore};
+
+/// Cooldown used when the provider does not say when to retry
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(5 * 60);
+pub const MAX_COOLDOWN: Duration = Duration::from_secs(60 * 60);
+/// Longest error text kept as the cooldown reason
+const MAX_REASON_CHARS: usize = 200;
+
+const RATE_LIMIT_MARKERS: &[&str] = &[
+    "rate limit",
+    "rate_limit",
+    "rate-limit",
+    "ratelimit",
+    "too many requests",
+    "usage limit",
+    "quota exceeded",
+    "status 429",
+    "error 429",
+    "429 too many",
+    "(429)",
+];
+
+static RETRY_AFTER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)(?:retry[- ]after|retry in|try again in|reset(?:s)? in)\D{0,10}(\d+)\s*(h|hours?|m|min|mins|minutes?|s|sec|secs|seconds?)?\b",
+    )
+    .unwrap()
+});
+
+/// Whether `text` reports a provider rate limit. Returns the cooldown to apply,
+/// taken from a "retry after" hint when present.
+pub fn detect_rate_limit(text: &str) -> Option<Duration> {
+    let lower = text.to_lowercase();
+    if !RATE_LIMIT_MARKERS
+        .iter()
+        .any(|marker| lower.contains(marker))
+    {
+        return None;
+    }
+    Some(retry_after(text).unwrap_or(DEFAULT_COOLDOWN).min(MAX_COOLDOWN))
+}
+
+fn retry_after(text: &str) -> Option<Duration> {
+    let captures = RETRY_AFTER.captures(text)?;
+    let amount: u64 = captures.get(1)?.as_str().parse().ok()?;
+    let unit = captures
+        .get(2)
+        .map(|unit| unit.as_str().to_lowercase())
+        .unwrap_or_default();
+    let secs = match unit.chars().next() {
+        Some('h') => amount.saturating_mul(3600),
+        Some('m') => amount.saturating_mul(60),
+        _ => amount,
+    };
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct ProviderCooldown {
+    pub provider: BaseCodingAgent,
+    pub until: DateTime<Utc>,
+    /// Error text that triggered the cooldown
+    pub reason: String,
+}
+
+#[derive(Debug, Clone)]
+struct CooldownState {
+    cooldown: ProviderCooldown,
+    duration: Duration,
+}
+
+/// Active provider cooldowns, shared between the container and the API
+#[derive(Clone, Default)]
+pub struct ProviderCooldowns {
+    inner: Arc<DashMap<BaseCodingAgent, CooldownState>>,
+}
+
+impl ProviderCooldowns {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Put `provider` on a cooldown of at least `duration`, doubling the previous
+    /// cooldown when it is still active
+    pub fn record(
+        &self,
+        provider: BaseCodingAgent,
+        duration: Duration,
+        reason: &str,
+    ) -> ProviderCooldown {
+        let now = Utc::now();
+        let duration = match self.inner.get(&provider) {
+            Some(state) if state.cooldown.until > now => {
+                duration.max(state.duration.saturating_mul(2))
+            }
+            _ => duration,
+        }
+        .min(MAX_COOLDOWN);
+
+        let cooldown = ProviderCooldown {
+            provider,
+            until: now + chrono::Duration::from_std(duration).unwrap_or(chrono::Duration::zero()),
+            reason: reason.trim().chars().take(MAX_REASON_CHARS).collect(),
+        };
+        tracing::warn!(
+            "{} rate limited, pausing new attempts until {}",
+            provider,
+            cooldown.until
+        );
+        self.inner.insert(
+            provider,
+            CooldownState {
+                cooldown: cooldown.clone(),
+                duration,
+            },
+        );
+        cooldown
+    }
+
+    /// The active cooldown for `provider`, if any
+    pub fn active(&self, provider: BaseCodingAgent) -> Option<ProviderCooldown> {
+        let now = Utc::now();
+        self.inner
+            .remove_if(&provider, |_, state| state.cooldown.until <= now);
+        self.inner
+            .get(&provider)
+            .map(|state| state.cooldown.clone())
+    }
+
+    /// All active cooldowns, soonest expiry first
+    pub fn all_active(&self) -> Vec<ProviderCooldown> {
+        let now = Utc::now();
+        self.inner.retain(|_, state| state.cooldown.until > now);
+        let mut cooldowns: Vec<_> = self
+            .inner
+            .iter()
+            .map(|state| state.cooldown.clone())
+            .collect();
+        cooldowns.sort_by_key(|cooldown| cooldown.until);
+        cooldowns
+    }
+
+    pub fn clear(&self, provider: BaseCodingAgent) -> bool {
+        self.inner.remove(&provider).is_some()
+    }
+
+    /// Watch an execution's output and record a cooldown for `provider` on the
+    /// first rate-limit error. One execution only ever records one hit.
+    pub fn watch_execution(
+        &self,
+        provider: BaseCodingAgent,
+        store: Arc<MsgStore>,
+    ) -> JoinHandle<()> {
+        let cooldowns = self.clone();
+        tokio::spawn(async move {
+            let mut stream = store.history_plus_stream();
+            while let Some(Ok(msg)) = stream.next().await {
+                let text = match &msg {
+                    LogMsg::Stderr(text) => Some(text.clone()),
+                    LogMsg::JsonPatch(patch) => extract_normalized_entry_from_patch(patch)
+                        .filter(|(_, entry)| {
+                            matches!(
+                                entry.entry_type,
+                                NormalizedEntryType::ErrorMessage { .. }
+                                    | NormalizedEntryType::SystemMessage
+                            )
+                        })
+                        .map(|(_, entry)| entry.content),
+                    LogMsg::Finished => break,
+                    _ => None,
+                };
+                if let Some(text) = text
+                    && let Some(duration) = detect_rate_limit(&text)
+                {
+                    cooldowns.record(provider, duration, &text);
+                    break;
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_rate_limit_markers() {
+        assert_eq!(
+            detect_rate_limit("API Error: 429 Too Many Requests"),
+            Some(DEFAULT_COOLDOWN)
+        );
+        assert_eq!(
+            detect_rate_limit("Claude usage limit reached"),
+            Some(DEFAULT_COOLDOWN)
+        );
+        assert_eq!(detect_rate_limit("Compiled 429 files"), None);
+        assert_eq!(detect_rate_limit("All tests passed"), None);
+    }
+
+    #[test]
+    fn test_detect_rate_limit_parses_retry_hint() {
+        assert_eq!(
+            detect_rate_limit("Rate limit exceeded, retry after 30 seconds"),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            detect_rate_limit("rate_limit_error: please try again in 2 minutes"),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            detect_rate_limit("Usage limit reached. Resets in 5 hours"),
+            Some(MAX_COOLDOWN)
+        );
+    }
+
+    #[test]
+    fn test_record_doubles_active_cooldown() {
+        let cooldowns = ProviderCooldowns::new();
+
+        let first = cooldowns.record(BaseCodingAgent::ClaudeCode, Duration::from_secs(60), "429");
+        let second = cooldowns.record(BaseCodingAgent::ClaudeCode, Duration::from_secs(60), "429");
+
+        let extension = second.until - first.until;
+        assert!(extension >= chrono::Duration::seconds(59));
+        assert!(cooldowns.active(BaseCodingAgent::ClaudeCode).is_some());
+        assert!(cooldowns.active(BaseCodingAgent::Gemini).is_none());
+    }
+
+    #[test]
+    fn test_expired_cooldowns_are_dropped() {
+        let cooldowns = ProviderCooldowns::new();
+        cooldowns.record(BaseCodingAgent::Amp, Duration::ZERO, "rate limit");
+
+        assert!(cooldowns.active(BaseCodingAgent::Amp).is_none());
+        assert!(cooldowns.all_active().is_empty());
+    }
+}
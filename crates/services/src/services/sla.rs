@@ -0,0 +1,236 @@
+//! SLA policy evaluation.
+//!
+//! Projects can limit how long a task may stay in a status (see
+//! [`SlaPolicy`]). [`find_violations`] reports tasks that have breached or are
+//! close to breaching a limit, and [`SlaMonitorService`] periodically sends a
+//! critical Telegram alert for each new breach.
+
+use std::{collections::HashSet, time::Duration};
+
+use chrono::{DateTime, Utc};
+use db::{
+    DBService,
+    models::{
+        sla_policy::{SlaPolicy, TaskInStatus},
+        task::TaskStatus,
+    },
+};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tokio::time::interval;
+use tracing::{error, info, warn};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::services::telegram::{TelegramService, escape_html};
+
+/// Fraction of the allowed time after which a task counts as near breach
+const NEAR_BREACH_RATIO: f64 = 0.8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum SlaState {
+    NearBreach,
+    Breached,
+}
+
+/// A task that has breached or is close to breaching an SLA policy
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct SlaViolation {
+    pub policy_id: Uuid,
+    pub project_id: Uuid,
+    pub task_id: Uuid,
+    pub title: String,
+    pub status: TaskStatus,
+    /// When the task entered `status`
+    pub entered_at: DateTime<Utc>,
+    /// When the task breaches (or breached) the policy
+    pub deadline: DateTime<Utc>,
+    pub state: SlaState,
+}
+
+/// Evaluate a single task against a policy at `now`
+pub fn evaluate(
+    policy: &SlaPolicy,
+    task: TaskInStatus,
+    now: DateTime<Utc>,
+) -> Option<SlaViolation> {
+    let allowed = chrono::Duration::hours(policy.max_hours);
+    let elapsed = now - task.entered_at;
+    let deadline = task.entered_at + allowed;
+
+    let state = if elapsed >= allowed {
+        SlaState::Breached
+    } else if elapsed.num_seconds() as f64 >= allowed.num_seconds() as f64 * NEAR_BREACH_RATIO {
+        SlaState::NearBreach
+    } else {
+        return None;
+    };
+
+    Some(SlaViolation {
+        policy_id: policy.id,
+        project_id: policy.project_id,
+        task_id: task.task_id,
+        title: task.title,
+        status: policy.status.clone(),
+        entered_at: task.entered_at,
+        deadline,
+        state,
+    })
+}
+
+/// Current breaches and near-breaches for the given policies, most overdue first
+pub async fn find_violations(
+    pool: &SqlitePool,
+    policies: &[SlaPolicy],
+) -> Result<Vec<SlaViolation>, sqlx::Error> {
+    let now = Utc::now();
+    let mut violations = Vec::new();
+    for policy in policies {
+        for task in policy.find_tasks_in_status(pool).await? {
+            violations.extend(evaluate(policy, task, now));
+        }
+    }
+    violations.sort_by_key(|v| v.deadline);
+    Ok(violations)
+}
+
+fn format_breach(violation: &SlaViolation, max_hours: i64) -> String {
+    format!(
+        "🚨 <b>SLA breached</b>\n\n<b>{}</b> has been {} for more than {}h (since {})",
+        escape_html(&violation.title),
+        violation.status,
+        max_hours,
+        violation.entered_at.format("%Y-%m-%d %H:%M UTC")
+    )
+}
+
+/// Service that alerts on new SLA breaches
+pub struct SlaMonitorService {
+    db: DBService,
+    telegram: TelegramService,
+    poll_interval: Duration,
+    /// Breaches already alerted, keyed by policy, task and status entry time so a
+    /// task that leaves and re-enters a status can alert again
+    alerted: HashSet<(Uuid, Uuid, DateTime<Utc>)>,
+}
+
+impl SlaMonitorService {
+    pub async fn spawn(db: DBService, telegram: TelegramService) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            db,
+            telegram,
+            poll_interval: Duration::from_secs(300),
+            alerted: HashSet::new(),
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(mut self) {
+        info!(
+            "Starting SLA monitor service with interval {:?}",
+            self.poll_interval
+        );
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.check_breaches().await {
+                error!("Error checking SLA breaches: {}", e);
+            }
+        }
+    }
+
+    async fn check_breaches(&mut self) -> Result<(), sqlx::Error> {
+        let policies = SlaPolicy::find_all(&self.db.pool).await?;
+        let violations = find_violations(&self.db.pool, &policies).await?;
+
+        let mut current = HashSet::new();
+        for violation in violations.iter().filter(|v| v.state == SlaState::Breached) {
+            let key = (violation.policy_id, violation.task_id, violation.entered_at);
+            current.insert(key);
+            if self.alerted.contains(&key) {
+                continue;
+            }
+
+            let max_hours = policies
+                .iter()
+                .find(|p| p.id == violation.policy_id)
+                .map(|p| p.max_hours)
+                .unwrap_or_default();
+            match self
+                .telegram
+                .send_critical_notification(&format_breach(violation, max_hours))
+                .await
+            {
+                Ok(()) => {
+                    self.alerted.insert(key);
+                }
+                Err(e) => warn!(
+                    "Failed to send SLA breach alert for task {}: {}",
+                    violation.task_id, e
+                ),
+            }
+        }
+
+        // Forget breaches that were resolved so the set does not grow forever
+        self.alerted.retain(|key| current.contains(key));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(max_hours: i64) -> SlaPolicy {
+        SlaPolicy {
+            id: Uuid::new_v4(),
+            project_id: Uuid::new_v4(),
+            status: TaskStatus::InReview,
+            max_hours,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn task_entered(entered_at: DateTime<Utc>) -> TaskInStatus {
+        TaskInStatus {
+            task_id: Uuid::new_v4(),
+            title: "review me".to_string(),
+            entered_at,
+        }
+    }
+
+    #[test]
+    fn test_evaluate_states() {
+        let now = Utc::now();
+        let policy = policy(10);
+
+        let fresh = evaluate(&policy, task_entered(now - chrono::Duration::hours(2)), now);
+        let near = evaluate(&policy, task_entered(now - chrono::Duration::hours(9)), now);
+        let breached = evaluate(&policy, task_entered(now - chrono::Duration::hours(11)), now);
+
+        assert!(fresh.is_none());
+        assert_eq!(near.unwrap().state, SlaState::NearBreach);
+        let breached = breached.unwrap();
+        assert_eq!(breached.state, SlaState::Breached);
+        assert_eq!(breached.deadline, breached.entered_at + chrono::Duration::hours(10));
+    }
+
+    #[test]
+    fn test_format_breach_escapes_title() {
+        let now = Utc::now();
+        let mut task = task_entered(now - chrono::Duration::hours(5));
+        task.title = "<fix>".to_string();
+        let violation = evaluate(&policy(1), task, now).unwrap();
+
+        let message = format_breach(&violation, 1);
+
+        assert!(message.contains("&lt;fix&gt;"));
+        assert!(message.contains("inreview for more than 1h"));
+    }
+}
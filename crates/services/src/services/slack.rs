@@ -0,0 +1,708 @@
+//! Slack app integration for vibe-kanban.
+//!
+//! Provides:
+//! - Linking a Slack workspace through the app's OAuth install flow
+//! - The `/vk` slash command (`/vk tasks`, `/vk newtask`, `/vk help`)
+//! - Task completion notifications posted to a channel
+//!
+//! The app's client ID, client secret and signing secret come from the
+//! environment; the bot token issued by the install is kept in
+//! `Config::slack`.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use db::models::{
+    notification_rule::{NotificationChannel, TaskNotificationKind},
+    project::Project,
+    task::{CreateTask, Task, TaskStatus},
+};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use sqlx::SqlitePool;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use url::{Url, form_urlencoded};
+use uuid::Uuid;
+
+use crate::services::{
+    config::{Config, NotificationEventType, SlackConfig},
+    notification_dispatcher::{NotificationEvent, Notifier, NotifierError},
+};
+
+const AUTHORIZE_URL: &str = "https://slack.com/oauth/v2/authorize";
+const API_BASE_URL: &str = "https://slack.com/api";
+
+/// Bot scopes requested on install. `incoming-webhook` makes Slack ask for
+/// the channel notifications go to.
+const BOT_SCOPES: &str = "commands,chat:write,chat:write.public,incoming-webhook";
+
+/// How long an install link stays valid
+const OAUTH_STATE_TTL_MINUTES: i64 = 15;
+
+/// Requests signed longer ago than this are rejected as replays
+const SIGNATURE_MAX_AGE_SECS: i64 = 5 * 60;
+
+/// Most tasks listed by `/vk tasks`
+const TASK_LIST_LIMIT: usize = 20;
+
+const HELP_TEXT: &str = "*vibe-kanban commands*\n\
+    `/vk tasks [project]` - List a project's tasks\n\
+    `/vk newtask [project_id] <title>` - Create a task, with an optional description on the following lines\n\
+    `/vk help` - Show this message\n\n\
+    Without a project, the default project from the Slack settings is used.";
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug, Error)]
+pub enum SlackError {
+    #[error("Slack API error: {0}")]
+    Api(String),
+
+    #[error("Slack request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("Slack workspace not linked")]
+    NotLinked,
+
+    #[error("Invalid request signature")]
+    InvalidSignature,
+
+    #[error("Invalid or expired install link")]
+    InvalidState,
+
+    #[error("Slack workspace {0} is not linked to this instance")]
+    UnknownTeam(String),
+
+    #[error("Changes are paused while the instance is in maintenance mode")]
+    Maintenance,
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("Project not found: {0}")]
+    ProjectNotFound(String),
+
+    #[error("No project given and no default project set")]
+    NoDefaultProject,
+
+    #[error("Invalid command: {0}")]
+    InvalidCommand(String),
+}
+
+/// App credentials from the Slack app's "Basic Information" page
+#[derive(Debug, Clone)]
+pub struct SlackAppCredentials {
+    pub client_id: String,
+    pub client_secret: String,
+    pub signing_secret: String,
+}
+
+/// Fields of a slash command request that the bot uses
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SlashCommand {
+    pub team_id: String,
+    pub user_name: String,
+    pub command: String,
+    pub text: String,
+}
+
+impl SlashCommand {
+    /// Parse the form-encoded body Slack posts for a slash command
+    pub fn parse(body: &[u8]) -> Self {
+        let mut command = Self::default();
+        for (key, value) in form_urlencoded::parse(body) {
+            match key.as_ref() {
+                "team_id" => command.team_id = value.into_owned(),
+                "user_name" => command.user_name = value.into_owned(),
+                "command" => command.command = value.into_owned(),
+                "text" => command.text = value.into_owned(),
+                _ => {}
+            }
+        }
+        command
+    }
+}
+
+/// Who sees a command's reply
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlackResponseType {
+    /// Only the user who ran the command
+    Ephemeral,
+    /// Everyone in the channel
+    InChannel,
+}
+
+/// Immediate reply to a slash command
+#[derive(Debug, Clone, Serialize)]
+pub struct SlackCommandResponse {
+    pub response_type: SlackResponseType,
+    pub text: String,
+}
+
+impl SlackCommandResponse {
+    pub fn ephemeral(text: impl Into<String>) -> Self {
+        Self {
+            response_type: SlackResponseType::Ephemeral,
+            text: text.into(),
+        }
+    }
+
+    fn in_channel(text: impl Into<String>) -> Self {
+        Self {
+            response_type: SlackResponseType::InChannel,
+            text: text.into(),
+        }
+    }
+}
+
+/// Partial update of the Slack notification settings
+#[derive(Debug, Clone, Default)]
+pub struct SlackSettingsUpdate {
+    pub notifications_enabled: Option<bool>,
+    /// Sets the Slack cell of the task done row of the notification
+    /// preferences
+    pub notify_on_task_done: Option<bool>,
+    pub channel_id: Option<String>,
+    pub default_project_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OAuthAccessResponse {
+    ok: bool,
+    error: Option<String>,
+    access_token: Option<String>,
+    team: Option<OAuthTeam>,
+    incoming_webhook: Option<OAuthIncomingWebhook>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OAuthTeam {
+    id: String,
+    name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OAuthIncomingWebhook {
+    channel_id: Option<String>,
+    channel: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiResponse {
+    ok: bool,
+    error: Option<String>,
+}
+
+/// Slack service
+#[derive(Clone)]
+pub struct SlackService {
+    credentials: Arc<SlackAppCredentials>,
+    /// Redirect URL registered with the app; Slack uses the app's only one
+    /// when unset
+    redirect_uri: Option<String>,
+    config: Arc<RwLock<Config>>,
+    pool: SqlitePool,
+    http: reqwest::Client,
+    /// Install links handed out and not yet used, by OAuth state
+    pending_states: Arc<DashMap<String, DateTime<Utc>>>,
+}
+
+impl SlackService {
+    pub fn new(
+        credentials: SlackAppCredentials,
+        config: Arc<RwLock<Config>>,
+        pool: SqlitePool,
+    ) -> Self {
+        Self {
+            credentials: Arc::new(credentials),
+            redirect_uri: None,
+            config,
+            pool,
+            http: reqwest::Client::new(),
+            pending_states: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Set the OAuth redirect URL, for apps that register more than one
+    pub fn with_redirect_uri(mut self, redirect_uri: String) -> Self {
+        self.redirect_uri = Some(redirect_uri);
+        self
+    }
+
+    /// URL that installs the app into a workspace and comes back to the
+    /// OAuth callback
+    pub fn authorize_url(&self) -> String {
+        let now = Utc::now();
+        self.pending_states
+            .retain(|_, expires_at| *expires_at > now);
+        let state = Uuid::new_v4().simple().to_string();
+        self.pending_states.insert(
+            state.clone(),
+            now + Duration::minutes(OAUTH_STATE_TTL_MINUTES),
+        );
+
+        let mut params = vec![
+            ("client_id", self.credentials.client_id.as_str()),
+            ("scope", BOT_SCOPES),
+            ("state", state.as_str()),
+        ];
+        if let Some(redirect_uri) = &self.redirect_uri {
+            params.push(("redirect_uri", redirect_uri.as_str()));
+        }
+        Url::parse_with_params(AUTHORIZE_URL, &params)
+            .expect("authorize URL is valid")
+            .to_string()
+    }
+
+    /// Exchange the code from the OAuth callback for a bot token and link the
+    /// workspace. Returns the updated settings.
+    pub async fn complete_oauth(&self, code: &str, state: &str) -> Result<SlackConfig, SlackError> {
+        let (_, expires_at) = self
+            .pending_states
+            .remove(state)
+            .ok_or(SlackError::InvalidState)?;
+        if expires_at <= Utc::now() {
+            return Err(SlackError::InvalidState);
+        }
+
+        let mut form = vec![
+            ("client_id", self.credentials.client_id.as_str()),
+            ("client_secret", self.credentials.client_secret.as_str()),
+            ("code", code),
+        ];
+        if let Some(redirect_uri) = &self.redirect_uri {
+            form.push(("redirect_uri", redirect_uri.as_str()));
+        }
+        let response: OAuthAccessResponse = self
+            .http
+            .post(format!("{API_BASE_URL}/oauth.v2.access"))
+            .form(&form)
+            .send()
+            .await?
+            .json()
+            .await?;
+        if !response.ok {
+            return Err(SlackError::Api(
+                response
+                    .error
+                    .unwrap_or_else(|| "unknown error".to_string()),
+            ));
+        }
+        let (Some(access_token), Some(team)) = (response.access_token, response.team) else {
+            return Err(SlackError::Api(
+                "oauth.v2.access returned no bot token".to_string(),
+            ));
+        };
+
+        let mut config = self.config.write().await;
+        // Enable notifications by default when first linked
+        if !config.slack.notifications_enabled {
+            config.slack.notifications_enabled = true;
+            config.notification_preferences.set(
+                NotificationEventType::TaskDone,
+                NotificationChannel::Slack,
+                true,
+            );
+        }
+        let slack = &mut config.slack;
+        slack.team_id = Some(team.id);
+        slack.team_name = team.name;
+        slack.bot_token = Some(access_token);
+        if let Some(webhook) = response.incoming_webhook {
+            slack.channel_id = webhook.channel_id;
+            slack.channel_name = webhook.channel;
+        }
+        Ok(slack.clone())
+    }
+
+    /// Forget the linked workspace and its settings
+    pub async fn unlink(&self) {
+        self.config.write().await.slack = SlackConfig::default();
+    }
+
+    /// Get the current link status
+    pub async fn get_link_status(&self) -> SlackConfig {
+        self.config.read().await.slack.clone()
+    }
+
+    /// Update Slack notification settings
+    pub async fn update_settings(
+        &self,
+        update: SlackSettingsUpdate,
+    ) -> Result<SlackConfig, SlackError> {
+        if let Some(project_id) = update.default_project_id {
+            Project::find_by_id(&self.pool, project_id)
+                .await?
+                .ok_or_else(|| SlackError::ProjectNotFound(project_id.to_string()))?;
+        }
+
+        let mut config = self.config.write().await;
+        if let Some(enabled) = update.notify_on_task_done {
+            config.notification_preferences.set(
+                NotificationEventType::TaskDone,
+                NotificationChannel::Slack,
+                enabled,
+            );
+        }
+        let slack = &mut config.slack;
+        if let Some(enabled) = update.notifications_enabled {
+            slack.notifications_enabled = enabled;
+        }
+        if let Some(channel_id) = update.channel_id {
+            slack.channel_name = None;
+            slack.channel_id = Some(channel_id).filter(|id| !id.is_empty());
+        }
+        if update.default_project_id.is_some() {
+            slack.default_project_id = update.default_project_id;
+        }
+        Ok(slack.clone())
+    }
+
+    /// Check the `X-Slack-Signature` of a request against its raw body.
+    /// `timestamp` is the `X-Slack-Request-Timestamp` header.
+    pub fn verify_signature(
+        &self,
+        timestamp: &str,
+        body: &[u8],
+        signature: &str,
+    ) -> Result<(), SlackError> {
+        verify_signature(
+            self.credentials.signing_secret.as_bytes(),
+            timestamp,
+            body,
+            signature,
+            Utc::now(),
+        )
+    }
+
+    /// Run a slash command from the linked workspace
+    pub async fn handle_command(
+        &self,
+        command: &SlashCommand,
+    ) -> Result<SlackCommandResponse, SlackError> {
+        let (maintenance_mode, linked_team) = {
+            let config = self.config.read().await;
+            (config.maintenance_mode, config.slack.team_id.clone())
+        };
+        match linked_team {
+            None => return Err(SlackError::NotLinked),
+            Some(team_id) if team_id != command.team_id => {
+                return Err(SlackError::UnknownTeam(command.team_id.clone()));
+            }
+            Some(_) => {}
+        }
+
+        let text = command.text.trim();
+        let (name, args) = text
+            .split_once(char::is_whitespace)
+            .map(|(name, args)| (name, args.trim()))
+            .unwrap_or((text, ""));
+        match name.to_lowercase().as_str() {
+            "" | "help" => Ok(SlackCommandResponse::ephemeral(HELP_TEXT)),
+            "tasks" => self.cmd_tasks(args).await,
+            "newtask" if maintenance_mode => Err(SlackError::Maintenance),
+            "newtask" => self.cmd_newtask(args, &command.user_name).await,
+            other => Err(SlackError::InvalidCommand(format!(
+                "Unknown command `{other}`. Try `{} help`.",
+                command.command
+            ))),
+        }
+    }
+
+    /// Handle `tasks` - list a project's tasks, by ID or name
+    async fn cmd_tasks(&self, args: &str) -> Result<SlackCommandResponse, SlackError> {
+        let project = self.resolve_project(args).await?;
+        let tasks = Task::find_by_project_id_with_attempt_status(&self.pool, project.id).await?;
+        if tasks.is_empty() {
+            return Ok(SlackCommandResponse::ephemeral(format!(
+                "No tasks in project *{}*.",
+                escape_mrkdwn(&project.name)
+            )));
+        }
+
+        let mut message = format!("*Tasks in {}*\n", escape_mrkdwn(&project.name));
+        for task in tasks.iter().take(TASK_LIST_LIMIT) {
+            message.push_str(&format_task_line(&task.task));
+        }
+        if tasks.len() > TASK_LIST_LIMIT {
+            message.push_str(&format!(
+                "... and {} more tasks",
+                tasks.len() - TASK_LIST_LIMIT
+            ));
+        }
+        Ok(SlackCommandResponse::ephemeral(message))
+    }
+
+    /// Handle `newtask` - create a task in the given or the default project
+    async fn cmd_newtask(
+        &self,
+        args: &str,
+        user_name: &str,
+    ) -> Result<SlackCommandResponse, SlackError> {
+        let (first_line, description) = args.split_once('\n').unwrap_or((args, ""));
+        let (project_arg, title) = match first_line.split_once(' ') {
+            Some((first, rest)) if Uuid::parse_str(first).is_ok() => (first, rest.trim()),
+            _ => ("", first_line.trim()),
+        };
+        if title.is_empty() {
+            return Err(SlackError::InvalidCommand(
+                "Usage: `/vk newtask [project_id] <title>`".to_string(),
+            ));
+        }
+        let project = self.resolve_project(project_arg).await?;
+
+        let description = description.trim();
+        let create_task = CreateTask::from_title_description(
+            project.id,
+            title.to_string(),
+            (!description.is_empty()).then(|| description.to_string()),
+        );
+        let task = Task::create(&self.pool, &create_task, Uuid::new_v4()).await?;
+
+        Ok(SlackCommandResponse::in_channel(format!(
+            ":white_check_mark: {} created a task in *{}*: *{}*\n`{}`",
+            escape_mrkdwn(user_name),
+            escape_mrkdwn(&project.name),
+            escape_mrkdwn(&task.title),
+            task.id
+        )))
+    }
+
+    /// The project named or identified by `arg`, or the default project when
+    /// it is empty
+    async fn resolve_project(&self, arg: &str) -> Result<Project, SlackError> {
+        let arg = arg.trim();
+        if arg.is_empty() {
+            let project_id = self
+                .config
+                .read()
+                .await
+                .slack
+                .default_project_id
+                .ok_or(SlackError::NoDefaultProject)?;
+            return Project::find_by_id(&self.pool, project_id)
+                .await?
+                .ok_or_else(|| SlackError::ProjectNotFound(project_id.to_string()));
+        }
+
+        if let Ok(project_id) = Uuid::parse_str(arg) {
+            return Project::find_by_id(&self.pool, project_id)
+                .await?
+                .ok_or_else(|| SlackError::ProjectNotFound(arg.to_string()));
+        }
+        Project::find_all(&self.pool)
+            .await?
+            .into_iter()
+            .find(|project| project.name.eq_ignore_ascii_case(arg))
+            .ok_or_else(|| SlackError::ProjectNotFound(arg.to_string()))
+    }
+
+    /// Post that `task`'s attempt finished to the notification channel, if
+    /// the workspace is linked and notifications are on
+    pub async fn send_task_notification(&self, task: &Task) -> Result<(), SlackError> {
+        let (token, channel) = {
+            let config = self.config.read().await;
+            let slack = &config.slack;
+            if !slack.notifications_enabled {
+                return Ok(());
+            }
+            match (&slack.bot_token, &slack.channel_id) {
+                (Some(token), Some(channel)) => (token.clone(), channel.clone()),
+                _ => return Ok(()),
+            }
+        };
+
+        let project_name = Project::find_by_id(&self.pool, task.project_id)
+            .await?
+            .map(|project| project.name);
+        let mut text = format!(
+            ":white_check_mark: Task finished: *{}*",
+            escape_mrkdwn(&task.title)
+        );
+        if let Some(name) = project_name {
+            text.push_str(&format!("\n:file_folder: {}", escape_mrkdwn(&name)));
+        }
+        text.push_str(&format!("\n`{}`", task.id));
+
+        self.post_message(&token, &channel, &text).await
+    }
+
+    async fn post_message(&self, token: &str, channel: &str, text: &str) -> Result<(), SlackError> {
+        let response: ApiResponse = self
+            .http
+            .post(format!("{API_BASE_URL}/chat.postMessage"))
+            .bearer_auth(token)
+            .json(&serde_json::json!({ "channel": channel, "text": text }))
+            .send()
+            .await?
+            .json()
+            .await?;
+        if response.ok {
+            Ok(())
+        } else {
+            Err(SlackError::Api(
+                response
+                    .error
+                    .unwrap_or_else(|| "unknown error".to_string()),
+            ))
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for SlackService {
+    fn channel(&self) -> NotificationChannel {
+        NotificationChannel::Slack
+    }
+
+    async fn wants(&self, kind: TaskNotificationKind) -> bool {
+        kind == TaskNotificationKind::Done && self.config.read().await.slack.notifications_enabled
+    }
+
+    /// Only completed runs are posted
+    async fn send(&self, event: &NotificationEvent) -> Result<(), NotifierError> {
+        if event.kind != TaskNotificationKind::Done || event.run.is_none() {
+            return Ok(());
+        }
+        self.send_task_notification(&event.task).await?;
+        Ok(())
+    }
+
+    async fn send_text(&self, title: &str, body: &str) -> Result<(), NotifierError> {
+        let (token, channel) = {
+            let config = self.config.read().await;
+            match (&config.slack.bot_token, &config.slack.channel_id) {
+                (Some(token), Some(channel)) => (token.clone(), channel.clone()),
+                _ => return Err(SlackError::NotLinked.into()),
+            }
+        };
+        let text = format!("*{}*\n{}", escape_mrkdwn(title), escape_mrkdwn(body));
+        self.post_message(&token, &channel, &text).await?;
+        Ok(())
+    }
+}
+
+/// Check a Slack request signature: `v0=` followed by the hex HMAC-SHA256 of
+/// `v0:{timestamp}:{body}` under the app's signing secret
+fn verify_signature(
+    secret: &[u8],
+    timestamp: &str,
+    body: &[u8],
+    signature: &str,
+    now: DateTime<Utc>,
+) -> Result<(), SlackError> {
+    let sent_at: i64 = timestamp
+        .parse()
+        .map_err(|_| SlackError::InvalidSignature)?;
+    if (now.timestamp() - sent_at).abs() > SIGNATURE_MAX_AGE_SECS {
+        return Err(SlackError::InvalidSignature);
+    }
+    let expected = signature
+        .strip_prefix("v0=")
+        .and_then(|hex_signature| hex::decode(hex_signature).ok())
+        .ok_or(SlackError::InvalidSignature)?;
+
+    let mut mac = HmacSha256::new_from_slice(secret).map_err(|_| SlackError::InvalidSignature)?;
+    mac.update(b"v0:");
+    mac.update(timestamp.as_bytes());
+    mac.update(b":");
+    mac.update(body);
+    mac.verify_slice(&expected)
+        .map_err(|_| SlackError::InvalidSignature)
+}
+
+/// Escape the characters Slack's mrkdwn treats as control characters
+pub fn escape_mrkdwn(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn status_emoji(status: &TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Todo => ":clipboard:",
+        TaskStatus::InProgress => ":arrows_counterclockwise:",
+        TaskStatus::InReview => ":eyes:",
+        TaskStatus::Done => ":white_check_mark:",
+        TaskStatus::Cancelled => ":x:",
+    }
+}
+
+fn format_task_line(task: &Task) -> String {
+    format!(
+        "{} *{}*  `{}`\n",
+        status_emoji(&task.status),
+        escape_mrkdwn(&task.title),
+        task.id
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign(secret: &[u8], timestamp: &str, body: &[u8]) -> String {
+        let mut mac = HmacSha256::new_from_slice(secret).unwrap();
+        mac.update(format!("v0:{timestamp}:").as_bytes());
+        mac.update(body);
+        format!("v0={}", hex::encode(mac.finalize().into_bytes()))
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let secret = b"8f742231b10e8888abcd99yyyzzz85a5";
+        let body = b"token=abc&team_id=T1&command=%2Fvk&text=tasks";
+        let now = Utc::now();
+        let timestamp = now.timestamp().to_string();
+        let signature = sign(secret, &timestamp, body);
+
+        assert!(verify_signature(secret, &timestamp, body, &signature, now).is_ok());
+        assert!(verify_signature(b"other-secret", &timestamp, body, &signature, now).is_err());
+        assert!(verify_signature(secret, &timestamp, b"text=newtask", &signature, now).is_err());
+        assert!(verify_signature(secret, &timestamp, body, "v0=zz", now).is_err());
+    }
+
+    #[test]
+    fn test_verify_signature_rejects_stale_timestamp() {
+        let secret = b"secret";
+        let body = b"text=tasks";
+        let now = Utc::now();
+        let timestamp = (now.timestamp() - SIGNATURE_MAX_AGE_SECS - 1).to_string();
+        let signature = sign(secret, &timestamp, body);
+
+        assert!(matches!(
+            verify_signature(secret, &timestamp, body, &signature, now),
+            Err(SlackError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn test_parse_slash_command() {
+        let command = SlashCommand::parse(
+            b"team_id=T0001&user_name=ada&command=%2Fvk&text=newtask+Fix+the+%3Cbuild%3E&response_url=x",
+        );
+
+        assert_eq!(
+            command,
+            SlashCommand {
+                team_id: "T0001".to_string(),
+                user_name: "ada".to_string(),
+                command: "/vk".to_string(),
+                text: "newtask Fix the <build>".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_escape_mrkdwn() {
+        assert_eq!(
+            escape_mrkdwn("a < b && c > <@U123>"),
+            "a &lt; b &amp;&amp; c &gt; &lt;@U123&gt;"
+        );
+    }
+}
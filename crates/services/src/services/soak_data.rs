@@ -0,0 +1,249 @@
+//! Generator of large synthetic datasets, for checking how pagination,
+//! indexes and memory use hold up at scale.
+//!
+//! Generated projects are named with [`SOAK_PROJECT_PREFIX`], so they can be
+//! told apart from real ones and removed again with [`remove`]. Rows are
+//! inserted in batches inside transactions rather than through the models,
+//! which commit every row.
+
+use db::models::{
+    execution_process::{CreateExecutionProcess, ExecutionProcess, ExecutionProcessRunReason},
+    project::{CreateProject, Project},
+    task::TaskStatus,
+};
+use executors::actions::{
+    ExecutorAction, ExecutorActionType,
+    script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
+};
+use sqlx::SqlitePool;
+use utils::log_msg::LogMsg;
+use uuid::Uuid;
+
+/// Name prefix of every generated project
+pub const SOAK_PROJECT_PREFIX: &str = "[soak] ";
+
+/// Rows inserted per transaction
+const BATCH_SIZE: usize = 5_000;
+
+const STATUSES: [TaskStatus; 5] = [
+    TaskStatus::Todo,
+    TaskStatus::InProgress,
+    TaskStatus::InReview,
+    TaskStatus::Done,
+    TaskStatus::Cancelled,
+];
+
+/// How much data to generate
+#[derive(Debug, Clone)]
+pub struct SoakDataSpec {
+    pub projects: usize,
+    pub tasks_per_project: usize,
+    /// Every n-th task gets a workspace and a session
+    pub attempt_every: usize,
+    /// Log lines of the one long execution process in each project
+    pub log_lines: usize,
+}
+
+impl Default for SoakDataSpec {
+    fn default() -> Self {
+        Self {
+            projects: 100,
+            tasks_per_project: 5_000,
+            attempt_every: 10,
+            log_lines: 50_000,
+        }
+    }
+}
+
+/// Rows generated so far
+#[derive(Debug, Clone, Default)]
+pub struct SoakDataReport {
+    pub projects: usize,
+    pub tasks: usize,
+    pub attempts: usize,
+    pub log_lines: usize,
+}
+
+/// Generate the dataset described by `spec`, calling `on_progress` after
+/// each project
+pub async fn generate(
+    pool: &SqlitePool,
+    spec: &SoakDataSpec,
+    mut on_progress: impl FnMut(&SoakDataReport),
+) -> Result<SoakDataReport, sqlx::Error> {
+    let attempt_every = spec.attempt_every.max(1);
+    let mut report = SoakDataReport::default();
+
+    for p in 0..spec.projects {
+        let project = Project::create(
+            pool,
+            &CreateProject {
+                name: format!("{SOAK_PROJECT_PREFIX}Project {p}"),
+                repositories: Vec::new(),
+            },
+            Uuid::new_v4(),
+        )
+        .await?;
+        report.projects += 1;
+
+        let mut first_session = None;
+        for start in (0..spec.tasks_per_project).step_by(BATCH_SIZE) {
+            let end = (start + BATCH_SIZE).min(spec.tasks_per_project);
+            let mut tx = pool.begin().await?;
+            for t in start..end {
+                let task_id = Uuid::new_v4();
+                sqlx::query(
+                    "INSERT INTO tasks (id, project_id, title, description, status, position)
+                     VALUES ($1, $2, $3, $4, $5, $6)",
+                )
+                .bind(task_id)
+                .bind(project.id)
+                .bind(format!("Soak task {t} of project {p}"))
+                .bind(task_description(t))
+                .bind(task_status(t))
+                .bind(t as i64)
+                .execute(&mut *tx)
+                .await?;
+                report.tasks += 1;
+
+                if t % attempt_every != 0 {
+                    continue;
+                }
+                let workspace_id = Uuid::new_v4();
+                sqlx::query("INSERT INTO workspaces (id, task_id, branch) VALUES ($1, $2, $3)")
+                    .bind(workspace_id)
+                    .bind(task_id)
+                    .bind(format!("vk/soak-{p}-{t}"))
+                    .execute(&mut *tx)
+                    .await?;
+                let session_id = Uuid::new_v4();
+                sqlx::query(
+                    "INSERT INTO sessions (id, workspace_id, executor) VALUES ($1, $2, $3)",
+                )
+                .bind(session_id)
+                .bind(workspace_id)
+                .bind("CLAUDE_CODE")
+                .execute(&mut *tx)
+                .await?;
+                first_session.get_or_insert(session_id);
+                report.attempts += 1;
+            }
+            tx.commit().await?;
+        }
+
+        if let Some(session_id) = first_session
+            && spec.log_lines > 0
+        {
+            report.log_lines += generate_logs(pool, session_id, spec.log_lines).await?;
+        }
+        on_progress(&report);
+    }
+
+    Ok(report)
+}
+
+/// Add an execution process with `lines` lines of output to a session
+async fn generate_logs(
+    pool: &SqlitePool,
+    session_id: Uuid,
+    lines: usize,
+) -> Result<usize, sqlx::Error> {
+    let execution = ExecutionProcess::create(
+        pool,
+        &CreateExecutionProcess {
+            session_id,
+            executor_action: ExecutorAction::new(
+                ExecutorActionType::ScriptRequest(ScriptRequest {
+                    script: "echo soak".to_string(),
+                    language: ScriptRequestLanguage::Bash,
+                    context: ScriptContext::SetupScript,
+                    working_dir: None,
+                }),
+                None,
+            ),
+            run_reason: ExecutionProcessRunReason::SetupScript,
+        },
+        Uuid::new_v4(),
+        &[],
+    )
+    .await?;
+
+    for start in (0..lines).step_by(BATCH_SIZE) {
+        let end = (start + BATCH_SIZE).min(lines);
+        let mut tx = pool.begin().await?;
+        for i in start..end {
+            let line = format!("{}\n", log_line(i));
+            sqlx::query(
+                "INSERT INTO execution_process_logs (execution_id, logs, byte_size, inserted_at)
+                 VALUES ($1, $2, $3, datetime('now', 'subsec'))",
+            )
+            .bind(execution.id)
+            .bind(&line)
+            .bind(line.len() as i64)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+    }
+
+    Ok(lines)
+}
+
+/// Remove every generated project, with its tasks and attempts. Returns the
+/// number of projects removed.
+pub async fn remove(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM projects WHERE substr(name, 1, length($1)) = $1")
+        .bind(SOAK_PROJECT_PREFIX)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// Statuses cycle so every board column gets its share
+fn task_status(index: usize) -> TaskStatus {
+    STATUSES[index % STATUSES.len()].clone()
+}
+
+/// Descriptions vary in length, with every tenth task left without one
+fn task_description(index: usize) -> Option<String> {
+    (index % 10 != 0).then(|| {
+        "Generated for soak testing. Lorem ipsum dolor sit amet, consectetur adipiscing elit. "
+            .repeat(1 + index % 7)
+    })
+}
+
+/// JSONL line as stored by the log streamer, mostly stdout with some stderr
+fn log_line(index: usize) -> String {
+    let msg = if index % 20 == 0 {
+        LogMsg::Stderr(format!(
+            "warning: soak line {index}: unused variable `x{index}`"
+        ))
+    } else {
+        LogMsg::Stdout(format!(
+            "soak line {index}: Compiling crate-{} v0.{}.0",
+            index % 300,
+            index % 17
+        ))
+    };
+    serde_json::to_string(&msg).expect("log messages serialize")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn task_statuses_cover_every_column() {
+        let statuses: Vec<_> = (0..STATUSES.len()).map(task_status).collect();
+        assert_eq!(statuses, STATUSES);
+        assert_eq!(task_status(STATUSES.len()), TaskStatus::Todo);
+    }
+
+    #[test]
+    fn log_lines_are_stored_log_messages() {
+        let msg: LogMsg = serde_json::from_str(&log_line(3)).unwrap();
+        assert!(matches!(msg, LogMsg::Stdout(line) if line.starts_with("soak line 3:")));
+        let msg: LogMsg = serde_json::from_str(&log_line(20)).unwrap();
+        assert!(matches!(msg, LogMsg::Stderr(_)));
+    }
+}
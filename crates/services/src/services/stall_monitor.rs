@@ -0,0 +1,143 @@
+//! Liveness detection for running execution processes.
+//!
+//! While a process runs, its runner records a heartbeat every
+//! [`HEARTBEAT_INTERVAL`]. [`StallMonitorService`] marks processes whose
+//! heartbeat stopped (crashed runner, killed container) as stalled so they do
+//! not show as running forever, moves their task back to review and sends a
+//! critical Telegram alert.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use db::{
+    DBService,
+    models::{
+        execution_process::{ExecutionProcess, ExecutionProcessRunReason},
+        task::{Task, TaskStatus},
+    },
+};
+use tokio::time::interval;
+use tracing::{error, info, warn};
+
+use crate::services::telegram::{TelegramService, escape_html};
+
+/// How often runners record a heartbeat for their process
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Missed heartbeats after which a running process counts as stalled
+const STALL_AFTER: Duration = Duration::from_secs(300);
+
+fn run_reason_label(run_reason: &ExecutionProcessRunReason) -> &'static str {
+    match run_reason {
+        ExecutionProcessRunReason::SetupScript => "setup script",
+        ExecutionProcessRunReason::CleanupScript => "cleanup script",
+        ExecutionProcessRunReason::CodingAgent => "coding agent",
+        ExecutionProcessRunReason::DevServer => "dev server",
+    }
+}
+
+fn format_stall_alert(task_title: &str, run_reason: &ExecutionProcessRunReason) -> String {
+    format!(
+        "⚠️ <b>Attempt stalled</b>\n\n<b>{}</b>\nNo heartbeat from the {} process for {} minutes, so it was marked as stalled.",
+        escape_html(task_title),
+        run_reason_label(run_reason),
+        STALL_AFTER.as_secs() / 60
+    )
+}
+
+/// Service that marks running processes without a recent heartbeat as stalled
+pub struct StallMonitorService {
+    db: DBService,
+    telegram: Option<TelegramService>,
+    poll_interval: Duration,
+}
+
+impl StallMonitorService {
+    pub async fn spawn(
+        db: DBService,
+        telegram: Option<TelegramService>,
+    ) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            db,
+            telegram,
+            poll_interval: Duration::from_secs(60),
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(self) {
+        info!(
+            "Starting stall monitor service with interval {:?}",
+            self.poll_interval
+        );
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.check_stalled().await {
+                error!("Error checking for stalled execution processes: {}", e);
+            }
+        }
+    }
+
+    async fn check_stalled(&self) -> Result<(), sqlx::Error> {
+        let cutoff = Utc::now()
+            - chrono::Duration::from_std(STALL_AFTER).unwrap_or(chrono::Duration::zero());
+        for process in ExecutionProcess::find_stalled(&self.db.pool, cutoff).await? {
+            // The process may have finished since it was loaded
+            if !ExecutionProcess::mark_stalled(&self.db.pool, process.id).await? {
+                continue;
+            }
+            warn!(
+                "Execution process {} stopped sending heartbeats, marked as stalled",
+                process.id
+            );
+
+            let ctx = match ExecutionProcess::load_context(&self.db.pool, process.id).await {
+                Ok(ctx) => ctx,
+                Err(e) => {
+                    warn!(
+                        "Failed to load context for stalled process {}: {}",
+                        process.id, e
+                    );
+                    continue;
+                }
+            };
+            if process.run_reason != ExecutionProcessRunReason::DevServer
+                && ctx.task.status == TaskStatus::InProgress
+            {
+                Task::update_status(&self.db.pool, ctx.task.id, TaskStatus::InReview).await?;
+            }
+
+            if let Some(telegram) = &self.telegram
+                && let Err(e) = telegram
+                    .send_critical_notification(&format_stall_alert(
+                        &ctx.task.title,
+                        &process.run_reason,
+                    ))
+                    .await
+            {
+                warn!(
+                    "Failed to send stall alert for process {}: {}",
+                    process.id, e
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_stall_alert() {
+        let alert = format_stall_alert("<fix>", &ExecutionProcessRunReason::CodingAgent);
+        assert!(alert.contains("&lt;fix&gt;"));
+        assert!(alert.contains("coding agent process for 5 minutes"));
+    }
+}
@@ -0,0 +1,173 @@
+//! Explicit ordering of a project's tasks.
+//!
+//! Tasks are listed by their stored position, lowest first. A move places one
+//! task right before or after another; the project's positions are then
+//! renumbered from zero, writing only the tasks whose position changed.
+
+use std::collections::HashMap;
+
+use db::models::task::Task;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use thiserror::Error;
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Error)]
+pub enum TaskOrderError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("Task {0} is not in this project")]
+    TaskNotInProject(Uuid),
+    #[error("Task {0} cannot be placed relative to itself")]
+    SelfReference(Uuid),
+}
+
+/// Where to put a task, relative to another task of the same project
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskPlacement {
+    Before(Uuid),
+    After(Uuid),
+}
+
+/// One task to move
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct TaskMove {
+    pub task_id: Uuid,
+    pub placement: TaskPlacement,
+}
+
+/// Apply `moves` in order to the tasks of `project_id`, all or none of them,
+/// and return the project's tasks in their new order
+pub async fn move_tasks(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    moves: &[TaskMove],
+) -> Result<Vec<Task>, TaskOrderError> {
+    let mut tx = pool.begin().await?;
+    let positions = Task::find_positions(&mut *tx, project_id).await?;
+    let mut order: Vec<Uuid> = positions.iter().map(|(id, _)| *id).collect();
+    for task_move in moves {
+        apply_move(&mut order, task_move)?;
+    }
+
+    for (id, position) in changed_positions(&order, &positions) {
+        Task::set_position(&mut *tx, id, position).await?;
+    }
+    tx.commit().await?;
+
+    Ok(Task::find_by_project_id(pool, project_id).await?)
+}
+
+/// Move one task within `order`, a project's task IDs in board order
+fn apply_move(order: &mut Vec<Uuid>, task_move: &TaskMove) -> Result<(), TaskOrderError> {
+    let (anchor, after) = match task_move.placement {
+        TaskPlacement::Before(anchor) => (anchor, false),
+        TaskPlacement::After(anchor) => (anchor, true),
+    };
+    if anchor == task_move.task_id {
+        return Err(TaskOrderError::SelfReference(anchor));
+    }
+    let from = order
+        .iter()
+        .position(|id| *id == task_move.task_id)
+        .ok_or(TaskOrderError::TaskNotInProject(task_move.task_id))?;
+    if !order.contains(&anchor) {
+        return Err(TaskOrderError::TaskNotInProject(anchor));
+    }
+
+    let task_id = order.remove(from);
+    let anchor_index = order
+        .iter()
+        .position(|id| *id == anchor)
+        .expect("anchor is still in the order");
+    order.insert(anchor_index + usize::from(after), task_id);
+    Ok(())
+}
+
+/// New positions of the tasks in `order` whose index there differs from
+/// their stored position
+fn changed_positions(order: &[Uuid], stored: &[(Uuid, i64)]) -> Vec<(Uuid, i64)> {
+    let stored: HashMap<Uuid, i64> = stored.iter().copied().collect();
+    order
+        .iter()
+        .enumerate()
+        .map(|(index, id)| (*id, index as i64))
+        .filter(|(id, position)| stored.get(id) != Some(position))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(count: usize) -> Vec<Uuid> {
+        (0..count).map(|_| Uuid::new_v4()).collect()
+    }
+
+    #[test]
+    fn test_apply_move_before_and_after() {
+        let tasks = ids(4);
+        let mut order = tasks.clone();
+
+        apply_move(
+            &mut order,
+            &TaskMove {
+                task_id: tasks[3],
+                placement: TaskPlacement::Before(tasks[0]),
+            },
+        )
+        .unwrap();
+        assert_eq!(order, vec![tasks[3], tasks[0], tasks[1], tasks[2]]);
+
+        apply_move(
+            &mut order,
+            &TaskMove {
+                task_id: tasks[3],
+                placement: TaskPlacement::After(tasks[2]),
+            },
+        )
+        .unwrap();
+        assert_eq!(order, tasks);
+    }
+
+    #[test]
+    fn test_apply_move_rejects_unknown_and_self() {
+        let tasks = ids(2);
+        let mut order = tasks.clone();
+
+        let unknown = Uuid::new_v4();
+        assert!(matches!(
+            apply_move(
+                &mut order,
+                &TaskMove {
+                    task_id: tasks[0],
+                    placement: TaskPlacement::After(unknown),
+                },
+            ),
+            Err(TaskOrderError::TaskNotInProject(id)) if id == unknown
+        ));
+        assert!(matches!(
+            apply_move(
+                &mut order,
+                &TaskMove {
+                    task_id: tasks[0],
+                    placement: TaskPlacement::Before(tasks[0]),
+                },
+            ),
+            Err(TaskOrderError::SelfReference(_))
+        ));
+        assert_eq!(order, tasks);
+    }
+
+    #[test]
+    fn test_changed_positions_skips_tasks_already_in_place() {
+        let tasks = ids(3);
+        let stored = vec![(tasks[0], 0), (tasks[1], 1), (tasks[2], 5)];
+
+        let changed = changed_positions(&[tasks[0], tasks[2], tasks[1]], &stored);
+
+        assert_eq!(changed, vec![(tasks[2], 1), (tasks[1], 2)]);
+    }
+}
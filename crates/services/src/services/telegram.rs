@@ -5,35 +5,94 @@
 //! - Account linking/unlinking
 //! - Webhook handling for bot commands
 //! - Slash command handling (/start, /help, /projects, etc.)
+//! - Photo/document attachments for tasks
+//! - Replies to notifications queued as task follow-ups
+//! - Per-project bots, for projects that notify through a bot of their own
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
 
-use std::{collections::HashMap, sync::Arc};
-
+use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use dashmap::DashMap;
+pub use db::models::notification_rule::TaskNotificationKind;
 use db::models::{
-    project::Project,
-    task::{CreateTask, Task, TaskStatus},
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
+    execution_process_logs::ExecutionProcessLogs,
+    image::TaskImage,
+    merge::{Merge, MergeStatus, PrMerge},
+    notification::Notification,
+    notification_rule::NotificationChannel,
+    project::{Project, ProjectActivity},
+    scratch::DraftFollowUpData,
+    session::Session,
+    task::{CreateTask, Task, TaskStatus, TaskStatusCounts},
+    task_assignee::{TaskAssignee, TaskAssigneeRole},
+    task_status_history::TaskStatusChange,
+    telegram_account::{TelegramAccount, TelegramAccountRole, TelegramDigestMode},
+    telegram_chat::{TelegramChatSettings, TelegramProjectShortcut, TelegramProjectTopic},
+    telegram_digest_item::TelegramDigestItem,
+    telegram_message::{TelegramMessageTask, TelegramProgressMessage, TelegramSeenUpdate},
+    telegram_outgoing_message::{CreateTelegramOutgoingMessage, TelegramOutgoingMessage},
+    workspace::Workspace,
+    workspace_repo::WorkspaceRepo,
 };
 use executors::logs::{
     utils::patch::extract_normalized_entry_from_patch, ActionType, NormalizedEntry,
     NormalizedEntryType,
 };
 use frankenstein::{
-    AsyncApi, AsyncTelegramApi, ChatId, EditMessageTextParams, ParseMode, SendMessageParams,
-    Update, UpdateContent,
+    AnswerCallbackQueryParams, AnswerInlineQueryParams, AsyncApi, AsyncTelegramApi, ChatId,
+    DeleteWebhookParams, EditMessageTextParams, GetFileParams, ParseMode, ReplyMarkup,
+    SendMessageParams, SendPhotoParams, SendVoiceParams, SetWebhookParams, Update, UpdateContent,
+    inline_mode::{
+        InlineQuery, InlineQueryResult, InlineQueryResultArticle, InputMessageContent,
+        InputTextMessageContent,
+    },
+    input_file::{FileUpload, InputFile},
+    objects::{
+        CallbackQuery, ForceReply, InlineKeyboardButton, InlineKeyboardMarkup, KeyboardButton,
+        MaybeInaccessibleMessage, Message, ReplyKeyboardMarkup, ReplyKeyboardRemove,
+    },
 };
 use futures::StreamExt;
+use git::{DiffTarget, GitService};
+use rand::{Rng, distributions::Alphanumeric};
+use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
 use thiserror::Error;
-use tokio::{
-    sync::RwLock,
-    task::JoinHandle,
-    time::{Duration, Instant},
+use tokio::{sync::RwLock, task::JoinHandle, time::Duration};
+use ts_rs::TS;
+use utils::{
+    diff::{Diff, compute_line_change_counts, create_unified_diff},
+    log_msg::LogMsg,
+    msg_store::MsgStore,
+    text::{strip_emoji, truncate_to_char_boundary},
+    version::APP_VERSION,
 };
-use utils::{log_msg::LogMsg, msg_store::MsgStore};
 use uuid::Uuid;
 
-use crate::services::config::{Config, TelegramConfig};
+use crate::services::{
+    config::{
+        Config, DigestSchedule, NotificationEventType, NotificationPreferences,
+        StatusTransitionRule, TelegramConfig, TelegramParseMode, TelegramRetryConfig,
+    },
+    digest,
+    git_host::{GitHostProvider, GitHostService},
+    health::{HealthService, HealthSnapshot, format_uptime},
+    i18n::{BotMessage, Locale, ServerMessage},
+    image::{ImageError, ImageService},
+    notification_dispatcher::{NotificationEvent, Notifier, NotifierError},
+    prompt_library,
+    queued_message::QueuedMessageService,
+    tts::TtsService,
+    workload,
+};
 
 /// Errors that can occur in the Telegram service
 #[derive(Debug, Error)]
@@ -47,6 +106,15 @@ pub enum TelegramError {
     #[error("Account not linked")]
     NotLinked,
 
+    #[error("Telegram user {0} is not allowed to use the bot")]
+    NotAuthorized(i64),
+
+    #[error("Read-only accounts cannot {0}")]
+    ReadOnly(String),
+
+    #[error("Changes are paused while the instance is in maintenance mode")]
+    Maintenance,
+
     #[error("Invalid link token")]
     InvalidLinkToken,
 
@@ -67,6 +135,153 @@ pub enum TelegramError {
 
     #[error("Invalid command: {0}")]
     InvalidCommand(String),
+
+    #[error("Failed to store attachment: {0}")]
+    Attachment(String),
+
+    #[error("Failed to queue message: {0}")]
+    QueueMessage(String),
+
+    #[error("Failed to read logs: {0}")]
+    Logs(String),
+
+    #[error("Failed to compute diff: {0}")]
+    Diff(String),
+
+    #[error("Failed to merge PR: {0}")]
+    Merge(String),
+
+    #[error("Token belongs to bot {actual}, not {expected}")]
+    WrongBot { expected: String, actual: String },
+}
+
+/// How the Telegram settings treat each kind of task notification
+trait TelegramNotificationKind {
+    /// Whether this kind is delivered without sound
+    fn is_silent(self, config: &TelegramConfig) -> bool;
+
+    fn header(self, locale: Locale) -> String;
+}
+
+impl TelegramNotificationKind for TaskNotificationKind {
+    fn is_silent(self, config: &TelegramConfig) -> bool {
+        config.silent || config.silent_kinds.contains(&self)
+    }
+
+    fn header(self, locale: Locale) -> String {
+        match self {
+            Self::Started => ServerMessage::TaskStarted,
+            Self::InReview => ServerMessage::TaskInReview,
+            Self::Failed => ServerMessage::TaskFailed,
+            Self::Done => ServerMessage::TaskCompleted,
+            Self::Cancelled => ServerMessage::TaskCancelled,
+        }
+        .render(locale)
+    }
+}
+
+/// A task notification rendered without sending it
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct NotificationPreview {
+    /// The notification as composed, in Telegram HTML
+    pub html: String,
+    /// The messages that would go out: converted to the parse mode and split
+    /// at Telegram's length limit
+    pub messages: Vec<String>,
+    pub parse_mode: TelegramParseMode,
+    /// Labels of the buttons under the last message. The merge button offered
+    /// while a PR is open is left out.
+    pub buttons: Vec<String>,
+    /// Whether it would be delivered without sound
+    pub silent: bool,
+    /// Whether the current settings send this kind of notification at all
+    pub enabled: bool,
+}
+
+/// A piece of the bot setup checked by `setup_state` and /setup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum TelegramSetupStepKind {
+    /// `TELEGRAM_BOT_TOKEN` is set and accepted by Telegram
+    Token,
+    /// `TELEGRAM_BOT_USERNAME` matches the bot, so link deep links open it
+    Username,
+    /// Telegram posts updates to this server's webhook route
+    Webhook,
+    /// An account is linked, giving notifications a chat to go to
+    LinkChat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum TelegramSetupStepStatus {
+    Done,
+    Missing,
+    /// Configured, but not working
+    Error,
+}
+
+/// How Telegram hands updates to the bot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum TelegramUpdateMode {
+    Webhook,
+    /// No webhook is registered, so Telegram keeps updates for `getUpdates`.
+    /// This server does not poll: the bot sends notifications but never sees
+    /// commands.
+    Polling,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct TelegramSetupStep {
+    pub kind: TelegramSetupStepKind,
+    pub status: TelegramSetupStepStatus,
+    /// What was found, e.g. the bot's username or the webhook's last error
+    pub detail: Option<String>,
+    /// What to do to complete the step, None once it is done
+    pub action: Option<String>,
+}
+
+/// Progress of the first-run bot setup, step by step
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct TelegramSetupState {
+    /// Whether every step is done
+    pub complete: bool,
+    /// None when Telegram could not be asked
+    pub update_mode: Option<TelegramUpdateMode>,
+    pub steps: Vec<TelegramSetupStep>,
+}
+
+impl TelegramSetupState {
+    /// The setup of a server started without `TELEGRAM_BOT_TOKEN`
+    pub fn without_bot(config: &TelegramConfig) -> Self {
+        build_setup_state(&SetupFacts {
+            linked_chat_id: config.chat_id,
+            ..Default::default()
+        })
+    }
+}
+
+/// Partial update of Telegram notification settings (None = keep current value)
+///
+/// The `notify_on_*` toggles set the Telegram cells of the notification
+/// preferences.
+#[derive(Debug, Clone, Default)]
+pub struct TelegramSettingsUpdate {
+    pub notifications_enabled: Option<bool>,
+    pub notify_on_task_done: Option<bool>,
+    pub include_llm_summary: Option<bool>,
+    pub stream_enabled: Option<bool>,
+    pub notify_on_started: Option<bool>,
+    pub notify_on_in_review: Option<bool>,
+    pub notify_on_failed: Option<bool>,
+    pub voice_summary: Option<bool>,
+    pub notify_transitions: Option<Vec<StatusTransitionRule>>,
+    pub silent: Option<bool>,
+    pub silent_kinds: Option<Vec<TaskNotificationKind>>,
+    pub edit_in_place: Option<bool>,
+    pub forward_questions: Option<bool>,
+    pub allowed_user_ids: Option<Vec<i64>>,
 }
 
 /// Information about a pending link token
@@ -90,27 +305,227 @@ impl LinkToken {
 pub enum UpdateResult {
     /// Command was processed successfully with a response message
     Response(String),
+    /// Command produced several messages, to be sent in order
+    Responses(Vec<String>),
+    /// Response message with one inline button per row
+    ResponseWithButtons {
+        text: String,
+        buttons: Vec<CallbackButton>,
+    },
+    /// Response message that shows the quick-action menu keyboard, or hides
+    /// it when `show` is false
+    ResponseWithMenu { text: String, show: bool },
+    /// A follow-up was sent for a session with nothing running, so the
+    /// caller starts it now instead of queuing it for the next run
+    FollowUpRequested {
+        session_id: Uuid,
+        follow_up: DraftFollowUpData,
+        text: String,
+    },
+    /// The Retry button of a failure notification was pressed; the caller
+    /// starts a new attempt of the task
+    RetryRequested { task_id: Uuid },
+    /// /stop was sent for a task with a running attempt; the caller stops
+    /// the attempt's agent
+    StopRequested { task_id: Uuid },
+    /// Command changed the Telegram config, which should be persisted before
+    /// sending the response
+    SettingsChanged(String),
     /// Command was processed but requires no response
     NoResponse,
     /// Link completed successfully
-    LinkCompleted { chat_id: i64, user_id: i64, username: Option<String> },
+    LinkCompleted {
+        chat_id: i64,
+        user_id: i64,
+        username: Option<String>,
+        /// Language of the user who linked, for the confirmation message
+        locale: Locale,
+    },
+}
+
+/// Inline keyboard button that sends `data` back as a callback query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallbackButton {
+    pub label: String,
+    pub data: String,
+}
+
+/// Chat a message is sent to, and the forum topic within it for groups with
+/// topics enabled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ReplyTarget {
+    pub chat_id: i64,
+    pub message_thread_id: Option<i32>,
+}
+
+impl ReplyTarget {
+    /// The chat itself, outside of any topic
+    pub fn chat(chat_id: i64) -> Self {
+        Self {
+            chat_id,
+            message_thread_id: None,
+        }
+    }
+}
+
+/// A task notification held back by the batching window
+#[derive(Debug, Clone)]
+struct BatchedNotification {
+    task_id: Uuid,
+    /// History entry of the notification, once the dispatcher recorded it
+    notification_id: Option<Uuid>,
+    message: String,
+    buttons: Option<ReplyMarkup>,
+    silent: bool,
+}
+
+/// Topic a message was posted in, if it was posted in a forum topic
+fn topic_thread_id(message: &Message) -> Option<i32> {
+    message
+        .message_thread_id
+        .filter(|_| message.is_topic_message == Some(true))
+}
+
+/// Bot API limits: about 30 messages per second overall and one per second
+/// to the same chat, with short bursts tolerated
+const GLOBAL_SEND_RATE: f64 = 30.0;
+const GLOBAL_SEND_BURST: f64 = 30.0;
+const CHAT_SEND_RATE: f64 = 1.0;
+const CHAT_SEND_BURST: f64 = 3.0;
+
+/// Token bucket that hands out reservations: taking a token from an empty
+/// bucket drives it negative and returns how long the caller has to wait
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    refill_per_sec: f64,
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(capacity: f64, refill_per_sec: f64, now: Instant) -> Self {
+        Self {
+            capacity,
+            refill_per_sec,
+            tokens: capacity,
+            updated_at: now,
+        }
+    }
+
+    /// Take one token, returning the wait until it is available
+    fn reserve(&mut self, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.updated_at = now;
+
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.refill_per_sec)
+        }
+    }
+}
+
+/// Outgoing message limiter shared by all clones of the service
+#[derive(Clone)]
+struct SendRateLimiter {
+    global: Arc<Mutex<TokenBucket>>,
+    per_chat: Arc<DashMap<i64, TokenBucket>>,
+}
+
+impl SendRateLimiter {
+    fn new() -> Self {
+        Self {
+            global: Arc::new(Mutex::new(TokenBucket::new(
+                GLOBAL_SEND_BURST,
+                GLOBAL_SEND_RATE,
+                Instant::now(),
+            ))),
+            per_chat: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Wait until a message to `chat_id` fits within both limits
+    async fn acquire(&self, chat_id: i64) {
+        let now = Instant::now();
+        let chat_wait = self
+            .per_chat
+            .entry(chat_id)
+            .or_insert_with(|| TokenBucket::new(CHAT_SEND_BURST, CHAT_SEND_RATE, now))
+            .reserve(now);
+        let global_wait = self
+            .global
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .reserve(now);
+
+        let wait = chat_wait.max(global_wait);
+        if !wait.is_zero() {
+            tracing::debug!("Telegram send to {} rate limited for {:?}", chat_id, wait);
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// A Bot API client and the token it was built from
+#[derive(Clone)]
+struct BotClient {
+    api: AsyncApi,
+    /// Needed to build file download URLs
+    token: String,
+}
+
+impl BotClient {
+    fn new(token: String) -> Self {
+        Self {
+            api: AsyncApi::new(&token),
+            token,
+        }
+    }
+
+    /// The bot's user ID, the part of the token before the colon
+    fn id(&self) -> &str {
+        self.token.split(':').next().unwrap_or_default()
+    }
 }
 
 /// Service for Telegram bot integration
 #[derive(Clone)]
 pub struct TelegramService {
-    /// Bot API client (None if token not configured)
-    api: Option<AsyncApi>,
+    /// Bot this handle speaks through (None if token not configured). Shared
+    /// by clones, so a token swapped in by `rotate_token` reaches all of them.
+    bot: Arc<Mutex<Option<BotClient>>>,
+    /// Clients of the per-project bots, keyed by project. Shared by all
+    /// handles and rebuilt when a project's token changes.
+    project_bots: Arc<DashMap<Uuid, BotClient>>,
     /// User config (contains TelegramConfig for link status)
     config: Arc<RwLock<Config>>,
     /// Database pool for queries
     pool: SqlitePool,
     /// Pending link tokens (token -> LinkToken)
     pending_links: Arc<DashMap<String, LinkToken>>,
-    /// Active project context per chat_id
-    active_projects: Arc<DashMap<i64, Uuid>>,
     /// Bot username (for deep links)
     bot_username: Option<String>,
+    /// Image store for attachments sent to the bot
+    images: Option<ImageService>,
+    /// HTTP client for downloading files from the Bot API
+    http: reqwest::Client,
+    /// Follow-up queue for replies to task notifications
+    queued_messages: Option<QueuedMessageService>,
+    /// Git access for /diff
+    git: Option<GitService>,
+    /// Server and executor health for /status
+    health: Option<HealthService>,
+    /// Speech synthesis for voice summaries
+    tts: TtsService,
+    /// Keeps outgoing messages within the Bot API limits
+    rate_limiter: SendRateLimiter,
+    /// Notifications waiting out the batching window, per chat
+    notification_batches: Arc<DashMap<ReplyTarget, Vec<BatchedNotification>>>,
+    /// Project whose own bot this handle speaks through, None for the default bot
+    bot_project_id: Option<Uuid>,
 }
 
 impl TelegramService {
@@ -123,15 +538,22 @@ impl TelegramService {
         config: Arc<RwLock<Config>>,
         pool: SqlitePool,
     ) -> Self {
-        let api = bot_token.map(|token| AsyncApi::new(&token));
-
         Self {
-            api,
+            bot: Arc::new(Mutex::new(bot_token.map(BotClient::new))),
+            project_bots: Arc::new(DashMap::new()),
             config,
             pool,
             pending_links: Arc::new(DashMap::new()),
-            active_projects: Arc::new(DashMap::new()),
             bot_username: None,
+            images: None,
+            http: reqwest::Client::new(),
+            queued_messages: None,
+            git: None,
+            health: None,
+            tts: TtsService::new(),
+            rate_limiter: SendRateLimiter::new(),
+            notification_batches: Arc::new(DashMap::new()),
+            bot_project_id: None,
         }
     }
 
@@ -141,791 +563,3401 @@ impl TelegramService {
         self
     }
 
-    /// Check if the bot is configured (has a token)
-    pub fn is_configured(&self) -> bool {
-        self.api.is_some()
+    /// Set the image store used for photo/document attachments
+    pub fn with_image_service(mut self, images: ImageService) -> Self {
+        self.images = Some(images);
+        self
     }
 
-    /// Get the API client, or return NotConfigured error
-    fn api(&self) -> Result<&AsyncApi, TelegramError> {
-        self.api.as_ref().ok_or(TelegramError::NotConfigured)
+    /// Set the follow-up queue used when replying to task notifications
+    pub fn with_queued_message_service(mut self, queued_messages: QueuedMessageService) -> Self {
+        self.queued_messages = Some(queued_messages);
+        self
     }
 
-    // ========================================================================
-    // Bot API Methods
-    // ========================================================================
+    /// Set the git service used to render attempt diffs
+    pub fn with_git_service(mut self, git: GitService) -> Self {
+        self.git = Some(git);
+        self
+    }
 
-    /// Send a text message to a chat
-    pub async fn send_message(&self, chat_id: i64, text: &str) -> Result<(), TelegramError> {
-        let api = self.api()?;
+    /// Set the health source reported by /status
+    pub fn with_health_service(mut self, health: HealthService) -> Self {
+        self.health = Some(health);
+        self
+    }
 
-        let params = SendMessageParams::builder()
-            .chat_id(ChatId::Integer(chat_id))
-            .text(text)
-            .parse_mode(ParseMode::Html)
-            .build();
+    /// Check if the bot is configured (has a token)
+    pub fn is_configured(&self) -> bool {
+        self.bot().is_some()
+    }
 
-        api.send_message(&params)
-            .await
-            .map_err(|e| TelegramError::Api(e.to_string()))?;
+    /// The bot this handle currently speaks through
+    fn bot(&self) -> Option<BotClient> {
+        self.bot
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
 
-        Ok(())
+    /// Get the API client, or return NotConfigured error
+    fn api(&self) -> Result<AsyncApi, TelegramError> {
+        self.bot()
+            .map(|bot| bot.api)
+            .ok_or(TelegramError::NotConfigured)
     }
 
-    /// Send a task completion notification
+    /// Swap in a new token for the default bot, without a restart.
     ///
-    /// If `include_llm_summary` is true and a summary is provided, it will be included.
-    pub async fn send_task_notification(
+    /// The token is checked with `getMe` first and must belong to the same
+    /// bot, since linked chats and recorded updates are keyed by the bot's ID.
+    /// Telegram revokes the old token as soon as a new one is issued, so there
+    /// is no grace period: calls already in flight with the old token fail.
+    /// The webhook is registered again when `webhook_base_url` is set. Returns
+    /// the bot's username.
+    pub async fn rotate_token(&self, token: String) -> Result<String, TelegramError> {
+        let bot = BotClient::new(token);
+        let username = verify_bot(&bot, self.bot().as_ref()).await?;
+        let api = bot.api.clone();
+        *self
+            .bot
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(bot);
+
+        self.reregister_webhook(&api, None).await?;
+        tracing::info!("Rotated the Telegram bot token of @{}", username);
+        Ok(username)
+    }
+
+    /// Like [`Self::rotate_token`], for a bot in `TelegramConfig::project_bots`.
+    /// Updates the config in memory; the caller saves it.
+    pub async fn rotate_project_token(
         &self,
-        task: &Task,
-        llm_summary: Option<&str>,
-    ) -> Result<(), TelegramError> {
-        let config = self.config.read().await;
-        let telegram_config = &config.telegram;
+        project_id: Uuid,
+        token: String,
+    ) -> Result<String, TelegramError> {
+        let current = {
+            let config = self.config.read().await;
+            let bot = config
+                .telegram
+                .project_bot(project_id)
+                .ok_or(TelegramError::ProjectNotFound(project_id))?;
+            BotClient::new(bot.bot_token.clone())
+        };
+        let bot = BotClient::new(token);
+        let username = verify_bot(&bot, Some(&current)).await?;
 
-        // Check if notifications are enabled and user is linked
-        if !telegram_config.notifications_enabled || !telegram_config.notify_on_task_done {
-            tracing::debug!("Telegram notifications disabled, skipping");
-            return Ok(());
+        {
+            let mut config = self.config.write().await;
+            let entry = config
+                .telegram
+                .project_bots
+                .iter_mut()
+                .find(|bot| bot.project_id == project_id)
+                .ok_or(TelegramError::ProjectNotFound(project_id))?;
+            entry.bot_token = bot.token.clone();
         }
+        // `for_project` rebuilds the client once it sees the new token
+        self.project_bots.insert(project_id, bot.clone());
+
+        self.reregister_webhook(&bot.api, Some(project_id)).await?;
+        tracing::info!(
+            "Rotated the Telegram bot token of @{} for project {}",
+            username,
+            project_id
+        );
+        Ok(username)
+    }
 
-        let chat_id = telegram_config.chat_id.ok_or(TelegramError::NotLinked)?;
+    /// Point a bot whose token was just rotated at its webhook route again,
+    /// with the same secret, when webhooks are in use
+    async fn reregister_webhook(
+        &self,
+        api: &AsyncApi,
+        project_id: Option<Uuid>,
+    ) -> Result<(), TelegramError> {
+        let Some(base_url) = self.config.read().await.telegram.webhook_base_url.clone() else {
+            return Ok(());
+        };
+        let params = SetWebhookParams::builder()
+            .url(webhook_url(&base_url, project_id))
+            .secret_token(self.webhook_secret_or_generate(project_id).await)
+            .build();
+        api.set_webhook(&params)
+            .await
+            .map_err(|e| TelegramError::Api(e.to_string()))?;
+        Ok(())
+    }
 
-        // Format the notification message
-        let mut message = format!(
-            "✅ <b>Task Completed</b>\n\n<b>{}</b>",
-            escape_html(&task.title)
-        );
+    /// A handle that speaks through `project_id`'s own bot, or through this
+    /// one when the project has none (see `TelegramConfig::project_bots`)
+    pub async fn for_project(&self, project_id: Uuid) -> Self {
+        let token = {
+            let config = self.config.read().await;
+            match config.telegram.project_bot(project_id) {
+                Some(bot) => bot.bot_token.clone(),
+                None => return self.clone(),
+            }
+        };
 
-        if telegram_config.include_llm_summary
-            && let Some(summary) = llm_summary
-        {
-            message.push_str("\n\n<b>Summary:</b>\n");
-            message.push_str(&escape_html(summary));
-        }
+        let cached = self
+            .project_bots
+            .get(&project_id)
+            .map(|bot| bot.clone())
+            .filter(|bot| bot.token == token);
+        let bot = cached.unwrap_or_else(|| {
+            let bot = BotClient::new(token);
+            self.project_bots.insert(project_id, bot.clone());
+            bot
+        });
 
-        self.send_message(chat_id, &message).await
+        Self {
+            bot: Arc::new(Mutex::new(Some(bot))),
+            bot_project_id: Some(project_id),
+            ..self.clone()
+        }
     }
 
     // ========================================================================
-    // Link Management
+    // Bot API Methods
     // ========================================================================
 
-    /// Generate a new link token for account linking
-    ///
-    /// Returns a tuple of (token, deep_link_url)
-    pub fn generate_link_token(&self) -> Result<(String, String), TelegramError> {
-        let token = Uuid::new_v4().to_string();
-        let link_token = LinkToken {
-            token: token.clone(),
-            created_at: Utc::now(),
-        };
+    /// Send a text message to a chat
+    pub async fn send_message(&self, chat_id: i64, text: &str) -> Result<(), TelegramError> {
+        self.send_reply(ReplyTarget::chat(chat_id), text).await
+    }
 
-        // Clean up expired tokens first
-        self.cleanup_expired_tokens();
+    /// Send a text message to a chat, inside the forum topic of `target` if any
+    pub async fn send_reply(&self, target: ReplyTarget, text: &str) -> Result<(), TelegramError> {
+        self.send_html(target, text).await.map(|_| ())
+    }
 
-        // Store the new token
-        self.pending_links.insert(token.clone(), link_token);
+    /// Send an HTML message and return the sent message
+    ///
+    /// The message is converted to the configured `parse_mode` before sending.
+    async fn send_html(&self, target: ReplyTarget, text: &str) -> Result<Message, TelegramError> {
+        self.send_html_with_markup(target, text, None, false).await
+    }
 
-        // Generate the deep link URL
-        let deep_link = if let Some(username) = &self.bot_username {
-            format!("https://t.me/{}?start={}", username, token)
+    /// Send a message that shows the quick-action menu keyboard, or removes it
+    pub async fn send_menu(
+        &self,
+        target: ReplyTarget,
+        text: &str,
+        show: bool,
+    ) -> Result<(), TelegramError> {
+        let markup = if show {
+            menu_keyboard()
         } else {
-            // If we don't know the bot username, just return the token
-            format!("start={}", token)
+            ReplyMarkup::ReplyKeyboardRemove(
+                ReplyKeyboardRemove::builder().remove_keyboard(true).build(),
+            )
         };
+        self.send_html_with_markup(target, text, Some(markup), false)
+            .await
+            .map(|_| ())
+    }
 
-        Ok((token, deep_link))
+    /// Send a message with one inline callback button per row
+    pub async fn send_message_with_buttons(
+        &self,
+        target: ReplyTarget,
+        text: &str,
+        buttons: &[CallbackButton],
+    ) -> Result<(), TelegramError> {
+        self.send_html_with_markup(target, text, Some(inline_keyboard(buttons)), false)
+            .await
+            .map(|_| ())
     }
 
-    /// Validate a link token (check if it exists and is not expired)
-    pub fn validate_link_token(&self, token: &str) -> Result<(), TelegramError> {
-        let link_token = self
-            .pending_links
-            .get(token)
-            .ok_or(TelegramError::InvalidLinkToken)?;
+    /// Messages over Telegram's length limit are split into several, with the
+    /// markup attached to the last one, which is returned. Only the first one
+    /// alerts.
+    async fn send_html_with_markup(
+        &self,
+        target: ReplyTarget,
+        text: &str,
+        reply_markup: Option<ReplyMarkup>,
+        silent: bool,
+    ) -> Result<Message, TelegramError> {
+        let api = self.api()?;
+        let chat_id = target.chat_id;
+
+        let mode = self.config.read().await.telegram.parse_mode;
+        let mut reply_markup = render_markup(reply_markup, mode);
+        let chunks = split_html_message(text, TELEGRAM_MAX_MESSAGE_LEN);
+        let last = chunks.len() - 1;
+        let mut sent = None;
+        for (index, chunk) in chunks.iter().enumerate() {
+            let (text, parse_mode) = render_message(chunk, mode);
+            let params = SendMessageParams::builder()
+                .chat_id(ChatId::Integer(chat_id))
+                .maybe_message_thread_id(target.message_thread_id)
+                .text(text)
+                .maybe_parse_mode(parse_mode)
+                .maybe_reply_markup(if index == last {
+                    reply_markup.take()
+                } else {
+                    None
+                })
+                .disable_notification(silent || index > 0)
+                .build();
 
-        if link_token.is_expired() {
-            // Remove expired token
-            drop(link_token);
-            self.pending_links.remove(token);
-            return Err(TelegramError::LinkTokenExpired);
+            let params = &params;
+            sent = Some(
+                self.with_retry(chat_id, move || api.send_message(params))
+                    .await?
+                    .result,
+            );
         }
 
-        Ok(())
+        Ok(sent.expect("a message has at least one chunk"))
     }
 
-    /// Complete the account linking process
-    ///
-    /// This is called when a user sends /start <token> to the bot.
-    /// Returns the updated TelegramConfig.
-    pub async fn complete_link(
+    /// Replace the text of a message the bot sent earlier
+    async fn edit_html(
         &self,
-        token: &str,
         chat_id: i64,
-        user_id: i64,
-        username: Option<String>,
-    ) -> Result<TelegramConfig, TelegramError> {
-        // Validate the token first
-        self.validate_link_token(token)?;
-
-        // Remove the token (single-use)
-        self.pending_links.remove(token);
+        message_id: i32,
+        text: &str,
+        reply_markup: Option<ReplyMarkup>,
+    ) -> Result<(), TelegramError> {
+        let api = self.api()?;
 
-        // Update the config
-        let mut config = self.config.write().await;
-        config.telegram.chat_id = Some(chat_id);
-        config.telegram.user_id = Some(user_id);
-        config.telegram.username = username;
-        config.telegram.notifications_enabled = true;
-        config.telegram.notify_on_task_done = true;
+        let mode = self.config.read().await.telegram.parse_mode;
+        let (text, parse_mode) = render_message(text, mode);
+        let reply_markup = match render_markup(reply_markup, mode) {
+            Some(ReplyMarkup::InlineKeyboardMarkup(markup)) => Some(markup),
+            _ => None,
+        };
+        let params = EditMessageTextParams::builder()
+            .chat_id(ChatId::Integer(chat_id))
+            .message_id(message_id)
+            .text(text)
+            .maybe_parse_mode(parse_mode)
+            .maybe_reply_markup(reply_markup)
+            .build();
 
-        Ok(config.telegram.clone())
-    }
+        let params = &params;
+        self.with_retry(chat_id, move || api.edit_message_text(params))
+            .await?;
 
-    /// Unlink the Telegram account
-    pub async fn unlink(&self) -> Result<(), TelegramError> {
-        let mut config = self.config.write().await;
-        config.telegram = TelegramConfig::default();
         Ok(())
     }
 
-    /// Check if an account is currently linked
-    pub async fn is_linked(&self) -> bool {
-        let config = self.config.read().await;
-        config.telegram.chat_id.is_some()
-    }
+    /// Send a photo with an HTML caption and return the sent message
+    async fn send_photo(
+        &self,
+        target: ReplyTarget,
+        photo: &Path,
+        caption: &str,
+        reply_markup: Option<ReplyMarkup>,
+        silent: bool,
+    ) -> Result<Message, TelegramError> {
+        let api = self.api()?;
+        let chat_id = target.chat_id;
 
-    /// Get the current link status
-    pub async fn get_link_status(&self) -> TelegramConfig {
-        let config = self.config.read().await;
-        config.telegram.clone()
+        let mode = self.config.read().await.telegram.parse_mode;
+        let (caption, parse_mode) = render_message(caption, mode);
+        let reply_markup = render_markup(reply_markup, mode);
+        let params = SendPhotoParams::builder()
+            .chat_id(ChatId::Integer(chat_id))
+            .maybe_message_thread_id(target.message_thread_id)
+            .photo(FileUpload::InputFile(InputFile {
+                path: photo.to_path_buf(),
+            }))
+            .caption(caption)
+            .maybe_parse_mode(parse_mode)
+            .maybe_reply_markup(reply_markup)
+            .disable_notification(silent)
+            .build();
+
+        let params = &params;
+        let sent = self
+            .with_retry(chat_id, move || api.send_photo(params))
+            .await?;
+
+        Ok(sent.result)
     }
 
-    /// Update Telegram notification settings
-    ///
-    /// Allows updating individual settings without affecting others.
-    pub async fn update_settings(
+    /// Send OGG/Opus audio as a voice message
+    async fn send_voice(
         &self,
-        notifications_enabled: Option<bool>,
-        notify_on_task_done: Option<bool>,
-        include_llm_summary: Option<bool>,
-        stream_enabled: Option<bool>,
-    ) -> Result<TelegramConfig, TelegramError> {
-        let mut config = self.config.write().await;
+        target: ReplyTarget,
+        audio: &[u8],
+        silent: bool,
+    ) -> Result<Message, TelegramError> {
+        let api = self.api()?;
+        let chat_id = target.chat_id;
 
-        if let Some(v) = notifications_enabled {
-            config.telegram.notifications_enabled = v;
-        }
-        if let Some(v) = notify_on_task_done {
-            config.telegram.notify_on_task_done = v;
-        }
-        if let Some(v) = include_llm_summary {
-            config.telegram.include_llm_summary = v;
-        }
-        if let Some(v) = stream_enabled {
-            config.telegram.stream_enabled = v;
-        }
+        // The Bot API client uploads from a path
+        let file = tempfile::Builder::new()
+            .suffix(".ogg")
+            .tempfile()
+            .map_err(|e| TelegramError::Api(e.to_string()))?;
+        tokio::fs::write(file.path(), audio)
+            .await
+            .map_err(|e| TelegramError::Api(e.to_string()))?;
 
-        Ok(config.telegram.clone())
+        let params = SendVoiceParams::builder()
+            .chat_id(ChatId::Integer(chat_id))
+            .maybe_message_thread_id(target.message_thread_id)
+            .voice(FileUpload::InputFile(InputFile {
+                path: file.path().to_path_buf(),
+            }))
+            .disable_notification(silent)
+            .build();
+
+        let params = &params;
+        let sent = self
+            .with_retry(chat_id, move || api.send_voice(params))
+            .await?;
+
+        Ok(sent.result)
     }
 
-    // ========================================================================
-    // Real-Time Streaming
-    // ========================================================================
+    /// Run a Bot API call sending to `chat_id`, retrying transient failures with
+    /// exponential backoff
+    ///
+    /// Every attempt waits for the send rate limiter first. Network errors, 5xx
+    /// responses and 429s are retried; a 429's `retry_after` takes precedence
+    /// over the computed backoff.
+    async fn with_retry<T, F, Fut>(&self, chat_id: i64, mut call: F) -> Result<T, TelegramError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, frankenstein::Error>>,
+    {
+        let policy = self.config.read().await.telegram.retry.clone();
+        let mut attempt = 1;
+        loop {
+            self.rate_limiter.acquire(chat_id).await;
+            let error = match call().await {
+                Ok(result) => return Ok(result),
+                Err(error) => error,
+            };
+            match retry_delay(&policy, attempt, api_error_details(&error)) {
+                Some(delay) => {
+                    tracing::warn!(
+                        "Telegram API call failed (attempt {}/{}), retrying in {:?}: {}",
+                        attempt,
+                        policy.max_attempts,
+                        delay,
+                        error
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                None => return Err(TelegramError::Api(error.to_string())),
+            }
+        }
+    }
 
-    /// Spawn a task that streams execution output to Telegram in real-time.
+    /// Send a task completion notification
     ///
-    /// The spawned task subscribes to the MsgStore broadcast channel and
-    /// sends/edits a single Telegram message with the growing output.
-    /// Updates are debounced to avoid hitting Telegram API rate limits.
-    pub fn spawn_stream_to_telegram(
+    /// If `include_llm_summary` is true and a summary is provided, it will be included.
+    pub async fn send_task_notification(
         &self,
-        execution_id: Uuid,
-        task_name: String,
-        msg_stores: Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>>,
-    ) -> Option<JoinHandle<()>> {
-        // Clone what we need for the spawned task
-        let config = self.config.clone();
-        let api = self.api.clone()?;
+        task: &Task,
+        llm_summary: Option<&str>,
+    ) -> Result<(), TelegramError> {
+        self.send_status_notification(task, TaskNotificationKind::Done, llm_summary)
+            .await
+    }
 
-        Some(tokio::spawn(async move {
-            // Check if streaming is enabled (inside async context)
-            let config_guard = config.read().await;
-            if !config_guard.telegram.stream_enabled || !config_guard.telegram.notifications_enabled
-            {
-                return;
-            }
-            let Some(chat_id) = config_guard.telegram.chat_id else {
-                return;
-            };
-            drop(config_guard);
+    /// Send a notification for a task status transition
+    ///
+    /// Which kinds reach Telegram is up to the notification preferences,
+    /// applied by the dispatcher. Here kinds are only narrowed down to the
+    /// `notify_transitions` when some are set, on top of the
+    /// `notifications_enabled` master switch. Nothing is sent while muted
+    /// with /mute. The notification goes to the task's assignees and watchers,
+    /// or to the primary chat while the task has none, and is held for the
+    /// next digest of accounts that chose one with /digest.
+    pub async fn send_status_notification(
+        &self,
+        task: &Task,
+        kind: TaskNotificationKind,
+        llm_summary: Option<&str>,
+    ) -> Result<(), TelegramError> {
+        self.send_status_notification_with_excerpt(task, kind, llm_summary, None)
+            .await
+    }
 
-            // Get MsgStore
-            let store = {
-                let map = msg_stores.read().await;
-                map.get(&execution_id).cloned()
-            };
+    /// Render a task notification with the current settings without sending
+    /// it, so settings changes can be previewed
+    pub async fn preview_notification(
+        &self,
+        kind: TaskNotificationKind,
+        title: &str,
+        llm_summary: Option<&str>,
+        error_excerpt: Option<&str>,
+    ) -> NotificationPreview {
+        let config = self.config.read().await;
+        let telegram_config = &config.telegram;
+        let locale = Locale::resolve(&config.language, None);
+
+        let html = format_status_notification(
+            kind,
+            title,
+            llm_summary,
+            error_excerpt,
+            telegram_config,
+            locale,
+        );
+        let mode = telegram_config.parse_mode;
+        let messages = split_html_message(&html, TELEGRAM_MAX_MESSAGE_LEN)
+            .iter()
+            .map(|chunk| render_message(chunk, mode).0)
+            .collect();
+        let buttons = if kind == TaskNotificationKind::Failed {
+            [ServerMessage::RetryButton, ServerMessage::AckButton]
+                .iter()
+                .map(|button| {
+                    let label = button.render(locale);
+                    match mode {
+                        TelegramParseMode::Accessible => strip_emoji(&label),
+                        _ => label,
+                    }
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
 
-            let Some(store) = store else {
-                tracing::debug!("No MsgStore found for execution {}", execution_id);
-                return;
-            };
+        // Failures are never narrowed down by transition, like when sending
+        let transitions = &telegram_config.notify_transitions;
+        let kind_enabled = config.notification_preferences.allows(
+            NotificationEventType::from_kind(kind),
+            NotificationChannel::Telegram,
+        ) && (transitions.is_empty()
+            || kind == TaskNotificationKind::Failed
+            || transitions
+                .iter()
+                .any(|rule| TaskNotificationKind::from_status(&rule.to) == Some(kind)));
+
+        NotificationPreview {
+            html,
+            messages,
+            parse_mode: mode,
+            buttons,
+            silent: kind.is_silent(telegram_config),
+            enabled: telegram_config.notifications_enabled
+                && kind_enabled
+                && !telegram_config.is_muted(Utc::now()),
+        }
+    }
 
-            // Send initial message
-            let initial_text = format!(
-                "🚀 <b>{}</b>\n\n⏳ Starting...",
-                escape_html(&task_name)
-            );
-            let send_params = SendMessageParams::builder()
-                .chat_id(ChatId::Integer(chat_id))
-                .text(&initial_text)
-                .parse_mode(ParseMode::Html)
-                .build();
+    /// Like [`Self::send_status_notification`], adding the tail of the run's
+    /// error output for failures
+    ///
+    /// Failure notifications offer a Retry button.
+    pub async fn send_status_notification_with_excerpt(
+        &self,
+        task: &Task,
+        kind: TaskNotificationKind,
+        llm_summary: Option<&str>,
+        error_excerpt: Option<&str>,
+    ) -> Result<(), TelegramError> {
+        let transitions = self.config.read().await.telegram.notify_transitions.clone();
+        let transition = if transitions.is_empty() || kind == TaskNotificationKind::Failed {
+            None
+        } else {
+            Some(TaskStatusChange::find_latest_transition(&self.pool, task.id).await?)
+        };
 
-            let msg_id = match api.send_message(&send_params).await {
-                Ok(response) => response.result.message_id,
-                Err(e) => {
-                    tracing::warn!("Failed to send initial stream message: {}", e);
-                    return;
-                }
-            };
+        let config = self.config.read().await;
+        let telegram_config = &config.telegram;
+        let locale = Locale::resolve(&config.language, None);
 
-            // Buffer for debouncing
-            let mut last_update = Instant::now();
-            let flush_interval = Duration::from_millis(500);
+        let enabled = match transition {
+            None => true,
+            Some(latest) => {
+                let (from, to) = latest.unwrap_or((None, task.status.clone()));
+                transitions.iter().any(|rule| rule.matches(from.as_ref(), &to))
+            }
+        };
 
-            // Track seen entries to avoid partial updates
-            let mut seen_entries: HashMap<usize, String> = HashMap::new();
-            let mut last_entry_index: Option<usize> = None;
+        // Check if notifications are enabled and user is linked
+        if !telegram_config.notifications_enabled || !enabled {
+            tracing::debug!("Telegram {:?} notifications disabled, skipping", kind);
+            return Ok(());
+        }
+        if telegram_config.is_muted(Utc::now()) {
+            tracing::debug!("Telegram notifications muted, skipping {:?}", kind);
+            return Ok(());
+        }
 
-            // Track message IDs for multi-message support
-            let mut message_ids: Vec<i32> = vec![msg_id];
-            let mut accumulated_content = String::new();
+        let chat_id = telegram_config.chat_id.ok_or(TelegramError::NotLinked)?;
 
-            tracing::debug!("Telegram streaming started for execution {}", execution_id);
+        let message = format_status_notification(
+            kind,
+            &task.title,
+            llm_summary,
+            error_excerpt,
+            telegram_config,
+            locale,
+        );
 
-            let mut stream = store.history_plus_stream();
-            while let Some(Ok(msg)) = stream.next().await {
-                match msg {
-                    LogMsg::JsonPatch(patch) => {
-                        tracing::debug!("Received JsonPatch in Telegram stream");
-                        // Extract NormalizedEntry from the patch
-                        if let Some((entry_index, entry)) = extract_normalized_entry_from_patch(&patch) {
-                            tracing::debug!("Extracted entry type: {:?}", entry.entry_type);
-                            if let Some(formatted) = format_entry(&entry) {
-                                tracing::debug!("Formatted entry: {}", formatted);
-
-                                // Check if we've seen this entry before
-                                let previous_content = seen_entries.get(&entry_index);
-
-                                // Determine if entry is complete
-                                let is_complete = formatted.ends_with('\n')
-                                    || formatted.ends_with('.')
-                                    || formatted.ends_with('!')
-                                    || formatted.ends_with('?')
-                                    || matches!(entry.entry_type, NormalizedEntryType::ToolUse { .. });
-
-                                // Also emit if a NEW entry arrives (previous must be done)
-                                let new_entry_arrived = last_entry_index.is_some()
-                                    && last_entry_index != Some(entry_index);
-
-                                // Only add to buffer if content changed AND is complete
-                                let content_changed = previous_content != Some(&formatted);
-
-                                if content_changed && (is_complete || new_entry_arrived) {
-                                    if !accumulated_content.is_empty() {
-                                        accumulated_content.push('\n');
-                                    }
-                                    accumulated_content.push_str(&formatted);
-                                    seen_entries.insert(entry_index, formatted);
-                                    last_entry_index = Some(entry_index);
-
-                                    // Debounce updates
-                                    if last_update.elapsed() >= flush_interval {
-                                        send_or_split_message(
-                                            &api,
-                                            chat_id,
-                                            &task_name,
-                                            &accumulated_content,
-                                            &mut message_ids,
-                                        ).await;
-                                        last_update = Instant::now();
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    LogMsg::Finished => {
-                        tracing::debug!("Telegram stream finished, accumulated content length: {}", accumulated_content.len());
+        let project_chat_id = telegram_config
+            .project_bot(task.project_id)
+            .map(|bot| bot.chat_id.unwrap_or(chat_id));
+        let silent = kind.is_silent(telegram_config);
+        let edit_in_place = telegram_config.edit_in_place;
+        let batch_window = (telegram_config.batch_window_secs > 0)
+            .then(|| Duration::from_secs(telegram_config.batch_window_secs));
+        let voice_summary = llm_summary
+            .filter(|_| telegram_config.voice_summary)
+            .filter(|_| matches!(kind, TaskNotificationKind::Done | TaskNotificationKind::Failed))
+            .map(|summary| (summary.to_string(), telegram_config.tts.clone()));
+
+        drop(config);
+
+        // Offer a one-tap retry and acknowledgment for failures, and a
+        // one-tap merge when the work is ready and a PR is open
+        let buttons = if kind == TaskNotificationKind::Failed {
+            Some(inline_keyboard(&[
+                CallbackButton {
+                    label: ServerMessage::RetryButton.render(locale),
+                    data: format!("{RETRY_CALLBACK_PREFIX}{}", task.id),
+                },
+                CallbackButton {
+                    label: ServerMessage::AckButton.render(locale),
+                    data: format!("{ACK_CALLBACK_PREFIX}{}", task.id),
+                },
+            ]))
+        } else if matches!(
+            kind,
+            TaskNotificationKind::Done | TaskNotificationKind::InReview
+        ) {
+            match self.open_pr_for_task(task).await {
+                Ok(pr) => pr.map(|pr| {
+                    inline_keyboard(&[CallbackButton {
+                        label: ServerMessage::MergePrButton(pr.pr_info.number).render(locale),
+                        data: format!("{MERGE_CALLBACK_PREFIX}{}", task.id),
+                    }])
+                }),
+                Err(e) => {
+                    tracing::warn!("Failed to look up PR for task {}: {}", task.id, e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
 
-                        // Final flush
-                        send_or_split_message(
-                            &api,
+        // A project with a bot of its own is only notified in that bot's chat
+        let recipients = match project_chat_id {
+            Some(chat_id) => vec![chat_id],
+            None => self.notification_chat_ids(task.id, chat_id).await?,
+        };
+        let sender = self.for_project(task.project_id).await;
+        // Synthesized once, after the first text notification is out
+        let mut voice_clip = None;
+
+        // A failed send to one recipient does not keep the others from theirs
+        let mut first_error = None;
+        for chat_id in recipients {
+            // Held for the account's digest, which is sent through the default bot
+            if project_chat_id.is_none()
+                && self.digest_mode(chat_id).await != TelegramDigestMode::Immediate
+            {
+                if let Err(e) =
+                    TelegramDigestItem::create(&self.pool, chat_id, Some(task.id), &message).await
+                {
+                    tracing::warn!(
+                        "Failed to hold notification for chat {} about task {}: {}",
+                        chat_id,
+                        task.id,
+                        e
+                    );
+                    first_error.get_or_insert(e.into());
+                }
+                continue;
+            }
+            let target = sender.notification_target(chat_id, task.project_id).await;
+            let edited = edit_in_place
+                && sender
+                    .edit_progress_message(task.id, chat_id, &message, buttons.clone())
+                    .await;
+            // Text notifications wait out the batching window, to be combined
+            // with the others the chat gets meanwhile
+            if let Some(window) = batch_window.filter(|_| !edit_in_place) {
+                sender.enqueue_batched(
+                    target,
+                    BatchedNotification {
+                        task_id: task.id,
+                        notification_id: None,
+                        message: message.clone(),
+                        buttons: buttons.clone(),
+                        silent,
+                    },
+                    window,
+                );
+            } else if !edit_in_place {
+                // Queued, so the notification survives restarts and outages
+                if let Err(e) = sender
+                    .enqueue_message(target, Some(task.id), &message, buttons.clone(), silent)
+                    .await
+                {
+                    tracing::warn!(
+                        "Failed to queue notification for chat {} about task {}: {}",
+                        chat_id,
+                        task.id,
+                        e
+                    );
+                    first_error.get_or_insert(e);
+                    continue;
+                }
+            } else if !edited {
+                let sent = match sender
+                    .send_html_with_markup(target, &message, buttons.clone(), silent)
+                    .await
+                {
+                    Ok(sent) => sent,
+                    Err(e) => {
+                        tracing::warn!(
+                            "Failed to notify chat {} about task {}: {}",
                             chat_id,
-                            &task_name,
-                            &accumulated_content,
-                            &mut message_ids,
-                        ).await;
-
-                        // Append done indicator to LAST message only
-                        let last_msg_id = *message_ids.last().unwrap();
-                        let final_text = format_stream_message(
-                            &task_name,
-                            &format!("{}\n\n✅ Done", accumulated_content)
+                            task.id,
+                            e
                         );
+                        first_error.get_or_insert(e);
+                        continue;
+                    }
+                };
+
+                // Remember which task this message is about so replies can be routed to it
+                if let Err(e) =
+                    TelegramMessageTask::create(&self.pool, chat_id, sent.message_id, task.id).await
+                {
+                    tracing::warn!(
+                        "Failed to record Telegram message for task {}: {}",
+                        task.id,
+                        e
+                    );
+                }
 
-                        let edit_params = EditMessageTextParams::builder()
-                            .chat_id(ChatId::Integer(chat_id))
-                            .message_id(last_msg_id)
-                            .text(&final_text)
-                            .parse_mode(ParseMode::Html)
-                            .build();
+                if edit_in_place
+                    && let Err(e) = TelegramProgressMessage::upsert(
+                        &self.pool,
+                        task.id,
+                        chat_id,
+                        sent.message_id,
+                    )
+                    .await
+                {
+                    tracing::warn!(
+                        "Failed to record progress message for task {}: {}",
+                        task.id,
+                        e
+                    );
+                }
+            }
 
-                        if let Err(e) = api.edit_message_text(&edit_params).await {
-                            tracing::debug!("Failed to edit final message: {}", e);
+            // The text notification is already out (and alerted), so the voice clip
+            // is sent silently and a failure is only logged
+            if let Some((summary, tts_config)) = &voice_summary {
+                if voice_clip.is_none() {
+                    voice_clip = Some(match self.tts.synthesize(tts_config, summary).await {
+                        Ok(audio) => Some(audio),
+                        Err(e) => {
+                            tracing::warn!("Failed to synthesize voice summary: {}", e);
+                            None
                         }
-                        break;
-                    }
-                    LogMsg::Stdout(_) => {
-                        tracing::trace!("Telegram stream received Stdout (ignored)");
-                    }
-                    _ => {} // Ignore Stderr, etc.
+                    });
+                }
+                if let Some(Some(audio)) = &voice_clip
+                    && let Err(e) = sender.send_voice(target, audio, true).await
+                {
+                    tracing::warn!("Failed to send voice summary for task {}: {}", task.id, e);
                 }
             }
-        }))
-    }
+        }
 
-    /// Clean up expired link tokens
-    fn cleanup_expired_tokens(&self) {
-        self.pending_links.retain(|_, token| !token.is_expired());
+        first_error.map_or(Ok(()), Err)
     }
-}
-
-// ============================================================================
-// Streaming Helper Functions
-// ============================================================================
-
-/// Send or split a message to Telegram, creating multiple messages if needed
-async fn send_or_split_message(
-    api: &AsyncApi,
-    chat_id: i64,
-    task_name: &str,
-    content: &str,
-    message_ids: &mut Vec<i32>,
-) {
-    const MAX_CONTENT_LEN: usize = 3900; // Leave room for header/footer
-
-    if content.len() <= MAX_CONTENT_LEN {
-        // Single message - edit the last one
-        let text = format_stream_message(task_name, content);
-        let last_msg_id = *message_ids.last().unwrap();
-
-        let edit_params = EditMessageTextParams::builder()
-            .chat_id(ChatId::Integer(chat_id))
-            .message_id(last_msg_id)
-            .text(&text)
-            .parse_mode(ParseMode::Html)
-            .build();
 
-        if let Err(e) = api.edit_message_text(&edit_params).await {
-            tracing::debug!("Failed to edit message: {}", e);
+    /// Send the preview screenshot of a finished attempt to the chats its
+    /// notification went to
+    ///
+    /// Capturing boots the dev server, so the screenshot follows the
+    /// notification once it is ready, silently. Accounts holding their
+    /// notifications for a digest do not get it.
+    pub async fn send_preview_screenshot(
+        &self,
+        task: &Task,
+        photo: &Path,
+    ) -> Result<(), TelegramError> {
+        let config = self.config.read().await;
+        let telegram_config = &config.telegram;
+        if !telegram_config.notifications_enabled || telegram_config.is_muted(Utc::now()) {
+            return Ok(());
         }
-    } else {
-        // Need to split - find last newline before limit
-        let split_pos = content[..MAX_CONTENT_LEN]
-            .rfind('\n')
-            .unwrap_or(MAX_CONTENT_LEN);
+        let chat_id = telegram_config.chat_id.ok_or(TelegramError::NotLinked)?;
+        let project_chat_id = telegram_config
+            .project_bot(task.project_id)
+            .map(|bot| bot.chat_id.unwrap_or(chat_id));
+        drop(config);
+
+        let caption = format!("🖼 Preview: {}", escape_html(&task.title));
+        let caption = if caption.len() <= TELEGRAM_MAX_CAPTION_LEN {
+            caption
+        } else {
+            "🖼 Preview".to_string()
+        };
+        let recipients = match project_chat_id {
+            Some(chat_id) => vec![chat_id],
+            None => self.notification_chat_ids(task.id, chat_id).await?,
+        };
+        let sender = self.for_project(task.project_id).await;
 
-        let first_part = &content[..split_pos];
-        let remaining = &content[split_pos..].trim_start();
+        let mut first_error = None;
+        for chat_id in recipients {
+            if project_chat_id.is_none()
+                && self.digest_mode(chat_id).await != TelegramDigestMode::Immediate
+            {
+                continue;
+            }
+            let target = sender.notification_target(chat_id, task.project_id).await;
+            match sender.send_photo(target, photo, &caption, None, true).await {
+                Ok(sent) => {
+                    if let Err(e) =
+                        TelegramMessageTask::create(&self.pool, chat_id, sent.message_id, task.id)
+                            .await
+                    {
+                        tracing::warn!(
+                            "Failed to record Telegram message for task {}: {}",
+                            task.id,
+                            e
+                        );
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to send preview to chat {} for task {}: {}",
+                        chat_id,
+                        task.id,
+                        e
+                    );
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
 
-        // Edit last message with first part
-        let last_msg_id = *message_ids.last().unwrap();
-        let text = format_stream_message(task_name, first_part);
-        let edit_params = EditMessageTextParams::builder()
-            .chat_id(ChatId::Integer(chat_id))
-            .message_id(last_msg_id)
-            .text(&text)
-            .parse_mode(ParseMode::Html)
-            .build();
+        first_error.map_or(Ok(()), Err)
+    }
 
-        if let Err(e) = api.edit_message_text(&edit_params).await {
-            tracing::debug!("Failed to edit message: {}", e);
+    /// Hold `notification` back for `window`, starting the window if it is the
+    /// first one for `target`
+    fn enqueue_batched(
+        &self,
+        target: ReplyTarget,
+        notification: BatchedNotification,
+        window: Duration,
+    ) {
+        let mut opened = false;
+        self.notification_batches
+            .entry(target)
+            .or_insert_with(|| {
+                opened = true;
+                Vec::new()
+            })
+            .push(notification);
+        if !opened {
             return;
         }
 
-        // Send new message for remaining content
-        let new_text = format!(
-            "🚀 <b>{}</b> (continued)\n\n<pre>{}</pre>",
-            escape_html(task_name),
-            escape_html(remaining)
-        );
-
-        let send_params = SendMessageParams::builder()
-            .chat_id(ChatId::Integer(chat_id))
-            .text(&new_text)
-            .parse_mode(ParseMode::Html)
-            .build();
-
-        match api.send_message(&send_params).await {
-            Ok(msg) => {
-                let new_msg_id = msg.result.message_id;
-                message_ids.push(new_msg_id);
+        let service = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(window).await;
+            if let Some((_, batch)) = service.notification_batches.remove(&target) {
+                service.send_batch(target, batch).await;
             }
-            Err(e) => tracing::debug!("Failed to send continuation message: {}", e),
-        }
+        });
     }
-}
-
-impl TelegramService {
-    // ========================================================================
-    // Webhook Handling
-    // ========================================================================
 
-    /// Handle an incoming Telegram update (webhook payload)
-    pub async fn handle_update(&self, update: Update) -> Result<UpdateResult, TelegramError> {
-        // Only handle message updates
-        let message = match update.content {
-            UpdateContent::Message(msg) => msg,
-            _ => return Ok(UpdateResult::NoResponse),
+    /// Queue the notifications batched for `target`: a lone one as it is,
+    /// with its buttons and reply routing, several as one combined message
+    async fn send_batch(&self, target: ReplyTarget, batch: Vec<BatchedNotification>) {
+        let queued = match batch.as_slice() {
+            [notification] => {
+                self.enqueue_message(
+                    target,
+                    Some(notification.task_id),
+                    &notification.message,
+                    notification.buttons.clone(),
+                    notification.silent,
+                )
+                .await
+            }
+            _ => {
+                let text = format_notification_batch(&batch, self.notification_locale().await);
+                let silent = batch.iter().all(|notification| notification.silent);
+                self.enqueue_message(target, None, &text, None, silent)
+                    .await
+            }
         };
-
-        // Get text content
-        let text = match &message.text {
-            Some(t) => t.as_str(),
-            None => return Ok(UpdateResult::NoResponse),
+        let queued = match queued {
+            Ok(id) => self
+                .link_batch(id, &batch)
+                .await
+                .map_err(TelegramError::from),
+            Err(e) => Err(e),
         };
-
-        let chat_id = message.chat.id;
-        // Telegram user IDs are u64, but we store as i64 (safe for all practical user IDs)
-        let user_id = message.from.as_ref().map(|u| u.id as i64).unwrap_or(0);
-        let username = message.from.as_ref().and_then(|u| u.username.clone());
-
-        // Parse command
-        if text.starts_with('/') {
-            let parts: Vec<&str> = text.splitn(2, ' ').collect();
-            let command = parts[0].trim_start_matches('/');
-            // Remove @botname suffix if present
-            let command = command.split('@').next().unwrap_or(command);
-            let args = parts.get(1).map(|s| s.trim()).unwrap_or("");
-
-            return self
-                .handle_command(command, args, chat_id, user_id, username)
-                .await;
+        if let Err(e) = queued {
+            tracing::warn!(
+                "Failed to queue batched notifications for chat {}: {}",
+                target.chat_id,
+                e
+            );
         }
+    }
 
-        // Non-command messages are ignored for now
-        Ok(UpdateResult::NoResponse)
+    /// Tie a queued batch message to the history entries of the
+    /// notifications it delivers
+    async fn link_batch(&self, id: Uuid, batch: &[BatchedNotification]) -> Result<(), sqlx::Error> {
+        for notification_id in batch
+            .iter()
+            .filter_map(|notification| notification.notification_id)
+        {
+            TelegramOutgoingMessage::link_notification(&self.pool, id, notification_id).await?;
+        }
+        Ok(())
     }
 
-    // ========================================================================
-    // Command Handlers
-    // ========================================================================
+    /// Add a message to the durable send queue, to be sent by this handle's bot
+    /// once the chat's earlier messages are out. Replies to it are routed to
+    /// `task_id`.
+    async fn enqueue_message(
+        &self,
+        target: ReplyTarget,
+        task_id: Option<Uuid>,
+        text: &str,
+        reply_markup: Option<ReplyMarkup>,
+        silent: bool,
+    ) -> Result<Uuid, TelegramError> {
+        let reply_markup = reply_markup
+            .map(|markup| serde_json::to_string(&markup))
+            .transpose()
+            .map_err(|e| TelegramError::Api(e.to_string()))?;
+        let id = TelegramOutgoingMessage::enqueue(
+            &self.pool,
+            &CreateTelegramOutgoingMessage {
+                bot_project_id: self.bot_project_id,
+                chat_id: target.chat_id,
+                message_thread_id: target.message_thread_id,
+                task_id,
+                text: text.to_string(),
+                reply_markup,
+                silent,
+            },
+        )
+        .await?;
+        Ok(id)
+    }
 
-    /// Handle a bot command
-    async fn handle_command(
+    /// Send a message from the queue through the bot it was queued for
+    pub async fn send_queued(
         &self,
-        command: &str,
-        args: &str,
-        chat_id: i64,
-        user_id: i64,
-        username: Option<String>,
-    ) -> Result<UpdateResult, TelegramError> {
-        match command {
-            "start" => self.cmd_start(args, chat_id, user_id, username).await,
-            "help" => self.cmd_help().await,
-            "projects" => self.cmd_projects().await,
-            "project" => self.cmd_project(args, chat_id).await,
-            "tasks" => self.cmd_tasks(args, chat_id).await,
-            "task" => self.cmd_task(args).await,
-            "newtask" => self.cmd_newtask(args, chat_id).await,
-            "message" => self.cmd_message(args).await,
-            _ => Ok(UpdateResult::Response(format!(
-                "Unknown command: /{}. Use /help to see available commands.",
-                command
-            ))),
+        message: &TelegramOutgoingMessage,
+    ) -> Result<(), TelegramError> {
+        let sender = match message.bot_project_id {
+            Some(project_id) => self.for_project(project_id).await,
+            None => self.clone(),
+        };
+        let reply_markup = message
+            .reply_markup
+            .as_deref()
+            .map(serde_json::from_str::<ReplyMarkup>)
+            .transpose()
+            .map_err(|e| TelegramError::Api(e.to_string()))?;
+        let target = ReplyTarget {
+            chat_id: message.chat_id,
+            message_thread_id: message.message_thread_id,
+        };
+        let sent = sender
+            .send_html_with_markup(target, &message.text, reply_markup, message.silent)
+            .await?;
+
+        // Remember which task this message is about so replies can be routed to it
+        if let Some(task_id) = message.task_id
+            && let Err(e) =
+                TelegramMessageTask::create(&self.pool, message.chat_id, sent.message_id, task_id)
+                    .await
+        {
+            tracing::warn!(
+                "Failed to record Telegram message for task {}: {}",
+                task_id,
+                e
+            );
         }
+        Ok(())
     }
 
-    /// Handle /start command (with optional link token)
-    async fn cmd_start(
-        &self,
-        args: &str,
-        chat_id: i64,
-        user_id: i64,
-        username: Option<String>,
-    ) -> Result<UpdateResult, TelegramError> {
-        // Check if this is a link request
-        if !args.is_empty() {
-            // Validate and complete the link
-            match self.complete_link(args, chat_id, user_id, username.clone()).await {
-                Ok(_) => {
-                    return Ok(UpdateResult::LinkCompleted {
-                        chat_id,
-                        user_id,
-                        username,
-                    });
-                }
-                Err(TelegramError::InvalidLinkToken) => {
-                    return Ok(UpdateResult::Response(
-                        "❌ Invalid or expired link token. Please generate a new link from the web interface.".to_string()
-                    ));
+    /// Digest mode of the account chatting in `chat_id`, immediate for chats
+    /// that are not an account's
+    async fn digest_mode(&self, chat_id: i64) -> TelegramDigestMode {
+        match TelegramAccount::find_digest_mode_by_chat_id(&self.pool, chat_id).await {
+            Ok(mode) => mode.unwrap_or_default(),
+            Err(e) => {
+                tracing::warn!("Failed to look up digest mode for chat {}: {}", chat_id, e);
+                TelegramDigestMode::Immediate
+            }
+        }
+    }
+
+    /// Queue the digests that are due, each combining the notifications held
+    /// for a chat into one message
+    pub async fn send_due_digests(&self) -> Result<(), TelegramError> {
+        let chat_ids = TelegramDigestItem::find_due_chat_ids(&self.pool).await?;
+        if chat_ids.is_empty() {
+            return Ok(());
+        }
+        let locale = self.notification_locale().await;
+        for chat_id in chat_ids {
+            let items = TelegramDigestItem::take_for_chat(&self.pool, chat_id).await?;
+            let target = ReplyTarget {
+                chat_id,
+                message_thread_id: None,
+            };
+            // Replies to a lone notification still go to its task
+            let queued = match items.as_slice() {
+                [] => continue,
+                [item] => {
+                    self.enqueue_message(target, item.task_id, &item.text, None, false)
+                        .await
                 }
-                Err(TelegramError::LinkTokenExpired) => {
-                    return Ok(UpdateResult::Response(
-                        "❌ This link has expired. Please generate a new link from the web interface.".to_string()
-                    ));
+                items => {
+                    self.enqueue_message(target, None, &format_digest(items, locale), None, false)
+                        .await
                 }
-                Err(e) => return Err(e),
+            };
+            if let Err(e) = queued {
+                tracing::warn!("Failed to queue digest for chat {}: {}", chat_id, e);
             }
         }
-
-        // Regular /start - show welcome message
-        let welcome = r#"👋 <b>Welcome to VibeKanban Bot!</b>
-
-I can help you manage your tasks and receive notifications.
-
-<b>Available commands:</b>
-/help - Show all commands
-/projects - List your projects
-/project &lt;id&gt; - Set active project
-/tasks - List tasks in active project
-/task &lt;id&gt; - Get task details
-/newtask &lt;title&gt; - Create a new task
-/message &lt;task_id&gt; &lt;text&gt; - Send message to a task
-
-To link your account, use the link from the web interface."#;
-
-        Ok(UpdateResult::Response(welcome.to_string()))
+        Ok(())
     }
 
-    /// Handle /help command
-    async fn cmd_help(&self) -> Result<UpdateResult, TelegramError> {
-        let help = r#"<b>VibeKanban Bot Commands</b>
-
-<b>Account:</b>
-/start - Welcome message & account linking
-
-<b>Projects:</b>
-/projects - List all projects
-/project &lt;id&gt; - Set active project for subsequent commands
-
-<b>Tasks:</b>
-/tasks - List tasks in active project
-/tasks &lt;project_id&gt; - List tasks in specific project
-/task &lt;id&gt; - Get task details
-/newtask &lt;title&gt; - Create task in active project
-/newtask &lt;project_id&gt; &lt;title&gt; - Create task in specific project
-
-<b>Messages:</b>
-/message &lt;task_id&gt; &lt;text&gt; - Send/queue a message for a task
-
-<b>Notes:</b>
-- Task and project IDs are UUIDs (can use short prefix)
-- Set an active project with /project to avoid typing IDs"#;
-
-        Ok(UpdateResult::Response(help.to_string()))
+    /// Chats notified about `task_id`: those of its assignees and watchers, or
+    /// the primary chat while the task has none
+    async fn notification_chat_ids(
+        &self,
+        task_id: Uuid,
+        primary_chat_id: i64,
+    ) -> Result<Vec<i64>, TelegramError> {
+        let mut chat_ids: Vec<i64> = Vec::new();
+        for assignee in TaskAssignee::find_by_task_id(&self.pool, task_id).await? {
+            if !chat_ids.contains(&assignee.chat_id) {
+                chat_ids.push(assignee.chat_id);
+            }
+        }
+        if chat_ids.is_empty() {
+            chat_ids.push(primary_chat_id);
+        }
+        Ok(chat_ids)
     }
 
-    /// Handle /projects command
-    async fn cmd_projects(&self) -> Result<UpdateResult, TelegramError> {
-        let projects = Project::find_all(&self.pool).await?;
-
-        if projects.is_empty() {
-            return Ok(UpdateResult::Response(
-                "No projects found. Create a project in the web interface first.".to_string(),
-            ));
+    /// Edit the task's progress message in `chat_id` to show `text`. Returns
+    /// false when there is none yet or it can no longer be edited, e.g.
+    /// because it was deleted, so the caller sends a new one instead.
+    async fn edit_progress_message(
+        &self,
+        task_id: Uuid,
+        chat_id: i64,
+        text: &str,
+        reply_markup: Option<ReplyMarkup>,
+    ) -> bool {
+        let message_id =
+            match TelegramProgressMessage::find_message_id(&self.pool, task_id, chat_id).await {
+                Ok(Some(message_id)) => message_id,
+                Ok(None) => return false,
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to look up progress message for task {}: {}",
+                        task_id,
+                        e
+                    );
+                    return false;
+                }
+            };
+        match self
+            .edit_html(chat_id, message_id, text, reply_markup)
+            .await
+        {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::debug!(
+                    "Failed to edit progress message for task {}, sending a new one: {}",
+                    task_id,
+                    e
+                );
+                false
+            }
         }
+    }
 
-        let mut message = String::from("<b>Your Projects:</b>\n\n");
-        for project in projects {
-            message.push_str(&format!(
-                "• <b>{}</b>\n  <code>{}</code>\n\n",
-                escape_html(&project.name),
-                project.id
-            ));
+    /// Where notifications about a project go in `chat_id`: the project's
+    /// forum topic when it has one there
+    async fn notification_target(&self, chat_id: i64, project_id: Uuid) -> ReplyTarget {
+        let message_thread_id =
+            match TelegramProjectTopic::find_thread_id(&self.pool, chat_id, project_id).await {
+                Ok(thread_id) => thread_id,
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to look up forum topic for project {}: {}",
+                        project_id,
+                        e
+                    );
+                    None
+                }
+            };
+        ReplyTarget {
+            chat_id,
+            message_thread_id,
         }
-        message.push_str("Use /project <id> to set the active project.");
-
-        Ok(UpdateResult::Response(message))
     }
 
-    /// Handle /project command - set active project
-    async fn cmd_project(&self, args: &str, chat_id: i64) -> Result<UpdateResult, TelegramError> {
-        if args.is_empty() {
-            // Show current active project
-            if let Some(project_id) = self.active_projects.get(&chat_id).map(|r| *r)
-                && let Some(project) = Project::find_by_id(&self.pool, project_id).await?
-            {
-                return Ok(UpdateResult::Response(format!(
-                    "Active project: <b>{}</b>\n<code>{}</code>",
-                    escape_html(&project.name),
-                    project.id
-                )));
-            }
-            return Ok(UpdateResult::Response(
-                "No active project set. Use /project <id> to set one.".to_string(),
-            ));
+    /// Send a critical alert to the linked chat
+    ///
+    /// Critical alerts only respect the `notifications_enabled` master switch, not
+    /// the per-kind toggles.
+    pub async fn send_critical_notification(&self, message: &str) -> Result<(), TelegramError> {
+        let config = self.config.read().await;
+        if !config.telegram.notifications_enabled {
+            return Ok(());
         }
+        let chat_id = config.telegram.chat_id.ok_or(TelegramError::NotLinked)?;
+        drop(config);
 
-        // Parse project ID
-        let project_id = parse_uuid(args)?;
+        self.send_message(chat_id, message).await
+    }
 
-        // Verify project exists
-        let project = Project::find_by_id(&self.pool, project_id)
-            .await?
-            .ok_or(TelegramError::ProjectNotFound(project_id))?;
+    // ========================================================================
+    // Link Management
+    // ========================================================================
 
-        // Set active project
-        self.active_projects.insert(chat_id, project_id);
+    /// Generate a new link token for account linking
+    ///
+    /// Returns a tuple of (token, deep_link_url)
+    pub fn generate_link_token(&self) -> Result<(String, String), TelegramError> {
+        let token = Uuid::new_v4().to_string();
+        let link_token = LinkToken {
+            token: token.clone(),
+            created_at: Utc::now(),
+        };
 
-        Ok(UpdateResult::Response(format!(
-            "✅ Active project set to: <b>{}</b>",
-            escape_html(&project.name)
-        )))
-    }
+        // Clean up expired tokens first
+        self.cleanup_expired_tokens();
 
-    /// Handle /tasks command
-    async fn cmd_tasks(&self, args: &str, chat_id: i64) -> Result<UpdateResult, TelegramError> {
-        // Determine project ID
-        let project_id = if args.is_empty() {
-            // Use active project
-            self.active_projects
-                .get(&chat_id)
-                .map(|r| *r)
-                .ok_or(TelegramError::NoActiveProject)?
+        // Store the new token
+        self.pending_links.insert(token.clone(), link_token);
+
+        // Generate the deep link URL
+        let deep_link = if let Some(username) = &self.bot_username {
+            format!("https://t.me/{}?start={}", username, token)
         } else {
-            parse_uuid(args)?
+            // If we don't know the bot username, just return the token
+            format!("start={}", token)
         };
 
-        // Get project name
-        let project = Project::find_by_id(&self.pool, project_id)
-            .await?
-            .ok_or(TelegramError::ProjectNotFound(project_id))?;
-
-        // Get tasks
-        let tasks = Task::find_by_project_id_with_attempt_status(&self.pool, project_id).await?;
-
-        if tasks.is_empty() {
-            return Ok(UpdateResult::Response(format!(
-                "No tasks in project <b>{}</b>.",
-                escape_html(&project.name)
-            )));
-        }
+        Ok((token, deep_link))
+    }
 
-        let mut message = format!("<b>Tasks in {}</b>\n\n", escape_html(&project.name));
-        for task in tasks.iter().take(20) {
-            let status_emoji = match task.task.status {
-                TaskStatus::Todo => "📋",
-                TaskStatus::InProgress => "🔄",
-                TaskStatus::InReview => "👀",
-                TaskStatus::Done => "✅",
-                TaskStatus::Cancelled => "❌",
-            };
-            message.push_str(&format!(
-                "{} <b>{}</b>\n  <code>{}</code>\n\n",
-                status_emoji,
-                escape_html(&task.task.title),
-                task.task.id
-            ));
-        }
+    /// Validate a link token (check if it exists and is not expired)
+    pub fn validate_link_token(&self, token: &str) -> Result<(), TelegramError> {
+        let link_token = self
+            .pending_links
+            .get(token)
+            .ok_or(TelegramError::InvalidLinkToken)?;
 
-        if tasks.len() > 20 {
-            message.push_str(&format!("... and {} more tasks", tasks.len() - 20));
+        if link_token.is_expired() {
+            // Remove expired token
+            drop(link_token);
+            self.pending_links.remove(token);
+            return Err(TelegramError::LinkTokenExpired);
         }
 
-        Ok(UpdateResult::Response(message))
+        Ok(())
     }
 
-    /// Handle /task command - get task details
-    async fn cmd_task(&self, args: &str) -> Result<UpdateResult, TelegramError> {
-        if args.is_empty() {
-            return Ok(UpdateResult::Response(
-                "Usage: /task <task_id>".to_string(),
-            ));
-        }
-
-        let task_id = parse_uuid(args)?;
-        let task = Task::find_by_id(&self.pool, task_id)
-            .await?
-            .ok_or(TelegramError::TaskNotFound(task_id))?;
+    /// Complete the account linking process
+    ///
+    /// This is called when a user sends /start <token> to the bot. The first
+    /// account to link becomes the primary one in the config; later accounts
+    /// are added alongside it so tasks can be assigned to them.
+    /// Returns the updated TelegramConfig.
+    pub async fn complete_link(
+        &self,
+        token: &str,
+        chat_id: i64,
+        user_id: i64,
+        username: Option<String>,
+    ) -> Result<TelegramConfig, TelegramError> {
+        // Validate the token first
+        self.validate_link_token(token)?;
 
-        let status_emoji = match task.status {
-            TaskStatus::Todo => "📋 Todo",
-            TaskStatus::InProgress => "🔄 In Progress",
-            TaskStatus::InReview => "👀 In Review",
-            TaskStatus::Done => "✅ Done",
-            TaskStatus::Cancelled => "❌ Cancelled",
-        };
+        // Remove the token (single-use)
+        self.pending_links.remove(token);
 
-        let mut message = format!(
-            "<b>{}</b>\n\nStatus: {}\nID: <code>{}</code>",
-            escape_html(&task.title),
-            status_emoji,
-            task.id
-        );
+        TelegramAccount::upsert(&self.pool, user_id, chat_id, username.as_deref()).await?;
 
-        if let Some(desc) = &task.description
-            && !desc.is_empty()
+        // Update the config
+        let mut config = self.config.write().await;
+        if config
+            .telegram
+            .user_id
+            .is_none_or(|primary| primary == user_id)
         {
-            message.push_str(&format!("\n\n<b>Description:</b>\n{}", escape_html(desc)));
+            config.telegram.chat_id = Some(chat_id);
+            config.telegram.user_id = Some(user_id);
+            config.telegram.username = username;
+            config.telegram.notifications_enabled = true;
+            config.notification_preferences.set(
+                NotificationEventType::TaskDone,
+                NotificationChannel::Telegram,
+                true,
+            );
         }
 
-        Ok(UpdateResult::Response(message))
+        Ok(config.telegram.clone())
     }
 
-    /// Handle /newtask command - create a new task
-    async fn cmd_newtask(&self, args: &str, chat_id: i64) -> Result<UpdateResult, TelegramError> {
-        if args.is_empty() {
-            return Ok(UpdateResult::Response(
-                "Usage: /newtask <title> or /newtask <project_id> <title>".to_string(),
-            ));
-        }
+    /// Unlink the Telegram account, along with any other linked accounts
+    pub async fn unlink(&self) -> Result<(), TelegramError> {
+        TelegramAccount::delete_all(&self.pool).await?;
+        let mut config = self.config.write().await;
+        config.telegram = TelegramConfig::default();
+        Ok(())
+    }
 
-        // Try to parse first word as UUID (project_id)
-        let parts: Vec<&str> = args.splitn(2, ' ').collect();
-        let (project_id, title) = if parts.len() == 2 {
-            if let Ok(id) = Uuid::parse_str(parts[0]) {
-                (id, parts[1].to_string())
-            } else {
-                // First word is not a UUID, use active project
-                let pid = self
-                    .active_projects
-                    .get(&chat_id)
-                    .map(|r| *r)
-                    .ok_or(TelegramError::NoActiveProject)?;
-                (pid, args.to_string())
-            }
-        } else {
-            // Single argument = title, use active project
-            let pid = self
-                .active_projects
-                .get(&chat_id)
-                .map(|r| *r)
-                .ok_or(TelegramError::NoActiveProject)?;
-            (pid, args.to_string())
+    /// The linked account of `user_id`. The primary account may predate the
+    /// account table, so it is added on first use.
+    async fn linked_account(&self, user_id: i64) -> Result<Option<TelegramAccount>, TelegramError> {
+        if let Some(account) = TelegramAccount::find_by_user_id(&self.pool, user_id).await? {
+            return Ok(Some(account));
+        }
+        let config = self.config.read().await;
+        let (Some(primary), Some(chat_id)) = (config.telegram.user_id, config.telegram.chat_id)
+        else {
+            return Ok(None);
         };
+        if primary != user_id {
+            return Ok(None);
+        }
+        let username = config.telegram.username.clone();
+        drop(config);
 
-        // Verify project exists
-        let project = Project::find_by_id(&self.pool, project_id)
-            .await?
-            .ok_or(TelegramError::ProjectNotFound(project_id))?;
+        TelegramAccount::upsert(&self.pool, user_id, chat_id, username.as_deref()).await?;
+        Ok(TelegramAccount::find_by_user_id(&self.pool, user_id).await?)
+    }
 
-        // Create the task
-        let create_task = CreateTask::from_title_description(project_id, title.clone(), None);
-        let task_id = Uuid::new_v4();
-        let task = Task::create(&self.pool, &create_task, task_id).await?;
+    /// Check if an account is currently linked
+    pub async fn is_linked(&self) -> bool {
+        let config = self.config.read().await;
+        config.telegram.chat_id.is_some()
+    }
 
-        Ok(UpdateResult::Response(format!(
-            "✅ Created task in <b>{}</b>:\n\n<b>{}</b>\n<code>{}</code>",
-            escape_html(&project.name),
-            escape_html(&task.title),
-            task.id
-        )))
+    /// Get the current link status
+    pub async fn get_link_status(&self) -> TelegramConfig {
+        let config = self.config.read().await;
+        config.telegram.clone()
     }
 
-    /// Handle /message command - send/queue a message for a task
+    /// Language of messages the bot sends on its own, like the digest
+    pub async fn notification_locale(&self) -> Locale {
+        Locale::resolve(&self.config.read().await.language, None)
+    }
+
+    /// Update Telegram notification settings
+    ///
+    /// Allows updating individual settings without affecting others.
+    pub async fn update_settings(
+        &self,
+        update: TelegramSettingsUpdate,
+    ) -> Result<TelegramConfig, TelegramError> {
+        let mut config = self.config.write().await;
+
+        if let Some(v) = update.notifications_enabled {
+            config.telegram.notifications_enabled = v;
+        }
+        if let Some(v) = update.include_llm_summary {
+            config.telegram.include_llm_summary = v;
+        }
+        if let Some(v) = update.stream_enabled {
+            config.telegram.stream_enabled = v;
+        }
+        if let Some(v) = update.notify_on_started {
+            config.notification_preferences.set(
+                NotificationEventType::AttemptStarted,
+                NotificationChannel::Telegram,
+                v,
+            );
+        }
+        if let Some(v) = update.notify_on_in_review {
+            config.notification_preferences.set(
+                NotificationEventType::TaskInReview,
+                NotificationChannel::Telegram,
+                v,
+            );
+        }
+        if let Some(v) = update.notify_on_task_done {
+            config.notification_preferences.set(
+                NotificationEventType::TaskDone,
+                NotificationChannel::Telegram,
+                v,
+            );
+        }
+        if let Some(v) = update.notify_on_failed {
+            config.notification_preferences.set(
+                NotificationEventType::TaskFailed,
+                NotificationChannel::Telegram,
+                v,
+            );
+        }
+        if let Some(v) = update.voice_summary {
+            config.telegram.voice_summary = v;
+        }
+        if let Some(v) = update.notify_transitions {
+            config.telegram.notify_transitions = v;
+        }
+        if let Some(v) = update.silent {
+            config.telegram.silent = v;
+        }
+        if let Some(v) = update.silent_kinds {
+            config.telegram.silent_kinds = v;
+        }
+        if let Some(v) = update.edit_in_place {
+            config.telegram.edit_in_place = v;
+        }
+        if let Some(v) = update.forward_questions {
+            config.telegram.forward_questions = v;
+        }
+        if let Some(v) = update.allowed_user_ids {
+            config.telegram.allowed_user_ids = v;
+        }
+
+        Ok(config.telegram.clone())
+    }
+
+    // ========================================================================
+    // Real-Time Streaming
+    // ========================================================================
+
+    /// Spawn a task that streams execution output to Telegram in real-time.
+    ///
+    /// The spawned task subscribes to the MsgStore broadcast channel and keeps a
+    /// single Telegram message up to date with the latest agent output. Edits are
+    /// throttled to `STREAM_EDIT_INTERVAL` to stay within Telegram rate limits.
+    pub fn spawn_stream_to_telegram(
+        &self,
+        execution_id: Uuid,
+        task_name: String,
+        msg_stores: Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>>,
+    ) -> Option<JoinHandle<()>> {
+        // Clone what we need for the spawned task
+        let config = self.config.clone();
+        let api = self.api().ok()?;
+
+        Some(tokio::spawn(async move {
+            // Check if streaming is enabled (inside async context)
+            let config_guard = config.read().await;
+            if !config_guard.telegram.stream_enabled || !config_guard.telegram.notifications_enabled
+            {
+                return;
+            }
+            let Some(chat_id) = config_guard.telegram.chat_id else {
+                return;
+            };
+            let mode = config_guard.telegram.parse_mode;
+            drop(config_guard);
+
+            // Get MsgStore
+            let store = {
+                let map = msg_stores.read().await;
+                map.get(&execution_id).cloned()
+            };
+
+            let Some(store) = store else {
+                tracing::debug!("No MsgStore found for execution {}", execution_id);
+                return;
+            };
+
+            // Send initial message
+            let (initial_text, parse_mode) =
+                render_message(&format_stream_message(&task_name, "⏳ Starting..."), mode);
+            let send_params = SendMessageParams::builder()
+                .chat_id(ChatId::Integer(chat_id))
+                .text(&initial_text)
+                .maybe_parse_mode(parse_mode)
+                .build();
+
+            let msg_id = match api.send_message(&send_params).await {
+                Ok(response) => response.result.message_id,
+                Err(e) => {
+                    tracing::warn!("Failed to send initial stream message: {}", e);
+                    return;
+                }
+            };
+
+            let mut stream_message = StreamMessage::new(&api, chat_id, msg_id, &task_name, mode);
+            // Latest formatted content per normalized entry index
+            let mut entries: Vec<(usize, String)> = Vec::new();
+            let mut dirty = false;
+            let mut ticker = tokio::time::interval(STREAM_EDIT_INTERVAL);
+
+            tracing::debug!("Telegram streaming started for execution {}", execution_id);
+
+            let mut stream = store.history_plus_stream();
+            loop {
+                tokio::select! {
+                    msg = stream.next() => match msg {
+                        Some(Ok(LogMsg::JsonPatch(patch))) => {
+                            if let Some((entry_index, entry)) = extract_normalized_entry_from_patch(&patch)
+                                && let Some(formatted) = format_entry(&entry)
+                            {
+                                match entries.iter_mut().find(|(index, _)| *index == entry_index) {
+                                    Some((_, existing)) if *existing == formatted => continue,
+                                    Some((_, existing)) => *existing = formatted,
+                                    None => entries.push((entry_index, formatted)),
+                                }
+                                // Only the tail is ever shown, so older entries can be dropped
+                                if entries.len() > STREAM_MAX_ENTRIES {
+                                    entries.remove(0);
+                                }
+                                dirty = true;
+                            }
+                        }
+                        Some(Ok(LogMsg::Finished)) | None => break,
+                        Some(Ok(_)) => {} // Ignore Stdout, Stderr, etc.
+                        Some(Err(e)) => {
+                            tracing::debug!("Telegram stream error: {}", e);
+                            break;
+                        }
+                    },
+                    _ = ticker.tick() => {
+                        if dirty {
+                            stream_message.edit(&join_entries(&entries)).await;
+                            dirty = false;
+                        }
+                    }
+                }
+            }
+
+            // Final edit with the last chunk and a completion marker
+            let mut final_content = join_entries(&entries);
+            final_content.push_str("\n\n✅ Done");
+            stream_message.edit(&final_content).await;
+
+            tracing::debug!("Telegram streaming finished for execution {}", execution_id);
+        }))
+    }
+
+    /// Spawn a task that forwards the question the agent ends its turn with.
+    ///
+    /// Once the execution finishes, a final assistant message that asks
+    /// something is sent to the chat as a forced reply. The answer reaches
+    /// the session through `handle_reply`, like any reply to a task message.
+    pub fn spawn_question_forwarder(
+        &self,
+        execution_id: Uuid,
+        task: &Task,
+        msg_stores: Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>>,
+    ) -> Option<JoinHandle<()>> {
+        self.bot()?;
+        let service = self.clone();
+        let (task_id, project_id, task_name) = (task.id, task.project_id, task.title.clone());
+
+        Some(tokio::spawn(async move {
+            let config = service.config.read().await;
+            if !config.telegram.forward_questions || !config.telegram.notifications_enabled {
+                return;
+            }
+            let Some(chat_id) = config.telegram.chat_id else {
+                return;
+            };
+            drop(config);
+
+            let store = msg_stores.read().await.get(&execution_id).cloned();
+            let Some(store) = store else {
+                tracing::debug!("No MsgStore found for execution {}", execution_id);
+                return;
+            };
+
+            // Latest conversational entry, and its text if it is an assistant message
+            let mut last: Option<(usize, Option<String>)> = None;
+            let mut stream = store.history_plus_stream();
+            while let Some(msg) = stream.next().await {
+                match msg {
+                    Ok(LogMsg::JsonPatch(patch)) => {
+                        if let Some((index, entry)) = extract_normalized_entry_from_patch(&patch)
+                            && is_conversation_entry(&entry.entry_type)
+                            && last
+                                .as_ref()
+                                .is_none_or(|(last_index, _)| index >= *last_index)
+                        {
+                            let text =
+                                matches!(entry.entry_type, NormalizedEntryType::AssistantMessage)
+                                    .then_some(entry.content);
+                            last = Some((index, text));
+                        }
+                    }
+                    Ok(LogMsg::Finished) => break,
+                    Ok(_) => {}
+                    Err(e) => {
+                        tracing::debug!("Telegram question stream error: {}", e);
+                        return;
+                    }
+                }
+            }
+
+            let Some((_, Some(question))) = last else {
+                return;
+            };
+            if !ends_with_question(&question) {
+                return;
+            }
+            if let Err(e) = service
+                .send_question(chat_id, project_id, task_id, &task_name, &question)
+                .await
+            {
+                tracing::warn!(
+                    "Failed to forward agent question for task {}: {}",
+                    task_id,
+                    e
+                );
+            }
+        }))
+    }
+
+    async fn send_question(
+        &self,
+        chat_id: i64,
+        project_id: Uuid,
+        task_id: Uuid,
+        task_name: &str,
+        question: &str,
+    ) -> Result<(), TelegramError> {
+        let target = self.notification_target(chat_id, project_id).await;
+        let text = format_question(task_name, question);
+        let markup = ReplyMarkup::ForceReply(
+            ForceReply::builder()
+                .force_reply(true)
+                .input_field_placeholder(QUESTION_REPLY_PLACEHOLDER.to_string())
+                .build(),
+        );
+        let sent = self
+            .send_html_with_markup(target, &text, Some(markup), false)
+            .await?;
+        TelegramMessageTask::create(&self.pool, chat_id, sent.message_id, task_id).await?;
+        Ok(())
+    }
+
+    /// Clean up expired link tokens
+    fn cleanup_expired_tokens(&self) {
+        self.pending_links.retain(|_, token| !token.is_expired());
+    }
+}
+
+#[async_trait]
+impl Notifier for TelegramService {
+    fn channel(&self) -> NotificationChannel {
+        NotificationChannel::Telegram
+    }
+
+    async fn wants(&self, _kind: TaskNotificationKind) -> bool {
+        // Which kinds are sent also depends on the task's latest transition,
+        // so that is left to `send`
+        self.config.read().await.telegram.notifications_enabled
+    }
+
+    /// An unlinked account is not an error here, as there is nowhere to send
+    async fn send(&self, event: &NotificationEvent) -> Result<(), NotifierError> {
+        let error_excerpt = event
+            .run
+            .as_ref()
+            .and_then(|run| run.error_excerpt.as_deref());
+        match self
+            .send_status_notification_with_excerpt(
+                &event.task,
+                event.kind,
+                event.summary.as_deref(),
+                error_excerpt,
+            )
+            .await
+        {
+            Ok(()) | Err(TelegramError::NotLinked) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn send_text(&self, title: &str, body: &str) -> Result<(), NotifierError> {
+        let text = format!("<b>{}</b>\n\n{}", escape_html(title), escape_html(body));
+        match self.send_critical_notification(&text).await {
+            Ok(()) | Err(TelegramError::NotLinked) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// `send` only queued the notification, so its messages, and those still
+    /// waiting out the batching window, are tied to the history entry for the
+    /// queue worker to mark failed if it gives up on them
+    async fn recorded(&self, event: &NotificationEvent, notification_id: Uuid) {
+        for mut batch in self.notification_batches.iter_mut() {
+            batch
+                .iter_mut()
+                .filter(|notification| {
+                    notification.task_id == event.task.id && notification.notification_id.is_none()
+                })
+                .for_each(|notification| notification.notification_id = Some(notification_id));
+        }
+        if let Err(e) = TelegramOutgoingMessage::link_task_notification(
+            &self.pool,
+            event.task.id,
+            notification_id,
+        )
+        .await
+        {
+            tracing::warn!(
+                "Failed to tie queued Telegram messages for task {} to their notification: {}",
+                event.task.id,
+                e
+            );
+        }
+    }
+}
+
+// ============================================================================
+// Streaming Helper Functions
+// ============================================================================
+
+/// Minimum time between edits of a streamed message
+const STREAM_EDIT_INTERVAL: Duration = Duration::from_secs(3);
+/// Number of most recent entries kept for the streamed message
+const STREAM_MAX_ENTRIES: usize = 50;
+/// Budget for the escaped content of a streamed message (Telegram caps messages at 4096)
+const STREAM_MAX_CONTENT_LEN: usize = 3800;
+/// Bytes of an agent question forwarded to the chat, leaving room for escaping
+const QUESTION_MAX_LEN: usize = 3000;
+/// Placeholder of the reply field shown with a forwarded question
+const QUESTION_REPLY_PLACEHOLDER: &str = "Your answer";
+
+/// A single Telegram message that is edited in place as output arrives
+struct StreamMessage<'a> {
+    api: &'a AsyncApi,
+    chat_id: i64,
+    message_id: i32,
+    task_name: &'a str,
+    mode: TelegramParseMode,
+    last_text: String,
+}
+
+impl<'a> StreamMessage<'a> {
+    fn new(
+        api: &'a AsyncApi,
+        chat_id: i64,
+        message_id: i32,
+        task_name: &'a str,
+        mode: TelegramParseMode,
+    ) -> Self {
+        Self {
+            api,
+            chat_id,
+            message_id,
+            task_name,
+            mode,
+            last_text: String::new(),
+        }
+    }
+
+    /// Edit the message to show `content`, skipping no-op edits
+    async fn edit(&mut self, content: &str) {
+        let text = format_stream_message(self.task_name, content);
+        if text == self.last_text {
+            return;
+        }
+
+        let (rendered, parse_mode) = render_message(&text, self.mode);
+        let edit_params = EditMessageTextParams::builder()
+            .chat_id(ChatId::Integer(self.chat_id))
+            .message_id(self.message_id)
+            .text(rendered)
+            .maybe_parse_mode(parse_mode)
+            .build();
+
+        match self.api.edit_message_text(&edit_params).await {
+            Ok(_) => self.last_text = text,
+            Err(e) => tracing::debug!("Failed to edit stream message: {}", e),
+        }
+    }
+}
+
+fn join_entries(entries: &[(usize, String)]) -> String {
+    entries
+        .iter()
+        .map(|(_, content)| content.as_str())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Maximum number of tasks returned by /search
+const SEARCH_RESULT_LIMIT: i64 = 10;
+/// Tasks offered for an inline query; Telegram accepts at most 50
+const INLINE_RESULT_LIMIT: i64 = 20;
+/// Seconds Telegram may cache the answer to an inline query
+const INLINE_CACHE_SECONDS: u32 = 10;
+/// Characters of a task's description pasted by an inline result
+const INLINE_DESCRIPTION_CHARS: usize = 200;
+/// Lines returned by /logs when no count is given
+const DEFAULT_LOG_LINES: usize = 50;
+/// Upper bound for the /logs line count
+const MAX_LOG_LINES: usize = 500;
+/// Output lines quoted in a failure notification
+const FAILURE_EXCERPT_LINES: usize = 15;
+/// Upper bound for the escaped failure excerpt, so the notification stays one message
+const FAILURE_EXCERPT_MAX_LEN: usize = 1500;
+/// How long handled update IDs are kept; Telegram stops redelivering an
+/// update after a day
+const SEEN_UPDATE_TTL_HOURS: i64 = 24;
+/// Length of generated webhook secrets; Telegram allows up to 256 characters
+const WEBHOOK_SECRET_LEN: usize = 48;
+/// Telegram's limit for the text of a single message
+const TELEGRAM_MAX_MESSAGE_LEN: usize = 4096;
+/// Telegram's limit for photo captions
+const TELEGRAM_MAX_CAPTION_LEN: usize = 1024;
+/// Callback data prefix for expanding a file of a /diff summary
+const DIFF_CALLBACK_PREFIX: &str = "diff:";
+/// Files offered as expand buttons in a /diff summary
+const MAX_DIFF_FILE_BUTTONS: usize = 20;
+/// Callback data prefix for the "Merge PR" button, which asks for confirmation
+const MERGE_CALLBACK_PREFIX: &str = "merge:";
+/// Callback data prefix for confirming a merge
+const MERGE_CONFIRM_CALLBACK_PREFIX: &str = "merge_confirm:";
+/// Callback data prefix for the page buttons of /projects
+const PROJECTS_CALLBACK_PREFIX: &str = "projects:";
+/// Projects listed per /projects page
+const PROJECTS_PAGE_SIZE: usize = 10;
+/// Callback data for dismissing a confirmation prompt
+const CANCEL_CALLBACK: &str = "cancel";
+/// Callback data prefix for the "Retry" button of failure notifications
+const RETRY_CALLBACK_PREFIX: &str = "retry:";
+/// Callback data prefix for the "Ack" button of failure notifications
+const ACK_CALLBACK_PREFIX: &str = "ack:";
+/// Commands that change data, which read-only accounts may not run
+const WRITE_COMMANDS: &[&str] = &[
+    "newtask", "message", "merge", "stop", "retry", "assign", "unassign", "mute", "unmute",
+    "shortcut", "role",
+];
+/// Longest pause /mute accepts
+const MAX_MUTE_DURATION: chrono::Duration = chrono::Duration::days(30);
+/// Rows of the /menu keyboard, as (button label, command) pairs. Pressing a
+/// button sends its label, which runs the command.
+const MENU_ROWS: &[&[(&str, &str)]] = &[
+    &[("📋 Tasks", "tasks"), ("➕ New Task", "newtask")],
+    &[("🖥 Status", "status"), ("📊 Report", "report")],
+];
+
+impl TelegramService {
+    // ========================================================================
+    // Webhook Handling
+    // ========================================================================
+
+    /// Handle an incoming Telegram update (webhook payload)
+    pub async fn handle_update(&self, update: Update) -> Result<UpdateResult, TelegramError> {
+        if !self.is_new_update(&update).await? {
+            tracing::debug!("Skipping Telegram update {} seen before", update.update_id);
+            return Ok(UpdateResult::NoResponse);
+        }
+
+        let language_code = match &update.content {
+            UpdateContent::Message(msg) => msg.from.as_ref().and_then(|u| u.language_code.clone()),
+            UpdateContent::CallbackQuery(query) => query.from.language_code.clone(),
+            UpdateContent::InlineQuery(query) => query.from.language_code.clone(),
+            _ => None,
+        };
+        let locale = self.locale(language_code.as_deref()).await;
+
+        // Mistakes the user can fix are answered in their language; anything
+        // else is left to the caller to log
+        match self.dispatch_update(update, locale).await {
+            // Replies to strangers would land in the primary chat, so they get none
+            Err(TelegramError::NotAuthorized(user_id)) => {
+                tracing::warn!(
+                    "Ignoring Telegram update from user {}, who is not linked or allowed",
+                    user_id
+                );
+                Ok(UpdateResult::NoResponse)
+            }
+            Err(e) => match user_error_reply(&e, locale) {
+                Some(reply) => Ok(UpdateResult::Response(reply)),
+                None => Err(e),
+            },
+            result => result,
+        }
+    }
+
+    /// Record `update` as handled, returning false when the bot already
+    /// handled it, e.g. because Telegram redelivered it after a slow response
+    async fn is_new_update(&self, update: &Update) -> Result<bool, TelegramError> {
+        let Some(bot) = self.bot() else {
+            return Ok(true);
+        };
+        let cutoff = Utc::now() - chrono::Duration::hours(SEEN_UPDATE_TTL_HOURS);
+        TelegramSeenUpdate::delete_seen_before(&self.pool, cutoff).await?;
+        Ok(TelegramSeenUpdate::record(&self.pool, bot.id(), i64::from(update.update_id)).await?)
+    }
+
+    /// Point the default bot and every per-project bot at this server's
+    /// webhook routes under `TelegramConfig::webhook_base_url`. Returns false,
+    /// doing nothing, when no base URL is configured.
+    ///
+    /// Each bot is registered with its webhook secret, which is generated
+    /// the first time; the caller saves the config afterwards.
+    pub async fn register_webhooks(&self) -> Result<bool, TelegramError> {
+        let Some((base_url, project_ids)) = self.webhook_bots().await else {
+            return Ok(false);
+        };
+
+        let params = SetWebhookParams::builder()
+            .url(webhook_url(&base_url, None))
+            .secret_token(self.webhook_secret_or_generate(None).await)
+            .build();
+        self.api()?
+            .set_webhook(&params)
+            .await
+            .map_err(|e| TelegramError::Api(e.to_string()))?;
+
+        // A misconfigured project bot does not keep the others from their webhooks
+        for project_id in project_ids {
+            let params = SetWebhookParams::builder()
+                .url(webhook_url(&base_url, Some(project_id)))
+                .secret_token(self.webhook_secret_or_generate(Some(project_id)).await)
+                .build();
+            let sender = self.for_project(project_id).await;
+            if let Err(e) = sender.api()?.set_webhook(&params).await {
+                tracing::warn!(
+                    "Failed to register the Telegram webhook of project {}: {}",
+                    project_id,
+                    e
+                );
+            }
+        }
+        Ok(true)
+    }
+
+    /// The secret Telegram sends with webhook requests of `project_id`'s own
+    /// bot, or of the default bot when `project_id` is `None` or has no bot
+    /// of its own. `None` when that bot's webhook was never registered.
+    pub async fn webhook_secret(&self, project_id: Option<Uuid>) -> Option<String> {
+        let config = self.config.read().await;
+        match project_id.and_then(|project_id| config.telegram.project_bot(project_id)) {
+            Some(bot) => bot.webhook_secret.clone(),
+            None => config.telegram.webhook_secret.clone(),
+        }
+    }
+
+    /// Like [`Self::webhook_secret`], generating the secret when there is
+    /// none yet. Updates the config in memory; the caller saves it.
+    async fn webhook_secret_or_generate(&self, project_id: Option<Uuid>) -> String {
+        let mut config = self.config.write().await;
+        let telegram = &mut config.telegram;
+        let secret = match project_id.and_then(|project_id| {
+            telegram
+                .project_bots
+                .iter_mut()
+                .find(|bot| bot.project_id == project_id)
+        }) {
+            Some(bot) => &mut bot.webhook_secret,
+            None => &mut telegram.webhook_secret,
+        };
+        secret.get_or_insert_with(generate_webhook_secret).clone()
+    }
+
+    /// Remove the webhooks `register_webhooks` set, so Telegram stops posting
+    /// updates to a server that is going away. Returns false, doing nothing,
+    /// when no base URL is configured.
+    pub async fn delete_webhooks(&self) -> Result<bool, TelegramError> {
+        let Some((_, project_ids)) = self.webhook_bots().await else {
+            return Ok(false);
+        };
+
+        let params = DeleteWebhookParams::builder().build();
+        for project_id in project_ids {
+            let sender = self.for_project(project_id).await;
+            if let Err(e) = sender.api()?.delete_webhook(&params).await {
+                tracing::warn!(
+                    "Failed to delete the Telegram webhook of project {}: {}",
+                    project_id,
+                    e
+                );
+            }
+        }
+        self.api()?
+            .delete_webhook(&params)
+            .await
+            .map_err(|e| TelegramError::Api(e.to_string()))?;
+        Ok(true)
+    }
+
+    /// The configured webhook base URL and the projects with bots of their own
+    async fn webhook_bots(&self) -> Option<(String, Vec<Uuid>)> {
+        let config = self.config.read().await;
+        let base_url = config.telegram.webhook_base_url.clone()?;
+        let project_ids = config
+            .telegram
+            .project_bots
+            .iter()
+            .map(|bot| bot.project_id)
+            .collect();
+        Some((base_url, project_ids))
+    }
+
+    /// Check each step of the bot setup against Telegram: the token with
+    /// `getMe`, the webhook with `getWebhookInfo`
+    pub async fn setup_state(&self) -> TelegramSetupState {
+        let (expected_webhook_url, linked_chat_id) = {
+            let config = self.config.read().await;
+            (
+                config
+                    .telegram
+                    .webhook_base_url
+                    .as_deref()
+                    .map(|base_url| webhook_url(base_url, self.bot_project_id)),
+                config.telegram.chat_id,
+            )
+        };
+        let mut facts = SetupFacts {
+            token_configured: self.is_configured(),
+            configured_username: self.bot_username.clone(),
+            expected_webhook_url,
+            linked_chat_id,
+            ..Default::default()
+        };
+
+        if let Ok(api) = self.api() {
+            facts.bot = Some(match api.get_me().await {
+                Ok(response) => Ok(response.result.username.unwrap_or_default()),
+                Err(e) => Err(e.to_string()),
+            });
+            // A token Telegram rejects gets nowhere with getWebhookInfo either
+            if matches!(facts.bot, Some(Ok(_))) {
+                facts.webhook = Some(match api.get_webhook_info().await {
+                    Ok(response) => Ok(WebhookStatus {
+                        url: response.result.url,
+                        pending_update_count: response.result.pending_update_count,
+                        last_error: response.result.last_error_message,
+                    }),
+                    Err(e) => Err(e.to_string()),
+                });
+            }
+        }
+        build_setup_state(&facts)
+    }
+
+    /// Chat for the replies to `update`: the sender's own chat when they
+    /// linked their account, otherwise the primary chat. Replies stay in the
+    /// forum topic the update came from when it came from that chat.
+    pub async fn reply_target(&self, update: &Update) -> Option<ReplyTarget> {
+        let (user_id, source) = match &update.content {
+            UpdateContent::Message(msg) => (msg.from.as_ref().map(|u| u.id as i64), Some(&**msg)),
+            UpdateContent::CallbackQuery(query) => (
+                Some(query.from.id as i64),
+                match &query.message {
+                    Some(MaybeInaccessibleMessage::Message(msg)) => Some(&**msg),
+                    _ => None,
+                },
+            ),
+            _ => (None, None),
+        };
+        let mut chat_id = None;
+        if let Some(user_id) = user_id {
+            match TelegramAccount::find_by_user_id(&self.pool, user_id).await {
+                Ok(Some(account)) => chat_id = Some(account.chat_id),
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Failed to look up Telegram account {}: {}", user_id, e),
+            }
+        }
+        let chat_id = match chat_id {
+            Some(chat_id) => chat_id,
+            None => self.config.read().await.telegram.chat_id?,
+        };
+        let message_thread_id = source
+            .filter(|msg| msg.chat.id == chat_id)
+            .and_then(topic_thread_id);
+        Some(ReplyTarget {
+            chat_id,
+            message_thread_id,
+        })
+    }
+
+    /// Language for replies to a user with the given Telegram `language_code`
+    async fn locale(&self, language_code: Option<&str>) -> Locale {
+        let config = self.config.read().await;
+        Locale::resolve(&config.language, language_code)
+    }
+
+    async fn dispatch_update(
+        &self,
+        update: Update,
+        locale: Locale,
+    ) -> Result<UpdateResult, TelegramError> {
+        // Only handle message, inline button and inline query updates
+        let message = match update.content {
+            UpdateContent::Message(msg) => msg,
+            UpdateContent::CallbackQuery(query) => {
+                return self.handle_callback(&query, locale).await;
+            }
+            UpdateContent::InlineQuery(query) => return self.handle_inline_query(&query).await,
+            _ => return Ok(UpdateResult::NoResponse),
+        };
+
+        let chat_id = message.chat.id;
+        // Telegram user IDs are u64, but we store as i64 (safe for all practical user IDs)
+        let user_id = message.from.as_ref().map(|u| u.id as i64).unwrap_or(0);
+        let username = message.from.as_ref().and_then(|u| u.username.clone());
+
+        // Photos and documents are attached to the task named in the caption
+        if message.photo.is_some() || message.document.is_some() {
+            self.authorize_write(user_id, "attach images").await?;
+            return self.handle_attachment(&message).await;
+        }
+
+        // Get text content
+        let text = match &message.text {
+            Some(t) => t.as_str(),
+            None => return Ok(UpdateResult::NoResponse),
+        };
+
+        // Parse command
+        if text.starts_with('/') {
+            // Split on any whitespace so arguments may start on the next line
+            let (command, args) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+            let command = command.trim_start_matches('/');
+            // Remove @botname suffix if present
+            let command = command.split('@').next().unwrap_or(command);
+            let args = args.trim();
+
+            let chat = ReplyTarget {
+                chat_id,
+                message_thread_id: topic_thread_id(&message),
+            };
+            return self
+                .handle_command(command, args, chat, user_id, username, locale)
+                .await;
+        }
+
+        // Buttons of the /menu keyboard send their label as plain text
+        if let Some(command) = menu_command(text) {
+            let chat = ReplyTarget {
+                chat_id,
+                message_thread_id: topic_thread_id(&message),
+            };
+            return self
+                .handle_command(command, "", chat, user_id, username, locale)
+                .await;
+        }
+
+        // Replies to a task notification become follow-ups for that task
+        if let Some(reply_to) = &message.reply_to_message {
+            self.authorize_write(user_id, "send follow-ups").await?;
+            return self.handle_reply(chat_id, reply_to.message_id, text).await;
+        }
+
+        // Other non-command messages are ignored
+        Ok(UpdateResult::NoResponse)
+    }
+
+    /// Handle an inline button press
+    async fn handle_callback(
+        &self,
+        query: &CallbackQuery,
+        locale: Locale,
+    ) -> Result<UpdateResult, TelegramError> {
+        // Stop the client's loading indicator regardless of the outcome
+        let params = AnswerCallbackQueryParams::builder()
+            .callback_query_id(query.id.clone())
+            .build();
+        if let Err(e) = self.api()?.answer_callback_query(&params).await {
+            tracing::warn!("Failed to answer Telegram callback query: {}", e);
+        }
+
+        let user_id = query.from.id as i64;
+        self.authorize(user_id).await?;
+
+        let data = query.data.as_deref().unwrap_or("");
+        if let Some(rest) = data.strip_prefix(DIFF_CALLBACK_PREFIX) {
+            let (task_arg, index) = rest
+                .split_once(':')
+                .ok_or_else(|| TelegramError::InvalidCommand("Malformed diff button".to_string()))?;
+            let index = index
+                .parse::<usize>()
+                .map_err(|_| TelegramError::InvalidCommand("Malformed diff button".to_string()))?;
+            return self.cmd_diff_file(parse_uuid(task_arg)?, index).await;
+        }
+        if let Some(task_arg) = data.strip_prefix(MERGE_CALLBACK_PREFIX) {
+            self.authorize_write(user_id, "use /merge").await?;
+            return self.cmd_merge(task_arg).await;
+        }
+        if let Some(task_arg) = data.strip_prefix(MERGE_CONFIRM_CALLBACK_PREFIX) {
+            self.authorize_write(user_id, "use /merge").await?;
+            return self.merge_task_pr(parse_uuid(task_arg)?).await;
+        }
+        if let Some(task_arg) = data.strip_prefix(RETRY_CALLBACK_PREFIX) {
+            self.authorize_write(user_id, "retry attempts").await?;
+            let task_id = parse_uuid(task_arg)?;
+            return Ok(UpdateResult::RetryRequested { task_id });
+        }
+        if let Some(task_arg) = data.strip_prefix(ACK_CALLBACK_PREFIX) {
+            return self
+                .acknowledge_failure(parse_uuid(task_arg)?, locale)
+                .await;
+        }
+        if let Some(page) = data.strip_prefix(PROJECTS_CALLBACK_PREFIX) {
+            return self.cmd_projects(page).await;
+        }
+        if data == CANCEL_CALLBACK {
+            return Ok(UpdateResult::Response(BotMessage::Cancelled.render(locale)));
+        }
+
+        Ok(UpdateResult::NoResponse)
+    }
+
+    /// Acknowledge the failure notifications of a task, from the "Ack"
+    /// button, which stops their escalation
+    async fn acknowledge_failure(
+        &self,
+        task_id: Uuid,
+        locale: Locale,
+    ) -> Result<UpdateResult, TelegramError> {
+        let acknowledged = Notification::acknowledge_for_task(
+            &self.pool,
+            task_id,
+            NotificationEventType::TaskFailed.as_str(),
+        )
+        .await?;
+        let message = if acknowledged == 0 {
+            ServerMessage::FailureAlreadyAcknowledged
+        } else {
+            ServerMessage::FailureAcknowledged
+        };
+        Ok(UpdateResult::Response(message.render(locale)))
+    }
+
+    /// Answer an inline query (`@bot fix login`) with the matching tasks, so
+    /// they can be pasted into any chat. Only authorized users get results.
+    async fn handle_inline_query(
+        &self,
+        query: &InlineQuery,
+    ) -> Result<UpdateResult, TelegramError> {
+        let mut results = Vec::new();
+        if self.role_of(query.from.id as i64).await?.is_some() {
+            let tasks = Task::search(&self.pool, &query.query, None, INLINE_RESULT_LIMIT).await?;
+            let projects: HashMap<Uuid, String> = Project::find_all(&self.pool)
+                .await?
+                .into_iter()
+                .map(|project| (project.id, project.name))
+                .collect();
+            results = tasks
+                .iter()
+                .map(|task| {
+                    inline_task_result(task, projects.get(&task.project_id).map(String::as_str))
+                })
+                .collect();
+        }
+
+        // Results depend on who asks, so Telegram must not share them
+        let params = AnswerInlineQueryParams::builder()
+            .inline_query_id(query.id.clone())
+            .results(results)
+            .cache_time(INLINE_CACHE_SECONDS)
+            .is_personal(true)
+            .build();
+        self.api()?
+            .answer_inline_query(&params)
+            .await
+            .map_err(|e| TelegramError::Api(e.to_string()))?;
+        Ok(UpdateResult::NoResponse)
+    }
+
+    /// Role of `user_id`, or None if they may not use the bot. The primary
+    /// linked user and users on `allowed_user_ids` are always admins; other
+    /// users need a linked account.
+    async fn role_of(&self, user_id: i64) -> Result<Option<TelegramAccountRole>, TelegramError> {
+        if self
+            .config
+            .read()
+            .await
+            .telegram
+            .is_configured_admin(user_id)
+        {
+            return Ok(Some(TelegramAccountRole::Admin));
+        }
+        Ok(TelegramAccount::find_by_user_id(&self.pool, user_id)
+            .await?
+            .map(|account| account.role))
+    }
+
+    async fn authorize(&self, user_id: i64) -> Result<TelegramAccountRole, TelegramError> {
+        self.role_of(user_id)
+            .await?
+            .ok_or(TelegramError::NotAuthorized(user_id))
+    }
+
+    /// Like `authorize`, but also refuses read-only accounts; `action` says
+    /// what they tried to do. Nobody may change anything in maintenance mode.
+    async fn authorize_write(&self, user_id: i64, action: &str) -> Result<(), TelegramError> {
+        let role = self.authorize(user_id).await?;
+        check_write(role, self.config.read().await.maintenance_mode, action)
+    }
+
+    /// Queue the reply text as a follow-up for the task the replied-to message was about
+    async fn handle_reply(
+        &self,
+        chat_id: i64,
+        reply_to_message_id: i32,
+        text: &str,
+    ) -> Result<UpdateResult, TelegramError> {
+        let Some(task_id) =
+            TelegramMessageTask::find_task_id(&self.pool, chat_id, reply_to_message_id).await?
+        else {
+            return Ok(UpdateResult::NoResponse);
+        };
+
+        let task = Task::find_by_id(&self.pool, task_id)
+            .await?
+            .ok_or(TelegramError::TaskNotFound(task_id))?;
+
+        self.queue_follow_up(&task, text).await
+    }
+
+    /// Send a follow-up message to the latest session of the task's latest
+    /// attempt: right away when the attempt is idle, otherwise queued for when
+    /// its current run finishes
+    async fn queue_follow_up(&self, task: &Task, text: &str) -> Result<UpdateResult, TelegramError> {
+        let Some(queued_messages) = &self.queued_messages else {
+            return Ok(UpdateResult::Response(
+                "Queuing messages is not supported by this server.".to_string(),
+            ));
+        };
+
+        // Workspaces are returned newest first
+        let workspace = Workspace::fetch_all(&self.pool, Some(task.id))
+            .await
+            .map_err(|e| TelegramError::QueueMessage(e.to_string()))?
+            .into_iter()
+            .next();
+        let session = match workspace {
+            Some(workspace) => Session::find_latest_by_workspace_id(&self.pool, workspace.id).await?,
+            None => None,
+        };
+        let Some(session) = session else {
+            return Ok(UpdateResult::Response(format!(
+                "Task <b>{}</b> has no attempt to send a message to.",
+                escape_html(&task.title)
+            )));
+        };
+
+        let Some(executor_profile_id) =
+            ExecutionProcess::latest_executor_profile_for_session(&self.pool, session.id)
+                .await
+                .map_err(|e| TelegramError::QueueMessage(e.to_string()))?
+        else {
+            return Ok(UpdateResult::Response(format!(
+                "Task <b>{}</b> has not run a coding agent yet.",
+                escape_html(&task.title)
+            )));
+        };
+
+        let running = ExecutionProcess::has_running_non_dev_server_processes_for_workspace(
+            &self.pool,
+            session.workspace_id,
+        )
+        .await?;
+        let follow_up = DraftFollowUpData {
+            message: text.to_string(),
+            executor_profile_id,
+        };
+        Ok(follow_up_result(
+            queued_messages,
+            session.id,
+            &task.title,
+            follow_up,
+            running,
+        ))
+    }
+
+    // ========================================================================
+    // Attachments
+    // ========================================================================
+
+    /// Store a photo or image document as an attachment of the task whose ID is
+    /// the first word of the caption
+    async fn handle_attachment(&self, message: &Message) -> Result<UpdateResult, TelegramError> {
+        let Some(images) = &self.images else {
+            return Ok(UpdateResult::Response(
+                "Attachments are not supported by this server.".to_string(),
+            ));
+        };
+
+        let Some(task_arg) = message
+            .caption
+            .as_deref()
+            .and_then(|caption| caption.split_whitespace().next())
+        else {
+            return Ok(UpdateResult::Response(
+                "To attach a file, add the task ID as the caption.".to_string(),
+            ));
+        };
+
+        let task_id = parse_uuid(task_arg)?;
+        let task = Task::find_by_id(&self.pool, task_id)
+            .await?
+            .ok_or(TelegramError::TaskNotFound(task_id))?;
+
+        let (file_id, filename) = if let Some(photo) =
+            message.photo.as_ref().and_then(|sizes| sizes.last())
+        {
+            // Telegram sends several sizes of the same photo; the last is the largest
+            (
+                photo.file_id.clone(),
+                format!("telegram_{}.jpg", photo.file_unique_id),
+            )
+        } else if let Some(document) = &message.document {
+            (
+                document.file_id.clone(),
+                document
+                    .file_name
+                    .clone()
+                    .unwrap_or_else(|| format!("telegram_{}", document.file_unique_id)),
+            )
+        } else {
+            return Ok(UpdateResult::NoResponse);
+        };
+
+        let data = self.download_file(&file_id).await?;
+        let image = match images.store_image(&data, &filename).await {
+            Ok(image) => image,
+            Err(ImageError::InvalidFormat) => {
+                return Ok(UpdateResult::Response(
+                    "❌ Only image files can be attached to tasks.".to_string(),
+                ));
+            }
+            Err(e) => return Err(TelegramError::Attachment(e.to_string())),
+        };
+        TaskImage::associate_many_dedup(&self.pool, task.id, &[image.id]).await?;
+
+        Ok(UpdateResult::Response(format!(
+            "📎 Attached <b>{}</b> to <b>{}</b>",
+            escape_html(&image.original_name),
+            escape_html(&task.title)
+        )))
+    }
+
+    /// Download a file sent to the bot
+    async fn download_file(&self, file_id: &str) -> Result<Vec<u8>, TelegramError> {
+        let bot = self.bot().ok_or(TelegramError::NotConfigured)?;
+        let (api, token) = (&bot.api, &bot.token);
+
+        let params = GetFileParams::builder().file_id(file_id).build();
+        let file = api
+            .get_file(&params)
+            .await
+            .map_err(|e| TelegramError::Api(e.to_string()))?
+            .result;
+        let file_path = file
+            .file_path
+            .ok_or_else(|| TelegramError::Api("File has no download path".to_string()))?;
+
+        let url = format!("https://api.telegram.org/file/bot{token}/{file_path}");
+        let response = self
+            .http
+            .get(&url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| TelegramError::Api(e.without_url().to_string()))?;
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| TelegramError::Api(e.without_url().to_string()))?;
+        Ok(bytes.to_vec())
+    }
+
+    // ========================================================================
+    // Command Handlers
+    // ========================================================================
+
+    /// Handle a bot command
+    async fn handle_command(
+        &self,
+        command: &str,
+        args: &str,
+        chat: ReplyTarget,
+        user_id: i64,
+        username: Option<String>,
+        locale: Locale,
+    ) -> Result<UpdateResult, TelegramError> {
+        let chat_id = chat.chat_id;
+        let alias = {
+            let config = self.config.read().await;
+            resolve_alias(&config.telegram.command_aliases, command, args)
+        };
+        let (command, args) = match &alias {
+            Some((command, args)) => (command.as_str(), args.as_str()),
+            None => (command, args),
+        };
+        match command_access(command) {
+            CommandAccess::Public => {}
+            CommandAccess::Read => {
+                self.authorize(user_id).await?;
+            }
+            CommandAccess::Write => {
+                self.authorize_write(user_id, &format!("use /{command}"))
+                    .await?;
+            }
+        }
+        match command {
+            "start" => self.cmd_start(args, chat_id, user_id, username, locale).await,
+            "help" => self.cmd_help(locale).await,
+            "status" => self.cmd_status().await,
+            "whoami" => self.cmd_whoami(chat, user_id).await,
+            "projects" => self.cmd_projects(args).await,
+            "project" => self.cmd_project(args, chat).await,
+            "tasks" => self.cmd_tasks(args, chat).await,
+            "workload" => self.cmd_workload(args, chat).await,
+            "search" => self.cmd_search(args, chat).await,
+            "task" => self.cmd_task(args).await,
+            "logs" => self.cmd_logs(args).await,
+            "diff" => self.cmd_diff(args).await,
+            "merge" => self.cmd_merge(args).await,
+            "stop" => self.cmd_stop(args).await,
+            "retry" => self.cmd_retry(args).await,
+            "assign" => self.cmd_assign(args, user_id).await,
+            "unassign" => self.cmd_unassign(args, user_id).await,
+            "watch" => self.cmd_watch(args, user_id).await,
+            "newtask" => self.cmd_newtask(args, chat).await,
+            "message" => self.cmd_message(args).await,
+            "mute" => self.cmd_mute(args).await,
+            "unmute" => self.cmd_unmute().await,
+            "shortcut" => self.cmd_shortcut(args, chat_id).await,
+            "role" => self.cmd_role(args, user_id).await,
+            "digest" => self.cmd_digest(args, user_id).await,
+            "menu" => self.cmd_menu(args).await,
+            "report" => self.cmd_report(locale).await,
+            "setup" => self.cmd_setup(args, chat_id, user_id).await,
+            _ => {
+                // Built-in commands take precedence over the chat's shortcuts
+                let shortcut = TelegramProjectShortcut::find_project_id(
+                    &self.pool,
+                    chat_id,
+                    &command.to_lowercase(),
+                )
+                .await?;
+                match shortcut {
+                    Some(project_id) => self.cmd_project(&project_id.to_string(), chat).await,
+                    None => Ok(UpdateResult::Response(
+                        BotMessage::UnknownCommand(&escape_html(command)).render(locale),
+                    )),
+                }
+            }
+        }
+    }
+
+    /// Handle /start command (with optional link token)
+    async fn cmd_start(
+        &self,
+        args: &str,
+        chat_id: i64,
+        user_id: i64,
+        username: Option<String>,
+        locale: Locale,
+    ) -> Result<UpdateResult, TelegramError> {
+        // Check if this is a link request
+        if !args.is_empty() {
+            // Validate and complete the link
+            match self.complete_link(args, chat_id, user_id, username.clone()).await {
+                Ok(_) => {
+                    return Ok(UpdateResult::LinkCompleted {
+                        chat_id,
+                        user_id,
+                        username,
+                        locale,
+                    });
+                }
+                Err(TelegramError::InvalidLinkToken) => {
+                    return Ok(UpdateResult::Response(
+                        BotMessage::InvalidLinkToken.render(locale),
+                    ));
+                }
+                Err(TelegramError::LinkTokenExpired) => {
+                    return Ok(UpdateResult::Response(
+                        BotMessage::LinkTokenExpired.render(locale),
+                    ));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        // Regular /start - show welcome message
+        Ok(UpdateResult::Response(BotMessage::Welcome.render(locale)))
+    }
+
+    /// Handle /help command
+    async fn cmd_help(&self, locale: Locale) -> Result<UpdateResult, TelegramError> {
+        Ok(UpdateResult::Response(BotMessage::Help.render(locale)))
+    }
+
+    /// Handle /status command
+    async fn cmd_status(&self) -> Result<UpdateResult, TelegramError> {
+        let Some(health) = &self.health else {
+            return Ok(UpdateResult::Response(
+                "Server status is not available.".to_string(),
+            ));
+        };
+        let snapshot = health.snapshot().await?;
+        Ok(UpdateResult::Response(format_health(&snapshot)))
+    }
+
+    /// Handle /whoami command: the IDs, account, settings and connectivity a
+    /// support conversation needs
+    async fn cmd_whoami(
+        &self,
+        chat: ReplyTarget,
+        user_id: i64,
+    ) -> Result<UpdateResult, TelegramError> {
+        let role = self.role_of(user_id).await?;
+        // Unlinked users only get their IDs, so there is no need to look further
+        let active_project = if role.is_some()
+            && let Some(project_id) = self.active_project_id(chat).await?
+        {
+            Project::find_by_id(&self.pool, project_id)
+                .await?
+                .map(|project| project.name)
+        } else {
+            None
+        };
+        let database_reachable = sqlx::query("SELECT 1").execute(&self.pool).await.is_ok();
+        let started = Instant::now();
+        let telegram_api = match self.api()?.get_me().await {
+            Ok(_) => Ok(started.elapsed()),
+            Err(e) => Err(e.to_string()),
+        };
+
+        let report = WhoamiReport {
+            chat_id: chat.chat_id,
+            user_id,
+            role,
+            active_project,
+            database_reachable,
+            telegram_api,
+        };
+        let config = self.config.read().await;
+        Ok(UpdateResult::Response(format_whoami(
+            &report,
+            &config.telegram,
+            &config.notification_preferences,
+            Utc::now(),
+        )))
+    }
+
+    /// Handle /setup: check each step of the bot setup and say what to do
+    /// next. `/setup test` sends a test notification to the linked chat.
+    async fn cmd_setup(
+        &self,
+        args: &str,
+        chat_id: i64,
+        user_id: i64,
+    ) -> Result<UpdateResult, TelegramError> {
+        self.authorize_write(user_id, "use /setup").await?;
+        match args.trim() {
+            "" => {}
+            "test" => {
+                let linked_chat_id = self
+                    .config
+                    .read()
+                    .await
+                    .telegram
+                    .chat_id
+                    .ok_or(TelegramError::NotLinked)?;
+                self.send_message(
+                    linked_chat_id,
+                    "🧪 <b>Test notification</b>\n\nTask notifications will arrive in this chat.",
+                )
+                .await?;
+                return Ok(UpdateResult::Response(if linked_chat_id == chat_id {
+                    "✅ Setup complete.".to_string()
+                } else {
+                    format!("✅ Sent a test notification to chat <code>{linked_chat_id}</code>.")
+                }));
+            }
+            _ => {
+                return Ok(UpdateResult::Response(
+                    "Usage: /setup, or /setup test to send a test notification".to_string(),
+                ));
+            }
+        }
+
+        let state = self.setup_state().await;
+        let unlinked = state.steps.iter().any(|step| {
+            step.kind == TelegramSetupStepKind::LinkChat
+                && step.status != TelegramSetupStepStatus::Done
+        });
+        // Finish linking from here with a fresh link token
+        let link_action = if unlinked {
+            let (token, deep_link) = self.generate_link_token()?;
+            Some(match &self.bot_username {
+                Some(_) => format!("Open {deep_link} to link this account"),
+                None => format!("Send <code>/start {token}</code> to link this chat"),
+            })
+        } else {
+            None
+        };
+        Ok(UpdateResult::Response(format_setup_state(
+            &state,
+            link_action.as_deref(),
+        )))
+    }
+
+    /// Handle /projects command
+    async fn cmd_projects(&self, args: &str) -> Result<UpdateResult, TelegramError> {
+        let page = match args.trim() {
+            "" => 1,
+            page => page
+                .parse::<usize>()
+                .map_err(|_| TelegramError::InvalidCommand("Page must be a number".to_string()))?,
+        };
+        let projects = Project::find_all_by_activity(&self.pool).await?;
+
+        if projects.is_empty() {
+            return Ok(UpdateResult::Response(
+                "No projects found. Create a project in the web interface first.".to_string(),
+            ));
+        }
+
+        let (text, buttons) = format_projects_page(&projects, page);
+        Ok(if buttons.is_empty() {
+            UpdateResult::Response(text)
+        } else {
+            UpdateResult::ResponseWithButtons { text, buttons }
+        })
+    }
+
+    /// Project chosen with /project in this chat, stored so it survives restarts.
+    /// Inside a forum topic, the project the topic was dedicated to.
+    async fn active_project_id(&self, chat: ReplyTarget) -> Result<Option<Uuid>, TelegramError> {
+        if let Some(thread_id) = chat.message_thread_id
+            && let Some(project_id) =
+                TelegramProjectTopic::find_project_id(&self.pool, chat.chat_id, thread_id).await?
+        {
+            return Ok(Some(project_id));
+        }
+        Ok(TelegramChatSettings::find_active_project_id(&self.pool, chat.chat_id).await?)
+    }
+
+    /// Handle /project command - set active project
+    ///
+    /// Sent inside a forum topic, it dedicates the topic to the project: the
+    /// project's notifications are posted there and commands sent there use it.
+    async fn cmd_project(
+        &self,
+        args: &str,
+        chat: ReplyTarget,
+    ) -> Result<UpdateResult, TelegramError> {
+        if args.is_empty() {
+            // Show current active project
+            if let Some(project_id) = self.active_project_id(chat).await?
+                && let Some(project) = Project::find_by_id(&self.pool, project_id).await?
+            {
+                let counts = Task::count_by_status(&self.pool, project.id).await?;
+                return Ok(UpdateResult::Response(format!(
+                    "Active project: <b>{}</b>\n<code>{}</code>\n{}",
+                    escape_html(&project.name),
+                    project.id,
+                    format_status_counts(&counts)
+                )));
+            }
+            return Err(TelegramError::NoActiveProject);
+        }
+
+        // Parse project ID
+        let project_id = parse_uuid(args)?;
+
+        // Verify project exists
+        let project = Project::find_by_id(&self.pool, project_id)
+            .await?
+            .ok_or(TelegramError::ProjectNotFound(project_id))?;
+
+        let counts = Task::count_by_status(&self.pool, project.id).await?;
+        if let Some(thread_id) = chat.message_thread_id {
+            TelegramProjectTopic::set(&self.pool, chat.chat_id, thread_id, project_id).await?;
+            return Ok(UpdateResult::Response(format!(
+                "✅ This topic now follows <b>{}</b>\n{}",
+                escape_html(&project.name),
+                format_status_counts(&counts)
+            )));
+        }
+
+        // Set active project
+        TelegramChatSettings::set_active_project_id(&self.pool, chat.chat_id, project_id).await?;
+
+        Ok(UpdateResult::Response(format!(
+            "✅ Active project set to: <b>{}</b>\n{}",
+            escape_html(&project.name),
+            format_status_counts(&counts)
+        )))
+    }
+
+    /// Handle /tasks command
+    async fn cmd_tasks(
+        &self,
+        args: &str,
+        chat: ReplyTarget,
+    ) -> Result<UpdateResult, TelegramError> {
+        // Determine project ID
+        let project_id = if args.is_empty() {
+            // Use active project
+            self.active_project_id(chat)
+                .await?
+                .ok_or(TelegramError::NoActiveProject)?
+        } else {
+            parse_uuid(args)?
+        };
+
+        // Get project name
+        let project = Project::find_by_id(&self.pool, project_id)
+            .await?
+            .ok_or(TelegramError::ProjectNotFound(project_id))?;
+
+        // Get tasks
+        let tasks = Task::find_by_project_id_with_attempt_status(&self.pool, project_id).await?;
+
+        if tasks.is_empty() {
+            return Ok(UpdateResult::Response(format!(
+                "No tasks in project <b>{}</b>.",
+                escape_html(&project.name)
+            )));
+        }
+
+        let mut message = format!("<b>Tasks in {}</b>\n\n", escape_html(&project.name));
+        for task in tasks.iter().take(20) {
+            message.push_str(&format_task_line(&task.task));
+        }
+
+        if tasks.len() > 20 {
+            message.push_str(&format!("... and {} more tasks", tasks.len() - 20));
+        }
+
+        Ok(UpdateResult::Response(message))
+    }
+
+    /// Handle /search command - full-text search in the active project, or all projects
+    async fn cmd_search(
+        &self,
+        args: &str,
+        chat: ReplyTarget,
+    ) -> Result<UpdateResult, TelegramError> {
+        if args.is_empty() {
+            return Ok(UpdateResult::Response(
+                "Usage: /search &lt;query&gt;".to_string(),
+            ));
+        }
+
+        let project_id = self.active_project_id(chat).await?;
+        let tasks = Task::search(&self.pool, args, project_id, SEARCH_RESULT_LIMIT).await?;
+
+        if tasks.is_empty() {
+            return Ok(UpdateResult::Response(format!(
+                "No tasks match <b>{}</b>.",
+                escape_html(args)
+            )));
+        }
+
+        let scope = if project_id.is_some() {
+            "active project"
+        } else {
+            "all projects"
+        };
+        let mut message = format!(
+            "<b>Results for {}</b> ({})\n\n",
+            escape_html(args),
+            scope
+        );
+        for task in &tasks {
+            message.push_str(&format_task_line(task));
+        }
+
+        Ok(UpdateResult::Response(message))
+    }
+
+    /// Handle /workload command - open tasks and running attempts per assignee
+    async fn cmd_workload(
+        &self,
+        args: &str,
+        chat: ReplyTarget,
+    ) -> Result<UpdateResult, TelegramError> {
+        let project_id = if args.is_empty() {
+            self.active_project_id(chat)
+                .await?
+                .ok_or(TelegramError::NoActiveProject)?
+        } else {
+            parse_uuid(args)?
+        };
+
+        let project = Project::find_by_id(&self.pool, project_id)
+            .await?
+            .ok_or(TelegramError::ProjectNotFound(project_id))?;
+
+        let tasks = Task::find_by_project_id_with_attempt_status(&self.pool, project_id).await?;
+        let assignees = TaskAssignee::find_assignees_by_project_id(&self.pool, project_id).await?;
+        let workloads = workload::summarize(&tasks, &assignees);
+
+        Ok(UpdateResult::Response(workload::format_workload(
+            &project.name,
+            &workloads,
+        )))
+    }
+
+    /// Handle /task command - get task details
+    async fn cmd_task(&self, args: &str) -> Result<UpdateResult, TelegramError> {
+        if args.is_empty() {
+            return Ok(UpdateResult::Response(
+                "Usage: /task <task_id>".to_string(),
+            ));
+        }
+
+        let task_id = parse_uuid(args)?;
+        let task = Task::find_by_id(&self.pool, task_id)
+            .await?
+            .ok_or(TelegramError::TaskNotFound(task_id))?;
+
+        let status_emoji = match task.status {
+            TaskStatus::Todo => "📋 Todo",
+            TaskStatus::InProgress => "🔄 In Progress",
+            TaskStatus::InReview => "👀 In Review",
+            TaskStatus::Done => "✅ Done",
+            TaskStatus::Cancelled => "❌ Cancelled",
+        };
+
+        let mut message = format!(
+            "<b>{}</b>\n\nStatus: {}\nID: <code>{}</code>",
+            escape_html(&task.title),
+            status_emoji,
+            task.id
+        );
+
+        if let Some(desc) = &task.description
+            && !desc.is_empty()
+        {
+            message.push_str(&format!("\n\n<b>Description:</b>\n{}", escape_html(desc)));
+        }
+
+        let assignees = TaskAssignee::find_by_task_id(&self.pool, task.id).await?;
+        message.push_str(&format_assignees(&assignees));
+
+        Ok(UpdateResult::Response(message))
+    }
+
+    /// Handle /assign command - assign a task to a linked account
+    async fn cmd_assign(&self, args: &str, user_id: i64) -> Result<UpdateResult, TelegramError> {
+        let mut parts = args.split_whitespace();
+        let (Some(task_arg), Some(user_arg)) = (parts.next(), parts.next()) else {
+            return Ok(UpdateResult::Response(escape_html(ASSIGN_USAGE)));
+        };
+
+        let task_id = parse_uuid(task_arg)?;
+        let task = Task::find_by_id(&self.pool, task_id)
+            .await?
+            .ok_or(TelegramError::TaskNotFound(task_id))?;
+        let account = self.resolve_account(user_arg, user_id).await?;
+
+        TaskAssignee::add(
+            &self.pool,
+            task.id,
+            account.user_id,
+            TaskAssigneeRole::Assignee,
+        )
+        .await?;
+
+        Ok(UpdateResult::Response(format!(
+            "👤 Assigned <b>{}</b> to {}",
+            escape_html(&task.title),
+            escape_html(&account_label(account.username.as_deref(), account.user_id))
+        )))
+    }
+
+    /// Handle /unassign command - remove an assignee or watcher from a task
+    async fn cmd_unassign(&self, args: &str, user_id: i64) -> Result<UpdateResult, TelegramError> {
+        let mut parts = args.split_whitespace();
+        let (Some(task_arg), Some(user_arg)) = (parts.next(), parts.next()) else {
+            return Ok(UpdateResult::Response(
+                "Usage: /unassign &lt;task_id&gt; &lt;user&gt;".to_string(),
+            ));
+        };
+
+        let task_id = parse_uuid(task_arg)?;
+        let task = Task::find_by_id(&self.pool, task_id)
+            .await?
+            .ok_or(TelegramError::TaskNotFound(task_id))?;
+        let account = self.resolve_account(user_arg, user_id).await?;
+        let label = escape_html(&account_label(account.username.as_deref(), account.user_id));
+
+        let response = if TaskAssignee::remove(&self.pool, task.id, account.user_id).await? {
+            format!("Removed {} from <b>{}</b>", label, escape_html(&task.title))
+        } else {
+            format!("{} is not on <b>{}</b>", label, escape_html(&task.title))
+        };
+        Ok(UpdateResult::Response(response))
+    }
+
+    /// Handle /watch command - get a task's notifications without being assigned
+    async fn cmd_watch(&self, args: &str, user_id: i64) -> Result<UpdateResult, TelegramError> {
+        if args.is_empty() {
+            return Ok(UpdateResult::Response(
+                "Usage: /watch &lt;task_id&gt;".to_string(),
+            ));
+        }
+
+        let task_id = parse_uuid(args)?;
+        let task = Task::find_by_id(&self.pool, task_id)
+            .await?
+            .ok_or(TelegramError::TaskNotFound(task_id))?;
+        let account = self.resolve_account("me", user_id).await?;
+
+        TaskAssignee::add(
+            &self.pool,
+            task.id,
+            account.user_id,
+            TaskAssigneeRole::Watcher,
+        )
+        .await?;
+
+        Ok(UpdateResult::Response(format!(
+            "👁 Watching <b>{}</b>. Its notifications now come to this chat.",
+            escape_html(&task.title)
+        )))
+    }
+
+    /// Linked account named by `arg`: `me`, `@username` or a numeric user id
+    async fn resolve_account(
+        &self,
+        arg: &str,
+        sender_id: i64,
+    ) -> Result<TelegramAccount, TelegramError> {
+        // Lets the primary account be found by name even if it predates the
+        // account table
+        let primary = self.config.read().await.telegram.user_id;
+        if let Some(primary) = primary {
+            self.linked_account(primary).await?;
+        }
+
+        let account = if arg.eq_ignore_ascii_case("me") {
+            self.linked_account(sender_id).await?
+        } else if let Ok(user_id) = arg.parse::<i64>() {
+            self.linked_account(user_id).await?
+        } else {
+            TelegramAccount::find_by_username(&self.pool, arg.trim_start_matches('@')).await?
+        };
+        account.ok_or_else(|| {
+            TelegramError::InvalidCommand(format!(
+                "{arg} has not linked a Telegram account. Accounts link with the link from the web interface."
+            ))
+        })
+    }
+
+    /// Handle /logs command - tail of the latest coding agent run for a task
+    async fn cmd_logs(&self, args: &str) -> Result<UpdateResult, TelegramError> {
+        let mut parts = args.split_whitespace();
+        let Some(task_arg) = parts.next() else {
+            return Ok(UpdateResult::Response(
+                "Usage: /logs &lt;task_id&gt; [lines]".to_string(),
+            ));
+        };
+        let line_count = match parts.next() {
+            Some(n) => n.parse::<usize>().map_err(|_| {
+                TelegramError::InvalidCommand("Line count must be a number".to_string())
+            })?,
+            None => DEFAULT_LOG_LINES,
+        }
+        .clamp(1, MAX_LOG_LINES);
+
+        let task_id = parse_uuid(task_arg)?;
+        let task = Task::find_by_id(&self.pool, task_id)
+            .await?
+            .ok_or(TelegramError::TaskNotFound(task_id))?;
+
+        let workspace = Workspace::fetch_all(&self.pool, Some(task.id))
+            .await
+            .map_err(|e| TelegramError::Logs(e.to_string()))?
+            .into_iter()
+            .next();
+        let process = match workspace {
+            Some(workspace) => {
+                ExecutionProcess::find_latest_by_workspace_and_run_reason(
+                    &self.pool,
+                    workspace.id,
+                    &ExecutionProcessRunReason::CodingAgent,
+                )
+                .await?
+            }
+            None => None,
+        };
+        let Some(process) = process else {
+            return Ok(UpdateResult::Response(format!(
+                "Task <b>{}</b> has no agent runs yet.",
+                escape_html(&task.title)
+            )));
+        };
+
+        let records = ExecutionProcessLogs::find_by_execution_id(&self.pool, process.id).await?;
+        let messages = ExecutionProcessLogs::parse_logs(&records)
+            .map_err(|e| TelegramError::Logs(e.to_string()))?;
+        let lines = tail_log_lines(&messages, line_count);
+        if lines.is_empty() {
+            return Ok(UpdateResult::Response(format!(
+                "No output yet for <b>{}</b>.",
+                escape_html(&task.title)
+            )));
+        }
+
+        let header = format!(
+            "📜 <b>{}</b> (last {} lines)",
+            escape_html(&task.title),
+            lines.len()
+        );
+        Ok(UpdateResult::Responses(chunk_log_lines(
+            &header,
+            &lines,
+            TELEGRAM_MAX_MESSAGE_LEN,
+        )))
+    }
+
+    /// Handle /diff command - changes made by the latest attempt of a task
+    ///
+    /// Small diffs are sent inline; larger ones as a per-file summary with a
+    /// button to expand each file.
+    async fn cmd_diff(&self, args: &str) -> Result<UpdateResult, TelegramError> {
+        if args.is_empty() {
+            return Ok(UpdateResult::Response(
+                "Usage: /diff &lt;task_id&gt;".to_string(),
+            ));
+        }
+
+        let task_id = parse_uuid(args)?;
+        let task = Task::find_by_id(&self.pool, task_id)
+            .await?
+            .ok_or(TelegramError::TaskNotFound(task_id))?;
+
+        let Some(files) = self.latest_attempt_diffs(&task).await? else {
+            return Ok(UpdateResult::Response(format!(
+                "Task <b>{}</b> has no attempts yet.",
+                escape_html(&task.title)
+            )));
+        };
+        if files.is_empty() {
+            return Ok(UpdateResult::Response(format!(
+                "No changes yet for <b>{}</b>.",
+                escape_html(&task.title)
+            )));
+        }
+
+        let summary = format_diff_stat(&task.title, &files, TELEGRAM_MAX_MESSAGE_LEN);
+        let unified = files
+            .iter()
+            .map(|(path, diff)| render_file_diff(path, diff))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let inline = format!("{summary}\n\n<pre>{}</pre>", escape_html(&unified));
+        if inline.len() <= TELEGRAM_MAX_MESSAGE_LEN {
+            return Ok(UpdateResult::Response(inline));
+        }
+
+        let buttons = files
+            .iter()
+            .enumerate()
+            .take(MAX_DIFF_FILE_BUTTONS)
+            .map(|(index, (path, _))| CallbackButton {
+                label: truncate_to_char_boundary(path, 60).to_string(),
+                data: format!("{DIFF_CALLBACK_PREFIX}{}:{index}", task.id),
+            })
+            .collect();
+        Ok(UpdateResult::ResponseWithButtons {
+            text: summary,
+            buttons,
+        })
+    }
+
+    /// Expand one file of a /diff summary
+    async fn cmd_diff_file(&self, task_id: Uuid, index: usize) -> Result<UpdateResult, TelegramError> {
+        let task = Task::find_by_id(&self.pool, task_id)
+            .await?
+            .ok_or(TelegramError::TaskNotFound(task_id))?;
+        let files = self.latest_attempt_diffs(&task).await?.unwrap_or_default();
+        let Some((path, diff)) = files.get(index) else {
+            return Ok(UpdateResult::Response(
+                "That file is no longer part of the diff. Run /diff again.".to_string(),
+            ));
+        };
+
+        let header = format!("<b>{}</b>\n", escape_html(path));
+        let body = pre_block_head(
+            &render_file_diff(path, diff),
+            TELEGRAM_MAX_MESSAGE_LEN - header.len(),
+        );
+        Ok(UpdateResult::Response(format!("{header}{body}")))
+    }
+
+    /// File diffs of the task's latest attempt against each repo's target branch,
+    /// or `None` if the task has never been attempted
+    async fn latest_attempt_diffs(
+        &self,
+        task: &Task,
+    ) -> Result<Option<Vec<(String, Diff)>>, TelegramError> {
+        let git = self
+            .git
+            .clone()
+            .ok_or_else(|| TelegramError::Diff("diffs are not supported by this server".into()))?;
+
+        // Workspaces are returned newest first
+        let Some(workspace) = Workspace::fetch_all(&self.pool, Some(task.id))
+            .await
+            .map_err(|e| TelegramError::Diff(e.to_string()))?
+            .into_iter()
+            .next()
+        else {
+            return Ok(None);
+        };
+        let Some(container_ref) = workspace.container_ref.clone().filter(|c| !c.is_empty())
+        else {
+            return Ok(Some(Vec::new()));
+        };
+
+        let repos =
+            WorkspaceRepo::find_repos_with_target_branch_for_workspace(&self.pool, workspace.id)
+                .await?;
+        let multi_repo = repos.len() > 1;
+        let branch = workspace.branch.clone();
+        let files = tokio::task::spawn_blocking(move || {
+            let mut files = Vec::new();
+            for repo_with_branch in repos {
+                let repo = &repo_with_branch.repo;
+                let worktree_path = std::path::PathBuf::from(&container_ref).join(&repo.name);
+                let base_commit =
+                    git.get_base_commit(&repo.path, &branch, &repo_with_branch.target_branch)?;
+                let diffs = git.get_diffs(
+                    DiffTarget::Worktree {
+                        worktree_path: &worktree_path,
+                        base_commit: &base_commit,
+                    },
+                    None,
+                )?;
+                for diff in diffs {
+                    let path = GitService::diff_path(&diff);
+                    let path = if multi_repo {
+                        format!("{}/{}", repo.name, path)
+                    } else {
+                        path
+                    };
+                    files.push((path, diff));
+                }
+            }
+            Ok::<_, git::GitServiceError>(files)
+        })
+        .await
+        .map_err(|e| TelegramError::Diff(e.to_string()))?
+        .map_err(|e| TelegramError::Diff(e.to_string()))?;
+
+        Ok(Some(files))
+    }
+
+    /// Handle /merge command - ask for confirmation before merging a task's PR
+    async fn cmd_merge(&self, args: &str) -> Result<UpdateResult, TelegramError> {
+        if args.is_empty() {
+            return Ok(UpdateResult::Response(
+                "Usage: /merge &lt;task_id&gt;".to_string(),
+            ));
+        }
+
+        let task_id = parse_uuid(args)?;
+        let task = Task::find_by_id(&self.pool, task_id)
+            .await?
+            .ok_or(TelegramError::TaskNotFound(task_id))?;
+        let Some(pr) = self.open_pr_for_task(&task).await? else {
+            return Ok(UpdateResult::Response(format!(
+                "Task <b>{}</b> has no open pull request.",
+                escape_html(&task.title)
+            )));
+        };
+
+        Ok(UpdateResult::ResponseWithButtons {
+            text: format!(
+                "Merge PR <a href=\"{}\">#{}</a> for <b>{}</b>?",
+                escape_html(&pr.pr_info.url),
+                pr.pr_info.number,
+                escape_html(&task.title)
+            ),
+            buttons: vec![
+                CallbackButton {
+                    label: "✅ Yes, merge".to_string(),
+                    data: format!("{MERGE_CONFIRM_CALLBACK_PREFIX}{}", task.id),
+                },
+                CallbackButton {
+                    label: "Cancel".to_string(),
+                    data: CANCEL_CALLBACK.to_string(),
+                },
+            ],
+        })
+    }
+
+    /// Handle /stop command
+    ///
+    /// Only checks that the task has something running; stopping it is left
+    /// to the caller, which owns the execution processes.
+    async fn cmd_stop(&self, args: &str) -> Result<UpdateResult, TelegramError> {
+        if args.is_empty() {
+            return Ok(UpdateResult::Response(
+                "Usage: /stop &lt;task_id&gt;".to_string(),
+            ));
+        }
+
+        let task_id = parse_uuid(args)?;
+        let task = Task::find_by_id(&self.pool, task_id)
+            .await?
+            .ok_or(TelegramError::TaskNotFound(task_id))?;
+        let mut running = false;
+        for workspace in Workspace::fetch_all(&self.pool, Some(task.id))
+            .await
+            .map_err(|e| TelegramError::Api(e.to_string()))?
+        {
+            if ExecutionProcess::has_running_non_dev_server_processes_for_workspace(
+                &self.pool,
+                workspace.id,
+            )
+            .await?
+            {
+                running = true;
+                break;
+            }
+        }
+        if !running {
+            return Ok(UpdateResult::Response(format!(
+                "Task <b>{}</b> has no running attempt.",
+                escape_html(&task.title)
+            )));
+        }
+
+        Ok(UpdateResult::StopRequested { task_id: task.id })
+    }
+
+    /// Handle /retry command
+    ///
+    /// Only checks that the task's latest attempt failed or was stopped;
+    /// starting the new attempt is left to the caller, as for the Retry button.
+    async fn cmd_retry(&self, args: &str) -> Result<UpdateResult, TelegramError> {
+        if args.is_empty() {
+            return Ok(UpdateResult::Response(
+                "Usage: /retry &lt;task_id&gt;".to_string(),
+            ));
+        }
+
+        let task_id = parse_uuid(args)?;
+        let task = Task::find_by_id(&self.pool, task_id)
+            .await?
+            .ok_or(TelegramError::TaskNotFound(task_id))?;
+        let latest = Workspace::fetch_all(&self.pool, Some(task.id))
+            .await
+            .map_err(|e| TelegramError::Api(e.to_string()))?
+            .into_iter()
+            .next();
+        let Some(workspace) = latest else {
+            return Ok(UpdateResult::Response(format!(
+                "Task <b>{}</b> has no attempt to retry.",
+                escape_html(&task.title)
+            )));
+        };
+        let run = ExecutionProcess::find_latest_by_workspace_and_run_reason(
+            &self.pool,
+            workspace.id,
+            &ExecutionProcessRunReason::CodingAgent,
+        )
+        .await?;
+        let failed = run.is_some_and(|run| {
+            matches!(
+                run.status,
+                ExecutionProcessStatus::Failed
+                    | ExecutionProcessStatus::Killed
+                    | ExecutionProcessStatus::Stalled
+            )
+        });
+        if !failed {
+            return Ok(UpdateResult::Response(format!(
+                "The latest attempt of <b>{}</b> did not fail, so there is nothing to retry.",
+                escape_html(&task.title)
+            )));
+        }
+
+        Ok(UpdateResult::RetryRequested { task_id: task.id })
+    }
+
+    /// Merge the open PR of a task after the user confirmed
+    ///
+    /// The PR monitor picks up the merged state and moves the task to Done.
+    async fn merge_task_pr(&self, task_id: Uuid) -> Result<UpdateResult, TelegramError> {
+        let task = Task::find_by_id(&self.pool, task_id)
+            .await?
+            .ok_or(TelegramError::TaskNotFound(task_id))?;
+        let Some(pr) = self.open_pr_for_task(&task).await? else {
+            return Ok(UpdateResult::Response(format!(
+                "Task <b>{}</b> has no open pull request.",
+                escape_html(&task.title)
+            )));
+        };
+
+        let git_host = GitHostService::from_url(&pr.pr_info.url)
+            .map_err(|e| TelegramError::Merge(e.to_string()))?;
+        let info = git_host
+            .merge_pr(&pr.pr_info.url)
+            .await
+            .map_err(|e| TelegramError::Merge(e.to_string()))?;
+
+        let message = match info.status {
+            MergeStatus::Merged => format!(
+                "🔀 Merged PR #{} for <b>{}</b>.",
+                info.number,
+                escape_html(&task.title)
+            ),
+            _ => format!(
+                "Merge of PR #{} for <b>{}</b> was requested; it will complete once checks allow.",
+                info.number,
+                escape_html(&task.title)
+            ),
+        };
+        Ok(UpdateResult::Response(message))
+    }
+
+    /// The most recent open PR of the task's latest attempt
+    async fn open_pr_for_task(&self, task: &Task) -> Result<Option<PrMerge>, TelegramError> {
+        // Workspaces are returned newest first
+        let Some(workspace) = Workspace::fetch_all(&self.pool, Some(task.id))
+            .await
+            .map_err(|e| TelegramError::Merge(e.to_string()))?
+            .into_iter()
+            .next()
+        else {
+            return Ok(None);
+        };
+
+        // Merges are returned newest first
+        let pr = Merge::find_by_workspace_id(&self.pool, workspace.id)
+            .await?
+            .into_iter()
+            .find_map(|merge| match merge {
+                Merge::Pr(pr) if matches!(pr.pr_info.status, MergeStatus::Open) => Some(pr),
+                _ => None,
+            });
+        Ok(pr)
+    }
+
+    /// Handle /newtask command - create a new task
+    async fn cmd_newtask(
+        &self,
+        args: &str,
+        chat: ReplyTarget,
+    ) -> Result<UpdateResult, TelegramError> {
+        if args.is_empty() {
+            return Ok(UpdateResult::Response(
+                "Usage: /newtask &lt;title&gt; or /newtask &lt;project_id&gt; &lt;title&gt;, with an optional description on the following lines".to_string(),
+            ));
+        }
+
+        let (first_line, user_description) = split_title_description(args);
+
+        // Try to parse first word as UUID (project_id)
+        let parts: Vec<&str> = first_line.splitn(2, ' ').collect();
+        let (project_id, title) = if parts.len() == 2 {
+            if let Ok(id) = Uuid::parse_str(parts[0]) {
+                (id, parts[1].to_string())
+            } else {
+                // First word is not a UUID, use active project
+                let pid = self
+                    .active_project_id(chat)
+                    .await?
+                    .ok_or(TelegramError::NoActiveProject)?;
+                (pid, first_line.to_string())
+            }
+        } else {
+            // Single argument = title, use active project
+            let pid = self
+                .active_project_id(chat)
+                .await?
+                .ok_or(TelegramError::NoActiveProject)?;
+            (pid, first_line.to_string())
+        };
+
+        // Verify project exists
+        let project = Project::find_by_id(&self.pool, project_id)
+            .await?
+            .ok_or(TelegramError::ProjectNotFound(project_id))?;
+
+        // `#name` references pull prompt snippets into the description
+        let snippets =
+            prompt_library::find_referenced_snippets(&self.pool, project_id, &title).await?;
+        let names: Vec<String> = snippets.iter().map(|s| s.name.clone()).collect();
+        let title = prompt_library::strip_references(&title, &names);
+        if title.is_empty() {
+            return Err(TelegramError::InvalidCommand(
+                "Task title is empty. Usage: /newtask <title> [#prompt-name]".to_string(),
+            ));
+        }
+
+        let variables = HashMap::from([
+            ("task_title".to_string(), title.clone()),
+            ("project_name".to_string(), project.name.clone()),
+        ]);
+        let mut missing: Vec<String> = Vec::new();
+        // The typed description comes first, followed by any referenced prompts
+        let sections: Vec<String> = user_description
+            .map(str::to_string)
+            .into_iter()
+            .chain(snippets.iter().map(|snippet| {
+                let rendered = prompt_library::render(&snippet.content, &variables);
+                for name in rendered.missing {
+                    if !missing.contains(&name) {
+                        missing.push(name);
+                    }
+                }
+                rendered.text
+            }))
+            .collect();
+        let description = (!sections.is_empty()).then(|| sections.join("\n\n"));
+
+        // Create the task
+        let create_task =
+            CreateTask::from_title_description(project_id, title.clone(), description);
+        let task_id = Uuid::new_v4();
+        let task = Task::create(&self.pool, &create_task, task_id).await?;
+
+        let mut response = format!(
+            "✅ Created task in <b>{}</b>:\n\n<b>{}</b>\n<code>{}</code>",
+            escape_html(&project.name),
+            escape_html(&task.title),
+            task.id
+        );
+        if !names.is_empty() {
+            response.push_str(&format!(
+                "\n\n📝 Prompts: {}",
+                escape_html(&names.join(", "))
+            ));
+        }
+        if !missing.is_empty() {
+            response.push_str(&format!(
+                "\n⚠️ Unfilled variables: {}",
+                escape_html(&missing.join(", "))
+            ));
+        }
+        Ok(UpdateResult::Response(response))
+    }
+
+    /// Handle /message command - send/queue a message for a task
     async fn cmd_message(&self, args: &str) -> Result<UpdateResult, TelegramError> {
         if args.is_empty() {
             return Ok(UpdateResult::Response(
@@ -933,417 +3965,2907 @@ To link your account, use the link from the web interface."#;
             ));
         }
 
-        let parts: Vec<&str> = args.splitn(2, ' ').collect();
-        if parts.len() < 2 {
-            return Ok(UpdateResult::Response(
-                "Usage: /message <task_id> <text>".to_string(),
-            ));
+        let parts: Vec<&str> = args.splitn(2, ' ').collect();
+        if parts.len() < 2 {
+            return Ok(UpdateResult::Response(
+                "Usage: /message <task_id> <text>".to_string(),
+            ));
+        }
+
+        let task_id = parse_uuid(parts[0])?;
+        let message_text = parts[1];
+
+        // Verify task exists
+        let task = Task::find_by_id(&self.pool, task_id)
+            .await?
+            .ok_or(TelegramError::TaskNotFound(task_id))?;
+
+        self.queue_follow_up(&task, message_text).await
+    }
+
+    /// Handle /mute command: pause task notifications for a while
+    async fn cmd_mute(&self, args: &str) -> Result<UpdateResult, TelegramError> {
+        if args.trim().is_empty() {
+            return Ok(UpdateResult::Response(
+                "Usage: /mute &lt;duration&gt;, e.g. /mute 30m, /mute 2h or /mute 1d".to_string(),
+            ));
+        }
+        let duration = parse_mute_duration(args).ok_or_else(|| {
+            TelegramError::InvalidCommand(format!(
+                "Invalid duration: {}. Use e.g. 30m, 2h, 1d or 1h30m (at most 30 days).",
+                args.trim()
+            ))
+        })?;
+
+        let until = Utc::now() + duration;
+        self.config.write().await.telegram.muted_until = Some(until);
+
+        Ok(UpdateResult::SettingsChanged(format!(
+            "🔕 Notifications muted until {} UTC. Use /unmute to resume earlier.",
+            until.format("%Y-%m-%d %H:%M")
+        )))
+    }
+
+    /// Handle /menu command: `/menu` shows the quick-action keyboard, `/menu off` hides it
+    async fn cmd_menu(&self, args: &str) -> Result<UpdateResult, TelegramError> {
+        match args.trim().to_lowercase().as_str() {
+            "" | "on" => Ok(UpdateResult::ResponseWithMenu {
+                text: "Quick actions are below the message box. Use /menu off to hide them."
+                    .to_string(),
+                show: true,
+            }),
+            "off" => Ok(UpdateResult::ResponseWithMenu {
+                text: "Menu hidden. Use /menu to bring it back.".to_string(),
+                show: false,
+            }),
+            other => Err(TelegramError::InvalidCommand(format!(
+                "Unknown option: {}. Use /menu or /menu off.",
+                other
+            ))),
+        }
+    }
+
+    /// Handle /report command - the digest of the last 24 hours, on demand
+    async fn cmd_report(&self, locale: Locale) -> Result<UpdateResult, TelegramError> {
+        let digest = digest::build_digest(&self.pool, Utc::now()).await?;
+        Ok(UpdateResult::Response(digest.format(locale)))
+    }
+
+    /// Handle /shortcut command: list, save or remove the chat's project shortcuts
+    async fn cmd_shortcut(&self, args: &str, chat_id: i64) -> Result<UpdateResult, TelegramError> {
+        let mut parts = args.split_whitespace();
+        let Some(name) = parts.next() else {
+            let shortcuts = TelegramProjectShortcut::find_by_chat_id(&self.pool, chat_id).await?;
+            if shortcuts.is_empty() {
+                return Ok(UpdateResult::Response(
+                    "No shortcuts yet. Use /shortcut &lt;name&gt; &lt;project_id&gt; to add one."
+                        .to_string(),
+                ));
+            }
+            let mut response = "<b>Shortcuts:</b>\n".to_string();
+            for shortcut in shortcuts {
+                let project = Project::find_by_id(&self.pool, shortcut.project_id).await?;
+                let target = project.map_or_else(
+                    || format!("<code>{}</code>", shortcut.project_id),
+                    |project| escape_html(&project.name),
+                );
+                response.push_str(&format!("/{} → {}\n", shortcut.name, target));
+            }
+            return Ok(UpdateResult::Response(response));
+        };
+
+        let name = name.trim_start_matches('/').to_lowercase();
+        if !is_valid_shortcut_name(&name) {
+            return Err(TelegramError::InvalidCommand(format!(
+                "Invalid shortcut name: {}. Use up to 32 letters, digits or underscores.",
+                name
+            )));
+        }
+
+        let Some(project_id) = parts.next() else {
+            let removed = TelegramProjectShortcut::delete(&self.pool, chat_id, &name).await?;
+            return Ok(UpdateResult::Response(if removed {
+                format!("🗑 Removed shortcut /{}", name)
+            } else {
+                format!("No shortcut named /{}.", name)
+            }));
+        };
+        let project_id = parse_uuid(project_id)?;
+        let project = Project::find_by_id(&self.pool, project_id)
+            .await?
+            .ok_or(TelegramError::ProjectNotFound(project_id))?;
+        TelegramProjectShortcut::set(&self.pool, chat_id, &name, project_id).await?;
+
+        Ok(UpdateResult::Response(format!(
+            "✅ /{} now switches to <b>{}</b>",
+            name,
+            escape_html(&project.name)
+        )))
+    }
+
+    /// Handle /role command - list accounts, or change one's role
+    async fn cmd_role(&self, args: &str, user_id: i64) -> Result<UpdateResult, TelegramError> {
+        let mut parts = args.split_whitespace();
+        let (Some(user_arg), Some(role_arg)) = (parts.next(), parts.next()) else {
+            if !args.is_empty() {
+                return Ok(UpdateResult::Response(escape_html(ROLE_USAGE)));
+            }
+            let accounts = TelegramAccount::find_all(&self.pool).await?;
+            if accounts.is_empty() {
+                return Ok(UpdateResult::Response(
+                    "No linked accounts yet.".to_string(),
+                ));
+            }
+            let mut response = "<b>Accounts:</b>\n".to_string();
+            for account in accounts {
+                // The stored role of the primary and allowlisted users is not used
+                let role = self.role_of(account.user_id).await?.unwrap_or(account.role);
+                response.push_str(&format!(
+                    "{} - {}\n",
+                    escape_html(&account_label(account.username.as_deref(), account.user_id)),
+                    role_name(role)
+                ));
+            }
+            return Ok(UpdateResult::Response(response));
+        };
+
+        let role = parse_role(role_arg)
+            .ok_or_else(|| TelegramError::InvalidCommand(ROLE_USAGE.to_string()))?;
+        let account = self.resolve_account(user_arg, user_id).await?;
+        let label = escape_html(&account_label(account.username.as_deref(), account.user_id));
+        TelegramAccount::set_role(&self.pool, account.user_id, role).await?;
+
+        let mut response = format!("✅ {} is now {}", label, role_name(role));
+        if role == TelegramAccountRole::ReadOnly
+            && self.role_of(account.user_id).await? == Some(TelegramAccountRole::Admin)
+        {
+            response.push_str(", but stays an admin while they are the primary or an allowed user");
+        }
+        Ok(UpdateResult::Response(response))
+    }
+
+    /// Handle /digest command - show or change how the sender's account gets
+    /// its notifications
+    async fn cmd_digest(&self, args: &str, user_id: i64) -> Result<UpdateResult, TelegramError> {
+        let account = self
+            .linked_account(user_id)
+            .await?
+            .ok_or(TelegramError::NotLinked)?;
+        if args.is_empty() {
+            return Ok(UpdateResult::Response(format!(
+                "Your notifications are {}.\n{}",
+                digest_mode_name(account.digest_mode),
+                escape_html(DIGEST_USAGE)
+            )));
+        }
+
+        let mode = parse_digest_mode(args)
+            .ok_or_else(|| TelegramError::InvalidCommand(DIGEST_USAGE.to_string()))?;
+        TelegramAccount::set_digest_mode(&self.pool, user_id, mode).await?;
+        Ok(UpdateResult::Response(format!(
+            "✅ Your notifications are now {}",
+            digest_mode_name(mode)
+        )))
+    }
+
+    /// Handle /unmute command
+    async fn cmd_unmute(&self) -> Result<UpdateResult, TelegramError> {
+        let mut config = self.config.write().await;
+        if !config.telegram.is_muted(Utc::now()) {
+            return Ok(UpdateResult::Response(
+                "Notifications are not muted.".to_string(),
+            ));
+        }
+        config.telegram.muted_until = None;
+
+        Ok(UpdateResult::SettingsChanged(
+            "🔔 Notifications resumed.".to_string(),
+        ))
+    }
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Check a new token with `getMe`, returning the bot's username. With
+/// `current` set, the token must belong to the same bot.
+async fn verify_bot(bot: &BotClient, current: Option<&BotClient>) -> Result<String, TelegramError> {
+    if let Some(current) = current
+        && current.id() != bot.id()
+    {
+        return Err(TelegramError::WrongBot {
+            expected: current.id().to_string(),
+            actual: bot.id().to_string(),
+        });
+    }
+    let me = bot
+        .api
+        .get_me()
+        .await
+        .map_err(|e| TelegramError::Api(e.to_string()))?
+        .result;
+    Ok(me.username.unwrap_or(me.first_name))
+}
+
+/// A new secret for `setWebhook`, which allows letters, digits, `_` and `-`
+fn generate_webhook_secret() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(WEBHOOK_SECRET_LEN)
+        .map(char::from)
+        .collect()
+}
+
+/// Where Telegram should post the updates of the default bot, or of
+/// `project_id`'s own bot
+fn webhook_url(base_url: &str, project_id: Option<Uuid>) -> String {
+    let base_url = base_url.trim_end_matches('/');
+    match project_id {
+        Some(project_id) => format!("{base_url}/api/telegram/webhook/{project_id}"),
+        None => format!("{base_url}/api/telegram/webhook"),
+    }
+}
+
+/// What `getWebhookInfo` reported
+#[derive(Debug)]
+struct WebhookStatus {
+    /// Empty when no webhook is registered
+    url: String,
+    pending_update_count: u32,
+    last_error: Option<String>,
+}
+
+/// What `setup_state` found out, for `build_setup_state`
+#[derive(Debug, Default)]
+struct SetupFacts {
+    token_configured: bool,
+    /// The bot's username from `getMe`, or why the call failed. None when it
+    /// was not made.
+    bot: Option<Result<String, String>>,
+    /// `TELEGRAM_BOT_USERNAME`
+    configured_username: Option<String>,
+    /// Where the webhook should point, None without `webhook_base_url`
+    expected_webhook_url: Option<String>,
+    /// None when `getWebhookInfo` was not called
+    webhook: Option<Result<WebhookStatus, String>>,
+    linked_chat_id: Option<i64>,
+}
+
+fn build_setup_state(facts: &SetupFacts) -> TelegramSetupState {
+    let step = |kind, status, detail: Option<String>, action: Option<String>| TelegramSetupStep {
+        kind,
+        status,
+        detail,
+        action,
+    };
+    let done = |kind, detail: String| step(kind, TelegramSetupStepStatus::Done, Some(detail), None);
+    let fix_token_first = || Some("Fix the bot token first".to_string());
+    let actual_username = match &facts.bot {
+        Some(Ok(username)) => Some(username.as_str()),
+        _ => None,
+    };
+
+    let token = match &facts.bot {
+        _ if !facts.token_configured => step(
+            TelegramSetupStepKind::Token,
+            TelegramSetupStepStatus::Missing,
+            None,
+            Some(
+                "Create a bot with @BotFather, set TELEGRAM_BOT_TOKEN to its token and restart \
+                 the server"
+                    .to_string(),
+            ),
+        ),
+        Some(Err(e)) => step(
+            TelegramSetupStepKind::Token,
+            TelegramSetupStepStatus::Error,
+            Some(e.clone()),
+            Some(
+                "Check TELEGRAM_BOT_TOKEN. If the token was revoked, get a new one from \
+                 @BotFather and send it to POST /api/telegram/rotate-token"
+                    .to_string(),
+            ),
+        ),
+        _ => done(
+            TelegramSetupStepKind::Token,
+            match actual_username {
+                Some(username) => format!("Accepted by Telegram for @{username}"),
+                None => "Configured".to_string(),
+            },
+        ),
+    };
+
+    let username_action = |username: &str| {
+        Some(format!(
+            "Set TELEGRAM_BOT_USERNAME={username} and restart the server, so account links \
+             open the bot"
+        ))
+    };
+    let username = match (facts.configured_username.as_deref(), actual_username) {
+        (Some(configured), Some(actual))
+            if !configured
+                .trim_start_matches('@')
+                .eq_ignore_ascii_case(actual) =>
+        {
+            step(
+                TelegramSetupStepKind::Username,
+                TelegramSetupStepStatus::Error,
+                Some(format!(
+                    "TELEGRAM_BOT_USERNAME is @{}, but the token belongs to @{actual}",
+                    configured.trim_start_matches('@')
+                )),
+                username_action(actual),
+            )
+        }
+        (Some(configured), _) => done(
+            TelegramSetupStepKind::Username,
+            format!("@{}", configured.trim_start_matches('@')),
+        ),
+        (None, Some(actual)) => step(
+            TelegramSetupStepKind::Username,
+            TelegramSetupStepStatus::Missing,
+            None,
+            username_action(actual),
+        ),
+        (None, None) => step(
+            TelegramSetupStepKind::Username,
+            TelegramSetupStepStatus::Missing,
+            None,
+            username_action("<bot username>"),
+        ),
+    };
+
+    let registered_url = match &facts.webhook {
+        Some(Ok(status)) if !status.url.is_empty() => Some(status.url.as_str()),
+        _ => None,
+    };
+    let webhook = match (&facts.expected_webhook_url, &facts.webhook) {
+        (None, _) => step(
+            TelegramSetupStepKind::Webhook,
+            TelegramSetupStepStatus::Missing,
+            Some(match registered_url {
+                Some(url) => format!("Telegram posts updates to {url}"),
+                None => "No webhook is registered, and this server does not poll for updates, \
+                         so the bot cannot receive commands"
+                    .to_string(),
+            }),
+            Some(
+                "Set telegram.webhook_base_url to this server's public HTTPS URL and restart \
+                 the server to register the webhook"
+                    .to_string(),
+            ),
+        ),
+        (Some(_), None) => step(
+            TelegramSetupStepKind::Webhook,
+            TelegramSetupStepStatus::Missing,
+            None,
+            fix_token_first(),
+        ),
+        (Some(_), Some(Err(e))) => step(
+            TelegramSetupStepKind::Webhook,
+            TelegramSetupStepStatus::Error,
+            Some(e.clone()),
+            Some("Try again; Telegram could not be asked about the webhook".to_string()),
+        ),
+        (Some(expected), Some(Ok(status))) if status.url != *expected => step(
+            TelegramSetupStepKind::Webhook,
+            TelegramSetupStepStatus::Error,
+            Some(match registered_url {
+                Some(url) => format!("Telegram posts updates to {url} instead of {expected}"),
+                None => format!("No webhook is registered, expected {expected}"),
+            }),
+            Some("Restart the server to register the webhook".to_string()),
+        ),
+        // Telegram keeps the last error after it recovers; it only matters
+        // while updates are piling up
+        (Some(expected), Some(Ok(status)))
+            if status.pending_update_count > 0 && status.last_error.is_some() =>
+        {
+            step(
+                TelegramSetupStepKind::Webhook,
+                TelegramSetupStepStatus::Error,
+                Some(format!(
+                    "{} updates are waiting; the last delivery failed: {}",
+                    status.pending_update_count,
+                    status.last_error.as_deref().unwrap_or_default()
+                )),
+                Some(format!(
+                    "Make sure {expected} is reachable from the internet over HTTPS"
+                )),
+            )
+        }
+        (Some(expected), Some(Ok(_))) => done(
+            TelegramSetupStepKind::Webhook,
+            format!("Telegram posts updates to {expected}"),
+        ),
+    };
+
+    let link_chat = match facts.linked_chat_id {
+        Some(chat_id) => done(
+            TelegramSetupStepKind::LinkChat,
+            format!("Notifications go to chat {chat_id}"),
+        ),
+        None => step(
+            TelegramSetupStepKind::LinkChat,
+            TelegramSetupStepStatus::Missing,
+            None,
+            Some(
+                "Open the link from GET /api/telegram/link (or scan its QR code) and press \
+                 Start in Telegram"
+                    .to_string(),
+            ),
+        ),
+    };
+
+    let steps = vec![token, username, webhook, link_chat];
+    TelegramSetupState {
+        complete: steps
+            .iter()
+            .all(|step| step.status == TelegramSetupStepStatus::Done),
+        update_mode: facts.webhook.as_ref().and_then(|webhook| {
+            webhook.as_ref().ok().map(|status| {
+                if status.url.is_empty() {
+                    TelegramUpdateMode::Polling
+                } else {
+                    TelegramUpdateMode::Webhook
+                }
+            })
+        }),
+        steps,
+    }
+}
+
+/// The /setup checklist. The command reaching the server shows updates get
+/// through, whatever `getWebhookInfo` said. `link_action` replaces the action
+/// of an unlinked chat.
+fn format_setup_state(state: &TelegramSetupState, link_action: Option<&str>) -> String {
+    let mut message = "🧰 <b>Bot setup</b>\n".to_string();
+    for step in &state.steps {
+        let icon = match step.status {
+            TelegramSetupStepStatus::Done => "✅",
+            TelegramSetupStepStatus::Missing => "⬜",
+            TelegramSetupStepStatus::Error => "❌",
+        };
+        let label = match step.kind {
+            TelegramSetupStepKind::Token => "Bot token",
+            TelegramSetupStepKind::Username => "Bot username",
+            TelegramSetupStepKind::Webhook => "Webhook",
+            TelegramSetupStepKind::LinkChat => "Linked chat",
+        };
+        message.push_str(&format!("\n{icon} <b>{label}</b>"));
+        if let Some(detail) = &step.detail {
+            message.push_str(&format!(": {}", escape_html(detail)));
+        }
+        if step.kind == TelegramSetupStepKind::LinkChat
+            && step.status != TelegramSetupStepStatus::Done
+            && let Some(action) = link_action
+        {
+            message.push_str(&format!("\n   → {action}"));
+        } else if let Some(action) = &step.action {
+            message.push_str(&format!("\n   → {}", escape_html(action)));
+        }
+    }
+
+    message.push_str("\n\n✅ This command reached the server, so the bot receives updates.");
+    if state.complete {
+        message.push_str(
+            "\nEverything is set up. Send /setup test to get a test notification in the linked chat.",
+        );
+    } else {
+        message.push_str("\nSend /setup again after each fix to check it.");
+    }
+    message
+}
+
+/// The /health report: uptime, running work, executor and rate-limited providers
+fn format_health(snapshot: &HealthSnapshot) -> String {
+    let executor = if snapshot.executor_available {
+        format!("{} ✅", snapshot.executor)
+    } else {
+        format!("{} ❌ not found", snapshot.executor)
+    };
+    let mut message = format!(
+        "🩺 <b>Server status</b>\n\nUptime: {}\nRunning attempts: {}\nQueued follow-ups: {}\nExecutor: {}",
+        format_uptime(snapshot.uptime_secs),
+        snapshot.running_attempts,
+        snapshot.queued_follow_ups,
+        executor
+    );
+    for cooldown in &snapshot.provider_cooldowns {
+        message.push_str(&format!(
+            "\n⏸ {} rate limited until {} UTC",
+            cooldown.provider,
+            cooldown.until.format("%H:%M")
+        ));
+    }
+    message
+}
+
+/// What /whoami reports besides the Telegram settings
+struct WhoamiReport {
+    chat_id: i64,
+    user_id: i64,
+    role: Option<TelegramAccountRole>,
+    active_project: Option<String>,
+    database_reachable: bool,
+    /// Round trip of a getMe call, or why it failed
+    telegram_api: Result<std::time::Duration, String>,
+}
+
+fn format_whoami(
+    report: &WhoamiReport,
+    config: &TelegramConfig,
+    preferences: &NotificationPreferences,
+    now: DateTime<Utc>,
+) -> String {
+    let yes_no = |value: bool| if value { "on" } else { "off" };
+    let account = match report.role {
+        Some(role) => format!("✅ linked ({})", role_name(role)),
+        None => "❌ not linked. Ask an admin to allow your user ID.".to_string(),
+    };
+    let project = match &report.active_project {
+        Some(name) => format!("<b>{}</b>", escape_html(name)),
+        None => "none (use /project &lt;id&gt;)".to_string(),
+    };
+    let mut message = format!(
+        "🪪 <b>Who am I</b>\n\nChat ID: <code>{}</code>\nUser ID: <code>{}</code>\nAccount: {}",
+        report.chat_id, report.user_id, account
+    );
+    // Everything else describes the setup, which is only shown to linked users
+    if report.role.is_none() {
+        message.push_str(&format!("\n\nBot version: {APP_VERSION}"));
+        return message;
+    }
+    message.push_str(&format!("\nActive project: {project}"));
+
+    let destination = match config.chat_id {
+        Some(chat_id) if chat_id == report.chat_id => "this chat",
+        Some(_) => "another chat",
+        None => "no chat",
+    };
+    message.push_str(&format!(
+        "\n\n<b>Notifications</b>\nEnabled: {} (sent to {})",
+        yes_no(config.notifications_enabled),
+        destination
+    ));
+    if config.is_muted(now)
+        && let Some(until) = config.muted_until
+    {
+        message.push_str(&format!(
+            "\n🔕 Muted until {} UTC",
+            until.format("%Y-%m-%d %H:%M")
+        ));
+    }
+    let sends = |event| preferences.allows(event, NotificationChannel::Telegram);
+    let events = if config.notify_transitions.is_empty() {
+        let labels = [
+            (NotificationEventType::AttemptStarted, "started"),
+            (NotificationEventType::TaskInReview, "in review"),
+            (NotificationEventType::TaskDone, "done"),
+            (NotificationEventType::TaskFailed, "failed"),
+            (NotificationEventType::TaskCancelled, "cancelled"),
+            (NotificationEventType::PrMerged, "PR merged"),
+        ];
+        let events: Vec<&str> = labels
+            .into_iter()
+            .filter(|(event, _)| sends(*event))
+            .map(|(_, label)| label)
+            .collect();
+        if events.is_empty() {
+            "none".to_string()
+        } else {
+            events.join(", ")
+        }
+    } else {
+        let failed = if sends(NotificationEventType::TaskFailed) {
+            " + failed"
+        } else {
+            ""
+        };
+        format!(
+            "{} custom transitions{failed}",
+            config.notify_transitions.len()
+        )
+    };
+    message.push_str(&format!("\nEvents: {events}"));
+    message.push_str(&format!(
+        "\nSilent: {} · Edit in place: {} · Summaries: {}",
+        yes_no(config.silent),
+        yes_no(config.edit_in_place),
+        yes_no(config.include_llm_summary)
+    ));
+    let digest = match config.digest_schedule {
+        DigestSchedule::Off => "off".to_string(),
+        DigestSchedule::Daily => format!("daily at {:02}:00", config.digest_hour),
+        DigestSchedule::Weekdays => format!("weekdays at {:02}:00", config.digest_hour),
+    };
+    message.push_str(&format!("\nDigest: {digest}"));
+
+    let database = if report.database_reachable {
+        "✅ reachable"
+    } else {
+        "❌ unreachable"
+    };
+    let telegram_api = match &report.telegram_api {
+        Ok(elapsed) => format!("✅ {} ms", elapsed.as_millis()),
+        Err(e) => format!("❌ {}", escape_html(e)),
+    };
+    message.push_str(&format!(
+        "\n\n<b>Diagnostics</b>\nBot version: {}\nDatabase: {}\nTelegram API: {}",
+        APP_VERSION, database, telegram_api
+    ));
+    message
+}
+
+/// One-line board summary for a project, e.g. "📋 3 to do · 1 in progress"
+fn format_status_counts(counts: &TaskStatusCounts) -> String {
+    if counts.total == 0 {
+        return "📋 No tasks yet".to_string();
+    }
+    let columns = [
+        (counts.todo, "to do"),
+        (counts.in_progress, "in progress"),
+        (counts.in_review, "in review"),
+        (counts.done, "done"),
+        (counts.cancelled, "cancelled"),
+    ];
+    let parts: Vec<String> = columns
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, label)| format!("{count} {label}"))
+        .collect();
+    format!("📋 {}", parts.join(" · "))
+}
+
+fn status_emoji(status: &TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Todo => "📋",
+        TaskStatus::InProgress => "🔄",
+        TaskStatus::InReview => "👀",
+        TaskStatus::Done => "✅",
+        TaskStatus::Cancelled => "❌",
+    }
+}
+
+/// One task in a list: status emoji, title and ID
+fn format_task_line(task: &Task) -> String {
+    format!(
+        "{} <b>{}</b>\n  <code>{}</code>\n\n",
+        status_emoji(&task.status),
+        escape_html(&task.title),
+        task.id
+    )
+}
+
+/// Summary of a task pasted into a chat by an inline query result
+fn format_inline_task(task: &Task, project_name: Option<&str>) -> String {
+    let mut text = format!(
+        "{} <b>{}</b>\n",
+        status_emoji(&task.status),
+        escape_html(&task.title)
+    );
+    if let Some(name) = project_name {
+        text.push_str(&format!("📁 {}\n", escape_html(name)));
+    }
+    text.push_str(&format!("<code>{}</code>", task.id));
+    if let Some(description) = task.description.as_deref().map(str::trim)
+        && !description.is_empty()
+    {
+        let mut excerpt: String = description.chars().take(INLINE_DESCRIPTION_CHARS).collect();
+        if excerpt.len() < description.len() {
+            excerpt.push('…');
+        }
+        text.push_str(&format!("\n\n{}", escape_html(&excerpt)));
+    }
+    text
+}
+
+fn inline_task_result(task: &Task, project_name: Option<&str>) -> InlineQueryResult {
+    let content = InputTextMessageContent::builder()
+        .message_text(format_inline_task(task, project_name))
+        .parse_mode(ParseMode::Html)
+        .build();
+    let article = InlineQueryResultArticle::builder()
+        .id(task.id.to_string())
+        .title(task.title.clone())
+        .input_message_content(InputMessageContent::Text(content))
+        .description(format!(
+            "{} {}",
+            status_emoji(&task.status),
+            project_name.unwrap_or("Unknown project")
+        ))
+        .build();
+    InlineQueryResult::Article(article)
+}
+
+/// Escape HTML special characters for Telegram HTML parse mode
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Characters that must be backslash-escaped in MarkdownV2 text
+const MARKDOWN_V2_RESERVED: &[char] = &[
+    '\\', '_', '*', '[', ']', '(', ')', '~', '`', '>', '#', '+', '-', '=', '|', '{', '}', '.', '!',
+];
+
+/// Escape special characters for Telegram MarkdownV2 parse mode
+pub(crate) fn escape_markdown_v2(text: &str) -> String {
+    escape_chars(text, MARKDOWN_V2_RESERVED)
+}
+
+/// Inside `code` and `pre` entities only backticks and backslashes are escaped
+fn escape_markdown_v2_code(text: &str) -> String {
+    escape_chars(text, &['\\', '`'])
+}
+
+fn escape_chars(text: &str, reserved: &[char]) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if reserved.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn unescape_html(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// Split a message built from the bot's HTML subset into text and tag names
+fn html_segments(html: &str) -> Vec<(bool, &str)> {
+    let mut segments = Vec::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        let Some(len) = rest[start..].find('>') else {
+            break;
+        };
+        if start > 0 {
+            segments.push((false, &rest[..start]));
+        }
+        segments.push((true, &rest[start + 1..start + len]));
+        rest = &rest[start + len + 1..];
+    }
+    if !rest.is_empty() {
+        segments.push((false, rest));
+    }
+    segments
+}
+
+/// Length as Telegram counts it, in UTF-16 code units
+fn telegram_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+/// Room kept in each chunk for the tags that close and reopen the formatting
+/// cut by the split
+const SPLIT_TAG_RESERVE: usize = 128;
+
+/// Split a message built from the bot's HTML subset into chunks of at most
+/// `max_len`, preferring paragraph, then line boundaries. Tags open at a cut
+/// are closed at the end of the chunk and reopened at the start of the next,
+/// so every chunk is valid HTML on its own.
+fn split_html_message(html: &str, max_len: usize) -> Vec<String> {
+    if telegram_len(html) <= max_len {
+        return vec![html.to_string()];
+    }
+    let budget = max_len.saturating_sub(SPLIT_TAG_RESERVE).max(1);
+
+    let mut pieces = Vec::new();
+    for paragraph in html.split_inclusive("\n\n") {
+        if telegram_len(paragraph) <= budget {
+            pieces.push(paragraph);
+            continue;
+        }
+        for line in paragraph.split_inclusive('\n') {
+            if telegram_len(line) <= budget {
+                pieces.push(line);
+            } else {
+                pieces.extend(hard_split_html(line, budget));
+            }
+        }
+    }
+
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for piece in pieces {
+        if !current.is_empty() && telegram_len(&current) + telegram_len(piece) > budget {
+            chunks.push(std::mem::take(&mut current));
+        }
+        current.push_str(piece);
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+
+    // Carry the formatting that is open at each cut over to the next chunk
+    let mut open_tags: Vec<String> = Vec::new();
+    let chunks: Vec<String> = chunks
+        .into_iter()
+        .filter_map(|chunk| {
+            let mut balanced: String = open_tags.iter().map(|tag| format!("<{tag}>")).collect();
+            balanced.push_str(&chunk);
+            update_open_tags(&mut open_tags, &chunk);
+            for tag in open_tags.iter().rev() {
+                let name = tag.split_whitespace().next().unwrap_or(tag);
+                balanced.push_str(&format!("</{name}>"));
+            }
+            (!html_to_plain_text(&balanced).trim().is_empty()).then_some(balanced)
+        })
+        .collect();
+    if chunks.is_empty() {
+        return vec![html.to_string()];
+    }
+    chunks
+}
+
+/// Track the tags left open by `html`, given those open before it
+fn update_open_tags(open_tags: &mut Vec<String>, html: &str) {
+    for (is_tag, segment) in html_segments(html) {
+        if !is_tag {
+            continue;
+        }
+        match segment.strip_prefix('/') {
+            Some(name) => {
+                if let Some(index) = open_tags
+                    .iter()
+                    .rposition(|tag| tag.split_whitespace().next() == Some(name))
+                {
+                    open_tags.truncate(index);
+                }
+            }
+            None => open_tags.push(segment.to_string()),
+        }
+    }
+}
+
+/// Cut a line with no break in it into pieces of at most `budget`, never
+/// inside a tag or an HTML entity
+fn hard_split_html(line: &str, budget: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut len = 0;
+    // Last position a cut may happen at, and the length up to it
+    let mut safe = (0, 0);
+    // End of the tag or entity being read
+    let mut markup_end = None;
+    for (index, c) in line.char_indices() {
+        if markup_end.is_none() {
+            safe = (index, len);
+        }
+        if len + c.len_utf16() > budget && safe.0 > start {
+            pieces.push(&line[start..safe.0]);
+            start = safe.0;
+            len -= safe.1;
+        }
+        match (markup_end, c) {
+            (None, '<') => markup_end = Some('>'),
+            (None, '&') => markup_end = Some(';'),
+            (Some(end), c) if c == end => markup_end = None,
+            _ => {}
+        }
+        len += c.len_utf16();
+    }
+    pieces.push(&line[start..]);
+    pieces
+}
+
+/// Convert a message built with `<b>`, `<i>`, `<code>` and `<pre>` to MarkdownV2
+fn html_to_markdown_v2(html: &str) -> String {
+    let mut markdown = String::with_capacity(html.len());
+    let mut in_code = false;
+    for (is_tag, segment) in html_segments(html) {
+        if !is_tag {
+            let text = unescape_html(segment);
+            if in_code {
+                markdown.push_str(&escape_markdown_v2_code(&text));
+            } else {
+                markdown.push_str(&escape_markdown_v2(&text));
+            }
+            continue;
+        }
+        match segment {
+            "b" | "/b" => markdown.push('*'),
+            "i" | "/i" => markdown.push('_'),
+            "code" | "/code" => {
+                in_code = segment == "code";
+                markdown.push('`');
+            }
+            "pre" => {
+                in_code = true;
+                markdown.push_str("```\n");
+            }
+            "/pre" => {
+                in_code = false;
+                markdown.push_str("\n```");
+            }
+            _ => {}
+        }
+    }
+    markdown
+}
+
+/// Strip tags and entities from a message built with the bot's HTML subset
+fn html_to_plain_text(html: &str) -> String {
+    html_segments(html)
+        .into_iter()
+        .filter(|(is_tag, _)| !is_tag)
+        .map(|(_, text)| unescape_html(text))
+        .collect()
+}
+
+/// The HTML of a task status notification: the kind's header and the task
+/// title, followed by the summary when summaries are on and, for failures,
+/// the tail of the error output
+fn format_status_notification(
+    kind: TaskNotificationKind,
+    title: &str,
+    llm_summary: Option<&str>,
+    error_excerpt: Option<&str>,
+    config: &TelegramConfig,
+    locale: Locale,
+) -> String {
+    let mut message = format!("{}\n\n<b>{}</b>", kind.header(locale), escape_html(title));
+
+    if config.include_llm_summary
+        && let Some(summary) = llm_summary
+    {
+        message.push_str(&format!(
+            "\n\n<b>{}</b>\n",
+            ServerMessage::SummaryHeading.render(locale)
+        ));
+        message.push_str(&escape_html(summary));
+    }
+    if kind == TaskNotificationKind::Failed
+        && let Some(excerpt) = error_excerpt
+    {
+        message.push_str(&format!(
+            "\n\n<b>{}</b>\n<pre>{}</pre>",
+            ServerMessage::ErrorOutputHeading.render(locale),
+            escape_html_tail(excerpt, FAILURE_EXCERPT_MAX_LEN)
+        ));
+    }
+    message
+}
+
+/// Combine batched notifications into one message under a count header
+fn format_notification_batch(batch: &[BatchedNotification], locale: Locale) -> String {
+    let messages: Vec<&str> = batch
+        .iter()
+        .map(|notification| notification.message.as_str())
+        .collect();
+    format!(
+        "{}\n\n{}",
+        ServerMessage::NotificationBatch(batch.len()).render(locale),
+        messages.join("\n\n")
+    )
+}
+
+/// Combine the notifications held for a digest into one message under a
+/// count header
+fn format_digest(items: &[TelegramDigestItem], locale: Locale) -> String {
+    let messages: Vec<&str> = items.iter().map(|item| item.text.as_str()).collect();
+    format!(
+        "{}\n\n{}",
+        ServerMessage::NotificationDigest(items.len()).render(locale),
+        messages.join("\n\n")
+    )
+}
+
+/// Convert an HTML message to the configured parse mode
+fn render_message(html: &str, mode: TelegramParseMode) -> (String, Option<ParseMode>) {
+    match mode {
+        TelegramParseMode::Html => (html.to_string(), Some(ParseMode::Html)),
+        TelegramParseMode::MarkdownV2 => (html_to_markdown_v2(html), Some(ParseMode::MarkdownV2)),
+        TelegramParseMode::PlainText => (html_to_plain_text(html), None),
+        TelegramParseMode::Accessible => (strip_emoji(&html_to_plain_text(html)), None),
+    }
+}
+
+/// Strip emoji from inline button labels too when rendering for screen readers
+fn render_markup(markup: Option<ReplyMarkup>, mode: TelegramParseMode) -> Option<ReplyMarkup> {
+    match markup {
+        Some(ReplyMarkup::InlineKeyboardMarkup(mut keyboard))
+            if mode == TelegramParseMode::Accessible =>
+        {
+            for button in keyboard.inline_keyboard.iter_mut().flatten() {
+                button.text = strip_emoji(&button.text);
+            }
+            Some(ReplyMarkup::InlineKeyboardMarkup(keyboard))
+        }
+        markup => markup,
+    }
+}
+
+/// Format a NormalizedEntry for Telegram display
+fn format_entry(entry: &NormalizedEntry) -> Option<String> {
+    match &entry.entry_type {
+        NormalizedEntryType::AssistantMessage => {
+            let content = entry.content.trim();
+            if content.is_empty() {
+                None
+            } else {
+                Some(format!("💬 {}", content))
+            }
+        }
+        NormalizedEntryType::Thinking => {
+            let content = entry.content.trim();
+            if content.is_empty() {
+                None
+            } else {
+                // Truncate long thinking content
+                let truncated = if content.len() > 200 {
+                    format!("{}...", &content[..200])
+                } else {
+                    content.to_string()
+                };
+                Some(format!("💭 {}", truncated))
+            }
+        }
+        NormalizedEntryType::ToolUse {
+            tool_name,
+            action_type,
+            ..
+        } => {
+            let icon = match action_type {
+                ActionType::FileRead { path } => format!("📖 Reading: {}", path),
+                ActionType::FileEdit { path, .. } => format!("📝 Editing: {}", path),
+                ActionType::CommandRun { command, .. } => format!("⚡ Running: {}", command),
+                ActionType::Search { query } => format!("🔍 Searching: {}", query),
+                ActionType::WebFetch { url } => format!("🌐 Fetching: {}", url),
+                _ => format!("🔧 {}", tool_name),
+            };
+            Some(icon)
+        }
+        NormalizedEntryType::ErrorMessage { .. } => {
+            Some(format!("❌ {}", entry.content.trim()))
+        }
+        // Skip user messages, system messages, loading states
+        _ => None,
+    }
+}
+
+/// Entries that make up the conversation, as opposed to progress and usage updates
+fn is_conversation_entry(entry_type: &NormalizedEntryType) -> bool {
+    matches!(
+        entry_type,
+        NormalizedEntryType::UserMessage
+            | NormalizedEntryType::UserFeedback { .. }
+            | NormalizedEntryType::AssistantMessage
+            | NormalizedEntryType::ToolUse { .. }
+            | NormalizedEntryType::ErrorMessage { .. }
+    )
+}
+
+/// Whether the last line of an agent message asks something
+fn ends_with_question(message: &str) -> bool {
+    message
+        .lines()
+        .map(str::trim)
+        .rfind(|line| !line.is_empty())
+        .map(|line| line.trim_end_matches(['*', '_', '`', ')', '"', '\'']))
+        .is_some_and(|line| line.ends_with('?') || line.ends_with('？'))
+}
+
+/// Message asking the user to answer an agent's question
+fn format_question(task_name: &str, question: &str) -> String {
+    let question = question.trim();
+    let excerpt = truncate_to_char_boundary(question, QUESTION_MAX_LEN);
+    let ellipsis = if excerpt.len() < question.len() {
+        "…"
+    } else {
+        ""
+    };
+    format!(
+        "❓ <b>{}</b> is waiting for your answer:\n\n{}{}\n\nReply to this message to answer.",
+        escape_html(task_name),
+        escape_html(excerpt),
+        ellipsis
+    )
+}
+
+/// Format a stream message for Telegram
+///
+/// Only the tail of `content` is kept so the message fits in a single Telegram message.
+fn format_stream_message(task_name: &str, content: &str) -> String {
+    format!(
+        "🚀 <b>{}</b>\n\n<pre>{}</pre>",
+        escape_html(task_name),
+        escape_html_tail(content, STREAM_MAX_CONTENT_LEN)
+    )
+}
+
+/// Escape the longest suffix of `text` whose escaped form fits in `max_len` bytes,
+/// prefixing "..." when anything was cut
+fn escape_html_tail(text: &str, max_len: usize) -> String {
+    let escaped = escape_html(text);
+    if escaped.len() <= max_len {
+        return escaped;
+    }
+
+    let budget = max_len - 3;
+    let mut len = 0;
+    let mut start = text.len();
+    for (index, c) in text.char_indices().rev() {
+        len += match c {
+            '&' => 5,
+            '<' | '>' => 4,
+            _ => c.len_utf8(),
+        };
+        if len > budget {
+            break;
+        }
+        start = index;
+    }
+    format!("...{}", escape_html(&text[start..]))
+}
+
+/// The last `count` stdout/stderr lines of an execution log
+fn tail_log_lines(messages: &[LogMsg], count: usize) -> Vec<String> {
+    let lines: Vec<&str> = messages
+        .iter()
+        .filter_map(|msg| match msg {
+            LogMsg::Stdout(text) | LogMsg::Stderr(text) => Some(text.as_str()),
+            _ => None,
+        })
+        .flat_map(str::lines)
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    let start = lines.len().saturating_sub(count);
+    lines[start..].iter().map(|line| line.to_string()).collect()
+}
+
+/// The last stderr lines of a failed run, or its last output lines when it
+/// wrote nothing to stderr
+pub fn failure_excerpt(messages: &[LogMsg]) -> Option<String> {
+    let stderr: Vec<&str> = messages
+        .iter()
+        .filter_map(|msg| match msg {
+            LogMsg::Stderr(text) => Some(text.as_str()),
+            _ => None,
+        })
+        .flat_map(str::lines)
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    let lines = if stderr.is_empty() {
+        tail_log_lines(messages, FAILURE_EXCERPT_LINES)
+    } else {
+        let start = stderr.len().saturating_sub(FAILURE_EXCERPT_LINES);
+        stderr[start..]
+            .iter()
+            .map(|line| line.to_string())
+            .collect()
+    };
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// Pack log lines into `<pre>` blocks that each fit in `max_len` bytes, with
+/// `header` at the top of the first message. Overlong lines keep their tail.
+fn chunk_log_lines(header: &str, lines: &[String], max_len: usize) -> Vec<String> {
+    const OPEN: &str = "<pre>";
+    const CLOSE: &str = "</pre>";
+    let line_budget = max_len - header.len() - OPEN.len() - CLOSE.len() - 1;
+
+    let mut chunks = Vec::new();
+    let mut current = format!("{header}\n{OPEN}");
+    let mut has_lines = false;
+    for line in lines {
+        let escaped = escape_html_tail(line, line_budget);
+        if has_lines && current.len() + 1 + escaped.len() + CLOSE.len() > max_len {
+            current.push_str(CLOSE);
+            chunks.push(std::mem::replace(&mut current, OPEN.to_string()));
+            has_lines = false;
+        }
+        if has_lines {
+            current.push('\n');
+        }
+        current.push_str(&escaped);
+        has_lines = true;
+    }
+    current.push_str(CLOSE);
+    chunks.push(current);
+    chunks
+}
+
+/// Inline keyboard with one callback button per row
+fn inline_keyboard(buttons: &[CallbackButton]) -> ReplyMarkup {
+    let rows = buttons
+        .iter()
+        .map(|button| {
+            vec![
+                InlineKeyboardButton::builder()
+                    .text(button.label.clone())
+                    .callback_data(button.data.clone())
+                    .build(),
+            ]
+        })
+        .collect();
+    ReplyMarkup::InlineKeyboardMarkup(InlineKeyboardMarkup::builder().inline_keyboard(rows).build())
+}
+
+fn menu_keyboard() -> ReplyMarkup {
+    let rows = MENU_ROWS
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|(label, _)| KeyboardButton::builder().text(label.to_string()).build())
+                .collect()
+        })
+        .collect();
+    ReplyMarkup::ReplyKeyboardMarkup(
+        ReplyKeyboardMarkup::builder()
+            .keyboard(rows)
+            .is_persistent(true)
+            .resize_keyboard(true)
+            .build(),
+    )
+}
+
+/// Command run by a /menu keyboard button, given the text the button sent
+fn menu_command(text: &str) -> Option<&'static str> {
+    MENU_ROWS
+        .iter()
+        .flat_map(|row| row.iter())
+        .find(|(label, _)| *label == text.trim())
+        .map(|(_, command)| *command)
+}
+
+/// Error code and `retry_after` of a Bot API error response, or None for
+/// transport failures
+fn api_error_details(error: &frankenstein::Error) -> Option<(u64, Option<u64>)> {
+    match error {
+        frankenstein::Error::Api(response) => Some((
+            response.error_code,
+            response
+                .parameters
+                .as_ref()
+                .and_then(|parameters| parameters.retry_after)
+                .map(u64::from),
+        )),
+        _ => None,
+    }
+}
+
+/// Delay before the next attempt after `attempt` failed, or None to give up
+fn retry_delay(
+    policy: &TelegramRetryConfig,
+    attempt: u32,
+    api_error: Option<(u64, Option<u64>)>,
+) -> Option<Duration> {
+    if attempt >= policy.max_attempts {
+        return None;
+    }
+    let max_backoff = Duration::from_millis(policy.max_backoff_ms);
+    match api_error {
+        Some((429, Some(retry_after))) => Some(Duration::from_secs(retry_after).min(max_backoff)),
+        // Other client errors (bad request, blocked bot) will fail again
+        Some((code, _)) if code != 429 && code < 500 => None,
+        _ => {
+            let backoff = policy
+                .initial_backoff_ms
+                .saturating_mul(1 << (attempt - 1).min(16));
+            Some(Duration::from_millis(backoff).min(max_backoff))
+        }
+    }
+}
+
+/// Added and deleted line counts for a file diff
+fn diff_line_counts(diff: &Diff) -> (usize, usize) {
+    match (diff.additions, diff.deletions) {
+        (Some(additions), Some(deletions)) => (additions, deletions),
+        _ => compute_line_change_counts(
+            diff.old_content.as_deref().unwrap_or(""),
+            diff.new_content.as_deref().unwrap_or(""),
+        ),
+    }
+}
+
+/// Per-file `+added -deleted` summary of a diff, listing as many files as fit
+/// in `max_len` bytes
+fn format_diff_stat(title: &str, files: &[(String, Diff)], max_len: usize) -> String {
+    // Room for the "and N more files" line
+    const MORE_RESERVE: usize = 40;
+    let (total_added, total_deleted) = files
+        .iter()
+        .map(|(_, diff)| diff_line_counts(diff))
+        .fold((0, 0), |(a, d), (fa, fd)| (a + fa, d + fd));
+
+    let mut message = format!(
+        "🔀 <b>{}</b>\n{} files changed, +{} -{}\n",
+        escape_html(title),
+        files.len(),
+        total_added,
+        total_deleted
+    );
+    for (listed, (path, diff)) in files.iter().enumerate() {
+        let (added, deleted) = diff_line_counts(diff);
+        let line = format!(
+            "\n<code>+{added} -{deleted}</code> {}",
+            escape_html(path)
+        );
+        if message.len() + line.len() + MORE_RESERVE > max_len {
+            message.push_str(&format!("\n… and {} more files", files.len() - listed));
+            break;
+        }
+        message.push_str(&line);
+    }
+    message
+}
+
+/// Unified diff text for one file
+fn render_file_diff(path: &str, diff: &Diff) -> String {
+    if diff.content_omitted {
+        return format!("--- {path}\n(content omitted)\n");
+    }
+    create_unified_diff(
+        path,
+        diff.old_content.as_deref().unwrap_or(""),
+        diff.new_content.as_deref().unwrap_or(""),
+    )
+}
+
+/// Escape `text` into a `<pre>` block of at most `max_len` bytes, keeping the
+/// leading lines and marking the cut
+fn pre_block_head(text: &str, max_len: usize) -> String {
+    const TRUNCATED: &str = "\n… (truncated)";
+    let budget = max_len - "<pre></pre>".len() - TRUNCATED.len();
+
+    let mut body = String::new();
+    let mut truncated = false;
+    for line in text.lines() {
+        // Escaping grows a character by at most 5 bytes (`&amp;`)
+        let escaped = escape_html(truncate_to_char_boundary(line, budget / 5));
+        let separator = usize::from(!body.is_empty());
+        if body.len() + separator + escaped.len() > budget {
+            truncated = true;
+            break;
+        }
+        if separator == 1 {
+            body.push('\n');
+        }
+        body.push_str(&escaped);
+    }
+    if truncated {
+        body.push_str(TRUNCATED);
+    }
+    format!("<pre>{body}</pre>")
+}
+
+/// Parse a /mute duration such as `45m`, `2h`, `1d` or `1h30m`. A bare
+/// number is taken as minutes.
+const ASSIGN_USAGE: &str =
+    "Usage: /assign <task_id> <user>, where <user> is me, @username or a Telegram user id";
+
+/// One page of the /projects listing, with buttons to the neighbouring pages.
+/// `page` is 1-based and clamped to the last page.
+fn format_projects_page(
+    projects: &[ProjectActivity],
+    page: usize,
+) -> (String, Vec<CallbackButton>) {
+    let pages = projects.len().div_ceil(PROJECTS_PAGE_SIZE).max(1);
+    let page = page.clamp(1, pages);
+
+    let mut text = if pages > 1 {
+        format!("<b>Your Projects</b> (page {page} of {pages})\n\n")
+    } else {
+        "<b>Your Projects:</b>\n\n".to_string()
+    };
+    for project in projects
+        .iter()
+        .skip((page - 1) * PROJECTS_PAGE_SIZE)
+        .take(PROJECTS_PAGE_SIZE)
+    {
+        let open = match project.open_tasks {
+            0 => "no open tasks".to_string(),
+            1 => "1 open task".to_string(),
+            n => format!("{n} open tasks"),
+        };
+        text.push_str(&format!(
+            "• <b>{}</b> - {}\n  <code>{}</code>\n\n",
+            escape_html(&project.name),
+            open,
+            project.id
+        ));
+    }
+    text.push_str("Use /project &lt;id&gt; to set the active project.");
+
+    let mut buttons = Vec::new();
+    if page > 1 {
+        buttons.push(CallbackButton {
+            label: "◀️ Previous".to_string(),
+            data: format!("{PROJECTS_CALLBACK_PREFIX}{}", page - 1),
+        });
+    }
+    if page < pages {
+        buttons.push(CallbackButton {
+            label: "Next ▶️".to_string(),
+            data: format!("{PROJECTS_CALLBACK_PREFIX}{}", page + 1),
+        });
+    }
+    (text, buttons)
+}
+
+/// Ask for `follow_up` to be started on the idle session, or queue it after
+/// the session's queued message while an agent is running
+fn follow_up_result(
+    queued_messages: &QueuedMessageService,
+    session_id: Uuid,
+    task_title: &str,
+    follow_up: DraftFollowUpData,
+    running: bool,
+) -> UpdateResult {
+    if !running {
+        let text = format!(
+            "📨 Sent to the agent of task <b>{}</b>:\n\n{}",
+            escape_html(task_title),
+            escape_html(&follow_up.message)
+        );
+        return UpdateResult::FollowUpRequested {
+            session_id,
+            follow_up,
+            text,
+        };
+    }
+
+    let text = format!(
+        "📨 Message queued for task <b>{}</b>:\n\n{}\n\nIt will be sent when the current run finishes.",
+        escape_html(task_title),
+        escape_html(&follow_up.message)
+    );
+    queued_messages.append_message(session_id, follow_up);
+    UpdateResult::Response(text)
+}
+
+/// What a command asks of the sender's role
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CommandAccess {
+    /// Open to everyone: linking happens through /start, and /whoami tells an
+    /// admin which IDs to allow
+    Public,
+    /// Any linked or allowed user
+    Read,
+    /// Admins only, and nobody in maintenance mode
+    Write,
+}
+
+fn command_access(command: &str) -> CommandAccess {
+    if WRITE_COMMANDS.contains(&command) {
+        CommandAccess::Write
+    } else if matches!(command, "start" | "help" | "whoami") {
+        CommandAccess::Public
+    } else {
+        CommandAccess::Read
+    }
+}
+
+/// Whether an account with `role` may change anything; `action` says what it
+/// tried to do
+fn check_write(
+    role: TelegramAccountRole,
+    maintenance_mode: bool,
+    action: &str,
+) -> Result<(), TelegramError> {
+    match role {
+        TelegramAccountRole::Admin if maintenance_mode => Err(TelegramError::Maintenance),
+        TelegramAccountRole::Admin => Ok(()),
+        TelegramAccountRole::ReadOnly => Err(TelegramError::ReadOnly(action.to_string())),
+    }
+}
+
+/// `@username`, or the numeric id for accounts without a username
+fn account_label(username: Option<&str>, user_id: i64) -> String {
+    match username {
+        Some(username) => format!("@{username}"),
+        None => user_id.to_string(),
+    }
+}
+
+const ROLE_USAGE: &str =
+    "Usage: /role <user> <admin|read_only>, where <user> is me, @username or a Telegram user id";
+
+/// Role named by a /role argument
+fn parse_role(arg: &str) -> Option<TelegramAccountRole> {
+    match arg.to_lowercase().replace('-', "_").as_str() {
+        "admin" => Some(TelegramAccountRole::Admin),
+        "read_only" | "readonly" => Some(TelegramAccountRole::ReadOnly),
+        _ => None,
+    }
+}
+
+fn role_name(role: TelegramAccountRole) -> &'static str {
+    match role {
+        TelegramAccountRole::Admin => "admin",
+        TelegramAccountRole::ReadOnly => "read-only",
+    }
+}
+
+const DIGEST_USAGE: &str = "Usage: /digest <immediate|hourly|daily>";
+
+/// Digest mode named by a /digest argument
+fn parse_digest_mode(arg: &str) -> Option<TelegramDigestMode> {
+    match arg.trim().to_lowercase().as_str() {
+        "immediate" | "off" => Some(TelegramDigestMode::Immediate),
+        "hourly" => Some(TelegramDigestMode::Hourly),
+        "daily" => Some(TelegramDigestMode::Daily),
+        _ => None,
+    }
+}
+
+fn digest_mode_name(mode: TelegramDigestMode) -> &'static str {
+    match mode {
+        TelegramDigestMode::Immediate => "sent as they happen",
+        TelegramDigestMode::Hourly => "collected into an hourly digest",
+        TelegramDigestMode::Daily => "collected into a daily digest",
+    }
+}
+
+fn format_assignees(assignees: &[TaskAssignee]) -> String {
+    let labels = |role: TaskAssigneeRole| {
+        assignees
+            .iter()
+            .filter(|a| a.role == role)
+            .map(|a| escape_html(&account_label(a.username.as_deref(), a.telegram_user_id)))
+            .collect::<Vec<_>>()
+    };
+    let mut text = String::new();
+    for (title, role) in [
+        ("Assignees", TaskAssigneeRole::Assignee),
+        ("Watchers", TaskAssigneeRole::Watcher),
+    ] {
+        let labels = labels(role);
+        if !labels.is_empty() {
+            text.push_str(&format!("\n{title}: {}", labels.join(", ")));
+        }
+    }
+    if !text.is_empty() {
+        text.insert(0, '\n');
+    }
+    text
+}
+
+/// Split `/newtask` arguments into the first line (title) and the remaining
+/// lines (description)
+fn split_title_description(text: &str) -> (&str, Option<&str>) {
+    let (title, description) = text.split_once('\n').unwrap_or((text, ""));
+    let description = description.trim();
+    (
+        title.trim(),
+        (!description.is_empty()).then_some(description),
+    )
+}
+
+fn parse_mute_duration(input: &str) -> Option<chrono::Duration> {
+    let input: String = input
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_lowercase();
+    let in_range = |d: &chrono::Duration| *d > chrono::Duration::zero() && *d <= MAX_MUTE_DURATION;
+    if let Ok(minutes) = input.parse::<i64>() {
+        return chrono::Duration::try_minutes(minutes).filter(in_range);
+    }
+
+    let mut total = chrono::Duration::zero();
+    let mut number = String::new();
+    for c in input.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let value: i64 = number.parse().ok()?;
+        number.clear();
+        let part = match c {
+            's' => chrono::Duration::try_seconds(value)?,
+            'm' => chrono::Duration::try_minutes(value)?,
+            'h' => chrono::Duration::try_hours(value)?,
+            'd' => chrono::Duration::try_days(value)?,
+            _ => return None,
+        };
+        total = total.checked_add(&part)?;
+    }
+
+    Some(total).filter(|d| number.is_empty() && in_range(d))
+}
+
+/// Expand a configured command alias, one level deep. Arguments in the
+/// alias come before the ones typed after it.
+fn resolve_alias(
+    aliases: &HashMap<String, String>,
+    command: &str,
+    args: &str,
+) -> Option<(String, String)> {
+    let target = aliases.get(command)?.trim().trim_start_matches('/');
+    let (target, alias_args) = target
+        .split_once(char::is_whitespace)
+        .unwrap_or((target, ""));
+    if target.is_empty() {
+        return None;
+    }
+    let args = [alias_args.trim(), args]
+        .into_iter()
+        .filter(|a| !a.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    Some((target.to_string(), args))
+}
+
+/// Shortcut names follow Telegram's command format, so clients can complete them
+fn is_valid_shortcut_name(name: &str) -> bool {
+    (1..=32).contains(&name.len())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Reply for errors caused by the user's input rather than the server
+fn user_error_reply(error: &TelegramError, locale: Locale) -> Option<String> {
+    let message = match error {
+        TelegramError::NotLinked => BotMessage::NotLinked,
+        TelegramError::ReadOnly(_) => BotMessage::ReadOnly,
+        TelegramError::NoActiveProject => BotMessage::NoActiveProject,
+        TelegramError::ProjectNotFound(id) => BotMessage::ProjectNotFound(*id),
+        TelegramError::TaskNotFound(id) => BotMessage::TaskNotFound(*id),
+        TelegramError::InvalidCommand(details) => {
+            return Some(BotMessage::InvalidCommand(&escape_html(details)).render(locale));
+        }
+        TelegramError::Maintenance => return Some(ServerMessage::MaintenanceMode.render(locale)),
+        _ => return None,
+    };
+    Some(message.render(locale))
+}
+
+/// Parse a UUID from a string, supporting short prefixes
+fn parse_uuid(s: &str) -> Result<Uuid, TelegramError> {
+    let s = s.trim();
+
+    // Try full UUID first
+    if let Ok(id) = Uuid::parse_str(s) {
+        return Ok(id);
+    }
+
+    // Invalid format
+    Err(TelegramError::InvalidCommand(format!(
+        "Invalid ID format: {}. Expected a UUID.",
+        s
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use executors::{executors::BaseCodingAgent, profile::ExecutorProfileId};
+
+    use super::*;
+
+    // ========================================================================
+    // Retry Tests
+    // ========================================================================
+
+    #[test]
+    fn test_token_bucket_allows_burst_then_spaces_sends() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(3.0, 1.0, start);
+
+        for _ in 0..3 {
+            assert_eq!(bucket.reserve(start), Duration::ZERO);
+        }
+        assert_eq!(bucket.reserve(start), Duration::from_secs(1));
+        assert_eq!(bucket.reserve(start), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_token_bucket_refills_up_to_capacity() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(2.0, 1.0, start);
+        bucket.reserve(start);
+        bucket.reserve(start);
+
+        let later = start + Duration::from_secs(10);
+        assert_eq!(bucket.reserve(later), Duration::ZERO);
+        assert_eq!(bucket.reserve(later), Duration::ZERO);
+        assert_eq!(bucket.reserve(later), Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_send_rate_limiter_is_shared_between_clones() {
+        let limiter = SendRateLimiter::new();
+        let clone = limiter.clone();
+
+        for _ in 0..CHAT_SEND_BURST as usize {
+            clone.acquire(1).await;
+        }
+
+        let wait = limiter
+            .per_chat
+            .get_mut(&1)
+            .unwrap()
+            .reserve(Instant::now());
+        assert!(wait > Duration::ZERO);
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off_exponentially() {
+        let policy = TelegramRetryConfig {
+            max_attempts: 4,
+            initial_backoff_ms: 100,
+            max_backoff_ms: 250,
+        };
+
+        assert_eq!(retry_delay(&policy, 1, None), Some(Duration::from_millis(100)));
+        assert_eq!(
+            retry_delay(&policy, 2, Some((502, None))),
+            Some(Duration::from_millis(200))
+        );
+        assert_eq!(retry_delay(&policy, 3, None), Some(Duration::from_millis(250)));
+        assert_eq!(retry_delay(&policy, 4, None), None);
+    }
+
+    #[test]
+    fn test_retry_delay_respects_retry_after() {
+        let policy = TelegramRetryConfig::default();
+
+        assert_eq!(
+            retry_delay(&policy, 1, Some((429, Some(7)))),
+            Some(Duration::from_secs(7))
+        );
+        assert_eq!(
+            retry_delay(&policy, 1, Some((429, Some(3600)))),
+            Some(Duration::from_millis(policy.max_backoff_ms))
+        );
+    }
+
+    #[test]
+    fn test_retry_delay_gives_up_on_client_errors() {
+        let policy = TelegramRetryConfig::default();
+
+        assert_eq!(retry_delay(&policy, 1, Some((400, None))), None);
+        assert_eq!(retry_delay(&policy, 1, Some((403, None))), None);
+        assert!(retry_delay(&policy, 1, Some((429, None))).is_some());
+    }
+
+    // ========================================================================
+    // HTML Escaping Tests
+    // ========================================================================
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(escape_html("<script>"), "&lt;script&gt;");
+        assert_eq!(escape_html("a & b"), "a &amp; b");
+        assert_eq!(escape_html("normal text"), "normal text");
+    }
+
+    #[test]
+    fn test_escape_html_all_special_chars() {
+        assert_eq!(escape_html("<"), "&lt;");
+        assert_eq!(escape_html(">"), "&gt;");
+        assert_eq!(escape_html("&"), "&amp;");
+        assert_eq!(escape_html("<>&"), "&lt;&gt;&amp;");
+    }
+
+    #[test]
+    fn test_escape_html_multiple_occurrences() {
+        assert_eq!(escape_html("<<>>"), "&lt;&lt;&gt;&gt;");
+        assert_eq!(escape_html("a && b && c"), "a &amp;&amp; b &amp;&amp; c");
+    }
+
+    #[test]
+    fn test_escape_html_mixed_content() {
+        assert_eq!(
+            escape_html("<b>Hello</b> & <i>World</i>"),
+            "&lt;b&gt;Hello&lt;/b&gt; &amp; &lt;i&gt;World&lt;/i&gt;"
+        );
+    }
+
+    #[test]
+    fn test_escape_html_empty_string() {
+        assert_eq!(escape_html(""), "");
+    }
+
+    #[test]
+    fn test_escape_html_preserves_other_chars() {
+        assert_eq!(escape_html("Hello, World!"), "Hello, World!");
+        assert_eq!(escape_html("123 + 456 = 579"), "123 + 456 = 579");
+        assert_eq!(escape_html("emoji: 🎉"), "emoji: 🎉");
+    }
+
+    // ========================================================================
+    // Link Token Expiry Tests
+    // ========================================================================
+
+    #[test]
+    fn test_link_token_expiry() {
+        let fresh_token = LinkToken {
+            token: "test".to_string(),
+            created_at: Utc::now(),
+        };
+        assert!(!fresh_token.is_expired());
+
+        let expired_token = LinkToken {
+            token: "test".to_string(),
+            created_at: Utc::now() - chrono::Duration::minutes(20),
+        };
+        assert!(expired_token.is_expired());
+    }
+
+    #[test]
+    fn test_link_token_exactly_at_expiry_boundary() {
+        // Token at exactly 15 minutes should not be expired yet
+        let token_at_boundary = LinkToken {
+            token: "test".to_string(),
+            created_at: Utc::now() - chrono::Duration::minutes(15),
+        };
+        // At exactly 15 minutes, now > expiry is false, so not expired
+        assert!(!token_at_boundary.is_expired());
+
+        // Token at 15 minutes + 1 second should be expired
+        let token_past_boundary = LinkToken {
+            token: "test".to_string(),
+            created_at: Utc::now() - chrono::Duration::minutes(15) - chrono::Duration::seconds(1),
+        };
+        assert!(token_past_boundary.is_expired());
+    }
+
+    #[test]
+    fn test_link_token_just_created() {
+        let token = LinkToken {
+            token: "fresh".to_string(),
+            created_at: Utc::now(),
+        };
+        assert!(!token.is_expired());
+    }
+
+    #[test]
+    fn test_link_token_14_minutes_old() {
+        let token = LinkToken {
+            token: "test".to_string(),
+            created_at: Utc::now() - chrono::Duration::minutes(14),
+        };
+        assert!(!token.is_expired());
+    }
+
+    // ========================================================================
+    // UUID Parsing Tests
+    // ========================================================================
+
+    #[test]
+    fn test_parse_uuid_valid() {
+        let uuid_str = "550e8400-e29b-41d4-a716-446655440000";
+        let result = parse_uuid(uuid_str);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().to_string(), uuid_str);
+    }
+
+    #[test]
+    fn test_parse_uuid_with_whitespace() {
+        let uuid_str = "  550e8400-e29b-41d4-a716-446655440000  ";
+        let result = parse_uuid(uuid_str);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_parse_uuid_invalid() {
+        let invalid = "not-a-uuid";
+        let result = parse_uuid(invalid);
+        assert!(result.is_err());
+        match result {
+            Err(TelegramError::InvalidCommand(msg)) => {
+                assert!(msg.contains("Invalid ID format"));
+            }
+            _ => panic!("Expected InvalidCommand error"),
+        }
+    }
+
+    #[test]
+    fn test_parse_uuid_empty() {
+        let result = parse_uuid("");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_uuid_partial() {
+        let result = parse_uuid("550e8400-e29b");
+        assert!(result.is_err());
+    }
+
+    // ========================================================================
+    // TelegramError Display Tests
+    // ========================================================================
+
+    #[test]
+    fn test_telegram_error_display() {
+        assert_eq!(
+            TelegramError::NotConfigured.to_string(),
+            "Bot token not configured"
+        );
+        assert_eq!(
+            TelegramError::NotLinked.to_string(),
+            "Account not linked"
+        );
+        assert_eq!(
+            TelegramError::InvalidLinkToken.to_string(),
+            "Invalid link token"
+        );
+        assert_eq!(
+            TelegramError::LinkTokenExpired.to_string(),
+            "Link token expired"
+        );
+        assert_eq!(
+            TelegramError::NoActiveProject.to_string(),
+            "No active project set. Use /project <id> to set one."
+        );
+    }
+
+    #[test]
+    fn test_telegram_error_project_not_found() {
+        let id = Uuid::new_v4();
+        let error = TelegramError::ProjectNotFound(id);
+        assert!(error.to_string().contains(&id.to_string()));
+    }
+
+    #[test]
+    fn test_telegram_error_task_not_found() {
+        let id = Uuid::new_v4();
+        let error = TelegramError::TaskNotFound(id);
+        assert!(error.to_string().contains(&id.to_string()));
+    }
+
+    #[test]
+    fn test_telegram_error_invalid_command() {
+        let error = TelegramError::InvalidCommand("test error".to_string());
+        assert_eq!(error.to_string(), "Invalid command: test error");
+    }
+
+    #[test]
+    fn test_telegram_error_attachment() {
+        let error = TelegramError::Attachment("disk full".to_string());
+        assert_eq!(error.to_string(), "Failed to store attachment: disk full");
+    }
+
+    #[test]
+    fn test_telegram_error_queue_message() {
+        let error = TelegramError::QueueMessage("no session".to_string());
+        assert_eq!(error.to_string(), "Failed to queue message: no session");
+    }
+
+    #[test]
+    fn test_telegram_error_api() {
+        let error = TelegramError::Api("connection failed".to_string());
+        assert_eq!(error.to_string(), "Telegram API error: connection failed");
+    }
+
+    // ========================================================================
+    // UpdateResult Tests
+    // ========================================================================
+
+    #[test]
+    fn test_update_result_response() {
+        let result = UpdateResult::Response("Hello".to_string());
+        match result {
+            UpdateResult::Response(msg) => assert_eq!(msg, "Hello"),
+            _ => panic!("Expected Response variant"),
+        }
+    }
+
+    #[test]
+    fn test_update_result_no_response() {
+        let result = UpdateResult::NoResponse;
+        match result {
+            UpdateResult::NoResponse => {}
+            _ => panic!("Expected NoResponse variant"),
+        }
+    }
+
+    #[test]
+    fn test_update_result_link_completed() {
+        let result = UpdateResult::LinkCompleted {
+            chat_id: 12345,
+            user_id: 67890,
+            username: Some("testuser".to_string()),
+            locale: Locale::En,
+        };
+        match result {
+            UpdateResult::LinkCompleted {
+                chat_id,
+                user_id,
+                username,
+                locale,
+            } => {
+                assert_eq!(chat_id, 12345);
+                assert_eq!(user_id, 67890);
+                assert_eq!(username, Some("testuser".to_string()));
+                assert_eq!(locale, Locale::En);
+            }
+            _ => panic!("Expected LinkCompleted variant"),
+        }
+    }
+
+    #[test]
+    fn test_update_result_link_completed_no_username() {
+        let result = UpdateResult::LinkCompleted {
+            chat_id: 12345,
+            user_id: 67890,
+            username: None,
+            locale: Locale::En,
+        };
+        match result {
+            UpdateResult::LinkCompleted { username, .. } => {
+                assert!(username.is_none());
+            }
+            _ => panic!("Expected LinkCompleted variant"),
         }
+    }
 
-        let task_id = parse_uuid(parts[0])?;
-        let message_text = parts[1];
+    // ========================================================================
+    // TelegramConfig Default Tests
+    // ========================================================================
 
-        // Verify task exists
-        let task = Task::find_by_id(&self.pool, task_id)
-            .await?
-            .ok_or(TelegramError::TaskNotFound(task_id))?;
+    #[test]
+    fn test_telegram_config_default() {
+        let config = TelegramConfig::default();
+        assert!(config.chat_id.is_none());
+        assert!(config.user_id.is_none());
+        assert!(config.username.is_none());
+        assert!(!config.notifications_enabled);
+        assert!(!config.include_llm_summary);
+        assert!(!config.stream_enabled);
+    }
 
-        // For now, just acknowledge the message
-        // The actual message queuing will be implemented when integrating with QueuedMessageService
-        Ok(UpdateResult::Response(format!(
-            "📨 Message queued for task <b>{}</b>:\n\n{}",
-            escape_html(&task.title),
-            escape_html(message_text)
-        )))
+    // ========================================================================
+    // Task Notification Kind Tests
+    // ========================================================================
+
+    #[test]
+    fn test_notification_kind_from_status() {
+        assert_eq!(
+            TaskNotificationKind::from_status(&TaskStatus::InProgress),
+            Some(TaskNotificationKind::Started)
+        );
+        assert_eq!(
+            TaskNotificationKind::from_status(&TaskStatus::InReview),
+            Some(TaskNotificationKind::InReview)
+        );
+        assert_eq!(
+            TaskNotificationKind::from_status(&TaskStatus::Done),
+            Some(TaskNotificationKind::Done)
+        );
+        assert_eq!(TaskNotificationKind::from_status(&TaskStatus::Todo), None);
+        assert_eq!(
+            TaskNotificationKind::from_status(&TaskStatus::Cancelled),
+            Some(TaskNotificationKind::Cancelled)
+        );
     }
-}
 
-// ============================================================================
-// Helper Functions
-// ============================================================================
+    #[test]
+    fn test_notification_kind_silent() {
+        let config = TelegramConfig {
+            silent_kinds: vec![TaskNotificationKind::Started, TaskNotificationKind::InReview],
+            ..Default::default()
+        };
+        assert!(TaskNotificationKind::Started.is_silent(&config));
+        assert!(!TaskNotificationKind::Done.is_silent(&config));
 
-/// Escape HTML special characters for Telegram HTML parse mode
-fn escape_html(text: &str) -> String {
-    text.replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-}
+        let config = TelegramConfig {
+            silent: true,
+            ..Default::default()
+        };
+        assert!(TaskNotificationKind::Done.is_silent(&config));
+    }
 
-/// Format a NormalizedEntry for Telegram display
-fn format_entry(entry: &NormalizedEntry) -> Option<String> {
-    match &entry.entry_type {
-        NormalizedEntryType::AssistantMessage => {
-            let content = entry.content.trim();
-            if content.is_empty() {
-                None
-            } else {
-                Some(format!("💬 {}", content))
-            }
-        }
-        NormalizedEntryType::Thinking => {
-            let content = entry.content.trim();
-            if content.is_empty() {
-                None
-            } else {
-                // Truncate long thinking content
-                let truncated = if content.len() > 200 {
-                    format!("{}...", &content[..200])
-                } else {
-                    content.to_string()
-                };
-                Some(format!("💭 {}", truncated))
-            }
-        }
-        NormalizedEntryType::ToolUse {
-            tool_name,
-            action_type,
-            ..
-        } => {
-            let icon = match action_type {
-                ActionType::FileRead { path } => format!("📖 Reading: {}", path),
-                ActionType::FileEdit { path, .. } => format!("📝 Editing: {}", path),
-                ActionType::CommandRun { command, .. } => format!("⚡ Running: {}", command),
-                ActionType::Search { query } => format!("🔍 Searching: {}", query),
-                ActionType::WebFetch { url } => format!("🌐 Fetching: {}", url),
-                _ => format!("🔧 {}", tool_name),
-            };
-            Some(icon)
-        }
-        NormalizedEntryType::ErrorMessage { .. } => {
-            Some(format!("❌ {}", entry.content.trim()))
-        }
-        // Skip user messages, system messages, loading states
-        _ => None,
+    #[test]
+    fn test_format_health() {
+        let snapshot = HealthSnapshot {
+            started_at: Utc::now(),
+            uptime_secs: 3 * 3600 + 20 * 60,
+            running_attempts: 2,
+            queued_follow_ups: 1,
+            executor: BaseCodingAgent::ClaudeCode,
+            executor_available: false,
+            provider_cooldowns: vec![],
+        };
+        let message = format_health(&snapshot);
+        assert!(message.contains("Uptime: 3h 20m"));
+        assert!(message.contains("Running attempts: 2"));
+        assert!(message.contains("Queued follow-ups: 1"));
+        assert!(message.contains("CLAUDE_CODE ❌ not found"));
+        assert!(!message.contains("rate limited"));
     }
-}
 
-/// Format a stream message for Telegram
-fn format_stream_message(task_name: &str, content: &str) -> String {
-    // No truncation needed - multi-message support handles overflow
-    format!(
-        "🚀 <b>{}</b>\n\n<pre>{}</pre>",
-        escape_html(task_name),
-        escape_html(content)
-    )
-}
+    #[test]
+    fn test_format_whoami() {
+        let mut report = WhoamiReport {
+            chat_id: 42,
+            user_id: 7,
+            role: None,
+            active_project: None,
+            database_reachable: true,
+            telegram_api: Err("timed out".to_string()),
+        };
+        let now = Utc::now();
+        let mut preferences = NotificationPreferences::default();
+        let message = format_whoami(&report, &TelegramConfig::default(), &preferences, now);
+        assert!(message.contains("Chat ID: <code>42</code>\nUser ID: <code>7</code>"));
+        assert!(message.contains("Account: ❌ not linked"));
+        assert!(message.contains(&format!("Bot version: {APP_VERSION}")));
+        assert!(!message.contains("Notifications"));
+
+        report.role = Some(TelegramAccountRole::Admin);
+        let message = format_whoami(&report, &TelegramConfig::default(), &preferences, now);
+        assert!(message.contains("Account: ✅ linked (admin)"));
+        assert!(message.contains("Active project: none"));
+        assert!(message.contains("Enabled: off (sent to no chat)"));
+        assert!(message.contains("Events: done, failed, PR merged"));
+        assert!(message.contains("Digest: off"));
+        assert!(message.contains(&format!("Bot version: {APP_VERSION}")));
+        assert!(message.contains("Database: ✅ reachable"));
+        assert!(message.contains("Telegram API: ❌ timed out"));
+        assert!(!message.contains("Muted"));
+
+        report.role = Some(TelegramAccountRole::ReadOnly);
+        report.active_project = Some("A & B".to_string());
+        report.telegram_api = Ok(Duration::from_millis(120));
+        let config = TelegramConfig {
+            chat_id: Some(42),
+            notifications_enabled: true,
+            muted_until: Some(now + chrono::Duration::hours(1)),
+            digest_schedule: DigestSchedule::Weekdays,
+            digest_hour: 9,
+            ..Default::default()
+        };
+        preferences.set(
+            NotificationEventType::PrMerged,
+            NotificationChannel::Telegram,
+            false,
+        );
+        let message = format_whoami(&report, &config, &preferences, now);
+        assert!(message.contains("Account: ✅ linked (read-only)"));
+        assert!(message.contains("Active project: <b>A &amp; B</b>"));
+        assert!(message.contains("Enabled: on (sent to this chat)"));
+        assert!(message.contains("🔕 Muted until"));
+        assert!(message.contains("Events: done, failed"));
+        assert!(message.contains("Digest: weekdays at 09:00"));
+        assert!(message.contains("Telegram API: ✅ 120 ms"));
+    }
 
-/// Parse a UUID from a string, supporting short prefixes
-fn parse_uuid(s: &str) -> Result<Uuid, TelegramError> {
-    let s = s.trim();
+    #[test]
+    fn test_format_status_counts() {
+        let counts = TaskStatusCounts {
+            todo: 3,
+            in_progress: 1,
+            done: 5,
+            total: 9,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_status_counts(&counts),
+            "📋 3 to do · 1 in progress · 5 done"
+        );
+        assert_eq!(
+            format_status_counts(&TaskStatusCounts::default()),
+            "📋 No tasks yet"
+        );
+    }
 
-    // Try full UUID first
-    if let Ok(id) = Uuid::parse_str(s) {
-        return Ok(id);
+    #[test]
+    fn test_format_assignees_groups_roles() {
+        let assignee = |user_id, username: Option<&str>, role| TaskAssignee {
+            task_id: Uuid::nil(),
+            telegram_user_id: user_id,
+            chat_id: user_id,
+            username: username.map(str::to_string),
+            role,
+            created_at: Utc::now(),
+        };
+
+        assert_eq!(format_assignees(&[]), "");
+        assert_eq!(
+            format_assignees(&[
+                assignee(1, Some("alice"), TaskAssigneeRole::Assignee),
+                assignee(2, None, TaskAssigneeRole::Assignee),
+                assignee(3, Some("carol"), TaskAssigneeRole::Watcher),
+            ]),
+            "\n\nAssignees: @alice, 2\nWatchers: @carol"
+        );
     }
 
-    // Invalid format
-    Err(TelegramError::InvalidCommand(format!(
-        "Invalid ID format: {}. Expected a UUID.",
-        s
-    )))
-}
+    #[test]
+    fn test_topic_thread_id_only_for_forum_topics() {
+        let message = |thread_id: Option<i32>, is_topic: Option<bool>| {
+            serde_json::from_value::<Message>(serde_json::json!({
+                "message_id": 1,
+                "date": 0,
+                "chat": { "id": -100, "type": "supergroup" },
+                "message_thread_id": thread_id,
+                "is_topic_message": is_topic,
+            }))
+            .unwrap()
+        };
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        assert_eq!(topic_thread_id(&message(None, None)), None);
+        assert_eq!(topic_thread_id(&message(Some(7), Some(true))), Some(7));
+        // Replies in groups without topics carry a thread id too
+        assert_eq!(topic_thread_id(&message(Some(7), None)), None);
+    }
+
+    #[test]
+    fn test_split_title_description() {
+        assert_eq!(split_title_description("Fix login"), ("Fix login", None));
+        assert_eq!(
+            split_title_description("Fix login\n\nUsers are logged out\non refresh\n"),
+            ("Fix login", Some("Users are logged out\non refresh"))
+        );
+        assert_eq!(
+            split_title_description("Fix login\n   \n"),
+            ("Fix login", None)
+        );
+    }
+
+    #[test]
+    fn test_format_inline_task() {
+        let id = Uuid::nil();
+        let mut task = Task {
+            id,
+            project_id: Uuid::nil(),
+            title: "Fix <login>".to_string(),
+            description: Some(format!("  {}  ", "a".repeat(INLINE_DESCRIPTION_CHARS + 1))),
+            status: TaskStatus::InProgress,
+            parent_workspace_id: None,
+            position: 0,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        assert_eq!(
+            format_inline_task(&task, Some("Web & API")),
+            format!(
+                "🔄 <b>Fix &lt;login&gt;</b>\n📁 Web &amp; API\n<code>{id}</code>\n\n{}…",
+                "a".repeat(INLINE_DESCRIPTION_CHARS)
+            )
+        );
+
+        task.description = Some(" ".to_string());
+        assert_eq!(
+            format_inline_task(&task, None),
+            format!("🔄 <b>Fix &lt;login&gt;</b>\n<code>{id}</code>")
+        );
+    }
+
+    #[test]
+    fn test_ends_with_question() {
+        assert!(ends_with_question(
+            "Done with the parser.\n\nShould I also update the docs?"
+        ));
+        assert!(ends_with_question("**Which database should I use?**\n\n"));
+        assert!(ends_with_question("どちらを使いますか？"));
+        assert!(!ends_with_question("Is this right? I went ahead anyway."));
+        assert!(!ends_with_question("All tests pass."));
+        assert!(!ends_with_question(""));
+    }
+
+    #[test]
+    fn test_format_question() {
+        assert_eq!(
+            format_question("Fix <login>", " Use OAuth? "),
+            "❓ <b>Fix &lt;login&gt;</b> is waiting for your answer:\n\nUse OAuth?\n\n\
+             Reply to this message to answer."
+        );
+        let long = "a".repeat(QUESTION_MAX_LEN + 1);
+        assert!(
+            format_question("Task", &long).contains(&format!("{}…", "a".repeat(QUESTION_MAX_LEN)))
+        );
+    }
+
+    #[test]
+    fn test_menu_command() {
+        assert_eq!(menu_command("📋 Tasks"), Some("tasks"));
+        assert_eq!(menu_command("➕ New Task"), Some("newtask"));
+        assert_eq!(menu_command("📊 Report"), Some("report"));
+        assert_eq!(menu_command(" 🖥 Status "), Some("status"));
+        assert_eq!(menu_command("Tasks"), None);
+    }
+
+    #[test]
+    fn test_format_projects_page() {
+        let projects: Vec<ProjectActivity> = (0..25)
+            .map(|i| ProjectActivity {
+                id: Uuid::new_v4(),
+                name: format!("Project {i}"),
+                open_tasks: i % 3,
+                last_active_at: Utc::now(),
+            })
+            .collect();
+
+        let (text, buttons) = format_projects_page(&projects, 1);
+        assert!(text.starts_with("<b>Your Projects</b> (page 1 of 3)"));
+        assert!(text.contains("<b>Project 0</b> - no open tasks"));
+        assert!(text.contains("<b>Project 1</b> - 1 open task\n"));
+        assert!(!text.contains("Project 10"));
+        assert_eq!(buttons.len(), 1);
+        assert_eq!(buttons[0].data, format!("{PROJECTS_CALLBACK_PREFIX}2"));
+
+        let (text, buttons) = format_projects_page(&projects, 99);
+        assert!(text.contains("(page 3 of 3)"));
+        assert!(text.contains("Project 24"));
+        assert_eq!(buttons.len(), 1);
+        assert_eq!(buttons[0].data, format!("{PROJECTS_CALLBACK_PREFIX}2"));
+
+        let (text, buttons) = format_projects_page(&projects[..3], 1);
+        assert!(text.starts_with("<b>Your Projects:</b>"));
+        assert!(buttons.is_empty());
+    }
+
+    #[test]
+    fn test_parse_role() {
+        assert_eq!(parse_role("admin"), Some(TelegramAccountRole::Admin));
+        assert_eq!(parse_role("Read_Only"), Some(TelegramAccountRole::ReadOnly));
+        assert_eq!(parse_role("read-only"), Some(TelegramAccountRole::ReadOnly));
+        assert_eq!(parse_role("owner"), None);
+    }
+
+    #[test]
+    fn test_parse_digest_mode() {
+        assert_eq!(
+            parse_digest_mode("Hourly"),
+            Some(TelegramDigestMode::Hourly)
+        );
+        assert_eq!(parse_digest_mode("daily"), Some(TelegramDigestMode::Daily));
+        assert_eq!(
+            parse_digest_mode("off"),
+            Some(TelegramDigestMode::Immediate)
+        );
+        assert_eq!(parse_digest_mode("weekly"), None);
+    }
+
+    #[test]
+    fn test_resolve_alias() {
+        let aliases = HashMap::from([
+            ("t".to_string(), "tasks".to_string()),
+            ("bug".to_string(), "/newtask #bug".to_string()),
+            ("blank".to_string(), " ".to_string()),
+        ]);
+
+        assert_eq!(
+            resolve_alias(&aliases, "t", ""),
+            Some(("tasks".to_string(), String::new()))
+        );
+        assert_eq!(
+            resolve_alias(&aliases, "bug", "Login fails"),
+            Some(("newtask".to_string(), "#bug Login fails".to_string()))
+        );
+        assert_eq!(resolve_alias(&aliases, "blank", "x"), None);
+        assert_eq!(resolve_alias(&aliases, "tasks", ""), None);
+    }
+
+    #[test]
+    fn test_is_valid_shortcut_name() {
+        assert!(is_valid_shortcut_name("p1"));
+        assert!(is_valid_shortcut_name("home_server"));
+        assert!(!is_valid_shortcut_name(""));
+        assert!(!is_valid_shortcut_name("p-1"));
+        assert!(!is_valid_shortcut_name("P1"));
+        assert!(!is_valid_shortcut_name(&"p".repeat(33)));
+    }
+
+    #[test]
+    fn test_parse_mute_duration() {
+        assert_eq!(
+            parse_mute_duration("30m"),
+            Some(chrono::Duration::minutes(30))
+        );
+        assert_eq!(parse_mute_duration("2H"), Some(chrono::Duration::hours(2)));
+        assert_eq!(
+            parse_mute_duration("1h 30m"),
+            Some(chrono::Duration::minutes(90))
+        );
+        assert_eq!(
+            parse_mute_duration("45"),
+            Some(chrono::Duration::minutes(45))
+        );
+        assert_eq!(parse_mute_duration("30d"), Some(MAX_MUTE_DURATION));
+        assert_eq!(parse_mute_duration("31d"), None);
+        assert_eq!(parse_mute_duration("0m"), None);
+        assert_eq!(parse_mute_duration("-5"), None);
+        assert_eq!(parse_mute_duration("1.5h"), None);
+        assert_eq!(parse_mute_duration("2"), Some(chrono::Duration::minutes(2)));
+        assert_eq!(parse_mute_duration("h"), None);
+        assert_eq!(parse_mute_duration("2w"), None);
+        assert_eq!(parse_mute_duration("99999999999999999999m"), None);
+    }
+
+    #[test]
+    fn test_mute_expires() {
+        let now = Utc::now();
+        let config = TelegramConfig {
+            muted_until: Some(now + chrono::Duration::minutes(5)),
+            ..Default::default()
+        };
+        assert!(config.is_muted(now));
+        assert!(!config.is_muted(now + chrono::Duration::minutes(6)));
+        assert!(!TelegramConfig::default().is_muted(now));
+    }
 
     // ========================================================================
-    // HTML Escaping Tests
+    // Stream Message Formatting Tests
     // ========================================================================
 
     #[test]
-    fn test_escape_html() {
-        assert_eq!(escape_html("<script>"), "&lt;script&gt;");
-        assert_eq!(escape_html("a & b"), "a &amp; b");
-        assert_eq!(escape_html("normal text"), "normal text");
+    fn test_format_stream_message_basic() {
+        let result = format_stream_message("Test Task", "Hello world");
+        assert!(result.contains("Test Task"));
+        assert!(result.contains("Hello world"));
+    }
+
+    #[test]
+    fn test_format_stream_message_escapes_html() {
+        let result = format_stream_message("<script>", "a < b && c > d");
+        assert!(result.contains("&lt;script&gt;"));
+        assert!(result.contains("a &lt; b &amp;&amp; c &gt; d"));
+    }
+
+    #[test]
+    fn test_format_stream_message_truncates_long_content() {
+        let long_content = "x".repeat(5000);
+        let result = format_stream_message("Task", &long_content);
+        // Result should be under 4096 chars
+        assert!(result.len() < 4096);
+        // Should have truncation indicator
+        assert!(result.contains("..."));
     }
 
     #[test]
-    fn test_escape_html_all_special_chars() {
-        assert_eq!(escape_html("<"), "&lt;");
-        assert_eq!(escape_html(">"), "&gt;");
-        assert_eq!(escape_html("&"), "&amp;");
-        assert_eq!(escape_html("<>&"), "&lt;&gt;&amp;");
+    fn test_format_stream_message_keeps_latest_output() {
+        let content = format!("{}\nlatest line", "old ".repeat(2000));
+        let result = format_stream_message("Task", &content);
+        assert!(result.contains("latest line"));
     }
 
     #[test]
-    fn test_escape_html_multiple_occurrences() {
-        assert_eq!(escape_html("<<>>"), "&lt;&lt;&gt;&gt;");
-        assert_eq!(escape_html("a && b && c"), "a &amp;&amp; b &amp;&amp; c");
+    fn test_escape_markdown_v2() {
+        assert_eq!(escape_markdown_v2("v1.2 (beta)!"), r"v1\.2 \(beta\)\!");
+        assert_eq!(escape_markdown_v2(r"a_b*c\d"), r"a\_b\*c\\d");
+        assert_eq!(escape_markdown_v2("plain text"), "plain text");
     }
 
     #[test]
-    fn test_escape_html_mixed_content() {
+    fn test_html_to_markdown_v2() {
+        let html = "✅ <b>Fix &lt;Login&gt;</b>\n<i>done.</i> <code>a_b`c</code>";
+
         assert_eq!(
-            escape_html("<b>Hello</b> & <i>World</i>"),
-            "&lt;b&gt;Hello&lt;/b&gt; &amp; &lt;i&gt;World&lt;/i&gt;"
+            html_to_markdown_v2(html),
+            "✅ *Fix <Login\\>*\n_done\\._ `a_b\\`c`"
         );
     }
 
     #[test]
-    fn test_escape_html_empty_string() {
-        assert_eq!(escape_html(""), "");
+    fn test_html_to_plain_text() {
+        let html = "<b>Tom &amp; Jerry</b>\n<pre>x &lt; y</pre>";
+
+        assert_eq!(html_to_plain_text(html), "Tom & Jerry\nx < y");
     }
 
     #[test]
-    fn test_escape_html_preserves_other_chars() {
-        assert_eq!(escape_html("Hello, World!"), "Hello, World!");
-        assert_eq!(escape_html("123 + 456 = 579"), "123 + 456 = 579");
-        assert_eq!(escape_html("emoji: 🎉"), "emoji: 🎉");
+    fn test_split_html_message_short_is_unchanged() {
+        assert_eq!(split_html_message("<b>hi</b>", 4096), vec!["<b>hi</b>"]);
     }
 
-    // ========================================================================
-    // Link Token Expiry Tests
-    // ========================================================================
+    #[test]
+    fn test_split_html_message_prefers_paragraphs() {
+        let first = "a".repeat(150);
+        let second = "b".repeat(150);
+        let html = format!("{first}\n\n{second}");
+
+        let chunks = split_html_message(&html, 300);
+
+        assert_eq!(chunks, vec![format!("{first}\n\n"), second]);
+    }
 
     #[test]
-    fn test_link_token_expiry() {
-        let fresh_token = LinkToken {
-            token: "test".to_string(),
-            created_at: Utc::now(),
-        };
-        assert!(!fresh_token.is_expired());
+    fn test_split_html_message_keeps_tags_balanced() {
+        let html = format!("<pre>{}</pre>", "x\n".repeat(300));
 
-        let expired_token = LinkToken {
-            token: "test".to_string(),
-            created_at: Utc::now() - chrono::Duration::minutes(20),
-        };
-        assert!(expired_token.is_expired());
+        let chunks = split_html_message(&html, 300);
+
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            assert!(telegram_len(chunk) <= 300);
+            assert!(chunk.starts_with("<pre>"));
+            assert!(chunk.ends_with("</pre>"));
+        }
+        let text: String = chunks
+            .iter()
+            .map(|chunk| html_to_plain_text(chunk))
+            .collect();
+        assert_eq!(text, "x\n".repeat(300));
     }
 
     #[test]
-    fn test_link_token_exactly_at_expiry_boundary() {
-        // Token at exactly 15 minutes should not be expired yet
-        let token_at_boundary = LinkToken {
-            token: "test".to_string(),
-            created_at: Utc::now() - chrono::Duration::minutes(15),
-        };
-        // At exactly 15 minutes, now > expiry is false, so not expired
-        assert!(!token_at_boundary.is_expired());
+    fn test_split_html_message_never_cuts_entities() {
+        let html = "&amp;".repeat(200);
 
-        // Token at 15 minutes + 1 second should be expired
-        let token_past_boundary = LinkToken {
-            token: "test".to_string(),
-            created_at: Utc::now() - chrono::Duration::minutes(15) - chrono::Duration::seconds(1),
-        };
-        assert!(token_past_boundary.is_expired());
+        let chunks = split_html_message(&html, 300);
+
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            assert!(telegram_len(chunk) <= 300);
+            assert_eq!(chunk.replace("&amp;", ""), "");
+        }
     }
 
     #[test]
-    fn test_link_token_just_created() {
-        let token = LinkToken {
-            token: "fresh".to_string(),
-            created_at: Utc::now(),
-        };
-        assert!(!token.is_expired());
+    fn test_bot_client_id() {
+        assert_eq!(BotClient::new("123456:ABC-DEF".to_string()).id(), "123456");
     }
 
     #[test]
-    fn test_link_token_14_minutes_old() {
-        let token = LinkToken {
-            token: "test".to_string(),
-            created_at: Utc::now() - chrono::Duration::minutes(14),
-        };
-        assert!(!token.is_expired());
+    fn test_webhook_url() {
+        let project_id = Uuid::nil();
+
+        assert_eq!(
+            webhook_url("https://kanban.example.com/", None),
+            "https://kanban.example.com/api/telegram/webhook"
+        );
+        assert_eq!(
+            webhook_url("https://kanban.example.com", Some(project_id)),
+            format!("https://kanban.example.com/api/telegram/webhook/{project_id}")
+        );
     }
 
-    // ========================================================================
-    // UUID Parsing Tests
-    // ========================================================================
+    #[tokio::test]
+    async fn test_rotated_token_must_belong_to_same_bot() {
+        let current = BotClient::new("111:old-secret".to_string());
+        let other = BotClient::new("222:new-secret".to_string());
+
+        match verify_bot(&other, Some(&current)).await {
+            Err(TelegramError::WrongBot { expected, actual }) => {
+                assert_eq!(expected, "111");
+                assert_eq!(actual, "222");
+            }
+            result => panic!("expected WrongBot, got {result:?}"),
+        }
+    }
 
     #[test]
-    fn test_parse_uuid_valid() {
-        let uuid_str = "550e8400-e29b-41d4-a716-446655440000";
-        let result = parse_uuid(uuid_str);
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap().to_string(), uuid_str);
+    fn test_command_access_by_role() {
+        let may_run = |role, command: &str| match command_access(command) {
+            CommandAccess::Write => check_write(role, false, command).is_ok(),
+            CommandAccess::Public | CommandAccess::Read => true,
+        };
+
+        assert!(!may_run(TelegramAccountRole::ReadOnly, "merge"));
+        assert!(may_run(TelegramAccountRole::Admin, "merge"));
+        assert!(!may_run(TelegramAccountRole::ReadOnly, "role"));
+        assert!(may_run(TelegramAccountRole::Admin, "role"));
+        assert!(may_run(TelegramAccountRole::ReadOnly, "tasks"));
+        assert_eq!(command_access("tasks"), CommandAccess::Read);
+        assert!(matches!(
+            check_write(TelegramAccountRole::ReadOnly, false, "use /merge"),
+            Err(TelegramError::ReadOnly(action)) if action == "use /merge"
+        ));
+        assert!(matches!(
+            check_write(TelegramAccountRole::Admin, true, "use /merge"),
+            Err(TelegramError::Maintenance)
+        ));
     }
 
     #[test]
-    fn test_parse_uuid_with_whitespace() {
-        let uuid_str = "  550e8400-e29b-41d4-a716-446655440000  ";
-        let result = parse_uuid(uuid_str);
-        assert!(result.is_ok());
+    fn test_only_linking_and_help_are_open_to_strangers() {
+        for command in ["start", "help", "whoami"] {
+            assert_eq!(command_access(command), CommandAccess::Public);
+        }
+        for command in ["tasks", "task", "status", "projects", "merge"] {
+            assert_ne!(command_access(command), CommandAccess::Public);
+        }
     }
 
     #[test]
-    fn test_parse_uuid_invalid() {
-        let invalid = "not-a-uuid";
-        let result = parse_uuid(invalid);
-        assert!(result.is_err());
-        match result {
-            Err(TelegramError::InvalidCommand(msg)) => {
-                assert!(msg.contains("Invalid ID format"));
+    fn test_follow_up_starts_when_idle_and_queues_while_running() {
+        let queued_messages = QueuedMessageService::new();
+        let session_id = Uuid::new_v4();
+        let follow_up = |message: &str| DraftFollowUpData {
+            message: message.to_string(),
+            executor_profile_id: ExecutorProfileId::new(BaseCodingAgent::ClaudeCode),
+        };
+
+        match follow_up_result(&queued_messages, session_id, "A", follow_up("now"), false) {
+            UpdateResult::FollowUpRequested {
+                session_id: requested,
+                follow_up,
+                ..
+            } => {
+                assert_eq!(requested, session_id);
+                assert_eq!(follow_up.message, "now");
             }
-            _ => panic!("Expected InvalidCommand error"),
+            result => panic!("expected FollowUpRequested, got {result:?}"),
         }
+        assert!(!queued_messages.has_queued(session_id));
+
+        follow_up_result(&queued_messages, session_id, "A", follow_up("one"), true);
+        let result = follow_up_result(&queued_messages, session_id, "A", follow_up("two"), true);
+        assert!(matches!(result, UpdateResult::Response(_)));
+        assert_eq!(
+            queued_messages.get_queued(session_id).unwrap().data.message,
+            "one\n\ntwo"
+        );
     }
 
     #[test]
-    fn test_parse_uuid_empty() {
-        let result = parse_uuid("");
-        assert!(result.is_err());
+    fn test_format_notification_batch() {
+        let notification = |message: &str| BatchedNotification {
+            task_id: Uuid::new_v4(),
+            notification_id: None,
+            message: message.to_string(),
+            buttons: None,
+            silent: false,
+        };
+        let batch = [
+            notification("✅ <b>Task Completed</b>\n\n<b>A</b>"),
+            notification("❌ <b>Task Failed</b>\n\n<b>B</b>"),
+        ];
+
+        assert_eq!(
+            format_notification_batch(&batch, Locale::En),
+            "🔔 <b>2 notifications</b>\n\n✅ <b>Task Completed</b>\n\n<b>A</b>\n\n❌ <b>Task Failed</b>\n\n<b>B</b>"
+        );
     }
 
     #[test]
-    fn test_parse_uuid_partial() {
-        let result = parse_uuid("550e8400-e29b");
-        assert!(result.is_err());
-    }
+    fn test_build_setup_state() {
+        let status_of = |state: &TelegramSetupState, kind| {
+            state
+                .steps
+                .iter()
+                .find(|step| step.kind == kind)
+                .map(|step| step.status)
+                .unwrap()
+        };
 
-    // ========================================================================
-    // TelegramError Display Tests
-    // ========================================================================
+        let state = TelegramSetupState::without_bot(&TelegramConfig::default());
+        assert!(!state.complete);
+        assert_eq!(state.update_mode, None);
+        assert!(
+            state.steps.iter().all(
+                |step| step.status == TelegramSetupStepStatus::Missing && step.action.is_some()
+            )
+        );
 
-    #[test]
-    fn test_telegram_error_display() {
+        let mut facts = SetupFacts {
+            token_configured: true,
+            bot: Some(Ok("vk_bot".to_string())),
+            configured_username: Some("@VK_Bot".to_string()),
+            expected_webhook_url: Some("https://vk.example/api/telegram/webhook".to_string()),
+            webhook: Some(Ok(WebhookStatus {
+                url: "https://vk.example/api/telegram/webhook".to_string(),
+                pending_update_count: 0,
+                last_error: Some("Connection timed out".to_string()),
+            })),
+            linked_chat_id: Some(42),
+        };
+        let state = build_setup_state(&facts);
+        assert!(state.complete);
+        assert_eq!(state.update_mode, Some(TelegramUpdateMode::Webhook));
+        assert!(state.steps.iter().all(|step| step.action.is_none()));
+
+        // A stale error only counts while updates are waiting
+        facts.webhook = Some(Ok(WebhookStatus {
+            url: "https://vk.example/api/telegram/webhook".to_string(),
+            pending_update_count: 3,
+            last_error: Some("Connection timed out".to_string()),
+        }));
+        facts.configured_username = Some("other_bot".to_string());
+        let state = build_setup_state(&facts);
         assert_eq!(
-            TelegramError::NotConfigured.to_string(),
-            "Bot token not configured"
+            status_of(&state, TelegramSetupStepKind::Webhook),
+            TelegramSetupStepStatus::Error
         );
         assert_eq!(
-            TelegramError::NotLinked.to_string(),
-            "Account not linked"
+            status_of(&state, TelegramSetupStepKind::Username),
+            TelegramSetupStepStatus::Error
         );
+
+        facts.expected_webhook_url = None;
+        facts.webhook = Some(Ok(WebhookStatus {
+            url: String::new(),
+            pending_update_count: 0,
+            last_error: None,
+        }));
+        facts.linked_chat_id = None;
+        let state = build_setup_state(&facts);
+        assert_eq!(state.update_mode, Some(TelegramUpdateMode::Polling));
         assert_eq!(
-            TelegramError::InvalidLinkToken.to_string(),
-            "Invalid link token"
+            status_of(&state, TelegramSetupStepKind::Webhook),
+            TelegramSetupStepStatus::Missing
         );
+        let message = format_setup_state(&state, Some("Send <code>/start abc</code>"));
+        assert!(message.contains("⬜ <b>Linked chat</b>\n   → Send <code>/start abc</code>"));
+        assert!(message.contains("Send /setup again"));
+
+        facts.bot = Some(Err("Unauthorized".to_string()));
+        facts.webhook = None;
+        facts.expected_webhook_url = Some("https://vk.example/api/telegram/webhook".to_string());
+        let state = build_setup_state(&facts);
         assert_eq!(
-            TelegramError::LinkTokenExpired.to_string(),
-            "Link token expired"
+            status_of(&state, TelegramSetupStepKind::Token),
+            TelegramSetupStepStatus::Error
         );
         assert_eq!(
-            TelegramError::NoActiveProject.to_string(),
-            "No active project set. Use /project <id> to set one."
+            status_of(&state, TelegramSetupStepKind::Webhook),
+            TelegramSetupStepStatus::Missing
         );
     }
 
     #[test]
-    fn test_telegram_error_project_not_found() {
-        let id = Uuid::new_v4();
-        let error = TelegramError::ProjectNotFound(id);
-        assert!(error.to_string().contains(&id.to_string()));
-    }
+    fn test_format_status_notification() {
+        let config = TelegramConfig {
+            include_llm_summary: false,
+            ..Default::default()
+        };
 
-    #[test]
-    fn test_telegram_error_task_not_found() {
-        let id = Uuid::new_v4();
-        let error = TelegramError::TaskNotFound(id);
-        assert!(error.to_string().contains(&id.to_string()));
+        let message = format_status_notification(
+            TaskNotificationKind::Failed,
+            "Fix <login>",
+            Some("Tried twice"),
+            Some("exit 1"),
+            &config,
+            Locale::En,
+        );
+        assert!(message.contains("<b>Fix &lt;login&gt;</b>"));
+        assert!(!message.contains("Tried twice"));
+        assert!(message.ends_with("<pre>exit 1</pre>"));
     }
 
     #[test]
-    fn test_telegram_error_invalid_command() {
-        let error = TelegramError::InvalidCommand("test error".to_string());
-        assert_eq!(error.to_string(), "Invalid command: test error");
+    fn test_render_message_modes() {
+        let html = "<b>hi</b>";
+
+        assert_eq!(
+            render_message(html, TelegramParseMode::Html),
+            ("<b>hi</b>".to_string(), Some(ParseMode::Html))
+        );
+        assert_eq!(
+            render_message(html, TelegramParseMode::MarkdownV2),
+            ("*hi*".to_string(), Some(ParseMode::MarkdownV2))
+        );
+        assert_eq!(
+            render_message(html, TelegramParseMode::PlainText),
+            ("hi".to_string(), None)
+        );
+        assert_eq!(
+            render_message("✅ <b>Task Completed</b>", TelegramParseMode::Accessible),
+            ("Task Completed".to_string(), None)
+        );
     }
 
     #[test]
-    fn test_telegram_error_api() {
-        let error = TelegramError::Api("connection failed".to_string());
-        assert_eq!(error.to_string(), "Telegram API error: connection failed");
+    fn test_tail_log_lines_splits_and_takes_last() {
+        let messages = vec![
+            LogMsg::Stdout("one\ntwo\n".to_string()),
+            LogMsg::Finished,
+            LogMsg::Stderr("three".to_string()),
+            LogMsg::Stdout("\n".to_string()),
+            LogMsg::Stdout("four".to_string()),
+        ];
+
+        assert_eq!(tail_log_lines(&messages, 2), vec!["three", "four"]);
+        assert_eq!(tail_log_lines(&messages, 10).len(), 4);
     }
 
-    // ========================================================================
-    // UpdateResult Tests
-    // ========================================================================
-
     #[test]
-    fn test_update_result_response() {
-        let result = UpdateResult::Response("Hello".to_string());
-        match result {
-            UpdateResult::Response(msg) => assert_eq!(msg, "Hello"),
-            _ => panic!("Expected Response variant"),
-        }
+    fn test_failure_excerpt_prefers_stderr() {
+        let mut messages = vec![
+            LogMsg::Stdout("building".to_string()),
+            LogMsg::Stderr("error: first\n".to_string()),
+        ];
+        messages.extend((0..20).map(|i| LogMsg::Stderr(format!("line {i}"))));
+
+        let excerpt = failure_excerpt(&messages).unwrap();
+        assert_eq!(excerpt.lines().count(), FAILURE_EXCERPT_LINES);
+        assert!(excerpt.ends_with("line 19"));
+        assert!(!excerpt.contains("building"));
+
+        let stdout_only = vec![LogMsg::Stdout("panicked at main.rs".to_string())];
+        assert_eq!(
+            failure_excerpt(&stdout_only).as_deref(),
+            Some("panicked at main.rs")
+        );
+        assert_eq!(failure_excerpt(&[LogMsg::Finished]), None);
     }
 
     #[test]
-    fn test_update_result_no_response() {
-        let result = UpdateResult::NoResponse;
-        match result {
-            UpdateResult::NoResponse => {}
-            _ => panic!("Expected NoResponse variant"),
+    fn test_chunk_log_lines_respects_max_len() {
+        let lines: Vec<String> = (0..200).map(|i| format!("line {i} <ok>")).collect();
+
+        let chunks = chunk_log_lines("<b>header</b>", &lines, 500);
+
+        assert!(chunks.len() > 1);
+        assert!(chunks[0].starts_with("<b>header</b>\n<pre>"));
+        for chunk in &chunks {
+            assert!(chunk.len() <= 500, "chunk too long: {}", chunk.len());
+            assert!(chunk.ends_with("</pre>"));
         }
+        assert!(chunks.last().unwrap().contains("line 199 &lt;ok&gt;"));
     }
 
     #[test]
-    fn test_update_result_link_completed() {
-        let result = UpdateResult::LinkCompleted {
-            chat_id: 12345,
-            user_id: 67890,
-            username: Some("testuser".to_string()),
-        };
-        match result {
-            UpdateResult::LinkCompleted {
-                chat_id,
-                user_id,
-                username,
-            } => {
-                assert_eq!(chat_id, 12345);
-                assert_eq!(user_id, 67890);
-                assert_eq!(username, Some("testuser".to_string()));
-            }
-            _ => panic!("Expected LinkCompleted variant"),
-        }
+    fn test_chunk_log_lines_truncates_long_line() {
+        let lines = vec!["x".repeat(1000)];
+
+        let chunks = chunk_log_lines("h", &lines, 100);
+
+        assert_eq!(chunks.len(), 1);
+        assert!(chunks[0].len() <= 100);
+        assert!(chunks[0].contains("..."));
     }
 
-    #[test]
-    fn test_update_result_link_completed_no_username() {
-        let result = UpdateResult::LinkCompleted {
-            chat_id: 12345,
-            user_id: 67890,
-            username: None,
-        };
-        match result {
-            UpdateResult::LinkCompleted { username, .. } => {
-                assert!(username.is_none());
-            }
-            _ => panic!("Expected LinkCompleted variant"),
+    fn modified(old: &str, new: &str) -> Diff {
+        Diff {
+            change: utils::diff::DiffChangeKind::Modified,
+            old_path: Some("src/main.rs".to_string()),
+            new_path: Some("src/main.rs".to_string()),
+            old_content: Some(old.to_string()),
+            new_content: Some(new.to_string()),
+            content_omitted: false,
+            additions: None,
+            deletions: None,
+            repo_id: None,
         }
     }
 
-    // ========================================================================
-    // TelegramConfig Default Tests
-    // ========================================================================
-
     #[test]
-    fn test_telegram_config_default() {
-        let config = TelegramConfig::default();
-        assert!(config.chat_id.is_none());
-        assert!(config.user_id.is_none());
-        assert!(config.username.is_none());
-        assert!(!config.notifications_enabled);
-        assert!(!config.notify_on_task_done);
-        assert!(!config.include_llm_summary);
-        assert!(!config.stream_enabled);
+    fn test_format_diff_stat_lists_files_within_limit() {
+        let files: Vec<(String, Diff)> = (0..50)
+            .map(|i| (format!("src/file_{i}.rs"), modified("a\n", "a\nb\n")))
+            .collect();
+
+        let full = format_diff_stat("Fix <bug>", &files[..2], 4096);
+        assert!(full.contains("Fix &lt;bug&gt;"));
+        assert!(full.contains("2 files changed, +2 -0"));
+        assert!(full.contains("<code>+1 -0</code> src/file_1.rs"));
+
+        let short = format_diff_stat("t", &files, 400);
+        assert!(short.len() <= 400);
+        assert!(short.contains("more files"));
     }
 
-    // ========================================================================
-    // Stream Message Formatting Tests
-    // ========================================================================
-
     #[test]
-    fn test_format_stream_message_basic() {
-        let result = format_stream_message("Test Task", "Hello world");
-        assert!(result.contains("Test Task"));
-        assert!(result.contains("Hello world"));
+    fn test_pre_block_head_keeps_leading_lines() {
+        let text = (0..100)
+            .map(|i| format!("+ line <{i}>"))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let block = pre_block_head(&text, 200);
+
+        assert!(block.len() <= 200);
+        assert!(block.starts_with("<pre>+ line &lt;0&gt;"));
+        assert!(block.ends_with("… (truncated)</pre>"));
+        assert!(pre_block_head(&"x".repeat(1000), 100).len() <= 100);
     }
 
     #[test]
-    fn test_format_stream_message_escapes_html() {
-        let result = format_stream_message("<script>", "a < b && c > d");
-        assert!(result.contains("&lt;script&gt;"));
-        assert!(result.contains("a &lt; b &amp;&amp; c &gt; d"));
+    fn test_callback_data_fits_telegram_limit() {
+        // Telegram rejects callback data longer than 64 bytes
+        let id = Uuid::new_v4();
+        for data in [
+            format!("{MERGE_CALLBACK_PREFIX}{id}"),
+            format!("{MERGE_CONFIRM_CALLBACK_PREFIX}{id}"),
+            format!("{RETRY_CALLBACK_PREFIX}{id}"),
+            format!("{ACK_CALLBACK_PREFIX}{id}"),
+            format!("{DIFF_CALLBACK_PREFIX}{id}:{MAX_DIFF_FILE_BUTTONS}"),
+            format!("{PROJECTS_CALLBACK_PREFIX}{}", usize::MAX),
+        ] {
+            assert!(data.len() <= 64, "{data} is too long");
+        }
     }
 
     #[test]
-    fn test_format_stream_message_truncates_long_content() {
-        let long_content = "x".repeat(5000);
-        let result = format_stream_message("Task", &long_content);
-        // Result should be under 4096 chars
-        assert!(result.len() < 4096);
-        // Should have truncation indicator
-        assert!(result.contains("..."));
+    fn test_escape_html_tail_does_not_split_entities() {
+        let result = escape_html_tail(&"<".repeat(100), 50);
+        assert!(result.len() <= 50);
+        assert!(result.starts_with("...&lt;"));
+        assert!(result.ends_with("&lt;"));
     }
 }
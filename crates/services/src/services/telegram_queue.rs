@@ -0,0 +1,148 @@
+//! Durable send queue for Telegram notifications.
+//!
+//! Notifications are written to the `telegram_outgoing_messages` table instead
+//! of being sent inline, and [`TelegramQueueWorker`] sends them, retrying with
+//! exponential backoff. Only the oldest unsent message of a chat is ever
+//! attempted, so a chat sees its notifications in the order they were queued
+//! even across restarts and Telegram outages.
+//!
+//! The worker also queues the hourly and daily digests of accounts that chose
+//! one, once they are due.
+
+use std::time::Duration;
+
+use db::{
+    DBService,
+    models::{
+        notification::Notification, notification_dead_letter::NotificationDeadLetter,
+        telegram_outgoing_message::TelegramOutgoingMessage,
+    },
+};
+use tokio::time::interval;
+use tracing::{debug, error, info, warn};
+
+use crate::services::{
+    outbox::{BATCH_SIZE, MAX_ATTEMPTS, backoff_secs},
+    telegram::{TelegramError, TelegramService},
+};
+
+/// Background worker that sends queued Telegram messages
+pub struct TelegramQueueWorker {
+    db: DBService,
+    telegram: TelegramService,
+    poll_interval: Duration,
+    digest_interval: Duration,
+}
+
+impl TelegramQueueWorker {
+    pub async fn spawn(db: DBService, telegram: TelegramService) -> tokio::task::JoinHandle<()> {
+        let worker = Self {
+            db,
+            telegram,
+            poll_interval: Duration::from_secs(1),
+            digest_interval: Duration::from_secs(60),
+        };
+        tokio::spawn(async move {
+            worker.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        info!(
+            "Starting Telegram send queue worker with interval {:?}",
+            self.poll_interval
+        );
+
+        let mut interval = interval(self.poll_interval);
+        let mut digest_interval = interval(self.digest_interval);
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    if let Err(e) = self.process_due().await {
+                        error!("Error processing Telegram send queue: {}", e);
+                    }
+                }
+                _ = digest_interval.tick() => {
+                    if let Err(e) = self.telegram.send_due_digests().await {
+                        error!("Error queueing Telegram digests: {}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    async fn process_due(&self) -> Result<(), TelegramError> {
+        let messages = TelegramOutgoingMessage::find_due_heads(&self.db.pool, BATCH_SIZE).await?;
+
+        for message in messages {
+            match self.telegram.send_queued(&message).await {
+                Ok(()) => {
+                    debug!(
+                        "Sent queued Telegram message {} to chat {}",
+                        message.id, message.chat_id
+                    );
+                    TelegramOutgoingMessage::mark_processed(&self.db.pool, message.id).await?;
+                }
+                Err(e) if message.attempts + 1 >= MAX_ATTEMPTS => {
+                    error!(
+                        "Giving up on Telegram message {} to chat {} after {} attempts: {}",
+                        message.id,
+                        message.chat_id,
+                        message.attempts + 1,
+                        e
+                    );
+                    TelegramOutgoingMessage::mark_failed(
+                        &self.db.pool,
+                        message.id,
+                        &e.to_string(),
+                        0,
+                    )
+                    .await?;
+                    TelegramOutgoingMessage::mark_processed(&self.db.pool, message.id).await?;
+                    self.record_undelivered(&message, &e.to_string()).await?;
+                }
+                Err(e) => {
+                    let retry_in = backoff_secs(message.attempts);
+                    warn!(
+                        "Telegram message {} to chat {} failed, retrying in {}s: {}",
+                        message.id, message.chat_id, retry_in, e
+                    );
+                    TelegramOutgoingMessage::mark_failed(
+                        &self.db.pool,
+                        message.id,
+                        &e.to_string(),
+                        retry_in,
+                    )
+                    .await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Mark the notifications a message that was given up on delivers as
+    /// failed in the history, and put them in the dead letters to be sent
+    /// again from there
+    async fn record_undelivered(
+        &self,
+        message: &TelegramOutgoingMessage,
+        error: &str,
+    ) -> Result<(), sqlx::Error> {
+        let pool = &self.db.pool;
+        for notification_id in
+            TelegramOutgoingMessage::find_notification_ids(pool, message.id).await?
+        {
+            Notification::mark_failed(pool, notification_id, error).await?;
+            NotificationDeadLetter::record_failure(
+                pool,
+                notification_id,
+                message.attempts + 1,
+                error,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,20 @@
+//! Values shared by the unit tests of several services
+
+use chrono::Utc;
+use db::models::task::{Task, TaskStatus};
+use uuid::Uuid;
+
+/// A task in review titled `title`, with nil ids
+pub fn task(title: &str) -> Task {
+    Task {
+        id: Uuid::nil(),
+        project_id: Uuid::nil(),
+        title: title.to_string(),
+        description: None,
+        status: TaskStatus::InReview,
+        parent_workspace_id: None,
+        position: 0,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
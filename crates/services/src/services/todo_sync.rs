@@ -0,0 +1,307 @@
+//! Markdown TODO file import.
+//!
+//! Projects can point at a `TODO.md`-style file in one of their repos (see
+//! [`ProjectTodoSyncSettings`]). Unchecked `- [ ] item` lines become tasks,
+//! ticking an item in the file completes its task, and with `write_back` a
+//! task finished on the board ticks its item in the file. Write-backs are left
+//! uncommitted in the repo's working tree.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Component, Path, PathBuf},
+    sync::{Arc, LazyLock},
+    time::Duration,
+};
+
+use db::{
+    DBService,
+    models::{
+        repo::Repo,
+        task::{CreateTask, Task, TaskStatus},
+        todo_sync::{ProjectTodoSyncSettings, TodoSyncItem},
+    },
+};
+use regex::Regex;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use thiserror::Error;
+use tokio::{sync::RwLock, time::interval};
+use tracing::{error, info};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::services::config::Config;
+
+static CHECKBOX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\s*[-*+]\s+\[)([ xX])(\]\s+)(.*?)\s*$").unwrap());
+
+#[derive(Debug, Error)]
+pub enum TodoSyncError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Repository not found")]
+    RepoNotFound,
+    #[error("Invalid TODO file path '{0}': must be relative and stay inside the repository")]
+    InvalidPath(String),
+}
+
+/// A checkbox line in a TODO file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TodoItem {
+    /// Zero-based line number in the file
+    pub line: usize,
+    pub title: String,
+    pub done: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, TS)]
+pub struct TodoSyncReport {
+    /// Tasks created from new unchecked items
+    pub created: usize,
+    /// Tasks marked done because their item was ticked in the file
+    pub completed: usize,
+    /// Items ticked in the file because their task was done
+    pub written_back: usize,
+}
+
+pub fn parse_todo(content: &str) -> Vec<TodoItem> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(line, text)| {
+            let captures = CHECKBOX.captures(text)?;
+            let title = captures[4].trim();
+            (!title.is_empty()).then(|| TodoItem {
+                line,
+                title: title.to_string(),
+                done: !captures[2].trim().is_empty(),
+            })
+        })
+        .collect()
+}
+
+/// Identity of an item across edits that only change case or spacing
+pub fn item_key(title: &str) -> String {
+    title
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Tick the checkboxes on `lines`, keeping everything else byte-for-byte
+pub fn tick_lines(content: &str, lines: &HashSet<usize>) -> String {
+    content
+        .split_inclusive('\n')
+        .enumerate()
+        .map(|(index, text)| {
+            if lines.contains(&index) {
+                CHECKBOX.replace(text, "${1}x${3}${4}").into_owned()
+                    + &text[text.trim_end().len()..]
+            } else {
+                text.to_string()
+            }
+        })
+        .collect()
+}
+
+pub fn validate_file_path(file_path: &str) -> Result<(), TodoSyncError> {
+    let path = Path::new(file_path);
+    let valid = !file_path.trim().is_empty()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+    if valid {
+        Ok(())
+    } else {
+        Err(TodoSyncError::InvalidPath(file_path.to_string()))
+    }
+}
+
+async fn todo_file_path(
+    pool: &SqlitePool,
+    settings: &ProjectTodoSyncSettings,
+) -> Result<PathBuf, TodoSyncError> {
+    validate_file_path(&settings.file_path)?;
+    let repo = Repo::find_by_id(pool, settings.repo_id)
+        .await?
+        .ok_or(TodoSyncError::RepoNotFound)?;
+    Ok(repo.path.join(&settings.file_path))
+}
+
+/// Import new items, complete tasks ticked in the file and, with
+/// `write_back`, tick items whose task is done. A missing file is not an
+/// error; there is simply nothing to import yet.
+pub async fn sync_project(
+    pool: &SqlitePool,
+    settings: &ProjectTodoSyncSettings,
+) -> Result<TodoSyncReport, TodoSyncError> {
+    let path = todo_file_path(pool, settings).await?;
+    let content = match tokio::fs::read_to_string(&path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+
+    let linked: HashMap<String, Uuid> = TodoSyncItem::find_by_project_id(pool, settings.project_id)
+        .await?
+        .into_iter()
+        .map(|item| (item.item_key, item.task_id))
+        .collect();
+
+    let mut report = TodoSyncReport::default();
+    let mut seen = HashSet::new();
+    let mut to_tick = HashSet::new();
+    for item in parse_todo(&content) {
+        let key = item_key(&item.title);
+        if !seen.insert(key.clone()) {
+            continue;
+        }
+
+        let Some(task_id) = linked.get(&key) else {
+            // Items already ticked before the first sync are history, not work
+            if !item.done {
+                let task = Task::create(
+                    pool,
+                    &CreateTask::from_title_description(settings.project_id, item.title, None),
+                    Uuid::new_v4(),
+                )
+                .await?;
+                TodoSyncItem::create(pool, settings.project_id, &key, task.id).await?;
+                report.created += 1;
+            }
+            continue;
+        };
+
+        let Some(task) = Task::find_by_id(pool, *task_id).await? else {
+            continue;
+        };
+        if item.done && task.status != TaskStatus::Done {
+            Task::update_status(pool, task.id, TaskStatus::Done).await?;
+            report.completed += 1;
+        } else if !item.done && task.status == TaskStatus::Done && settings.write_back {
+            to_tick.insert(item.line);
+        }
+    }
+
+    if !to_tick.is_empty() {
+        tokio::fs::write(&path, tick_lines(&content, &to_tick)).await?;
+        report.written_back = to_tick.len();
+    }
+
+    ProjectTodoSyncSettings::mark_synced(pool, settings.project_id).await?;
+    Ok(report)
+}
+
+/// Service that keeps every configured project in sync with its TODO file
+pub struct TodoSyncService {
+    db: DBService,
+    config: Arc<RwLock<Config>>,
+    poll_interval: Duration,
+}
+
+impl TodoSyncService {
+    pub async fn spawn(db: DBService, config: Arc<RwLock<Config>>) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            db,
+            config,
+            poll_interval: Duration::from_secs(300),
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(self) {
+        info!(
+            "Starting TODO file sync service with interval {:?}",
+            self.poll_interval
+        );
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            // Syncing changes tasks, which maintenance mode holds back
+            if self.config.read().await.maintenance_mode {
+                continue;
+            }
+            if let Err(e) = self.sync_all().await {
+                error!("Error syncing TODO files: {}", e);
+            }
+        }
+    }
+
+    async fn sync_all(&self) -> Result<(), sqlx::Error> {
+        for settings in ProjectTodoSyncSettings::find_all(&self.db.pool).await? {
+            match sync_project(&self.db.pool, &settings).await {
+                Ok(report) if report != TodoSyncReport::default() => info!(
+                    "Synced TODO file for project {}: {:?}",
+                    settings.project_id, report
+                ),
+                Ok(_) => {}
+                Err(e) => error!(
+                    "Failed to sync TODO file for project {}: {}",
+                    settings.project_id, e
+                ),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_todo() {
+        let content = "# Backlog\n\n- [ ] Fix login\n  * [x] Write docs  \n- plain bullet\n+ [X] Ship it\n- [ ]   \n";
+
+        assert_eq!(
+            parse_todo(content),
+            vec![
+                TodoItem {
+                    line: 2,
+                    title: "Fix login".to_string(),
+                    done: false,
+                },
+                TodoItem {
+                    line: 3,
+                    title: "Write docs".to_string(),
+                    done: true,
+                },
+                TodoItem {
+                    line: 5,
+                    title: "Ship it".to_string(),
+                    done: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_item_key_ignores_case_and_spacing() {
+        assert_eq!(item_key("Fix  the Login"), item_key("fix the login"));
+    }
+
+    #[test]
+    fn test_tick_lines_preserves_other_content() {
+        let content = "- [ ] One\r\n- [ ] Two\n  - [ ] Three";
+        let ticked = tick_lines(content, &HashSet::from([1, 2]));
+
+        assert_eq!(ticked, "- [ ] One\r\n- [x] Two\n  - [x] Three");
+    }
+
+    #[test]
+    fn test_validate_file_path() {
+        assert!(validate_file_path("TODO.md").is_ok());
+        assert!(validate_file_path("docs/BACKLOG.md").is_ok());
+        assert!(validate_file_path("../TODO.md").is_err());
+        assert!(validate_file_path("/etc/passwd").is_err());
+        assert!(validate_file_path("./TODO.md").is_err());
+        assert!(validate_file_path("").is_err());
+    }
+}
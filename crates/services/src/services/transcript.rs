@@ -0,0 +1,422 @@
+//! Shareable transcripts of agent sessions.
+//!
+//! A transcript lists every coding agent turn of an attempt: the prompt, the
+//! agent's messages, tool calls with their file diffs and command output, and
+//! errors. Loading spinners, token usage and next-action entries are dropped.
+
+use std::{collections::BTreeMap, time::Duration};
+
+use chrono::{DateTime, Utc};
+use executors::logs::{
+    ActionType, FileChange, NormalizedEntry, NormalizedEntryType,
+    utils::patch::extract_normalized_entry_from_patch,
+};
+use futures::{StreamExt, stream::BoxStream};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utils::log_msg::LogMsg;
+use uuid::Uuid;
+
+use crate::services::telegram::escape_html;
+
+/// How long to wait for further normalized entries before treating a log
+/// stream as complete. Live processes never send `Finished` on this stream.
+pub const ENTRY_IDLE_TIMEOUT: Duration = Duration::from_millis(500);
+/// Command output longer than this is cut in rendered transcripts
+const MAX_COMMAND_OUTPUT_CHARS: usize = 4000;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptFormat {
+    #[default]
+    Markdown,
+    Json,
+    Html,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptEntryKind {
+    User,
+    Assistant,
+    Thinking,
+    ToolCall,
+    System,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct TranscriptEntry {
+    pub kind: TranscriptEntryKind,
+    pub content: String,
+    pub tool_name: Option<String>,
+    /// Unified diff of the file changes made by this tool call
+    pub diff: Option<String>,
+    /// Output of a command run by this tool call
+    pub output: Option<String>,
+}
+
+/// One coding agent execution: the prompt and everything the agent did
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct TranscriptTurn {
+    pub execution_process_id: Uuid,
+    pub executor: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub prompt: Option<String>,
+    pub entries: Vec<TranscriptEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct Transcript {
+    pub workspace_id: Uuid,
+    pub task_title: String,
+    pub branch: String,
+    pub generated_at: DateTime<Utc>,
+    pub turns: Vec<TranscriptTurn>,
+}
+
+/// Read normalized entries from a log stream, keeping the latest version of each
+/// entry. Stops at `Finished` or after `idle` without a new message.
+pub async fn collect_normalized_entries(
+    mut stream: BoxStream<'static, Result<LogMsg, std::io::Error>>,
+    idle: Duration,
+) -> Vec<NormalizedEntry> {
+    let mut entries = BTreeMap::new();
+    loop {
+        match tokio::time::timeout(idle, stream.next()).await {
+            Ok(Some(Ok(LogMsg::JsonPatch(patch)))) => {
+                if let Some((index, entry)) = extract_normalized_entry_from_patch(&patch) {
+                    entries.insert(index, entry);
+                }
+            }
+            Ok(Some(Ok(LogMsg::Finished))) | Ok(None) | Err(_) => break,
+            Ok(Some(_)) => {}
+        }
+    }
+    entries.into_values().collect()
+}
+
+/// Clean a normalized entry for the transcript, or None for UI-only entries
+pub fn transcript_entry(entry: &NormalizedEntry) -> Option<TranscriptEntry> {
+    let content = entry.content.trim().to_string();
+    let mut transcript_entry = TranscriptEntry {
+        kind: TranscriptEntryKind::System,
+        content,
+        tool_name: None,
+        diff: None,
+        output: None,
+    };
+    match &entry.entry_type {
+        NormalizedEntryType::UserMessage | NormalizedEntryType::UserFeedback { .. } => {
+            transcript_entry.kind = TranscriptEntryKind::User;
+        }
+        NormalizedEntryType::AssistantMessage => {
+            transcript_entry.kind = TranscriptEntryKind::Assistant;
+        }
+        NormalizedEntryType::Thinking => transcript_entry.kind = TranscriptEntryKind::Thinking,
+        NormalizedEntryType::SystemMessage => transcript_entry.kind = TranscriptEntryKind::System,
+        NormalizedEntryType::ErrorMessage { .. } => {
+            transcript_entry.kind = TranscriptEntryKind::Error;
+        }
+        NormalizedEntryType::ToolUse {
+            tool_name,
+            action_type,
+            ..
+        } => {
+            transcript_entry.kind = TranscriptEntryKind::ToolCall;
+            transcript_entry.tool_name = Some(tool_name.clone());
+            match action_type {
+                ActionType::FileEdit { path, changes } => {
+                    transcript_entry.diff = file_changes_diff(path, changes);
+                }
+                ActionType::CommandRun {
+                    result: Some(result),
+                    ..
+                } => {
+                    transcript_entry.output = result
+                        .output
+                        .as_deref()
+                        .map(str::trim)
+                        .filter(|output| !output.is_empty())
+                        .map(truncate_output);
+                }
+                _ => {}
+            }
+        }
+        NormalizedEntryType::Loading
+        | NormalizedEntryType::NextAction { .. }
+        | NormalizedEntryType::TokenUsageInfo(_) => return None,
+    }
+
+    let empty = transcript_entry.content.is_empty()
+        && transcript_entry.diff.is_none()
+        && transcript_entry.output.is_none();
+    (!empty).then_some(transcript_entry)
+}
+
+/// Build a turn from collected entries, dropping the echo of the prompt
+pub fn transcript_turn(
+    execution_process_id: Uuid,
+    executor: Option<String>,
+    started_at: DateTime<Utc>,
+    prompt: Option<String>,
+    entries: &[NormalizedEntry],
+) -> TranscriptTurn {
+    let prompt = prompt
+        .map(|prompt| prompt.trim().to_string())
+        .filter(|prompt| !prompt.is_empty());
+    let entries = entries
+        .iter()
+        .filter_map(transcript_entry)
+        .filter(|entry| {
+            entry.kind != TranscriptEntryKind::User || Some(&entry.content) != prompt.as_ref()
+        })
+        .collect();
+    TranscriptTurn {
+        execution_process_id,
+        executor,
+        started_at,
+        prompt,
+        entries,
+    }
+}
+
+fn file_changes_diff(path: &str, changes: &[FileChange]) -> Option<String> {
+    let diffs: Vec<String> = changes
+        .iter()
+        .filter_map(|change| match change {
+            FileChange::Edit { unified_diff, .. } => Some(unified_diff.trim_end().to_string()),
+            FileChange::Write { content } => Some(format!(
+                "--- /dev/null\n+++ b/{path}\n{}",
+                content
+                    .lines()
+                    .map(|line| format!("+{line}"))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            )),
+            FileChange::Delete => Some(format!("--- a/{path}\n+++ /dev/null")),
+            FileChange::Rename { new_path } => {
+                Some(format!("rename from {path}\nrename to {new_path}"))
+            }
+        })
+        .collect();
+    (!diffs.is_empty()).then(|| diffs.join("\n"))
+}
+
+fn truncate_output(output: &str) -> String {
+    if output.chars().count() <= MAX_COMMAND_OUTPUT_CHARS {
+        return output.to_string();
+    }
+    let cut: String = output.chars().take(MAX_COMMAND_OUTPUT_CHARS).collect();
+    format!("{cut}\n… (truncated)")
+}
+
+/// Backtick fence longer than any run of backticks in `text`
+fn code_fence(text: &str) -> String {
+    let longest = text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+fn kind_label(kind: TranscriptEntryKind) -> &'static str {
+    match kind {
+        TranscriptEntryKind::User => "User",
+        TranscriptEntryKind::Assistant => "Assistant",
+        TranscriptEntryKind::Thinking => "Thinking",
+        TranscriptEntryKind::ToolCall => "Tool",
+        TranscriptEntryKind::System => "System",
+        TranscriptEntryKind::Error => "Error",
+    }
+}
+
+fn turn_heading(number: usize, turn: &TranscriptTurn) -> String {
+    let executor = turn.executor.as_deref().unwrap_or("agent");
+    format!(
+        "Turn {number} · {executor} · {}",
+        turn.started_at.format("%Y-%m-%d %H:%M UTC")
+    )
+}
+
+pub fn render_markdown(transcript: &Transcript) -> String {
+    let mut out = format!(
+        "# {}\n\nBranch `{}` · generated {}\n",
+        transcript.task_title,
+        transcript.branch,
+        transcript.generated_at.format("%Y-%m-%d %H:%M UTC")
+    );
+
+    for (i, turn) in transcript.turns.iter().enumerate() {
+        out.push_str(&format!("\n## {}\n", turn_heading(i + 1, turn)));
+        if let Some(prompt) = &turn.prompt {
+            out.push_str("\n**Prompt**\n\n");
+            for line in prompt.lines() {
+                out.push_str(&format!("> {line}\n"));
+            }
+        }
+
+        for entry in &turn.entries {
+            let label = match (&entry.kind, &entry.tool_name) {
+                (TranscriptEntryKind::ToolCall, Some(tool)) => format!("Tool `{tool}`"),
+                (kind, _) => kind_label(*kind).to_string(),
+            };
+            out.push_str(&format!("\n**{label}**"));
+            if entry.kind == TranscriptEntryKind::Thinking {
+                out.push_str(&format!("\n\n_{}_\n", entry.content));
+            } else if !entry.content.is_empty() {
+                out.push_str(&format!("\n\n{}\n", entry.content));
+            } else {
+                out.push('\n');
+            }
+            for (lang, block) in [("diff", &entry.diff), ("text", &entry.output)] {
+                if let Some(block) = block {
+                    let fence = code_fence(block);
+                    out.push_str(&format!("\n{fence}{lang}\n{block}\n{fence}\n"));
+                }
+            }
+        }
+    }
+    out
+}
+
+pub fn render_html(transcript: &Transcript) -> String {
+    let title = escape_html(&transcript.task_title);
+    let mut body = format!(
+        "<h1>{title}</h1>\n<p class=\"meta\">Branch <code>{}</code> · generated {}</p>\n",
+        escape_html(&transcript.branch),
+        transcript.generated_at.format("%Y-%m-%d %H:%M UTC")
+    );
+
+    for (i, turn) in transcript.turns.iter().enumerate() {
+        body.push_str(&format!(
+            "<section>\n<h2>{}</h2>\n",
+            escape_html(&turn_heading(i + 1, turn))
+        ));
+        if let Some(prompt) = &turn.prompt {
+            body.push_str(&format!(
+                "<blockquote class=\"prompt\">{}</blockquote>\n",
+                escape_html(prompt)
+            ));
+        }
+        for entry in &turn.entries {
+            let label = match &entry.tool_name {
+                Some(tool) => format!("Tool <code>{}</code>", escape_html(tool)),
+                None => kind_label(entry.kind).to_string(),
+            };
+            body.push_str(&format!(
+                "<div class=\"entry {}\">\n<div class=\"label\">{label}</div>\n",
+                kind_label(entry.kind).to_lowercase()
+            ));
+            if !entry.content.is_empty() {
+                body.push_str(&format!(
+                    "<div class=\"content\">{}</div>\n",
+                    escape_html(&entry.content)
+                ));
+            }
+            for block in [&entry.diff, &entry.output].into_iter().flatten() {
+                body.push_str(&format!("<pre>{}</pre>\n", escape_html(block)));
+            }
+            body.push_str("</div>\n");
+        }
+        body.push_str("</section>\n");
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+         <style>{TRANSCRIPT_CSS}</style>\n</head>\n<body>\n{body}</body>\n</html>\n"
+    )
+}
+
+const TRANSCRIPT_CSS: &str = "body{font-family:system-ui,sans-serif;max-width:900px;margin:2rem auto;padding:0 1rem;color:#1f2328}\
+.meta{color:#59636e}.entry{margin:1rem 0}.label{font-weight:600;margin-bottom:.25rem}\
+.content{white-space:pre-wrap}.thinking .content{font-style:italic;color:#59636e}\
+.error .content{color:#cf222e}blockquote{border-left:4px solid #d1d9e0;margin:1rem 0;padding:0 1rem;white-space:pre-wrap}\
+pre{background:#f6f8fa;padding:.75rem;overflow-x:auto;border-radius:6px}";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(entry_type: NormalizedEntryType, content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type,
+            content: content.to_string(),
+            metadata: None,
+        }
+    }
+
+    fn sample_transcript() -> Transcript {
+        let entries = vec![
+            entry(NormalizedEntryType::UserMessage, "Fix the login bug"),
+            entry(NormalizedEntryType::Loading, ""),
+            entry(NormalizedEntryType::AssistantMessage, "Fixed <form> handling"),
+        ];
+        Transcript {
+            workspace_id: Uuid::nil(),
+            task_title: "Login bug".to_string(),
+            branch: "vk/login".to_string(),
+            generated_at: Utc::now(),
+            turns: vec![transcript_turn(
+                Uuid::nil(),
+                Some("CLAUDE_CODE".to_string()),
+                Utc::now(),
+                Some("Fix the login bug".to_string()),
+                &entries,
+            )],
+        }
+    }
+
+    #[test]
+    fn test_transcript_turn_drops_ui_entries_and_prompt_echo() {
+        let transcript = sample_transcript();
+        let turn = &transcript.turns[0];
+
+        assert_eq!(turn.entries.len(), 1);
+        assert_eq!(turn.entries[0].kind, TranscriptEntryKind::Assistant);
+    }
+
+    #[test]
+    fn test_file_changes_diff() {
+        let changes = vec![
+            FileChange::Edit {
+                unified_diff: "@@ -1 +1 @@\n-a\n+b\n".to_string(),
+                has_line_numbers: true,
+            },
+            FileChange::Rename {
+                new_path: "new.rs".to_string(),
+            },
+        ];
+
+        let diff = file_changes_diff("old.rs", &changes).unwrap();
+
+        assert!(diff.starts_with("@@ -1 +1 @@\n-a\n+b\n"));
+        assert!(diff.ends_with("rename from old.rs\nrename to new.rs"));
+        assert_eq!(file_changes_diff("old.rs", &[]), None);
+    }
+
+    #[test]
+    fn test_code_fence_outgrows_content_backticks() {
+        assert_eq!(code_fence("plain"), "```");
+        assert_eq!(code_fence("has ```` inside"), "`````");
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let markdown = render_markdown(&sample_transcript());
+
+        assert!(markdown.starts_with("# Login bug\n"));
+        assert!(markdown.contains("> Fix the login bug\n"));
+        assert!(markdown.contains("**Assistant**\n\nFixed <form> handling\n"));
+    }
+
+    #[test]
+    fn test_render_html_escapes_content() {
+        let html = render_html(&sample_transcript());
+
+        assert!(html.contains("Fixed &lt;form&gt; handling"));
+        assert!(!html.contains("<form>"));
+    }
+}
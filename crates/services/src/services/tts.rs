@@ -0,0 +1,182 @@
+//! Text-to-speech for voice summary notifications.
+//!
+//! Audio is always produced as OGG/Opus, the only format Telegram plays as a
+//! voice message.
+
+use std::{process::Stdio, time::Duration};
+
+use serde_json::json;
+use thiserror::Error;
+use tokio::{io::AsyncWriteExt, process::Command};
+use utils::shell::get_shell_command;
+
+use crate::services::config::{TtsBackend, TtsConfig};
+
+/// Summaries are cut to roughly half a minute of speech
+const MAX_SPOKEN_CHARS: usize = 600;
+const SYNTHESIS_TIMEOUT: Duration = Duration::from_secs(60);
+const OPENAI_SPEECH_URL: &str = "https://api.openai.com/v1/audio/speech";
+const OPENAI_TTS_MODEL: &str = "tts-1";
+
+#[derive(Debug, Error)]
+pub enum TtsError {
+    #[error("TTS backend not configured: {0}")]
+    NotConfigured(&'static str),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("TTS backend failed: {0}")]
+    Backend(String),
+
+    #[error("TTS synthesis timed out")]
+    Timeout,
+}
+
+/// Shorten `text` for speech, preferring to cut at the end of a sentence
+pub fn spoken_text(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= MAX_SPOKEN_CHARS {
+        return text;
+    }
+
+    let cut: String = text.chars().take(MAX_SPOKEN_CHARS).collect();
+    match cut.rfind(['.', '!', '?']) {
+        Some(end) if end > MAX_SPOKEN_CHARS / 2 => cut[..=end].to_string(),
+        _ => format!("{}…", cut.trim_end()),
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct TtsService {
+    http: reqwest::Client,
+}
+
+impl TtsService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Synthesize `text` into OGG/Opus audio with the configured backend
+    pub async fn synthesize(&self, config: &TtsConfig, text: &str) -> Result<Vec<u8>, TtsError> {
+        let text = spoken_text(text);
+        tokio::time::timeout(SYNTHESIS_TIMEOUT, async {
+            match config.backend {
+                TtsBackend::Command => synthesize_with_command(config, &text).await,
+                TtsBackend::OpenAi => self.synthesize_with_openai(config, &text).await,
+            }
+        })
+        .await
+        .map_err(|_| TtsError::Timeout)?
+    }
+
+    async fn synthesize_with_openai(
+        &self,
+        config: &TtsConfig,
+        text: &str,
+    ) -> Result<Vec<u8>, TtsError> {
+        let api_key = config
+            .openai_api_key
+            .as_deref()
+            .filter(|key| !key.is_empty())
+            .ok_or(TtsError::NotConfigured("missing OpenAI API key"))?;
+
+        let response = self
+            .http
+            .post(OPENAI_SPEECH_URL)
+            .bearer_auth(api_key)
+            .json(&json!({
+                "model": OPENAI_TTS_MODEL,
+                "voice": config.voice,
+                "input": text,
+                "response_format": "opus",
+            }))
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(TtsError::Backend(format!("{status}: {body}")));
+        }
+        Ok(response.bytes().await?.to_vec())
+    }
+}
+
+/// Run the configured command with `text` on stdin and read audio from stdout
+async fn synthesize_with_command(config: &TtsConfig, text: &str) -> Result<Vec<u8>, TtsError> {
+    let command = config
+        .command
+        .as_deref()
+        .filter(|command| !command.trim().is_empty())
+        .ok_or(TtsError::NotConfigured("missing TTS command"))?;
+
+    let (shell_cmd, shell_arg) = get_shell_command();
+    let mut child = Command::new(shell_cmd)
+        .arg(shell_arg)
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes()).await?;
+    }
+    let output = child.wait_with_output().await?;
+
+    if !output.status.success() {
+        return Err(TtsError::Backend(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    if output.stdout.is_empty() {
+        return Err(TtsError::Backend("command produced no audio".to_string()));
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spoken_text_keeps_short_text() {
+        assert_eq!(spoken_text("Fixed the\n  login bug."), "Fixed the login bug.");
+    }
+
+    #[test]
+    fn test_spoken_text_cuts_at_sentence_end() {
+        let sentence = "This sentence is part of a long summary. ";
+        let text = sentence.repeat(40);
+
+        let spoken = spoken_text(&text);
+
+        assert!(spoken.chars().count() <= MAX_SPOKEN_CHARS);
+        assert!(spoken.ends_with('.'));
+    }
+
+    #[test]
+    fn test_spoken_text_marks_cut_without_sentence_end() {
+        let text = "word ".repeat(300);
+
+        let spoken = spoken_text(&text);
+
+        assert!(spoken.ends_with('…'));
+        assert!(spoken.chars().count() <= MAX_SPOKEN_CHARS + 1);
+    }
+
+    #[tokio::test]
+    async fn test_synthesize_requires_backend_settings() {
+        let service = TtsService::new();
+        let config = TtsConfig::default();
+
+        let result = service.synthesize(&config, "hello").await;
+
+        assert!(matches!(result, Err(TtsError::NotConfigured(_))));
+    }
+}
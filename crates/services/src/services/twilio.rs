@@ -0,0 +1,251 @@
+//! Task failures sent as text messages through Twilio.
+//!
+//! Messages go from `Config::twilio.from_number` to each of its
+//! `to_numbers`, for failures of the watched tasks only. Every message is
+//! billed, so no more than `max_per_hour` are sent in any hour; the rest are
+//! dropped.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use db::models::{notification_rule::NotificationChannel, task::Task};
+use serde::Deserialize;
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+use crate::services::{
+    config::{Config, TwilioConfig},
+    http_notifier,
+    notification_dispatcher::{NotificationEvent, Notifier, NotifierError},
+    telegram::TaskNotificationKind,
+};
+
+const API_URL: &str = "https://api.twilio.com/2010-04-01";
+/// Two SMS segments; longer bodies are split into more billed segments
+const MAX_BODY_CHARS: usize = 306;
+const RATE_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Error)]
+pub enum TwilioError {
+    #[error("SMS notifications are not configured: {0}")]
+    NotConfigured(&'static str),
+
+    #[error("Twilio request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("Twilio returned HTTP {status}: {message}")]
+    Rejected { status: u16, message: String },
+
+    #[error("SMS limit of {0} per hour reached")]
+    RateLimited(u32),
+}
+
+/// Body of an API error response
+#[derive(Debug, Deserialize)]
+struct TwilioErrorResponse {
+    message: String,
+}
+
+/// Times of the messages sent in the last hour, shared by all clones of the
+/// notifier
+#[derive(Clone, Default)]
+struct SendBudget {
+    sent: Arc<Mutex<VecDeque<Instant>>>,
+}
+
+impl SendBudget {
+    /// Count `messages` as sent at `now` if they fit within `max_per_hour`
+    fn try_spend(&self, messages: usize, max_per_hour: u32, now: Instant) -> bool {
+        let mut sent = self
+            .sent
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        while sent
+            .front()
+            .is_some_and(|&at| now.duration_since(at) >= RATE_WINDOW)
+        {
+            sent.pop_front();
+        }
+        if sent.len() + messages > max_per_hour as usize {
+            return false;
+        }
+        sent.extend(std::iter::repeat_n(now, messages));
+        true
+    }
+}
+
+/// Text of a message about `task`
+fn task_message(task: &Task, kind: TaskNotificationKind, summary: Option<&str>) -> String {
+    let event = match kind {
+        TaskNotificationKind::Started => "started",
+        TaskNotificationKind::InReview => "ready for review",
+        TaskNotificationKind::Failed => "failed",
+        TaskNotificationKind::Done => "completed",
+        TaskNotificationKind::Cancelled => "cancelled",
+    };
+    let mut message = format!("Task {event}: {}", task.title);
+    if let Some(summary) = summary.map(str::trim).filter(|summary| !summary.is_empty()) {
+        message.push_str("\n\n");
+        message.push_str(summary);
+    }
+    truncate(&message)
+}
+
+fn truncate(message: &str) -> String {
+    if message.chars().count() <= MAX_BODY_CHARS {
+        return message.to_string();
+    }
+    let mut truncated: String = message.chars().take(MAX_BODY_CHARS - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Sends text messages about failed tasks through Twilio
+#[derive(Clone)]
+pub struct TwilioNotifier {
+    config: Arc<RwLock<Config>>,
+    http: reqwest::Client,
+    budget: SendBudget,
+}
+
+impl TwilioNotifier {
+    pub fn new(config: Arc<RwLock<Config>>) -> Self {
+        Self {
+            config,
+            http: http_notifier::client(),
+            budget: SendBudget::default(),
+        }
+    }
+
+    /// Text `body` to every configured number. Does nothing if SMS is off.
+    pub async fn send_message(&self, body: &str) -> Result<(), TwilioError> {
+        let config = self.config.read().await.twilio.clone();
+        if !config.enabled {
+            return Ok(());
+        }
+        self.send(&config, body).await
+    }
+
+    async fn send(&self, config: &TwilioConfig, body: &str) -> Result<(), TwilioError> {
+        let non_empty = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        let account_sid =
+            non_empty(&config.account_sid).ok_or(TwilioError::NotConfigured("no account SID"))?;
+        let auth_token =
+            non_empty(&config.auth_token).ok_or(TwilioError::NotConfigured("no auth token"))?;
+        let from =
+            non_empty(&config.from_number).ok_or(TwilioError::NotConfigured("no from number"))?;
+        if config.to_numbers.is_empty() {
+            return Err(TwilioError::NotConfigured("no recipients"));
+        }
+
+        if !self
+            .budget
+            .try_spend(config.to_numbers.len(), config.max_per_hour, Instant::now())
+        {
+            tracing::warn!(
+                "Dropping SMS notification, {} per hour already sent",
+                config.max_per_hour
+            );
+            return Err(TwilioError::RateLimited(config.max_per_hour));
+        }
+
+        let url = format!("{API_URL}/Accounts/{account_sid}/Messages.json");
+        for to in &config.to_numbers {
+            let response = self
+                .http
+                .post(&url)
+                .basic_auth(&account_sid, Some(&auth_token))
+                .form(&[("From", from.as_str()), ("To", to.as_str()), ("Body", body)])
+                .send()
+                .await?;
+            let status = response.status();
+            if !status.is_success() {
+                let message = match response.json::<TwilioErrorResponse>().await {
+                    Ok(body) => body.message,
+                    Err(_) => "no error details".to_string(),
+                };
+                return Err(TwilioError::Rejected {
+                    status: status.as_u16(),
+                    message,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Notifier for TwilioNotifier {
+    fn channel(&self) -> NotificationChannel {
+        NotificationChannel::Sms
+    }
+
+    async fn wants(&self, kind: TaskNotificationKind) -> bool {
+        self.config.read().await.twilio.enabled && kind == TaskNotificationKind::Failed
+    }
+
+    async fn send(&self, event: &NotificationEvent) -> Result<(), NotifierError> {
+        let config = self.config.read().await.twilio.clone();
+        if !config.enabled
+            || (!config.watched_task_ids.is_empty()
+                && !config.watched_task_ids.contains(&event.task.id))
+        {
+            return Ok(());
+        }
+        let body = task_message(&event.task, event.kind, event.summary.as_deref());
+        self.send(&config, &body).await?;
+        Ok(())
+    }
+
+    async fn send_text(&self, title: &str, body: &str) -> Result<(), NotifierError> {
+        let config = self.config.read().await.twilio.clone();
+        self.send(&config, &truncate(&format!("{title}\n\n{body}")))
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_fixtures::task;
+
+    #[test]
+    fn test_budget_limits_messages_per_hour() {
+        let budget = SendBudget::default();
+        let start = Instant::now();
+
+        assert!(budget.try_spend(2, 3, start));
+        assert!(!budget.try_spend(2, 3, start + Duration::from_secs(60)));
+        assert!(budget.try_spend(1, 3, start + Duration::from_secs(60)));
+        assert!(!budget.try_spend(1, 3, start + Duration::from_secs(120)));
+
+        // Clones share the budget, and the first messages expire after an hour
+        let clone = budget.clone();
+        assert!(clone.try_spend(2, 3, start + RATE_WINDOW));
+        assert!(!budget.try_spend(1, 3, start + RATE_WINDOW));
+    }
+
+    #[test]
+    fn test_task_message() {
+        let task = task("Add dark mode");
+
+        assert_eq!(
+            task_message(&task, TaskNotificationKind::Failed, Some("Tests failed.")),
+            "Task failed: Add dark mode\n\nTests failed."
+        );
+        let long = task_message(&task, TaskNotificationKind::Failed, Some(&"x".repeat(500)));
+        assert_eq!(long.chars().count(), MAX_BODY_CHARS);
+        assert!(long.ends_with('…'));
+    }
+}
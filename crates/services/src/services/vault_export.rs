@@ -0,0 +1,421 @@
+//! Markdown vault export.
+//!
+//! Writes every task as a markdown note with YAML frontmatter into
+//! `VaultExportConfig::directory`, one folder per project, so the board can
+//! be browsed from Obsidian or any other markdown knowledge base. The prompts
+//! sent to a task's agents and their summaries become the note's comments.
+//!
+//! Notes are overwritten on every export; a task's note is found again by the
+//! short id at the end of its file name, so renaming a task moves its note.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use db::{
+    DBService,
+    models::{coding_agent_turn::CodingAgentTurn, project::Project, task::Task},
+};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use thiserror::Error;
+use tokio::{sync::RwLock, time::interval};
+use tracing::{error, info};
+use ts_rs::TS;
+use utils::path::expand_tilde;
+
+use crate::services::config::{Config, VaultExportSchedule};
+
+/// Longest task title kept in a note's file name
+const MAX_FILE_STEM_CHARS: usize = 80;
+/// Length of the ` (1a2b3c4d).md` suffix that identifies a task's note
+const NOTE_SUFFIX_LEN: usize = 14;
+
+#[derive(Debug, Error)]
+pub enum VaultExportError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("No vault export directory is configured")]
+    NoDirectory,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct VaultExportReport {
+    pub directory: String,
+    pub notes_written: usize,
+    /// Notes removed because their task was renamed
+    pub notes_removed: usize,
+}
+
+/// Keep a value on one frontmatter line; JSON strings are valid YAML scalars
+fn yaml_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+/// Strip characters that are invalid in file names or break Obsidian links
+fn sanitize_file_stem(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '#' | '^' | '[' | ']' => ' ',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect();
+    let stem = cleaned
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches('.')
+        .chars()
+        .take(MAX_FILE_STEM_CHARS)
+        .collect::<String>();
+    let stem = stem.trim().to_string();
+    if stem.is_empty() {
+        "Untitled".to_string()
+    } else {
+        stem
+    }
+}
+
+fn short_id(task: &Task) -> String {
+    task.id.simple().to_string()[..8].to_string()
+}
+
+fn note_suffix(task: &Task) -> String {
+    format!(" ({}).md", short_id(task))
+}
+
+/// File name of a task's note, e.g. `Fix login (1a2b3c4d).md`
+pub fn note_file_name(task: &Task) -> String {
+    format!("{}{}", sanitize_file_stem(&task.title), note_suffix(task))
+}
+
+fn format_timestamp(timestamp: DateTime<Utc>) -> String {
+    timestamp.format("%Y-%m-%d %H:%M UTC").to_string()
+}
+
+/// Render a task as a markdown note
+pub fn render_note(task: &Task, project: &Project, turns: &[CodingAgentTurn]) -> String {
+    let status = task.status.to_string();
+    let mut note = String::from("---\n");
+    note.push_str(&format!("id: {}\n", task.id));
+    note.push_str(&format!("title: {}\n", yaml_string(&task.title)));
+    note.push_str(&format!("project: {}\n", yaml_string(&project.name)));
+    note.push_str(&format!("status: {status}\n"));
+    note.push_str(&format!("tags:\n  - vibe-kanban\n  - status/{status}\n"));
+    note.push_str(&format!("created: {}\n", task.created_at.to_rfc3339()));
+    note.push_str(&format!("updated: {}\n", task.updated_at.to_rfc3339()));
+    note.push_str("---\n\n");
+
+    note.push_str(&format!("# {}\n", task.title.trim()));
+    if let Some(description) = task
+        .description
+        .as_deref()
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+    {
+        note.push_str(&format!("\n{description}\n"));
+    }
+
+    let comments: Vec<(&CodingAgentTurn, Option<&str>, Option<&str>)> = turns
+        .iter()
+        .map(|turn| {
+            let prompt = turn
+                .prompt
+                .as_deref()
+                .map(str::trim)
+                .filter(|p| !p.is_empty());
+            let summary = turn
+                .summary
+                .as_deref()
+                .map(str::trim)
+                .filter(|s| !s.is_empty());
+            (turn, prompt, summary)
+        })
+        .filter(|(_, prompt, summary)| prompt.is_some() || summary.is_some())
+        .collect();
+    if !comments.is_empty() {
+        note.push_str("\n## Comments\n");
+        for (turn, prompt, summary) in comments {
+            note.push_str(&format!("\n### {}\n", format_timestamp(turn.created_at)));
+            if let Some(prompt) = prompt {
+                note.push_str(&format!("\n**Prompt**\n\n{prompt}\n"));
+            }
+            if let Some(summary) = summary {
+                note.push_str(&format!("\n**Agent**\n\n{summary}\n"));
+            }
+        }
+    }
+
+    note
+}
+
+/// Remove notes left behind under an old title; `current` maps each note
+/// suffix to the file name just written for that task
+async fn remove_stale_notes(
+    dir: &Path,
+    current: &HashMap<String, String>,
+) -> std::io::Result<usize> {
+    let mut removed = 0;
+    let mut entries = tokio::fs::read_dir(dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        let suffix = name.get(name.len().saturating_sub(NOTE_SUFFIX_LEN)..);
+        let stale = suffix
+            .and_then(|suffix| current.get(suffix))
+            .is_some_and(|file_name| file_name != name);
+        if stale {
+            tokio::fs::remove_file(entry.path()).await?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Write a note for every task into `directory`
+pub async fn export_vault(
+    pool: &SqlitePool,
+    directory: &Path,
+) -> Result<VaultExportReport, VaultExportError> {
+    let mut report = VaultExportReport {
+        directory: directory.to_string_lossy().to_string(),
+        notes_written: 0,
+        notes_removed: 0,
+    };
+
+    for project in Project::find_all(pool).await? {
+        let project_dir = directory.join(sanitize_file_stem(&project.name));
+        tokio::fs::create_dir_all(&project_dir).await?;
+
+        let mut written = HashMap::new();
+        for task in Task::find_by_project_id_with_attempt_status(pool, project.id).await? {
+            let turns = CodingAgentTurn::find_by_task_id(pool, task.id).await?;
+            let file_name = note_file_name(&task);
+            tokio::fs::write(
+                project_dir.join(&file_name),
+                render_note(&task, &project, &turns),
+            )
+            .await?;
+            written.insert(note_suffix(&task), file_name);
+        }
+        report.notes_written += written.len();
+        report.notes_removed += remove_stale_notes(&project_dir, &written).await?;
+    }
+
+    Ok(report)
+}
+
+/// Export into the configured directory
+pub async fn export_configured_vault(
+    pool: &SqlitePool,
+    config: &RwLock<Config>,
+) -> Result<VaultExportReport, VaultExportError> {
+    let directory = configured_directory(config)
+        .await
+        .ok_or(VaultExportError::NoDirectory)?;
+    export_vault(pool, &directory).await
+}
+
+async fn configured_directory(config: &RwLock<Config>) -> Option<PathBuf> {
+    let config = config.read().await;
+    config
+        .vault_export
+        .directory
+        .as_deref()
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(expand_tilde)
+}
+
+/// Whether a scheduled export is due, given when the last one ran
+pub(crate) fn is_due(
+    schedule: VaultExportSchedule,
+    now: DateTime<Utc>,
+    last_export: Option<DateTime<Utc>>,
+) -> bool {
+    let every = match schedule {
+        VaultExportSchedule::Manual => return false,
+        VaultExportSchedule::Hourly => chrono::Duration::hours(1),
+        VaultExportSchedule::Daily => chrono::Duration::days(1),
+    };
+    last_export.is_none_or(|last| now - last >= every)
+}
+
+/// Service that runs the vault export on its configured schedule
+pub struct VaultExportService {
+    db: DBService,
+    config: Arc<RwLock<Config>>,
+    poll_interval: Duration,
+    last_export: Option<DateTime<Utc>>,
+}
+
+impl VaultExportService {
+    pub async fn spawn(db: DBService, config: Arc<RwLock<Config>>) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            db,
+            config,
+            poll_interval: Duration::from_secs(60),
+            last_export: None,
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(mut self) {
+        info!(
+            "Starting vault export service with interval {:?}",
+            self.poll_interval
+        );
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            let schedule = self.config.read().await.vault_export.schedule;
+            let now = Utc::now();
+            if !is_due(schedule, now, self.last_export)
+                || configured_directory(&self.config).await.is_none()
+            {
+                continue;
+            }
+
+            self.last_export = Some(now);
+            match export_configured_vault(&self.db.pool, &self.config).await {
+                Ok(report) => info!(
+                    "Exported {} notes to {}",
+                    report.notes_written, report.directory
+                ),
+                Err(e) => error!("Error exporting vault: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::services::test_fixtures;
+
+    fn task(title: &str, description: Option<&str>) -> Task {
+        Task {
+            id: Uuid::parse_str("1a2b3c4d-0000-4000-8000-000000000000").unwrap(),
+            description: description.map(str::to_string),
+            created_at: DateTime::parse_from_rfc3339("2026-01-02T03:04:05Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            updated_at: DateTime::parse_from_rfc3339("2026-01-03T03:04:05Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            ..test_fixtures::task(title)
+        }
+    }
+
+    fn project() -> Project {
+        Project {
+            id: Uuid::new_v4(),
+            name: "Web \"app\"".to_string(),
+            default_agent_working_dir: None,
+            remote_project_id: None,
+            archived_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn turn(prompt: Option<&str>, summary: Option<&str>) -> CodingAgentTurn {
+        CodingAgentTurn {
+            id: Uuid::new_v4(),
+            execution_process_id: Uuid::new_v4(),
+            agent_session_id: None,
+            agent_message_id: None,
+            prompt: prompt.map(str::to_string),
+            summary: summary.map(str::to_string),
+            seen: true,
+            created_at: DateTime::parse_from_rfc3339("2026-01-02T10:30:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_note_file_name() {
+        assert_eq!(
+            note_file_name(&task("Fix: login / [redirect]?", None)),
+            "Fix login redirect (1a2b3c4d).md"
+        );
+        assert_eq!(note_file_name(&task("...", None)), "Untitled (1a2b3c4d).md");
+    }
+
+    #[test]
+    fn test_render_note() {
+        let note = render_note(
+            &task("Fix login", Some("Redirect loops after OAuth\n")),
+            &project(),
+            &[
+                turn(Some("Fix the redirect"), Some("Changed the callback URL")),
+                turn(Some("  "), None),
+            ],
+        );
+
+        assert_eq!(
+            note,
+            "---\n\
+             id: 1a2b3c4d-0000-4000-8000-000000000000\n\
+             title: \"Fix login\"\n\
+             project: \"Web \\\"app\\\"\"\n\
+             status: inreview\n\
+             tags:\n  - vibe-kanban\n  - status/inreview\n\
+             created: 2026-01-02T03:04:05+00:00\n\
+             updated: 2026-01-03T03:04:05+00:00\n\
+             ---\n\n\
+             # Fix login\n\n\
+             Redirect loops after OAuth\n\n\
+             ## Comments\n\n\
+             ### 2026-01-02 10:30 UTC\n\n\
+             **Prompt**\n\nFix the redirect\n\n\
+             **Agent**\n\nChanged the callback URL\n"
+        );
+    }
+
+    #[test]
+    fn test_render_note_without_comments() {
+        let note = render_note(&task("Fix login", None), &project(), &[]);
+
+        assert!(note.ends_with("---\n\n# Fix login\n"));
+    }
+
+    #[test]
+    fn test_is_due() {
+        use VaultExportSchedule::*;
+
+        let now = Utc::now();
+
+        assert!(!is_due(Manual, now, None));
+        assert!(is_due(Hourly, now, None));
+        assert!(!is_due(
+            Hourly,
+            now,
+            Some(now - chrono::Duration::minutes(30))
+        ));
+        assert!(is_due(
+            Hourly,
+            now,
+            Some(now - chrono::Duration::minutes(61))
+        ));
+        assert!(!is_due(Daily, now, Some(now - chrono::Duration::hours(2))));
+    }
+}
@@ -0,0 +1,494 @@
+//! Task notifications pushed to browsers through the Web Push protocol.
+//!
+//! The web UI subscribes with the VAPID public key from
+//! [`WebPushNotifier::vapid_public_key`] and registers the subscription with
+//! the server. Messages are encrypted for each browser as in RFC 8291
+//! (`aes128gcm`) and posted to its push service with a VAPID authorization
+//! (RFC 8292), so the service worker can show them while no tab is open.
+
+use std::sync::Arc;
+
+use aes_gcm::{
+    Aes128Gcm, Key, Nonce,
+    aead::{Aead, KeyInit},
+};
+use async_trait::async_trait;
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::Utc;
+use db::models::{
+    notification_rule::NotificationChannel,
+    task::Task,
+    web_push_subscription::{WebPushKeys, WebPushSubscription},
+};
+use hkdf::Hkdf;
+use p256::{
+    PublicKey, SecretKey,
+    ecdsa::{Signature, SigningKey, signature::Signer},
+    elliptic_curve::sec1::ToEncodedPoint,
+};
+use rand::{RngCore, rngs::OsRng};
+use reqwest::header;
+use serde_json::json;
+use sha2::Sha256;
+use sqlx::SqlitePool;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use url::Url;
+
+use crate::services::{
+    config::{Config, WebPushConfig},
+    http_notifier::{self, StatusError},
+    notification_dispatcher::{NotificationEvent, Notifier, NotifierError},
+    telegram::TaskNotificationKind,
+};
+
+/// How long push services keep a message for a browser that is offline
+const TTL_SECS: u32 = 24 * 60 * 60;
+/// How long a VAPID token is valid; push services accept at most 24 hours
+const VAPID_TOKEN_SECS: i64 = 12 * 60 * 60;
+/// Sent as the VAPID `sub` claim when no subject is configured
+const DEFAULT_SUBJECT: &str = "https://www.vibekanban.com";
+/// Size of the single encrypted record. The body must stay below the 4 KiB
+/// push services accept.
+const RECORD_SIZE: u32 = 4096;
+/// Longest notification body; the payload is JSON in one record
+const MAX_BODY_CHARS: usize = 500;
+
+#[derive(Debug, Error)]
+pub enum WebPushError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+
+    #[error("Web Push request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("Invalid VAPID key: {0}")]
+    InvalidVapidKey(String),
+
+    #[error("Invalid push subscription: {0}")]
+    InvalidSubscription(String),
+
+    #[error("Failed to encrypt the push message")]
+    Encryption,
+
+    #[error("Push service returned {0}")]
+    Status(#[from] StatusError),
+}
+
+/// Sends task notifications to every subscribed browser
+#[derive(Clone)]
+pub struct WebPushNotifier {
+    config: Arc<RwLock<Config>>,
+    pool: SqlitePool,
+    http: reqwest::Client,
+}
+
+impl WebPushNotifier {
+    pub fn new(config: Arc<RwLock<Config>>, pool: SqlitePool) -> Self {
+        Self {
+            config,
+            pool,
+            http: http_notifier::client(),
+        }
+    }
+
+    /// The VAPID public key browsers subscribe with, base64url. The key pair
+    /// is generated on first use and kept in the config in memory; the second
+    /// value says whether that happened, so the caller saves the config.
+    pub async fn vapid_public_key(&self) -> Result<(String, bool), WebPushError> {
+        if let Some(private_key) = self.config.read().await.web_push.vapid_private_key.clone() {
+            return Ok((public_key_of(&signing_key(&private_key)?), false));
+        }
+
+        let mut config = self.config.write().await;
+        // Another request may have generated it while we waited for the lock
+        if let Some(private_key) = &config.web_push.vapid_private_key {
+            return Ok((public_key_of(&signing_key(private_key)?), false));
+        }
+        let key = SigningKey::random(&mut OsRng);
+        config.web_push.vapid_private_key = Some(URL_SAFE_NO_PAD.encode(key.to_bytes()));
+        Ok((public_key_of(&key), true))
+    }
+
+    /// Push a notification about `task` to every subscribed browser. Does
+    /// nothing if Web Push is off.
+    pub async fn send_task_notification(
+        &self,
+        task: &Task,
+        kind: TaskNotificationKind,
+        summary: Option<&str>,
+    ) -> Result<(), WebPushError> {
+        let config = self.config.read().await.web_push.clone();
+        if !config.enabled {
+            return Ok(());
+        }
+        let urgency = match kind {
+            TaskNotificationKind::Failed => "high",
+            TaskNotificationKind::Started | TaskNotificationKind::Cancelled => "low",
+            _ => "normal",
+        };
+        self.push_to_all(&config, &task_payload(task, kind, summary), urgency)
+            .await
+    }
+
+    /// Push `payload` to every subscribed browser. Subscriptions the push
+    /// service reports as gone are deleted; other failures are logged and do
+    /// not stop the remaining pushes.
+    async fn push_to_all(
+        &self,
+        config: &WebPushConfig,
+        payload: &str,
+        urgency: &str,
+    ) -> Result<(), WebPushError> {
+        // No key means no browser could have subscribed yet
+        let Some(private_key) = &config.vapid_private_key else {
+            return Ok(());
+        };
+        let subscriptions = WebPushSubscription::find_all(&self.pool).await?;
+        if subscriptions.is_empty() {
+            return Ok(());
+        }
+
+        let key = signing_key(private_key)?;
+        let subject = config.subject.as_deref().unwrap_or(DEFAULT_SUBJECT);
+        for subscription in subscriptions {
+            match self
+                .push(&subscription, payload.as_bytes(), &key, subject, urgency)
+                .await
+            {
+                Ok(()) => {}
+                Err(WebPushError::Status(StatusError {
+                    status: 404 | 410, ..
+                })) => {
+                    tracing::info!("Removing expired Web Push subscription {}", subscription.id);
+                    WebPushSubscription::delete(&self.pool, subscription.id).await?;
+                }
+                Err(e) => tracing::warn!(
+                    "Failed to push to Web Push subscription {}: {}",
+                    subscription.id,
+                    e
+                ),
+            }
+        }
+        Ok(())
+    }
+
+    async fn push(
+        &self,
+        subscription: &WebPushSubscription,
+        payload: &[u8],
+        key: &SigningKey,
+        subject: &str,
+        urgency: &str,
+    ) -> Result<(), WebPushError> {
+        let (ua_public, auth_secret) = decode_keys(&WebPushKeys {
+            p256dh: subscription.p256dh.clone(),
+            auth: subscription.auth.clone(),
+        })?;
+        let body = encrypt(payload, &ua_public, &auth_secret)?;
+        let authorization =
+            vapid_authorization(&subscription.endpoint, key, subject, Utc::now().timestamp())?;
+
+        let response = self
+            .http
+            .post(&subscription.endpoint)
+            .header(header::AUTHORIZATION, authorization)
+            .header(header::CONTENT_ENCODING, "aes128gcm")
+            .header(header::CONTENT_TYPE, "application/octet-stream")
+            .header("TTL", TTL_SECS)
+            .header("Urgency", urgency)
+            .body(body)
+            .send()
+            .await?;
+        http_notifier::check_status(response).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Notifier for WebPushNotifier {
+    fn channel(&self) -> NotificationChannel {
+        NotificationChannel::WebPush
+    }
+
+    async fn wants(&self, _kind: TaskNotificationKind) -> bool {
+        self.config.read().await.web_push.enabled
+    }
+
+    async fn send(&self, event: &NotificationEvent) -> Result<(), NotifierError> {
+        self.send_task_notification(&event.task, event.kind, event.summary.as_deref())
+            .await?;
+        Ok(())
+    }
+
+    async fn send_text(&self, title: &str, body: &str) -> Result<(), NotifierError> {
+        let config = self.config.read().await.web_push.clone();
+        let payload = json!({
+            "title": title,
+            "body": body.chars().take(MAX_BODY_CHARS).collect::<String>(),
+            "url": "/",
+        })
+        .to_string();
+        self.push_to_all(&config, &payload, "normal").await?;
+        Ok(())
+    }
+}
+
+/// Check that a subscription's keys can be encrypted for, before storing it
+pub fn validate_subscription(keys: &WebPushKeys) -> Result<(), WebPushError> {
+    decode_keys(keys).map(|_| ())
+}
+
+/// The browser's public key and auth secret
+fn decode_keys(keys: &WebPushKeys) -> Result<(PublicKey, Vec<u8>), WebPushError> {
+    let public_key = URL_SAFE_NO_PAD
+        .decode(keys.p256dh.trim_end_matches('='))
+        .ok()
+        .and_then(|bytes| PublicKey::from_sec1_bytes(&bytes).ok())
+        .ok_or_else(|| WebPushError::InvalidSubscription("bad p256dh key".to_string()))?;
+    let auth_secret = URL_SAFE_NO_PAD
+        .decode(keys.auth.trim_end_matches('='))
+        .ok()
+        .filter(|secret| secret.len() == 16)
+        .ok_or_else(|| WebPushError::InvalidSubscription("bad auth secret".to_string()))?;
+    Ok((public_key, auth_secret))
+}
+
+/// The JSON the service worker turns into a notification. `tag` makes a
+/// newer notification about the same task replace the older one.
+fn task_payload(task: &Task, kind: TaskNotificationKind, summary: Option<&str>) -> String {
+    let event = match kind {
+        TaskNotificationKind::Started => "started",
+        TaskNotificationKind::InReview => "ready for review",
+        TaskNotificationKind::Failed => "failed",
+        TaskNotificationKind::Done => "completed",
+        TaskNotificationKind::Cancelled => "cancelled",
+    };
+    let body = match summary.map(str::trim).filter(|summary| !summary.is_empty()) {
+        Some(summary) => summary,
+        None => task.title.as_str(),
+    };
+    json!({
+        "title": format!("Task {event}: {}", task.title),
+        "body": body.chars().take(MAX_BODY_CHARS).collect::<String>(),
+        "kind": kind,
+        "tag": task.id,
+        "url": format!("/projects/{}/tasks/{}", task.project_id, task.id),
+    })
+    .to_string()
+}
+
+fn signing_key(private_key: &str) -> Result<SigningKey, WebPushError> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(private_key.trim())
+        .map_err(|e| WebPushError::InvalidVapidKey(e.to_string()))?;
+    SigningKey::from_slice(&bytes).map_err(|e| WebPushError::InvalidVapidKey(e.to_string()))
+}
+
+/// Uncompressed public point of `key`, base64url, as `applicationServerKey`
+/// expects it
+fn public_key_of(key: &SigningKey) -> String {
+    URL_SAFE_NO_PAD.encode(key.verifying_key().to_encoded_point(false).as_bytes())
+}
+
+/// `Authorization` header value: a JWT for the endpoint's origin, signed
+/// with the VAPID key, and the key's public half
+fn vapid_authorization(
+    endpoint: &str,
+    key: &SigningKey,
+    subject: &str,
+    now: i64,
+) -> Result<String, WebPushError> {
+    let audience = Url::parse(endpoint)
+        .map_err(|e| WebPushError::InvalidSubscription(e.to_string()))?
+        .origin()
+        .ascii_serialization();
+    let header = URL_SAFE_NO_PAD.encode(r#"{"typ":"JWT","alg":"ES256"}"#);
+    let claims = URL_SAFE_NO_PAD.encode(
+        json!({
+            "aud": audience,
+            "exp": now + VAPID_TOKEN_SECS,
+            "sub": subject,
+        })
+        .to_string(),
+    );
+    let signing_input = format!("{header}.{claims}");
+    let signature: Signature = key.sign(signing_input.as_bytes());
+    Ok(format!(
+        "vapid t={signing_input}.{}, k={}",
+        URL_SAFE_NO_PAD.encode(signature.to_bytes()),
+        public_key_of(key)
+    ))
+}
+
+fn encrypt(
+    payload: &[u8],
+    ua_public: &PublicKey,
+    auth_secret: &[u8],
+) -> Result<Vec<u8>, WebPushError> {
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    encrypt_with(
+        payload,
+        ua_public,
+        auth_secret,
+        &SecretKey::random(&mut OsRng),
+        &salt,
+    )
+}
+
+/// Encrypt `payload` as one `aes128gcm` record for the browser with the
+/// public key `ua_public`, using the ephemeral key `as_secret`
+fn encrypt_with(
+    payload: &[u8],
+    ua_public: &PublicKey,
+    auth_secret: &[u8],
+    as_secret: &SecretKey,
+    salt: &[u8; 16],
+) -> Result<Vec<u8>, WebPushError> {
+    let ua_public_bytes = ua_public.to_encoded_point(false);
+    let as_public_bytes = as_secret.public_key().to_encoded_point(false);
+    let shared_secret =
+        p256::ecdh::diffie_hellman(as_secret.to_nonzero_scalar(), ua_public.as_affine());
+
+    let mut key_info = b"WebPush: info\0".to_vec();
+    key_info.extend_from_slice(ua_public_bytes.as_bytes());
+    key_info.extend_from_slice(as_public_bytes.as_bytes());
+    let mut ikm = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(auth_secret), shared_secret.raw_secret_bytes())
+        .expand(&key_info, &mut ikm)
+        .map_err(|_| WebPushError::Encryption)?;
+
+    let hkdf = Hkdf::<Sha256>::new(Some(salt.as_slice()), &ikm);
+    let mut cek = [0u8; 16];
+    let mut nonce = [0u8; 12];
+    hkdf.expand(b"Content-Encoding: aes128gcm\0", &mut cek)
+        .map_err(|_| WebPushError::Encryption)?;
+    hkdf.expand(b"Content-Encoding: nonce\0", &mut nonce)
+        .map_err(|_| WebPushError::Encryption)?;
+
+    // A single record, so it ends with the last-record delimiter and no padding
+    let mut plaintext = payload.to_vec();
+    plaintext.push(2);
+    let ciphertext = Aes128Gcm::new(Key::<Aes128Gcm>::from_slice(&cek))
+        .encrypt(Nonce::from_slice(&nonce), plaintext.as_slice())
+        .map_err(|_| WebPushError::Encryption)?;
+
+    let as_public = as_public_bytes.as_bytes();
+    let mut body = Vec::with_capacity(16 + 4 + 1 + as_public.len() + ciphertext.len());
+    body.extend_from_slice(salt);
+    body.extend_from_slice(&RECORD_SIZE.to_be_bytes());
+    body.push(as_public.len() as u8);
+    body.extend_from_slice(as_public);
+    body.extend_from_slice(&ciphertext);
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use p256::ecdsa::{VerifyingKey, signature::Verifier};
+
+    use super::*;
+
+    /// Decrypt as the browser would, with its private key
+    fn decrypt(body: &[u8], ua_secret: &SecretKey, auth_secret: &[u8]) -> Vec<u8> {
+        let salt = &body[..16];
+        let id_len = body[20] as usize;
+        let as_public = PublicKey::from_sec1_bytes(&body[21..21 + id_len]).unwrap();
+        let ciphertext = &body[21 + id_len..];
+
+        let shared_secret =
+            p256::ecdh::diffie_hellman(ua_secret.to_nonzero_scalar(), as_public.as_affine());
+        let mut key_info = b"WebPush: info\0".to_vec();
+        key_info.extend_from_slice(ua_secret.public_key().to_encoded_point(false).as_bytes());
+        key_info.extend_from_slice(as_public.to_encoded_point(false).as_bytes());
+        let mut ikm = [0u8; 32];
+        Hkdf::<Sha256>::new(Some(auth_secret), shared_secret.raw_secret_bytes())
+            .expand(&key_info, &mut ikm)
+            .unwrap();
+        let hkdf = Hkdf::<Sha256>::new(Some(salt), &ikm);
+        let mut cek = [0u8; 16];
+        let mut nonce = [0u8; 12];
+        hkdf.expand(b"Content-Encoding: aes128gcm\0", &mut cek)
+            .unwrap();
+        hkdf.expand(b"Content-Encoding: nonce\0", &mut nonce)
+            .unwrap();
+        Aes128Gcm::new(Key::<Aes128Gcm>::from_slice(&cek))
+            .decrypt(Nonce::from_slice(&nonce), ciphertext)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let ua_secret = SecretKey::random(&mut OsRng);
+        let auth_secret = [7u8; 16];
+        let payload = br#"{"title":"Task completed"}"#;
+
+        let body = encrypt(payload, &ua_secret.public_key(), &auth_secret).unwrap();
+        assert_eq!(&body[16..20], &RECORD_SIZE.to_be_bytes());
+        assert_eq!(body[20], 65);
+
+        let plaintext = decrypt(&body, &ua_secret, &auth_secret);
+        assert_eq!(plaintext.last(), Some(&2));
+        assert_eq!(&plaintext[..plaintext.len() - 1], payload);
+    }
+
+    /// The example of RFC 8291, Appendix A
+    #[test]
+    fn test_encrypt_matches_rfc_8291_example() {
+        let decode = |value: &str| URL_SAFE_NO_PAD.decode(value).unwrap();
+        let as_secret =
+            SecretKey::from_slice(&decode("yfWPiYE-n46HLnH0KqZOF1fJJU3MYrct3AELtAQ-oRw")).unwrap();
+        let ua_public = PublicKey::from_sec1_bytes(&decode(
+            "BCVxsr7N_eNgVRqvHtD0zTZsEc6-VV-JvLexhqUzORcxaOzi6-AYWXvTBHm4bjyPjs7Vd8pZGH6SRpkNtoIAiw4",
+        ))
+        .unwrap();
+        let auth_secret = decode("BTBZMqHH6r4Tts7J_aSIgg");
+        let salt: [u8; 16] = decode("DGv6ra1nlYgDCS1FRnbzlw").try_into().unwrap();
+
+        let body = encrypt_with(
+            b"When I grow up, I want to be a watermelon",
+            &ua_public,
+            &auth_secret,
+            &as_secret,
+            &salt,
+        )
+        .unwrap();
+
+        assert_eq!(
+            URL_SAFE_NO_PAD.encode(body),
+            "DGv6ra1nlYgDCS1FRnbzlwAAEABBBP4z9KsN6nGRTbVYI_c7VJSPQTBtkgcy27mlmlMoZIIgDll6e3vCYLocInmYWAmS6TlzAC8wEqKK6PBru3jl7A_yl95bQpu6cVPTpK4Mqgkf1CXztLVBSt2Ks3oZwbuwXPXLWyouBWLVWGNWQexSgSxsj_Qulcy4a-fN"
+        );
+    }
+
+    #[test]
+    fn test_vapid_authorization_is_signed_for_the_origin() {
+        let key = SigningKey::random(&mut OsRng);
+        let header = vapid_authorization(
+            "https://fcm.googleapis.com/fcm/send/abc",
+            &key,
+            "mailto:admin@example.com",
+            1_700_000_000,
+        )
+        .unwrap();
+
+        let (token, public_key) = header
+            .strip_prefix("vapid t=")
+            .and_then(|rest| rest.split_once(", k="))
+            .unwrap();
+        assert_eq!(public_key, public_key_of(&key));
+
+        let (signing_input, signature) = token.rsplit_once('.').unwrap();
+        let claims = signing_input.split('.').nth(1).unwrap();
+        let claims: serde_json::Value =
+            serde_json::from_slice(&URL_SAFE_NO_PAD.decode(claims).unwrap()).unwrap();
+        assert_eq!(claims["aud"], "https://fcm.googleapis.com");
+        assert_eq!(claims["exp"], 1_700_000_000 + VAPID_TOKEN_SECS);
+        assert_eq!(claims["sub"], "mailto:admin@example.com");
+
+        let signature = Signature::from_slice(&URL_SAFE_NO_PAD.decode(signature).unwrap()).unwrap();
+        VerifyingKey::from(&key)
+            .verify(signing_input.as_bytes(), &signature)
+            .unwrap();
+    }
+}
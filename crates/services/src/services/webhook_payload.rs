@@ -0,0 +1,259 @@
+//! Payload formats for outbound integrations.
+//!
+//! An event is described once as a [`WebhookEvent`] and rendered per endpoint
+//! in the format that endpoint expects, so one dispatcher can post to Slack,
+//! Discord, CloudEvents consumers and plain JSON receivers alike. Endpoints may
+//! also list the fields of the event data they want; everything else is left
+//! out of the payload.
+
+use chrono::{DateTime, Utc};
+pub use db::models::webhook_endpoint::WebhookPayloadFormat;
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+use uuid::Uuid;
+
+/// `source` attribute of CloudEvents payloads
+const CLOUDEVENTS_SOURCE: &str = "vibe-kanban";
+/// Prefix of CloudEvents `type` attributes, per the reverse-DNS convention
+const CLOUDEVENTS_TYPE_PREFIX: &str = "dev.vibekanban.";
+/// Slack allows at most 10 fields per section block
+const SLACK_MAX_FIELDS: usize = 10;
+/// Discord allows at most 25 fields per embed
+const DISCORD_MAX_FIELDS: usize = 25;
+/// Discord rejects embed field values longer than this
+const DISCORD_FIELD_VALUE_MAX: usize = 1024;
+
+/// Something that happened, before it is rendered for an endpoint
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WebhookEvent {
+    pub id: Uuid,
+    /// Dotted event name, e.g. `task.status_changed`
+    pub event_type: String,
+    pub occurred_at: DateTime<Utc>,
+    /// One-line description used as the message text of chat formats
+    pub summary: String,
+    pub data: Value,
+}
+
+/// `data` reduced to the listed fields. Fields are dotted paths into nested
+/// objects (`task.title`); an empty list keeps everything.
+pub fn filter_fields(data: &Value, fields: &[String]) -> Value {
+    if fields.is_empty() {
+        return data.clone();
+    }
+    let mut filtered = Value::Object(Map::new());
+    for field in fields {
+        let path: Vec<&str> = field.split('.').filter(|key| !key.is_empty()).collect();
+        if let Some(value) = lookup(data, &path) {
+            insert(&mut filtered, &path, value.clone());
+        }
+    }
+    filtered
+}
+
+fn lookup<'a>(value: &'a Value, path: &[&str]) -> Option<&'a Value> {
+    path.iter().try_fold(value, |value, key| value.get(key))
+}
+
+fn insert(target: &mut Value, path: &[&str], value: Value) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+    let mut current = target;
+    for key in parents {
+        let Value::Object(map) = current else {
+            return;
+        };
+        current = map
+            .entry(key.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    if let Value::Object(map) = current {
+        map.insert(last.to_string(), value);
+    }
+}
+
+/// Leaf values of `data` keyed by their dotted path, for formats that show
+/// data as a list of name/value pairs
+fn flatten(data: &Value) -> Vec<(String, String)> {
+    fn walk(prefix: &str, value: &Value, out: &mut Vec<(String, String)>) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map {
+                    let path = if prefix.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{prefix}.{key}")
+                    };
+                    walk(&path, value, out);
+                }
+            }
+            Value::Null => {}
+            Value::String(s) => out.push((prefix.to_string(), s.clone())),
+            other => out.push((prefix.to_string(), other.to_string())),
+        }
+    }
+
+    let mut out = Vec::new();
+    walk("", data, &mut out);
+    out
+}
+
+fn truncate(value: &str, max_chars: usize) -> String {
+    if value.chars().count() <= max_chars {
+        return value.to_string();
+    }
+    let mut truncated: String = value.chars().take(max_chars - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Body to post for `event` to an endpoint using `format` and `fields`
+pub fn render_payload(
+    event: &WebhookEvent,
+    format: WebhookPayloadFormat,
+    fields: &[String],
+) -> Value {
+    let data = filter_fields(&event.data, fields);
+    match format {
+        WebhookPayloadFormat::Native => json!({
+            "id": event.id,
+            "type": event.event_type,
+            "occurred_at": event.occurred_at,
+            "data": data,
+        }),
+        WebhookPayloadFormat::Slack => {
+            let mut blocks = vec![json!({
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": event.summary },
+            })];
+            let fields: Vec<Value> = flatten(&data)
+                .into_iter()
+                .take(SLACK_MAX_FIELDS)
+                .map(|(name, value)| {
+                    json!({ "type": "mrkdwn", "text": format!("*{name}*\n{value}") })
+                })
+                .collect();
+            if !fields.is_empty() {
+                blocks.push(json!({ "type": "section", "fields": fields }));
+            }
+            // `text` is the notification fallback when blocks are present
+            json!({ "text": event.summary, "blocks": blocks })
+        }
+        WebhookPayloadFormat::Discord => {
+            let fields: Vec<Value> = flatten(&data)
+                .into_iter()
+                .take(DISCORD_MAX_FIELDS)
+                .map(|(name, value)| {
+                    json!({
+                        "name": name,
+                        "value": truncate(&value, DISCORD_FIELD_VALUE_MAX),
+                        "inline": true,
+                    })
+                })
+                .collect();
+            json!({
+                "content": event.summary,
+                "embeds": [{
+                    "title": event.event_type,
+                    "timestamp": event.occurred_at,
+                    "fields": fields,
+                }],
+            })
+        }
+        WebhookPayloadFormat::CloudEvents => json!({
+            "specversion": "1.0",
+            "id": event.id,
+            "source": CLOUDEVENTS_SOURCE,
+            "type": format!("{CLOUDEVENTS_TYPE_PREFIX}{}", event.event_type),
+            "time": event.occurred_at,
+            "datacontenttype": "application/json",
+            "data": data,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event() -> WebhookEvent {
+        WebhookEvent {
+            id: Uuid::nil(),
+            event_type: "task.status_changed".to_string(),
+            occurred_at: "2026-02-01T12:00:00Z".parse().unwrap(),
+            summary: "Fix login moved to done".to_string(),
+            data: json!({
+                "task": { "id": "t1", "title": "Fix login", "description": null },
+                "from": "inreview",
+                "to": "done",
+            }),
+        }
+    }
+
+    fn fields(fields: &[&str]) -> Vec<String> {
+        fields.iter().map(|field| field.to_string()).collect()
+    }
+
+    #[test]
+    fn test_filter_fields() {
+        let data = event().data;
+
+        assert_eq!(filter_fields(&data, &[]), data);
+        assert_eq!(
+            filter_fields(
+                &data,
+                &fields(&["task.title", "to", "missing", "to.nested"])
+            ),
+            json!({ "task": { "title": "Fix login" }, "to": "done" })
+        );
+    }
+
+    #[test]
+    fn test_render_native_and_cloudevents() {
+        let event = event();
+        let only_to = fields(&["to"]);
+
+        assert_eq!(
+            render_payload(&event, WebhookPayloadFormat::Native, &only_to),
+            json!({
+                "id": Uuid::nil(),
+                "type": "task.status_changed",
+                "occurred_at": "2026-02-01T12:00:00Z",
+                "data": { "to": "done" },
+            })
+        );
+        let cloud_event = render_payload(&event, WebhookPayloadFormat::CloudEvents, &only_to);
+        assert_eq!(cloud_event["specversion"], "1.0");
+        assert_eq!(cloud_event["type"], "dev.vibekanban.task.status_changed");
+        assert_eq!(cloud_event["source"], "vibe-kanban");
+        assert_eq!(cloud_event["data"], json!({ "to": "done" }));
+    }
+
+    #[test]
+    fn test_render_chat_formats_flatten_data() {
+        let event = event();
+        let selected = fields(&["task.title", "to"]);
+
+        let slack = render_payload(&event, WebhookPayloadFormat::Slack, &selected);
+        assert_eq!(slack["text"], "Fix login moved to done");
+        assert_eq!(
+            slack["blocks"][1]["fields"],
+            json!([
+                { "type": "mrkdwn", "text": "*task.title*\nFix login" },
+                { "type": "mrkdwn", "text": "*to*\ndone" },
+            ])
+        );
+
+        let discord = render_payload(&event, WebhookPayloadFormat::Discord, &[]);
+        assert_eq!(discord["content"], "Fix login moved to done");
+        let names: Vec<&str> = discord["embeds"][0]["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|field| field["name"].as_str().unwrap())
+            .collect();
+        // Null values are left out
+        assert_eq!(names, vec!["task.id", "task.title", "from", "to"]);
+    }
+}
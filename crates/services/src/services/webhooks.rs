@@ -0,0 +1,389 @@
+//! Delivery of outgoing webhooks.
+//!
+//! Database triggers queue a [`WebhookDelivery`] per subscribed endpoint for
+//! every task creation, task status change and finished coding agent run.
+//! [`WebhookWorker`] renders each one in its endpoint's format, signs it and
+//! posts it, retrying with exponential backoff. The rendered body is kept on
+//! the delivery, so retries and redeliveries send exactly the same payload.
+//!
+//! Each request carries these headers:
+//! - `X-Vibe-Kanban-Event`: the event type, e.g. `task.created`
+//! - `X-Vibe-Kanban-Delivery`: the delivery ID, which is also the event ID
+//! - `X-Vibe-Kanban-Timestamp`: Unix time the request was signed at
+//! - `X-Vibe-Kanban-Signature`: `sha256=` followed by the hex HMAC-SHA256 of
+//!   `{timestamp}.{body}` under the endpoint's secret. For a while after the
+//!   secret is rotated, a second, comma-separated signature under the previous
+//!   secret follows it; receivers should accept a request if any one matches.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use db::{
+    DBService,
+    models::{
+        execution_process::ExecutionProcess,
+        project::Project,
+        session::Session,
+        task::Task,
+        webhook_delivery::WebhookDelivery,
+        webhook_endpoint::{WebhookEndpoint, WebhookEventType},
+        workspace::Workspace,
+    },
+};
+use hmac::{Hmac, Mac};
+use serde_json::{Value, json};
+use sha2::Sha256;
+use sqlx::SqlitePool;
+use thiserror::Error;
+use tokio::time::interval;
+use tracing::{debug, error, info, warn};
+
+use crate::services::{
+    http_notifier::{self, StatusError},
+    outbox::backoff_secs,
+    webhook_payload::{WebhookEvent, render_payload},
+};
+
+/// Deliveries fetched per poll
+const BATCH_SIZE: i64 = 50;
+/// Deliveries are abandoned after this many failed attempts
+const MAX_ATTEMPTS: i64 = 8;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+pub const EVENT_HEADER: &str = "X-Vibe-Kanban-Event";
+pub const DELIVERY_HEADER: &str = "X-Vibe-Kanban-Delivery";
+pub const TIMESTAMP_HEADER: &str = "X-Vibe-Kanban-Timestamp";
+pub const SIGNATURE_HEADER: &str = "X-Vibe-Kanban-Signature";
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug, Error)]
+pub enum WebhookError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error(transparent)]
+    Status(#[from] StatusError),
+}
+
+impl WebhookError {
+    fn response_status(&self) -> Option<i64> {
+        match self {
+            Self::Status(error) => Some(i64::from(error.status)),
+            _ => None,
+        }
+    }
+}
+
+/// `sha256=` followed by the hex HMAC-SHA256 of `{timestamp}.{body}`
+pub fn sign_payload(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{timestamp}.{body}").as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Signatures for the signature header: under the current secret, then under
+/// the previous one while it is within its grace period
+fn signature_header(endpoint: &WebhookEndpoint, timestamp: i64, body: &str) -> String {
+    let signature = sign_payload(&endpoint.secret, timestamp, body);
+    match endpoint.active_previous_secret() {
+        Some(previous) => format!("{signature}, {}", sign_payload(previous, timestamp, body)),
+        None => signature,
+    }
+}
+
+/// Background worker that sends pending webhook deliveries
+pub struct WebhookWorker {
+    db: DBService,
+    http: reqwest::Client,
+    poll_interval: Duration,
+}
+
+impl WebhookWorker {
+    pub async fn spawn(db: DBService) -> tokio::task::JoinHandle<()> {
+        let worker = Self {
+            db,
+            http: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .user_agent("vibe-kanban-webhooks")
+                .build()
+                .unwrap_or_default(),
+            poll_interval: Duration::from_secs(2),
+        };
+        tokio::spawn(async move {
+            worker.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        info!(
+            "Starting webhook worker with interval {:?}",
+            self.poll_interval
+        );
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.process_due().await {
+                error!("Error processing webhook deliveries: {}", e);
+            }
+        }
+    }
+
+    async fn process_due(&self) -> Result<(), WebhookError> {
+        let pool = &self.db.pool;
+        let deliveries = WebhookDelivery::find_due(pool, BATCH_SIZE).await?;
+
+        for delivery in deliveries {
+            // Deliveries are removed along with their endpoint
+            let Some(endpoint) = WebhookEndpoint::find_by_id(pool, delivery.endpoint_id).await?
+            else {
+                continue;
+            };
+            if !endpoint.enabled {
+                WebhookDelivery::mark_failed(pool, delivery.id, None, "Endpoint disabled", None)
+                    .await?;
+                continue;
+            }
+
+            match self.deliver(&endpoint, &delivery).await {
+                Ok(status) => {
+                    debug!(
+                        "Delivered webhook {} ({}) to {}",
+                        delivery.id,
+                        delivery.event_type.as_str(),
+                        endpoint.url
+                    );
+                    WebhookDelivery::mark_delivered(pool, delivery.id, i64::from(status)).await?;
+                }
+                Err(WebhookError::Database(e)) => return Err(e.into()),
+                Err(e) if delivery.attempts + 1 >= MAX_ATTEMPTS => {
+                    warn!(
+                        "Giving up on webhook {} to {} after {} attempts: {}",
+                        delivery.id,
+                        endpoint.url,
+                        delivery.attempts + 1,
+                        e
+                    );
+                    WebhookDelivery::mark_failed(
+                        pool,
+                        delivery.id,
+                        e.response_status(),
+                        &e.to_string(),
+                        None,
+                    )
+                    .await?;
+                }
+                Err(e) => {
+                    let retry_in = backoff_secs(delivery.attempts);
+                    debug!(
+                        "Webhook {} to {} failed, retrying in {}s: {}",
+                        delivery.id, endpoint.url, retry_in, e
+                    );
+                    WebhookDelivery::mark_failed(
+                        pool,
+                        delivery.id,
+                        e.response_status(),
+                        &e.to_string(),
+                        Some(retry_in),
+                    )
+                    .await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Post the delivery to its endpoint, returning the response status
+    async fn deliver(
+        &self,
+        endpoint: &WebhookEndpoint,
+        delivery: &WebhookDelivery,
+    ) -> Result<u16, WebhookError> {
+        let body = match &delivery.payload {
+            Some(payload) => payload.clone(),
+            None => {
+                let event = build_event(&self.db.pool, delivery).await?;
+                let payload =
+                    render_payload(&event, endpoint.format, &endpoint.fields.0).to_string();
+                WebhookDelivery::set_payload(&self.db.pool, delivery.id, &payload).await?;
+                payload
+            }
+        };
+
+        let timestamp = Utc::now().timestamp();
+        let response = self
+            .http
+            .post(&endpoint.url)
+            .header(
+                reqwest::header::CONTENT_TYPE,
+                endpoint.format.content_type(),
+            )
+            .header(EVENT_HEADER, delivery.event_type.as_str())
+            .header(DELIVERY_HEADER, delivery.id.to_string())
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(
+                SIGNATURE_HEADER,
+                signature_header(endpoint, timestamp, &body),
+            )
+            .body(body)
+            .send()
+            .await?;
+
+        let response = http_notifier::check_status(response).await?;
+        Ok(response.status().as_u16())
+    }
+}
+
+/// Describe the delivery's event from the current state of its task. Status
+/// changes carry the statuses recorded when the change happened.
+async fn build_event(
+    pool: &SqlitePool,
+    delivery: &WebhookDelivery,
+) -> Result<WebhookEvent, sqlx::Error> {
+    let task = Task::find_by_id(pool, delivery.task_id).await?;
+    let project = match &task {
+        Some(task) => Project::find_by_id(pool, task.project_id).await?,
+        None => None,
+    };
+    let title = task
+        .as_ref()
+        .map(|task| task.title.clone())
+        .unwrap_or_else(|| delivery.task_id.to_string());
+
+    let mut data = json!({
+        "task": match &task {
+            Some(task) => json!({
+                "id": task.id,
+                "project_id": task.project_id,
+                "title": task.title,
+                "description": task.description,
+                "status": task.status,
+            }),
+            None => json!({ "id": delivery.task_id }),
+        },
+        "project": project.map(|project| json!({ "id": project.id, "name": project.name })),
+    });
+
+    let summary = match delivery.event_type {
+        WebhookEventType::TaskCreated => format!("Task created: {title}"),
+        WebhookEventType::TaskStatusChanged => {
+            data["from"] = json!(delivery.from_status);
+            data["to"] = json!(delivery.to_status);
+            format!(
+                "{title} moved from {} to {}",
+                delivery.from_status.as_deref().unwrap_or("?"),
+                delivery.to_status.as_deref().unwrap_or("?")
+            )
+        }
+        WebhookEventType::AttemptFinished => {
+            data["attempt"] = attempt_data(pool, delivery).await?;
+            format!(
+                "Attempt at {title} {}",
+                match delivery.to_status.as_deref() {
+                    Some("completed") => "completed",
+                    Some("killed") => "was stopped",
+                    _ => "failed",
+                }
+            )
+        }
+    };
+
+    Ok(WebhookEvent {
+        id: delivery.id,
+        event_type: delivery.event_type.as_str().to_string(),
+        occurred_at: delivery.created_at,
+        summary,
+        data,
+    })
+}
+
+async fn attempt_data(pool: &SqlitePool, delivery: &WebhookDelivery) -> Result<Value, sqlx::Error> {
+    let process = match delivery.execution_process_id {
+        Some(id) => ExecutionProcess::find_by_id(pool, id).await?,
+        None => None,
+    };
+    let session = match &process {
+        Some(process) => Session::find_by_id(pool, process.session_id).await?,
+        None => None,
+    };
+    let workspace = match &session {
+        Some(session) => Workspace::find_by_id(pool, session.workspace_id).await?,
+        None => None,
+    };
+
+    Ok(json!({
+        "execution_process_id": delivery.execution_process_id,
+        "status": delivery.to_status,
+        "exit_code": process.and_then(|process| process.exit_code),
+        "executor": session.and_then(|session| session.executor),
+        "workspace_id": workspace.as_ref().map(|workspace| workspace.id),
+        "branch": workspace.map(|workspace| workspace.branch),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_covers_timestamp_and_body() {
+        let signature = sign_payload("whsec_test", 1_700_000_000, r#"{"id":1}"#);
+
+        let mut mac = HmacSha256::new_from_slice(b"whsec_test").unwrap();
+        mac.update(br#"1700000000.{"id":1}"#);
+        assert_eq!(
+            signature,
+            format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+        );
+        assert_ne!(
+            signature,
+            sign_payload("whsec_test", 1_700_000_001, r#"{"id":1}"#)
+        );
+        assert_ne!(
+            signature,
+            sign_payload("whsec_other", 1_700_000_000, r#"{"id":1}"#)
+        );
+    }
+
+    #[test]
+    fn test_previous_secret_signs_during_grace_period() {
+        let mut endpoint = WebhookEndpoint {
+            id: uuid::Uuid::new_v4(),
+            url: "https://example.com/hook".to_string(),
+            secret: "whsec_new".to_string(),
+            events: sqlx::types::Json(Vec::new()),
+            format: Default::default(),
+            fields: sqlx::types::Json(Vec::new()),
+            enabled: true,
+            previous_secret: Some("whsec_old".to_string()),
+            previous_secret_expires_at: Some(Utc::now() + chrono::Duration::hours(1)),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let new = sign_payload("whsec_new", 1_700_000_000, "{}");
+        let old = sign_payload("whsec_old", 1_700_000_000, "{}");
+
+        assert_eq!(
+            signature_header(&endpoint, 1_700_000_000, "{}"),
+            format!("{new}, {old}")
+        );
+
+        endpoint.previous_secret_expires_at = Some(Utc::now() - chrono::Duration::seconds(1));
+        assert_eq!(signature_header(&endpoint, 1_700_000_000, "{}"), new);
+    }
+
+    #[test]
+    fn test_status_errors_keep_response_status() {
+        let error = WebhookError::Status(StatusError {
+            status: 503,
+            body: "down for maintenance".to_string(),
+        });
+        assert_eq!(error.response_status(), Some(503));
+        assert_eq!(error.to_string(), "HTTP 503: down for maintenance");
+    }
+}
@@ -0,0 +1,439 @@
+//! Weekly project report by email.
+//!
+//! Once a week, on the day and hour in `EmailConfig::weekly_digest`, each
+//! project with activity gets an HTML email covering the last seven days:
+//! tasks completed and their cycle time, PRs still open and failed agent
+//! runs. The email goes out through the SMTP server of the email
+//! notifications.
+
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+use chrono::{DateTime, Datelike, Local, NaiveDate, Timelike, Utc};
+use db::{
+    DBService,
+    models::{
+        execution_process::{ExecutionProcess, FailedAgentRun},
+        merge::{Merge, OpenPr},
+        project::Project,
+        task_status_history::{CompletedTask, TaskStatusChange},
+    },
+};
+use sqlx::SqlitePool;
+use tokio::{sync::RwLock, time::interval};
+use tracing::{debug, error, info};
+use uuid::Uuid;
+
+use crate::services::{
+    config::{Config, WeeklyDigestConfig},
+    email::EmailNotifier,
+    telegram::escape_html,
+};
+
+/// How far back a report looks
+const REPORT_PERIOD: chrono::Duration = chrono::Duration::days(7);
+/// Maximum number of items listed per section
+const MAX_ITEMS_PER_SECTION: usize = 20;
+
+/// One project's week
+#[derive(Debug)]
+pub struct ProjectReport {
+    pub project_name: String,
+    pub since: DateTime<Utc>,
+    pub completed: Vec<CompletedTask>,
+    pub open_prs: Vec<OpenPr>,
+    pub failed_runs: Vec<FailedAgentRun>,
+}
+
+impl ProjectReport {
+    pub fn is_empty(&self) -> bool {
+        self.completed.is_empty() && self.open_prs.is_empty() && self.failed_runs.is_empty()
+    }
+
+    /// Median time from creation to completion of the completed tasks
+    pub fn median_cycle_time(&self) -> Option<chrono::Duration> {
+        let mut cycle_times: Vec<_> = self
+            .completed
+            .iter()
+            .map(|task| task.completed_at - task.created_at)
+            .collect();
+        if cycle_times.is_empty() {
+            return None;
+        }
+        cycle_times.sort();
+        let middle = cycle_times.len() / 2;
+        Some(if cycle_times.len() % 2 == 0 {
+            (cycle_times[middle - 1] + cycle_times[middle]) / 2
+        } else {
+            cycle_times[middle]
+        })
+    }
+
+    /// Failed runs per task, most failures first
+    fn failures_by_task(&self) -> Vec<(&str, usize)> {
+        let mut counts: BTreeMap<Uuid, (&str, usize)> = BTreeMap::new();
+        for run in &self.failed_runs {
+            counts
+                .entry(run.task_id)
+                .or_insert((run.task_title.as_str(), 0))
+                .1 += 1;
+        }
+        let mut failures: Vec<_> = counts.into_values().collect();
+        failures.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        failures
+    }
+
+    pub fn subject(&self) -> String {
+        format!("[vibe-kanban] Weekly report: {}", self.project_name)
+    }
+
+    pub fn plain_body(&self) -> String {
+        let mut body = format!(
+            "Weekly report for {}, since {}\n",
+            self.project_name,
+            self.since.format("%Y-%m-%d")
+        );
+
+        body.push_str(&format!("\nTasks completed: {}\n", self.completed.len()));
+        if let Some(cycle_time) = self.median_cycle_time() {
+            body.push_str(&format!(
+                "Median cycle time: {}\n",
+                format_duration(cycle_time)
+            ));
+        }
+        for task in self.completed.iter().take(MAX_ITEMS_PER_SECTION) {
+            body.push_str(&format!("- {}\n", task.title));
+        }
+        push_more(&mut body, self.completed.len(), "\n");
+
+        body.push_str(&format!("\nOpen PRs: {}\n", self.open_prs.len()));
+        for pr in self.open_prs.iter().take(MAX_ITEMS_PER_SECTION) {
+            body.push_str(&format!(
+                "- #{} {} ({})\n",
+                pr.pr_number, pr.task_title, pr.pr_url
+            ));
+        }
+        push_more(&mut body, self.open_prs.len(), "\n");
+
+        let failures = self.failures_by_task();
+        body.push_str(&format!("\nFailed attempts: {}\n", self.failed_runs.len()));
+        for (title, count) in failures.iter().take(MAX_ITEMS_PER_SECTION) {
+            body.push_str(&format!("- {title} ({count})\n"));
+        }
+        push_more(&mut body, failures.len(), "\n");
+        body
+    }
+
+    pub fn html_body(&self) -> String {
+        let mut html = format!(
+            "<html><body><h2>Weekly report: {}</h2><p style=\"color: #888\">Since {}</p>",
+            escape_html(&self.project_name),
+            self.since.format("%Y-%m-%d")
+        );
+
+        html.push_str(&format!(
+            "<h3>Tasks completed ({})</h3>",
+            self.completed.len()
+        ));
+        if let Some(cycle_time) = self.median_cycle_time() {
+            html.push_str(&format!(
+                "<p>Median cycle time: <b>{}</b></p>",
+                format_duration(cycle_time)
+            ));
+        }
+        push_list(
+            &mut html,
+            self.completed.len(),
+            self.completed.iter().map(|task| escape_html(&task.title)),
+        );
+
+        html.push_str(&format!("<h3>Open PRs ({})</h3>", self.open_prs.len()));
+        push_list(
+            &mut html,
+            self.open_prs.len(),
+            self.open_prs.iter().map(|pr| {
+                format!(
+                    "<a href=\"{}\">#{}</a> {}",
+                    escape_html(&pr.pr_url),
+                    pr.pr_number,
+                    escape_html(&pr.task_title)
+                )
+            }),
+        );
+
+        let failures = self.failures_by_task();
+        html.push_str(&format!(
+            "<h3>Failed attempts ({})</h3>",
+            self.failed_runs.len()
+        ));
+        push_list(
+            &mut html,
+            failures.len(),
+            failures
+                .iter()
+                .map(|(title, count)| format!("{} ({count})", escape_html(title))),
+        );
+
+        html.push_str("</body></html>");
+        html
+    }
+}
+
+/// Append a list of up to `MAX_ITEMS_PER_SECTION` of `total` items
+fn push_list(html: &mut String, total: usize, items: impl Iterator<Item = String>) {
+    if total == 0 {
+        html.push_str("<p style=\"color: #888\">None</p>");
+        return;
+    }
+    html.push_str("<ul>");
+    for item in items.take(MAX_ITEMS_PER_SECTION) {
+        html.push_str(&format!("<li>{item}</li>"));
+    }
+    html.push_str("</ul>");
+    push_more(html, total, "");
+}
+
+fn push_more(text: &mut String, total: usize, end: &str) {
+    if total > MAX_ITEMS_PER_SECTION {
+        text.push_str(&format!("…and {} more{end}", total - MAX_ITEMS_PER_SECTION));
+    }
+}
+
+/// A duration as days and hours, or hours and minutes when under a day
+fn format_duration(duration: chrono::Duration) -> String {
+    let minutes = duration.num_minutes().max(0);
+    let (days, hours, minutes) = (minutes / (24 * 60), minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else {
+        format!("{hours}h {minutes}m")
+    }
+}
+
+/// Reports for the week before `now`, one per project with any activity
+pub async fn build_reports(
+    pool: &SqlitePool,
+    now: DateTime<Utc>,
+) -> Result<Vec<ProjectReport>, sqlx::Error> {
+    let since = now - REPORT_PERIOD;
+    let completed = TaskStatusChange::find_completed_since(pool, since).await?;
+    let open_prs = Merge::find_open_prs_with_task(pool).await?;
+    let failed_runs = ExecutionProcess::find_failed_agent_runs_since(pool, since).await?;
+
+    let mut reports: Vec<_> = Project::find_all(pool)
+        .await?
+        .into_iter()
+        .map(|project| ProjectReport {
+            since,
+            completed: completed
+                .iter()
+                .filter(|task| task.project_id == project.id)
+                .cloned()
+                .collect(),
+            open_prs: open_prs
+                .iter()
+                .filter(|pr| pr.project_id == project.id)
+                .cloned()
+                .collect(),
+            failed_runs: failed_runs
+                .iter()
+                .filter(|run| run.project_id == project.id)
+                .cloned()
+                .collect(),
+            project_name: project.name,
+        })
+        .collect();
+    reports.retain(|report| !report.is_empty());
+    Ok(reports)
+}
+
+/// Whether the report should be sent at local time `now`, given the date it
+/// was last sent
+fn is_due(config: &WeeklyDigestConfig, now: DateTime<Local>, last_sent: Option<NaiveDate>) -> bool {
+    config.enabled
+        && now.weekday() == config.day.weekday()
+        && now.hour() == u32::from(config.hour)
+        && last_sent != Some(now.date_naive())
+}
+
+/// Service that emails the weekly reports
+pub struct WeeklyDigestService {
+    db: DBService,
+    config: Arc<RwLock<Config>>,
+    email: EmailNotifier,
+    poll_interval: Duration,
+    last_sent: Option<NaiveDate>,
+}
+
+impl WeeklyDigestService {
+    pub async fn spawn(
+        db: DBService,
+        config: Arc<RwLock<Config>>,
+        email: EmailNotifier,
+    ) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            db,
+            config,
+            email,
+            poll_interval: Duration::from_secs(60),
+            last_sent: None,
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(mut self) {
+        info!(
+            "Starting weekly digest service with interval {:?}",
+            self.poll_interval
+        );
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+
+            let config = self.config.read().await.email.weekly_digest.clone();
+            let now = Local::now();
+            if !is_due(&config, now, self.last_sent) {
+                continue;
+            }
+
+            // Reports that fail are not retried, so the others are not sent twice
+            self.last_sent = Some(now.date_naive());
+            if let Err(e) = self.send_reports(&config).await {
+                error!("Failed to build weekly reports: {}", e);
+            }
+        }
+    }
+
+    async fn send_reports(&self, config: &WeeklyDigestConfig) -> Result<(), sqlx::Error> {
+        for report in build_reports(&self.db.pool, Utc::now()).await? {
+            match self
+                .email
+                .send_report(
+                    &config.recipients,
+                    &report.subject(),
+                    report.plain_body(),
+                    report.html_body(),
+                )
+                .await
+            {
+                Ok(()) => debug!("Sent weekly report for {}", report.project_name),
+                Err(e) => error!(
+                    "Failed to send weekly report for {}: {}",
+                    report.project_name, e
+                ),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::services::config::DigestWeekday;
+
+    fn completed(title: &str, hours: i64) -> CompletedTask {
+        let completed_at = Utc::now();
+        CompletedTask {
+            task_id: Uuid::new_v4(),
+            project_id: Uuid::nil(),
+            title: title.to_string(),
+            created_at: completed_at - chrono::Duration::hours(hours),
+            completed_at,
+        }
+    }
+
+    fn failed(task_id: Uuid, title: &str) -> FailedAgentRun {
+        FailedAgentRun {
+            execution_process_id: Uuid::new_v4(),
+            project_id: Uuid::nil(),
+            task_id,
+            task_title: title.to_string(),
+            completed_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_report_sections() {
+        let flaky = Uuid::new_v4();
+        let report = ProjectReport {
+            project_name: "Web <app>".to_string(),
+            since: Utc::now() - REPORT_PERIOD,
+            completed: vec![completed("Fix login", 2), completed("Add dark mode", 50)],
+            open_prs: vec![OpenPr {
+                project_id: Uuid::nil(),
+                task_id: Uuid::new_v4(),
+                task_title: "Add search".to_string(),
+                pr_number: 42,
+                pr_url: "https://github.com/acme/web/pull/42".to_string(),
+                created_at: Utc::now(),
+            }],
+            failed_runs: vec![
+                failed(Uuid::new_v4(), "Upgrade deps"),
+                failed(flaky, "Fix CI"),
+                failed(flaky, "Fix CI"),
+            ],
+        };
+
+        assert_eq!(
+            report.median_cycle_time(),
+            Some(chrono::Duration::hours(26))
+        );
+        assert_eq!(
+            report.failures_by_task(),
+            vec![("Fix CI", 2), ("Upgrade deps", 1)]
+        );
+
+        let plain = report.plain_body();
+        assert!(plain.contains("Tasks completed: 2\nMedian cycle time: 1d 2h\n"));
+        assert!(plain.contains("- #42 Add search (https://github.com/acme/web/pull/42)"));
+        assert!(plain.contains("Failed attempts: 3\n- Fix CI (2)\n"));
+
+        let html = report.html_body();
+        assert!(html.contains("Weekly report: Web &lt;app&gt;"));
+        assert!(
+            html.contains("<a href=\"https://github.com/acme/web/pull/42\">#42</a> Add search")
+        );
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(chrono::Duration::minutes(95)), "1h 35m");
+        assert_eq!(format_duration(chrono::Duration::hours(75)), "3d 3h");
+        assert_eq!(format_duration(chrono::Duration::minutes(-5)), "0h 0m");
+    }
+
+    #[test]
+    fn test_is_due_on_day_and_hour_once() {
+        let config = WeeklyDigestConfig {
+            enabled: true,
+            day: DigestWeekday::Friday,
+            hour: 16,
+            recipients: Vec::new(),
+        };
+        // 2025-01-10 is a Friday
+        let friday = Local.with_ymd_and_hms(2025, 1, 10, 16, 5, 0).unwrap();
+
+        assert!(is_due(&config, friday, None));
+        assert!(!is_due(&config, friday, Some(friday.date_naive())));
+        assert!(!is_due(&config, friday.with_hour(15).unwrap(), None));
+        assert!(!is_due(
+            &config,
+            Local.with_ymd_and_hms(2025, 1, 9, 16, 5, 0).unwrap(),
+            None
+        ));
+        assert!(!is_due(
+            &WeeklyDigestConfig {
+                enabled: false,
+                ..config
+            },
+            friday,
+            None
+        ));
+    }
+}
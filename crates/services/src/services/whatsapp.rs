@@ -0,0 +1,556 @@
+//! WhatsApp integration through the Cloud API.
+//!
+//! Provides:
+//! - Task completion and failure notifications, sent as the approved message
+//!   template named in `Config::whatsapp`, since WhatsApp only delivers
+//!   free-form messages to people who wrote in the last 24 hours
+//! - Commands sent as messages (`tasks`, `newtask`, `help`), from the
+//!   configured recipients only
+//!
+//! Meta delivers incoming messages to `/api/whatsapp/webhook`, signed with
+//! the app secret.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use db::models::{
+    notification_rule::{NotificationChannel, TaskNotificationKind},
+    project::Project,
+    task::{CreateTask, Task, TaskStatus},
+};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use sha2::Sha256;
+use sqlx::SqlitePool;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::services::{
+    config::{Config, WhatsAppConfig},
+    http_notifier,
+    notification_dispatcher::{NotificationEvent, Notifier, NotifierError},
+};
+
+const API_BASE_URL: &str = "https://graph.facebook.com/v21.0";
+
+/// WhatsApp rejects text messages longer than this
+const MAX_TEXT_CHARS: usize = 4096;
+
+/// Most tasks listed by `tasks`
+const TASK_LIST_LIMIT: usize = 20;
+
+const HELP_TEXT: &str = "*vibe-kanban commands*\n\
+    tasks [project] - List a project's tasks\n\
+    newtask [project_id] <title> - Create a task, with an optional description on the following lines\n\
+    help - Show this message\n\n\
+    Without a project, the default project from the WhatsApp settings is used.";
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug, Error)]
+pub enum WhatsAppError {
+    #[error("WhatsApp is not configured: {0}")]
+    NotConfigured(&'static str),
+
+    #[error("WhatsApp request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("WhatsApp returned HTTP {status}: {message}")]
+    Api { status: u16, message: String },
+
+    #[error("Invalid request signature")]
+    InvalidSignature,
+
+    #[error("Invalid verify token")]
+    InvalidVerifyToken,
+
+    #[error("Changes are paused while the instance is in maintenance mode")]
+    Maintenance,
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("Project not found: {0}")]
+    ProjectNotFound(String),
+
+    #[error("No project given and no default project set")]
+    NoDefaultProject,
+
+    #[error("Invalid command: {0}")]
+    InvalidCommand(String),
+}
+
+/// Body of a webhook request; only the parts the bot reads
+#[derive(Debug, Default, Deserialize)]
+pub struct WebhookPayload {
+    #[serde(default)]
+    entry: Vec<WebhookEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WebhookEntry {
+    #[serde(default)]
+    changes: Vec<WebhookChange>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WebhookChange {
+    value: WebhookValue,
+}
+
+#[derive(Debug, Deserialize)]
+struct WebhookValue {
+    /// Missing for delivery status updates
+    #[serde(default)]
+    messages: Vec<IncomingMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IncomingMessage {
+    from: String,
+    /// Set for text messages only
+    text: Option<IncomingText>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IncomingText {
+    body: String,
+}
+
+impl WebhookPayload {
+    /// Sender and text of each text message in the request
+    fn text_messages(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entry
+            .iter()
+            .flat_map(|entry| &entry.changes)
+            .flat_map(|change| &change.value.messages)
+            .filter_map(|message| {
+                let text = message.text.as_ref()?;
+                Some((message.from.as_str(), text.body.as_str()))
+            })
+    }
+}
+
+/// Body of an API error response
+#[derive(Debug, Deserialize)]
+struct ApiErrorResponse {
+    error: ApiErrorDetail,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiErrorDetail {
+    message: String,
+}
+
+/// Credentials for sending, taken from the config
+struct Sender {
+    phone_number_id: String,
+    access_token: String,
+}
+
+impl Sender {
+    fn from_config(config: &WhatsAppConfig) -> Result<Self, WhatsAppError> {
+        let non_empty = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        Ok(Self {
+            phone_number_id: non_empty(&config.phone_number_id)
+                .ok_or(WhatsAppError::NotConfigured("no phone number ID"))?,
+            access_token: non_empty(&config.access_token)
+                .ok_or(WhatsAppError::NotConfigured("no access token"))?,
+        })
+    }
+}
+
+/// The notification template filled in for `task`
+fn template_message(config: &WhatsAppConfig, to: &str, task: &Task, event: &str) -> Value {
+    json!({
+        "messaging_product": "whatsapp",
+        "to": to,
+        "type": "template",
+        "template": {
+            "name": config.template_name,
+            "language": { "code": config.template_language },
+            "components": [{
+                "type": "body",
+                "parameters": [
+                    { "type": "text", "text": task.title },
+                    { "type": "text", "text": event },
+                ],
+            }],
+        },
+    })
+}
+
+fn text_message(to: &str, body: &str) -> Value {
+    let body: String = body.chars().take(MAX_TEXT_CHARS).collect();
+    json!({
+        "messaging_product": "whatsapp",
+        "to": to,
+        "type": "text",
+        "text": { "body": body },
+    })
+}
+
+/// WhatsApp service
+#[derive(Clone)]
+pub struct WhatsAppService {
+    config: Arc<RwLock<Config>>,
+    pool: SqlitePool,
+    http: reqwest::Client,
+}
+
+impl WhatsAppService {
+    pub fn new(config: Arc<RwLock<Config>>, pool: SqlitePool) -> Self {
+        Self {
+            config,
+            pool,
+            http: http_notifier::client(),
+        }
+    }
+
+    /// Answer Meta's check when the webhook is subscribed, by returning
+    /// `challenge` if `verify_token` is the configured one
+    pub async fn verify_subscription(
+        &self,
+        mode: &str,
+        verify_token: &str,
+        challenge: &str,
+    ) -> Result<String, WhatsAppError> {
+        let config = self.config.read().await;
+        match config.whatsapp.verify_token.as_deref() {
+            Some(expected) if mode == "subscribe" && !expected.is_empty() => {
+                if verify_token == expected {
+                    Ok(challenge.to_string())
+                } else {
+                    Err(WhatsAppError::InvalidVerifyToken)
+                }
+            }
+            Some(_) => Err(WhatsAppError::InvalidVerifyToken),
+            None => Err(WhatsAppError::NotConfigured("no verify token")),
+        }
+    }
+
+    /// Check the `X-Hub-Signature-256` of a webhook request against its raw
+    /// body
+    pub async fn verify_signature(
+        &self,
+        body: &[u8],
+        signature: &str,
+    ) -> Result<(), WhatsAppError> {
+        let secret = self
+            .config
+            .read()
+            .await
+            .whatsapp
+            .app_secret
+            .clone()
+            .filter(|secret| !secret.is_empty())
+            .ok_or(WhatsAppError::NotConfigured("no app secret"))?;
+        verify_signature(secret.as_bytes(), body, signature)
+    }
+
+    /// Run the commands in a webhook request and reply to each. Messages
+    /// from numbers that are not recipients are ignored.
+    pub async fn handle_webhook(&self, payload: &WebhookPayload) -> Result<(), WhatsAppError> {
+        let config = self.config.read().await.whatsapp.clone();
+        if !config.enabled {
+            return Ok(());
+        }
+        let sender = Sender::from_config(&config)?;
+        for (from, text) in payload.text_messages() {
+            if !config.recipients.iter().any(|number| number == from) {
+                tracing::debug!("Ignoring WhatsApp message from unknown number");
+                continue;
+            }
+            let reply = match self.handle_command(text).await {
+                Ok(reply) => reply,
+                Err(e) => {
+                    if let WhatsAppError::Database(_) = e {
+                        tracing::error!("Error handling WhatsApp command: {}", e);
+                    }
+                    format!("⚠️ {e}")
+                }
+            };
+            self.post_message(&sender, &text_message(from, &reply))
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn handle_command(&self, text: &str) -> Result<String, WhatsAppError> {
+        let maintenance_mode = self.config.read().await.maintenance_mode;
+        let text = text.trim();
+        let (name, args) = text
+            .split_once(char::is_whitespace)
+            .map(|(name, args)| (name, args.trim()))
+            .unwrap_or((text, ""));
+        match name.trim_start_matches('/').to_lowercase().as_str() {
+            "" | "help" => Ok(HELP_TEXT.to_string()),
+            "tasks" => self.cmd_tasks(args).await,
+            "newtask" if maintenance_mode => Err(WhatsAppError::Maintenance),
+            "newtask" => self.cmd_newtask(args).await,
+            other => Err(WhatsAppError::InvalidCommand(format!(
+                "Unknown command '{other}'. Send 'help' for the list."
+            ))),
+        }
+    }
+
+    /// Handle `tasks` - list a project's tasks, by ID or name
+    async fn cmd_tasks(&self, args: &str) -> Result<String, WhatsAppError> {
+        let project = self.resolve_project(args).await?;
+        let tasks = Task::find_by_project_id_with_attempt_status(&self.pool, project.id).await?;
+        if tasks.is_empty() {
+            return Ok(format!("No tasks in project *{}*.", project.name));
+        }
+
+        let mut message = format!("*Tasks in {}*\n", project.name);
+        for task in tasks.iter().take(TASK_LIST_LIMIT) {
+            message.push_str(&format_task_line(&task.task));
+        }
+        if tasks.len() > TASK_LIST_LIMIT {
+            message.push_str(&format!(
+                "... and {} more tasks",
+                tasks.len() - TASK_LIST_LIMIT
+            ));
+        }
+        Ok(message)
+    }
+
+    /// Handle `newtask` - create a task in the given or the default project
+    async fn cmd_newtask(&self, args: &str) -> Result<String, WhatsAppError> {
+        let (first_line, description) = args.split_once('\n').unwrap_or((args, ""));
+        let (project_arg, title) = match first_line.split_once(' ') {
+            Some((first, rest)) if Uuid::parse_str(first).is_ok() => (first, rest.trim()),
+            _ => ("", first_line.trim()),
+        };
+        if title.is_empty() {
+            return Err(WhatsAppError::InvalidCommand(
+                "Usage: newtask [project_id] <title>".to_string(),
+            ));
+        }
+        let project = self.resolve_project(project_arg).await?;
+
+        let description = description.trim();
+        let create_task = CreateTask::from_title_description(
+            project.id,
+            title.to_string(),
+            (!description.is_empty()).then(|| description.to_string()),
+        );
+        let task = Task::create(&self.pool, &create_task, Uuid::new_v4()).await?;
+
+        Ok(format!(
+            "✅ Created a task in *{}*: *{}*\n```{}```",
+            project.name, task.title, task.id
+        ))
+    }
+
+    /// The project named or identified by `arg`, or the default project when
+    /// it is empty
+    async fn resolve_project(&self, arg: &str) -> Result<Project, WhatsAppError> {
+        let arg = arg.trim();
+        if arg.is_empty() {
+            let project_id = self
+                .config
+                .read()
+                .await
+                .whatsapp
+                .default_project_id
+                .ok_or(WhatsAppError::NoDefaultProject)?;
+            return Project::find_by_id(&self.pool, project_id)
+                .await?
+                .ok_or_else(|| WhatsAppError::ProjectNotFound(project_id.to_string()));
+        }
+
+        if let Ok(project_id) = Uuid::parse_str(arg) {
+            return Project::find_by_id(&self.pool, project_id)
+                .await?
+                .ok_or_else(|| WhatsAppError::ProjectNotFound(arg.to_string()));
+        }
+        Project::find_all(&self.pool)
+            .await?
+            .into_iter()
+            .find(|project| project.name.eq_ignore_ascii_case(arg))
+            .ok_or_else(|| WhatsAppError::ProjectNotFound(arg.to_string()))
+    }
+
+    /// Send the notification template about `task` to every recipient. Does
+    /// nothing if WhatsApp is off.
+    pub async fn send_task_notification(
+        &self,
+        task: &Task,
+        kind: TaskNotificationKind,
+    ) -> Result<(), WhatsAppError> {
+        let config = self.config.read().await.whatsapp.clone();
+        if !config.enabled {
+            return Ok(());
+        }
+        let event = match kind {
+            TaskNotificationKind::Done => "completed",
+            TaskNotificationKind::Failed => "failed",
+            _ => return Ok(()),
+        };
+        let sender = Sender::from_config(&config)?;
+        for to in &config.recipients {
+            self.post_message(&sender, &template_message(&config, to, task, event))
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn post_message(&self, sender: &Sender, message: &Value) -> Result<(), WhatsAppError> {
+        let response = self
+            .http
+            .post(format!(
+                "{API_BASE_URL}/{}/messages",
+                sender.phone_number_id
+            ))
+            .bearer_auth(&sender.access_token)
+            .json(message)
+            .send()
+            .await?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let message = match response.json::<ApiErrorResponse>().await {
+            Ok(body) => body.error.message,
+            Err(_) => "no error details".to_string(),
+        };
+        Err(WhatsAppError::Api {
+            status: status.as_u16(),
+            message,
+        })
+    }
+}
+
+#[async_trait]
+impl Notifier for WhatsAppService {
+    fn channel(&self) -> NotificationChannel {
+        NotificationChannel::WhatsApp
+    }
+
+    async fn wants(&self, kind: TaskNotificationKind) -> bool {
+        matches!(
+            kind,
+            TaskNotificationKind::Done | TaskNotificationKind::Failed
+        ) && self.config.read().await.whatsapp.enabled
+    }
+
+    async fn send(&self, event: &NotificationEvent) -> Result<(), NotifierError> {
+        self.send_task_notification(&event.task, event.kind).await?;
+        Ok(())
+    }
+
+    /// Sent as free-form text, which only reaches recipients who wrote to
+    /// the number in the last 24 hours
+    async fn send_text(&self, title: &str, body: &str) -> Result<(), NotifierError> {
+        let config = self.config.read().await.whatsapp.clone();
+        let sender = Sender::from_config(&config)?;
+        let text = format!("*{title}*\n{body}");
+        for to in &config.recipients {
+            self.post_message(&sender, &text_message(to, &text)).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Check a webhook signature: `sha256=` followed by the hex HMAC-SHA256 of
+/// the body under the app secret
+fn verify_signature(secret: &[u8], body: &[u8], signature: &str) -> Result<(), WhatsAppError> {
+    let expected = signature
+        .strip_prefix("sha256=")
+        .and_then(|hex_signature| hex::decode(hex_signature).ok())
+        .ok_or(WhatsAppError::InvalidSignature)?;
+
+    let mut mac =
+        HmacSha256::new_from_slice(secret).map_err(|_| WhatsAppError::InvalidSignature)?;
+    mac.update(body);
+    mac.verify_slice(&expected)
+        .map_err(|_| WhatsAppError::InvalidSignature)
+}
+
+fn status_emoji(status: &TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Todo => "📋",
+        TaskStatus::InProgress => "🔄",
+        TaskStatus::InReview => "👀",
+        TaskStatus::Done => "✅",
+        TaskStatus::Cancelled => "❌",
+    }
+}
+
+fn format_task_line(task: &Task) -> String {
+    format!(
+        "{} *{}*  ```{}```\n",
+        status_emoji(&task.status),
+        task.title,
+        task.id
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_fixtures::task;
+
+    #[test]
+    fn test_verify_signature() {
+        let secret = b"app-secret";
+        let body = br#"{"object":"whatsapp_business_account","entry":[]}"#;
+        let mut mac = HmacSha256::new_from_slice(secret).unwrap();
+        mac.update(body);
+        let signature = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+
+        assert!(verify_signature(secret, body, &signature).is_ok());
+        assert!(verify_signature(b"other-secret", body, &signature).is_err());
+        assert!(verify_signature(secret, b"{}", &signature).is_err());
+        assert!(verify_signature(secret, body, "sha1=abc").is_err());
+    }
+
+    #[test]
+    fn test_webhook_text_messages() {
+        let payload: WebhookPayload = serde_json::from_str(
+            r#"{
+                "object": "whatsapp_business_account",
+                "entry": [{
+                    "id": "1",
+                    "changes": [
+                        {"field": "messages", "value": {
+                            "messaging_product": "whatsapp",
+                            "messages": [
+                                {"from": "15551234567", "id": "a", "type": "text", "text": {"body": "tasks Web"}},
+                                {"from": "15551234567", "id": "b", "type": "image", "image": {"id": "c"}}
+                            ]
+                        }},
+                        {"field": "messages", "value": {"statuses": [{"id": "a", "status": "read"}]}}
+                    ]
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            payload.text_messages().collect::<Vec<_>>(),
+            vec![("15551234567", "tasks Web")]
+        );
+    }
+
+    #[test]
+    fn test_template_message() {
+        let task = task("Add dark mode");
+        let message = template_message(&WhatsAppConfig::default(), "15551234567", &task, "failed");
+
+        assert_eq!(message["template"]["name"], "task_update");
+        assert_eq!(message["template"]["language"]["code"], "en_US");
+        let parameters = &message["template"]["components"][0]["parameters"];
+        assert_eq!(parameters[0]["text"], "Add dark mode");
+        assert_eq!(parameters[1]["text"], "failed");
+    }
+}
@@ -0,0 +1,214 @@
+//! Per-assignee workload summaries.
+//!
+//! Open work is grouped by the accounts assigned to each task; a task with
+//! several assignees counts for each of them. Tasks nobody is assigned to are
+//! reported as unassigned.
+
+use std::collections::{BTreeMap, HashMap};
+
+use db::models::{
+    task::{TaskStatus, TaskWithAttemptStatus},
+    task_assignee::TaskAssignee,
+};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::services::telegram::escape_html;
+
+/// Open tasks and running attempts for one assignee
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct AssigneeWorkload {
+    /// Telegram user ID of the assignee, or None for unassigned tasks
+    #[ts(type = "number | null")]
+    pub telegram_user_id: Option<i64>,
+    pub username: Option<String>,
+    pub todo: usize,
+    pub in_progress: usize,
+    pub in_review: usize,
+    pub running_attempts: usize,
+}
+
+impl AssigneeWorkload {
+    pub fn open_tasks(&self) -> usize {
+        self.todo + self.in_progress + self.in_review
+    }
+
+    /// @username, the user ID when there is no username, or "Unassigned"
+    pub fn label(&self) -> String {
+        match (&self.username, self.telegram_user_id) {
+            (Some(username), _) => format!("@{username}"),
+            (None, Some(user_id)) => user_id.to_string(),
+            (None, None) => "Unassigned".to_string(),
+        }
+    }
+}
+
+/// Group a project's open tasks by assignee, busiest first
+pub fn summarize(
+    tasks: &[TaskWithAttemptStatus],
+    assignees: &[TaskAssignee],
+) -> Vec<AssigneeWorkload> {
+    let mut assignees_by_task: HashMap<Uuid, Vec<&TaskAssignee>> = HashMap::new();
+    for assignee in assignees {
+        assignees_by_task
+            .entry(assignee.task_id)
+            .or_default()
+            .push(assignee);
+    }
+
+    let mut by_assignee: BTreeMap<Option<i64>, AssigneeWorkload> = BTreeMap::new();
+    for task in tasks {
+        if matches!(task.status, TaskStatus::Done | TaskStatus::Cancelled) {
+            continue;
+        }
+        let task_assignees: Vec<(Option<i64>, Option<String>)> =
+            match assignees_by_task.get(&task.id) {
+                Some(assignees) => assignees
+                    .iter()
+                    .map(|a| (Some(a.telegram_user_id), a.username.clone()))
+                    .collect(),
+                None => vec![(None, None)],
+            };
+        for (telegram_user_id, username) in task_assignees {
+            let workload =
+                by_assignee
+                    .entry(telegram_user_id)
+                    .or_insert_with(|| AssigneeWorkload {
+                        telegram_user_id,
+                        username,
+                        ..Default::default()
+                    });
+            match task.status {
+                TaskStatus::Todo => workload.todo += 1,
+                TaskStatus::InProgress => workload.in_progress += 1,
+                TaskStatus::InReview => workload.in_review += 1,
+                TaskStatus::Done | TaskStatus::Cancelled => {}
+            }
+            if task.has_in_progress_attempt {
+                workload.running_attempts += 1;
+            }
+        }
+    }
+
+    let mut workloads: Vec<_> = by_assignee.into_values().collect();
+    workloads.sort_by_key(|w| std::cmp::Reverse((w.running_attempts, w.open_tasks())));
+    workloads
+}
+
+/// Render a workload summary as a Telegram HTML message
+pub fn format_workload(project_name: &str, workloads: &[AssigneeWorkload]) -> String {
+    let mut message = format!("<b>Workload in {}</b>", escape_html(project_name));
+    if workloads.is_empty() {
+        message.push_str("\n\nNo open tasks.");
+        return message;
+    }
+    for workload in workloads {
+        message.push_str(&format!(
+            "\n\n<b>{}</b>: {} open, {} running\n  📋 {} · 🔄 {} · 👀 {}",
+            escape_html(&workload.label()),
+            workload.open_tasks(),
+            workload.running_attempts,
+            workload.todo,
+            workload.in_progress,
+            workload.in_review
+        ));
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use db::models::{task::Task, task_assignee::TaskAssigneeRole};
+
+    use super::*;
+
+    fn task(status: TaskStatus, running: bool) -> TaskWithAttemptStatus {
+        TaskWithAttemptStatus {
+            task: Task {
+                id: Uuid::new_v4(),
+                project_id: Uuid::nil(),
+                title: "task".to_string(),
+                description: None,
+                status,
+                parent_workspace_id: None,
+                position: 0,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            },
+            has_in_progress_attempt: running,
+            last_attempt_failed: false,
+            executor: "CLAUDE_CODE".to_string(),
+        }
+    }
+
+    fn assignee(task: &TaskWithAttemptStatus, user_id: i64, username: &str) -> TaskAssignee {
+        TaskAssignee {
+            task_id: task.id,
+            telegram_user_id: user_id,
+            chat_id: user_id,
+            username: Some(username.to_string()),
+            role: TaskAssigneeRole::Assignee,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_summarize_groups_open_tasks_by_assignee() {
+        let tasks = vec![
+            task(TaskStatus::Todo, false),
+            task(TaskStatus::InProgress, true),
+            task(TaskStatus::InReview, false),
+            task(TaskStatus::InProgress, false),
+            task(TaskStatus::Done, false),
+        ];
+        let assignees = vec![
+            assignee(&tasks[1], 1, "alice"),
+            assignee(&tasks[2], 1, "alice"),
+            assignee(&tasks[3], 1, "alice"),
+            assignee(&tasks[3], 2, "bob"),
+            assignee(&tasks[4], 2, "bob"),
+        ];
+
+        let workloads = summarize(&tasks, &assignees);
+
+        assert_eq!(workloads.len(), 3);
+        assert_eq!(workloads[0].label(), "@alice");
+        assert_eq!(workloads[0].open_tasks(), 3);
+        assert_eq!(workloads[0].running_attempts, 1);
+        assert!(
+            workloads
+                .iter()
+                .any(|w| w.label() == "@bob" && w.open_tasks() == 1)
+        );
+        assert!(
+            workloads
+                .iter()
+                .any(|w| w.telegram_user_id.is_none() && w.todo == 1)
+        );
+    }
+
+    #[test]
+    fn test_format_workload() {
+        let workloads = vec![
+            AssigneeWorkload {
+                telegram_user_id: Some(42),
+                username: None,
+                in_review: 1,
+                ..Default::default()
+            },
+            AssigneeWorkload {
+                todo: 2,
+                ..Default::default()
+            },
+        ];
+
+        let message = format_workload("A&B", &workloads);
+
+        assert!(message.contains("Workload in A&amp;B"));
+        assert!(message.contains("<b>42</b>: 1 open, 0 running"));
+        assert!(message.contains("<b>Unassigned</b>: 2 open, 0 running"));
+        assert!(format_workload("p", &[]).contains("No open tasks."));
+    }
+}
@@ -40,6 +40,35 @@ pub fn truncate_to_char_boundary(content: &str, max_len: usize) -> &str {
     &content[..cutoff]
 }
 
+fn is_emoji(c: char) -> bool {
+    matches!(
+        c,
+        '\u{1F000}'..='\u{1FAFF}'
+            | '\u{2300}'..='\u{23FF}'
+            | '\u{2600}'..='\u{27BF}'
+            | '\u{2B00}'..='\u{2BFF}'
+            | '\u{FE00}'..='\u{FE0F}'
+            | '\u{200D}'
+            | '\u{20E3}'
+    )
+}
+
+/// Remove emoji, and the spaces they leave behind at the start of a line, so
+/// screen readers don't spell them out
+pub fn strip_emoji(text: &str) -> String {
+    text.split('\n')
+        .map(|line| {
+            let stripped: String = line.chars().filter(|c| !is_emoji(*c)).collect();
+            if stripped.len() == line.len() {
+                stripped
+            } else {
+                stripped.trim_start().replace("  ", " ")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -57,4 +86,14 @@ mod tests {
         assert_eq!(truncate_to_char_boundary(input, 5), "🔥");
         assert_eq!(truncate_to_char_boundary(input, 3), "");
     }
+
+    #[test]
+    fn test_strip_emoji() {
+        use super::strip_emoji;
+
+        assert_eq!(strip_emoji("✅ Task Completed"), "Task Completed");
+        assert_eq!(strip_emoji("☀️ Daily Digest\n• Fix login"), "Daily Digest\n• Fix login");
+        assert_eq!(strip_emoji("Merge 🔀 PR #12"), "Merge PR #12");
+        assert_eq!(strip_emoji("  indented code"), "  indented code");
+    }
 }
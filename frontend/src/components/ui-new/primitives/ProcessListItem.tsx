@@ -41,6 +41,7 @@ const STATUS_COLORS: Record<ExecutionProcessStatus, string> = {
   completed: 'bg-success',
   failed: 'bg-destructive',
   killed: 'bg-low',
+  stalled: 'bg-warning',
 };
 
 export function ProcessListItem({
@@ -94,6 +94,18 @@ import {
   CreateFromPrError,
   TelegramLinkInfo,
   TelegramStatusResponse,
+  WebPushPublicKey,
+  WebPushSubscription,
+  CreateWebPushSubscription,
+  NotificationRule,
+  CreateNotificationRule,
+  UpdateNotificationRule,
+  NotificationPreferences,
+  Notification,
+  NotificationFilter,
+  NotificationDeadLetter,
+  DeadLetterFilter,
+  RedeliveryReport,
 } from 'shared/types';
 import type { WorkspaceWithSession } from '@/types/attempt';
 import { createWorkspaceWithSession } from '@/types/attempt';
@@ -903,6 +915,13 @@ export const configApi = {
     });
     return handleApiResponse<Config>(response);
   },
+  patchConfig: async (patch: Partial<Config>): Promise<Config> => {
+    const response = await makeRequest('/api/config', {
+      method: 'PATCH',
+      body: JSON.stringify(patch),
+    });
+    return handleApiResponse<Config>(response);
+  },
   checkEditorAvailability: async (
     editorType: EditorType
   ): Promise<CheckEditorAvailabilityResponse> => {
@@ -1403,3 +1422,165 @@ export const telegramApi = {
     return handleApiResponse<TelegramStatusResponse>(response);
   },
 };
+
+export const webPushApi = {
+  /**
+   * Get the VAPID key to pass as applicationServerKey when subscribing
+   */
+  getPublicKey: async (): Promise<WebPushPublicKey> => {
+    const response = await makeRequest('/api/web-push/vapid-public-key');
+    return handleApiResponse<WebPushPublicKey>(response);
+  },
+
+  /**
+   * List the browsers subscribed to push notifications
+   */
+  listSubscriptions: async (): Promise<WebPushSubscription[]> => {
+    const response = await makeRequest('/api/web-push/subscriptions');
+    return handleApiResponse<WebPushSubscription[]>(response);
+  },
+
+  /**
+   * Register this browser's push subscription
+   */
+  subscribe: async (
+    data: CreateWebPushSubscription
+  ): Promise<WebPushSubscription> => {
+    const response = await makeRequest('/api/web-push/subscriptions', {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<WebPushSubscription>(response);
+  },
+
+  /**
+   * Unregister the browser that owned this push endpoint
+   */
+  unsubscribe: async (endpoint: string): Promise<void> => {
+    const response = await makeRequest('/api/web-push/subscriptions', {
+      method: 'DELETE',
+      body: JSON.stringify({ endpoint }),
+    });
+    return handleApiResponse<void>(response);
+  },
+};
+
+export const notificationRulesApi = {
+  list: async (): Promise<NotificationRule[]> => {
+    const response = await makeRequest('/api/notification-rules');
+    return handleApiResponse<NotificationRule[]>(response);
+  },
+
+  create: async (data: CreateNotificationRule): Promise<NotificationRule> => {
+    const response = await makeRequest('/api/notification-rules', {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<NotificationRule>(response);
+  },
+
+  update: async (
+    id: string,
+    data: UpdateNotificationRule
+  ): Promise<NotificationRule> => {
+    const response = await makeRequest(`/api/notification-rules/${id}`, {
+      method: 'PUT',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<NotificationRule>(response);
+  },
+
+  delete: async (id: string): Promise<void> => {
+    const response = await makeRequest(`/api/notification-rules/${id}`, {
+      method: 'DELETE',
+    });
+    return handleApiResponse<void>(response);
+  },
+};
+
+export const notificationPreferencesApi = {
+  get: async (): Promise<NotificationPreferences> => {
+    const response = await makeRequest('/api/notification-preferences');
+    return handleApiResponse<NotificationPreferences>(response);
+  },
+
+  update: async (
+    data: NotificationPreferences
+  ): Promise<NotificationPreferences> => {
+    const response = await makeRequest('/api/notification-preferences', {
+      method: 'PUT',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<NotificationPreferences>(response);
+  },
+};
+
+export const notificationsApi = {
+  list: async (
+    filter: Partial<NotificationFilter> = {},
+    limit?: number
+  ): Promise<Notification[]> => {
+    const params = new URLSearchParams();
+    for (const [key, value] of Object.entries(filter)) {
+      if (value != null) params.set(key, String(value));
+    }
+    if (limit != null) params.set('limit', String(limit));
+    const response = await makeRequest(
+      `/api/notifications?${params.toString()}`
+    );
+    return handleApiResponse<Notification[]>(response);
+  },
+
+  resend: async (id: string): Promise<Notification> => {
+    const response = await makeRequest(`/api/notifications/${id}/resend`, {
+      method: 'POST',
+    });
+    return handleApiResponse<Notification>(response);
+  },
+
+  markRead: async (id: string): Promise<Notification> => {
+    const response = await makeRequest(`/api/notifications/${id}/read`, {
+      method: 'POST',
+    });
+    return handleApiResponse<Notification>(response);
+  },
+
+  acknowledge: async (id: string): Promise<Notification> => {
+    const response = await makeRequest(
+      `/api/notifications/${id}/acknowledge`,
+      { method: 'POST' }
+    );
+    return handleApiResponse<Notification>(response);
+  },
+
+  listDeadLetters: async (
+    filter: Partial<DeadLetterFilter> = {},
+    limit?: number
+  ): Promise<NotificationDeadLetter[]> => {
+    const params = new URLSearchParams();
+    for (const [key, value] of Object.entries(filter)) {
+      if (value != null) params.set(key, value);
+    }
+    if (limit != null) params.set('limit', String(limit));
+    const response = await makeRequest(
+      `/api/notifications/dead-letters?${params.toString()}`
+    );
+    return handleApiResponse<NotificationDeadLetter[]>(response);
+  },
+
+  discardDeadLetter: async (id: string): Promise<void> => {
+    const response = await makeRequest(
+      `/api/notifications/dead-letters/${id}`,
+      { method: 'DELETE' }
+    );
+    return handleApiResponse<void>(response);
+  },
+
+  redeliver: async (ids?: string[]): Promise<RedeliveryReport> => {
+    const response = await makeRequest('/api/notifications/redeliver', {
+      method: 'POST',
+      body: JSON.stringify({ ids: ids ?? null }),
+    });
+    return handleApiResponse<RedeliveryReport>(response);
+  },
+};
@@ -4,7 +4,11 @@
 
 // If you are an AI, and you absolutely have to edit this file, please confirm with the user first.
 
-export type Project = { id: string, name: string, default_agent_working_dir: string | null, remote_project_id: string | null, created_at: Date, updated_at: Date, };
+export type Project = { id: string, name: string, default_agent_working_dir: string | null, remote_project_id: string | null, 
+/**
+ * Set while the project is archived
+ */
+archived_at: Date | null, created_at: Date, updated_at: Date, };
 
 export type CreateProject = { name: string, repositories: Array<CreateProjectRepo>, };
 
@@ -38,17 +42,165 @@ export type CreateTag = { tag_name: string, content: string, };
 
 export type UpdateTag = { tag_name: string | null, content: string | null, };
 
+/**
+ * Reusable prompt text with `{{variable}}` placeholders. Snippets without a
+ * project are available everywhere.
+ */
+export type PromptSnippet = { id: string, project_id: string | null, name: string, description: string | null, content: string, created_at: string, updated_at: string, };
+
+export type CreatePromptSnippet = { project_id: string | null, name: string, description: string | null, content: string, };
+
+export type UpdatePromptSnippet = { name: string | null, description: string | null, content: string | null, };
+
+/**
+ * A labeled set of benchmark tasks run once against each of two executor
+ * profiles
+ */
+export type EvalRun = { id: string, project_id: string, name: string, profile_a: ExecutorProfileId, profile_b: ExecutorProfileId, 
+/**
+ * Run in the attempt's worktree once the agent finishes; exit code 0
+ * counts as a pass. Without one, a clean agent exit is a pass.
+ */
+test_command: string | null, created_at: string, };
+
+export type EvalVariant = "a" | "b";
+
+export type EvalOutcome = "pending" | "passed" | "failed";
+
+export type EvalAttempt = { id: string, eval_run_id: string, benchmark_label: string, variant: EvalVariant, task_id: string, workspace_id: string | null, outcome: EvalOutcome, 
+/**
+ * Wall-clock time of the coding agent run
+ */
+duration_secs: bigint | null, 
+/**
+ * Peak context usage reported by the agent, used as a cost proxy
+ */
+total_tokens: bigint | null, created_at: string, completed_at: string | null, };
+
 export type TaskStatus = "todo" | "inprogress" | "inreview" | "done" | "cancelled";
 
-export type Task = { id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_workspace_id: string | null, created_at: string, updated_at: string, };
+export type Task = { id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_workspace_id: string | null, 
+/**
+ * Place on the project's board, lowest first
+ */
+position: bigint, created_at: string, updated_at: string, };
+
+export type TaskWithAttemptStatus = { has_in_progress_attempt: boolean, last_attempt_failed: boolean, executor: string, id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_workspace_id: string | null, 
+/**
+ * Place on the project's board, lowest first
+ */
+position: bigint, created_at: string, updated_at: string, };
 
-export type TaskWithAttemptStatus = { has_in_progress_attempt: boolean, last_attempt_failed: boolean, executor: string, id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_workspace_id: string | null, created_at: string, updated_at: string, };
+/**
+ * Number of tasks in each board column of a project
+ */
+export type TaskStatusCounts = { todo: number, in_progress: number, in_review: number, done: number, cancelled: number, total: number, };
 
 export type TaskRelationships = { parent_task: Task | null, current_workspace: Workspace, children: Array<Task>, };
 
 export type CreateTask = { project_id: string, title: string, description: string | null, status: TaskStatus | null, parent_workspace_id: string | null, image_ids: Array<string> | null, };
 
-export type UpdateTask = { title: string | null, description: string | null, status: TaskStatus | null, parent_workspace_id: string | null, image_ids: Array<string> | null, };
+export type UpdateTask = { title: string | null, description: string | null, status: TaskStatus | null, parent_workspace_id: string | null, image_ids: Array<string> | null, 
+/**
+ * `updated_at` of the client's copy; the update is rejected if the task changed since
+ */
+expected_updated_at?: string, };
+
+/**
+ * A single status transition, recorded by triggers on the tasks table
+ */
+export type TaskStatusChange = { id: string, task_id: string, status: TaskStatus, changed_at: string, };
+
+/**
+ * A test the project's CI reported failing, with how often it did
+ */
+export type CiTestFailure = { project_id: string, test_name: string, occurrences: bigint, 
+/**
+ * Task filed once the test failed repeatedly
+ */
+task_id: string | null, last_run_url: string | null, first_seen_at: string, last_seen_at: string, };
+
+/**
+ * A task and the status it had at a point in time
+ */
+export type TaskStatusAt = { task_id: string, title: string, status: TaskStatus, };
+
+/**
+ * Maximum time a task in a project may stay in `status` before the SLA is breached
+ */
+export type SlaPolicy = { id: string, project_id: string, status: TaskStatus, max_hours: bigint, created_at: string, updated_at: string, };
+
+export type UpsertSlaPolicy = { status: TaskStatus, max_hours: bigint, };
+
+export type ProjectPreviewSettings = { project_id: string, 
+/**
+ * Page to capture once the dev server is up, e.g. http://localhost:3000
+ */
+preview_url: string, 
+/**
+ * How long to wait for `preview_url` to respond before giving up
+ */
+startup_timeout_secs: bigint, created_at: string, updated_at: string, };
+
+export type UpsertProjectPreviewSettings = { preview_url: string, startup_timeout_secs: bigint | null, };
+
+/**
+ * Imports checkbox items from a markdown file in one of the project's repos
+ */
+export type ProjectTodoSyncSettings = { project_id: string, repo_id: string, 
+/**
+ * Relative to the repo root, e.g. `TODO.md` or `docs/BACKLOG.md`
+ */
+file_path: string, 
+/**
+ * Tick an item's checkbox in the file once its task is done
+ */
+write_back: boolean, last_synced_at: string | null, created_at: string, updated_at: string, };
+
+export type UpsertProjectTodoSyncSettings = { repo_id: string, file_path: string | null, write_back: boolean | null, };
+
+/**
+ * Replicates the project's tasks with a project on another instance
+ */
+export type ProjectPeerSyncSettings = { project_id: string, 
+/**
+ * Base URL of the other instance, e.g. `http://homeserver:3000`
+ */
+peer_url: string, 
+/**
+ * The same project's id on the other instance
+ */
+remote_project_id: string, last_synced_at: string | null, created_at: string, updated_at: string, };
+
+export type UpsertProjectPeerSyncSettings = { peer_url: string, remote_project_id: string, token: string, };
+
+/**
+ * Key that lets automation read and write the tasks of a single project
+ */
+export type ProjectApiKey = { id: string, project_id: string, name: string, 
+/**
+ * Start of the key, to tell keys apart without revealing them
+ */
+key_prefix: string, last_used_at: string | null, rotated_at: string | null, created_at: string, };
+
+export type CreateProjectApiKey = { name: string, };
+
+export type TelegramAccountRole = "admin" | "read_only";
+
+/**
+ * How an account gets its task notifications
+ */
+export type TelegramDigestMode = "immediate" | "hourly" | "daily";
+
+/**
+ * A Telegram account that linked itself to the bot
+ */
+export type TelegramAccount = { user_id: bigint, chat_id: bigint, username: string | null, role: TelegramAccountRole, digest_mode: TelegramDigestMode, created_at: string, };
+
+/**
+ * A file produced by an attempt, beyond its diff
+ */
+export type WorkspaceArtifact = { id: string, workspace_id: string, file_path: string, original_name: string, mime_type: string | null, size_bytes: bigint, hash: string, created_at: string, };
 
 export type DraftFollowUpData = { message: string, executor_profile_id: ExecutorProfileId, };
 
@@ -88,7 +240,7 @@ export type ExecutionProcess = { id: string, session_id: string, run_reason: Exe
  */
 dropped: boolean, started_at: string, completed_at: string | null, created_at: string, updated_at: string, };
 
-export enum ExecutionProcessStatus { running = "running", completed = "completed", failed = "failed", killed = "killed" }
+export enum ExecutionProcessStatus { running = "running", completed = "completed", failed = "failed", killed = "killed", stalled = "stalled" }
 
 export type ExecutionProcessRunReason = "setupscript" | "cleanupscript" | "codingagent" | "devserver";
 
@@ -188,6 +340,23 @@ export type TagSearchParams = { search: string | null, };
 
 export type TokenResponse = { access_token: string, expires_at: string | null, };
 
+export type BoardColumn = { status: TaskStatus, tasks: Array<TaskStatusAt>, };
+
+/**
+ * The project board as it looked at a past point in time
+ */
+export type BoardSnapshot = { at: string, columns: Array<BoardColumn>, };
+
+/**
+ * A project API key along with its secret, which is only ever returned when
+ * the key is created or rotated
+ */
+export type ProjectApiKeyWithSecret = { secret: string, id: string, project_id: string, name: string, 
+/**
+ * Start of the key, to tell keys apart without revealing them
+ */
+key_prefix: string, last_used_at: string | null, rotated_at: string | null, created_at: string, };
+
 export type UserSystemInfo = { config: Config, analytics_user_id: string, login_status: LoginStatus, environment: Environment, 
 /**
  * Capabilities supported per executor (e.g., { "CLAUDE_CODE": ["SESSION_FORK"] })
@@ -208,6 +377,18 @@ export type CheckEditorAvailabilityResponse = { available: boolean, };
 
 export type CheckAgentAvailabilityQuery = { executor: BaseCodingAgent, };
 
+export type RotateGitHubTokenRequest = { 
+/**
+ * The new personal access token
+ */
+token: string, };
+
+export type GitHubTokenRotation = { 
+/**
+ * Login of the account the new token belongs to
+ */
+username: string, };
+
 export type CurrentUserResponse = { user_id: string, };
 
 export type CreateFollowUpAttempt = { prompt: string, executor_profile_id: ExecutorProfileId, retry_process_id: string | null, force_when_dirty: boolean | null, perform_git_reset: boolean | null, };
@@ -234,6 +415,14 @@ export type OpenEditorResponse = { url: string | null, };
 
 export type CreateAndStartTaskRequest = { task: CreateTask, executor_profile_id: ExecutorProfileId, repos: Array<WorkspaceRepoInput>, };
 
+export type ReorderTasksRequest = { project_id: string, 
+/**
+ * Applied in order, all or none of them
+ */
+moves: Array<TaskMove>, };
+
+export type MaintenanceStatus = { enabled: boolean, };
+
 export type CreatePrApiRequest = { title: string, body: string | null, target_branch: string | null, draft: boolean | null, repo_id: string, auto_generate_description: boolean, };
 
 export type ImageResponse = { id: string, file_path: string, original_name: string, mime_type: string | null, size_bytes: bigint, hash: string, created_at: string, updated_at: string, };
@@ -362,9 +551,51 @@ export type DirectoryListResponse = { entries: Array<DirectoryEntry>, current_pa
 
 export type SearchMode = "taskform" | "settings";
 
-export type Config = { config_version: string, theme: ThemeMode, executor_profile: ExecutorProfileId, disclaimer_acknowledged: boolean, onboarding_acknowledged: boolean, notifications: NotificationConfig, editor: EditorConfig, github: GitHubConfig, analytics_enabled: boolean, workspace_dir: string | null, last_app_version: string | null, show_release_notes: boolean, language: UiLanguage, git_branch_prefix: string, showcases: ShowcaseState, pr_auto_description_enabled: boolean, pr_auto_description_prompt: string | null, beta_workspaces: boolean, beta_workspaces_invitation_sent: boolean, commit_reminder: boolean, send_message_shortcut: SendMessageShortcut, telegram: TelegramConfig, };
+export type Config = { config_version: string, theme: ThemeMode, executor_profile: ExecutorProfileId, disclaimer_acknowledged: boolean, onboarding_acknowledged: boolean, notifications: NotificationConfig, editor: EditorConfig, github: GitHubConfig, analytics_enabled: boolean, workspace_dir: string | null, last_app_version: string | null, show_release_notes: boolean, language: UiLanguage, git_branch_prefix: string, showcases: ShowcaseState, pr_auto_description_enabled: boolean, pr_auto_description_prompt: string | null, beta_workspaces: boolean, beta_workspaces_invitation_sent: boolean, commit_reminder: boolean, send_message_shortcut: SendMessageShortcut, 
+/**
+ * Which channels each kind of task event is sent through
+ */
+notification_preferences: NotificationPreferences, 
+/**
+ * Windows during which notifications are held back
+ */
+quiet_hours: Array<QuietHours>, 
+/**
+ * Where failures nobody acknowledges are sent next
+ */
+escalation: EscalationPolicy, telegram: TelegramConfig, slack: SlackConfig, email: EmailConfig, ntfy: NtfyConfig, gotify: GotifyConfig, web_push: WebPushConfig, apprise: AppriseConfig, twilio: TwilioConfig, whatsapp: WhatsAppConfig, 
+/**
+ * Maximum total size of attempt artifacts stored per project
+ */
+artifact_quota_mb: bigint, vault_export: VaultExportConfig, post_merge_verification: PostMergeVerificationConfig, analytics_export: AnalyticsExportConfig, 
+/**
+ * Reject every change with 503 while reads keep working, e.g. during
+ * backups and migrations
+ */
+maintenance_mode: boolean, };
+
+export type NotificationConfig = { sound_enabled: boolean, push_enabled: boolean, sound_file: SoundFile, 
+/**
+ * Leave emoji out of push notifications, for screen readers
+ */
+plain_text: boolean, 
+/**
+ * Task notifications sent to the Pushover apps, alongside the desktop ones
+ */
+pushover: PushoverConfig, };
 
-export type NotificationConfig = { sound_enabled: boolean, push_enabled: boolean, sound_file: SoundFile, };
+/**
+ * Credentials for Pushover notifications
+ */
+export type PushoverConfig = { enabled: boolean, 
+/**
+ * User or group key the notifications are sent to
+ */
+user_key: string | null, 
+/**
+ * API token of the Pushover application sending them
+ */
+app_token: string | null, };
 
 export enum ThemeMode { LIGHT = "LIGHT", DARK = "DARK", SYSTEM = "SYSTEM" }
 
@@ -374,7 +605,17 @@ export enum EditorType { VS_CODE = "VS_CODE", VS_CODE_INSIDERS = "VS_CODE_INSIDE
 
 export type EditorOpenError = { "type": "executable_not_found", executable: string, editor_type: EditorType, } | { "type": "invalid_command", details: string, editor_type: EditorType, } | { "type": "launch_failed", executable: string, details: string, editor_type: EditorType, };
 
-export type GitHubConfig = { pat: string | null, oauth_token: string | null, username: string | null, primary_email: string | null, default_pr_base: string | null, };
+export type GitHubConfig = { pat: string | null, oauth_token: string | null, username: string | null, primary_email: string | null, default_pr_base: string | null, 
+/**
+ * Token `gh` runs with instead of its own login. Only
+ * `POST /api/github/rotate-token` sets it.
+ */
+cli_token: string | null, 
+/**
+ * Account `cli_token` belongs to; later rotations must be to a token of
+ * the same account
+ */
+cli_token_login: string | null, };
 
 export enum SoundFile { ABSTRACT_SOUND1 = "ABSTRACT_SOUND1", ABSTRACT_SOUND2 = "ABSTRACT_SOUND2", ABSTRACT_SOUND3 = "ABSTRACT_SOUND3", ABSTRACT_SOUND4 = "ABSTRACT_SOUND4", COW_MOOING = "COW_MOOING", PHONE_VIBRATION = "PHONE_VIBRATION", ROOSTER = "ROOSTER" }
 
@@ -384,69 +625,1204 @@ export type ShowcaseState = { seen_features: Array<string>, };
 
 export type SendMessageShortcut = "ModifierEnter" | "Enter";
 
-export type TelegramConfig = { chat_id: bigint | null, user_id: bigint | null, username: string | null, notifications_enabled: boolean, notify_on_task_done: boolean, include_llm_summary: boolean, stream_enabled: boolean, };
+/**
+ * Channels each kind of event is sent through. A channel also has to be
+ * enabled and set up in its own settings.
+ */
+export type NotificationPreferences = { attempt_started: Array<NotificationChannel>, task_in_review: Array<NotificationChannel>, task_done: Array<NotificationChannel>, task_failed: Array<NotificationChannel>, task_cancelled: Array<NotificationChannel>, pr_merged: Array<NotificationChannel>, };
 
-export type TelegramLinkInfo = { 
 /**
- * The token used for linking (for reference)
+ * Events the notification preferences pick channels for
  */
-token: string, 
+export type NotificationEventType = "attempt_started" | "task_in_review" | "task_done" | "task_failed" | "task_cancelled" | "pr_merged";
+
 /**
- * The deep link URL to open in Telegram
+ * A daily window during which notifications to some channels are held
+ * back, to be sent as one catch-up message once it ends
  */
-deep_link: string, 
+export type QuietHours = { enabled: boolean, 
 /**
- * Whether the bot is configured (has a token)
+ * Channels the window applies to; empty for all of them
  */
-bot_configured: boolean, };
+channels: Array<NotificationChannel>, 
+/**
+ * Hour (0-23) the window starts at
+ */
+start_hour: number, 
+/**
+ * Hour (0-23) the window ends at; earlier than `start_hour` for a window
+ * spanning midnight
+ */
+end_hour: number, 
+/**
+ * Offset from UTC the hours are in, in minutes; the server's local time
+ * when unset
+ */
+utc_offset_minutes: number | null, 
+/**
+ * Whether failures are sent right away instead of being held
+ */
+failures_break_through: boolean, };
 
-export type TelegramStatusResponse = { 
 /**
- * Whether an account is currently linked
+ * One step of an escalation: a channel an unacknowledged failure is sent
+ * through next
  */
-linked: boolean, 
+export type EscalationStep = { channel: NotificationChannel, 
 /**
- * The linked Telegram username (if available)
+ * Minutes after the failure at which it is sent here, if nobody has
+ * acknowledged it by then
  */
-username: string | null, 
+after_minutes: number, };
+
 /**
- * Whether notifications are enabled
+ * Task failures nobody acknowledges are sent through more channels as time
+ * passes, e.g. Telegram after a quarter of an hour, then SMS
  */
-notifications_enabled: boolean, 
+export type EscalationPolicy = { enabled: boolean, 
 /**
- * Whether to notify on task completion
+ * Steps in the order they are taken
  */
-notify_on_task_done: boolean, 
+steps: Array<EscalationStep>, };
+
+export type TelegramConfig = { chat_id: bigint | null, user_id: bigint | null, username: string | null, notifications_enabled: boolean, include_llm_summary: boolean, stream_enabled: boolean, digest_schedule: DigestSchedule, digest_hour: number, parse_mode: TelegramParseMode, 
 /**
- * Whether to include LLM summaries in notifications
+ * Send the LLM summary of finished attempts as a voice message
  */
-include_llm_summary: boolean, 
+voice_summary: boolean, tts: TtsConfig, retry: TelegramRetryConfig, 
 /**
- * Whether real-time streaming is enabled
+ * When non-empty, of the events the notification preferences send to
+ * Telegram, only these status transitions are sent. Attempt failures are
+ * not filtered.
  */
-stream_enabled: boolean, 
+notify_transitions: Array<StatusTransitionRule>, 
 /**
- * Whether the bot is configured (has a token)
+ * Deliver every notification without sound
  */
-bot_configured: boolean, };
+silent: boolean, 
+/**
+ * Notification kinds delivered without sound even when `silent` is off
+ */
+silent_kinds: Array<TaskNotificationKind>, 
+/**
+ * Task notifications are paused until this time (set with /mute)
+ */
+muted_until: string | null, 
+/**
+ * Keep one message per task and edit it on each status change instead of
+ * sending a new one. Edits arrive without sound.
+ */
+edit_in_place: boolean, 
+/**
+ * Forward questions an agent ends its turn with to the chat, so the
+ * reply can be sent back as a follow-up
+ */
+forward_questions: boolean, 
+/**
+ * Telegram user IDs allowed to use the bot besides the linked accounts.
+ * Everyone else can only run /start and /help.
+ */
+allowed_user_ids: Array<bigint>, 
+/**
+ * Bot command aliases, without the leading slash. A value may carry
+ * leading arguments, e.g. `"bug": "newtask #bug"`.
+ */
+command_aliases: { [key in string]?: string }, 
+/**
+ * Projects that send their notifications through a bot of their own.
+ * Each one receives updates at `/api/telegram/webhook/{project_id}`.
+ */
+project_bots: Array<TelegramProjectBot>, 
+/**
+ * Task notifications a chat gets within this many seconds of each
+ * other are combined into one message. 0 sends each right away.
+ */
+batch_window_secs: bigint, 
+/**
+ * Public URL this server is reachable at, e.g. `https://kanban.example.com`.
+ * When set, the bots' webhooks are registered with Telegram on startup and
+ * removed on shutdown.
+ */
+webhook_base_url: string | null, 
+/**
+ * Secret Telegram sends with each webhook request of the default bot,
+ * generated when the webhook is first registered
+ */
+webhook_secret: string | null, 
+/**
+ * Only accept webhook requests from `webhook_allowed_cidrs`, on top of
+ * the webhook secret. Behind a reverse proxy on the same host, the
+ * address the proxy appended to `X-Forwarded-For` is checked instead of
+ * the proxy's own.
+ */
+verify_webhook_source: boolean, 
+/**
+ * Address ranges webhook requests may come from, Telegram's published
+ * ranges by default
+ */
+webhook_allowed_cidrs: Array<string>, };
 
-export type UpdateTelegramSettingsRequest = { 
 /**
- * Whether to enable notifications (master switch)
+ * A bot of its own for one project, e.g. a client-facing bot
  */
-notifications_enabled: boolean | null, 
+export type TelegramProjectBot = { project_id: string, bot_token: string, 
 /**
- * Whether to notify on task completion
+ * Chat the project's notifications go to, or the linked chat when unset
  */
-notify_on_task_done: boolean | null, 
+chat_id: bigint | null, 
 /**
- * Whether to include LLM summaries in notifications
+ * Secret Telegram sends with each webhook request of this bot, generated
+ * when the webhook is first registered
  */
-include_llm_summary: boolean | null, 
+webhook_secret: string | null, };
+
 /**
- * Whether to enable real-time streaming
+ * Slack workspace linked through the app's OAuth install flow
+ */
+export type SlackConfig = { team_id: string | null, team_name: string | null, 
+/**
+ * Bot token issued by the install
+ */
+bot_token: string | null, 
+/**
+ * Channel task notifications are posted to, the one picked during the
+ * install unless changed later
+ */
+channel_id: string | null, channel_name: string | null, 
+/**
+ * Project `/vk tasks` and `/vk newtask` use when none is given
+ */
+default_project_id: string | null, notifications_enabled: boolean, };
+
+/**
+ * How the SMTP connection is secured
+ */
+export type SmtpTls = "None" | "StartTls" | "Tls";
+
+/**
+ * Task notifications sent by email
+ */
+export type EmailConfig = { enabled: boolean, smtp_host: string | null, smtp_port: number, tls: SmtpTls, username: string | null, password: string | null, 
+/**
+ * Sender, e.g. `Vibe Kanban <kanban@example.com>`
+ */
+from_address: string | null, to_addresses: Array<string>, weekly_digest: WeeklyDigestConfig, };
+
+export type DigestWeekday = "Monday" | "Tuesday" | "Wednesday" | "Thursday" | "Friday" | "Saturday" | "Sunday";
+
+/**
+ * Weekly report emailed for each project, sent over the SMTP server of the
+ * email notifications
+ */
+export type WeeklyDigestConfig = { enabled: boolean, day: DigestWeekday, 
+/**
+ * Local hour the report is sent at, 0-23
+ */
+hour: number, 
+/**
+ * Who gets the report; the notification recipients when empty
+ */
+recipients: Array<string>, };
+
+/**
+ * Task notifications pushed through an ntfy server, e.g. ntfy.sh, whose
+ * mobile apps need no bot of their own
+ */
+export type NtfyConfig = { enabled: boolean, 
+/**
+ * Topic to publish to, e.g. `https://ntfy.sh/my-kanban`
+ */
+topic_url: string | null, 
+/**
+ * Access token for protected topics, sent as a bearer token
+ */
+access_token: string | null, };
+
+/**
+ * Task notifications pushed through a self-hosted Gotify server
+ */
+export type GotifyConfig = { enabled: boolean, 
+/**
+ * Base URL of the Gotify server, e.g. `https://gotify.example.com`
+ */
+server_url: string | null, 
+/**
+ * Token of the Gotify application the messages are posted as
+ */
+app_token: string | null, 
+/**
+ * Have the Gotify clients render messages as markdown
+ */
+markdown: boolean, };
+
+/**
+ * Task notifications pushed to the browsers that subscribed in the web UI
+ */
+export type WebPushConfig = { enabled: boolean, 
+/**
+ * Contact for push services, a `mailto:` or `https:` URL
+ */
+subject: string | null, 
+/**
+ * VAPID signing key, base64url; generated when the web UI first asks for
+ * the public key
+ */
+vapid_private_key: string | null, };
+
+/**
+ * Task notifications sent to Apprise-style URLs, e.g. `tgram://`,
+ * `slack://` or `mailto://`; see `services::apprise` for the schemes
+ * supported
+ */
+export type AppriseConfig = { enabled: boolean, urls: Array<string>, };
+
+/**
+ * Text messages sent through Twilio for critical events. Each message
+ * costs money, so only failures are sent and at most `max_per_hour` go out.
+ */
+export type TwilioConfig = { enabled: boolean, account_sid: string | null, auth_token: string | null, 
+/**
+ * Twilio number the messages are sent from, in E.164 format
+ */
+from_number: string | null, 
+/**
+ * Numbers the messages are sent to, in E.164 format
+ */
+to_numbers: Array<string>, 
+/**
+ * Tasks whose failures are texted; failures of any task when empty
+ */
+watched_task_ids: Array<string>, 
+/**
+ * Messages sent in any hour, counting every recipient; the rest are
+ * dropped
+ */
+max_per_hour: number, };
+
+/**
+ * WhatsApp Business number, through the Cloud API, that sends task
+ * notifications and answers commands
+ */
+export type WhatsAppConfig = { enabled: boolean, 
+/**
+ * ID of the business phone number messages are sent from
+ */
+phone_number_id: string | null, access_token: string | null, 
+/**
+ * Secret of the Meta app, which signs webhook requests
+ */
+app_secret: string | null, 
+/**
+ * Token Meta sends back when the webhook is subscribed
+ */
+verify_token: string | null, 
+/**
+ * Numbers notified, with country code and digits only. Commands are
+ * only taken from these numbers.
+ */
+recipients: Array<string>, 
+/**
+ * Approved message template for notifications. Its body takes the
+ * task title as `{{1}}` and what happened, e.g. "completed", as `{{2}}`.
+ */
+template_name: string, template_language: string, 
+/**
+ * Project `tasks` and `newtask` use when none is given
+ */
+default_project_id: string | null, };
+
+export type DigestSchedule = "Off" | "Daily" | "Weekdays";
+
+/**
+ * Formatting used for outgoing Telegram messages
+ */
+export type TelegramParseMode = "Html" | "MarkdownV2" | "PlainText" | "Accessible";
+
+/**
+ * Text-to-speech engine used for voice summaries. `Command` runs a local shell
+ * command that reads text on stdin and writes OGG/Opus audio to stdout.
+ */
+export type TtsBackend = "Command" | "OpenAi";
+
+export type TtsConfig = { backend: TtsBackend, 
+/**
+ * Shell command for the `Command` backend
+ */
+command: string | null, 
+/**
+ * API key for the `OpenAi` backend
+ */
+openai_api_key: string | null, 
+/**
+ * Voice name passed to the `OpenAi` backend
+ */
+voice: string, };
+
+/**
+ * Retry policy for Bot API sends
+ */
+export type TelegramRetryConfig = { 
+/**
+ * Total attempts per message, including the first one
+ */
+max_attempts: number, 
+/**
+ * Backoff after the first failure, doubled on each further failure
+ */
+initial_backoff_ms: bigint, max_backoff_ms: bigint, };
+
+/**
+ * A task status change that sends a Telegram notification
+ */
+export type StatusTransitionRule = { 
+/**
+ * Previous status, or any status when unset
+ */
+from: TaskStatus | null, to: TaskStatus, };
+
+/**
+ * Task events that can trigger a notification
+ */
+export type TaskNotificationKind = "started" | "in_review" | "failed" | "done" | "cancelled";
+
+/**
+ * A task notification rendered without sending it
+ */
+export type NotificationPreview = { 
+/**
+ * The notification as composed, in Telegram HTML
+ */
+html: string, 
+/**
+ * The messages that would go out: converted to the parse mode and split
+ * at Telegram's length limit
+ */
+messages: Array<string>, parse_mode: TelegramParseMode, 
+/**
+ * Labels of the buttons under the last message. The merge button offered
+ * while a PR is open is left out.
+ */
+buttons: Array<string>, 
+/**
+ * Whether it would be delivered without sound
+ */
+silent: boolean, 
+/**
+ * Whether the current settings send this kind of notification at all
+ */
+enabled: boolean, };
+
+/**
+ * A piece of the bot setup checked by `setup_state` and /setup
+ */
+export type TelegramSetupStepKind = "token" | "username" | "webhook" | "link_chat";
+
+export type TelegramSetupStepStatus = "done" | "missing" | "error";
+
+/**
+ * How Telegram hands updates to the bot
+ */
+export type TelegramUpdateMode = "webhook" | "polling";
+
+export type TelegramSetupStep = { kind: TelegramSetupStepKind, status: TelegramSetupStepStatus, 
+/**
+ * What was found, e.g. the bot's username or the webhook's last error
+ */
+detail: string | null, 
+/**
+ * What to do to complete the step, None once it is done
+ */
+action: string | null, };
+
+/**
+ * Progress of the first-run bot setup, step by step
+ */
+export type TelegramSetupState = { 
+/**
+ * Whether every step is done
+ */
+complete: boolean, 
+/**
+ * None when Telegram could not be asked
+ */
+update_mode: TelegramUpdateMode | null, steps: Array<TelegramSetupStep>, };
+
+/**
+ * Export of tasks as markdown notes, e.g. into an Obsidian vault
+ */
+export type VaultExportConfig = { 
+/**
+ * Directory notes are written to; exporting is disabled while unset
+ */
+directory: string | null, schedule: VaultExportSchedule, };
+
+/**
+ * How often the markdown vault export runs on its own
+ */
+export type VaultExportSchedule = "Manual" | "Hourly" | "Daily";
+
+/**
+ * Build and test run on the updated base branch after an attempt's PR merges
+ */
+export type PostMergeVerificationConfig = { 
+/**
+ * Shell command run from the repository root, e.g. `cargo build && cargo
+ * test`; verification is disabled while unset
+ */
+command: string | null, };
+
+/**
+ * Copy of the operational data for reporting queries, so they do not
+ * contend with the live database
+ */
+export type AnalyticsExportConfig = { 
+/**
+ * Directory the export is written to; exporting is disabled while unset
+ */
+directory: string | null, format: AnalyticsExportFormat, schedule: VaultExportSchedule, 
+/**
+ * DuckDB CLI used for the Parquet and DuckDB formats; `duckdb` from the
+ * PATH when unset
+ */
+duckdb_path: string | null, };
+
+/**
+ * File format of the analytics export
+ */
+export type AnalyticsExportFormat = "Parquet" | "DuckDb" | "Sqlite";
+
+/**
+ * Field-level summary of what changed when a config file was loaded and upgraded.
+ *
+ * Paths use dot notation (e.g. `telegram.stream_enabled`). Arrays are compared as
+ * whole values rather than element by element.
+ */
+export type ConfigMigrationReport = { 
+/**
+ * Version found in the config file on disk, if any
+ */
+from_version: string | null, 
+/**
+ * Version the config was loaded as
+ */
+to_version: string, 
+/**
+ * Fields missing from the file that were filled in with defaults
+ */
+added: Array<string>, 
+/**
+ * Fields present in the file whose value was replaced during migration
+ */
+defaulted: Array<string>, 
+/**
+ * Fields present in the file that no longer exist in the current schema
+ */
+dropped: Array<string>, };
+
+export type SlaState = "near_breach" | "breached";
+
+/**
+ * A task that has breached or is close to breaching an SLA policy
+ */
+export type SlaViolation = { policy_id: string, project_id: string, task_id: string, title: string, status: TaskStatus, 
+/**
+ * When the task entered `status`
+ */
+entered_at: string, 
+/**
+ * When the task breaches (or breached) the policy
+ */
+deadline: string, state: SlaState, };
+
+export type TodoSyncReport = { 
+/**
+ * Tasks created from new unchecked items
+ */
+created: number, 
+/**
+ * Tasks marked done because their item was ticked in the file
+ */
+completed: number, 
+/**
+ * Items ticked in the file because their task was done
+ */
+written_back: number, };
+
+export type PeerSyncReport = { 
+/**
+ * Tasks sent to the peer
+ */
+sent: number, 
+/**
+ * Tasks created or updated here from the peer's copy
+ */
+applied: number, };
+
+/**
+ * A failed CI workflow run
+ */
+export type CiFailureReport = { 
+/**
+ * Workflow name, e.g. `CI` or `release.yml`
+ */
+workflow: string, 
+/**
+ * Branch the run was on; a `refs/heads/` prefix is ignored
+ */
+branch: string, 
+/**
+ * The repository's default branch, when the CI knows it. Otherwise the
+ * project repos' default target branches are used, or `main`/`master`.
+ */
+default_branch?: string, run_url?: string, commit_sha?: string, 
+/**
+ * End of the failing job's log
+ */
+log_excerpt?: string, 
+/**
+ * Start an attempt at fixing the failure when a new task is filed
+ */
+auto_start: boolean, 
+/**
+ * Names of the tests that failed in the run
+ */
+failed_tests: Array<string>, };
+
+/**
+ * What a reported failure did
+ */
+export type CiFailureOutcome = { "outcome": "ignored" } | { "outcome": "created", task: Task, 
+/**
+ * Whether a fix attempt was started (see `CiFailureReport::auto_start`)
+ */
+attempt_started: boolean, 
+/**
+ * Failed tests that have failed before, each with a task of its own
+ */
+recurring_tests: Array<CiTestFailure>, } | { "outcome": "updated", task: Task, failure_count: bigint, 
+/**
+ * Failed tests that have failed before, each with a task of its own
+ */
+recurring_tests: Array<CiTestFailure>, };
+
+export type VaultExportReport = { directory: string, notes_written: number, 
+/**
+ * Notes removed because their task was renamed
+ */
+notes_removed: number, };
+
+export type AnalyticsExportReport = { directory: string, format: AnalyticsExportFormat, 
+/**
+ * Tables copied into the export
+ */
+tables: number, };
+
+export type WebhookPayloadFormat = "native" | "slack" | "discord" | "cloud_events";
+
+/**
+ * Events an endpoint can subscribe to
+ */
+export type WebhookEventType = "task.created" | "task.status_changed" | "attempt.finished";
+
+/**
+ * An outgoing webhook. Deliveries are queued by database triggers, so every
+ * change is covered no matter which API, bot or background job made it.
+ */
+export type WebhookEndpoint = { id: string, url: string, 
+/**
+ * Events delivered to this endpoint; empty for all of them
+ */
+events: Array<WebhookEventType>, format: WebhookPayloadFormat, 
+/**
+ * Dotted paths of the event data to include; empty for all of it
+ */
+fields: Array<string>, enabled: boolean, 
+/**
+ * When requests stop carrying a signature under the previous secret
+ */
+previous_secret_expires_at: string | null, created_at: string, updated_at: string, };
+
+export type CreateWebhookEndpoint = { url: string, events: Array<WebhookEventType>, format: WebhookPayloadFormat, fields: Array<string>, };
+
+export type UpdateWebhookEndpoint = { url: string | null, events: Array<WebhookEventType> | null, format: WebhookPayloadFormat | null, fields: Array<string> | null, enabled: boolean | null, };
+
+export type WebhookDeliveryStatus = "pending" | "delivered" | "failed";
+
+/**
+ * An event queued for, or sent to, one webhook endpoint
+ */
+export type WebhookDelivery = { id: string, endpoint_id: string, event_type: WebhookEventType, task_id: string, 
+/**
+ * The coding agent run, for `attempt.finished`
+ */
+execution_process_id: string | null, 
+/**
+ * Task status before the change, or the run's status for `attempt.finished`
+ */
+from_status: string | null, 
+/**
+ * Task status after the change, or the run's final status
+ */
+to_status: string | null, 
+/**
+ * Body as first sent
+ */
+payload: string | null, status: WebhookDeliveryStatus, attempts: number, 
+/**
+ * HTTP status of the last response, if the endpoint answered
+ */
+response_status: number | null, last_error: string | null, next_attempt_at: string, delivered_at: string | null, created_at: string, };
+
+/**
+ * An endpoint along with its signing secret, which is only ever returned
+ * when the endpoint is created
+ */
+export type WebhookEndpointWithSecret = { secret: string, id: string, url: string, 
+/**
+ * Events delivered to this endpoint; empty for all of them
+ */
+events: Array<WebhookEventType>, format: WebhookPayloadFormat, 
+/**
+ * Dotted paths of the event data to include; empty for all of it
+ */
+fields: Array<string>, enabled: boolean, 
+/**
+ * When requests stop carrying a signature under the previous secret
+ */
+previous_secret_expires_at: string | null, created_at: string, updated_at: string, };
+
+export type WebhookDeliveryQuery = { 
+/**
+ * Most deliveries to return, 50 by default
+ */
+limit: number | null, };
+
+export type RotateWebhookSecretRequest = { 
+/**
+ * Seconds the previous secret keeps signing requests alongside the new
+ * one, a day by default; 0 drops it right away
+ */
+grace_period_secs: number | null, };
+
+/**
+ * A browser that asked to be sent Web Push notifications
+ */
+export type WebPushSubscription = { id: string, 
+/**
+ * Push service URL the encrypted messages are posted to
+ */
+endpoint: string, user_agent: string | null, created_at: Date, updated_at: Date, };
+
+/**
+ * Encryption keys of a push subscription, as in `PushSubscription.toJSON()`
+ */
+export type WebPushKeys = { p256dh: string, auth: string, };
+
+/**
+ * A browser's `PushSubscription.toJSON()`, along with its user agent
+ */
+export type CreateWebPushSubscription = { endpoint: string, keys: WebPushKeys, user_agent: string | null, };
+
+export type WebPushPublicKey = { 
+/**
+ * Uncompressed P-256 public key, base64url
+ */
+public_key: string, };
+
+export type DeleteWebPushSubscriptionRequest = { endpoint: string, };
+
+/**
+ * Where task notifications can be delivered
+ */
+export type NotificationChannel = "desktop" | "telegram" | "slack" | "email" | "ntfy" | "pushover" | "gotify" | "web_push" | "apprise" | "sms" | "whatsapp";
+
+/**
+ * Routes task events matching its conditions to a set of channels
+ */
+export type NotificationRule = { id: string, name: string, 
+/**
+ * Only events of this project match; `None` for any project
+ */
+project_id: string | null, 
+/**
+ * Kinds of events that match; empty for all of them
+ */
+events: Array<TaskNotificationKind>, 
+/**
+ * Channels matching events are delivered through
+ */
+channels: Array<NotificationChannel>, enabled: boolean, created_at: Date, updated_at: Date, };
+
+export type CreateNotificationRule = { name: string, project_id: string | null, events: Array<TaskNotificationKind>, channels: Array<NotificationChannel>, };
+
+export type UpdateNotificationRule = { name: string | null, 
+/**
+ * `null` makes the rule match any project
+ */
+project_id?: string | null, events: Array<TaskNotificationKind> | null, channels: Array<NotificationChannel> | null, enabled: boolean | null, };
+
+export type NotificationStatus = "sent" | "failed";
+
+/**
+ * What a notification said about its task
+ */
+export type NotificationPayload = { task_title: string, 
+/**
+ * The agent's summary of its work
+ */
+summary: string | null, };
+
+/**
+ * A task notification sent, or attempted, through one channel
+ */
+export type Notification = { id: string, task_id: string, project_id: string, channel: NotificationChannel, 
+/**
+ * Row of the notification preferences the event belonged to
+ */
+event_type: NotificationEventType, payload: NotificationPayload, status: NotificationStatus, 
+/**
+ * Why the last delivery failed
+ */
+error: string | null, 
+/**
+ * When it was first seen
+ */
+read_at: Date | null, 
+/**
+ * When someone saw to it; set, it stops the failure's escalation
+ */
+acknowledged_at: Date | null, created_at: Date, updated_at: Date, };
+
+/**
+ * Which notifications to list; unset fields match any value
+ */
+export type NotificationFilter = { project_id: string | null, task_id: string | null, channel: NotificationChannel | null, event_type: NotificationEventType | null, status: NotificationStatus | null, read: boolean | null, acknowledged: boolean | null, };
+
+export type NotificationHistoryQuery = { 
+/**
+ * Most notifications to return, 50 by default
+ */
+limit: number | null, };
+
+/**
+ * A notification that could not be delivered even after retrying, with
+ * what the history knows about it
+ */
+export type NotificationDeadLetter = { id: string, notification_id: string, task_id: string, project_id: string, channel: NotificationChannel, event_type: NotificationEventType, payload: NotificationPayload, 
+/**
+ * Deliveries tried, redeliveries included
+ */
+attempts: number, last_error: string, created_at: Date, updated_at: Date, };
+
+/**
+ * Which dead letters to list; unset fields match any value
+ */
+export type DeadLetterFilter = { project_id: string | null, channel: NotificationChannel | null, };
+
+/**
+ * Outcome of redelivering dead letters
+ */
+export type RedeliveryReport = { delivered: number, 
+/**
+ * Dead letters that failed again and stay queued
+ */
+failed: number, };
+
+export type RedeliverRequest = { 
+/**
+ * Dead letters to send again; all of them when unset
+ */
+ids: Array<string> | null, };
+
+/**
+ * Change applied to every project of a batch
+ */
+export type BatchProjectOperation = { "type": "archive" } | { "type": "unarchive" } | { "type": "delete" } | { "type": "restore" } | { "type": "update_settings", 
+/**
+ * Empty string clears the directory
+ */
+default_agent_working_dir: string, };
+
+export type BatchProjectRequest = { project_ids: Array<string>, operation: BatchProjectOperation, };
+
+/**
+ * Outcome of a batch; the batch is all-or-nothing, so either every project
+ * is listed here or none was touched
+ */
+export type BatchProjectResult = { updated: Array<string>, 
+/**
+ * Projects that were already in the requested state
+ */
+unchanged: Array<string>, };
+
+/**
+ * Open tasks and running attempts for one coding agent
+ */
+export type AssigneeWorkload = { 
+/**
+ * Telegram user ID of the assignee, or None for unassigned tasks
+ */
+telegram_user_id: number | null, username: string | null, todo: number, in_progress: number, in_review: number, running_attempts: number, };
+
+/**
+ * Where to put a task, relative to another task of the same project
+ */
+export type TaskPlacement = { "before": string } | { "after": string };
+
+/**
+ * One task to move
+ */
+export type TaskMove = { task_id: string, placement: TaskPlacement, };
+
+export type ProviderCooldown = { provider: BaseCodingAgent, until: string, 
+/**
+ * Error text that triggered the cooldown
+ */
+reason: string, };
+
+export type HealthSnapshot = { started_at: string, uptime_secs: bigint, 
+/**
+ * Coding agent attempts currently running
+ */
+running_attempts: number, 
+/**
+ * Sessions with a follow-up message waiting for the current run to finish
+ */
+queued_follow_ups: number, 
+/**
+ * Executor of the default profile in the user config
+ */
+executor: BaseCodingAgent, 
+/**
+ * Whether that executor's binary or login was found on this machine
+ */
+executor_available: boolean, 
+/**
+ * Providers paused after a rate-limit error
+ */
+provider_cooldowns: Array<ProviderCooldown>, };
+
+export type TranscriptFormat = "markdown" | "json" | "html";
+
+export type TranscriptEntryKind = "user" | "assistant" | "thinking" | "tool_call" | "system" | "error";
+
+export type TranscriptEntry = { kind: TranscriptEntryKind, content: string, tool_name: string | null, 
+/**
+ * Unified diff of the file changes made by this tool call
+ */
+diff: string | null, 
+/**
+ * Output of a command run by this tool call
+ */
+output: string | null, };
+
+/**
+ * One coding agent execution: the prompt and everything the agent did
+ */
+export type TranscriptTurn = { execution_process_id: string, executor: string | null, started_at: string, prompt: string | null, entries: Array<TranscriptEntry>, };
+
+export type Transcript = { workspace_id: string, task_title: string, branch: string, generated_at: string, turns: Array<TranscriptTurn>, };
+
+export type RenderPromptRequest = { variables: { [key in string]?: string }, };
+
+export type RenderedPrompt = { text: string, 
+/**
+ * Placeholders without a value or fallback, left as-is in `text`
+ */
+missing: Array<string>, };
+
+export type PromptSnippetQuery = { 
+/**
+ * Limit to snippets usable in this project (its own plus global ones)
+ */
+project_id: string | null, };
+
+export type EvalVariantSummary = { variant: EvalVariant, profile: ExecutorProfileId, attempts: number, passed: number, failed: number, pending: number, 
+/**
+ * Passed share of finished attempts, `None` until one finishes
+ */
+pass_rate: number | null, avg_duration_secs: number | null, total_tokens: bigint, };
+
+export type EvalBenchmarkRow = { label: string, a: EvalAttempt | null, b: EvalAttempt | null, };
+
+export type EvalReport = { run: EvalRun, a: EvalVariantSummary, b: EvalVariantSummary, 
+/**
+ * One row per benchmark label, in the order the tasks were given
+ */
+benchmarks: Array<EvalBenchmarkRow>, };
+
+export type EvalBenchmarkTask = { 
+/**
+ * Identifies the benchmark across runs, e.g. `fix-login-redirect`
+ */
+label: string, prompt: string, };
+
+export type CreateEvalRunBody = { project_id: string, name: string, profile_a: ExecutorProfileId, profile_b: ExecutorProfileId, 
+/**
+ * Shell command run in each worktree after the agent finishes; exit code
+ * 0 is a pass
+ */
+test_command: string | null, repos: Array<WorkspaceRepoInput>, tasks: Array<EvalBenchmarkTask>, };
+
+export type TelegramLinkInfo = { 
+/**
+ * The token used for linking (for reference)
+ */
+token: string, 
+/**
+ * The deep link URL to open in Telegram
+ */
+deep_link: string, 
+/**
+ * Whether the bot is configured (has a token)
+ */
+bot_configured: boolean, };
+
+/**
+ * Response format for GET /api/telegram/link: `TelegramLinkInfo` as JSON, or
+ * a PNG (`qr`) or SVG (`qr_svg`) QR code of the deep link
+ */
+export type TelegramLinkFormat = "json" | "qr" | "qr_svg";
+
+export type TelegramLinkQuery = { format: TelegramLinkFormat, };
+
+export type TelegramStatusResponse = { 
+/**
+ * Whether an account is currently linked
+ */
+linked: boolean, 
+/**
+ * The linked Telegram username (if available)
+ */
+username: string | null, 
+/**
+ * Whether notifications are enabled
+ */
+notifications_enabled: boolean, 
+/**
+ * Whether to notify on task completion
+ */
+notify_on_task_done: boolean, 
+/**
+ * Whether to include LLM summaries in notifications
+ */
+include_llm_summary: boolean, 
+/**
+ * Whether real-time streaming is enabled
+ */
+stream_enabled: boolean, 
+/**
+ * Whether to notify when a task starts
+ */
+notify_on_started: boolean, 
+/**
+ * Whether to notify when a task moves to review
+ */
+notify_on_in_review: boolean, 
+/**
+ * Whether to notify when an attempt fails
+ */
+notify_on_failed: boolean, 
+/**
+ * Whether LLM summaries are also sent as voice messages
+ */
+voice_summary: boolean, 
+/**
+ * Status transitions that notify, narrowing down the per-status toggles when non-empty
+ */
+notify_transitions: Array<StatusTransitionRule>, 
+/**
+ * Whether every notification is delivered without sound
+ */
+silent: boolean, 
+/**
+ * Notification kinds delivered without sound
+ */
+silent_kinds: Array<TaskNotificationKind>, 
+/**
+ * Task notifications are paused until this time (set with /mute)
+ */
+muted_until: string | null, 
+/**
+ * Whether status changes edit the task's previous notification
+ */
+edit_in_place: boolean, 
+/**
+ * Whether questions agents end their turn with are forwarded to the chat
+ */
+forward_questions: boolean, 
+/**
+ * Telegram user IDs allowed to use the bot besides the linked accounts
+ */
+allowed_user_ids: Array<bigint>, 
+/**
+ * Whether the bot is configured (has a token)
+ */
+bot_configured: boolean, };
+
+export type UpdateTelegramSettingsRequest = { 
+/**
+ * Whether to enable notifications (master switch)
+ */
+notifications_enabled: boolean | null, 
+/**
+ * Whether to notify on task completion
+ */
+notify_on_task_done: boolean | null, 
+/**
+ * Whether to include LLM summaries in notifications
+ */
+include_llm_summary: boolean | null, 
+/**
+ * Whether to enable real-time streaming
+ */
+stream_enabled: boolean | null, 
+/**
+ * Whether to notify when a task starts
+ */
+notify_on_started: boolean | null, 
+/**
+ * Whether to notify when a task moves to review
+ */
+notify_on_in_review: boolean | null, 
+/**
+ * Whether to notify when an attempt fails
+ */
+notify_on_failed: boolean | null, 
+/**
+ * Whether to send LLM summaries as voice messages
+ */
+voice_summary: boolean | null, 
+/**
+ * Replace the status transitions that notify; an empty list sends every
+ * status the toggles allow
+ */
+notify_transitions: Array<StatusTransitionRule> | null, 
+/**
+ * Whether to deliver every notification without sound
+ */
+silent: boolean | null, 
+/**
+ * Notification kinds to deliver without sound
+ */
+silent_kinds: Array<TaskNotificationKind> | null, 
+/**
+ * Whether status changes edit the task's previous notification
+ */
+edit_in_place: boolean | null, 
+/**
+ * Whether to forward questions agents end their turn with to the chat
+ */
+forward_questions: boolean | null, 
+/**
+ * Replace the Telegram user IDs allowed to use the bot besides the
+ * linked accounts
+ */
+allowed_user_ids: Array<bigint> | null, };
+
+/**
+ * Request body for changing a linked account's role
+ */
+export type SetTelegramAccountRoleRequest = { role: TelegramAccountRole, };
+
+/**
+ * Request body for changing how a linked account gets its notifications
+ */
+export type SetTelegramDigestModeRequest = { digest_mode: TelegramDigestMode, };
+
+/**
+ * Request body for swapping in a new bot token
+ */
+export type RotateTelegramTokenRequest = { 
+/**
+ * The new token from BotFather
+ */
+token: string, 
+/**
+ * Rotate this project's own bot instead of the default one
+ */
+project_id: string | null, };
+
+/**
+ * Response to a token rotation
+ */
+export type TelegramTokenRotation = { 
+/**
+ * Username of the bot the new token belongs to
+ */
+bot_username: string, };
+
+/**
+ * Request body for previewing a notification. Unset fields are filled with
+ * sample text.
+ */
+export type TelegramPreviewRequest = { kind: TaskNotificationKind, 
+/**
+ * Take the title from this task
+ */
+task_id: string | null, title: string | null, 
+/**
+ * Shown when LLM summaries are on
+ */
+summary: string | null, 
+/**
+ * Shown for failures
+ */
+error_excerpt: string | null, };
+
+export type SlackInstallInfo = { 
+/**
+ * Slack's OAuth page; it redirects back to /api/slack/oauth/callback
+ */
+authorize_url: string, };
+
+export type SlackStatusResponse = { 
+/**
+ * Whether a workspace is currently linked
+ */
+linked: boolean, 
+/**
+ * Name of the linked workspace
+ */
+team_name: string | null, 
+/**
+ * Channel task notifications are posted to
+ */
+channel_id: string | null, channel_name: string | null, 
+/**
+ * Project slash commands use when none is given
+ */
+default_project_id: string | null, 
+/**
+ * Whether notifications are enabled
+ */
+notifications_enabled: boolean, 
+/**
+ * Whether to notify on task completion
+ */
+notify_on_task_done: boolean, 
+/**
+ * Whether the Slack app credentials are configured
+ */
+app_configured: boolean, };
+
+export type UpdateSlackSettingsRequest = { 
+/**
+ * Whether to enable notifications (master switch)
+ */
+notifications_enabled: boolean | null, 
+/**
+ * Whether to notify on task completion
+ */
+notify_on_task_done: boolean | null, 
+/**
+ * Channel to post notifications to; empty to stop posting
+ */
+channel_id: string | null, 
+/**
+ * Project slash commands use when none is given
  */
-stream_enabled: boolean | null, };
+default_project_id: string | null, };
 
 export type GitBranch = { name: string, is_current: boolean, is_remote: boolean, last_commit_date: Date, };
 